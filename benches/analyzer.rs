@@ -0,0 +1,75 @@
+//! Analyzer throughput, tracked per release
+//!
+//! Run with `cargo bench --bench analyzer`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use openclaw_harness::analyzer::Analyzer;
+use openclaw_harness::rules::{default_rules, Rule, RuleAction};
+use openclaw_harness::{ActionType, AgentAction, AgentType, RiskLevel};
+
+fn action(action_type: ActionType, content: &str, target: Option<&str>) -> AgentAction {
+    AgentAction {
+        id: "bench".to_string(),
+        timestamp: chrono::Utc::now(),
+        agent: AgentType::ClaudeCode,
+        action_type,
+        content: content.to_string(),
+        target: target.map(str::to_string),
+        session_id: None,
+        metadata: None,
+    }
+}
+
+/// Default rules plus `extra` generated ones, like a large custom pack
+fn rule_set(extra: usize) -> Vec<Rule> {
+    let mut rules = default_rules();
+    for i in 0..extra {
+        let mut rule = Rule::new(
+            format!("custom_{}", i),
+            "generated",
+            format!(r"\bdeploy-tool-{}\s+--(force|prune)", i),
+            RiskLevel::Warning,
+            RuleAction::Alert,
+        );
+        if i % 2 == 0 {
+            rule.applies_to = vec![ActionType::Exec];
+        }
+        rule.compile().unwrap();
+        rules.push(rule);
+    }
+    rules
+}
+
+fn bench_analyze(c: &mut Criterion) {
+    let actions = [
+        action(ActionType::Exec, "cargo build --release", None),
+        action(ActionType::Exec, "rm -rf / --no-preserve-root", None),
+        action(
+            ActionType::FileWrite,
+            &"fn main() { println!(\"hello\"); }\n".repeat(200),
+            Some("/home/user/project/src/main.rs"),
+        ),
+        action(ActionType::HttpRequest, "GET https://example.com/api", None),
+    ];
+
+    let mut group = c.benchmark_group("analyze");
+    group.throughput(Throughput::Elements(actions.len() as u64));
+    for extra in [0, 100, 500] {
+        let analyzer = Analyzer::new(rule_set(extra));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}_extra_rules", extra)),
+            &analyzer,
+            |b, analyzer| {
+                b.iter(|| {
+                    for a in &actions {
+                        criterion::black_box(analyzer.analyze(a));
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_analyze);
+criterion_main!(benches);
@@ -0,0 +1,34 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use openclaw_harness::proxy::attribution::Attribution;
+use openclaw_harness::proxy::interceptor::{intercept_response, MessageOptions};
+use openclaw_harness::rules::{default_rules, Rule};
+use std::sync::OnceLock;
+
+fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let mut rules = default_rules();
+        for rule in &mut rules {
+            let _ = rule.compile();
+        }
+        rules
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    for enforce in [true, false] {
+        let (out, _) = intercept_response(
+            data,
+            rules(),
+            enforce,
+            MessageOptions::default(),
+            &Attribution::default(),
+        );
+        // A JSON body must come back as JSON
+        if serde_json::from_slice::<serde_json::Value>(data).is_ok() {
+            assert!(serde_json::from_slice::<serde_json::Value>(&out).is_ok());
+        }
+    }
+});
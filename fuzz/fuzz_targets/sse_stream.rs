@@ -0,0 +1,44 @@
+#![no_main]
+
+//! An upstream stream cut into chunks at the fuzzer's choice, through the
+//! line buffer and the stream interceptor as the proxy runs them
+
+use libfuzzer_sys::fuzz_target;
+use openclaw_harness::proxy::streaming::{parse_sse_events, SseLineBuffer, StreamInterceptor};
+use openclaw_harness::rules::{default_rules, Rule};
+use std::sync::OnceLock;
+
+fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let mut rules = default_rules();
+        for rule in &mut rules {
+            let _ = rule.compile();
+        }
+        rules
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    // First byte: enforce or monitor; then chunks of up to 16 bytes, each
+    // prefixed by its length
+    let Some((&mode, mut rest)) = data.split_first() else {
+        return;
+    };
+    let mut buf = SseLineBuffer::new();
+    let mut interceptor = StreamInterceptor::new(rules().to_vec(), mode & 1 == 1);
+    let mut blocks = vec![];
+    while let Some((&len, tail)) = rest.split_first() {
+        let (chunk, tail) = tail.split_at((len as usize % 17).min(tail.len()));
+        rest = tail;
+        blocks.extend(buf.feed_bytes(chunk));
+    }
+    blocks.extend(buf.finish());
+    for block in blocks {
+        for event in parse_sse_events(&block) {
+            for out in interceptor.process_event(event) {
+                out.to_sse_bytes();
+            }
+        }
+    }
+});
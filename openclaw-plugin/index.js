@@ -96,7 +96,7 @@ const MULTISTEP_PATTERNS = [
     name: "multistep_script_execute",
     description: "Write script → chmod → execute chain",
     steps: [
-      { tool: ["write", "Write"], contentCheck: /#!/bin\/(bash|sh)|rm |curl |wget / },
+      { tool: ["write", "Write"], contentCheck: /#!\/bin\/(bash|sh)|rm |curl |wget / },
       { tool: ["exec"], commandCheck: /chmod.*\+x/ },
       { tool: ["exec"], commandCheck: /\.(\/|\/)\w+\.sh|^\.\/|^bash / },
     ],
@@ -284,11 +284,46 @@ function matchCommand(command, rules) {
   return matches;
 }
 
+function isPauseAndAsk(action) {
+  return (action || "").replace(/[^a-zA-Z]/g, "").toLowerCase() === "pauseandask";
+}
+
 function shouldBlock(action) {
   const normalized = (action || "").replace(/[^a-zA-Z]/g, "").toLowerCase();
   return normalized === "criticalalert" || normalized === "pauseandask" || normalized === "block";
 }
 
+// ---------------------------------------------------------------------------
+// Pre-write backup for approved PauseAndAsk writes
+// ---------------------------------------------------------------------------
+
+// The daemon's API token (OPENCLAW_HARNESS_API_TOKEN), needed for backups
+// once the daemon is started with one
+function authHeaders() {
+  const token = (process.env.OPENCLAW_HARNESS_API_TOKEN || "").trim();
+  return token ? { Authorization: `Bearer ${token}` } : {};
+}
+
+export async function backupBeforeWrite(apiUrl, filePath, ruleName, logger) {
+  try {
+    const res = await fetch(`${apiUrl}/api/backups`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json", ...authHeaders() },
+      body: JSON.stringify({ path: filePath, rule: ruleName }),
+    });
+    if (!res.ok) {
+      logger?.warn?.(`[harness-guard] Backup of ${filePath} failed: HTTP ${res.status}`);
+      return;
+    }
+    const data = await res.json();
+    if (data?.backup) {
+      logger?.info?.(`[harness-guard] 💾 Backed up ${filePath} → ${data.backup}`);
+    }
+  } catch (err) {
+    logger?.warn?.(`[harness-guard] Backup of ${filePath} failed: ${err}`);
+  }
+}
+
 // ---------------------------------------------------------------------------
 // Self-protection: file path checking (for write/edit tools)
 // ---------------------------------------------------------------------------
@@ -538,6 +573,14 @@ export default function register(api) {
           };
         }
 
+        // PauseAndAsk rule matched the path but the write is allowed through —
+        // snapshot the current file first so the approval can be undone
+        const rules = await getRules(apiUrl, cacheTtl, api.logger);
+        const pauseRule = matchCommand(filePath, rules || []).find((r) => isPauseAndAsk(r.action));
+        if (pauseRule && !daemonDown) {
+          await backupBeforeWrite(apiUrl, filePath, pauseRule.name, api.logger);
+        }
+
         // write/edit that isn't a protected path/content — allow
         return;
       }
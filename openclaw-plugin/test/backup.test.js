@@ -0,0 +1,26 @@
+import { test } from "node:test";
+import assert from "node:assert/strict";
+import http from "node:http";
+import { backupBeforeWrite } from "../index.js";
+
+test("backupBeforeWrite sends the API token", async () => {
+  const seen = [];
+  const server = http.createServer((req, res) => {
+    seen.push(req.headers.authorization);
+    res.setHeader("Content-Type", "application/json");
+    res.end(JSON.stringify({ backup: "/tmp/backup" }));
+  });
+  await new Promise((resolve) => server.listen(0, "127.0.0.1", resolve));
+  const apiUrl = `http://127.0.0.1:${server.address().port}`;
+
+  try {
+    process.env.OPENCLAW_HARNESS_API_TOKEN = "s3cret";
+    await backupBeforeWrite(apiUrl, "/tmp/deploy.toml", "ask_deploy");
+    delete process.env.OPENCLAW_HARNESS_API_TOKEN;
+    await backupBeforeWrite(apiUrl, "/tmp/deploy.toml", "ask_deploy");
+  } finally {
+    server.close();
+  }
+
+  assert.deepEqual(seen, ["Bearer s3cret", undefined]);
+});
@@ -1,93 +1,108 @@
 //! Action analyzer and rule engine
 //!
 //! Analyzes incoming actions against configured rules
-//! and produces risk assessments.
+//! and produces risk assessments, by way of the stages in `pipeline`.
 
+pub mod enrich;
+pub mod pipeline;
 pub mod risk_scorer;
 pub mod rule_engine;
 
 use super::rules::Rule;
-use super::{AgentAction, AnalysisResult, Recommendation, RiskLevel};
+use super::{AgentAction, AnalysisResult};
+use crate::db::DbPool;
+use pipeline::{Analysis, CanaryOrigins, Stage};
+use rule_engine::{Evaluation, RuleSet};
 
 /// The main analyzer that processes actions
 pub struct Analyzer {
-    rules: Vec<Rule>,
+    rules: RuleSet,
+    stages: Vec<Box<dyn Stage>>,
 }
 
 impl Analyzer {
+    /// Analyzer running the configured stages (see `pipeline`) with the
+    /// configured rule evaluation (see `rule_engine`)
     pub fn new(rules: Vec<Rule>) -> Self {
-        Self { rules }
+        let mut rules = RuleSet::new(rules);
+        rules.set_evaluation(Evaluation::from_env());
+        Self {
+            rules,
+            stages: pipeline::configured_stages(),
+        }
+    }
+
+    /// Count every matching rule, or only the highest-priority one
+    pub fn with_evaluation(mut self, evaluation: Evaluation) -> Self {
+        self.rules.set_evaluation(evaluation);
+        self
+    }
+
+    /// Append a stage to the pipeline
+    pub fn with_stage(mut self, stage: Box<dyn Stage>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Look up reused canary tokens in this database, recording the reuse
+    /// and explaining which block they came from
+    pub fn with_canary_db(self, db: DbPool) -> Self {
+        self.with_stage(Box::new(CanaryOrigins::new(db)))
+    }
+
+    /// Names of the stages, in order
+    pub fn stage_names(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|s| s.name()).collect()
     }
 
     /// Analyze an action and return the result
     pub fn analyze(&self, action: &AgentAction) -> AnalysisResult {
-        let mut matched_rules = Vec::new();
-        let mut highest_risk = RiskLevel::Info;
-        let mut recommendation = Recommendation::LogOnly;
-        let mut explanations = Vec::new();
-
-        for rule in &self.rules {
-            if rule.matches(action) {
-                matched_rules.push(rule.name.clone());
-
-                if rule.risk_level > highest_risk {
-                    highest_risk = rule.risk_level;
-                }
-
-                match rule.action {
-                    crate::rules::RuleAction::CriticalAlert => {
-                        recommendation = Recommendation::CriticalAlert;
-                    }
-                    crate::rules::RuleAction::Block
-                        if recommendation != Recommendation::CriticalAlert =>
-                    {
-                        recommendation = Recommendation::CriticalAlert;
-                    }
-                    crate::rules::RuleAction::PauseAndAsk
-                        if recommendation != Recommendation::CriticalAlert =>
-                    {
-                        recommendation = Recommendation::PauseAndAsk;
-                    }
-                    crate::rules::RuleAction::Alert
-                        if recommendation == Recommendation::LogOnly =>
-                    {
-                        recommendation = Recommendation::Alert;
-                    }
-                    _ => {}
-                }
-
-                explanations.push(format!(
-                    "Matched rule: {} - {}",
-                    rule.name, rule.description
-                ));
-            }
+        let span = tracing::info_span!(
+            "analyze",
+            action_id = %action.id,
+            matched = tracing::field::Empty,
+            risk = tracing::field::Empty
+        );
+        let _entered = span.enter();
+        let mut analysis = Analysis::new(action, &self.rules);
+        for stage in &self.stages {
+            stage.run(&mut analysis);
         }
 
-        let explanation = if explanations.is_empty() {
+        let explanation = if analysis.explanations.is_empty() {
             "No rules matched".to_string()
         } else {
-            explanations.join("; ")
+            analysis.explanations.join("; ")
         };
 
+        let matched_rules: Vec<String> = analysis.matched.iter().map(|r| r.name.clone()).collect();
+        span.record("matched", matched_rules.join(",").as_str());
+        span.record("risk", tracing::field::display(&analysis.risk_level));
+
+        let mut result_action = action.clone();
+        result_action.metadata = analysis.action.metadata;
         AnalysisResult {
-            action: action.clone(),
+            action: result_action,
             matched_rules,
-            risk_level: highest_risk,
-            recommendation,
+            risk_level: analysis.risk_level,
+            recommendation: analysis.recommendation,
             explanation,
         }
     }
 
     /// Reload rules
     pub fn reload_rules(&mut self, rules: Vec<Rule>) {
-        self.rules = rules;
+        let evaluation = self.rules.evaluation();
+        self.rules = RuleSet::new(rules);
+        self.rules.set_evaluation(evaluation);
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ActionType, AgentType};
+    use crate::db::DbPool;
+    use crate::{canary, ActionType, AgentType, Recommendation, RiskLevel};
     use chrono::Utc;
 
     #[test]
@@ -108,4 +123,50 @@ mod tests {
         assert_eq!(result.risk_level, RiskLevel::Info);
         assert_eq!(result.recommendation, Recommendation::LogOnly);
     }
+
+    #[test]
+    fn test_reused_canary_token_is_traced() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = DbPool::open(&dir.path().join("test.db")).unwrap();
+        let token = canary::generate();
+        pool.get()
+            .unwrap()
+            .insert_canary_token(&crate::db::CanaryToken {
+                token: token.clone(),
+                created_at: "2026-03-01T00:00:00+00:00".to_string(),
+                rule_name: "dangerous_rm".to_string(),
+                tool_name: "Bash".to_string(),
+                agent: "claude_code".to_string(),
+                session_id: None,
+                trigger_count: 0,
+                last_triggered: None,
+            })
+            .unwrap();
+
+        let analyzer = Analyzer::new(vec![canary::rule()]).with_canary_db(pool.clone());
+        let action = AgentAction {
+            id: "test".to_string(),
+            timestamp: Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::HttpRequest,
+            content: canary::url(&token),
+            target: None,
+            session_id: None,
+            metadata: None,
+        };
+        let result = analyzer.analyze(&action);
+        assert_eq!(result.risk_level, RiskLevel::Critical);
+        assert_eq!(result.recommendation, Recommendation::CriticalAlert);
+        assert!(result
+            .explanation
+            .contains("issued when Bash was blocked by dangerous_rm"));
+
+        let origin = pool
+            .get()
+            .unwrap()
+            .trigger_canary_token(&token)
+            .unwrap()
+            .unwrap();
+        assert_eq!(origin.trigger_count, 2);
+    }
 }
@@ -0,0 +1,260 @@
+//! Analysis pipeline
+//!
+//! `Analyzer::analyze` runs an action through a list of stages, each of which
+//! reads and updates a shared `Analysis`:
+//!
+//! 1. `normalize` — strips characters that hide commands from rules
+//! 2. `git`, `geoip` — repository facts for file targets and network facts
+//!    for outbound requests, in metadata (`enrich`)
+//! 3. `rules` — finds matching rules (`rule_engine::RuleMatcher`)
+//! 4. `score` — turns matches into a risk level and recommendation
+//!    (`risk_scorer::RiskScorer`)
+//! 5. enrichment — extra explanations, e.g. `CanaryOrigins`
+//!
+//! Metadata attached by stages is kept on the resulting action; the content
+//! is left as received. The built-in stages can be reordered or left out
+//! with `OPENCLAW_HARNESS_ANALYZER_STAGES` (default
+//! `normalize,git,geoip,rules,score`);
+//! further stages (LLM scoring, baselines, allowlists, ...) implement `Stage`
+//! and are added with `Analyzer::with_stage`.
+
+use super::enrich::{GeoIp, GitContext};
+use super::risk_scorer::RiskScorer;
+use super::rule_engine::{RuleMatcher, RuleSet};
+use crate::db::DbPool;
+use crate::rules::Rule;
+use crate::{canary, AgentAction, Recommendation, RiskLevel};
+use tracing::warn;
+
+/// Environment variable listing the built-in stages to run, in order
+pub const STAGES_ENV: &str = "OPENCLAW_HARNESS_ANALYZER_STAGES";
+
+const DEFAULT_STAGES: [&str; 5] = ["normalize", "git", "geoip", "rules", "score"];
+
+/// Work in progress for one action
+pub struct Analysis<'a> {
+    /// The action as rules see it; normalization may rewrite it
+    pub action: AgentAction,
+    /// Rules the analyzer was configured with
+    pub rules: &'a RuleSet,
+    /// Rules that matched, in rule order
+    pub matched: Vec<&'a Rule>,
+    pub risk_level: RiskLevel,
+    pub recommendation: Recommendation,
+    pub explanations: Vec<String>,
+}
+
+impl<'a> Analysis<'a> {
+    pub fn new(action: &AgentAction, rules: &'a RuleSet) -> Self {
+        Self {
+            action: action.clone(),
+            rules,
+            matched: vec![],
+            risk_level: RiskLevel::Info,
+            recommendation: Recommendation::LogOnly,
+            explanations: vec![],
+        }
+    }
+
+    pub fn has_match(&self, rule_name: &str) -> bool {
+        self.matched.iter().any(|r| r.name == rule_name)
+    }
+}
+
+/// One step of the pipeline
+pub trait Stage: Send + Sync {
+    /// Name used in logs and `OPENCLAW_HARNESS_ANALYZER_STAGES`
+    fn name(&self) -> &'static str;
+
+    fn run(&self, analysis: &mut Analysis<'_>);
+}
+
+/// Built-in stage called `name`
+pub fn builtin(name: &str) -> Option<Box<dyn Stage>> {
+    match name {
+        "normalize" => Some(Box::new(Normalize)),
+        "git" => Some(Box::new(GitContext)),
+        "geoip" => Some(Box::new(GeoIp::from_env())),
+        "rules" => Some(Box::new(RuleMatcher)),
+        "score" => Some(Box::new(RiskScorer)),
+        _ => None,
+    }
+}
+
+/// Stages named in the environment, or the default pipeline
+pub fn configured_stages() -> Vec<Box<dyn Stage>> {
+    let names = std::env::var(STAGES_ENV).ok();
+    let names: Vec<&str> = match names.as_deref() {
+        Some(list) => list
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .collect(),
+        None => DEFAULT_STAGES.to_vec(),
+    };
+    if !names.contains(&"rules") {
+        warn!("{} has no 'rules' stage; no rule will match", STAGES_ENV);
+    }
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let stage = builtin(name);
+            if stage.is_none() {
+                warn!("Unknown analyzer stage '{}' in {}", name, STAGES_ENV);
+            }
+            stage
+        })
+        .collect()
+}
+
+/// Removes ANSI escape sequences and invisible characters (zero-width
+/// spaces, joiners, BOM, soft hyphens) that can split a command so a rule's
+/// pattern no longer sees it
+pub struct Normalize;
+
+impl Normalize {
+    fn clean(text: &str) -> Option<String> {
+        let invisible = |c: char| {
+            matches!(
+                c,
+                '\u{200B}'..='\u{200F}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}' | '\u{00AD}'
+            )
+        };
+        if !text.contains('\x1b') && !text.chars().any(invisible) {
+            return None;
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                // CSI: ESC [ params final-byte
+                if chars.peek() == Some(&'[') {
+                    chars.next();
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                continue;
+            }
+            if !invisible(c) {
+                out.push(c);
+            }
+        }
+        Some(out)
+    }
+}
+
+impl Stage for Normalize {
+    fn name(&self) -> &'static str {
+        "normalize"
+    }
+
+    fn run(&self, analysis: &mut Analysis<'_>) {
+        let action = &mut analysis.action;
+        if let Some(content) = Self::clean(&action.content) {
+            action.content = content;
+        }
+        if let Some(target) = action.target.as_deref().and_then(Self::clean) {
+            action.target = Some(target);
+        }
+    }
+}
+
+/// Explains which block a reused canary token came from, recording the reuse
+pub struct CanaryOrigins {
+    db: DbPool,
+}
+
+impl CanaryOrigins {
+    pub fn new(db: DbPool) -> Self {
+        Self { db }
+    }
+}
+
+impl Stage for CanaryOrigins {
+    fn name(&self) -> &'static str {
+        "canary"
+    }
+
+    fn run(&self, analysis: &mut Analysis<'_>) {
+        if !analysis.has_match(canary::RULE_NAME) {
+            return;
+        }
+        let Ok(db) = self.db.get() else {
+            return;
+        };
+        let action = &analysis.action;
+        let text = format!(
+            "{} {}",
+            action.content,
+            action.target.as_deref().unwrap_or_default()
+        );
+        let origins: Vec<String> = canary::find_tokens(&text)
+            .iter()
+            .filter_map(|token| db.trigger_canary_token(token).ok().flatten())
+            .map(|origin| {
+                format!(
+                    "Canary {} was issued when {} was blocked by {} at {}",
+                    origin.token, origin.tool_name, origin.rule_name, origin.created_at
+                )
+            })
+            .collect();
+        analysis.explanations.extend(origins);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::Analyzer;
+    use crate::rules::default_rules;
+    use crate::{ActionType, AgentType};
+
+    /// Downgrades everything to Info, like an allowlist would
+    struct AllowAll;
+
+    impl Stage for AllowAll {
+        fn name(&self) -> &'static str {
+            "allow_all"
+        }
+
+        fn run(&self, analysis: &mut Analysis<'_>) {
+            analysis.risk_level = RiskLevel::Info;
+            analysis.recommendation = Recommendation::LogOnly;
+            analysis.explanations.push("Allowlisted".to_string());
+        }
+    }
+
+    #[test]
+    fn test_pipeline_stages() {
+        let action = AgentAction {
+            id: "t".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "r\u{200B}m -rf \x1b[0m/".to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        };
+
+        let analyzer = Analyzer::new(default_rules());
+        assert_eq!(analyzer.stage_names(), DEFAULT_STAGES);
+        let result = analyzer.analyze(&action);
+        assert!(result.matched_rules.contains(&"dangerous_rm".to_string()));
+        assert_eq!(result.risk_level, RiskLevel::Critical);
+        // The stored action is the original
+        assert_eq!(result.action.content, action.content);
+
+        let allowed = Analyzer::new(default_rules())
+            .with_stage(Box::new(AllowAll))
+            .analyze(&action);
+        assert_eq!(allowed.risk_level, RiskLevel::Info);
+        assert!(allowed.explanation.ends_with("Allowlisted"));
+        assert!(allowed.matched_rules.contains(&"dangerous_rm".to_string()));
+
+        assert!(builtin("llm").is_none());
+    }
+}
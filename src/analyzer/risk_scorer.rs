@@ -1,6 +1,48 @@
 //! Risk scoring based on multiple factors
 
-use super::{AgentAction, RiskLevel};
+use super::pipeline::{Analysis, Stage};
+use crate::rules::RuleAction;
+use crate::{AgentAction, Recommendation, RiskLevel};
+
+/// The `score` stage: the highest risk among matched rules, and the
+/// strongest recommendation their actions call for
+pub struct RiskScorer;
+
+impl Stage for RiskScorer {
+    fn name(&self) -> &'static str {
+        "score"
+    }
+
+    fn run(&self, analysis: &mut Analysis<'_>) {
+        for rule in &analysis.matched {
+            if rule.risk_level > analysis.risk_level {
+                analysis.risk_level = rule.risk_level;
+            }
+
+            let recommendation = &mut analysis.recommendation;
+            match rule.action {
+                RuleAction::CriticalAlert => {
+                    *recommendation = Recommendation::CriticalAlert;
+                }
+                RuleAction::Block if *recommendation != Recommendation::CriticalAlert => {
+                    *recommendation = Recommendation::CriticalAlert;
+                }
+                RuleAction::PauseAndAsk if *recommendation != Recommendation::CriticalAlert => {
+                    *recommendation = Recommendation::PauseAndAsk;
+                }
+                RuleAction::Alert if *recommendation == Recommendation::LogOnly => {
+                    *recommendation = Recommendation::Alert;
+                }
+                _ => {}
+            }
+
+            analysis.explanations.push(format!(
+                "Matched rule: {} - {}",
+                rule.name, rule.description
+            ));
+        }
+    }
+}
 
 /// Calculate overall risk score for an action
 pub fn calculate_risk(_action: &AgentAction, matched_rules: &[String]) -> RiskLevel {
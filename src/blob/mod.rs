@@ -0,0 +1,176 @@
+//! Content-addressed storage for oversized action content
+//!
+//! A file write can carry megabytes of content, and storing it inline bloats
+//! the actions table, the event journal and every alert that quotes it.
+//! Content longer than the inline limit is written once to
+//! `<db dir>/blobs/<aa>/<sha256>` and the stored action keeps only a prefix,
+//! with `content_blob: {sha256, size}` in its metadata. The full text is
+//! served by `GET /api/events/:id/content`.
+//!
+//! Rules always see the full content: callers analyze the original action
+//! and store the offloaded copy.
+
+use crate::AgentAction;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Environment variable overriding the inline limit (bytes; 0 disables offloading)
+pub const INLINE_LIMIT_ENV: &str = "OPENCLAW_HARNESS_MAX_INLINE_CONTENT";
+
+const DEFAULT_INLINE_LIMIT: usize = 16 * 1024;
+
+/// Metadata key recording where the full content went
+pub const METADATA_KEY: &str = "content_blob";
+
+pub struct BlobStore {
+    root: PathBuf,
+    inline_limit: usize,
+}
+
+impl BlobStore {
+    pub fn new(root: impl Into<PathBuf>, inline_limit: usize) -> Self {
+        Self {
+            root: root.into(),
+            inline_limit,
+        }
+    }
+
+    /// Store next to the database at `db_path`, with the limit from the environment
+    pub fn for_db(db_path: &Path) -> Self {
+        let inline_limit = std::env::var(INLINE_LIMIT_ENV)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_INLINE_LIMIT);
+        let dir = db_path.parent().unwrap_or(Path::new("."));
+        Self::new(dir.join("blobs"), inline_limit)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path_of(&self, hash: &str) -> Option<PathBuf> {
+        // Hashes come back from metadata and URLs; never let one escape the root
+        if hash.len() != 64 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(self.root.join(&hash[..2]).join(hash))
+    }
+
+    /// Write `content`, returning its hash. Existing blobs are left as is.
+    pub fn put(&self, content: &str) -> anyhow::Result<String> {
+        let hash: String = Sha256::digest(content.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        let path = self.path_of(&hash).expect("sha256 hex digest");
+        if !path.exists() {
+            fs::create_dir_all(path.parent().unwrap())?;
+            // Write under a temporary name so readers never see a partial blob
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, content)?;
+            fs::rename(&tmp, &path)?;
+        }
+        Ok(hash)
+    }
+
+    /// Content of blob `hash`, if present
+    pub fn get(&self, hash: &str) -> Option<String> {
+        fs::read_to_string(self.path_of(hash)?).ok()
+    }
+
+    /// Delete blob `hash`; returns whether it existed
+    pub fn remove(&self, hash: &str) -> bool {
+        self.path_of(hash)
+            .is_some_and(|path| fs::remove_file(path).is_ok())
+    }
+
+    /// Copy of `action` for storage: content over the limit is moved into a
+    /// blob and replaced by a prefix. If the blob can't be written the
+    /// content stays inline.
+    pub fn offload(&self, action: &AgentAction) -> AgentAction {
+        let mut stored = action.clone();
+        if self.inline_limit == 0 || action.content.len() <= self.inline_limit {
+            return stored;
+        }
+        let metadata = stored.metadata.get_or_insert_with(|| serde_json::json!({}));
+        let Some(metadata) = metadata.as_object_mut() else {
+            return stored;
+        };
+        let hash = match self.put(&action.content) {
+            Ok(hash) => hash,
+            Err(e) => {
+                warn!("Failed to offload content of {}: {}", action.id, e);
+                return action.clone();
+            }
+        };
+
+        let mut cut = self.inline_limit;
+        while !action.content.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        stored.content = format!(
+            "{}… [truncated, {} bytes total]",
+            &action.content[..cut],
+            action.content.len()
+        );
+        metadata.insert(
+            METADATA_KEY.to_string(),
+            serde_json::json!({ "sha256": hash, "size": action.content.len() }),
+        );
+        stored
+    }
+}
+
+/// Blob hash recorded in an offloaded action's metadata
+pub fn blob_hash(action: &AgentAction) -> Option<&str> {
+    action
+        .metadata
+        .as_ref()?
+        .get(METADATA_KEY)?
+        .get("sha256")?
+        .as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentType};
+
+    #[test]
+    fn test_offload_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BlobStore::new(dir.path(), 8);
+        let action = AgentAction {
+            id: "a1".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::FileWrite,
+            content: "ééééé and more".to_string(),
+            target: Some("/tmp/x".to_string()),
+            session_id: None,
+            metadata: Some(serde_json::json!({"tool": "Write"})),
+        };
+
+        let stored = store.offload(&action);
+        // Cut on a char boundary at or below the limit
+        assert_eq!(stored.content, "éééé… [truncated, 19 bytes total]");
+        assert_eq!(stored.metadata.as_ref().unwrap()["tool"], "Write");
+        let hash = blob_hash(&stored).unwrap();
+        assert_eq!(store.get(hash).unwrap(), action.content);
+        // Same content, same blob
+        assert_eq!(store.put(&action.content).unwrap(), hash);
+
+        let small = AgentAction {
+            content: "ls".to_string(),
+            ..action.clone()
+        };
+        assert!(blob_hash(&store.offload(&small)).is_none());
+        assert!(store.get("../../etc/passwd").is_none());
+
+        assert!(store.remove(hash));
+        assert!(store.get(hash).is_none());
+    }
+}
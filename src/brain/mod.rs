@@ -1,8 +1,11 @@
+use crate::RiskLevel;
 use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+pub mod notes;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OntologyNode {
@@ -42,10 +45,42 @@ pub struct BrainInsights {
     pub skills_inferred: usize,
 }
 
+/// Words that make a command look like a decision
+const DECISION_KEYWORDS: [&str; 6] = [
+    "fix",
+    "refactor",
+    "implement",
+    "optimize",
+    "deploy",
+    "rollback",
+];
+
+/// Stored risk levels that count as incidents
+const RISKY: [&str; 2] = [RiskLevel::Warning.as_str(), RiskLevel::Critical.as_str()];
+
+/// Directory holding ontology snapshots, reports and decision notes:
+/// `SAFEBOT_DATA_DIR` if set, else the harness directory (see
+/// `crate::harness_dir`)
+pub fn data_dir() -> PathBuf {
+    std::env::var("SAFEBOT_DATA_DIR")
+        .ok()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| crate::expand_home(d.trim()))
+        .unwrap_or_else(crate::harness_dir)
+}
+
 pub fn build_ontology_from_db(
     conn: &Connection,
 ) -> anyhow::Result<(Vec<OntologyNode>, Vec<OntologyEdge>)> {
-    let actions = load_actions(conn)?;
+    build_ontology_scoped(conn, None)
+}
+
+/// The v1 ontology of one workspace's actions, or of all of them
+pub fn build_ontology_scoped(
+    conn: &Connection,
+    workspace: Option<&str>,
+) -> anyhow::Result<(Vec<OntologyNode>, Vec<OntologyEdge>)> {
+    let actions = load_actions(conn, workspace)?;
     let mut nodes = Vec::new();
     let mut edges = Vec::new();
     let mut node_seen = HashSet::new();
@@ -183,10 +218,17 @@ pub fn build_ontology_from_db(
     }
 
     // incidents + links
-    let mut stmt2 = conn.prepare(
-        "SELECT action_id, risk_level, matched_rules FROM analysis_results WHERE risk_level IN ('Warning','Critical') ORDER BY id DESC LIMIT 2000",
-    )?;
-    let rows2 = stmt2.query_map([], |r| {
+    let scope = if workspace.is_some() {
+        "AND action_id IN (SELECT id FROM actions WHERE workspace = ?3)"
+    } else {
+        ""
+    };
+    let mut stmt2 = conn.prepare(&format!(
+        "SELECT action_id, risk_level, matched_rules FROM analysis_results WHERE risk_level IN (?1, ?2) {} ORDER BY id DESC LIMIT 2000",
+        scope
+    ))?;
+    let params = RISKY.into_iter().chain(workspace);
+    let rows2 = stmt2.query_map(rusqlite::params_from_iter(params), |r| {
         Ok((
             r.get::<_, String>(0)?,
             r.get::<_, String>(1)?,
@@ -237,14 +279,22 @@ pub fn build_ontology_from_db(
 pub fn build_ontology_v2_from_db(
     conn: &Connection,
 ) -> anyhow::Result<(Vec<OntologyNode>, Vec<OntologyEdge>, BrainInsights)> {
-    let (mut nodes, mut edges) = build_ontology_from_db(conn)?;
+    build_ontology_v2_scoped(conn, None)
+}
+
+/// The v2 ontology of one workspace's actions, or of all of them
+pub fn build_ontology_v2_scoped(
+    conn: &Connection,
+    workspace: Option<&str>,
+) -> anyhow::Result<(Vec<OntologyNode>, Vec<OntologyEdge>, BrainInsights)> {
+    let (mut nodes, mut edges) = build_ontology_scoped(conn, workspace)?;
     let mut node_seen: HashSet<String> = nodes.iter().map(|n| n.id.clone()).collect();
     let mut edge_seen: HashSet<String> = edges
         .iter()
         .map(|e| format!("{}|{}|{}", e.from, e.to, e.rel))
         .collect();
 
-    let actions = load_actions(conn)?;
+    let actions = load_actions(conn, workspace)?;
 
     // 1) TaskPattern from repeated commands
     let mut command_counts: HashMap<String, u32> = HashMap::new();
@@ -280,21 +330,13 @@ pub fn build_ontology_v2_from_db(
     }
 
     // 2) Decisions from intent-like commands
-    let decision_keywords = [
-        "fix",
-        "refactor",
-        "implement",
-        "optimize",
-        "deploy",
-        "rollback",
-    ];
     let mut decisions_detected = 0usize;
     for a in actions
         .iter()
         .filter(|a| a.action_type.eq_ignore_ascii_case("Exec"))
     {
         let lower = a.content.to_lowercase();
-        if decision_keywords.iter().any(|k| lower.contains(k)) {
+        if DECISION_KEYWORDS.iter().any(|k| lower.contains(k)) {
             decisions_detected += 1;
             let decision_id = format!(
                 "decision:{}",
@@ -327,13 +369,13 @@ pub fn build_ontology_v2_from_db(
         "SELECT a.content, COUNT(*) as c
          FROM analysis_results r
          JOIN actions a ON a.id = r.action_id
-         WHERE r.risk_level IN ('Warning','Critical')
+         WHERE r.risk_level IN (?1, ?2)
          GROUP BY a.content
          HAVING c >= 2
          ORDER BY c DESC
          LIMIT 20",
     )?;
-    let rows = stmt.query_map([], |r| {
+    let rows = stmt.query_map(RISKY, |r| {
         Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)? as u32))
     })?;
 
@@ -375,10 +417,10 @@ pub fn build_ontology_v2_from_db(
         "SELECT a.agent, LOWER(a.action_type), COUNT(*)
          FROM analysis_results r
          JOIN actions a ON a.id = r.action_id
-         WHERE r.risk_level IN ('Warning','Critical')
+         WHERE r.risk_level IN (?1, ?2)
          GROUP BY a.agent, LOWER(a.action_type)",
     )?;
-    let risk_rows = stmt_risk.query_map([], |r| {
+    let risk_rows = stmt_risk.query_map(RISKY, |r| {
         Ok((
             r.get::<_, String>(0)?,
             r.get::<_, String>(1)?,
@@ -421,6 +463,40 @@ pub fn build_ontology_v2_from_db(
         }
     }
 
+    // 5) Decision notes written for ended sessions (none in a database
+    // without the table); a workspace's are those of its sessions
+    let notes: Vec<(String, String)> =
+        match conn.prepare("SELECT session_id, title FROM decision_notes") {
+            Ok(mut stmt) => stmt
+                .query_map([], |r| Ok((r.get(0)?, r.get(1)?)))?
+                .collect::<Result<_, _>>()?,
+            Err(_) => vec![],
+        };
+    for (session_id, title) in notes {
+        if workspace.is_some() && !node_seen.contains(&format!("session:{}", session_id)) {
+            continue;
+        }
+        let note_id = format!("note:{}", session_id);
+        push_node(
+            &mut nodes,
+            &mut node_seen,
+            OntologyNode {
+                id: note_id.clone(),
+                kind: "DecisionNote".to_string(),
+                title,
+            },
+        );
+        push_edge(
+            &mut edges,
+            &mut edge_seen,
+            OntologyEdge {
+                from: format!("session:{}", session_id),
+                to: note_id,
+                rel: "has_decision_note".to_string(),
+            },
+        );
+    }
+
     let insights = BrainInsights {
         repeated_patterns,
         decisions_detected,
@@ -506,15 +582,101 @@ pub fn persist_ontology_v2(
     Ok(summary)
 }
 
-fn load_actions(conn: &Connection) -> anyhow::Result<Vec<ActionRow>> {
-    let mut stmt = conn.prepare(
+/// Build the v2 ontology of `workspace` (or of every action) from the
+/// database and write it to its partition under `base_dir`
+pub fn rebuild_ontology_v2(
+    conn: &Connection,
+    base_dir: &Path,
+    workspace: Option<&str>,
+) -> anyhow::Result<(OntologyBuildSummary, BrainInsights)> {
+    let (nodes, edges, insights) = build_ontology_v2_scoped(conn, workspace)?;
+    let dir = crate::workspace::partition_dir(base_dir, workspace);
+    let summary = persist_ontology_v2(&dir, &nodes, &edges, &insights)?;
+    Ok((summary, insights))
+}
+
+/// Nodes of the last v2 build
+pub fn load_ontology_v2_nodes(base_dir: &Path) -> anyhow::Result<Vec<OntologyNode>> {
+    let text = fs::read_to_string(base_dir.join("ontology").join("v2").join("nodes.jsonl"))?;
+    Ok(text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Counts of the last v2 build
+pub fn load_brain_insights(base_dir: &Path) -> Option<BrainInsights> {
+    let text =
+        fs::read_to_string(base_dir.join("ontology").join("v2").join("insights.json")).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// A pattern, bottleneck or skill with its count or score
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RankedInsight {
+    pub title: String,
+    pub score: u32,
+}
+
+/// The highest-ranked TaskPatterns, Bottlenecks and Skills
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BrainOverview {
+    pub patterns: Vec<RankedInsight>,
+    pub bottlenecks: Vec<RankedInsight>,
+    pub skills: Vec<RankedInsight>,
+}
+
+/// Rank the v2 nodes by the count or score in their titles
+/// (`repeat x3: cmd`, `risk-hit x2: cmd`, `agent tool mastery score=5`)
+pub fn top_insights(nodes: &[OntologyNode], limit: usize) -> BrainOverview {
+    let ranked = |kind: &str, parse: fn(&str) -> Option<RankedInsight>| {
+        let mut ranked: Vec<RankedInsight> = nodes
+            .iter()
+            .filter(|n| n.kind == kind)
+            .filter_map(|n| parse(&n.title))
+            .collect();
+        ranked.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+        ranked.truncate(limit);
+        ranked
+    };
+    let counted = |title: &str| {
+        let (count, rest) = title.split_once(" x")?.1.split_once(": ")?;
+        Some(RankedInsight {
+            title: rest.to_string(),
+            score: count.parse().ok()?,
+        })
+    };
+    BrainOverview {
+        patterns: ranked("TaskPattern", counted),
+        bottlenecks: ranked("Bottleneck", counted),
+        skills: ranked("Skill", |title| {
+            let (name, score) = title.split_once(" mastery score=")?;
+            Some(RankedInsight {
+                title: name.to_string(),
+                score: score.parse().ok()?,
+            })
+        }),
+    }
+}
+
+fn load_actions(conn: &Connection, workspace: Option<&str>) -> anyhow::Result<Vec<ActionRow>> {
+    // Unscoped builds also run on `actions` tables without the column
+    let scope = if workspace.is_some() {
+        "WHERE workspace = ?1"
+    } else {
+        ""
+    };
+    let mut stmt = conn.prepare(&format!(
         "SELECT id, agent, action_type, content, target, session_id
          FROM actions
+         {}
          ORDER BY timestamp DESC
          LIMIT 5000",
-    )?;
+        scope
+    ))?;
 
-    let rows = stmt.query_map([], |r| {
+    let rows = stmt.query_map(rusqlite::params_from_iter(workspace), |r| {
         Ok(ActionRow {
             id: r.get(0)?,
             agent: r.get(1)?,
@@ -569,4 +731,49 @@ mod tests {
             "/Volumes/formac/proj/safebot"
         );
     }
+
+    #[test]
+    fn test_top_insights_ranks_by_count() {
+        let node = |kind: &str, title: &str| OntologyNode {
+            id: title.to_string(),
+            kind: kind.to_string(),
+            title: title.to_string(),
+        };
+        let nodes = vec![
+            node("TaskPattern", "repeat x3: cargo test"),
+            node("TaskPattern", "repeat x7: git status"),
+            node("TaskPattern", "repeat x5: ls -la"),
+            node("Bottleneck", "risk-hit x2: rm -rf build"),
+            node("Skill", "claude_code exec mastery score=12"),
+            node("Decision", "fix the build"),
+        ];
+        let overview = top_insights(&nodes, 2);
+        assert_eq!(
+            overview.patterns,
+            vec![
+                RankedInsight {
+                    title: "git status".to_string(),
+                    score: 7
+                },
+                RankedInsight {
+                    title: "ls -la".to_string(),
+                    score: 5
+                },
+            ]
+        );
+        assert_eq!(overview.bottlenecks[0].title, "rm -rf build");
+        assert_eq!(overview.skills[0].title, "claude_code exec");
+        assert_eq!(overview.skills[0].score, 12);
+
+        let tmp = tempfile::tempdir().unwrap();
+        let pool = crate::db::DbPool::open(&tmp.path().join("harness.db")).unwrap();
+        let (summary, insights) =
+            rebuild_ontology_v2(&pool.connection().unwrap(), tmp.path(), None).unwrap();
+        assert_eq!(insights.repeated_patterns, 0);
+        assert_eq!(
+            load_ontology_v2_nodes(tmp.path()).unwrap().len(),
+            summary.nodes
+        );
+        assert!(load_brain_insights(tmp.path()).is_some());
+    }
 }
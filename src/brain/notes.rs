@@ -0,0 +1,490 @@
+//! Decision notes
+//!
+//! When a session ends (no new action for
+//! `OPENCLAW_HARNESS_SESSION_IDLE_MINUTES`, default 30), the daemon writes
+//! what the agent did into a markdown note under `<data dir>/notes`: the
+//! decisions it took (fix, deploy, rollback, ... commands), its most run
+//! commands, the files it touched and the incidents it caused. Notes are
+//! recorded in the `decision_notes` table, so the v2 ontology links each
+//! session to its `DecisionNote`. A session that resumes gets its note
+//! rewritten when it ends again. Sessions that ended more than
+//! `LOOKBACK_DAYS` ago are not backfilled. Set
+//! `OPENCLAW_HARNESS_DECISION_NOTES=0` to turn notes off.
+
+use super::DECISION_KEYWORDS;
+use crate::db::DbPool;
+use crate::RiskLevel;
+use chrono::{DateTime, Duration, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable with the idle minutes that end a session
+pub const IDLE_MINUTES_ENV: &str = "OPENCLAW_HARNESS_SESSION_IDLE_MINUTES";
+/// Environment variable that turns notes off when `0`
+pub const ENABLED_ENV: &str = "OPENCLAW_HARNESS_DECISION_NOTES";
+/// How often the daemon looks for ended sessions
+pub const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+
+const DEFAULT_IDLE_MINUTES: i64 = 30;
+/// Sessions that ended before this are left alone
+pub const LOOKBACK_DAYS: i64 = 7;
+const MAX_COMMANDS: usize = 10;
+const MAX_FILES: usize = 20;
+const MAX_INCIDENTS: usize = 20;
+const MAX_LINE_CHARS: usize = 160;
+
+pub fn enabled() -> bool {
+    std::env::var(ENABLED_ENV).map_or(true, |v| v.trim() != "0")
+}
+
+pub fn idle_limit() -> Duration {
+    let minutes = std::env::var(IDLE_MINUTES_ENV)
+        .ok()
+        .and_then(|m| m.trim().parse().ok())
+        .filter(|m: &i64| *m > 0)
+        .unwrap_or(DEFAULT_IDLE_MINUTES);
+    Duration::minutes(minutes)
+}
+
+pub fn notes_dir(base_dir: &Path) -> PathBuf {
+    base_dir.join("notes")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NoteIncident {
+    pub timestamp: String,
+    pub risk_level: String,
+    pub rules: String,
+    pub content: String,
+}
+
+/// What one session did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecisionNote {
+    pub session_id: String,
+    pub agent: String,
+    pub started_at: String,
+    pub ended_at: String,
+    pub actions: usize,
+    /// Commands that look like decisions, in order
+    pub decisions: Vec<String>,
+    /// Most run commands with their counts
+    pub commands: Vec<(String, u32)>,
+    pub files: Vec<String>,
+    pub incidents: Vec<NoteIncident>,
+}
+
+impl DecisionNote {
+    pub fn title(&self) -> String {
+        let outcome = match self.incidents.len() {
+            0 => "no incidents".to_string(),
+            1 => "1 incident".to_string(),
+            n => format!("{} incidents", n),
+        };
+        format!(
+            "{} session {}: {} actions, {}",
+            self.agent,
+            short(&self.session_id),
+            self.actions,
+            outcome
+        )
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# Decision note: {}\n\n", self.title());
+        out.push_str(&format!("- Session: `{}`\n", self.session_id));
+        out.push_str(&format!("- Agent: {}\n", self.agent));
+        out.push_str(&format!(
+            "- Time (UTC): {} ~ {}\n",
+            self.started_at, self.ended_at
+        ));
+        out.push_str(&format!("- Actions: {}\n", self.actions));
+
+        let section = |out: &mut String, title: &str, lines: Vec<String>| {
+            out.push_str(&format!("\n## {}\n", title));
+            if lines.is_empty() {
+                out.push_str("- (none)\n");
+            }
+            for line in lines {
+                out.push_str(&format!("- {}\n", line));
+            }
+        };
+        section(
+            &mut out,
+            "Decisions",
+            self.decisions.iter().map(|d| format!("`{}`", d)).collect(),
+        );
+        section(
+            &mut out,
+            "Key commands",
+            self.commands
+                .iter()
+                .map(|(c, n)| format!("`{}` ×{}", c, n))
+                .collect(),
+        );
+        section(
+            &mut out,
+            "Files touched",
+            self.files.iter().map(|f| format!("`{}`", f)).collect(),
+        );
+        section(
+            &mut out,
+            "Incidents",
+            self.incidents
+                .iter()
+                .map(|i| {
+                    format!(
+                        "{} **{}** {}: `{}`",
+                        i.timestamp, i.risk_level, i.rules, i.content
+                    )
+                })
+                .collect(),
+        );
+        out
+    }
+}
+
+fn short(session_id: &str) -> &str {
+    session_id
+        .char_indices()
+        .nth(12)
+        .map_or(session_id, |(i, _)| &session_id[..i])
+}
+
+fn one_line(text: &str) -> String {
+    let line = text.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > MAX_LINE_CHARS {
+        format!("{}…", line.chars().take(MAX_LINE_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// Sessions whose last action is older than `idle` (but within
+/// `LOOKBACK_DAYS`) and whose note is missing or older than that action
+pub fn ended_sessions(
+    conn: &Connection,
+    now: DateTime<Utc>,
+    idle: Duration,
+) -> anyhow::Result<Vec<String>> {
+    let cutoff = now - idle;
+    let mut stmt = conn.prepare(
+        "SELECT a.session_id
+         FROM actions a LEFT JOIN decision_notes n ON n.session_id = a.session_id
+         WHERE a.session_id IS NOT NULL AND a.session_id != ''
+         GROUP BY a.session_id
+         HAVING MAX(a.timestamp) < ?1 AND MAX(a.timestamp) >= ?2
+            AND (MAX(n.ended_at) IS NULL OR MAX(n.ended_at) < MAX(a.timestamp))",
+    )?;
+    let rows = stmt.query_map(
+        params![
+            cutoff.to_rfc3339(),
+            (cutoff - Duration::days(LOOKBACK_DAYS)).to_rfc3339()
+        ],
+        |r| r.get::<_, String>(0),
+    )?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+/// Summarize a session from its actions and their latest analyses
+pub fn build_note(conn: &Connection, session_id: &str) -> anyhow::Result<Option<DecisionNote>> {
+    let mut stmt = conn.prepare(
+        "SELECT a.timestamp, a.agent, a.action_type, a.content, a.target,
+                r.risk_level, r.matched_rules
+         FROM actions a
+         LEFT JOIN analysis_results r ON r.id = (
+             SELECT MAX(id) FROM analysis_results WHERE action_id = a.id
+         )
+         WHERE a.session_id = ?1
+         ORDER BY a.timestamp ASC",
+    )?;
+    let mut rows = stmt.query([session_id])?;
+
+    let mut agents: HashMap<String, usize> = HashMap::new();
+    let mut command_counts: HashMap<String, u32> = HashMap::new();
+    let mut decisions = vec![];
+    let mut files: Vec<String> = vec![];
+    let mut incidents = vec![];
+    let (mut started_at, mut ended_at, mut actions) = (None, String::new(), 0);
+    while let Some(row) = rows.next()? {
+        let timestamp: String = row.get(0)?;
+        let agent: String = row.get(1)?;
+        let action_type: String = row.get(2)?;
+        let content: String = row.get(3)?;
+        let target: Option<String> = row.get(4)?;
+        let risk_level: Option<String> = row.get(5)?;
+        let rules: Option<String> = row.get(6)?;
+
+        actions += 1;
+        started_at.get_or_insert_with(|| timestamp.clone());
+        *agents.entry(agent).or_default() += 1;
+        if action_type.eq_ignore_ascii_case("exec") {
+            let command = one_line(&content);
+            let lower = command.to_lowercase();
+            if DECISION_KEYWORDS.iter().any(|k| lower.contains(k)) {
+                decisions.push(command.clone());
+            }
+            *command_counts.entry(command).or_default() += 1;
+        }
+        if let Some(target) = target.filter(|t| t.starts_with('/')) {
+            if !files.contains(&target) {
+                files.push(target);
+            }
+        }
+        let risky = matches!(
+            risk_level.as_deref(),
+            Some(r) if r == RiskLevel::Warning.as_str() || r == RiskLevel::Critical.as_str()
+        );
+        if risky {
+            incidents.push(NoteIncident {
+                timestamp: timestamp.clone(),
+                risk_level: risk_level.unwrap_or_default(),
+                rules: rules.unwrap_or_default(),
+                content: one_line(&content),
+            });
+        }
+        ended_at = timestamp;
+    }
+    let Some(started_at) = started_at else {
+        return Ok(None);
+    };
+
+    let mut commands: Vec<(String, u32)> = command_counts.into_iter().collect();
+    commands.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    commands.truncate(MAX_COMMANDS);
+    files.truncate(MAX_FILES);
+    decisions.dedup();
+    // Critical first, then in order
+    incidents.sort_by_key(|i| i.risk_level != RiskLevel::Critical.as_str());
+    incidents.truncate(MAX_INCIDENTS);
+    let agent = agents
+        .into_iter()
+        .max_by_key(|(_, n)| *n)
+        .map(|(a, _)| a)
+        .unwrap_or_default();
+
+    Ok(Some(DecisionNote {
+        session_id: session_id.to_string(),
+        agent,
+        started_at,
+        ended_at,
+        actions,
+        decisions,
+        commands,
+        files,
+        incidents,
+    }))
+}
+
+/// File name for a session's note; session ids are not trusted as paths
+fn note_file(session_id: &str) -> String {
+    let safe: String = session_id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect();
+    format!("{}-{}.md", safe, &super::hash_short(session_id)[..8])
+}
+
+/// Write the note under `base_dir/notes` and record it in the database
+pub fn write_note(
+    conn: &Connection,
+    base_dir: &Path,
+    note: &DecisionNote,
+) -> anyhow::Result<PathBuf> {
+    let dir = notes_dir(base_dir);
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(note_file(&note.session_id));
+    fs::write(&path, note.to_markdown())?;
+    conn.execute(
+        "INSERT INTO decision_notes (session_id, agent, started_at, ended_at, title, path, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+         ON CONFLICT(session_id) DO UPDATE SET
+             agent = excluded.agent, started_at = excluded.started_at,
+             ended_at = excluded.ended_at, title = excluded.title,
+             path = excluded.path, created_at = excluded.created_at",
+        params![
+            note.session_id,
+            note.agent,
+            note.started_at,
+            note.ended_at,
+            note.title(),
+            path.to_string_lossy(),
+            Utc::now().to_rfc3339()
+        ],
+    )?;
+    Ok(path)
+}
+
+/// Where a session's note was written, if it has one
+pub fn note_path(conn: &Connection, session_id: &str) -> anyhow::Result<Option<PathBuf>> {
+    Ok(conn
+        .query_row(
+            "SELECT path FROM decision_notes WHERE session_id = ?1",
+            [session_id],
+            |r| r.get::<_, String>(0),
+        )
+        .optional()?
+        .map(PathBuf::from))
+}
+
+/// A note as recorded in the database
+#[derive(Debug, Clone)]
+pub struct StoredNote {
+    pub session_id: String,
+    pub started_at: String,
+    pub ended_at: String,
+    pub path: PathBuf,
+}
+
+pub fn stored_notes(conn: &Connection) -> anyhow::Result<Vec<StoredNote>> {
+    let mut stmt =
+        conn.prepare("SELECT session_id, started_at, ended_at, path FROM decision_notes")?;
+    let rows = stmt.query_map([], |r| {
+        Ok(StoredNote {
+            session_id: r.get(0)?,
+            started_at: r.get(1)?,
+            ended_at: r.get(2)?,
+            path: PathBuf::from(r.get::<_, String>(3)?),
+        })
+    })?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+/// Drop a note's record (the file is the caller's)
+pub fn forget_note(conn: &Connection, session_id: &str) -> anyhow::Result<()> {
+    conn.execute(
+        "DELETE FROM decision_notes WHERE session_id = ?1",
+        [session_id],
+    )?;
+    Ok(())
+}
+
+/// Move a note to another session id, e.g. an anonymized alias
+pub fn rename_note(conn: &Connection, session_id: &str, new_id: &str) -> anyhow::Result<()> {
+    conn.execute(
+        "UPDATE OR REPLACE decision_notes SET session_id = ?2, title = replace(title, ?3, ?4)
+         WHERE session_id = ?1",
+        params![session_id, new_id, short(session_id), short(new_id)],
+    )?;
+    Ok(())
+}
+
+/// Write notes for every session that has ended; returns how many
+pub fn write_due_notes(
+    pool: &DbPool,
+    base_dir: &Path,
+    now: DateTime<Utc>,
+) -> anyhow::Result<usize> {
+    let conn = pool.connection()?;
+    let mut written = 0;
+    for session_id in ended_sessions(&conn, now, idle_limit())? {
+        if let Some(note) = build_note(&conn, &session_id)? {
+            write_note(&conn, base_dir, &note)?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentAction, AgentType, AnalysisResult, Recommendation};
+
+    #[test]
+    fn test_decision_note_for_ended_session() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pool = DbPool::open(&tmp.path().join("harness.db")).unwrap();
+        let db = pool.get().unwrap();
+        let start = Utc::now() - Duration::hours(2);
+        let store = |id: &str, minutes: i64, session: &str, kind: ActionType, content: &str| {
+            let action = AgentAction {
+                id: id.to_string(),
+                timestamp: start + Duration::minutes(minutes),
+                agent: AgentType::ClaudeCode,
+                action_type: kind,
+                content: content.to_string(),
+                target: content.starts_with('/').then(|| content.to_string()),
+                session_id: Some(session.to_string()),
+                metadata: None,
+            };
+            db.store_action(&action).unwrap();
+            action
+        };
+        store("a1", 0, "s1", ActionType::Exec, "cargo test");
+        store("a2", 1, "s1", ActionType::Exec, "cargo test");
+        store("a3", 2, "s1", ActionType::FileWrite, "/repo/src/lib.rs");
+        store(
+            "a4",
+            3,
+            "s1",
+            ActionType::Exec,
+            "git commit -m 'fix parser'",
+        );
+        let risky = store("a5", 4, "s1", ActionType::Exec, "rm -rf /repo/target");
+        db.store_analysis(&AnalysisResult {
+            action: risky,
+            matched_rules: vec!["dangerous_rm".to_string()],
+            risk_level: RiskLevel::Critical,
+            recommendation: Recommendation::CriticalAlert,
+            explanation: "rm -rf".to_string(),
+        })
+        .unwrap();
+        // Still active
+        store("b1", 110, "s2", ActionType::Exec, "ls");
+
+        let conn = pool.connection().unwrap();
+        let now = Utc::now();
+        assert_eq!(
+            ended_sessions(&conn, now, Duration::minutes(30)).unwrap(),
+            ["s1"]
+        );
+
+        let note = build_note(&conn, "s1").unwrap().unwrap();
+        assert_eq!(note.actions, 5);
+        assert_eq!(note.commands[0], ("cargo test".to_string(), 2));
+        assert_eq!(note.decisions, ["git commit -m 'fix parser'"]);
+        assert_eq!(note.files, ["/repo/src/lib.rs"]);
+        assert_eq!(note.incidents[0].rules, "dangerous_rm");
+        let markdown = note.to_markdown();
+        assert!(markdown.contains("## Incidents\n- "));
+        assert!(markdown.contains("**critical** dangerous_rm: `rm -rf /repo/target`"));
+
+        let path = write_note(&conn, tmp.path(), &note).unwrap();
+        assert!(path.starts_with(tmp.path().join("notes")));
+        assert_eq!(note_path(&conn, "s1").unwrap(), Some(path));
+        // Written once, until the session resumes
+        assert!(ended_sessions(&conn, now, Duration::minutes(30))
+            .unwrap()
+            .is_empty());
+        store("a6", 10, "s1", ActionType::Exec, "cargo build");
+        assert_eq!(
+            ended_sessions(&conn, now, Duration::minutes(30)).unwrap(),
+            ["s1"]
+        );
+
+        // The ontology links the session to its note
+        let (nodes, edges, _) = super::super::build_ontology_v2_from_db(&conn).unwrap();
+        assert!(nodes
+            .iter()
+            .any(|n| n.id == "note:s1" && n.kind == "DecisionNote"));
+        assert!(edges
+            .iter()
+            .any(|e| e.from == "session:s1" && e.to == "note:s1" && e.rel == "has_decision_note"));
+
+        assert_eq!(
+            note_file("../../etc/passwd").len(),
+            "______etc_passwd".len() + 12
+        );
+    }
+}
@@ -0,0 +1,301 @@
+//! Behaviour records for adaptive campaigns
+//!
+//! Missions (see `campaign`) are planned from the `Behaviours` table. Rows
+//! come from two places: clients post them to `POST /api/behaviours`, and
+//! the daemon derives one per agent action (`derive_from_actions`). A
+//! derived record's user is the agent, its event type the action type in
+//! snake case (`exec`, `file_write`, ...), and it succeeds when the action
+//! raised no Warning or Critical finding. Its duration is the time until
+//! the session's next action, capped at `MAX_GAP_MINUTES`. Actions are
+//! mapped once they are that old, so the gap and the analysis are settled.
+
+use super::BehaviourRecord;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+/// How often the daemon derives records from new actions
+pub const DERIVE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(300);
+/// Longest duration credited to a derived record
+pub const MAX_GAP_MINUTES: i64 = 30;
+/// Actions older than this are not backfilled
+pub const LOOKBACK_DAYS: i64 = 7;
+
+/// `created_at` as SQLite's `datetime()` writes it, in UTC
+const TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const BATCH: usize = 1000;
+const SOURCE_API: &str = "api";
+const SOURCE_ACTION: &str = "action";
+
+/// A record posted by a client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewBehaviour {
+    pub user_id: String,
+    pub event_type: String,
+    pub success: bool,
+    #[serde(default)]
+    pub duration_minutes: i64,
+    /// RFC 3339 or `YYYY-MM-DD HH:MM:SS` (UTC); defaults to now
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+/// Parse an RFC 3339 or `YYYY-MM-DD HH:MM:SS` (UTC) time
+pub fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    DateTime::parse_from_rfc3339(s)
+        .map(|t| t.with_timezone(&Utc))
+        .ok()
+        .or_else(|| {
+            NaiveDateTime::parse_from_str(s, TIME_FORMAT)
+                .ok()
+                .map(|t| t.and_utc())
+        })
+}
+
+fn format_time(t: DateTime<Utc>) -> String {
+    t.format(TIME_FORMAT).to_string()
+}
+
+/// Store posted records; nothing is stored if any of them is invalid
+pub fn insert(
+    conn: &Connection,
+    records: &[NewBehaviour],
+    now: DateTime<Utc>,
+) -> anyhow::Result<usize> {
+    let mut rows = Vec::with_capacity(records.len());
+    for (i, r) in records.iter().enumerate() {
+        if r.user_id.trim().is_empty() || r.event_type.trim().is_empty() {
+            anyhow::bail!("record {}: user_id and event_type are required", i);
+        }
+        if r.duration_minutes < 0 {
+            anyhow::bail!("record {}: duration_minutes must not be negative", i);
+        }
+        let created_at = match &r.created_at {
+            Some(t) => parse_time(t)
+                .ok_or_else(|| anyhow::anyhow!("record {}: bad created_at {:?}", i, t))?,
+            None => now,
+        };
+        rows.push((r, format_time(created_at)));
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    for (r, created_at) in &rows {
+        tx.execute(
+            "INSERT INTO Behaviours (user_id, event_type, success, duration_minutes, created_at, source)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                r.user_id.trim(),
+                r.event_type.trim(),
+                r.success,
+                r.duration_minutes,
+                created_at,
+                SOURCE_API
+            ],
+        )?;
+    }
+    tx.commit()?;
+    Ok(rows.len())
+}
+
+/// A user's most recent records
+pub fn list(
+    conn: &Connection,
+    user_id: &str,
+    limit: usize,
+) -> anyhow::Result<Vec<BehaviourRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT user_id, event_type, success, duration_minutes, created_at
+         FROM Behaviours
+         WHERE user_id = ?1
+         ORDER BY created_at DESC
+         LIMIT ?2",
+    )?;
+
+    let rows = stmt.query_map(params![user_id, limit as i64], |row| {
+        Ok(BehaviourRecord {
+            user_id: row.get(0)?,
+            event_type: row.get(1)?,
+            success: row.get::<_, i64>(2)? == 1,
+            duration_minutes: row.get(3)?,
+            created_at: row.get(4)?,
+        })
+    })?;
+
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+/// Remove all of a user's records
+pub fn delete_user(conn: &Connection, user_id: &str) -> anyhow::Result<usize> {
+    Ok(conn.execute("DELETE FROM Behaviours WHERE user_id = ?1", [user_id])?)
+}
+
+/// `FileWrite` -> `file_write`
+pub fn event_type(action_type: &str) -> String {
+    let mut out = String::with_capacity(action_type.len() + 4);
+    for (i, c) in action_type.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Add a record for each settled action that has none yet
+pub fn derive_from_actions(conn: &Connection, now: DateTime<Utc>) -> anyhow::Result<usize> {
+    let from = (now - Duration::days(LOOKBACK_DAYS)).to_rfc3339();
+    let settled = (now - Duration::minutes(MAX_GAP_MINUTES)).to_rfc3339();
+    let mut stmt = conn.prepare(
+        "SELECT a.id, a.agent, a.action_type, a.timestamp,
+                (SELECT MIN(n.timestamp) FROM actions n
+                 WHERE n.session_id = a.session_id AND n.timestamp > a.timestamp),
+                EXISTS (SELECT 1 FROM analysis_results r
+                        WHERE r.action_id = a.id AND r.risk_level IN ('warning', 'critical'))
+         FROM actions a
+         WHERE a.timestamp BETWEEN ?1 AND ?2
+           AND NOT EXISTS (SELECT 1 FROM Behaviours b WHERE b.action_id = a.id)
+         ORDER BY a.timestamp
+         LIMIT ?3",
+    )?;
+
+    let mut total = 0;
+    loop {
+        let rows = stmt
+            .query_map(params![from, settled, BATCH as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                    row.get::<_, Option<String>>(4)?,
+                    row.get::<_, bool>(5)?,
+                ))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tx = conn.unchecked_transaction()?;
+        for (id, agent, action_type, timestamp, next, incident) in &rows {
+            let at = parse_time(timestamp);
+            let duration = match (at, next.as_deref().and_then(parse_time)) {
+                (Some(at), Some(next)) => (next - at).num_minutes().clamp(0, MAX_GAP_MINUTES),
+                _ => 0,
+            };
+            tx.execute(
+                "INSERT OR IGNORE INTO Behaviours
+                 (user_id, event_type, success, duration_minutes, created_at, source, action_id)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    agent,
+                    event_type(action_type),
+                    !incident,
+                    duration,
+                    at.map_or_else(|| timestamp.clone(), format_time),
+                    SOURCE_ACTION,
+                    id
+                ],
+            )?;
+            total += 1;
+        }
+        tx.commit()?;
+
+        if rows.len() < BATCH {
+            return Ok(total);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbPool;
+    use crate::{ActionType, AgentAction, AgentType, AnalysisResult, Recommendation, RiskLevel};
+
+    fn action(id: &str, minutes_ago: i64, action_type: ActionType) -> AgentAction {
+        AgentAction {
+            id: id.to_string(),
+            timestamp: Utc::now() - Duration::minutes(minutes_ago),
+            agent: AgentType::ClaudeCode,
+            action_type,
+            content: "cargo build".to_string(),
+            target: None,
+            session_id: Some("s1".to_string()),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_behaviours_ingest_and_derive() {
+        assert_eq!(event_type("FileWrite"), "file_write");
+        assert_eq!(event_type("Exec"), "exec");
+
+        let tmp = tempfile::tempdir().unwrap();
+        let pool = DbPool::open(&tmp.path().join("harness.db")).unwrap();
+        let db = pool.get().unwrap();
+        let conn = &*pool.connection().unwrap();
+        let now = Utc::now();
+
+        let posted = vec![
+            NewBehaviour {
+                user_id: "u1".to_string(),
+                event_type: "checkout".to_string(),
+                success: true,
+                duration_minutes: 12,
+                created_at: Some("2026-03-01T10:00:00+02:00".to_string()),
+            },
+            NewBehaviour {
+                user_id: "u1".to_string(),
+                event_type: "checkout".to_string(),
+                success: false,
+                duration_minutes: 3,
+                created_at: None,
+            },
+        ];
+        assert_eq!(insert(conn, &posted, now).unwrap(), 2);
+        let records = list(conn, "u1", 10).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].created_at, "2026-03-01 08:00:00");
+
+        // One bad record rejects the whole batch
+        let mut bad = posted.clone();
+        bad[1].created_at = Some("yesterday".to_string());
+        assert!(insert(conn, &bad, now).is_err());
+        assert_eq!(list(conn, "u1", 10).unwrap().len(), 2);
+
+        // 50 and 40 minutes ago are settled; 10 minutes ago is not yet
+        db.store_action(&action("a1", 50, ActionType::Exec))
+            .unwrap();
+        let risky = action("a2", 40, ActionType::FileWrite);
+        db.store_action(&risky).unwrap();
+        db.store_action(&action("a3", 10, ActionType::Exec))
+            .unwrap();
+        db.store_analysis(&AnalysisResult {
+            action: risky,
+            matched_rules: vec!["secrets".to_string()],
+            risk_level: RiskLevel::Warning,
+            recommendation: Recommendation::Alert,
+            explanation: "x".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(derive_from_actions(conn, now).unwrap(), 2);
+        assert_eq!(derive_from_actions(conn, now).unwrap(), 0);
+        let derived = list(conn, "claude_code", 10).unwrap();
+        assert_eq!(derived.len(), 2);
+        assert_eq!(derived[0].event_type, "file_write");
+        assert!(!derived[0].success);
+        // a2 -> a3 is 30 minutes, the cap
+        assert_eq!(derived[0].duration_minutes, MAX_GAP_MINUTES);
+        assert!(derived[1].success);
+        assert_eq!(derived[1].duration_minutes, 10);
+
+        // Purging an action drops its record
+        db.delete_action("a1").unwrap();
+        assert_eq!(list(conn, "claude_code", 10).unwrap().len(), 1);
+        assert_eq!(delete_user(conn, "u1").unwrap(), 2);
+    }
+}
@@ -1,7 +1,12 @@
+pub mod behaviours;
+
+use crate::db::DbPool;
+use crate::llm::{LlmClient, LlmConfig};
 use anyhow::Context;
-use reqwest::blocking::Client;
+use async_trait::async_trait;
 use rusqlite::{params, Connection};
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BehaviourRecord {
@@ -64,50 +69,316 @@ pub struct MissionPlan {
     pub clamped: bool,
 }
 
-pub trait MissionAiPlanner {
-    fn propose(
+/// Why a mission could not be planned
+#[derive(Debug, thiserror::Error)]
+pub enum CampaignError {
+    /// The chosen planner can't run, e.g. no LLM key is configured
+    #[error("planner unavailable: {0}")]
+    PlannerUnavailable(String),
+    #[error("no behaviour history for {0}")]
+    NoHistory(String),
+    /// The LLM could not be reached or answered with an error
+    #[error("LLM request failed: {0}")]
+    Llm(String),
+    /// The LLM kept answering with a mission that doesn't validate
+    #[error("planner output invalid after {attempts} attempts: {reason}")]
+    InvalidDraft { attempts: u32, reason: String },
+    /// The mission breaks the campaign constraints
+    #[error("mission rejected: {0}")]
+    Rejected(String),
+    #[error("mission planning timed out after {0:?}")]
+    Timeout(Duration),
+    #[error(transparent)]
+    Internal(#[from] anyhow::Error),
+}
+
+impl CampaignError {
+    /// Short machine-readable name
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::PlannerUnavailable(_) => "planner_unavailable",
+            Self::NoHistory(_) => "no_history",
+            Self::Llm(_) => "llm_error",
+            Self::InvalidDraft { .. } => "invalid_draft",
+            Self::Rejected(_) => "rejected",
+            Self::Timeout(_) => "timeout",
+            Self::Internal(_) => "internal",
+        }
+    }
+}
+
+#[async_trait]
+pub trait MissionAiPlanner: Send + Sync {
+    async fn propose(
         &self,
-        conn: &Connection,
         stats: &UserBehaviourStats,
         history: &[BehaviourRecord],
         constraints: &CampaignConstraints,
-    ) -> anyhow::Result<MissionDraft>;
+    ) -> Result<MissionDraft, CampaignError>;
+}
+
+#[async_trait]
+impl<P: MissionAiPlanner + ?Sized> MissionAiPlanner for Box<P> {
+    async fn propose(
+        &self,
+        stats: &UserBehaviourStats,
+        history: &[BehaviourRecord],
+        constraints: &CampaignConstraints,
+    ) -> Result<MissionDraft, CampaignError> {
+        (**self).propose(stats, history, constraints).await
+    }
+}
+
+/// Env var choosing the planner: `llm` or `heuristic`. Unset, the LLM
+/// planner is used when `SAFEBOT_LLM_API_KEY` is set and the heuristic one
+/// otherwise.
+pub const PLANNER_ENV: &str = "OPENCLAW_HARNESS_CAMPAIGN_PLANNER";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PlannerKind {
+    Llm,
+    Heuristic,
+}
+
+impl PlannerKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "llm" => Some(Self::Llm),
+            "heuristic" => Some(Self::Heuristic),
+            _ => None,
+        }
+    }
+
+    pub fn from_env() -> Self {
+        if let Some(kind) = std::env::var(PLANNER_ENV)
+            .ok()
+            .and_then(|v| Self::parse(&v))
+        {
+            return kind;
+        }
+        if LlmConfig::from_env().is_ok() {
+            Self::Llm
+        } else {
+            Self::Heuristic
+        }
+    }
+
+    /// The planner of this kind; the LLM one audits its attempts to `audit`
+    pub fn planner(
+        self,
+        audit: Option<DbPool>,
+    ) -> Result<Box<dyn MissionAiPlanner>, CampaignError> {
+        Ok(match self {
+            Self::Llm => Box::new(
+                LlmAiPlanner::from_env()
+                    .map_err(|e| CampaignError::PlannerUnavailable(e.to_string()))?
+                    .with_audit(audit),
+            ),
+            Self::Heuristic => Box::new(HeuristicPlanner),
+        })
+    }
+}
+
+/// Offline planner: a templated mission built from behaviour stats alone.
+///
+/// The user's most frequent event type becomes the mission target. The
+/// tier (warm-up, steady, stretch) follows their success rate with it, and
+/// the required count follows how often they do it, scaled by the tier.
+/// Tiers are eased until the mission fits the campaign constraints.
+pub struct HeuristicPlanner;
+
+struct MissionTier {
+    name: &'static str,
+    /// Multiplier on the user's usual count in a window
+    factor: f32,
+    difficulty: f32,
+    probability: f32,
+}
+
+const TIERS: [MissionTier; 3] = [
+    MissionTier {
+        name: "Warm-up",
+        factor: 0.6,
+        difficulty: 0.3,
+        probability: 0.8,
+    },
+    MissionTier {
+        name: "Steady",
+        factor: 1.0,
+        difficulty: 0.5,
+        probability: 0.7,
+    },
+    MissionTier {
+        name: "Stretch",
+        factor: 1.25,
+        difficulty: 0.7,
+        probability: 0.6,
+    },
+];
+
+const HEURISTIC_WINDOW_HOURS: u32 = 48;
+const POINTS_PER_EVENT: f32 = 10.0;
+
+fn parse_created_at(s: &str) -> Option<chrono::NaiveDateTime> {
+    chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .or_else(|| {
+            chrono::DateTime::parse_from_rfc3339(s)
+                .ok()
+                .map(|t| t.naive_utc())
+        })
+}
+
+/// Events of `records` per day, taking at least one day as the span
+fn events_per_day(records: &[&BehaviourRecord]) -> f32 {
+    let times: Vec<_> = records
+        .iter()
+        .filter_map(|r| parse_created_at(&r.created_at))
+        .collect();
+    let span_days = match (times.iter().min(), times.iter().max()) {
+        (Some(first), Some(last)) => ((*last - *first).num_minutes() as f32 / 1440.0).max(1.0),
+        _ => 1.0,
+    };
+    records.len() as f32 / span_days
+}
+
+#[async_trait]
+impl MissionAiPlanner for HeuristicPlanner {
+    async fn propose(
+        &self,
+        stats: &UserBehaviourStats,
+        history: &[BehaviourRecord],
+        constraints: &CampaignConstraints,
+    ) -> Result<MissionDraft, CampaignError> {
+        let mut counts: std::collections::BTreeMap<&str, usize> = Default::default();
+        for h in history {
+            *counts.entry(h.event_type.as_str()).or_default() += 1;
+        }
+        let Some((event_type, _)) = counts.iter().max_by(|a, b| a.1.cmp(b.1).then(b.0.cmp(a.0)))
+        else {
+            return Err(CampaignError::NoHistory(stats.user_id.clone()));
+        };
+
+        let records: Vec<&BehaviourRecord> = history
+            .iter()
+            .filter(|h| h.event_type == *event_type)
+            .collect();
+        let success_rate =
+            records.iter().filter(|r| r.success).count() as f32 / records.len() as f32;
+        let avg_minutes = records
+            .iter()
+            .map(|r| r.duration_minutes.max(0) as f32)
+            .sum::<f32>()
+            / records.len() as f32;
+        let usual = events_per_day(&records) * HEURISTIC_WINDOW_HOURS as f32 / 24.0;
+
+        let start = if success_rate >= 0.7 {
+            2
+        } else if success_rate >= 0.4 {
+            1
+        } else {
+            0
+        };
+        // Ease off until the tier is likely enough; the warm-up is the floor
+        let tier_index = (0..=start)
+            .rev()
+            .find(|&i| TIERS[i].probability >= constraints.min_completion_probability)
+            .unwrap_or(0);
+        let tier = &TIERS[tier_index];
+
+        let mut required_count = ((usual * tier.factor).round() as u32).max(1);
+        if avg_minutes > 0.0 {
+            let fits = (constraints.max_expected_hours * 60.0 / avg_minutes).floor() as u32;
+            required_count = required_count.min(fits.max(1));
+        }
+        let expected_hours = required_count as f32 * avg_minutes / 60.0;
+        let recommended_points = clamp_points(
+            (required_count as f32 * POINTS_PER_EVENT * (1.0 + tier.difficulty)).round() as u32,
+            constraints.max_points_per_mission,
+        );
+
+        Ok(MissionDraft {
+            title: format!("{}: {} x{}", tier.name, event_type, required_count),
+            description: format!(
+                "Complete {} {} events within {} hours",
+                required_count, event_type, HEURISTIC_WINDOW_HOURS
+            ),
+            rule: MissionRule {
+                mission_type: "count_event".to_string(),
+                required_count,
+                event_type: event_type.to_string(),
+                window_hours: HEURISTIC_WINDOW_HOURS,
+            },
+            difficulty_score: tier.difficulty,
+            expected_completion_probability: tier.probability,
+            expected_hours,
+            recommended_points,
+            analysis: format!(
+                "Heuristic plan from {} events: {} is the most frequent ({} events, \
+                 success_rate={:.2}, ~{:.1} per {}h, avg_duration={:.1}m)",
+                stats.total_events,
+                event_type,
+                records.len(),
+                success_rate,
+                usual,
+                HEURISTIC_WINDOW_HOURS,
+                avg_minutes
+            ),
+        })
+    }
 }
 
 /// Production LLM planner.
 ///
-/// Env vars:
-/// - `SAFEBOT_LLM_API_KEY` (required)
-/// - `SAFEBOT_LLM_BASE_URL` (optional, default https://api.openai.com/v1)
-/// - `SAFEBOT_LLM_MODEL` (optional, default gpt-4o-mini)
+/// Configured by the `SAFEBOT_LLM_*` env vars (see `crate::llm`). Each
+/// attempt is recorded in `mission_generation_audit` when an audit database
+/// is given.
 pub struct LlmAiPlanner {
-    client: Client,
-    api_key: String,
-    base_url: String,
-    model: String,
+    client: LlmClient,
+    audit: Option<DbPool>,
     max_attempts: u32,
 }
 
 impl LlmAiPlanner {
     pub fn from_env() -> anyhow::Result<Self> {
-        let api_key = std::env::var("SAFEBOT_LLM_API_KEY")
-            .context("missing SAFEBOT_LLM_API_KEY for LLM planner")?;
-        let base_url = std::env::var("SAFEBOT_LLM_BASE_URL")
-            .unwrap_or_else(|_| "https://api.openai.com/v1".to_string());
-        let model =
-            std::env::var("SAFEBOT_LLM_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_string());
-
         Ok(Self {
-            client: Client::builder()
-                .timeout(std::time::Duration::from_secs(30))
-                .build()?,
-            api_key,
-            base_url,
-            model,
+            client: LlmClient::from_env()?,
+            audit: None,
             max_attempts: 3,
         })
     }
 
+    pub fn with_audit(mut self, audit: Option<DbPool>) -> Self {
+        self.audit = audit;
+        self
+    }
+
+    fn audit(
+        &self,
+        user_id: &str,
+        attempt: u32,
+        status: &str,
+        prompt: &str,
+        raw_output: &str,
+        error: Option<&str>,
+    ) -> anyhow::Result<()> {
+        let Some(pool) = &self.audit else {
+            return Ok(());
+        };
+        let conn = pool.connection()?;
+        ensure_audit_table(&conn)?;
+        write_audit_log(
+            &conn,
+            user_id,
+            attempt as i64,
+            status,
+            prompt,
+            raw_output,
+            error,
+        )
+    }
+
     fn build_prompt(
         &self,
         stats: &UserBehaviourStats,
@@ -148,72 +419,35 @@ impl LlmAiPlanner {
              Broken output:\n{broken}"
         )
     }
-
-    fn call_chat(&self, prompt: &str) -> anyhow::Result<String> {
-        let url = format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
-        let body = serde_json::json!({
-            "model": self.model,
-            "temperature": 0.2,
-            "response_format": {"type":"json_object"},
-            "messages": [
-                {"role":"system","content":"You generate strict JSON for adaptive campaigns."},
-                {"role":"user","content":prompt}
-            ]
-        });
-
-        let resp = self
-            .client
-            .post(url)
-            .bearer_auth(&self.api_key)
-            .json(&body)
-            .send()?
-            .error_for_status()?;
-
-        let v: serde_json::Value = resp.json()?;
-        let content = v["choices"][0]["message"]["content"]
-            .as_str()
-            .ok_or_else(|| anyhow::anyhow!("LLM response missing content"))?;
-        Ok(content.to_string())
-    }
 }
 
+#[async_trait]
 impl MissionAiPlanner for LlmAiPlanner {
-    fn propose(
+    async fn propose(
         &self,
-        conn: &Connection,
         stats: &UserBehaviourStats,
         history: &[BehaviourRecord],
         constraints: &CampaignConstraints,
-    ) -> anyhow::Result<MissionDraft> {
-        ensure_audit_table(conn)?;
-
-        let initial_prompt = self.build_prompt(stats, history, constraints);
-        let mut prompt = initial_prompt.clone();
+    ) -> Result<MissionDraft, CampaignError> {
+        let mut prompt = self.build_prompt(stats, history, constraints);
         let mut last_err = String::new();
 
         for attempt in 1..=self.max_attempts {
             let raw = self
-                .call_chat(&prompt)
-                .with_context(|| format!("LLM call failed at attempt {attempt}"))?;
+                .client
+                .chat_json("You generate strict JSON for adaptive campaigns.", &prompt)
+                .await
+                .map_err(|e| CampaignError::Llm(format!("attempt {attempt}: {e}")))?;
             match validate_mission_draft_json(&raw, constraints) {
                 Ok(draft) => {
-                    write_audit_log(
-                        conn,
-                        &stats.user_id,
-                        attempt as i64,
-                        "ok",
-                        &prompt,
-                        &raw,
-                        None,
-                    )?;
+                    self.audit(&stats.user_id, attempt, "ok", &prompt, &raw, None)?;
                     return Ok(draft);
                 }
                 Err(e) => {
-                    last_err = e.to_string();
-                    write_audit_log(
-                        conn,
+                    last_err = format!("{:#}", e);
+                    self.audit(
                         &stats.user_id,
-                        attempt as i64,
+                        attempt,
                         "repair_needed",
                         &prompt,
                         &raw,
@@ -224,7 +458,10 @@ impl MissionAiPlanner for LlmAiPlanner {
             }
         }
 
-        anyhow::bail!("LLM planner failed after retries: {last_err}")
+        Err(CampaignError::InvalidDraft {
+            attempts: self.max_attempts,
+            reason: last_err,
+        })
     }
 }
 
@@ -237,22 +474,26 @@ impl<P: MissionAiPlanner> CampaignEngine<P> {
         Self { planner }
     }
 
-    pub fn generate_mission(
+    /// Plan a mission from `history` (see `load_history`)
+    pub async fn generate_mission(
         &self,
-        conn: &Connection,
         user_id: &str,
+        history: &[BehaviourRecord],
         constraints: &CampaignConstraints,
-    ) -> anyhow::Result<MissionPlan> {
-        let history = load_behaviours(conn, user_id)?;
-        let stats = compute_stats(user_id, &history);
+    ) -> Result<MissionPlan, CampaignError> {
+        let stats = compute_stats(user_id, history);
 
-        let draft = self.planner.propose(conn, &stats, &history, constraints)?;
+        let draft = self.planner.propose(&stats, history, constraints).await?;
 
         if draft.expected_completion_probability < constraints.min_completion_probability {
-            anyhow::bail!("mission rejected: expected completion probability too low")
+            return Err(CampaignError::Rejected(
+                "expected completion probability too low".to_string(),
+            ));
         }
         if draft.expected_hours > constraints.max_expected_hours {
-            anyhow::bail!("mission rejected: expected hours exceeds user capacity")
+            return Err(CampaignError::Rejected(
+                "expected hours exceeds user capacity".to_string(),
+            ));
         }
 
         let final_points =
@@ -340,29 +581,12 @@ fn validate_mission_draft_json(
     Ok(draft)
 }
 
-fn load_behaviours(conn: &Connection, user_id: &str) -> anyhow::Result<Vec<BehaviourRecord>> {
-    let mut stmt = conn.prepare(
-        "SELECT user_id, event_type, success, duration_minutes, created_at
-         FROM Behaviours
-         WHERE user_id = ?1
-         ORDER BY created_at DESC
-         LIMIT 200",
-    )?;
-
-    let rows = stmt.query_map(params![user_id], |row| {
-        Ok(BehaviourRecord {
-            user_id: row.get(0)?,
-            event_type: row.get(1)?,
-            success: row.get::<_, i64>(2)? == 1,
-            duration_minutes: row.get(3)?,
-            created_at: row.get(4)?,
-        })
-    })?;
-
-    Ok(rows.filter_map(Result::ok).collect())
+/// The user's recent behaviour records that missions are planned from
+pub fn load_history(conn: &Connection, user_id: &str) -> anyhow::Result<Vec<BehaviourRecord>> {
+    behaviours::list(conn, user_id, 200)
 }
 
-fn compute_stats(user_id: &str, history: &[BehaviourRecord]) -> UserBehaviourStats {
+pub fn compute_stats(user_id: &str, history: &[BehaviourRecord]) -> UserBehaviourStats {
     if history.is_empty() {
         return UserBehaviourStats {
             user_id: user_id.to_string(),
@@ -430,4 +654,75 @@ mod tests {
         let draft = validate_mission_draft_json(raw, &constraints).unwrap();
         assert_eq!(draft.recommended_points, 100);
     }
+
+    #[tokio::test]
+    async fn test_heuristic_planner() {
+        assert_eq!(
+            PlannerKind::parse(" Heuristic"),
+            Some(PlannerKind::Heuristic)
+        );
+        assert_eq!(PlannerKind::parse("gpt"), None);
+
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE Behaviours (user_id TEXT, event_type TEXT, success INTEGER,
+             duration_minutes INTEGER, created_at TEXT);",
+        )
+        .unwrap();
+        // 12 checkouts over 4 days, mostly successful, and one signup
+        for i in 0..12 {
+            conn.execute(
+                "INSERT INTO Behaviours VALUES ('u1', 'checkout', ?1, 10, ?2)",
+                params![i < 10, format!("2026-03-0{} 12:00:00", 1 + i % 5)],
+            )
+            .unwrap();
+        }
+        conn.execute(
+            "INSERT INTO Behaviours VALUES ('u1', 'signup', 1, 5, '2026-03-01 09:00:00')",
+            [],
+        )
+        .unwrap();
+
+        let constraints = CampaignConstraints {
+            max_points_per_mission: 50,
+            min_completion_probability: 0.35,
+            max_expected_hours: 3.0,
+        };
+        let engine = CampaignEngine::new(PlannerKind::Heuristic.planner(None).unwrap());
+        let history = load_history(&conn, "u1").unwrap();
+        let plan = engine
+            .generate_mission("u1", &history, &constraints)
+            .await
+            .unwrap();
+        assert_eq!(plan.rule.event_type, "checkout");
+        assert!(plan.title.starts_with("Stretch"));
+        // 3 a day over 48h, stretched by 1.25
+        assert_eq!(plan.rule.required_count, 8);
+        assert_eq!(plan.final_points, 50);
+
+        // A demanding probability eases the tier; short capacity caps the count
+        let strict = CampaignConstraints {
+            max_points_per_mission: 500,
+            min_completion_probability: 0.75,
+            max_expected_hours: 0.5,
+        };
+        let plan = engine
+            .generate_mission("u1", &history, &strict)
+            .await
+            .unwrap();
+        assert!(plan.title.starts_with("Warm-up"));
+        assert_eq!(plan.rule.required_count, 3);
+        assert!(!plan.clamped);
+
+        let err = engine
+            .generate_mission("nobody", &[], &constraints)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), "no_history");
+
+        // Without a key the LLM planner is unavailable rather than failing later
+        std::env::remove_var(crate::llm::API_KEY_ENV);
+        let err = PlannerKind::Llm.planner(None).err().unwrap();
+        assert_eq!(err.kind(), "planner_unavailable");
+    }
 }
@@ -0,0 +1,99 @@
+//! Canary tokens in block messages
+//!
+//! With `canary_tokens` enabled, the proxy puts a unique link into the text
+//! that replaces a blocked tool call. The link is useless to a well-behaved
+//! agent; one that later fetches it (or pastes it into a request) is acting
+//! on content it was told was blocked. The `canary_token_used` rule catches
+//! the token in any outbound HTTP action, and the analyzer looks the token up
+//! in the `canary_tokens` table to say which block it came from.
+
+use crate::rules::{Rule, RuleAction};
+use crate::{ActionType, RiskLevel};
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// Name of the rule that fires when a canary token shows up again
+pub const RULE_NAME: &str = "canary_token_used";
+
+const TOKEN_PREFIX: &str = "och-canary-";
+pub(crate) const TOKEN_PATTERN: &str = r"och-canary-[0-9a-f]{20}";
+
+fn token_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(TOKEN_PATTERN).unwrap())
+}
+
+/// A fresh token
+pub fn generate() -> String {
+    let hex = uuid::Uuid::new_v4().simple().to_string();
+    format!("{}{}", TOKEN_PREFIX, &hex[..20])
+}
+
+/// Link embedding `token`. The `.invalid` TLD never resolves, so following
+/// it reaches nobody.
+pub fn url(token: &str) -> String {
+    format!("https://canary.openclaw-harness.invalid/{}", token)
+}
+
+/// Canary tokens mentioned in `text`
+pub fn find_tokens(text: &str) -> Vec<String> {
+    token_regex()
+        .find_iter(text)
+        .map(|m| m.as_str().to_string())
+        .collect()
+}
+
+/// Critical rule matching a canary token in an outbound HTTP action
+pub fn rule() -> Rule {
+    let mut rule = Rule::new(
+        RULE_NAME,
+        "🐤 Agent acted on blocked content (canary token from a block message)",
+        TOKEN_PATTERN,
+        RiskLevel::Critical,
+        RuleAction::CriticalAlert,
+    );
+    rule.applies_to = vec![
+        ActionType::HttpRequest,
+        ActionType::BrowserAction,
+        ActionType::Exec,
+    ];
+    rule.protected = true;
+    rule
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AgentAction, AgentType};
+
+    fn action(action_type: ActionType, content: &str) -> AgentAction {
+        AgentAction {
+            id: "t".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type,
+            content: content.to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_canary_rule() {
+        let token = generate();
+        assert_ne!(token, generate());
+        let link = url(&token);
+        assert_eq!(
+            find_tokens(&format!("see {} now", link)),
+            vec![token.clone()]
+        );
+
+        let rule = rule();
+        assert!(rule.matches(&action(ActionType::HttpRequest, &link)));
+        assert!(rule.matches(&action(ActionType::Exec, &format!("curl -s '{}'", link))));
+        // Writing the message to a file isn't an outbound request
+        assert!(!rule.matches(&action(ActionType::FileWrite, &link)));
+        assert!(!rule.matches(&action(ActionType::Exec, "curl https://example.com")));
+    }
+}
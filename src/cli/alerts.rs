@@ -0,0 +1,47 @@
+//! Alerts command - verify alert channels, move credentials to the keyring
+
+use openclaw_harness::enforcer::alerter::Alerter;
+use openclaw_harness::enforcer::secrets::{self, KEYRING_ENV};
+use openclaw_harness::web::routes::{configured_alerts, migrate_alert_secrets};
+
+/// Send a test message through each configured channel; returns false if
+/// any delivery failed
+pub async fn test(channel: Option<&str>) -> anyhow::Result<bool> {
+    let alerter = Alerter::new(configured_alerts());
+    let results = alerter.test_channels(channel).await?;
+    let mut all_ok = true;
+    for result in &results {
+        match &result.error {
+            None => println!(
+                "✅ {}: delivered in {} ms",
+                result.channel, result.latency_ms
+            ),
+            Some(e) => {
+                all_ok = false;
+                println!(
+                    "❌ {}: failed after {} ms: {}",
+                    result.channel, result.latency_ms, e
+                );
+            }
+        }
+    }
+    Ok(all_ok)
+}
+
+/// Move credentials from the environment and the alert config file into
+/// the OS keyring
+pub fn migrate() -> anyhow::Result<()> {
+    let Some(store) = secrets::keyring() else {
+        anyhow::bail!(
+            "no OS keyring available (or {}=0); credentials stay in {}",
+            KEYRING_ENV,
+            secrets::alerts_path().display()
+        );
+    };
+    let vars = migrate_alert_secrets(store)?;
+    println!("🔐 Alert credentials are in the {}", store.name());
+    for var in vars {
+        println!("   {} was copied; you can unset it now", var);
+    }
+    Ok(())
+}
@@ -0,0 +1,72 @@
+//! Bench command - measure what the proxy adds to streaming requests
+
+use openclaw_harness::proxy::bench::{self, BenchOptions, TargetStats};
+use openclaw_harness::rules::{load_configured_rules, load_rules_from_file};
+use std::path::Path;
+
+pub async fn proxy(
+    requests: usize,
+    concurrency: usize,
+    rules_path: Option<&Path>,
+    json: bool,
+) -> anyhow::Result<()> {
+    let rules = match rules_path {
+        Some(path) => load_rules_from_file(path)?,
+        None => load_configured_rules(),
+    };
+    if !json {
+        println!(
+            "🏁 Benchmarking the proxy: {} streaming requests per target, {} at a time, {} rules",
+            requests,
+            concurrency,
+            rules.len()
+        );
+    }
+    let report = bench::run(BenchOptions {
+        requests,
+        concurrency,
+        rules,
+    })
+    .await?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "   Each response: {} SSE events, one tool call\n",
+        report.events_per_response
+    );
+    println!(
+        "{:<10} {:>9} {:>9} {:>9} {:>9} {:>10} {:>7}",
+        "", "p50", "p90", "p99", "max", "req/s", "errors"
+    );
+    for stats in [&report.direct, &report.monitor, &report.enforce] {
+        row(stats);
+    }
+    println!("\nAdded by the proxy:");
+    for overhead in &report.overhead {
+        println!(
+            "   {:<8} p50 {:+.2} ms, p90 {:+.2} ms, p99 {:+.2} ms, {:.0}% of direct throughput",
+            format!("{:?}", overhead.mode).to_lowercase(),
+            overhead.p50_ms,
+            overhead.p90_ms,
+            overhead.p99_ms,
+            overhead.throughput_ratio * 100.0
+        );
+    }
+    Ok(())
+}
+
+fn row(stats: &TargetStats) {
+    println!(
+        "{:<10} {:>6.2} ms {:>6.2} ms {:>6.2} ms {:>6.2} ms {:>10.0} {:>7}",
+        stats.target,
+        stats.p50_ms,
+        stats.p90_ms,
+        stats.p99_ms,
+        stats.max_ms,
+        stats.requests_per_sec,
+        stats.errors
+    );
+}
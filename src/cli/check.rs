@@ -0,0 +1,81 @@
+//! Check command - gate planned commands against the rule set (for CI)
+
+use openclaw_harness::analyzer::Analyzer;
+use openclaw_harness::policy::{self, PlannedAction};
+use openclaw_harness::rules::{load_configured_rules, load_rules_from_file};
+use std::io::Read;
+use std::path::PathBuf;
+
+pub struct CheckArgs {
+    pub commands_files: Vec<PathBuf>,
+    pub commands: Vec<String>,
+    pub fail_on: String,
+    pub rules: Option<PathBuf>,
+    pub json: bool,
+}
+
+/// Returns whether the check passed; the caller sets the exit code
+pub fn run(args: CheckArgs) -> anyhow::Result<bool> {
+    let fail_on = policy::parse_fail_on(&args.fail_on)?;
+    let rules = match &args.rules {
+        Some(path) => load_rules_from_file(path)?,
+        None => load_configured_rules(),
+    };
+    let analyzer = Analyzer::new(rules);
+
+    let mut actions: Vec<PlannedAction> = vec![];
+    for file in &args.commands_files {
+        let text = if file.as_os_str() == "-" {
+            let mut s = String::new();
+            std::io::stdin().read_to_string(&mut s)?;
+            s
+        } else {
+            std::fs::read_to_string(file)
+                .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", file.display(), e))?
+        };
+        actions.extend(policy::parse_actions(&text)?);
+    }
+    actions.extend(policy::parse_actions(&args.commands.join("\n"))?);
+    if actions.is_empty() {
+        anyhow::bail!("Nothing to check (use --commands-file or --command)");
+    }
+
+    let violations = policy::evaluate(&analyzer, &actions, fail_on);
+    if args.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "checked": actions.len(),
+                "fail_on": fail_on,
+                "passed": violations.is_empty(),
+                "violations": violations,
+            }))?
+        );
+        return Ok(violations.is_empty());
+    }
+
+    for v in &violations {
+        let emoji = match v.risk_level {
+            openclaw_harness::RiskLevel::Critical => "🚨",
+            openclaw_harness::RiskLevel::Warning => "⚠️ ",
+            openclaw_harness::RiskLevel::Info => "ℹ️ ",
+        };
+        println!("{} line {}: {}", emoji, v.line, v.content);
+        println!("   {} [{}]", v.matched_rules.join(", "), v.risk_level);
+    }
+    if violations.is_empty() {
+        println!(
+            "✅ {} action(s) checked, none at {} or above",
+            actions.len(),
+            fail_on
+        );
+    } else {
+        println!(
+            "❌ {} of {} action(s) at {} or above",
+            violations.len(),
+            actions.len(),
+            fail_on
+        );
+    }
+    Ok(violations.is_empty())
+}
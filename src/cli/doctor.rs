@@ -0,0 +1,72 @@
+//! Doctor command - harness-internal failures recorded by the daemon and proxy
+
+use chrono::{Duration, Utc};
+use openclaw_harness::db::Database;
+use openclaw_harness::diagnostics::{Diagnostic, DiagnosticCounts, Source};
+use openclaw_harness::{expand_home, Config};
+
+/// Returns whether nothing failed in the last day
+pub fn run() -> anyhow::Result<bool> {
+    println!("🩺 OpenClaw Harness Doctor");
+    println!("─────────────────");
+
+    let db_path = expand_home(&Config::default().db_path);
+    if !db_path.exists() {
+        println!("No database at {} yet.", db_path.display());
+        return Ok(true);
+    }
+    let entries = Database::open(&db_path)?.diagnostics()?;
+    if entries.is_empty() {
+        println!("✅ No internal failures recorded");
+        return Ok(true);
+    }
+
+    let counts = DiagnosticCounts::of(&entries);
+    println!(
+        "{} internal failure(s): {} panic, {} collector, {} alert, {} proxy",
+        counts.total, counts.panic, counts.collector, counts.alert, counts.proxy
+    );
+    for source in [
+        Source::Panic,
+        Source::Collector,
+        Source::Alert,
+        Source::Proxy,
+    ] {
+        let of_source: Vec<&Diagnostic> = entries.iter().filter(|d| d.source == source).collect();
+        if of_source.is_empty() {
+            continue;
+        }
+        println!("\n{}", heading(source));
+        for d in of_source {
+            println!(
+                "   {:<28} {:>6}×  last {}",
+                d.component,
+                d.count,
+                d.last_seen.format("%Y-%m-%d %H:%M:%S UTC")
+            );
+            println!("      {}", d.last_message);
+        }
+    }
+
+    let recent = counts
+        .last_seen
+        .is_some_and(|t| t > Utc::now() - Duration::hours(24));
+    if recent {
+        println!(
+            "\n⚠️  Failures in the last 24 hours; `openclaw-harness logs --self` has the details"
+        );
+    } else {
+        println!("\n✅ Nothing failed in the last 24 hours");
+    }
+    Ok(!recent)
+}
+
+/// What failures from `source` mean
+fn heading(source: Source) -> &'static str {
+    match source {
+        Source::Panic => "💥 Panics (a harness task crashed; please report these):",
+        Source::Collector => "📡 Collector parse errors (the agent's log format may have changed):",
+        Source::Alert => "🔔 Alert delivery failures (check with `openclaw-harness alerts test`):",
+        Source::Proxy => "🔀 Proxy upstream errors (API calls that failed or broke off):",
+    }
+}
@@ -0,0 +1,40 @@
+//! Export command - dump actions and analysis for offline analysis
+
+use openclaw_harness::db::Database;
+use openclaw_harness::export::{self, ExportFormat};
+use openclaw_harness::{expand_home, Config};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+pub fn run(since: Option<&str>, format: Option<&str>, out: Option<&str>) -> anyhow::Result<()> {
+    let format = match (format, out) {
+        (Some(f), _) => f.parse()?,
+        (None, Some(path)) => {
+            ExportFormat::from_path(Path::new(path)).unwrap_or(ExportFormat::Jsonl)
+        }
+        (None, None) => ExportFormat::Jsonl,
+    };
+    let since = since.map(export::parse_since).transpose()?;
+
+    let db_path = expand_home(&Config::default().db_path);
+    if !db_path.exists() {
+        anyhow::bail!("No database at {}", db_path.display());
+    }
+    let db = Database::open(&db_path)?;
+
+    match out {
+        Some(path) => {
+            let mut writer = BufWriter::new(File::create(path)?);
+            let count = export::export(&db, since, format, &mut writer)?;
+            writer.flush()?;
+            println!("📤 Exported {} action(s) to {}", count, path);
+        }
+        None => {
+            let mut writer = BufWriter::new(std::io::stdout().lock());
+            export::export(&db, since, format, &mut writer)?;
+            writer.flush()?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! Githook command - run commits and pushes through the rule engine
+//!
+//! `githook install` writes pre-commit and pre-push hooks that call back into
+//! `githook run`, which analyzes the staged diff (or the commits being
+//! pushed) and rejects them when a rule matches at the `--fail-on` level.
+
+use openclaw_harness::analyzer::Analyzer;
+use openclaw_harness::policy;
+use openclaw_harness::rules::load_configured_rules;
+use openclaw_harness::RiskLevel;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Marks hooks written by this command
+const MARKER: &str = "# Installed by openclaw-harness githook";
+const HOOKS: [&str; 2] = ["pre-commit", "pre-push"];
+/// Object name git uses for a missing side of a ref update
+const ZERO_SHA: &str = "0000000000000000000000000000000000000000";
+
+fn git(args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git").args(args).output()?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn hooks_dir() -> anyhow::Result<PathBuf> {
+    Ok(PathBuf::from(
+        git(&["rev-parse", "--git-path", "hooks"])?.trim(),
+    ))
+}
+
+fn is_ours(path: &Path) -> bool {
+    std::fs::read_to_string(path).is_ok_and(|s| s.contains(MARKER))
+}
+
+pub fn install(fail_on: &str, force: bool) -> anyhow::Result<()> {
+    policy::parse_fail_on(fail_on)?;
+    let dir = hooks_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let exe = std::env::current_exe()?;
+
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        if path.exists() && !is_ours(&path) {
+            if !force {
+                anyhow::bail!(
+                    "{} already exists (use --force to replace it; the old hook is kept as {}.bak)",
+                    path.display(),
+                    hook
+                );
+            }
+            std::fs::rename(&path, dir.join(format!("{}.bak", hook)))?;
+        }
+        let script = format!(
+            "#!/bin/sh\n{}\nexec \"{}\" githook run {} --fail-on {} \"$@\"\n",
+            MARKER,
+            exe.display(),
+            hook,
+            fail_on
+        );
+        std::fs::write(&path, script)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        println!("🪝 Installed {}", path.display());
+    }
+    Ok(())
+}
+
+pub fn uninstall() -> anyhow::Result<()> {
+    let dir = hooks_dir()?;
+    for hook in HOOKS {
+        let path = dir.join(hook);
+        if !is_ours(&path) {
+            continue;
+        }
+        std::fs::remove_file(&path)?;
+        let backup = dir.join(format!("{}.bak", hook));
+        if backup.exists() {
+            std::fs::rename(&backup, &path)?;
+            println!("🧹 Removed {} (restored the previous hook)", path.display());
+        } else {
+            println!("🧹 Removed {}", path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Diff text for the commits a push would send, from pre-push's stdin
+fn pushed_diff() -> anyhow::Result<String> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let mut diff = String::new();
+    for line in input.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let [_, local_sha, _, remote_sha] = fields[..] else {
+            continue;
+        };
+        if local_sha == ZERO_SHA {
+            // Deleting a remote branch sends no content
+            continue;
+        }
+        let range = if remote_sha == ZERO_SHA {
+            // New branch: commits not on any remote yet
+            git(&["rev-list", local_sha, "--not", "--remotes"])?
+        } else {
+            git(&["rev-list", &format!("{}..{}", remote_sha, local_sha)])?
+        };
+        for commit in range.lines() {
+            diff.push_str(&git(&[
+                "diff-tree",
+                "-p",
+                "-U0",
+                "-r",
+                "--root",
+                "--no-commit-id",
+                commit,
+            ])?);
+        }
+    }
+    Ok(diff)
+}
+
+/// Run as a hook; returns whether the commit/push may go ahead
+pub fn run(hook: &str, fail_on: &str) -> anyhow::Result<bool> {
+    let fail_on = policy::parse_fail_on(fail_on)?;
+    let diff = match hook {
+        "pre-commit" => git(&["diff", "--cached", "-U0", "--no-color"])?,
+        "pre-push" => pushed_diff()?,
+        other => anyhow::bail!("Unknown hook '{}' (pre-commit, pre-push)", other),
+    };
+    let root = PathBuf::from(git(&["rev-parse", "--show-toplevel"])?.trim());
+
+    let actions = policy::diff_actions(&diff, &root);
+    let analyzer = Analyzer::new(load_configured_rules());
+    let violations = policy::evaluate(&analyzer, &actions, fail_on);
+    if violations.is_empty() {
+        return Ok(true);
+    }
+
+    eprintln!(
+        "🛡️ OpenClaw Harness rejected this {}:",
+        hook.trim_start_matches("pre-")
+    );
+    for v in &violations {
+        let path = actions
+            .iter()
+            .find(|a| a.line == v.line)
+            .and_then(|a| a.action.target.as_deref())
+            .unwrap_or_default();
+        let emoji = if v.risk_level == RiskLevel::Critical {
+            "🚨"
+        } else {
+            "⚠️ "
+        };
+        eprintln!("{} {}: {}", emoji, path, v.matched_rules.join(", "));
+    }
+    eprintln!("   Bypass with --no-verify only if you are sure.");
+    Ok(false)
+}
@@ -0,0 +1,153 @@
+//! Hook-exec command - PreToolUse hook entry point for Claude Code
+//!
+//! Claude Code pipes each tool call as JSON on stdin. Exit code 2 blocks the
+//! call and feeds stderr back to the model; exit 0 lets it run.
+//!
+//! A PauseAndAsk call is held for approval: it is filed in the database
+//! (the Slack app posts it, a foreground daemon asks in its terminal) and
+//! lets through once someone approves it.
+//!
+//! The hook fails closed. Rules come only from the harness's own
+//! `rules.yaml` (or the built-in rules), never from the project the agent is
+//! working in, and unreadable input or rules block the call instead of
+//! letting it run.
+
+use openclaw_harness::db::DbPool;
+use openclaw_harness::enforcer::backup::{backup_approved_writes, BackupStore};
+use openclaw_harness::proxy::attribution::Attribution;
+use openclaw_harness::proxy::interceptor::{check_tool_use, InterceptResult};
+use openclaw_harness::rules::{fallback_rules, load_rules_from_file, Rule, RuleAction};
+use openclaw_harness::slack::APPROVAL_VALID_MINUTES;
+use openclaw_harness::{expand_home, harness_path, AgentType, Config, RiskLevel};
+use serde::Deserialize;
+use std::io::Read;
+
+#[derive(Debug, Deserialize)]
+struct HookInput {
+    tool_name: String,
+    #[serde(default)]
+    tool_input: serde_json::Value,
+    /// "PreToolUse" (Claude Code) or "BeforeTool" (Gemini CLI)
+    #[serde(default)]
+    hook_event_name: Option<String>,
+    #[serde(default)]
+    session_id: Option<String>,
+}
+
+/// Block the call: exit code 2, with `message` fed back to the model.
+/// Any other failure exit would count as "allow".
+fn refuse(message: std::fmt::Arguments) -> ! {
+    eprintln!("🛑 OpenClaw Harness: {}", message);
+    std::process::exit(2);
+}
+
+/// The harness's `rules.yaml`, else the built-in rules
+fn hook_rules() -> anyhow::Result<Vec<Rule>> {
+    let path = harness_path("rules.yaml");
+    let mut rules = if path.exists() {
+        load_rules_from_file(&path)?
+    } else {
+        fallback_rules()
+    };
+    for rule in &mut rules {
+        rule.compile()?;
+    }
+    Ok(rules)
+}
+
+pub async fn run() -> anyhow::Result<()> {
+    let mut raw = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut raw) {
+        refuse(format_args!("cannot read the hook input ({})", e));
+    }
+    let input: HookInput = match serde_json::from_str(&raw) {
+        Ok(i) => i,
+        Err(e) => refuse(format_args!("invalid hook input ({})", e)),
+    };
+
+    let rules = match hook_rules() {
+        Ok(rules) => rules,
+        Err(e) => refuse(format_args!("cannot load rules ({:#})", e)),
+    };
+
+    let source = Attribution {
+        agent: match input.hook_event_name.as_deref() {
+            Some("BeforeTool") => AgentType::GeminiCli,
+            _ => AgentType::ClaudeCode,
+        },
+        session_id: input.session_id,
+    };
+
+    if let Some(hit) = check_tool_use(0, &input.tool_name, &input.tool_input, &rules, &source) {
+        let approval = if hit.action == RuleAction::PauseAndAsk {
+            match approval(&hit) {
+                Ok(Approval::Granted) => {
+                    eprintln!(
+                        "✅ OpenClaw Harness: [{}] approved (rule: {})",
+                        hit.tool_name, hit.rule_name
+                    );
+                    // Keep the file as it was, so the approval can be undone
+                    backup_approved_writes(&BackupStore::default(), std::slice::from_ref(&hit));
+                    return Ok(());
+                }
+                Ok(Approval::Requested(id)) => Some(id),
+                Err(e) => {
+                    eprintln!("openclaw-harness hook-exec: approvals unavailable: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let icon = if hit.risk_level == RiskLevel::Critical {
+            "🚨"
+        } else {
+            "⚠️"
+        };
+        eprintln!(
+            "{} Blocked by OpenClaw Harness: [{}] {} (rule: {})",
+            icon, hit.tool_name, hit.reason, hit.rule_name
+        );
+        if let Some(id) = approval {
+            eprintln!(
+                "This needs approval (id {}). Ask the user to approve it, then retry.",
+                id
+            );
+        }
+        std::process::exit(2);
+    }
+
+    Ok(())
+}
+
+enum Approval {
+    Granted,
+    Requested(String),
+}
+
+/// Use up a recent approval of this exact call, or file a request for one
+fn approval(hit: &InterceptResult) -> anyhow::Result<Approval> {
+    // Same WAL and busy timeout as the daemon, so a busy database waits
+    // instead of failing the request
+    let db = DbPool::with_size(&expand_home(&Config::default().db_path), 1)?.get()?;
+    let fingerprint = hit.fingerprint();
+    if db.take_approval(
+        &fingerprint,
+        chrono::Duration::minutes(APPROVAL_VALID_MINUTES),
+    )? {
+        return Ok(Approval::Granted);
+    }
+    let mut summary = format!("{}: {}", hit.tool_name, hit.content);
+    if summary.len() > 200 {
+        let mut cut = 200;
+        while !summary.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        summary.truncate(cut);
+        summary.push('…');
+    }
+    let summary = format!("{} (rule: {})", summary, hit.rule_name);
+    Ok(Approval::Requested(
+        db.request_approval(&fingerprint, &summary)?,
+    ))
+}
@@ -0,0 +1,87 @@
+//! Import command - backfill the database from historical agent logs
+
+use openclaw_harness::analyzer::Analyzer;
+use openclaw_harness::blob::BlobStore;
+use openclaw_harness::collectors::collector_by_name;
+use openclaw_harness::correlation::{self, Correlation};
+use openclaw_harness::db::DbPool;
+use openclaw_harness::rules::{fallback_rules, load_rules_from_file};
+use openclaw_harness::{expand_home, Config, RiskLevel};
+use std::fs;
+
+pub fn run(agent: &str, path: Option<&str>, dry_run: bool) -> anyhow::Result<()> {
+    let Some(collector) = collector_by_name(agent) else {
+        anyhow::bail!(
+            "Unknown agent '{}' (expected openclaw, claude_code, gemini_cli, copilot_cli, aider or openhands)",
+            agent
+        );
+    };
+    let path = path.map(expand_home);
+
+    println!(
+        "📥 Importing {} history from {}",
+        collector.name(),
+        path.as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "default location".to_string())
+    );
+
+    let mut actions = collector.import(path.as_deref())?;
+    actions.sort_by_key(|a| a.timestamp);
+    println!("   Found {} action(s)", actions.len());
+
+    let config_path = std::path::Path::new("config/rules.yaml");
+    let rules = if config_path.exists() {
+        load_rules_from_file(config_path).unwrap_or_else(|_| fallback_rules())
+    } else {
+        fallback_rules()
+    };
+
+    let db_path = expand_home(&Config::default().db_path);
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let pool = DbPool::open(&db_path)?;
+    let db = pool.get()?;
+    let analyzer = Analyzer::new(rules).with_canary_db(pool.clone());
+    let window = correlation::window();
+    let blobs = BlobStore::for_db(&db_path);
+
+    let (mut imported, mut duplicates, mut flagged) = (0, 0, 0);
+    for action in &actions {
+        // Already imported or seen live by the collector
+        if db.find_replay(action)?.is_some() {
+            duplicates += 1;
+            continue;
+        }
+        let result = analyzer.analyze(action);
+        if result.risk_level != RiskLevel::Info {
+            flagged += 1;
+        }
+        if dry_run {
+            imported += 1;
+            continue;
+        }
+        match correlation::record(&db, &blobs.offload(action), window)? {
+            Correlation::New => {
+                db.store_analysis(&result)?;
+                imported += 1;
+            }
+            // Seen by the proxy at the time
+            Correlation::Duplicate { .. } | Correlation::Replay { .. } => duplicates += 1,
+        }
+    }
+
+    if dry_run {
+        println!(
+            "🔍 Dry run: would import {} action(s) ({} already stored, {} flagged by rules)",
+            imported, duplicates, flagged
+        );
+    } else {
+        println!(
+            "✅ Imported {} action(s) ({} duplicate(s) skipped, {} flagged by rules)",
+            imported, duplicates, flagged
+        );
+    }
+    Ok(())
+}
@@ -1,5 +1,8 @@
 //! Logs command - view recent activity
 
+use openclaw_harness::logfile;
+use tracing::Level;
+
 pub async fn run(
     tail: usize,
     _agent: Option<String>,
@@ -15,3 +18,34 @@ pub async fn run(
 
     Ok(())
 }
+
+/// `logs --self`: the daemon's own log, from its log files
+pub fn harness(tail: usize, level: Option<&str>) -> anyhow::Result<()> {
+    let level = level.map(parse_level).transpose()?;
+    let dir = logfile::log_dir();
+    let lines = logfile::tail(&dir, tail, level)?;
+
+    println!("📝 Harness log (last {} lines, {})", tail, dir.display());
+    println!("─────────────────────────────────────");
+    if lines.is_empty() {
+        println!("\nNothing logged yet. The daemon writes here once started.");
+    }
+    for line in lines {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// Log level named by `--level`; risk level names are accepted too
+fn parse_level(name: &str) -> anyhow::Result<Level> {
+    match name.trim().to_lowercase().as_str() {
+        "critical" => Ok(Level::ERROR),
+        "warning" => Ok(Level::WARN),
+        other => other.parse().map_err(|_| {
+            anyhow::anyhow!(
+                "Unknown log level: {} (error, warn, info, debug, trace)",
+                name
+            )
+        }),
+    }
+}
@@ -1,11 +1,25 @@
 //! CLI command handlers
 
+pub mod alerts;
+pub mod bench;
+pub mod check;
+pub mod doctor;
+pub mod export;
+pub mod githook;
+pub mod hook_exec;
+pub mod import;
 pub mod logs;
 pub mod patch;
 pub mod proxy;
+pub mod purge;
+pub mod replay;
+pub mod rollback;
 pub mod rules;
 pub mod start;
 pub mod status;
 pub mod stop;
 pub mod test;
+pub mod tray;
+pub mod tripwire;
 pub mod tui;
+pub mod verify_log;
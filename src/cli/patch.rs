@@ -3,20 +3,28 @@
 use anyhow::{bail, Result};
 use tracing::info;
 
-use crate::patcher::clawdbot;
+use crate::patcher::{claude_code, clawdbot, gemini_cli};
 
 #[derive(Debug, Clone, Copy)]
 pub enum PatchMode {
     Apply,
     Revert,
     Check,
+    /// Report which anchors match, without modifying anything
+    Probe,
 }
 
 pub async fn run(target: &str, mode: PatchMode) -> Result<()> {
     match target {
         "openclaw" | "clawdbot" => run_openclaw(mode),
+        "claude-code" | "claude_code" | "claude" => run_claude_code(mode),
+        "gemini-cli" | "gemini_cli" | "gemini" => run_gemini_cli(mode),
+        "copilot-cli" | "copilot_cli" | "copilot" => bail!(
+            "Copilot CLI has no hook point to patch. It is monitored by the \
+             copilot_cli collector; for blocking use: openclaw-harness proxy start --preset copilot-cli"
+        ),
         _ => bail!(
-            "Unknown patch target: '{}'. Supported: openclaw (or clawdbot)",
+            "Unknown patch target: '{}'. Supported: openclaw (or clawdbot), claude-code, gemini-cli",
             target
         ),
     }
@@ -51,6 +59,38 @@ fn run_openclaw(mode: PatchMode) -> Result<()> {
                 println!("   Run: openclaw-harness patch openclaw");
             }
         }
+        PatchMode::Probe => {
+            let version = clawdbot::detect_clawdbot_version();
+            match version {
+                Some(ref v) if clawdbot::version_in_registry(v) => {
+                    println!("📌 OpenClaw version: {} (in anchor registry)", v)
+                }
+                Some(ref v) => println!("📌 OpenClaw version: {} (unknown to anchor registry)", v),
+                None => println!("📌 OpenClaw version: unknown"),
+            }
+            if clawdbot::has_builtin_before_tool_call(&dist)? {
+                println!("✅ Built-in before_tool_call hooks present (no patch needed)");
+            }
+            for result in clawdbot::probe(&dist, version.as_deref()) {
+                println!("\n📄 {}", result.file.display());
+                if !result.exists {
+                    println!("   ❌ file not found");
+                    continue;
+                }
+                if result.already_patched {
+                    println!("   ✅ already patched");
+                }
+                match result.anchor {
+                    Some((id, clawdbot::MatchKind::Exact)) => {
+                        println!("   ✅ anchor '{}' matched exactly", id)
+                    }
+                    Some((id, clawdbot::MatchKind::Fuzzy)) => {
+                        println!("   ⚠️  anchor '{}' matched ignoring whitespace", id)
+                    }
+                    None => println!("   ❌ no known anchor matched"),
+                }
+            }
+        }
         PatchMode::Apply => {
             println!("🔧 Applying before_tool_call hook patches...");
             clawdbot::apply_patch(&dist)?;
@@ -65,3 +105,57 @@ fn run_openclaw(mode: PatchMode) -> Result<()> {
 
     Ok(())
 }
+
+fn run_claude_code(mode: PatchMode) -> Result<()> {
+    let settings = claude_code::settings_file()?;
+    println!("📍 Claude Code settings: {}", settings.display());
+
+    match mode {
+        PatchMode::Check | PatchMode::Probe => {
+            if claude_code::is_patched(&settings)? {
+                println!("✅ Claude Code PreToolUse hook is registered");
+            } else {
+                println!("❌ Claude Code is NOT patched (no PreToolUse hook)");
+                println!("   Run: openclaw-harness patch claude-code");
+            }
+        }
+        PatchMode::Apply => {
+            println!("🔧 Registering PreToolUse hook...");
+            claude_code::apply_patch(&settings, &claude_code::hook_command())?;
+            println!("\n🎉 Hook active for new Claude Code sessions.");
+        }
+        PatchMode::Revert => {
+            println!("↩️  Removing PreToolUse hook...");
+            claude_code::revert_patch(&settings)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_gemini_cli(mode: PatchMode) -> Result<()> {
+    let settings = gemini_cli::settings_file()?;
+    println!("📍 Gemini CLI settings: {}", settings.display());
+
+    match mode {
+        PatchMode::Check | PatchMode::Probe => {
+            if gemini_cli::is_patched(&settings)? {
+                println!("✅ Gemini CLI BeforeTool hook is registered");
+            } else {
+                println!("❌ Gemini CLI is NOT patched (no BeforeTool hook)");
+                println!("   Run: openclaw-harness patch gemini-cli");
+            }
+        }
+        PatchMode::Apply => {
+            println!("🔧 Registering BeforeTool hook...");
+            gemini_cli::apply_patch(&settings, &gemini_cli::hook_command())?;
+            println!("\n🎉 Hook active for new Gemini CLI sessions.");
+        }
+        PatchMode::Revert => {
+            println!("↩️  Removing BeforeTool hook...");
+            gemini_cli::revert_patch(&settings)?;
+        }
+    }
+
+    Ok(())
+}
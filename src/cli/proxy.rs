@@ -1,20 +1,80 @@
 //! CLI handler for the proxy subcommand
 
-use openclaw_harness::proxy::config::{ProxyConfig, ProxyMode};
-use openclaw_harness::proxy::start_proxy;
+use openclaw_harness::diagnostics;
+use openclaw_harness::enforcer::secrets;
+use openclaw_harness::listen;
+use openclaw_harness::proxy::capture::CaptureStore;
+use openclaw_harness::proxy::config::{preset_target, ListenerConfig, ProxyConfig, ProxyMode};
+use openclaw_harness::proxy::{admin, start_proxy};
+use openclaw_harness::{expand_home, Config};
 use openclaw_harness::{AlertConfig, TelegramConfig};
 use tracing::info;
 
+/// Upstream handling options for `proxy start`
+pub struct ProxyOptions {
+    pub fallbacks: Vec<String>,
+    pub health_interval: Option<u64>,
+    pub cache_ttl: Option<u64>,
+    pub canary_tokens: bool,
+    pub coaching: bool,
+    pub capture: bool,
+    pub scan_thinking: bool,
+    pub telemetry: bool,
+    /// `--listener` specs (see `ListenerConfig::parse`)
+    pub listeners: Vec<String>,
+    /// `--listen` address
+    pub listen: Option<String>,
+    /// Allow non-loopback addresses
+    pub expose: bool,
+}
+
 pub async fn start(
     port: Option<u16>,
     target: Option<String>,
     mode: Option<String>,
+    preset: Option<String>,
+    agent: Option<String>,
+    options: ProxyOptions,
 ) -> anyhow::Result<()> {
-    let mut config = ProxyConfig::default();
+    let mut config = ProxyConfig {
+        agent,
+        fallback_targets: options.fallbacks,
+        canary_tokens: options.canary_tokens,
+        coaching: options.coaching,
+        capture: options.capture,
+        scan_thinking: options.scan_thinking,
+        telemetry: options.telemetry,
+        listeners: options
+            .listeners
+            .iter()
+            .map(|spec| ListenerConfig::parse(spec))
+            .collect::<anyhow::Result<_>>()?,
+        ..Default::default()
+    };
+    if let Some(secs) = options.health_interval {
+        config.health_check_interval_secs = secs;
+    }
+    if let Some(secs) = options.cache_ttl {
+        config.cache_ttl_secs = secs;
+    }
+
+    if let Some(name) = preset {
+        match preset_target(&name) {
+            Some(t) => config.target = t.to_string(),
+            None => anyhow::bail!(
+                "Unknown preset '{}' (available: anthropic, openai, gemini-cli, copilot-cli)",
+                name
+            ),
+        }
+    }
 
     if let Some(p) = port {
         config.listen = format!("127.0.0.1:{}", p);
     }
+    if let Some(addr) = options.listen {
+        config.listen = listen::normalize(&addr);
+    }
+    config.expose |= options.expose;
     if let Some(t) = target {
         config.target = t;
     }
@@ -29,12 +89,12 @@ pub async fn start(
         };
     }
 
-    // Try to load Telegram config from environment
+    // Try to load Telegram config from environment (or the OS keyring)
     let alert_config = match (
-        std::env::var("OPENCLAW_HARNESS_TELEGRAM_BOT_TOKEN"),
+        secrets::lookup(secrets::keyring(), secrets::TELEGRAM_BOT_TOKEN),
         std::env::var("OPENCLAW_HARNESS_TELEGRAM_CHAT_ID"),
     ) {
-        (Ok(token), Ok(chat_id)) => {
+        (Some(token), Ok(chat_id)) => {
             info!("Telegram alerts enabled");
             Some(AlertConfig {
                 telegram: Some(TelegramConfig {
@@ -43,6 +103,8 @@ pub async fn start(
                 }),
                 slack: None,
                 discord: None,
+                desktop: None,
+                quiet_hours: Default::default(),
             })
         }
         _ => {
@@ -51,17 +113,157 @@ pub async fn start(
         }
     };
 
+    diagnostics::install_panic_hook(expand_home(&Config::default().db_path));
     start_proxy(config, alert_config).await
 }
 
-pub async fn status() -> anyhow::Result<()> {
-    // Simple status check — try to connect to the proxy port
-    let client = reqwest::Client::new();
-    match client.get("http://127.0.0.1:9090/health").send().await {
-        Ok(_) => println!("✅ OpenClaw Harness proxy is running on 127.0.0.1:9090"),
-        Err(_) => {
-            println!("❌ OpenClaw Harness proxy is not running (or not on default port 9090)")
+pub async fn status(addr: Option<String>) -> anyhow::Result<()> {
+    let addr = addr.unwrap_or_else(|| listen::connect_addr(&ProxyConfig::default().listen));
+    let Some(status) = admin::fetch(&addr).await? else {
+        println!("❌ No OpenClaw Harness proxy running on {}", addr);
+        return Ok(());
+    };
+
+    println!(
+        "✅ OpenClaw Harness proxy v{} running on {}",
+        status.version, status.listen
+    );
+    println!("   Mode:    {:?}", status.mode);
+    println!(
+        "   Uptime:  {} (since {})",
+        format_uptime(status.uptime_secs),
+        status.started_at
+    );
+    println!("   Target:  {}", status.active_target);
+    for upstream in status.upstreams.iter().filter(|u| !u.healthy) {
+        println!(
+            "   ⚠️  {} unhealthy: {}",
+            upstream.target,
+            upstream.last_error.as_deref().unwrap_or("no response")
+        );
+    }
+
+    let counters = &status.counters;
+    println!(
+        "\n📊 Requests: {} ({} passed, {} warned, {} blocked)",
+        counters.total_requests,
+        counters.passed_count,
+        counters.warning_count,
+        counters.blocked_count
+    );
+    let mut providers: Vec<_> = counters.by_provider.iter().collect();
+    providers.sort_by(|a, b| b.1.cmp(a.1));
+    for (provider, count) in providers {
+        println!("   {:<12} {}", provider, count);
+    }
+
+    if status.recent_intercepts.is_empty() {
+        println!("\n🟢 No intercepts yet");
+    } else {
+        println!("\n🚨 Recent intercepts:");
+        for i in &status.recent_intercepts {
+            println!(
+                "   {} {} {} [{}] {}",
+                if i.blocked { "⛔" } else { "👁" },
+                i.timestamp,
+                i.tool_name,
+                i.risk_level,
+                i.rule_name
+            );
         }
     }
     Ok(())
 }
+
+fn format_uptime(secs: u64) -> String {
+    match secs {
+        s if s < 60 => format!("{}s", s),
+        s if s < 3600 => format!("{}m {}s", s / 60, s % 60),
+        s if s < 86400 => format!("{}h {}m", s / 3600, s % 3600 / 60),
+        s => format!("{}d {}h", s / 86400, s % 86400 / 3600),
+    }
+}
+
+fn capture_store() -> CaptureStore {
+    CaptureStore::for_db(&expand_home(&Config::default().db_path))
+}
+
+pub fn capture_list(limit: usize) -> anyhow::Result<()> {
+    let store = capture_store();
+    let ids = store.ids();
+    if ids.is_empty() {
+        println!("📭 No captures (start the proxy with --capture)");
+        return Ok(());
+    }
+    println!("📼 {} capture(s), newest first:", ids.len());
+    for id in ids.iter().take(limit) {
+        let Ok(capture) = store.get(id) else { continue };
+        let rules: Vec<&str> = capture
+            .intercepts
+            .iter()
+            .map(|i| i.rule_name.as_str())
+            .collect();
+        println!(
+            "   {}  {} {} → {}  {}",
+            capture.id,
+            capture.method,
+            capture.path,
+            capture.status,
+            if rules.is_empty() {
+                "no intercepts".to_string()
+            } else {
+                rules.join(", ")
+            }
+        );
+    }
+    Ok(())
+}
+
+pub fn capture_show(id: &str) -> anyhow::Result<()> {
+    let capture = capture_store().get(id)?;
+    println!("📼 Capture {}", capture.id);
+    println!("   Time:   {}", capture.captured_at);
+    println!(
+        "   Call:   {} {}{} ({:?} mode)",
+        capture.method, capture.target, capture.path, capture.mode
+    );
+    println!("   Status: {}", capture.status);
+    if capture.truncated {
+        println!("   ⚠️  Bodies over 1 MiB were truncated");
+    }
+
+    println!("\n📨 Request headers:");
+    for (name, value) in &capture.request_headers {
+        println!("   {}: {}", name, value);
+    }
+    println!("\n📨 Request body:\n{}", pretty_body(&capture.request_body));
+    println!(
+        "\n📬 Upstream response:\n{}",
+        pretty_body(&capture.response_body)
+    );
+
+    if capture.intercepts.is_empty() {
+        println!("\n🟢 No rule matched");
+    } else {
+        println!("\n🚨 Intercepts:");
+        for i in &capture.intercepts {
+            println!(
+                "   {} {} [{}] {} — {}",
+                if i.blocked { "⛔" } else { "👁" },
+                i.tool_name,
+                i.risk_level,
+                i.rule_name,
+                i.reason
+            );
+        }
+    }
+    Ok(())
+}
+
+/// JSON bodies pretty-printed; anything else (e.g. SSE text) as is
+fn pretty_body(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or_else(|| body.to_string())
+}
@@ -0,0 +1,66 @@
+//! Purge command - delete or anonymize stored data for compliance requests
+
+use openclaw_harness::db::DbPool;
+use openclaw_harness::export::parse_since;
+use openclaw_harness::privacy::{self, PurgeMode, PurgeScope};
+use openclaw_harness::{brain, expand_home, Config};
+
+pub struct PurgeArgs {
+    pub session: Option<String>,
+    pub path_prefix: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub anonymize: bool,
+    pub dry_run: bool,
+}
+
+pub fn run(args: PurgeArgs) -> anyhow::Result<()> {
+    let scope = PurgeScope {
+        session_id: args.session,
+        path_prefix: args
+            .path_prefix
+            .map(|p| expand_home(&p).to_string_lossy().to_string()),
+        from: args.from.as_deref().map(parse_since).transpose()?,
+        to: args.to.as_deref().map(parse_since).transpose()?,
+    };
+    let mode = if args.anonymize {
+        PurgeMode::Anonymize
+    } else {
+        PurgeMode::Delete
+    };
+
+    let db_path = expand_home(&Config::default().db_path);
+    if !db_path.exists() {
+        anyhow::bail!("No database at {}", db_path.display());
+    }
+    let user = std::env::var("USER").unwrap_or_else(|_| "cli".to_string());
+    let summary = privacy::purge(
+        &DbPool::open(&db_path)?,
+        &brain::data_dir(),
+        &scope,
+        mode,
+        args.dry_run,
+        &format!("cli:{}", user),
+    )?;
+
+    let verb = match (args.dry_run, mode) {
+        (true, PurgeMode::Delete) => "Would delete",
+        (true, PurgeMode::Anonymize) => "Would anonymize",
+        (false, PurgeMode::Delete) => "🗑️  Deleted",
+        (false, PurgeMode::Anonymize) => "🕶️  Anonymized",
+    };
+    println!(
+        "{} {} action(s), {} weekly report(s) and {} decision note(s)",
+        verb, summary.actions, summary.reports, summary.notes
+    );
+    if !summary.ontology_rebuilt.is_empty() {
+        println!(
+            "   Rebuilt ontology: {}",
+            summary.ontology_rebuilt.join(", ")
+        );
+    }
+    if let Some(id) = summary.audit_id {
+        println!("   Audit record #{}", id);
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Replay command - run the simulation corpus through the interceptor
+
+use openclaw_harness::proxy::corpus;
+use openclaw_harness::rules::{load_configured_rules, load_rules_from_file};
+use std::path::Path;
+
+/// Replay every fixture under `corpus_path`; returns false when any
+/// intercept decision differs from the expected one
+pub fn run(corpus_path: &Path, rules_path: Option<&Path>, json: bool) -> anyhow::Result<bool> {
+    let rules = match rules_path {
+        Some(path) => load_rules_from_file(path)?,
+        None => load_configured_rules(),
+    };
+    let fixtures = corpus::load(corpus_path)?;
+    if fixtures.is_empty() {
+        anyhow::bail!("No fixtures found in {}", corpus_path.display());
+    }
+
+    // The interceptor logs each match as the proxy would; only the report
+    // is wanted here
+    let results: Vec<corpus::ReplayResult> =
+        tracing::subscriber::with_default(tracing::subscriber::NoSubscriber::default(), || {
+            fixtures
+                .iter()
+                .map(|(file, fixture)| corpus::replay(file, fixture, &rules))
+                .collect()
+        });
+    let failed = results.iter().filter(|r| !r.failures.is_empty()).count();
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "replayed": results.len(),
+                "failed": failed,
+                "results": results,
+            }))?
+        );
+        return Ok(failed == 0);
+    }
+
+    println!(
+        "🎬 Replaying {} fixture(s) from {}",
+        results.len(),
+        corpus_path.display()
+    );
+    for result in &results {
+        if result.failures.is_empty() {
+            println!("   ✅ {}", result.name);
+        } else {
+            println!("   ❌ {} ({})", result.name, result.file.display());
+            for failure in &result.failures {
+                println!("      {}", failure);
+            }
+        }
+    }
+    println!("\n{} passed, {} failed", results.len() - failed, failed);
+    Ok(failed == 0)
+}
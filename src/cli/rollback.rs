@@ -0,0 +1,45 @@
+//! Rollback command - undo a file write/delete detected too late
+
+use openclaw_harness::db::Database;
+use openclaw_harness::rollback::{self, RollbackMethod, RollbackOutcome, ShadowStore};
+use openclaw_harness::{expand_home, Config};
+
+pub async fn run(
+    action_id: Option<String>,
+    path: Option<String>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let shadow = ShadowStore::new(ShadowStore::default_root());
+
+    let outcome = match (action_id, path) {
+        (_, Some(path)) => rollback::rollback_path(&expand_home(&path), &shadow, dry_run)?,
+        (Some(id), None) => {
+            let db = Database::open(&expand_home(&Config::default().db_path))?;
+            let action = db
+                .get_action(&id)?
+                .ok_or_else(|| anyhow::anyhow!("Action '{}' not found", id))?;
+            rollback::rollback_action(&action, &shadow, dry_run)?
+        }
+        (None, None) => anyhow::bail!("Specify an action ID or --path"),
+    };
+
+    print_outcome(&outcome, dry_run);
+    Ok(())
+}
+
+fn print_outcome(outcome: &RollbackOutcome, dry_run: bool) {
+    let method = match outcome.method {
+        RollbackMethod::Git => "git",
+        RollbackMethod::Shadow => "shadow snapshot",
+    };
+
+    if dry_run {
+        println!("🔍 Would restore {} via {}", outcome.path.display(), method);
+    } else {
+        println!("↩️  Restored {} via {}", outcome.path.display(), method);
+    }
+    println!("   {}", outcome.detail);
+    if let Some(ref diff) = outcome.saved_diff {
+        println!("   Discarded changes saved to {}", diff.display());
+    }
+}
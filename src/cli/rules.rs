@@ -1,12 +1,20 @@
 //! Rules management commands
 
+use openclaw_harness::analyzer::Analyzer;
+use openclaw_harness::db::Database;
+use openclaw_harness::policy::suite;
 use openclaw_harness::rules::{
-    all_templates, default_rules, load_rules_from_file, self_protection_rules, KeywordMatch,
-    MatchType, Rule, RuleAction, TemplateParams,
+    all_templates, fallback_rules, load_configured_rules, load_rules_from_file,
+    self_protection_rules, KeywordMatch, MatchType, Rule, RuleAction, TemplateParams,
 };
-use openclaw_harness::RiskLevel;
+use openclaw_harness::web::routes::CHANGED_BY_HEADER;
+use openclaw_harness::web::{auth, WebConfig};
+use openclaw_harness::{expand_home, Config, RiskLevel};
+use reqwest::StatusCode;
+use std::collections::BTreeMap;
+use std::time::Duration;
 
-pub async fn list() -> anyhow::Result<()> {
+pub async fn list(category: Option<&str>, tag: Option<&str>) -> anyhow::Result<()> {
     println!("📜 Configured Rules");
     println!("───────────────────");
 
@@ -15,27 +23,51 @@ pub async fn list() -> anyhow::Result<()> {
     let rules = if config_path.exists() {
         match load_rules_from_file(config_path) {
             Ok(r) => r,
-            Err(_) => default_rules(),
+            Err(_) => fallback_rules(),
         }
     } else {
-        default_rules()
+        fallback_rules()
     };
 
+    let mut groups: BTreeMap<String, Vec<&Rule>> = BTreeMap::new();
     for rule in &rules {
-        let status = if rule.enabled { "✅" } else { "❌" };
-        let match_type = match rule.match_type {
-            MatchType::Regex => "regex",
-            MatchType::Keyword => "keyword",
-            MatchType::Template => "template",
-        };
-        let lock = if rule.protected { " 🔒" } else { "" };
-        println!(
-            "{} [{}] {} [{:?}]{} - {}",
-            status, match_type, rule.name, rule.risk_level, lock, rule.description
-        );
+        let name = rule.category_name();
+        if category.is_some_and(|c| !name.eq_ignore_ascii_case(c.trim()))
+            || tag.is_some_and(|t| !rule.has_tag(t))
+        {
+            continue;
+        }
+        groups.entry(name).or_default().push(rule);
+    }
+
+    for (name, group) in &groups {
+        println!("\n── {} ──", name);
+        for rule in group {
+            let status = if rule.enabled { "✅" } else { "❌" };
+            let match_type = match rule.match_type {
+                MatchType::Regex => "regex",
+                MatchType::Keyword => "keyword",
+                MatchType::Template => "template",
+            };
+            let lock = if rule.protected { " 🔒" } else { "" };
+            let tags = if rule.tags.is_empty() {
+                String::new()
+            } else {
+                format!(" #{}", rule.tags.join(" #"))
+            };
+            println!(
+                "{} [{}] {} [{:?}]{} - {}{}",
+                status, match_type, rule.name, rule.risk_level, lock, rule.description, tags
+            );
+        }
     }
 
-    println!("\nTotal: {} rules", rules.len());
+    let shown: usize = groups.values().map(Vec::len).sum();
+    if shown == rules.len() {
+        println!("\nTotal: {} rules", rules.len());
+    } else {
+        println!("\nShowing {} of {} rules", shown, rules.len());
+    }
     Ok(())
 }
 
@@ -93,11 +125,9 @@ pub async fn add_template(
         extra: Default::default(),
     };
 
-    let risk_level = match risk.unwrap_or("warning") {
-        "critical" => RiskLevel::Critical,
-        "info" => RiskLevel::Info,
-        _ => RiskLevel::Warning,
-    };
+    let risk_level = risk
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(RiskLevel::Warning);
 
     let action = match rule_action.unwrap_or("block") {
         "log_only" => RuleAction::LogOnly,
@@ -142,11 +172,9 @@ pub async fn add_keyword(
             .unwrap_or_default(),
     };
 
-    let risk_level = match risk.unwrap_or("warning") {
-        "critical" => RiskLevel::Critical,
-        "info" => RiskLevel::Info,
-        _ => RiskLevel::Warning,
-    };
+    let risk_level = risk
+        .and_then(|r| r.parse().ok())
+        .unwrap_or(RiskLevel::Warning);
 
     let action = match rule_action.unwrap_or("block") {
         "log_only" => RuleAction::LogOnly,
@@ -202,7 +230,7 @@ pub async fn disable(name: &str) -> anyhow::Result<()> {
 }
 
 pub async fn show(name: &str) -> anyhow::Result<()> {
-    let rules = default_rules();
+    let rules = load_configured_rules();
 
     if let Some(rule) = rules.iter().find(|r| r.name == name) {
         println!("Rule: {}", rule.name);
@@ -212,6 +240,15 @@ pub async fn show(name: &str) -> anyhow::Result<()> {
         println!("Risk Level: {:?}", rule.risk_level);
         println!("Action: {:?}", rule.action);
         println!("Enabled: {}", rule.enabled);
+        println!("Category: {}", rule.category_name());
+        if rule.priority != 0 {
+            println!("Priority: {}", rule.priority);
+        }
+        if !rule.tags.is_empty() {
+            println!("Tags: {}", rule.tags.join(", "));
+        }
+        print_rule_stats(name);
+        print_rule_history(name);
     } else {
         // Check templates
         let templates = all_templates();
@@ -229,6 +266,129 @@ pub async fn show(name: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Match counts from the database, if there is one
+fn print_rule_stats(name: &str) {
+    let db_path = expand_home(&Config::default().db_path);
+    if !db_path.exists() {
+        return;
+    }
+    let since = (chrono::Utc::now() - chrono::Duration::days(6))
+        .format("%Y-%m-%d")
+        .to_string();
+    let stats = match Database::open(&db_path).and_then(|db| db.rule_stats(&since)) {
+        Ok(mut stats) => stats.remove(name).unwrap_or_default(),
+        Err(e) => {
+            println!("⚠️  Could not read rule stats: {}", e);
+            return;
+        }
+    };
+    println!(
+        "Matches: {} ({} blocked, {} monitored)",
+        stats.total, stats.blocked, stats.monitored
+    );
+    println!(
+        "Last Matched: {}",
+        stats.last_matched.as_deref().unwrap_or("never")
+    );
+    if !stats.by_day.is_empty() {
+        println!("Last 7 Days:");
+        for (day, count) in &stats.by_day {
+            println!("  {}  {}", day, count);
+        }
+    }
+}
+
+/// Versions recorded for the rule, if any
+fn print_rule_history(name: &str) {
+    let db_path = expand_home(&Config::default().db_path);
+    if !db_path.exists() {
+        return;
+    }
+    let history = match Database::open(&db_path).and_then(|db| db.rule_history(name)) {
+        Ok(history) => history,
+        Err(e) => {
+            println!("⚠️  Could not read rule history: {}", e);
+            return;
+        }
+    };
+    if history.is_empty() {
+        return;
+    }
+    println!("History:");
+    for version in &history {
+        println!(
+            "  v{:<3} {}  {:<8} by {}",
+            version.version, version.changed_at, version.change, version.changed_by
+        );
+        for change in &version.diff {
+            println!("        {}", change);
+        }
+    }
+}
+
+/// Restore a rule in the running daemon to an earlier version
+pub async fn rollback(name: &str, version: u32) -> anyhow::Result<()> {
+    // The name is one path segment, whatever characters it has
+    let mut url = reqwest::Url::parse(&WebConfig::from_env().local_url())?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Cannot build the rollback URL"))?
+        .extend(["api", "v1", "rules", name, "rollback"]);
+    let user = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+    let mut request = reqwest::Client::new()
+        .post(url)
+        .header(CHANGED_BY_HEADER, format!("cli:{}", user))
+        .json(&serde_json::json!({ "version": version }))
+        .timeout(Duration::from_secs(10));
+    if let Some(token) = auth::token_from_env() {
+        request = request.bearer_auth(token);
+    }
+    let sent = request.send().await;
+    let resp = match sent {
+        Ok(resp) => resp,
+        Err(e) if e.is_connect() => return print_version(name, version),
+        Err(e) => return Err(e.into()),
+    };
+    match resp.status() {
+        StatusCode::OK => {
+            println!("⏪ Rule '{}' rolled back to version {}", name, version);
+            Ok(())
+        }
+        StatusCode::NOT_FOUND => anyhow::bail!(
+            "Rule '{}' has no version {} (see 'openclaw-harness rules show {}')",
+            name,
+            version,
+            name
+        ),
+        StatusCode::BAD_REQUEST => anyhow::bail!(
+            "Version {} of '{}' records its deletion; pick an earlier version",
+            version,
+            name
+        ),
+        StatusCode::FORBIDDEN => anyhow::bail!("🔒 Rule '{}' is protected", name),
+        StatusCode::UNAUTHORIZED => anyhow::bail!(
+            "🔑 The daemon wants its API token; set {} to it",
+            auth::TOKEN_ENV
+        ),
+        status => anyhow::bail!("Rollback failed: {}", status),
+    }
+}
+
+/// Without a daemon there is nothing to roll back; show the version so it
+/// can go into the rules file
+fn print_version(name: &str, version: u32) -> anyhow::Result<()> {
+    let db = Database::open(&expand_home(&Config::default().db_path))?;
+    let Some(rule) = db.rule_version(name, version)?.and_then(|v| v.rule) else {
+        anyhow::bail!("Rule '{}' has no version {} to restore", name, version);
+    };
+    println!(
+        "⚠️  The daemon is not running. Version {} of '{}':\n",
+        version, name
+    );
+    println!("{}", serde_yaml::to_string(&vec![rule])?);
+    println!("💡 Put it in config/rules.yaml, then start the daemon.");
+    Ok(())
+}
+
 pub async fn reload() -> anyhow::Result<()> {
     println!("Reloading rules from config...");
     let config_path = std::path::Path::new("config/rules.yaml");
@@ -240,3 +400,70 @@ pub async fn reload() -> anyhow::Result<()> {
     }
     Ok(())
 }
+
+/// Lint a rules file; returns false when it has errors
+pub fn lint(path: Option<&std::path::Path>) -> anyhow::Result<bool> {
+    let path = path.unwrap_or(std::path::Path::new("config/rules.yaml"));
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", path.display(), e))?;
+    let rules: Vec<Rule> = match serde_yaml::from_str(&content) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("❌ {}: {}", path.display(), e);
+            return Ok(false);
+        }
+    };
+
+    let issues = suite::lint(&rules);
+    for issue in &issues {
+        let emoji = if issue.error { "❌" } else { "⚠️ " };
+        println!("{} {}: {}", emoji, issue.rule, issue.message);
+    }
+    let errors = issues.iter().filter(|i| i.error).count();
+    if issues.is_empty() {
+        println!("✅ {}: {} rules, no problems", path.display(), rules.len());
+    } else {
+        println!(
+            "\n{} rules, {} error(s), {} warning(s)",
+            rules.len(),
+            errors,
+            issues.len() - errors
+        );
+    }
+    Ok(errors == 0)
+}
+
+/// Run expectation files; returns false when any case fails
+pub fn test_suite(
+    files: &[std::path::PathBuf],
+    rules_path: Option<&std::path::Path>,
+) -> anyhow::Result<bool> {
+    let rules = match rules_path {
+        Some(path) => load_rules_from_file(path)?,
+        None => load_configured_rules(),
+    };
+    let analyzer = Analyzer::new(rules);
+
+    let (mut passed, mut failed) = (0, 0);
+    for file in files {
+        let content = std::fs::read_to_string(file)
+            .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", file.display(), e))?;
+        let test_suite: suite::TestSuite = serde_yaml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("{}: {}", file.display(), e))?;
+        println!("🧪 {}", file.display());
+        for result in suite::run_suite(&analyzer, &test_suite) {
+            if result.failures.is_empty() {
+                passed += 1;
+                println!("   ✅ {}", result.name);
+            } else {
+                failed += 1;
+                println!("   ❌ {}", result.name);
+                for failure in &result.failures {
+                    println!("      {}", failure);
+                }
+            }
+        }
+    }
+    println!("\n{} passed, {} failed", passed, failed);
+    Ok(failed == 0)
+}
@@ -1,19 +1,58 @@
 //! Start command - launches the OpenClaw Harness daemon
 
-use openclaw_harness::analyzer::Analyzer;
-use openclaw_harness::collectors::{openclaw::OpenclawCollector, Collector};
+use openclaw_harness::analyzer::{rule_engine::RuleSet, Analyzer};
+use openclaw_harness::blob::BlobStore;
+use openclaw_harness::brain::{self, notes};
+use openclaw_harness::campaign::behaviours;
+use openclaw_harness::collectors::{
+    create_collectors, manager, queue, ActionQueue, CollectorManager,
+};
+use openclaw_harness::correlation::{self, Correlation};
+use openclaw_harness::db::{chain, maintenance, Database, DbPool};
+use openclaw_harness::diagnostics;
 use openclaw_harness::enforcer::alerter::Alerter;
-use openclaw_harness::rules::{default_rules, load_rules_from_file};
+use openclaw_harness::enforcer::confirm::TerminalApprover;
+use openclaw_harness::enforcer::lockdown::LockdownPolicy;
+use openclaw_harness::enforcer::secrets;
+use openclaw_harness::events::{Event, EventBus, Subscriber, RESUME_WINDOW_MINUTES};
+use openclaw_harness::ids;
+use openclaw_harness::listen;
+use openclaw_harness::patcher::integrity::{self, IntegrityMonitor};
+use openclaw_harness::proxy::{self, config::ProxyConfig, control::ProxyControl};
+use openclaw_harness::rollback::{protected_snapshot_paths, ShadowStore};
+use openclaw_harness::rules::{deployment::Deployment, fallback_rules, load_rules_from_file};
+use openclaw_harness::slack::SlackApp;
+use openclaw_harness::trace;
+use openclaw_harness::web::report_email::{self, ReportMailer};
+use openclaw_harness::web::routes::{configured_alerts, migrate_alert_secrets};
 use openclaw_harness::web::{self, WebEvent};
-use openclaw_harness::{AgentAction, AlertConfig, Recommendation, RiskLevel, TelegramConfig};
+use openclaw_harness::{
+    expand_home, ActionType, AgentAction, AlertConfig, AnalysisResult, Config, Recommendation,
+    RiskLevel,
+};
 use sha2::{Digest, Sha256};
 use std::fs;
 use std::process::Command;
+use std::sync::Arc;
 use tokio::sync::{broadcast, mpsc};
-use tracing::{error, info, warn};
+use tracing::{error, info, warn, Instrument};
 
 const PID_FILE: &str = "/tmp/openclaw-harness.pid";
 const CONFIG_HASH_FILE: &str = "/tmp/openclaw-harness-config.hash";
+/// How often protected paths are copied to the shadow directory
+const SNAPSHOT_INTERVAL_SECS: u64 = 300;
+/// How often alerts held during quiet hours are checked for delivery
+const DIGEST_CHECK_SECS: u64 = 60;
+/// Seconds between heartbeats of the main loop
+const HEARTBEAT_SECS: u64 = 30;
+/// Patch integrity is re-verified every N heartbeats
+const PATCH_CHECK_HEARTBEATS: u64 = 10;
+/// How often journaled events every consumer has acknowledged are dropped
+const COMPACT_SECS: u64 = 30;
+/// How often recorded internal failures are written to the database
+const DIAGNOSTICS_FLUSH_SECS: u64 = 30;
+/// How often collectors are checked for stale logs
+const STALE_CHECK_SECS: u64 = 60;
 
 /// Compute SHA256 hash of a file
 fn compute_config_hash(path: &std::path::Path) -> Option<String> {
@@ -23,7 +62,9 @@ fn compute_config_hash(path: &std::path::Path) -> Option<String> {
     Some(format!("{:x}", hasher.finalize()))
 }
 
-pub async fn run(foreground: bool) -> anyhow::Result<()> {
+/// Start the daemon; `expose` allows the web server and embedded proxy to
+/// listen on non-loopback addresses
+pub async fn run(foreground: bool, expose: bool) -> anyhow::Result<()> {
     // Check if already running
     if is_running() {
         println!("⚠️  OpenClaw Harness is already running!");
@@ -32,10 +73,10 @@ pub async fn run(foreground: bool) -> anyhow::Result<()> {
 
     if foreground {
         info!("Running in foreground mode");
-        run_daemon().await
+        run_daemon(expose, true).await
     } else {
         info!("Daemonizing...");
-        daemonize().await
+        daemonize(expose).await
     }
 }
 
@@ -62,25 +103,9 @@ fn remove_pid() {
     let _ = fs::remove_file(PID_FILE);
 }
 
-async fn daemonize() -> anyhow::Result<()> {
+async fn daemonize(expose: bool) -> anyhow::Result<()> {
     println!("🛡️  Starting OpenClaw Harness daemon...");
-    run_daemon().await
-}
-
-/// Load Telegram config from environment variables
-fn load_telegram_config() -> Option<TelegramConfig> {
-    let bot_token = std::env::var("OPENCLAW_HARNESS_TELEGRAM_BOT_TOKEN")
-        .or_else(|_| std::env::var("SAFEBOT_TELEGRAM_BOT_TOKEN"))
-        .ok()?;
-    let chat_id = std::env::var("OPENCLAW_HARNESS_TELEGRAM_CHAT_ID")
-        .or_else(|_| std::env::var("SAFEBOT_TELEGRAM_CHAT_ID"))
-        .ok()?;
-
-    if bot_token.is_empty() || chat_id.is_empty() {
-        return None;
-    }
-
-    Some(TelegramConfig { bot_token, chat_id })
+    run_daemon(expose, false).await
 }
 
 /// Attempt to interrupt Clawdbot
@@ -127,7 +152,22 @@ async fn block_action(action: &AgentAction) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn run_daemon() -> anyhow::Result<()> {
+/// Run the daemon; `interactive` (foreground) asks about PauseAndAsk
+/// actions in the terminal when there is one
+async fn run_daemon(expose: bool, interactive: bool) -> anyhow::Result<()> {
+    // Refuse external addresses before anything starts
+    let config = Config::default();
+    let mut web_config = config.web;
+    web_config.expose |= expose;
+    listen::check(&web_config.listen, web_config.expose)?;
+    let mut embedded_proxy = config.proxy;
+    if let Some(proxy) = &mut embedded_proxy {
+        proxy.expose |= expose;
+        for listener in proxy.effective_listeners() {
+            listen::check(&listener.listen, proxy.expose)?;
+        }
+    }
+
     // Write PID file
     write_pid()?;
 
@@ -151,17 +191,34 @@ async fn run_daemon() -> anyhow::Result<()> {
             }
             Err(e) => {
                 warn!("⚠️ Failed to load config/rules.yaml: {}, using defaults", e);
-                let r = default_rules();
+                let r = fallback_rules();
                 info!("📜 Loaded {} default rules", r.len());
                 r
             }
         }
     } else {
-        let r = default_rules();
+        let r = fallback_rules();
         info!("📜 Loaded {} default rules", r.len());
         r
     };
 
+    let deployment = Deployment::current();
+    info!(
+        "🔒 Self-protection covers {}, {}, {} and web port {}",
+        deployment
+            .executable
+            .as_ref()
+            .map_or("(unknown binary)".to_string(), |p| p.display().to_string()),
+        deployment
+            .config_dirs
+            .iter()
+            .map(|d| d.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        deployment.db_path.display(),
+        deployment.web_port
+    );
+
     // Config integrity: compute and store initial hash
     let config_hash = if config_path.exists() {
         let hash = compute_config_hash(config_path);
@@ -184,58 +241,329 @@ async fn run_daemon() -> anyhow::Result<()> {
     let web_tx_clone = web_tx.clone();
 
     // Start web server
-    let web_port = std::env::var("OPENCLAW_HARNESS_WEB_PORT")
-        .ok()
-        .and_then(|p| p.parse().ok())
-        .unwrap_or(8380);
+    let db_path = expand_home(&Config::default().db_path);
+    if let Some(parent) = db_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    diagnostics::install_panic_hook(db_path.clone());
+    // One pool for the daemon: correlation, the journal, the writer and
+    // the web server all take connections from it
+    let pool = DbPool::open(&db_path)?;
+    let db = match pool.get() {
+        Ok(db) => Some(db),
+        Err(e) => {
+            warn!("⚠️  Failed to open database {}: {}", db_path.display(), e);
+            None
+        }
+    };
 
-    let db_path = "~/.openclaw-harness/openclaw-harness.db".to_string();
-    tokio::spawn(async move {
-        if let Err(e) = web::start_server(web_port, web_tx_clone, db_path, None).await {
-            error!("Web server error: {}", e);
+    if chain::enabled_from_env() {
+        match db.as_ref().map(|db| db.start_chain()) {
+            Some(Ok(true)) => info!("⛓️  Started the hash chain over stored actions"),
+            Some(Ok(false)) | None => {}
+            Some(Err(e)) => warn!("⚠️  Failed to start the hash chain: {}", e),
         }
-    });
+    }
 
-    // Create analyzer
-    let analyzer = Analyzer::new(rules);
+    // Everything downstream of analysis hangs off the event bus
+    let bus = match pool.get() {
+        Ok(journal) => EventBus::with_journal(journal),
+        Err(e) => {
+            warn!(
+                "⚠️  Event journal unavailable ({}), delivering live only",
+                e
+            );
+            EventBus::in_memory()
+        }
+    };
+    let web_sub = bus.subscribe("web");
+    if let Ok(writer_db) = pool.get() {
+        spawn_db_writer(bus.subscribe("db_writer"), writer_db);
+    }
 
-    // Load alert config from environment
-    let telegram_config = load_telegram_config();
-    let alerter = if telegram_config.is_some() {
-        info!("📱 Telegram alerts enabled");
-        Some(Alerter::new(AlertConfig {
-            telegram: telegram_config,
-            slack: None,
-            discord: None,
-        }))
+    // Alert credentials in the environment or the alert config go to the
+    // OS keyring when there is one
+    if let Some(store) = secrets::keyring() {
+        match migrate_alert_secrets(store) {
+            Ok(vars) => {
+                for var in vars {
+                    info!("🔐 {} is in the {}; it can be unset", var, store.name());
+                }
+            }
+            Err(e) => warn!(
+                "⚠️ Failed to move alert credentials into the keyring: {}",
+                e
+            ),
+        }
+    }
+
+    // Alert channels from the environment, the keyring and the alert config
+    let alert_config = configured_alerts();
+    let alerter = if alert_config.telegram.is_some()
+        || alert_config.slack.is_some()
+        || alert_config.discord.is_some()
+        || alert_config.desktop.is_some()
+    {
+        info!("📱 Alerts enabled");
+        Some(Arc::new(
+            Alerter::new(alert_config.clone()).with_config_file(secrets::alerts_path()),
+        ))
     } else {
-        warn!("⚠️  No Telegram config found (set OPENCLAW_HARNESS_TELEGRAM_BOT_TOKEN and OPENCLAW_HARNESS_TELEGRAM_CHAT_ID)");
+        warn!("⚠️  No alert channels configured (set OPENCLAW_HARNESS_TELEGRAM_BOT_TOKEN and OPENCLAW_HARNESS_TELEGRAM_CHAT_ID, or use the web UI)");
         None
     };
 
-    // Create channel for actions
-    let (tx, mut rx) = mpsc::channel::<AgentAction>(100);
+    let web_db = pool.clone();
+    let mut web_rules = rules.clone();
+    for r in &mut web_rules {
+        r.compile()?;
+    }
+    // Web server and embedded proxy share one set of rules and one mode
+    let proxy_mode = embedded_proxy
+        .as_ref()
+        .map_or(ProxyConfig::default().mode, |p| p.mode);
+    let proxy_control = ProxyControl::new(proxy_mode, web_rules);
+    spawn_web_bridge(web_sub, web_tx.clone(), proxy_control.clone());
+    if let Some(policy) = LockdownPolicy::from_env() {
+        info!("🕘 After-hours lockdown enabled");
+        policy.spawn(proxy_control.clone());
+    }
+    let web_control = proxy_control.clone();
+    // Collectors are started later; the web server reports on them as they come up
+    let collectors = CollectorManager::new();
+    let web_collectors = collectors.clone();
+    let web_alerter = alerter.clone();
+    tokio::spawn(async move {
+        if let Err(e) = web::start_server(
+            web_config,
+            web_tx_clone,
+            web_db,
+            None,
+            web_control,
+            web_collectors,
+            web_alerter,
+        )
+        .await
+        {
+            error!("Web server error: {}", e);
+        }
+    });
 
-    // Start OpenClaw collector
-    let collector = OpenclawCollector::new();
-    if collector.is_available() {
-        info!("🦞 OpenClaw collector available");
-        let tx_clone = tx.clone();
+    // Keep the database file in shape and under its size limit
+    let maintenance_pool = pool.clone();
+    tokio::spawn(async move {
+        loop {
+            let pool = maintenance_pool.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                let db = pool.get()?;
+                let report = db.maintain(maintenance::max_size_bytes())?;
+                // Anchor after pruning, so the anchor covers its deletions
+                if db.chaining()? {
+                    if let Err(e) = db.anchor_chain(&chain::anchors_path()) {
+                        warn!("⚠️  Failed to anchor the hash chain: {}", e);
+                    }
+                }
+                anyhow::Ok(report)
+            })
+            .await;
+            match result {
+                Ok(Ok(report)) if report.reclaimed_bytes > 0 => info!(
+                    "🧹 Database maintenance freed {} KiB",
+                    report.reclaimed_bytes / 1024
+                ),
+                Ok(Ok(_)) => {}
+                Ok(Err(e)) => warn!("⚠️  Database maintenance failed: {}", e),
+                Err(e) => warn!("⚠️  Database maintenance task failed: {}", e),
+            }
+            tokio::time::sleep(maintenance::MAINTENANCE_INTERVAL).await;
+        }
+    });
+
+    // Write a decision note for each session that has gone quiet
+    if notes::enabled() {
+        let notes_pool = pool.clone();
         tokio::spawn(async move {
-            if let Err(e) = collector.start(tx_clone).await {
-                error!("OpenClaw collector error: {}", e);
+            loop {
+                tokio::time::sleep(notes::CHECK_INTERVAL).await;
+                let pool = notes_pool.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    notes::write_due_notes(&pool, &brain::data_dir(), chrono::Utc::now())
+                })
+                .await;
+                match result {
+                    Ok(Ok(0)) => {}
+                    Ok(Ok(n)) => info!("📝 Wrote {} decision note(s)", n),
+                    Ok(Err(e)) => warn!("⚠️  Failed to write decision notes: {}", e),
+                    Err(e) => warn!("⚠️  Decision note task failed: {}", e),
+                }
             }
         });
-    } else {
-        warn!("⚠️  OpenClaw sessions directory not found");
+    }
+
+    // Derive campaign behaviour records from settled actions
+    let behaviours_pool = pool.clone();
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(behaviours::DERIVE_INTERVAL).await;
+            let pool = behaviours_pool.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                behaviours::derive_from_actions(&*pool.connection()?, chrono::Utc::now())
+            })
+            .await;
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(n)) => info!("🎯 Derived {} behaviour record(s)", n),
+                Ok(Err(e)) => warn!("⚠️  Failed to derive behaviour records: {}", e),
+                Err(e) => warn!("⚠️  Behaviour derivation task failed: {}", e),
+            }
+        }
+    });
+
+    // Periodically snapshot protected paths so late-detected writes can be rolled back
+    tokio::spawn(async move {
+        let shadow_root = ShadowStore::default_root();
+        loop {
+            let root = shadow_root.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                let shadow = ShadowStore::new(root);
+                protected_snapshot_paths()
+                    .iter()
+                    .map(|p| shadow.snapshot(p))
+                    .sum::<anyhow::Result<usize>>()
+            })
+            .await;
+            match result {
+                Ok(Ok(0)) => {}
+                Ok(Ok(n)) => info!("📸 Snapshotted {} protected file(s)", n),
+                Ok(Err(e)) => warn!("⚠️  Snapshot failed: {}", e),
+                Err(e) => warn!("⚠️  Snapshot task failed: {}", e),
+            }
+            tokio::time::sleep(tokio::time::Duration::from_secs(SNAPSHOT_INTERVAL_SECS)).await;
+        }
+    });
+
+    // Create analyzer; the action queue triages overflow with the same rules
+    let triage_rules = RuleSet::new(rules.clone());
+    let analyzer = Analyzer::new(rules).with_canary_db(pool.clone());
+
+    let approver = interactive
+        .then(|| TerminalApprover::from_terminal(pool.clone()))
+        .flatten();
+    if let Some(approver) = approver.clone() {
+        info!("⌨️  PauseAndAsk actions will be put to this terminal (y/N)");
+        tokio::spawn(approver.run());
+    }
+    spawn_enforcer(bus.subscribe("enforcer"), alerter.clone(), approver);
+    if let Some(proxy_config) = embedded_proxy {
+        info!("🛡️ Hosting the API proxy on {}", proxy_config.listen);
+        let alert_config = alert_config.telegram.map(|telegram| AlertConfig {
+            telegram: Some(telegram),
+            slack: None,
+            discord: None,
+            desktop: None,
+            quiet_hours: Default::default(),
+        });
+        let proxy_bus = bus.clone();
+        let proxy_pool = pool.clone();
+        tokio::spawn(async move {
+            if let Err(e) = proxy::start_proxy_with(
+                proxy_config,
+                alert_config,
+                proxy_control,
+                Some(proxy_bus),
+                Some(proxy_pool),
+            )
+            .await
+            {
+                error!("Proxy error: {}", e);
+            }
+        });
+    }
+    if let Some(mailer) = ReportMailer::from_env() {
+        info!("📧 Weekly report email enabled");
+        let report_pool = pool.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = mailer.send_due(&report_pool).await {
+                    warn!("Failed to email the weekly report: {}", e);
+                }
+                tokio::time::sleep(report_email::CHECK_INTERVAL).await;
+            }
+        });
+    }
+    if let Some(app) = SlackApp::from_env(pool.clone(), alerter.clone()) {
+        info!("💬 Slack app enabled (/harness commands, approvals)");
+        tokio::spawn(app.run());
+    }
+    if let Some(alerter) = alerter.clone() {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(tokio::time::Duration::from_secs(DIGEST_CHECK_SECS)).await;
+                if let Err(e) = alerter.send_digests().await {
+                    warn!("Failed to send alert digest: {}", e);
+                }
+            }
+        });
+    }
+
+    // Bounded channel for actions; on overflow, actions no rule flags are
+    // dropped and the rest spilled or waited for
+    let (tx, mut rx) = mpsc::channel::<AgentAction>(queue::capacity());
+    let mut action_queue = ActionQueue::new(tx.clone(), move |action: &AgentAction| {
+        triage_rules
+            .matching(action)
+            .iter()
+            .map(|rule| rule.risk_level)
+            .max()
+            .unwrap_or(RiskLevel::Info)
+    });
+    if queue::spill_enabled() {
+        if let Some(dir) = db_path.parent() {
+            let spill = dir.join("spill").join("actions.jsonl");
+            info!("💾 Spilling overflow actions to {}", spill.display());
+            action_queue = action_queue.with_spill(spill);
+        }
+    }
+    let action_queue = Arc::new(action_queue);
+    action_queue.spawn_drain();
+
+    // Start every enabled collector whose agent is installed, restarting
+    // the ones that fail
+    let collector_state = db_path.parent().map(|dir| dir.join("collector-state"));
+    let started = collectors.spawn(
+        create_collectors(&Config::default().collectors, collector_state.as_deref()),
+        action_queue,
+    );
+    if started == 0 {
+        warn!("⚠️  No agent log directories found (OpenClaw, Gemini CLI, Copilot CLI, Aider, OpenHands)");
     }
 
     info!("✅ OpenClaw Harness daemon started successfully");
     info!("👀 Monitoring for AI agent actions...");
 
+    spawn_compaction(bus.clone());
+    if let Ok(db) = pool.get() {
+        spawn_diagnostics_flush(db);
+    }
+    if let Some(threshold) = manager::stale_threshold() {
+        spawn_stale_check(collectors.clone(), alerter.clone(), threshold);
+    }
+
     // Keep tx alive to prevent channel from closing
     let _tx_keepalive = tx;
 
+    // Remember which agents are patched so we notice when an upgrade undoes it
+    let mut patch_monitor = IntegrityMonitor::new();
+    patch_monitor.check();
+    let mut heartbeats: u64 = 0;
+    // One timer for the whole loop, so a steady stream of actions can't
+    // keep putting the heartbeat off
+    let mut heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(HEARTBEAT_SECS));
+    heartbeat.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    heartbeat.tick().await;
+    let correlation_window = correlation::window();
+    let blobs = BlobStore::for_db(&db_path);
+
     info!("🔄 Entering main event loop...");
 
     // Main event loop - process actions
@@ -244,67 +572,33 @@ async fn run_daemon() -> anyhow::Result<()> {
             action_opt = rx.recv() => {
                 match action_opt {
                     Some(action) => {
+                        let _span = trace::action_span(&action).entered();
                         info!("📥 Received action: {} - {}", action.action_type, truncate(&action.content, 50));
 
-                        // Broadcast to web clients
-                        let _ = web_tx.send(WebEvent::from(&action));
-
-                        // Analyze the action
-                        let result = analyzer.analyze(&action);
-
-                        // Broadcast analysis result
-                        let _ = web_tx.send(WebEvent::from(&result));
-
-                        // Handle based on result
-                        if result.matched_rules.is_empty() {
-                            continue;
-                        }
-
-                        match result.risk_level {
-                            RiskLevel::Critical => {
-                                error!("🚨 CRITICAL: {} (rules: {:?})",
-                                    result.explanation, result.matched_rules);
-
-                                // Send alert
-                                if let Some(ref alerter) = alerter {
-                                    if let Err(e) = alerter.send_alert(&result).await {
-                                        error!("Failed to send alert: {}", e);
-                                    }
-                                }
-
-                                match result.recommendation {
-                                    Recommendation::CriticalAlert => {
-                                        error!("🛑 ACTION BLOCKED");
-                                        if let Err(e) = block_action(&action).await {
-                                            error!("Failed to block: {}", e);
-                                        }
-                                    }
-                                    Recommendation::PauseAndAsk => {
-                                        warn!("⏸️  Requires user approval");
-                                        // Send alert for approval
-                                        if let Some(ref alerter) = alerter {
-                                            let _ = alerter.send_alert(&result).await;
-                                        }
-                                    }
-                                    _ => {}
+                        // Rules see the full content; storage and subscribers get
+                        // the enriched copy with oversized content offloaded
+                        let mut result = analyzer.analyze(&action);
+                        let stored = blobs.offload(&result.action);
+
+                        // Persist, unless the proxy already reported this same call
+                        if let Some(ref db) = db {
+                            match correlation::record(db, &stored, correlation_window) {
+                                Ok(Correlation::Duplicate { primary_id }) => {
+                                    info!("🔗 Same event as {} (already handled), skipping", primary_id);
+                                    continue;
                                 }
-                            }
-                            RiskLevel::Warning => {
-                                warn!("⚠️  WARNING: {} (rules: {:?})",
-                                    result.explanation, result.matched_rules);
-
-                                // Send alert for warnings too
-                                if let Some(ref alerter) = alerter {
-                                    if let Err(e) = alerter.send_alert(&result).await {
-                                        error!("Failed to send alert: {}", e);
-                                    }
+                                Ok(Correlation::Replay { primary_id }) => {
+                                    info!("🔁 Already processed as {} (replayed log line), skipping", primary_id);
+                                    continue;
                                 }
-                            }
-                            RiskLevel::Info => {
-                                info!("ℹ️  INFO: {}", result.explanation);
-                                // Don't send alerts for info level
+                                Ok(Correlation::New) => {}
+                                Err(e) => warn!("Failed to store action: {}", e),
                             }
                         }
+
+                        result.action = stored.clone();
+                        bus.publish(Event::Action(stored));
+                        bus.publish(Event::Analysis(result));
                     }
                     None => {
                         warn!("⚠️  Channel closed, all senders dropped");
@@ -313,8 +607,53 @@ async fn run_daemon() -> anyhow::Result<()> {
                 }
             }
             // Heartbeat + config integrity check every 30 seconds
-            _ = tokio::time::sleep(tokio::time::Duration::from_secs(30)) => {
+            _ = heartbeat.tick() => {
                 info!("💓 Daemon heartbeat - still monitoring...");
+                heartbeats += 1;
+
+                // Patch integrity check
+                if heartbeats.is_multiple_of(PATCH_CHECK_HEARTBEATS) {
+                    for lost in patch_monitor.check() {
+                        error!("🚨 PATCH LOST: {} hooks are no longer installed (agent upgrade?)", lost.target);
+
+                        let mut note = format!("Run: openclaw-harness patch {}", lost.target);
+                        if integrity::auto_repatch_enabled() && lost.can_repatch {
+                            match integrity::repatch(lost.target) {
+                                Ok(()) => {
+                                    info!("🩹 Re-applied {} patch", lost.target);
+                                    patch_monitor.mark_patched(lost.target);
+                                    note = "Patch was re-applied automatically; restart the agent to activate it.".to_string();
+                                }
+                                Err(e) => error!("Failed to re-apply {} patch: {}", lost.target, e),
+                            }
+                        } else if !lost.can_repatch {
+                            note = format!("Anchors no longer match — {} may need a patcher update.", lost.target);
+                        }
+
+                        if let Some(ref alerter) = alerter {
+                            let lost_action = AgentAction {
+                                id: ids::generate(ids::Source::Harness),
+                                timestamp: chrono::Utc::now(),
+                                agent: openclaw_harness::AgentType::Unknown,
+                                action_type: ActionType::FileWrite,
+                                content: format!("PATCH LOST: {} hooks removed", lost.target),
+                                target: Some(lost.target.to_string()),
+                                session_id: None,
+                                metadata: None,
+                            };
+                            let lost_result = openclaw_harness::AnalysisResult {
+                                action: lost_action,
+                                risk_level: RiskLevel::Critical,
+                                matched_rules: vec!["PATCH_INTEGRITY".to_string()],
+                                explanation: format!("⚠️ {} is no longer patched — tool calls are unprotected. {}", lost.target, note),
+                                recommendation: Recommendation::CriticalAlert,
+                            };
+                            if let Err(e) = alerter.send_alert(&lost_result).await {
+                                error!("Failed to send patch integrity alert: {}", e);
+                            }
+                        }
+                    }
+                }
 
                 // Config integrity check
                 if let Some(ref original_hash) = config_hash_ref {
@@ -328,7 +667,7 @@ async fn run_daemon() -> anyhow::Result<()> {
                                 // Send Telegram alert
                                 if let Some(ref alerter) = alerter {
                                     let tamper_action = AgentAction {
-                                        id: format!("tamper-{}", chrono::Utc::now().timestamp()),
+                                        id: ids::generate(ids::Source::Harness),
                                         timestamp: chrono::Utc::now(),
                                         agent: openclaw_harness::AgentType::Unknown,
                                         action_type: openclaw_harness::ActionType::FileWrite,
@@ -357,6 +696,241 @@ async fn run_daemon() -> anyhow::Result<()> {
     }
 }
 
+// ============================================
+// Event bus consumers
+// ============================================
+
+/// Forward bus events to WebSocket clients, with the categories of the
+/// rules in effect
+fn spawn_web_bridge(
+    mut sub: Subscriber,
+    web_tx: broadcast::Sender<WebEvent>,
+    control: Arc<ProxyControl>,
+) {
+    tokio::spawn(async move {
+        while let Some(env) = sub.recv().await {
+            let _ = web_tx.send(WebEvent::from_envelope(&env, &control.rules()));
+            sub.ack();
+        }
+    });
+}
+
+/// Keep the event journal to what some consumer still has to read, plus the
+/// last few minutes for SSE clients resuming by `seq`
+fn spawn_compaction(bus: EventBus) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(COMPACT_SECS));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let compacted = bus.compact_keeping(chrono::Duration::minutes(RESUME_WINDOW_MINUTES));
+            if compacted > 0 {
+                info!("🧹 Compacted {} delivered events", compacted);
+            }
+        }
+    });
+}
+
+/// Write recorded internal failures to the database, for `doctor` and
+/// `/api/v1/status`
+fn spawn_diagnostics_flush(db: Database) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(tokio::time::Duration::from_secs(DIAGNOSTICS_FLUSH_SECS));
+        loop {
+            interval.tick().await;
+            if let Err(e) = diagnostics::flush(&db) {
+                warn!("Failed to persist diagnostics: {}", e);
+            }
+        }
+    });
+}
+
+/// Warn, and alert, about collectors that went quiet while their agent is
+/// running
+fn spawn_stale_check(
+    collectors: Arc<CollectorManager>,
+    alerter: Option<Arc<Alerter>>,
+    threshold: std::time::Duration,
+) {
+    tokio::spawn(async move {
+        let mut interval =
+            tokio::time::interval(tokio::time::Duration::from_secs(STALE_CHECK_SECS));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            for stale in collectors.check_stale(threshold, &manager::list_processes()) {
+                let minutes = stale.idle_secs.unwrap_or_default() / 60;
+                warn!(
+                    "⚠️  {} collector has seen no events for {} min while the agent is running",
+                    stale.name, minutes
+                );
+                let Some(ref alerter) = alerter else {
+                    continue;
+                };
+                let stale_action = AgentAction {
+                    id: ids::generate(ids::Source::Harness),
+                    timestamp: chrono::Utc::now(),
+                    agent: openclaw_harness::AgentType::from_name(&stale.name),
+                    action_type: ActionType::Unknown,
+                    content: format!("COLLECTOR STALE: {} quiet for {} min", stale.name, minutes),
+                    target: None,
+                    session_id: None,
+                    metadata: None,
+                };
+                let stale_result = openclaw_harness::AnalysisResult {
+                    action: stale_action,
+                    risk_level: RiskLevel::Warning,
+                    matched_rules: vec!["COLLECTOR_STALE".to_string()],
+                    explanation: format!("⚠️ The {} agent is running but its collector has seen nothing for {} min. It may be logging somewhere new after an update; its actions are not being monitored.", stale.name, minutes),
+                    recommendation: Recommendation::Alert,
+                };
+                if let Err(e) = alerter.send_alert(&stale_result).await {
+                    error!("Failed to send stale collector alert: {}", e);
+                }
+            }
+        }
+    });
+}
+
+/// Persist analysis results (actions are stored at ingest, for correlation)
+fn spawn_db_writer(mut sub: Subscriber, db: Database) {
+    tokio::spawn(async move {
+        while let Some(env) = sub.recv().await {
+            if let Event::Analysis(result) = &env.event {
+                if let Err(e) = db.store_analysis(result) {
+                    warn!("Failed to store analysis: {}", e);
+                }
+            }
+            sub.ack();
+        }
+    });
+}
+
+/// Alert on and respond to risky analysis results
+fn spawn_enforcer(
+    mut sub: Subscriber,
+    alerter: Option<Arc<Alerter>>,
+    approver: Option<Arc<TerminalApprover>>,
+) {
+    tokio::spawn(async move {
+        while let Some(env) = sub.recv().await {
+            if let Event::Analysis(result) = &env.event {
+                let span = trace::action_span(&result.action);
+                let enforce_span = span.in_scope(|| trace::enforce_span(result));
+                enforce(result, alerter.as_deref())
+                    .instrument(enforce_span)
+                    .await;
+                if let Some(approver) = &approver {
+                    span.in_scope(|| confirm_in_terminal(result, approver));
+                }
+            }
+            sub.ack();
+        }
+    });
+}
+
+async fn enforce(result: &AnalysisResult, alerter: Option<&Alerter>) {
+    let action = &result.action;
+
+    // Handle based on result
+    if result.matched_rules.is_empty() {
+        return;
+    }
+
+    match result.risk_level {
+        RiskLevel::Critical => {
+            error!(
+                "🚨 CRITICAL: {} (rules: {:?})",
+                result.explanation, result.matched_rules
+            );
+
+            if matches!(
+                action.action_type,
+                ActionType::FileWrite | ActionType::FileDelete
+            ) {
+                warn!(
+                    "↩️  Already executed? Undo with: openclaw-harness rollback {}",
+                    action.id
+                );
+            }
+
+            // Send alert
+            if let Some(alerter) = alerter {
+                if let Err(e) = alerter.send_alert(result).await {
+                    error!("Failed to send alert: {}", e);
+                }
+            }
+
+            match result.recommendation {
+                Recommendation::CriticalAlert => {
+                    error!("🛑 ACTION BLOCKED");
+                    if let Err(e) = block_action(action).await {
+                        error!("Failed to block: {}", e);
+                    }
+                }
+                Recommendation::PauseAndAsk => {
+                    warn!("⏸️  Requires user approval");
+                    // Send alert for approval
+                    if let Some(alerter) = alerter {
+                        let _ = alerter.send_alert(result).await;
+                    }
+                }
+                _ => {}
+            }
+        }
+        RiskLevel::Warning => {
+            warn!(
+                "⚠️  WARNING: {} (rules: {:?})",
+                result.explanation, result.matched_rules
+            );
+
+            // Send alert for warnings too
+            if let Some(alerter) = alerter {
+                if let Err(e) = alerter.send_alert(result).await {
+                    error!("Failed to send alert: {}", e);
+                }
+            }
+        }
+        RiskLevel::Info => {
+            info!("ℹ️  INFO: {}", result.explanation);
+            // Don't send alerts for info level
+        }
+    }
+}
+
+/// Put a PauseAndAsk action to the terminal without holding up the
+/// enforcer; a denied one is interrupted like a blocked one
+fn confirm_in_terminal(result: &AnalysisResult, approver: &Arc<TerminalApprover>) {
+    if result.recommendation != Recommendation::PauseAndAsk || result.matched_rules.is_empty() {
+        return;
+    }
+    let approval = match approver.request(result) {
+        Ok(approval) => approval,
+        Err(e) => {
+            warn!("⚠️ Failed to file the approval request: {}", e);
+            return;
+        }
+    };
+    let approver = approver.clone();
+    let action = result.action.clone();
+    tokio::spawn(async move {
+        match approver.confirm(&approval).await {
+            Ok(Some(decision)) if decision.approved() => {
+                info!("✅ {} approved", action.id);
+            }
+            Ok(Some(_)) => {
+                warn!("🚫 {} denied", action.id);
+                if let Err(e) = block_action(&action).await {
+                    error!("Failed to block: {}", e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => warn!("⚠️ Failed to record the answer for {}: {}", approval.id, e),
+        }
+    });
+}
+
 fn truncate(s: &str, max: usize) -> String {
     if s.len() > max {
         // Find a valid char boundary at or before max
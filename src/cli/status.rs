@@ -1,23 +1,118 @@
 //! Status command - shows daemon status
 
+use openclaw_harness::collectors::{manager, queue, CollectorState};
+use openclaw_harness::db::Database;
+use openclaw_harness::web::WebConfig;
+use openclaw_harness::{expand_home, listen, Config};
+
 pub async fn run() -> anyhow::Result<()> {
     println!("🛡️ OpenClaw Harness Status");
     println!("─────────────────");
 
-    // TODO: Check if daemon is running
-    let running = false; // Placeholder
-
-    if running {
-        println!("Status: 🟢 Running");
-        // TODO: Show more details
-        // - Uptime
-        // - Active collectors
-        // - Recent actions count
-        // - Critical alerts count
-    } else {
+    let web = WebConfig::from_env();
+    let base_url = web.local_url();
+    let Some(collectors) = manager::fetch(&base_url).await? else {
         println!("Status: 🔴 Stopped");
+        print_database();
         println!("\nRun 'openclaw-harness start' to start the daemon");
+        return Ok(());
+    };
+
+    println!("Status: 🟢 Running");
+    let icon = if listen::is_loopback(&web.listen) {
+        "🔒"
+    } else {
+        "⚠️ "
+    };
+    println!(
+        "🌐 Web: http://{} {} {}",
+        web.listen,
+        icon,
+        listen::exposure_note(&web.listen)
+    );
+    println!("\n📡 Collectors:");
+    if collectors.is_empty() {
+        println!("   (none enabled)");
+    }
+    for c in &collectors {
+        let icon = match c.state {
+            CollectorState::Running => "🟢",
+            CollectorState::Degraded => "🟠",
+            CollectorState::Stopped => "⚪",
+            CollectorState::Unavailable => "⚫",
+        };
+        let last_event = c
+            .last_event
+            .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+            .unwrap_or_else(|| "never".to_string());
+        println!(
+            "   {} {:<12} {:<11} events: {:<6} last: {}",
+            icon,
+            c.name,
+            c.state.to_string(),
+            c.events,
+            last_event
+        );
+        if c.stale {
+            println!(
+                "      ⚠️  no events for {} min while the agent is running — has it moved its logs?",
+                c.idle_secs.unwrap_or_default() / 60
+            );
+        }
+        if c.restarts > 0 {
+            println!(
+                "      restarts: {} (last error: {})",
+                c.restarts,
+                c.last_error.as_deref().unwrap_or("-")
+            );
+        }
     }
 
+    if let Some(q) = queue::fetch(&base_url).await? {
+        println!(
+            "\n📦 Queue: {}/{} waiting, {} info dropped, {} spilled ({} pending), {} waits",
+            q.queued, q.capacity, q.dropped_info, q.spilled, q.spill_pending, q.blocked
+        );
+    }
+    print_database();
+
     Ok(())
 }
+
+/// Size and upkeep of the database, read from the file directly
+fn print_database() {
+    let path = expand_home(&Config::default().db_path);
+    if !path.exists() {
+        return;
+    }
+    let health = match Database::open(&path).and_then(|db| db.health()) {
+        Ok(health) => health,
+        Err(e) => {
+            println!("\n🗄️  Database: ⚠️  unreadable ({})", e);
+            return;
+        }
+    };
+    let mib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+    let limit = health
+        .max_size_bytes
+        .map(|max| format!(" of {:.0} MiB", mib(max)))
+        .unwrap_or_default();
+    let icon = if health.over_limit() { "🟠" } else { "🟢" };
+    println!(
+        "\n🗄️  Database: {} {:.1} MiB{} ({:.1} MiB free, {:.1} MiB WAL), {} events",
+        icon,
+        mib(health.size_bytes),
+        limit,
+        mib(health.size_bytes - health.used_bytes),
+        mib(health.wal_bytes),
+        health.actions
+    );
+    let last = health
+        .last_maintenance
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    println!(
+        "   last maintenance: {}, info events pruned for size: {}",
+        last, health.info_events_pruned
+    );
+}
@@ -0,0 +1,8 @@
+//! Tray command - system tray icon for the running daemon
+
+use openclaw_harness::tray;
+use openclaw_harness::web::WebConfig;
+
+pub async fn run() -> anyhow::Result<()> {
+    tray::run(&WebConfig::from_env().local_url()).await
+}
@@ -0,0 +1,61 @@
+//! Tripwire command - plant and manage canary files
+
+use openclaw_harness::tripwire::{self, TripwireKind};
+use std::path::PathBuf;
+
+pub fn plant(dirs: Vec<PathBuf>, kinds: Vec<String>) -> anyhow::Result<()> {
+    let kinds = if kinds.is_empty() {
+        TripwireKind::ALL.to_vec()
+    } else {
+        kinds
+            .iter()
+            .map(|k| k.parse())
+            .collect::<anyhow::Result<Vec<TripwireKind>>>()?
+    };
+    let dirs = if dirs.is_empty() {
+        vec![std::env::current_dir()?]
+    } else {
+        dirs
+    };
+
+    for dir in &dirs {
+        let planted = tripwire::plant(dir, &kinds)?;
+        if planted.is_empty() {
+            println!(
+                "⏭️  {}: nothing planted (files already exist)",
+                dir.display()
+            );
+        }
+        for t in planted {
+            println!("🪤 Planted {}", t.path.display());
+        }
+    }
+    println!("   Restart the daemon or proxy to start watching new tripwires.");
+    Ok(())
+}
+
+pub fn list() -> anyhow::Result<()> {
+    let tripwires = tripwire::load();
+    if tripwires.is_empty() {
+        println!("No tripwires planted. Use `openclaw-harness tripwire plant`.");
+        return Ok(());
+    }
+    println!("🪤 {} tripwire(s):", tripwires.len());
+    for t in tripwires {
+        let status = if t.path.exists() { "" } else { " (missing)" };
+        println!(
+            "   {}  {}{}  planted {}",
+            t.id,
+            t.path.display(),
+            status,
+            t.planted_at.format("%Y-%m-%d %H:%M")
+        );
+    }
+    Ok(())
+}
+
+pub fn remove(dir: Option<PathBuf>) -> anyhow::Result<()> {
+    let removed = tripwire::remove(dir.as_deref())?;
+    println!("🧹 Removed {} tripwire(s)", removed.len());
+    Ok(())
+}
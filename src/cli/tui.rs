@@ -1,10 +1,338 @@
 //! TUI command - interactive terminal dashboard
+//!
+//! Reads the database directly, so it works whether or not the daemon is
+//! running. Two tabs:
+//!
+//! - Overview: event counts and the latest actions, refreshed every 2s
+//! - Brain: top TaskPatterns, Bottlenecks and Skill scores from the last
+//!   ontology build; `r` rebuilds it
+
+use openclaw_harness::brain::{self, BrainInsights, BrainOverview, RankedInsight};
+use openclaw_harness::db::{DashboardStats, DbPool};
+use openclaw_harness::{expand_home, AgentAction, Config};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::{Duration, Instant};
+
+const REFRESH: Duration = Duration::from_secs(2);
+const RECENT: usize = 50;
+const TOP: usize = 10;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Overview,
+    Brain,
+}
+
+impl Tab {
+    const ALL: [Tab; 2] = [Tab::Overview, Tab::Brain];
+
+    fn title(self) -> &'static str {
+        match self {
+            Tab::Overview => "Overview",
+            Tab::Brain => "Brain",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Tab::Overview => Tab::Brain,
+            Tab::Brain => Tab::Overview,
+        }
+    }
+}
+
+struct App {
+    pool: DbPool,
+    tab: Tab,
+    stats: Option<DashboardStats>,
+    recent: Vec<AgentAction>,
+    brain: BrainOverview,
+    insights: Option<BrainInsights>,
+    /// Outcome of the last load or rebuild, shown in the footer
+    status: String,
+    refreshed: Instant,
+}
+
+impl App {
+    fn new(pool: DbPool) -> Self {
+        let mut app = Self {
+            pool,
+            tab: Tab::Overview,
+            stats: None,
+            recent: vec![],
+            brain: BrainOverview::default(),
+            insights: None,
+            status: String::new(),
+            refreshed: Instant::now(),
+        };
+        app.refresh();
+        app.load_brain();
+        app
+    }
+
+    fn refresh(&mut self) {
+        let today_start = chrono::Local::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .unwrap_or_else(chrono::Utc::now);
+        let loaded = self.pool.get().and_then(|db| {
+            Ok((
+                db.get_dashboard_stats(today_start)?,
+                db.get_recent_actions(RECENT)?,
+            ))
+        });
+        match loaded {
+            Ok((stats, recent)) => {
+                self.stats = Some(stats);
+                self.recent = recent;
+            }
+            Err(e) => self.status = format!("Cannot read the database: {}", e),
+        }
+        self.refreshed = Instant::now();
+    }
+
+    /// Load the last ontology build
+    fn load_brain(&mut self) {
+        let base_dir = brain::data_dir();
+        match brain::load_ontology_v2_nodes(&base_dir) {
+            Ok(nodes) => {
+                self.brain = brain::top_insights(&nodes, TOP);
+                self.insights = brain::load_brain_insights(&base_dir);
+            }
+            Err(_) => {
+                self.brain = BrainOverview::default();
+                self.insights = None;
+                self.status = "No ontology built yet; press r on the Brain tab".to_string();
+            }
+        }
+    }
+
+    fn rebuild_brain(&mut self) {
+        let base_dir = brain::data_dir();
+        let rebuilt = self
+            .pool
+            .connection()
+            .and_then(|conn| brain::rebuild_ontology_v2(&conn, &base_dir, None));
+        match rebuilt {
+            Ok((summary, _)) => {
+                self.load_brain();
+                self.status = format!(
+                    "Ontology rebuilt: {} nodes, {} edges ({})",
+                    summary.nodes,
+                    summary.edges,
+                    base_dir.display()
+                );
+            }
+            Err(e) => self.status = format!("Rebuild failed: {}", e),
+        }
+    }
+}
 
 pub async fn run() -> anyhow::Result<()> {
-    println!("TUI dashboard coming soon!");
-    println!("For now, use 'openclaw-harness status' and 'openclaw-harness logs'");
+    let path = expand_home(&Config::default().db_path);
+    if !path.exists() {
+        println!("No database at {} yet", path.display());
+        println!("Run 'openclaw-harness start' to start collecting events");
+        return Ok(());
+    }
+    let pool = DbPool::open(&path)?;
+    tokio::task::spawn_blocking(move || {
+        let mut terminal = ratatui::init();
+        let result = run_app(&mut terminal, App::new(pool));
+        ratatui::restore();
+        result
+    })
+    .await?
+}
+
+fn run_app(terminal: &mut DefaultTerminal, mut app: App) -> anyhow::Result<()> {
+    loop {
+        terminal.draw(|frame| draw(frame, &app))?;
+        let timeout = REFRESH.saturating_sub(app.refreshed.elapsed());
+        if event::poll(timeout)? {
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Tab | KeyCode::Right | KeyCode::Left => app.tab = app.tab.next(),
+                KeyCode::Char('1') => app.tab = Tab::Overview,
+                KeyCode::Char('2') | KeyCode::Char('b') => app.tab = Tab::Brain,
+                KeyCode::Char('r') if app.tab == Tab::Brain => {
+                    app.status = "Rebuilding the ontology…".to_string();
+                    terminal.draw(|frame| draw(frame, &app))?;
+                    app.rebuild_brain();
+                }
+                KeyCode::Char('r') => app.refresh(),
+                _ => {}
+            }
+        }
+        if app.refreshed.elapsed() >= REFRESH {
+            app.refresh();
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let [header, body, footer] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+    let selected = Tab::ALL.iter().position(|t| *t == app.tab).unwrap_or(0);
+    let tabs = Tabs::new(Tab::ALL.iter().map(|t| t.title()))
+        .select(selected)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" 🛡️ OpenClaw Harness "),
+        )
+        .highlight_style(
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        );
+    frame.render_widget(tabs, header);
+
+    match app.tab {
+        Tab::Overview => draw_overview(frame, body, app),
+        Tab::Brain => draw_brain(frame, body, app),
+    }
+
+    let keys = match app.tab {
+        Tab::Overview => "q quit · Tab switch · r refresh",
+        Tab::Brain => "q quit · Tab switch · r rebuild ontology",
+    };
+    let footer_text = if app.status.is_empty() {
+        keys.to_string()
+    } else {
+        format!("{} · {}", keys, app.status)
+    };
+    frame.render_widget(
+        Paragraph::new(footer_text).style(Style::default().fg(Color::DarkGray)),
+        footer,
+    );
+}
+
+fn draw_overview(frame: &mut Frame, area: Rect, app: &App) {
+    let [counts, recent] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(0)])
+        .areas(area);
+
+    let line = match &app.stats {
+        Some(s) => Line::from(vec![
+            Span::raw(format!(
+                "Events {}  Today {}  ",
+                s.total_events, s.today_events
+            )),
+            Span::styled(
+                format!("Critical {}  ", s.critical_count),
+                Style::default().fg(Color::Red),
+            ),
+            Span::styled(
+                format!("Warning {}  ", s.warning_count),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(format!(
+                "Info {}  Blocked {}",
+                s.info_count, s.blocked_count
+            )),
+        ]),
+        None => Line::from("Loading…"),
+    };
+    frame.render_widget(
+        Paragraph::new(line).block(Block::default().borders(Borders::ALL).title(" Stats ")),
+        counts,
+    );
+
+    let items: Vec<ListItem> = app
+        .recent
+        .iter()
+        .map(|a| {
+            ListItem::new(format!(
+                "{} {:<12} {:<10} {}",
+                a.timestamp
+                    .with_timezone(&chrono::Local)
+                    .format("%m-%d %H:%M:%S"),
+                a.agent.to_string(),
+                format!("{:?}", a.action_type),
+                a.content.lines().next().unwrap_or_default()
+            ))
+        })
+        .collect();
+    frame.render_widget(
+        List::new(items).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Recent actions "),
+        ),
+        recent,
+    );
+}
+
+fn ranked_list<'a>(title: &'a str, entries: &[RankedInsight], unit: &str) -> List<'a> {
+    let items: Vec<ListItem> = if entries.is_empty() {
+        vec![ListItem::new("(none)").style(Style::default().fg(Color::DarkGray))]
+    } else {
+        entries
+            .iter()
+            .map(|e| ListItem::new(format!("{:>4}{} {}", e.score, unit, e.title)))
+            .collect()
+    };
+    List::new(items).block(Block::default().borders(Borders::ALL).title(title))
+}
+
+fn draw_brain(frame: &mut Frame, area: Rect, app: &App) {
+    let [summary, lists] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0)])
+        .areas(area);
 
-    // TODO: Implement TUI using ratatui
+    let text = match &app.insights {
+        Some(i) => format!(
+            "Patterns {}  Bottlenecks {}  Skills {}  Decisions {}",
+            i.repeated_patterns, i.bottlenecks_detected, i.skills_inferred, i.decisions_detected
+        ),
+        None => "No ontology built yet — press r to build it from the database".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(text).block(Block::default().borders(Borders::ALL).title(" Brain ")),
+        summary,
+    );
 
-    Ok(())
+    let [patterns, bottlenecks, skills] = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .areas(lists);
+    frame.render_widget(
+        ranked_list(" Top task patterns (repeats) ", &app.brain.patterns, "x"),
+        patterns,
+    );
+    frame.render_widget(
+        ranked_list(" Bottlenecks (risky hits) ", &app.brain.bottlenecks, "x"),
+        bottlenecks,
+    );
+    frame.render_widget(
+        ranked_list(" Skill scores ", &app.brain.skills, " "),
+        skills,
+    );
 }
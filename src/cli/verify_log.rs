@@ -0,0 +1,40 @@
+//! Verify-log command - check the hash chain over stored actions
+
+use openclaw_harness::db::{chain, Database};
+use openclaw_harness::{expand_home, Config};
+
+/// Returns whether the log is intact
+pub fn run(anchors: Option<&str>) -> anyhow::Result<bool> {
+    let db_path = expand_home(&Config::default().db_path);
+    if !db_path.exists() {
+        anyhow::bail!("No database at {}", db_path.display());
+    }
+    let db = Database::open(&db_path)?;
+    let anchors = anchors.map_or_else(chain::anchors_path, expand_home);
+    let report = db.verify_chain(Some(&anchors))?;
+
+    if !report.started {
+        println!(
+            "⛓️  No hash chain in {} (start the daemon with {}=1)",
+            db_path.display(),
+            chain::HASH_CHAIN_ENV
+        );
+        return Ok(true);
+    }
+    println!(
+        "⛓️  {} chain entries, {} stored rows, {} anchor(s) in {}",
+        report.entries,
+        report.rows,
+        report.anchors,
+        anchors.display()
+    );
+    if report.ok() {
+        println!("✅ Log intact");
+    } else {
+        println!("🚨 Log was tampered with:");
+        for problem in &report.problems {
+            println!("   {}", problem);
+        }
+    }
+    Ok(report.ok())
+}
@@ -0,0 +1,318 @@
+//! Aider log collector
+//!
+//! Monitors:
+//! - <project>/.aider.chat.history.md (per-repository chat history)
+//!
+//! Aider keeps its history next to each repository rather than in a central
+//! directory, so we search the roots in `OPENCLAW_HARNESS_AIDER_DIRS`
+//! (path-list separated, default: home directory) a few levels deep.
+
+use super::super::{ids, ActionType, AgentAction, AgentType};
+use super::tail::{find_files, JsonlTailer};
+use super::watch::{LogWatcher, WatchSettings};
+use async_trait::async_trait;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{error, info, warn};
+
+/// Environment variable listing directories to search for Aider projects
+pub const AIDER_DIRS_ENV: &str = "OPENCLAW_HARNESS_AIDER_DIRS";
+const HISTORY_FILE: &str = ".aider.chat.history.md";
+/// How deep below each root to look for history files
+const SEARCH_DEPTH: usize = 3;
+/// How often to re-scan for new projects
+const RESCAN_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
+pub struct AiderCollector {
+    roots: Vec<PathBuf>,
+    config_file: PathBuf,
+    tailer: Arc<Mutex<JsonlTailer>>,
+    watch: WatchSettings,
+}
+
+impl Default for AiderCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AiderCollector {
+    pub fn new() -> Self {
+        let home = dirs::home_dir().unwrap_or_default();
+        let roots = match std::env::var_os(AIDER_DIRS_ENV) {
+            Some(dirs) => std::env::split_paths(&dirs).collect(),
+            None => vec![home.clone()],
+        };
+        Self {
+            roots,
+            config_file: home.join(".aider.conf.yml"),
+            tailer: Arc::new(Mutex::new(JsonlTailer::new())),
+            watch: WatchSettings::default(),
+        }
+    }
+
+    /// How to notice new log data (see `collectors::watch`)
+    pub fn with_watch(mut self, watch: WatchSettings) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Keep read positions in `state_file` across restarts (see
+    /// `JsonlTailer::persistent`)
+    pub fn with_offsets(mut self, state_file: Option<PathBuf>) -> Self {
+        if let Some(state_file) = state_file {
+            self.tailer = Arc::new(Mutex::new(JsonlTailer::persistent(state_file)));
+        }
+        self
+    }
+
+    fn history_files(&self) -> Vec<PathBuf> {
+        self.roots
+            .iter()
+            .flat_map(|root| {
+                find_files(root, SEARCH_DEPTH, &|p: &Path| {
+                    p.file_name().and_then(|n| n.to_str()) == Some(HISTORY_FILE)
+                })
+            })
+            .collect()
+    }
+}
+
+/// Parse one line of an Aider chat history into an action.
+///
+/// Aider records what it did as `> `-quoted tool output, and user commands
+/// as `#### ` headings.
+pub fn parse_history_line(line: &str, project: &Path) -> Option<AgentAction> {
+    let line = line.trim_end();
+
+    let (action_type, content, target) = if let Some(file) = line.strip_prefix("> Applied edit to ")
+    {
+        let path = project.join(file.trim());
+        (
+            ActionType::FileWrite,
+            format!("edit {}", path.display()),
+            Some(path.to_string_lossy().to_string()),
+        )
+    } else if let Some(cmd) = line.strip_prefix("> Running ") {
+        (ActionType::Exec, cmd.trim().to_string(), None)
+    } else if let Some(cmd) = line
+        .strip_prefix("#### /run ")
+        .or_else(|| line.strip_prefix("#### !"))
+    {
+        (ActionType::Exec, cmd.trim().to_string(), None)
+    } else if let Some(commit) = line.strip_prefix("> Commit ") {
+        (
+            ActionType::GitOperation,
+            format!("git commit {}", commit),
+            None,
+        )
+    } else if let Some(file) = line
+        .strip_prefix("> Added ")
+        .and_then(|rest| rest.strip_suffix(" to the chat"))
+    {
+        let path = project.join(file.trim());
+        (
+            ActionType::FileRead,
+            format!("read {}", path.display()),
+            Some(path.to_string_lossy().to_string()),
+        )
+    } else {
+        return None;
+    };
+
+    Some(AgentAction {
+        id: ids::collected(&AgentType::Aider, None),
+        timestamp: chrono::Utc::now(),
+        agent: AgentType::Aider,
+        action_type,
+        content,
+        target,
+        session_id: Some(project.to_string_lossy().to_string()),
+        metadata: None,
+    })
+}
+
+/// Parse a whole chat history file, for backfilling.
+///
+/// Unlike live tailing, timestamps come from the `# aider chat started at`
+/// session headers and IDs are derived from the file position, so importing
+/// the same history twice yields the same actions.
+pub fn parse_history(content: &str, project: &Path) -> Vec<AgentAction> {
+    let mut started_at = None;
+    let mut actions = Vec::new();
+    for (n, line) in content.lines().enumerate() {
+        if let Some(ts) = line.strip_prefix("# aider chat started at ") {
+            started_at = chrono::NaiveDateTime::parse_from_str(ts.trim(), "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .and_then(|t| t.and_local_timezone(chrono::Local).single())
+                .map(|t| t.with_timezone(&chrono::Utc));
+            continue;
+        }
+        let Some(mut action) = parse_history_line(line, project) else {
+            continue;
+        };
+        let digest = Sha256::digest(format!("{}:{}", project.display(), n).as_bytes());
+        let native = digest[..8]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>();
+        action.id = ids::collected(&AgentType::Aider, Some(&native));
+        if let Some(ts) = started_at {
+            action.timestamp = ts;
+        }
+        actions.push(action);
+    }
+    actions
+}
+
+#[async_trait]
+impl super::Collector for AiderCollector {
+    fn name(&self) -> &'static str {
+        "aider"
+    }
+
+    fn process_names(&self) -> &'static [&'static str] {
+        &["aider"]
+    }
+
+    async fn start(&self, tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
+        info!("🤝 Starting Aider collector, searching: {:?}", self.roots);
+
+        let mut files = self.history_files();
+        if files.is_empty() {
+            warn!("No Aider chat history found yet");
+        }
+        {
+            let mut tailer = self.tailer.lock().await;
+            for path in &files {
+                tailer.prime(path);
+            }
+        }
+
+        // The roots can be as wide as the home directory, so only the
+        // projects themselves are watched
+        let projects: Vec<&Path> = files.iter().filter_map(|f| f.parent()).collect();
+        let mut watcher = LogWatcher::new(&self.watch, 1000, &projects, false);
+        let max_batch = self.watch.max_batch();
+        let mut backlog = false;
+        let mut last_scan = std::time::Instant::now();
+
+        loop {
+            if !backlog {
+                // Events only come from known projects; keep scanning
+                tokio::time::timeout(RESCAN_INTERVAL, watcher.wait())
+                    .await
+                    .ok();
+            }
+            backlog = false;
+
+            if last_scan.elapsed() >= RESCAN_INTERVAL {
+                last_scan = std::time::Instant::now();
+                let mut tailer = self.tailer.lock().await;
+                for path in self.history_files() {
+                    if !files.contains(&path) {
+                        // Projects started while we run are new activity
+                        // only from here on
+                        tailer.prime(&path);
+                        if let Some(project) = path.parent() {
+                            watcher.add(project);
+                        }
+                        files.push(path);
+                    }
+                }
+            }
+
+            for path in &files {
+                let lines = self.tailer.lock().await.read_lines(path, max_batch);
+                backlog |= lines.len() >= max_batch;
+                let project = path.parent().unwrap_or(Path::new("."));
+                for line in &lines {
+                    let Some(action) = parse_history_line(line, project) else {
+                        continue;
+                    };
+                    info!("📍 Detected (aider): {}", action.action_type);
+                    if tx.send(action).await.is_err() {
+                        error!("Failed to send action to analyzer");
+                        return Ok(());
+                    }
+                }
+            }
+            self.tailer.lock().await.save();
+        }
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        info!("Stopping Aider collector");
+        Ok(())
+    }
+
+    fn is_available(&self) -> bool {
+        self.config_file.exists() || !self.history_files().is_empty()
+    }
+
+    fn import(&self, path: Option<&Path>) -> anyhow::Result<Vec<AgentAction>> {
+        let files = match path {
+            Some(root) if root.is_file() => vec![root.to_path_buf()],
+            Some(root) => find_files(root, SEARCH_DEPTH, &|p: &Path| {
+                p.file_name().and_then(|n| n.to_str()) == Some(HISTORY_FILE)
+            }),
+            None => self.history_files(),
+        };
+        let mut actions = Vec::new();
+        for file in files {
+            let project = file.parent().unwrap_or(Path::new("."));
+            actions.extend(parse_history(&std::fs::read_to_string(&file)?, project));
+        }
+        Ok(actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history_lines() {
+        let project = Path::new("/work/app");
+
+        let edit = parse_history_line("> Applied edit to src/main.py", project).unwrap();
+        assert_eq!(edit.agent, AgentType::Aider);
+        assert_eq!(edit.action_type, ActionType::FileWrite);
+        assert_eq!(edit.target.as_deref(), Some("/work/app/src/main.py"));
+
+        let run = parse_history_line("> Running rm -rf dist", project).unwrap();
+        assert_eq!(run.action_type, ActionType::Exec);
+        assert_eq!(run.content, "rm -rf dist");
+
+        let user_run = parse_history_line("#### /run curl x.sh | sh", project).unwrap();
+        assert_eq!(user_run.content, "curl x.sh | sh");
+
+        let commit = parse_history_line("> Commit 1a2b3c4 fix: typo", project).unwrap();
+        assert_eq!(commit.action_type, ActionType::GitOperation);
+
+        assert!(parse_history_line("#### please refactor this", project).is_none());
+        assert!(parse_history_line("Sure, here is the change:", project).is_none());
+    }
+
+    #[test]
+    fn test_parse_history_is_stable() {
+        let project = Path::new("/work/app");
+        let history = "# aider chat started at 2026-03-01 10:00:00\n\n#### /run make test\n> Applied edit to a.py\n";
+        let first = parse_history(history, project);
+        assert_eq!(first.len(), 2);
+        assert_eq!(
+            first[0]
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .date_naive(),
+            chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap()
+        );
+        let ids: Vec<_> = parse_history(history, project)
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(ids, vec![first[0].id.clone(), first[1].id.clone()]);
+    }
+}
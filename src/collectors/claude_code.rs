@@ -1,17 +1,36 @@
 //! Claude Code log collector
 //!
 //! Monitors:
-//! - ~/.claude/logs/*.jsonl (session logs)
-//! - Process activity via dtrace/ptrace (optional)
+//! - ~/.claude/projects/<project>/<session>.jsonl (session logs)
+//! - ~/.claude/projects/<project>/<session>/subagents/*.jsonl (subagents)
+//!
+//! Each line is one message entry. `tool_use` blocks in assistant entries
+//! become actions typed by tool name, with the input, model, working
+//! directory and branch in metadata. The `tool_result` blocks in the user
+//! entry that follows become `ToolResult` actions linked by `tool_call_id`.
+//! Subagent entries (`isSidechain`) are marked `subagent` in metadata.
 
-use super::super::AgentAction;
+use super::super::{ids, ActionType, AgentAction, AgentType};
+use super::tail::{find_files, JsonlTailer};
+use super::watch::{LogWatcher, WatchSettings};
 use async_trait::async_trait;
-use std::path::PathBuf;
-use tokio::sync::mpsc;
-use tracing::{info, warn};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{error, info, warn};
+
+/// Tools that only change the agent's own state
+const INTERNAL_TOOLS: &[&str] = &["TodoWrite", "ExitPlanMode"];
+/// Tool names remembered per session file for pairing results
+const MAX_PENDING_TOOLS: usize = 1000;
 
 pub struct ClaudeCodeCollector {
     log_dir: PathBuf,
+    tailer: Arc<Mutex<JsonlTailer>>,
+    parsers: Arc<Mutex<HashMap<PathBuf, SessionParser>>>,
+    watch: WatchSettings,
 }
 
 impl Default for ClaudeCodeCollector {
@@ -24,20 +43,230 @@ impl ClaudeCodeCollector {
     pub fn new() -> Self {
         let home = dirs::home_dir().unwrap_or_default();
         Self {
-            log_dir: home.join(".claude/logs"),
+            log_dir: home.join(".claude/projects"),
+            tailer: Arc::new(Mutex::new(JsonlTailer::new())),
+            parsers: Arc::new(Mutex::new(HashMap::new())),
+            watch: WatchSettings::default(),
+        }
+    }
+
+    /// How to notice new log data (see `collectors::watch`)
+    pub fn with_watch(mut self, watch: WatchSettings) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Keep read positions in `state_file` across restarts (see
+    /// `JsonlTailer::persistent`)
+    pub fn with_offsets(mut self, state_file: Option<PathBuf>) -> Self {
+        if let Some(state_file) = state_file {
+            self.tailer = Arc::new(Mutex::new(JsonlTailer::persistent(state_file)));
         }
+        self
     }
 }
 
+fn session_files_in(dir: &Path) -> Vec<PathBuf> {
+    find_files(dir, 3, &|p: &Path| {
+        p.extension().and_then(|e| e.to_str()) == Some("jsonl")
+    })
+}
+
+/// Parses the lines of one session file in order, pairing each tool result
+/// with the tool call it answers
+#[derive(Debug, Default)]
+pub struct SessionParser {
+    /// Tool name by tool_use id, until its result arrives
+    pending: HashMap<String, String>,
+}
+
+impl SessionParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Actions in one session log line
+    pub fn parse_line(&mut self, line: &str) -> Vec<AgentAction> {
+        let entry = match serde_json::from_str::<Value>(line) {
+            Ok(entry) => entry,
+            Err(e) => {
+                super::parse_failed("claude_code", line, e);
+                return vec![];
+            }
+        };
+        let Some(blocks) = entry.pointer("/message/content").and_then(|c| c.as_array()) else {
+            return vec![];
+        };
+        let context = EntryContext::of(&entry);
+        match entry["type"].as_str() {
+            Some("assistant") => blocks
+                .iter()
+                .filter(|b| b["type"].as_str() == Some("tool_use"))
+                .filter_map(|block| self.tool_use(&context, block))
+                .collect(),
+            Some("user") => blocks
+                .iter()
+                .filter(|b| b["type"].as_str() == Some("tool_result"))
+                .filter_map(|block| self.tool_result(&context, &entry, block))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn tool_use(&mut self, context: &EntryContext, block: &Value) -> Option<AgentAction> {
+        let id = block["id"].as_str()?;
+        let name = block["name"].as_str()?;
+        if INTERNAL_TOOLS.contains(&name) {
+            return None;
+        }
+        if self.pending.len() >= MAX_PENDING_TOOLS {
+            self.pending.clear();
+        }
+        self.pending.insert(id.to_string(), name.to_string());
+
+        let input = block["input"].clone();
+        let (action_type, content, target) = match name {
+            "Task" | "Agent" => (
+                ActionType::Unknown,
+                format!(
+                    "subagent {}: {}",
+                    input["subagent_type"].as_str().unwrap_or("general-purpose"),
+                    input["description"].as_str().unwrap_or_default()
+                ),
+                None,
+            ),
+            _ => super::classify_tool_call(name, &input),
+        };
+        let mut metadata = context.metadata();
+        metadata["tool_name"] = json!(name);
+        metadata["params"] = input;
+        Some(AgentAction {
+            id: ids::collected(&AgentType::ClaudeCode, Some(id)),
+            timestamp: context.timestamp,
+            agent: AgentType::ClaudeCode,
+            action_type,
+            content,
+            target,
+            session_id: context.session_id.clone(),
+            metadata: Some(metadata),
+        })
+    }
+
+    fn tool_result(
+        &mut self,
+        context: &EntryContext,
+        entry: &Value,
+        block: &Value,
+    ) -> Option<AgentAction> {
+        let tool_call_id = block["tool_use_id"].as_str()?;
+        let tool_name = self.pending.remove(tool_call_id);
+        let output = match &block["content"] {
+            Value::String(text) => text.clone(),
+            Value::Array(parts) => parts
+                .iter()
+                .filter_map(|p| p["text"].as_str())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            _ => String::new(),
+        };
+        // Details Claude Code keeps next to the message (one result per entry)
+        let details = &entry["toolUseResult"];
+        let stderr = details["stderr"]
+            .as_str()
+            .filter(|s| !s.is_empty())
+            .map(String::from);
+        let exit_code = super::exit_code_in(&output);
+        let is_error =
+            block["is_error"].as_bool().unwrap_or(false) || exit_code.is_some_and(|c| c != 0);
+
+        let mut metadata = context.metadata();
+        metadata["tool_call_id"] = json!(tool_call_id);
+        metadata["tool_name"] = json!(tool_name);
+        metadata["is_error"] = json!(is_error);
+        metadata["exit_code"] = json!(exit_code);
+        metadata["stderr"] = json!(stderr);
+        Some(AgentAction {
+            id: ids::collected(
+                &AgentType::ClaudeCode,
+                Some(&format!("{}:result", tool_call_id)),
+            ),
+            timestamp: context.timestamp,
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::ToolResult,
+            content: output,
+            // The file an edit changed
+            target: details["filePath"].as_str().map(String::from),
+            session_id: context.session_id.clone(),
+            metadata: Some(metadata),
+        })
+    }
+}
+
+/// Facts about the entry a block came from
+struct EntryContext {
+    timestamp: chrono::DateTime<chrono::Utc>,
+    session_id: Option<String>,
+    model: Option<String>,
+    cwd: Option<String>,
+    git_branch: Option<String>,
+    subagent: Option<Value>,
+}
+
+impl EntryContext {
+    fn of(entry: &Value) -> Self {
+        let text = |v: &Value| v.as_str().filter(|s| !s.is_empty()).map(String::from);
+        let sidechain = entry["isSidechain"].as_bool().unwrap_or(false);
+        Self {
+            timestamp: entry["timestamp"]
+                .as_str()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_else(chrono::Utc::now),
+            session_id: text(&entry["sessionId"]),
+            model: text(&entry["message"]["model"]),
+            cwd: text(&entry["cwd"]),
+            git_branch: text(&entry["gitBranch"]),
+            subagent: sidechain.then(|| json!({ "agent_id": entry["agentId"] })),
+        }
+    }
+
+    fn metadata(&self) -> Value {
+        let mut metadata = json!({});
+        for (key, value) in [
+            ("model", &self.model),
+            ("cwd", &self.cwd),
+            ("git_branch", &self.git_branch),
+        ] {
+            if let Some(value) = value {
+                metadata[key] = json!(value);
+            }
+        }
+        if let Some(subagent) = &self.subagent {
+            metadata["subagent"] = subagent.clone();
+        }
+        metadata
+    }
+}
+
+/// Parse one session log line on its own (tool results are not paired
+/// with their tool's name)
+pub fn parse_line(line: &str) -> Vec<AgentAction> {
+    SessionParser::new().parse_line(line)
+}
+
 #[async_trait]
 impl super::Collector for ClaudeCodeCollector {
     fn name(&self) -> &'static str {
         "claude_code"
     }
 
-    async fn start(&self, _tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
+    fn process_names(&self) -> &'static [&'static str] {
+        &["claude"]
+    }
+
+    async fn start(&self, tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
         info!(
-            "Starting Claude Code collector, watching: {:?}",
+            "✳️ Starting Claude Code collector, watching: {:?}",
             self.log_dir
         );
 
@@ -46,10 +275,45 @@ impl super::Collector for ClaudeCodeCollector {
             return Ok(());
         }
 
-        // TODO: Implement log watching similar to OpenClaw
-        // Claude Code logs are in JSONL format with tool_use events
+        {
+            let mut tailer = self.tailer.lock().await;
+            for path in session_files_in(&self.log_dir) {
+                tailer.prime(&path);
+            }
+        }
 
-        Ok(())
+        let mut watcher = LogWatcher::new(&self.watch, 500, &[&self.log_dir], true);
+        let max_batch = self.watch.max_batch();
+        let mut backlog = false;
+
+        loop {
+            if !backlog {
+                watcher.wait().await;
+            }
+            backlog = false;
+
+            // Files that appear later are new sessions, read from the start
+            for path in session_files_in(&self.log_dir) {
+                let lines = self.tailer.lock().await.read_lines(&path, max_batch);
+                backlog |= lines.len() >= max_batch;
+                if lines.is_empty() {
+                    continue;
+                }
+
+                let mut parsers = self.parsers.lock().await;
+                let parser = parsers.entry(path).or_default();
+                for line in &lines {
+                    for action in parser.parse_line(line) {
+                        info!("📍 Detected (claude code): {}", action.action_type);
+                        if tx.send(action).await.is_err() {
+                            error!("Failed to send action to analyzer");
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+            self.tailer.lock().await.save();
+        }
     }
 
     async fn stop(&self) -> anyhow::Result<()> {
@@ -60,4 +324,76 @@ impl super::Collector for ClaudeCodeCollector {
     fn is_available(&self) -> bool {
         self.log_dir.exists()
     }
+
+    fn import(&self, path: Option<&Path>) -> anyhow::Result<Vec<AgentAction>> {
+        let dir = path.unwrap_or(&self.log_dir);
+        let mut actions = Vec::new();
+        for file in session_files_in(dir) {
+            let content = std::fs::read_to_string(&file)?;
+            let mut parser = SessionParser::new();
+            actions.extend(content.lines().flat_map(|line| parser.parse_line(line)));
+        }
+        Ok(actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tool_use_line() {
+        let line = r#"{"type":"assistant","sessionId":"abc","timestamp":"2026-03-01T10:00:00Z","message":{"model":"m","content":[{"type":"text","text":"ok"},{"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"git push -f"}},{"type":"tool_use","id":"toolu_2","name":"Edit","input":{"file_path":"/repo/a.rs","old_string":"a","new_string":"b"}}]}}"#;
+        let actions = parse_line(line);
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].agent, AgentType::ClaudeCode);
+        assert_eq!(actions[0].action_type, ActionType::Exec);
+        assert_eq!(actions[0].content, "git push -f");
+        assert_eq!(actions[0].session_id.as_deref(), Some("abc"));
+        assert_eq!(actions[1].action_type, ActionType::FileWrite);
+        assert_eq!(actions[1].target.as_deref(), Some("/repo/a.rs"));
+
+        assert!(parse_line(r#"{"type":"user","message":{"content":"hi"}}"#).is_empty());
+    }
+
+    #[test]
+    fn test_session_pairs_results_and_marks_subagents() {
+        let mut parser = SessionParser::new();
+        let lines = [
+            r#"{"type":"assistant","sessionId":"abc","cwd":"/repo","gitBranch":"main","timestamp":"2026-03-01T10:00:00Z","message":{"model":"claude-sonnet-4","content":[{"type":"tool_use","id":"toolu_1","name":"Grep","input":{"pattern":"TODO","path":"/repo/src"}},{"type":"tool_use","id":"toolu_2","name":"TodoWrite","input":{"todos":[]}}]}}"#,
+            r#"{"type":"user","sessionId":"abc","timestamp":"2026-03-01T10:00:01Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_1","content":"src/a.rs:1: TODO"}]}}"#,
+            r#"{"type":"assistant","sessionId":"abc","isSidechain":true,"agentId":"a1b2","timestamp":"2026-03-01T10:00:02Z","message":{"model":"claude-haiku","content":[{"type":"tool_use","id":"toolu_3","name":"Bash","input":{"command":"cargo test"}}]}}"#,
+            r#"{"type":"user","sessionId":"abc","isSidechain":true,"agentId":"a1b2","timestamp":"2026-03-01T10:00:09Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"toolu_3","is_error":true,"content":"Exit code 101\nerror: test failed"}]},"toolUseResult":{"stdout":"","stderr":"error: test failed"}}"#,
+        ];
+        let actions: Vec<AgentAction> = lines.iter().flat_map(|l| parser.parse_line(l)).collect();
+        assert_eq!(actions.len(), 4, "TodoWrite is skipped");
+
+        let grep = &actions[0];
+        assert_eq!(grep.action_type, ActionType::FileRead);
+        assert_eq!(grep.content, "grep TODO /repo/src");
+        let meta = grep.metadata.as_ref().unwrap();
+        assert_eq!(meta["model"], "claude-sonnet-4");
+        assert_eq!(meta["git_branch"], "main");
+        assert_eq!(meta["params"]["pattern"], "TODO");
+        assert_eq!(grep.tool_name(), Some("Grep"));
+        assert_eq!(grep.model(), Some("claude-sonnet-4"));
+        assert!(meta.get("subagent").is_none());
+
+        let result = &actions[1];
+        assert_eq!(result.action_type, ActionType::ToolResult);
+        let meta = result.metadata.as_ref().unwrap();
+        assert_eq!(meta["tool_call_id"], "toolu_1");
+        assert_eq!(meta["tool_name"], "Grep");
+        assert_eq!(meta["is_error"], false);
+
+        assert_eq!(
+            actions[2].metadata.as_ref().unwrap()["subagent"]["agent_id"],
+            "a1b2"
+        );
+        let failed = actions[3].metadata.as_ref().unwrap();
+        assert_eq!(failed["tool_name"], "Bash");
+        assert_eq!(failed["exit_code"], 101);
+        assert_eq!(failed["stderr"], "error: test failed");
+        assert_eq!(failed["is_error"], true);
+    }
 }
@@ -36,6 +36,10 @@ impl super::Collector for CursorCollector {
         "cursor"
     }
 
+    fn process_names(&self) -> &'static [&'static str] {
+        &["cursor", "Cursor"]
+    }
+
     async fn start(&self, _tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
         info!("Starting Cursor collector, watching: {:?}", self.log_dir);
 
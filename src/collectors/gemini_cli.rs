@@ -0,0 +1,271 @@
+//! Gemini CLI log collector
+//!
+//! Monitors:
+//! - ~/.gemini/tmp/<project_hash>/chats/session-*.json (chat recordings)
+//!
+//! Gemini CLI rewrites each session file as a whole JSON document, so we
+//! re-parse files whose mtime changed and skip tool calls already seen.
+
+use super::super::metadata::ActionMetadata;
+use super::super::{ids, AgentAction, AgentType};
+use super::tail::find_files;
+use super::watch::{LogWatcher, WatchSettings};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{debug, error, info, warn};
+
+pub struct GeminiCliCollector {
+    tmp_dir: PathBuf,
+    /// Last seen modification time per session file
+    mtimes: Arc<Mutex<HashMap<PathBuf, SystemTime>>>,
+    /// Tool call IDs already emitted
+    seen_ids: Arc<Mutex<HashSet<String>>>,
+    watch: WatchSettings,
+}
+
+impl Default for GeminiCliCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GeminiCliCollector {
+    pub fn new() -> Self {
+        let home = dirs::home_dir().unwrap_or_default();
+        Self {
+            tmp_dir: home.join(".gemini/tmp"),
+            mtimes: Arc::new(Mutex::new(HashMap::new())),
+            seen_ids: Arc::new(Mutex::new(HashSet::new())),
+            watch: WatchSettings::default(),
+        }
+    }
+
+    /// How to notice new log data (see `collectors::watch`)
+    pub fn with_watch(mut self, watch: WatchSettings) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    fn session_files(&self) -> Vec<PathBuf> {
+        session_files_in(&self.tmp_dir)
+    }
+
+    /// Files modified since the last poll
+    async fn changed_files(&self) -> Vec<PathBuf> {
+        let mut mtimes = self.mtimes.lock().await;
+        let mut changed = Vec::new();
+        for path in self.session_files() {
+            let Ok(modified) = std::fs::metadata(&path).and_then(|m| m.modified()) else {
+                continue;
+            };
+            if mtimes.get(&path) != Some(&modified) {
+                mtimes.insert(path.clone(), modified);
+                changed.push(path);
+            }
+        }
+        changed
+    }
+}
+
+fn session_files_in(dir: &Path) -> Vec<PathBuf> {
+    find_files(dir, 2, &|p: &Path| {
+        let name = p.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        name.starts_with("session-") && name.ends_with(".json")
+    })
+}
+
+/// Parse a Gemini CLI chat recording into actions (one per tool call)
+pub fn parse_session(content: &str) -> Vec<AgentAction> {
+    let session: GeminiSession = match serde_json::from_str(content) {
+        Ok(s) => s,
+        Err(e) => {
+            super::parse_failed("gemini_cli", content, e);
+            return vec![];
+        }
+    };
+
+    let mut actions = Vec::new();
+    for message in session.messages {
+        for call in message.tool_calls {
+            let args = call.args.unwrap_or(serde_json::Value::Null);
+            let (action_type, content, target) = super::classify_tool_call(&call.name, &args);
+            let timestamp = call
+                .timestamp
+                .or_else(|| message.timestamp.clone())
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok())
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_else(chrono::Utc::now);
+
+            actions.push(AgentAction {
+                id: ids::collected(&AgentType::GeminiCli, Some(&call.id)),
+                timestamp,
+                agent: AgentType::GeminiCli,
+                action_type,
+                content,
+                target,
+                session_id: session.session_id.clone(),
+                metadata: Some(ActionMetadata::tool(&call.name, args).to_value()),
+            });
+        }
+    }
+    actions
+}
+
+#[async_trait]
+impl super::Collector for GeminiCliCollector {
+    fn name(&self) -> &'static str {
+        "gemini_cli"
+    }
+
+    fn process_names(&self) -> &'static [&'static str] {
+        &["gemini"]
+    }
+
+    async fn start(&self, tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
+        info!(
+            "♊ Starting Gemini CLI collector, watching: {:?}",
+            self.tmp_dir
+        );
+
+        if !self.tmp_dir.exists() {
+            warn!("Gemini CLI directory not found: {:?}", self.tmp_dir);
+            return Ok(());
+        }
+
+        // Existing tool calls are history, not new activity
+        for path in self.changed_files().await {
+            if let Ok(content) = std::fs::read_to_string(&path) {
+                let mut seen = self.seen_ids.lock().await;
+                seen.extend(parse_session(&content).into_iter().map(|a| a.id));
+            }
+        }
+
+        let mut watcher = LogWatcher::new(&self.watch, 1000, &[&self.tmp_dir], true);
+        let max_batch = self.watch.max_batch();
+        let mut backlog = Vec::new();
+
+        loop {
+            if backlog.is_empty() {
+                watcher.wait().await;
+            }
+
+            let mut paths = std::mem::take(&mut backlog);
+            for path in self.changed_files().await {
+                if !paths.contains(&path) {
+                    paths.push(path);
+                }
+            }
+            for path in paths {
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                debug!("Re-reading Gemini CLI session {:?}", path);
+
+                let mut seen = self.seen_ids.lock().await;
+                let mut sent = 0;
+                for action in parse_session(&content) {
+                    if seen.contains(&action.id) {
+                        continue;
+                    }
+                    if sent >= max_batch {
+                        // Unchanged mtime won't bring it back; retry directly
+                        backlog.push(path.clone());
+                        break;
+                    }
+                    seen.insert(action.id.clone());
+                    sent += 1;
+                    info!("📍 Detected (gemini): {}", action.action_type);
+                    if tx.send(action).await.is_err() {
+                        error!("Failed to send action to analyzer");
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        info!("Stopping Gemini CLI collector");
+        Ok(())
+    }
+
+    fn is_available(&self) -> bool {
+        self.tmp_dir.exists()
+    }
+
+    fn import(&self, path: Option<&Path>) -> anyhow::Result<Vec<AgentAction>> {
+        let mut actions = Vec::new();
+        for file in session_files_in(path.unwrap_or(&self.tmp_dir)) {
+            actions.extend(parse_session(&std::fs::read_to_string(&file)?));
+        }
+        Ok(actions)
+    }
+}
+
+// ============================================
+// Serde structures for Gemini CLI chat recordings
+// ============================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiSession {
+    #[serde(default)]
+    session_id: Option<String>,
+    #[serde(default)]
+    messages: Vec<GeminiMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiMessage {
+    #[serde(default)]
+    timestamp: Option<String>,
+    #[serde(default)]
+    tool_calls: Vec<GeminiToolCall>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiToolCall {
+    id: String,
+    name: String,
+    #[serde(default)]
+    args: Option<serde_json::Value>,
+    #[serde(default)]
+    timestamp: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ActionType;
+
+    #[test]
+    fn test_parse_gemini_session() {
+        let content = r#"{
+            "sessionId": "s-1",
+            "projectHash": "abc",
+            "messages": [
+                {"id": "m1", "type": "user", "content": "clean up", "timestamp": "2026-03-01T10:00:00Z"},
+                {"id": "m2", "type": "gemini", "content": "", "timestamp": "2026-03-01T10:00:01Z",
+                 "toolCalls": [
+                    {"id": "run_shell_command-1", "name": "run_shell_command", "args": {"command": "rm -rf build"}},
+                    {"id": "write_file-2", "name": "write_file", "args": {"file_path": "/tmp/a.txt", "content": "x"}}
+                 ]}
+            ]
+        }"#;
+
+        let actions = parse_session(content);
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].agent, AgentType::GeminiCli);
+        assert_eq!(actions[0].action_type, ActionType::Exec);
+        assert_eq!(actions[0].content, "rm -rf build");
+        assert_eq!(actions[0].session_id.as_deref(), Some("s-1"));
+        assert_eq!(actions[1].action_type, ActionType::FileWrite);
+        assert_eq!(actions[1].target.as_deref(), Some("/tmp/a.txt"));
+    }
+}
@@ -0,0 +1,450 @@
+//! Collector supervision
+//!
+//! `CollectorManager` runs each collector in its own task and keeps its
+//! status for `/api/collectors` and `openclaw-harness status`. A collector
+//! that fails or panics is restarted with exponential backoff (1s doubling
+//! up to 5 minutes, reset after 5 minutes of clean running). One that
+//! returns normally has nothing to watch and stays stopped.
+//!
+//! A running collector that has seen no events for a while although its
+//! agent's process is running (say, the agent now logs somewhere else) is
+//! reported as stale by `check_stale`.
+
+use super::queue::{ActionQueue, QueueMetrics};
+use super::Collector;
+use crate::AgentAction;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+/// A run this long counts as healthy and resets the backoff
+const HEALTHY_RUN: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CollectorState {
+    Running,
+    /// Failed; waiting to be restarted
+    Degraded,
+    /// Returned without error, nothing to watch
+    Stopped,
+    /// Agent not installed
+    Unavailable,
+}
+
+impl std::fmt::Display for CollectorState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CollectorState::Running => write!(f, "running"),
+            CollectorState::Degraded => write!(f, "degraded"),
+            CollectorState::Stopped => write!(f, "stopped"),
+            CollectorState::Unavailable => write!(f, "unavailable"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectorStatus {
+    pub name: String,
+    pub state: CollectorState,
+    /// When the current (or last) run started
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_event: Option<DateTime<Utc>>,
+    /// Seconds since the last event, or since the run started
+    pub idle_secs: Option<u64>,
+    /// Actions emitted since the daemon started
+    pub events: u64,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    /// Whether the agent's process was running at the last stale check
+    pub agent_running: Option<bool>,
+    /// No events for too long while the agent is running
+    pub stale: bool,
+}
+
+impl CollectorStatus {
+    fn new(name: &str, state: CollectorState) -> Self {
+        Self {
+            name: name.to_string(),
+            state,
+            started_at: None,
+            last_event: None,
+            idle_secs: None,
+            events: 0,
+            restarts: 0,
+            last_error: None,
+            agent_running: None,
+            stale: false,
+        }
+    }
+}
+
+/// Minutes without events before a collector whose agent is running is
+/// reported stale, from `OPENCLAW_HARNESS_STALE_COLLECTOR_MINS` (default
+/// 30, 0 disables)
+pub fn stale_threshold() -> Option<Duration> {
+    let minutes = std::env::var("OPENCLAW_HARNESS_STALE_COLLECTOR_MINS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(30);
+    (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+/// Command lines of the running processes (empty if `ps` fails)
+pub fn list_processes() -> Vec<String> {
+    std::process::Command::new("ps")
+        .args(["-Ao", "args="])
+        .output()
+        .map(|out| {
+            String::from_utf8_lossy(&out.stdout)
+                .lines()
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether any command line runs one of `names`, matched on the file name
+/// of each word (so `node /usr/lib/node_modules/.bin/claude` counts). The
+/// harness itself never counts.
+fn agent_running(processes: &[String], names: &[&str]) -> bool {
+    processes.iter().any(|args| {
+        args.split_whitespace().any(|word| {
+            let file = word.rsplit('/').next().unwrap_or(word);
+            !file.starts_with("openclaw-harness") && names.iter().any(|n| file.starts_with(n))
+        })
+    })
+}
+
+/// Delay before restart number `failures` (0-based)
+fn backoff(failures: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(failures))
+        .min(MAX_BACKOFF)
+}
+
+#[derive(Debug, Default)]
+pub struct CollectorManager {
+    statuses: Mutex<BTreeMap<String, CollectorStatus>>,
+    /// Process names of each collector's agent
+    processes: Mutex<BTreeMap<String, &'static [&'static str]>>,
+    /// Where collectors' actions go, once spawned
+    queue: Mutex<Option<Arc<ActionQueue>>>,
+}
+
+impl CollectorManager {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Start every available collector, forwarding its actions to `queue`;
+    /// returns how many were started
+    pub fn spawn(
+        self: &Arc<Self>,
+        collectors: Vec<Box<dyn Collector>>,
+        queue: Arc<ActionQueue>,
+    ) -> usize {
+        if let Ok(mut current) = self.queue.lock() {
+            *current = Some(queue.clone());
+        }
+        let mut started = 0;
+        for collector in collectors {
+            let name = collector.name();
+            if !collector.is_available() {
+                info!("Collector {} not available, skipping", name);
+                self.set(CollectorStatus::new(name, CollectorState::Unavailable));
+                continue;
+            }
+            info!("📡 {} collector available", name);
+            self.set(CollectorStatus::new(name, CollectorState::Running));
+            if let Ok(mut processes) = self.processes.lock() {
+                processes.insert(name.to_string(), collector.process_names());
+            }
+            started += 1;
+            tokio::spawn(self.clone().supervise(Arc::from(collector), queue.clone()));
+        }
+        started
+    }
+
+    /// Status of every collector, by name
+    pub fn statuses(&self) -> Vec<CollectorStatus> {
+        let now = Utc::now();
+        self.statuses
+            .lock()
+            .map(|s| {
+                s.values()
+                    .cloned()
+                    .map(|mut status| {
+                        status.idle_secs = status
+                            .last_event
+                            .or(status.started_at)
+                            .map(|t| (now - t).num_seconds().max(0) as u64);
+                        status
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Counters of the queue collectors feed, once they are spawned
+    pub fn queue_metrics(&self) -> Option<QueueMetrics> {
+        self.queue
+            .lock()
+            .ok()
+            .and_then(|q| q.as_ref().map(|q| q.metrics()))
+    }
+
+    /// Mark running collectors that have been idle for `threshold` while
+    /// their agent is in `processes` as stale; returns the newly stale ones
+    pub fn check_stale(&self, threshold: Duration, processes: &[String]) -> Vec<CollectorStatus> {
+        let names = self.processes.lock().map(|p| p.clone()).unwrap_or_default();
+        let now = Utc::now();
+        let mut newly_stale = vec![];
+        let Ok(mut statuses) = self.statuses.lock() else {
+            return newly_stale;
+        };
+        for status in statuses.values_mut() {
+            let agent = names.get(&status.name).copied().unwrap_or_default();
+            if status.state != CollectorState::Running || agent.is_empty() {
+                continue;
+            }
+            let running = agent_running(processes, agent);
+            let idle = status
+                .last_event
+                .or(status.started_at)
+                .and_then(|t| (now - t).to_std().ok())
+                .unwrap_or_default();
+            let stale = running && idle >= threshold;
+            status.agent_running = Some(running);
+            status.idle_secs = Some(idle.as_secs());
+            if stale && !status.stale {
+                newly_stale.push(status.clone());
+            }
+            status.stale = stale;
+        }
+        newly_stale
+    }
+
+    fn set(&self, status: CollectorStatus) {
+        if let Ok(mut statuses) = self.statuses.lock() {
+            statuses.insert(status.name.clone(), status);
+        }
+    }
+
+    fn update(&self, name: &str, f: impl FnOnce(&mut CollectorStatus)) {
+        if let Ok(mut statuses) = self.statuses.lock() {
+            if let Some(status) = statuses.get_mut(name) {
+                f(status);
+            }
+        }
+    }
+
+    async fn supervise(self: Arc<Self>, collector: Arc<dyn Collector>, queue: Arc<ActionQueue>) {
+        let name = collector.name();
+        let mut failures = 0;
+        loop {
+            self.update(name, |s| {
+                s.state = CollectorState::Running;
+                s.started_at = Some(Utc::now());
+            });
+            let run_started = Instant::now();
+            let (run_tx, mut run_rx) = mpsc::channel::<AgentAction>(100);
+            let run = tokio::spawn({
+                let collector = collector.clone();
+                async move { collector.start(run_tx).await }
+            });
+
+            // Ends when the run finishes and drops its sender
+            while let Some(action) = run_rx.recv().await {
+                self.update(name, |s| {
+                    s.events += 1;
+                    s.last_event = Some(Utc::now());
+                    s.stale = false;
+                });
+                if queue.send(action).await.is_err() {
+                    run.abort();
+                    return;
+                }
+            }
+
+            let error = match run.await {
+                Ok(Ok(())) => {
+                    info!("{} collector stopped", name);
+                    self.update(name, |s| s.state = CollectorState::Stopped);
+                    return;
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(e) if e.is_panic() => "collector panicked".to_string(),
+                Err(e) => e.to_string(),
+            };
+            if run_started.elapsed() >= HEALTHY_RUN {
+                failures = 0;
+            }
+            let delay = backoff(failures);
+            failures += 1;
+            error!(
+                "{} collector error: {} — restarting in {}s",
+                name,
+                error,
+                delay.as_secs()
+            );
+            self.update(name, |s| {
+                s.state = CollectorState::Degraded;
+                s.restarts += 1;
+                s.last_error = Some(error);
+            });
+            tokio::time::sleep(delay).await;
+            warn!("🔁 Restarting {} collector", name);
+        }
+    }
+}
+
+/// Collector statuses from the daemon's web API at `base_url`; None when
+/// nothing is listening
+pub async fn fetch(base_url: &str) -> anyhow::Result<Option<Vec<CollectorStatus>>> {
+    let url = format!("{}/api/v1/collectors", base_url.trim_end_matches('/'));
+    let resp = match reqwest::Client::new()
+        .get(&url)
+        .timeout(Duration::from_secs(3))
+        .send()
+        .await
+    {
+        Ok(resp) => resp,
+        Err(e) if e.is_connect() => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Some(resp.error_for_status()?.json().await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+
+    struct Fake {
+        name: &'static str,
+        available: bool,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl Collector for Fake {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn start(&self, tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
+            let action = AgentAction {
+                id: uuid::Uuid::new_v4().to_string(),
+                timestamp: chrono::Utc::now(),
+                agent: crate::AgentType::Unknown,
+                action_type: crate::ActionType::Exec,
+                content: "ls".to_string(),
+                target: None,
+                session_id: None,
+                metadata: None,
+            };
+            tx.send(action).await?;
+            if self.fail {
+                anyhow::bail!("log directory vanished");
+            }
+            Ok(())
+        }
+
+        async fn stop(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn is_available(&self) -> bool {
+            self.available
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collectors_supervised() {
+        assert_eq!(backoff(0), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+        assert_eq!(backoff(40), MAX_BACKOFF);
+
+        let manager = CollectorManager::new();
+        let (tx, mut rx) = mpsc::channel(10);
+        let fake = |name, available, fail| -> Box<dyn Collector> {
+            Box::new(Fake {
+                name,
+                available,
+                fail,
+            })
+        };
+        let started = manager.spawn(
+            vec![
+                fake("done", true, false),
+                fake("flaky", true, true),
+                fake("missing", false, false),
+            ],
+            Arc::new(ActionQueue::new(tx, |_: &AgentAction| {
+                crate::RiskLevel::Info
+            })),
+        );
+        assert_eq!(started, 2);
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let statuses = manager.statuses();
+        let state = |name: &str| statuses.iter().find(|s| s.name == name).unwrap();
+        assert_eq!(state("done").state, CollectorState::Stopped);
+        assert_eq!(state("done").events, 1);
+        assert!(state("done").last_event.is_some());
+        assert_eq!(state("flaky").state, CollectorState::Degraded);
+        assert_eq!(state("flaky").restarts, 1);
+        assert_eq!(
+            state("flaky").last_error.as_deref(),
+            Some("log directory vanished")
+        );
+        assert_eq!(state("missing").state, CollectorState::Unavailable);
+    }
+
+    #[test]
+    fn test_stale_collector_detected() {
+        let procs = |lines: &[&str]| lines.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+        let running = procs(&[
+            "/usr/bin/node /usr/local/bin/claude --resume",
+            "openclaw-harness start --foreground",
+        ]);
+        assert!(agent_running(&running, &["claude"]));
+        assert!(!agent_running(&running, &["openclaw"]));
+
+        let manager = CollectorManager::new();
+        let mut status = CollectorStatus::new("claude_code", CollectorState::Running);
+        status.started_at = Some(Utc::now() - chrono::Duration::minutes(45));
+        manager.set(status);
+        manager
+            .processes
+            .lock()
+            .unwrap()
+            .insert("claude_code".to_string(), &["claude"]);
+
+        let threshold = Duration::from_secs(30 * 60);
+        // Agent not running: idle is expected
+        assert!(manager.check_stale(threshold, &procs(&["vim"])).is_empty());
+        let stale = manager.check_stale(threshold, &running);
+        assert_eq!(stale.len(), 1);
+        assert!(stale[0].idle_secs.unwrap() >= 45 * 60);
+        // Reported once per stale spell
+        assert!(manager.check_stale(threshold, &running).is_empty());
+        assert!(manager.statuses()[0].stale);
+
+        manager.update("claude_code", |s| {
+            s.last_event = Some(Utc::now());
+            s.stale = false;
+        });
+        assert!(manager.check_stale(threshold, &running).is_empty());
+        assert!(!manager.statuses()[0].stale);
+    }
+}
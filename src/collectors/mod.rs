@@ -5,12 +5,21 @@
 //! 2. Parsing log entries into `AgentAction`
 //! 3. Emitting actions to the analyzer
 
+pub mod aider;
 pub mod claude_code;
+pub mod copilot_cli;
 pub mod cursor;
+pub mod gemini_cli;
+pub mod manager;
 pub mod openclaw;
+pub mod openhands;
+pub mod queue;
+pub mod tail;
+pub mod watch;
 
-use super::{AgentAction, CollectorConfig};
+use super::{ActionType, AgentAction, CollectorConfig};
 use async_trait::async_trait;
+use std::path::Path;
 use tokio::sync::mpsc;
 
 /// Trait for log collectors
@@ -27,23 +36,213 @@ pub trait Collector: Send + Sync {
 
     /// Check if the agent is installed/available
     fn is_available(&self) -> bool;
+
+    /// Executable names of the agent, to tell whether it is running while
+    /// the collector sees nothing (see `manager`)
+    fn process_names(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Parse every action already in the logs under `path` (default: the
+    /// collector's own log directory). Used by `import` to backfill history.
+    fn import(&self, path: Option<&Path>) -> anyhow::Result<Vec<AgentAction>> {
+        let _ = path;
+        anyhow::bail!("the {} collector does not support import", self.name())
+    }
+}
+
+/// Count a log entry `collector` couldn't parse (see `diagnostics`);
+/// blank lines aren't entries
+fn parse_failed(collector: &str, content: &str, error: impl std::fmt::Display) {
+    if content.trim().is_empty() {
+        return;
+    }
+    tracing::debug!("Unparseable {} log entry: {}", collector, error);
+    crate::diagnostics::record(
+        crate::diagnostics::Source::Collector,
+        collector,
+        format!("unparseable log entry: {}", error),
+    );
+}
+
+/// Create a single collector by agent name (for `import`)
+pub fn collector_by_name(name: &str) -> Option<Box<dyn Collector>> {
+    let collector: Box<dyn Collector> = match name.replace('-', "_").as_str() {
+        "openclaw" => Box::new(openclaw::OpenclawCollector::new()),
+        "claude_code" | "claude" => Box::new(claude_code::ClaudeCodeCollector::new()),
+        "cursor" => Box::new(cursor::CursorCollector::new()),
+        "gemini_cli" | "gemini" => Box::new(gemini_cli::GeminiCliCollector::new()),
+        "copilot_cli" | "copilot" => Box::new(copilot_cli::CopilotCliCollector::new()),
+        "aider" => Box::new(aider::AiderCollector::new()),
+        "openhands" => Box::new(openhands::OpenHandsCollector::new()),
+        _ => return None,
+    };
+    Some(collector)
 }
 
+pub use manager::{CollectorManager, CollectorState, CollectorStatus};
+pub use queue::{ActionQueue, QueueMetrics};
+
 /// Create all enabled collectors
-pub fn create_collectors(config: &CollectorConfig) -> Vec<Box<dyn Collector>> {
+/// Create the enabled collectors. Those that tail logs keep their read
+/// positions in `state_dir`, when given, so a restart resumes where the
+/// last run stopped.
+pub fn create_collectors(
+    config: &CollectorConfig,
+    state_dir: Option<&Path>,
+) -> Vec<Box<dyn Collector>> {
     let mut collectors: Vec<Box<dyn Collector>> = Vec::new();
+    let offsets = |name: &str| state_dir.map(|dir| dir.join(format!("{}.json", name)));
 
     if config.openclaw {
-        collectors.push(Box::new(openclaw::OpenclawCollector::new()));
+        collectors.push(Box::new(
+            openclaw::OpenclawCollector::new()
+                .with_watch(config.watch_for("openclaw"))
+                .with_offsets(offsets("openclaw")),
+        ));
     }
 
     if config.claude_code {
-        collectors.push(Box::new(claude_code::ClaudeCodeCollector::new()));
+        collectors.push(Box::new(
+            claude_code::ClaudeCodeCollector::new()
+                .with_watch(config.watch_for("claude_code"))
+                .with_offsets(offsets("claude_code")),
+        ));
     }
 
     if config.cursor {
         collectors.push(Box::new(cursor::CursorCollector::new()));
     }
 
+    if config.gemini_cli {
+        collectors.push(Box::new(
+            gemini_cli::GeminiCliCollector::new().with_watch(config.watch_for("gemini_cli")),
+        ));
+    }
+
+    if config.copilot_cli {
+        collectors.push(Box::new(
+            copilot_cli::CopilotCliCollector::new()
+                .with_watch(config.watch_for("copilot_cli"))
+                .with_offsets(offsets("copilot_cli")),
+        ));
+    }
+
+    if config.aider {
+        collectors.push(Box::new(
+            aider::AiderCollector::new()
+                .with_watch(config.watch_for("aider"))
+                .with_offsets(offsets("aider")),
+        ));
+    }
+
+    if config.openhands {
+        collectors.push(Box::new(
+            openhands::OpenHandsCollector::new().with_watch(config.watch_for("openhands")),
+        ));
+    }
+
     collectors
 }
+
+/// Exit code from the note shell tools add to failed output ("Command
+/// exited with code 1", "Exit code 1")
+pub(crate) fn exit_code_in(output: &str) -> Option<i64> {
+    static RE: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+    RE.get_or_init(|| regex::Regex::new(r"(?i)(?:exited with|exit) code:? (-?\d+)").unwrap())
+        .captures_iter(output)
+        .last()
+        .and_then(|c| c[1].parse().ok())
+}
+
+/// Map a tool call from Claude Code / Gemini CLI / Copilot CLI style agents to
+/// (action type, content, target)
+pub(crate) fn classify_tool_call(
+    name: &str,
+    args: &serde_json::Value,
+) -> (ActionType, String, Option<String>) {
+    let field = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| args.get(*k).and_then(|v| v.as_str()))
+            .map(String::from)
+    };
+
+    match name {
+        "run_shell_command" | "bash" | "Bash" | "shell" => (
+            ActionType::Exec,
+            field(&["command"]).unwrap_or_default(),
+            None,
+        ),
+        "read_file" | "view" | "Read" => {
+            let path = field(&["absolute_path", "file_path", "path"]);
+            (
+                ActionType::FileRead,
+                format!("read {}", path.as_deref().unwrap_or("")),
+                path,
+            )
+        }
+        "write_file" | "create" | "Write" => {
+            let path = field(&["file_path", "path"]);
+            (
+                ActionType::FileWrite,
+                format!("write {}", path.as_deref().unwrap_or("")),
+                path,
+            )
+        }
+        "replace" | "edit" | "str_replace" | "str_replace_editor" | "Edit" | "MultiEdit" => {
+            let path = field(&["file_path", "path"]);
+            (
+                ActionType::FileWrite,
+                format!("edit {}", path.as_deref().unwrap_or("")),
+                path,
+            )
+        }
+        "NotebookEdit" => {
+            let path = field(&["notebook_path"]);
+            (
+                ActionType::FileWrite,
+                format!("edit {}", path.as_deref().unwrap_or("")),
+                path,
+            )
+        }
+        "Glob" | "Grep" | "LS" | "list_directory" | "glob" | "search_file_content" => {
+            let path = field(&["path", "dir_path"]);
+            let pattern = field(&["pattern"]).unwrap_or_default();
+            let verb = match name {
+                "Grep" | "search_file_content" => "grep",
+                "Glob" | "glob" => "glob",
+                _ => "ls",
+            };
+            (
+                ActionType::FileRead,
+                format!("{} {} {}", verb, pattern, path.as_deref().unwrap_or(""))
+                    .split_whitespace()
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                path,
+            )
+        }
+        "web_fetch" | "WebFetch" => {
+            let url = field(&["url", "prompt"]);
+            (
+                ActionType::HttpRequest,
+                format!("fetch {}", url.as_deref().unwrap_or("")),
+                url,
+            )
+        }
+        "google_web_search" | "web_search" | "WebSearch" => (
+            ActionType::HttpRequest,
+            format!("search: {}", field(&["query"]).unwrap_or_default()),
+            None,
+        ),
+        _ => crate::proxy::tools::registry()
+            .classify(name, args)
+            .unwrap_or_else(|| {
+                (
+                    ActionType::Unknown,
+                    serde_json::to_string(args).unwrap_or_default(),
+                    None,
+                )
+            }),
+    }
+}
@@ -3,14 +3,20 @@
 //! Monitors:
 //! - ~/.openclaw/agents/main/sessions/*.jsonl (session logs)
 //! - Falls back to ~/.clawdbot/agents/main/sessions/ for legacy installs
+//!
+//! Tool calls in assistant messages become actions. The `toolResult`
+//! message answering each one becomes a `ToolResult` action whose metadata
+//! links it back (`tool_call_id`) and carries `is_error`, `exit_code` and
+//! `stderr` when known, so retries and failures show up on the timeline.
 
-use super::super::{ActionType, AgentAction, AgentType};
+use super::super::metadata::ActionMetadata;
+use super::super::{ids, ActionType, AgentAction, AgentType};
 // When compiled as part of lib, use super's parent
+use super::tail::JsonlTailer;
+use super::watch::{LogWatcher, WatchSettings};
 use async_trait::async_trait;
 use serde::Deserialize;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
@@ -19,8 +25,8 @@ use tracing::{debug, error, info, warn};
 /// Collector for OpenClaw/Clawdbot
 pub struct OpenclawCollector {
     sessions_dir: PathBuf,
-    /// Track file positions to only read new content
-    file_positions: Arc<Mutex<std::collections::HashMap<PathBuf, u64>>>,
+    tailer: Arc<Mutex<JsonlTailer>>,
+    watch: WatchSettings,
     /// Track seen action IDs to avoid duplicates
     seen_ids: Arc<Mutex<HashSet<String>>>,
 }
@@ -43,18 +49,49 @@ impl OpenclawCollector {
         };
         Self {
             sessions_dir,
-            file_positions: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            tailer: Arc::new(Mutex::new(JsonlTailer::new())),
+            watch: WatchSettings::default(),
             seen_ids: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
+    /// How to notice new log data (see `collectors::watch`)
+    pub fn with_watch(mut self, watch: WatchSettings) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    /// Keep read positions in `state_file` across restarts (see
+    /// `JsonlTailer::persistent`)
+    pub fn with_offsets(mut self, state_file: Option<PathBuf>) -> Self {
+        if let Some(state_file) = state_file {
+            self.tailer = Arc::new(Mutex::new(JsonlTailer::persistent(state_file)));
+        }
+        self
+    }
+
     /// Parse a JSONL session log line and extract tool calls
     fn parse_log_line(&self, line: &str) -> Vec<AgentAction> {
         let mut actions = Vec::new();
 
-        let entry: OpenclawLogEntry = match serde_json::from_str(line) {
+        let value: serde_json::Value = match serde_json::from_str(line) {
+            Ok(v) => v,
+            Err(e) => {
+                super::parse_failed("openclaw", line, e);
+                return actions;
+            }
+        };
+        // Entries of other types have other shapes; a message that doesn't
+        // fit means the format changed
+        let is_message = value["type"].as_str() == Some("message");
+        let entry: OpenclawLogEntry = match serde_json::from_value(value) {
             Ok(e) => e,
-            Err(_) => return actions,
+            Err(e) => {
+                if is_message {
+                    super::parse_failed("openclaw", line, e);
+                }
+                return actions;
+            }
         };
 
         // Only process message entries
@@ -67,6 +104,15 @@ impl OpenclawCollector {
             None => return actions,
         };
 
+        let timestamp = chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .unwrap_or_else(|_| chrono::Utc::now());
+
+        if message.role == "toolResult" {
+            actions.extend(tool_result_action(&entry.id, timestamp, message));
+            return actions;
+        }
+
         // Only process assistant messages (which contain tool calls)
         if message.role != "assistant" {
             return actions;
@@ -91,16 +137,20 @@ impl OpenclawCollector {
                     let (content, target) = extract_content_and_target(&tool_call);
 
                     actions.push(AgentAction {
-                        id: tool_call.id,
-                        timestamp: chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
-                            .map(|dt| dt.with_timezone(&chrono::Utc))
-                            .unwrap_or_else(|_| chrono::Utc::now()),
+                        id: ids::collected(&AgentType::OpenClaw, Some(&tool_call.id)),
+                        timestamp,
                         agent: AgentType::OpenClaw,
                         action_type,
                         content,
                         target,
                         session_id: Some(entry.id.clone()),
-                        metadata: tool_call.arguments,
+                        metadata: Some(
+                            ActionMetadata::tool(
+                                &tool_call.name,
+                                tool_call.arguments.unwrap_or_default(),
+                            )
+                            .to_value(),
+                        ),
                     });
                 }
             }
@@ -109,57 +159,6 @@ impl OpenclawCollector {
         actions
     }
 
-    /// Read new lines from a file
-    async fn read_new_lines(&self, path: &PathBuf) -> Vec<String> {
-        let mut positions = self.file_positions.lock().await;
-        let current_pos = positions.get(path).copied().unwrap_or(0);
-
-        let file = match File::open(path) {
-            Ok(f) => f,
-            Err(e) => {
-                warn!("Failed to open log file {:?}: {}", path, e);
-                return vec![];
-            }
-        };
-
-        let metadata = match file.metadata() {
-            Ok(m) => m,
-            Err(_) => return vec![],
-        };
-
-        let file_size = metadata.len();
-
-        // If file is smaller than our position, it was truncated/rotated
-        let start_pos = if file_size < current_pos {
-            0
-        } else {
-            current_pos
-        };
-
-        let mut reader = BufReader::new(file);
-        if reader.seek(SeekFrom::Start(start_pos)).is_err() {
-            return vec![];
-        }
-
-        let mut lines = Vec::new();
-        let mut new_pos = start_pos;
-
-        for line in reader.lines() {
-            match line {
-                Ok(l) => {
-                    new_pos += l.len() as u64 + 1; // +1 for newline
-                    if !l.is_empty() {
-                        lines.push(l);
-                    }
-                }
-                Err(_) => break,
-            }
-        }
-
-        positions.insert(path.clone(), new_pos);
-        lines
-    }
-
     /// Get all JSONL files in sessions directory
     fn get_session_files(&self) -> Vec<PathBuf> {
         let mut files = Vec::new();
@@ -175,6 +174,51 @@ impl OpenclawCollector {
     }
 }
 
+/// The result of an earlier tool call, linked to it through metadata
+fn tool_result_action(
+    entry_id: &str,
+    timestamp: chrono::DateTime<chrono::Utc>,
+    message: Message,
+) -> Option<AgentAction> {
+    let tool_call_id = message.tool_call_id?;
+    let output: Vec<&str> = message
+        .content
+        .iter()
+        .filter_map(|c| c.text.as_deref())
+        .collect();
+    let output = output.join("\n");
+    let details = message.details.unwrap_or_default();
+    let exit_code = ["exitCode", "exit_code"]
+        .iter()
+        .find_map(|k| details.get(*k).and_then(|v| v.as_i64()))
+        .or_else(|| super::exit_code_in(&output));
+    let stderr = details
+        .get("stderr")
+        .and_then(|v| v.as_str())
+        .map(String::from);
+    let is_error = message.is_error || exit_code.is_some_and(|c| c != 0);
+
+    Some(AgentAction {
+        id: ids::collected(
+            &AgentType::OpenClaw,
+            Some(&format!("{}:result", tool_call_id)),
+        ),
+        timestamp,
+        agent: AgentType::OpenClaw,
+        action_type: ActionType::ToolResult,
+        content: output,
+        target: None,
+        session_id: Some(entry_id.to_string()),
+        metadata: Some(serde_json::json!({
+            "tool_call_id": tool_call_id,
+            "tool_name": message.tool_name,
+            "is_error": is_error,
+            "exit_code": exit_code,
+            "stderr": stderr,
+        })),
+    })
+}
+
 fn extract_content_and_target(tool_call: &ToolCall) -> (String, Option<String>) {
     let args = match &tool_call.arguments {
         Some(a) => a,
@@ -262,6 +306,10 @@ impl super::Collector for OpenclawCollector {
         "openclaw"
     }
 
+    fn process_names(&self) -> &'static [&'static str] {
+        &["openclaw", "clawdbot"]
+    }
+
     async fn start(&self, tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
         info!(
             "🦞 Starting OpenClaw collector, watching: {:?}",
@@ -276,24 +324,29 @@ impl super::Collector for OpenclawCollector {
             return Ok(());
         }
 
-        // Initialize file positions to end of existing files
-        for path in self.get_session_files() {
-            if let Ok(metadata) = std::fs::metadata(&path) {
-                let mut positions = self.file_positions.lock().await;
-                positions.insert(path, metadata.len());
+        // Only lines written from now on are new activity
+        {
+            let mut tailer = self.tailer.lock().await;
+            for path in self.get_session_files() {
+                tailer.prime(&path);
             }
         }
 
         info!("OpenClaw collector started, monitoring for new tool calls...");
 
-        // Polling-based monitoring (simpler than notify for now)
-        let poll_interval = tokio::time::Duration::from_millis(500);
+        let mut watcher = LogWatcher::new(&self.watch, 500, &[&self.sessions_dir], false);
+        let max_batch = self.watch.max_batch();
+        let mut backlog = false;
 
         loop {
-            tokio::time::sleep(poll_interval).await;
+            if !backlog {
+                watcher.wait().await;
+            }
+            backlog = false;
 
             for path in self.get_session_files() {
-                let lines = self.read_new_lines(&path).await;
+                let lines = self.tailer.lock().await.read_lines(&path, max_batch);
+                backlog |= lines.len() >= max_batch;
 
                 if lines.is_empty() {
                     continue;
@@ -325,6 +378,7 @@ impl super::Collector for OpenclawCollector {
                     }
                 }
             }
+            self.tailer.lock().await.save();
         }
     }
 
@@ -336,6 +390,20 @@ impl super::Collector for OpenclawCollector {
     fn is_available(&self) -> bool {
         self.sessions_dir.exists()
     }
+
+    fn import(&self, path: Option<&std::path::Path>) -> anyhow::Result<Vec<AgentAction>> {
+        let dir = path.unwrap_or(&self.sessions_dir);
+        let mut actions = Vec::new();
+        for file in super::tail::find_files(dir, 1, &|p: &std::path::Path| {
+            p.extension().is_some_and(|ext| ext == "jsonl")
+        }) {
+            let content = std::fs::read_to_string(&file)?;
+            for line in content.lines() {
+                actions.extend(self.parse_log_line(line));
+            }
+        }
+        Ok(actions)
+    }
 }
 
 fn truncate(s: &str, max_len: usize) -> String {
@@ -364,10 +432,20 @@ struct OpenclawLogEntry {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct Message {
     role: String,
     #[serde(default)]
     content: Vec<Content>,
+    /// On `toolResult` messages: the call answered
+    #[serde(default)]
+    tool_call_id: Option<String>,
+    #[serde(default)]
+    tool_name: Option<String>,
+    #[serde(default)]
+    is_error: bool,
+    #[serde(default)]
+    details: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -380,6 +458,8 @@ struct Content {
     name: Option<String>,
     #[serde(default)]
     arguments: Option<serde_json::Value>,
+    #[serde(default)]
+    text: Option<String>,
 }
 
 impl Content {
@@ -432,4 +512,40 @@ mod tests {
         assert_eq!(action.action_type, ActionType::FileWrite);
         assert_eq!(action.target, Some("/tmp/test.txt".to_string()));
     }
+
+    #[test]
+    fn test_parse_tool_result() {
+        let collector = OpenclawCollector::new();
+        let line = r#"{"type":"message","id":"res1","parentId":"test123","timestamp":"2026-01-27T23:50:48.000Z","message":{"role":"toolResult","toolCallId":"tool1","toolName":"exec","content":[{"type":"text","text":"rm: /etc/hosts: Permission denied\n\nCommand exited with code 1"}],"isError":false}}"#;
+
+        let actions = collector.parse_log_line(line);
+        assert_eq!(actions.len(), 1);
+        let result = &actions[0];
+        assert_eq!(result.action_type, ActionType::ToolResult);
+        assert_eq!(result.id, "collector-openclaw-tool1:result");
+        assert!(result.content.contains("Permission denied"));
+        let meta = result.metadata.as_ref().unwrap();
+        assert_eq!(meta["tool_call_id"], "tool1");
+        assert_eq!(meta["tool_name"], "exec");
+        assert_eq!(meta["exit_code"], 1);
+        assert_eq!(meta["is_error"], true);
+
+        let line = r#"{"type":"message","id":"res2","timestamp":"2026-01-27T23:50:49.000Z","message":{"role":"toolResult","toolCallId":"tool2","toolName":"exec","content":[{"type":"text","text":"ok"}],"details":{"exitCode":0,"stderr":""},"isError":false}}"#;
+        let meta = collector.parse_log_line(line)[0].metadata.clone().unwrap();
+        assert_eq!(meta["exit_code"], 0);
+        assert_eq!(meta["is_error"], false);
+
+        // Rules for every action type leave tool output alone; rules that
+        // name tool results still see it
+        let mut rule = crate::rules::Rule::new(
+            "denied",
+            "",
+            "Permission denied",
+            crate::RiskLevel::Warning,
+            crate::rules::RuleAction::Alert,
+        );
+        assert!(!rule.matches(result));
+        rule.applies_to = vec![ActionType::ToolResult];
+        assert!(rule.matches(result));
+    }
 }
@@ -0,0 +1,273 @@
+//! OpenHands event stream collector
+//!
+//! Monitors:
+//! - ~/.openhands/sessions/<sid>/events/<n>.json (one file per event)
+//! - Falls back to ~/.openhands-state/sessions/ for older installs
+//!
+//! Event files are written once and never modified, so we only need to
+//! remember which ones we have already read.
+
+use super::super::metadata::ActionMetadata;
+use super::super::{ids, ActionType, AgentAction, AgentType};
+use super::tail::find_files;
+use super::watch::{LogWatcher, WatchSettings};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tracing::{error, info, warn};
+
+pub struct OpenHandsCollector {
+    sessions_dir: PathBuf,
+    /// Event files already processed
+    seen_files: Arc<Mutex<HashSet<PathBuf>>>,
+    watch: WatchSettings,
+}
+
+impl Default for OpenHandsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenHandsCollector {
+    pub fn new() -> Self {
+        let home = dirs::home_dir().unwrap_or_default();
+        let current = home.join(".openhands/sessions");
+        let sessions_dir = if current.is_dir() {
+            current
+        } else {
+            home.join(".openhands-state/sessions")
+        };
+        Self {
+            sessions_dir,
+            seen_files: Arc::new(Mutex::new(HashSet::new())),
+            watch: WatchSettings::default(),
+        }
+    }
+
+    /// How to notice new log data (see `collectors::watch`)
+    pub fn with_watch(mut self, watch: WatchSettings) -> Self {
+        self.watch = watch;
+        self
+    }
+
+    fn event_files(&self) -> Vec<PathBuf> {
+        event_files_in(&self.sessions_dir)
+    }
+}
+
+fn event_files_in(dir: &Path) -> Vec<PathBuf> {
+    find_files(dir, 2, &|p: &Path| {
+        p.extension().and_then(|e| e.to_str()) == Some("json")
+            && p.parent()
+                .and_then(|d| d.file_name())
+                .and_then(|n| n.to_str())
+                == Some("events")
+    })
+}
+
+/// Parse one OpenHands event into an action.
+/// Observations, messages and user events return `None`.
+pub fn parse_event(content: &str, session_id: Option<&str>) -> Option<AgentAction> {
+    let event: Value = match serde_json::from_str(content) {
+        Ok(event) => event,
+        Err(e) => {
+            super::parse_failed("openhands", content, e);
+            return None;
+        }
+    };
+    if event["source"].as_str() != Some("agent") {
+        return None;
+    }
+    let args = &event["args"];
+    let arg = |key: &str| args[key].as_str().map(String::from);
+
+    let tool_name = event["action"].as_str()?;
+    let (action_type, content, target) = match tool_name {
+        "run" => (ActionType::Exec, arg("command")?, None),
+        "run_ipython" => (ActionType::Exec, arg("code")?, None),
+        "read" => {
+            let path = arg("path")?;
+            (ActionType::FileRead, format!("read {}", path), Some(path))
+        }
+        "write" => {
+            let path = arg("path")?;
+            (ActionType::FileWrite, format!("write {}", path), Some(path))
+        }
+        "edit" => {
+            let path = arg("path")?;
+            if arg("command").as_deref() == Some("view") {
+                (ActionType::FileRead, format!("read {}", path), Some(path))
+            } else {
+                (ActionType::FileWrite, format!("edit {}", path), Some(path))
+            }
+        }
+        "browse" => {
+            let url = arg("url")?;
+            (
+                ActionType::BrowserAction,
+                format!("browse {}", url),
+                Some(url),
+            )
+        }
+        "browse_interactive" => (ActionType::BrowserAction, arg("browser_actions")?, None),
+        _ => return None,
+    };
+
+    // OpenHands writes naive local timestamps
+    let timestamp = event["timestamp"]
+        .as_str()
+        .and_then(|t| {
+            chrono::DateTime::parse_from_rfc3339(t)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .ok()
+                .or_else(|| {
+                    chrono::NaiveDateTime::parse_from_str(t, "%Y-%m-%dT%H:%M:%S%.f")
+                        .ok()
+                        .and_then(|n| n.and_local_timezone(chrono::Local).single())
+                        .map(|dt| dt.with_timezone(&chrono::Utc))
+                })
+        })
+        .unwrap_or_else(chrono::Utc::now);
+
+    let native = match (session_id, event["id"].as_i64()) {
+        (Some(sid), Some(n)) => Some(format!("{}-{}", sid, n)),
+        _ => None,
+    };
+    let id = ids::collected(&AgentType::OpenHands, native.as_deref());
+
+    Some(AgentAction {
+        id,
+        timestamp,
+        agent: AgentType::OpenHands,
+        action_type,
+        content,
+        target,
+        session_id: session_id.map(String::from),
+        metadata: Some(ActionMetadata::tool(tool_name, args.clone()).to_value()),
+    })
+}
+
+/// Session ID from `<sessions>/<sid>/events/<n>.json`
+fn session_of(path: &Path) -> Option<&str> {
+    path.parent()?.parent()?.file_name()?.to_str()
+}
+
+#[async_trait]
+impl super::Collector for OpenHandsCollector {
+    fn name(&self) -> &'static str {
+        "openhands"
+    }
+
+    fn process_names(&self) -> &'static [&'static str] {
+        &["openhands"]
+    }
+
+    async fn start(&self, tx: mpsc::Sender<AgentAction>) -> anyhow::Result<()> {
+        info!(
+            "🙌 Starting OpenHands collector, watching: {:?}",
+            self.sessions_dir
+        );
+
+        if !self.sessions_dir.exists() {
+            warn!(
+                "OpenHands sessions directory not found: {:?}",
+                self.sessions_dir
+            );
+            return Ok(());
+        }
+
+        self.seen_files.lock().await.extend(self.event_files());
+
+        let mut watcher = LogWatcher::new(&self.watch, 1000, &[&self.sessions_dir], true);
+        let max_batch = self.watch.max_batch();
+        let mut backlog = false;
+
+        loop {
+            if !backlog {
+                watcher.wait().await;
+            }
+
+            let new_files: Vec<PathBuf> = {
+                let mut seen = self.seen_files.lock().await;
+                let mut unseen: Vec<PathBuf> = self
+                    .event_files()
+                    .into_iter()
+                    .filter(|p| !seen.contains(p))
+                    .collect();
+                unseen.sort();
+                backlog = unseen.len() > max_batch;
+                unseen.truncate(max_batch);
+                seen.extend(unseen.iter().cloned());
+                unseen
+            };
+
+            for path in new_files {
+                let Ok(content) = std::fs::read_to_string(&path) else {
+                    continue;
+                };
+                let Some(action) = parse_event(&content, session_of(&path)) else {
+                    continue;
+                };
+                info!("📍 Detected (openhands): {}", action.action_type);
+                if tx.send(action).await.is_err() {
+                    error!("Failed to send action to analyzer");
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    async fn stop(&self) -> anyhow::Result<()> {
+        info!("Stopping OpenHands collector");
+        Ok(())
+    }
+
+    fn is_available(&self) -> bool {
+        self.sessions_dir.exists()
+    }
+
+    fn import(&self, path: Option<&Path>) -> anyhow::Result<Vec<AgentAction>> {
+        let mut files = event_files_in(path.unwrap_or(&self.sessions_dir));
+        files.sort();
+        let mut actions = Vec::new();
+        for file in files {
+            let content = std::fs::read_to_string(&file)?;
+            actions.extend(parse_event(&content, session_of(&file)));
+        }
+        Ok(actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_openhands_events() {
+        let run = r#"{"id": 4, "timestamp": "2026-03-01T10:00:00.123456", "source": "agent",
+            "message": "Running command: rm -rf /tmp/x", "action": "run",
+            "args": {"command": "rm -rf /tmp/x", "is_input": false}}"#;
+        let action = parse_event(run, Some("abc")).unwrap();
+        assert_eq!(action.id, "collector-openhands-abc-4");
+        assert_eq!(action.agent, AgentType::OpenHands);
+        assert_eq!(action.action_type, ActionType::Exec);
+        assert_eq!(action.content, "rm -rf /tmp/x");
+
+        let edit = r#"{"id": 5, "source": "agent", "action": "edit",
+            "args": {"path": "/workspace/.env", "command": "create", "file_text": "x"}}"#;
+        let action = parse_event(edit, Some("abc")).unwrap();
+        assert_eq!(action.action_type, ActionType::FileWrite);
+        assert_eq!(action.target.as_deref(), Some("/workspace/.env"));
+
+        let observation = r#"{"id": 6, "source": "agent", "observation": "run",
+            "content": "ok", "extras": {}}"#;
+        assert!(parse_event(observation, Some("abc")).is_none());
+
+        let user = r#"{"id": 1, "source": "user", "action": "message", "args": {"content": "hi"}}"#;
+        assert!(parse_event(user, Some("abc")).is_none());
+    }
+}
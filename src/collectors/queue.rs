@@ -0,0 +1,290 @@
+//! Bounded hand-off from collectors to the analyzer
+//!
+//! When the analyzer falls behind (a burst of agent activity, a slow
+//! database) the channel fills up. `ActionQueue` then triages each action
+//! with the rules before deciding what to do with it:
+//!
+//! - Info (no rule matched): dropped and counted
+//! - Warning and Critical: written to the spill file when one is
+//!   configured, and read back once the channel has room; otherwise the
+//!   collector waits for room. These are never dropped.
+//!
+//! Counts are reported through `/api/v1/collectors/queue` and
+//! `openclaw-harness status`.
+
+use crate::{AgentAction, RiskLevel};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{info, warn};
+
+/// Channel capacity, overridden by `OPENCLAW_HARNESS_ACTION_QUEUE_SIZE`
+pub const DEFAULT_CAPACITY: usize = 1000;
+/// How often the spill file is checked for actions to read back
+const DRAIN_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Channel capacity from the environment, or the default
+pub fn capacity() -> usize {
+    std::env::var("OPENCLAW_HARNESS_ACTION_QUEUE_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_CAPACITY)
+}
+
+/// Whether `OPENCLAW_HARNESS_ACTION_SPILL` asks for a spill file
+pub fn spill_enabled() -> bool {
+    std::env::var("OPENCLAW_HARNESS_ACTION_SPILL")
+        .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Queue counters since the daemon started
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueueMetrics {
+    pub capacity: usize,
+    /// Actions waiting for the analyzer
+    pub queued: usize,
+    /// Info actions dropped because the queue was full
+    pub dropped_info: u64,
+    /// Actions written to the spill file
+    pub spilled: u64,
+    /// Spilled actions not read back yet
+    pub spill_pending: u64,
+    /// Times a collector had to wait for room
+    pub blocked: u64,
+}
+
+type Triage = Box<dyn Fn(&AgentAction) -> RiskLevel + Send + Sync>;
+
+pub struct ActionQueue {
+    tx: mpsc::Sender<AgentAction>,
+    /// Risk estimate for an action that doesn't fit
+    triage: Triage,
+    spill: Option<Mutex<PathBuf>>,
+    dropped_info: AtomicU64,
+    spilled: AtomicU64,
+    spill_pending: AtomicU64,
+    blocked: AtomicU64,
+}
+
+impl std::fmt::Debug for ActionQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActionQueue")
+            .field("metrics", &self.metrics())
+            .field("spill", &self.spill)
+            .finish()
+    }
+}
+
+impl ActionQueue {
+    pub fn new(
+        tx: mpsc::Sender<AgentAction>,
+        triage: impl Fn(&AgentAction) -> RiskLevel + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            tx,
+            triage: Box::new(triage),
+            spill: None,
+            dropped_info: AtomicU64::new(0),
+            spilled: AtomicU64::new(0),
+            spill_pending: AtomicU64::new(0),
+            blocked: AtomicU64::new(0),
+        }
+    }
+
+    /// Spill Warning and Critical actions to `path` (JSONL) instead of
+    /// waiting when the queue is full. Actions left there by an earlier
+    /// run are read back too.
+    pub fn with_spill(mut self, path: PathBuf) -> Self {
+        let pending = std::fs::File::open(&path)
+            .map(|f| BufReader::new(f).lines().count() as u64)
+            .unwrap_or(0);
+        self.spill_pending.store(pending, Ordering::Relaxed);
+        self.spill = Some(Mutex::new(path));
+        self
+    }
+
+    /// Queue `action`; fails only when the analyzer has gone away
+    pub async fn send(&self, action: AgentAction) -> anyhow::Result<()> {
+        let action = match self.tx.try_send(action) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Closed(_)) => anyhow::bail!("analyzer channel closed"),
+            Err(TrySendError::Full(action)) => action,
+        };
+
+        if (self.triage)(&action) == RiskLevel::Info {
+            let dropped = self.dropped_info.fetch_add(1, Ordering::Relaxed) + 1;
+            if dropped.is_power_of_two() {
+                warn!(
+                    "⚠️  Action queue full, dropped {} info-level action(s) so far",
+                    dropped
+                );
+            }
+            return Ok(());
+        }
+
+        if let Some(spill) = &self.spill {
+            match self.push_spill(spill, &action) {
+                Ok(()) => return Ok(()),
+                Err(e) => warn!("Failed to spill action {}: {}", action.id, e),
+            }
+        }
+        self.blocked.fetch_add(1, Ordering::Relaxed);
+        self.tx
+            .send(action)
+            .await
+            .map_err(|_| anyhow::anyhow!("analyzer channel closed"))
+    }
+
+    fn push_spill(&self, spill: &Mutex<PathBuf>, action: &AgentAction) -> anyhow::Result<()> {
+        let path = spill
+            .lock()
+            .map_err(|_| anyhow::anyhow!("spill lock poisoned"))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&*path)?;
+        writeln!(file, "{}", serde_json::to_string(action)?)?;
+        self.spilled.fetch_add(1, Ordering::Relaxed);
+        self.spill_pending.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Take the spilled actions, leaving an empty spill file
+    fn take_spill(&self) -> Vec<AgentAction> {
+        let Some(spill) = &self.spill else {
+            return vec![];
+        };
+        let Ok(path) = spill.lock() else {
+            return vec![];
+        };
+        let Ok(content) = std::fs::read_to_string(&*path) else {
+            return vec![];
+        };
+        if let Err(e) = std::fs::remove_file(&*path) {
+            warn!("Failed to clear spill file {:?}: {}", *path, e);
+            return vec![];
+        }
+        self.spill_pending.store(0, Ordering::Relaxed);
+        content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()
+    }
+
+    /// Read spilled actions back into the queue whenever it is at most
+    /// half full
+    pub fn spawn_drain(self: &Arc<Self>) {
+        if self.spill.is_none() {
+            return;
+        }
+        let queue = self.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(DRAIN_INTERVAL).await;
+                if queue.spill_pending.load(Ordering::Relaxed) == 0
+                    || queue.tx.capacity() < queue.tx.max_capacity() / 2
+                {
+                    continue;
+                }
+                let actions = queue.take_spill();
+                info!("📤 Re-queueing {} spilled action(s)", actions.len());
+                for action in actions {
+                    if queue.tx.send(action).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Current counters
+    pub fn metrics(&self) -> QueueMetrics {
+        QueueMetrics {
+            capacity: self.tx.max_capacity(),
+            queued: self.tx.max_capacity() - self.tx.capacity(),
+            dropped_info: self.dropped_info.load(Ordering::Relaxed),
+            spilled: self.spilled.load(Ordering::Relaxed),
+            spill_pending: self.spill_pending.load(Ordering::Relaxed),
+            blocked: self.blocked.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Queue counters from the daemon's web API at `base_url`; None when
+/// nothing is listening
+pub async fn fetch(base_url: &str) -> anyhow::Result<Option<QueueMetrics>> {
+    let url = format!("{}/api/v1/collectors/queue", base_url.trim_end_matches('/'));
+    let resp = match reqwest::Client::new()
+        .get(&url)
+        .timeout(Duration::from_secs(3))
+        .send()
+        .await
+    {
+        Ok(resp) => resp,
+        Err(e) if e.is_connect() => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Some(resp.error_for_status()?.json().await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentType};
+
+    fn action(id: &str, content: &str) -> AgentAction {
+        AgentAction {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: content.to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_full_queue_drops_info_and_spills_the_rest() {
+        let dir = tempfile::tempdir().unwrap();
+        let (tx, mut rx) = mpsc::channel(1);
+        let queue = Arc::new(
+            ActionQueue::new(tx, |a: &AgentAction| {
+                if a.content.contains("rm -rf") {
+                    RiskLevel::Critical
+                } else {
+                    RiskLevel::Info
+                }
+            })
+            .with_spill(dir.path().join("spill.jsonl")),
+        );
+
+        queue.send(action("a1", "ls")).await.unwrap();
+        queue.send(action("a2", "ls")).await.unwrap();
+        queue.send(action("a3", "rm -rf /")).await.unwrap();
+        let metrics = queue.metrics();
+        assert_eq!(metrics.queued, 1);
+        assert_eq!(metrics.dropped_info, 1);
+        assert_eq!((metrics.spilled, metrics.spill_pending), (1, 1));
+
+        // Room again: the spilled action comes back, the dropped one doesn't
+        assert_eq!(rx.recv().await.unwrap().id, "a1");
+        queue.spawn_drain();
+        let back = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(back.id, "a3");
+        assert_eq!(queue.metrics().spill_pending, 0);
+    }
+}
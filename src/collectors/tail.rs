@@ -0,0 +1,200 @@
+//! Helpers shared by polling collectors: incremental JSONL reading and
+//! log file discovery
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Reads only the complete lines appended to files since the last call
+#[derive(Default)]
+pub struct JsonlTailer {
+    positions: HashMap<PathBuf, u64>,
+    /// Where positions are kept between daemon runs
+    state_file: Option<PathBuf>,
+    /// Positions changed since the last save
+    dirty: bool,
+}
+
+impl JsonlTailer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A tailer that resumes from the positions saved in `state_file`, so
+    /// a restart neither replays processed lines nor misses the ones
+    /// written while the daemon was down
+    pub fn persistent(state_file: PathBuf) -> Self {
+        let positions = std::fs::read_to_string(&state_file)
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+        Self {
+            positions,
+            state_file: Some(state_file),
+            dirty: false,
+        }
+    }
+
+    /// Skip the current contents of `path` so only new lines are reported,
+    /// unless a saved position says where we left off
+    pub fn prime(&mut self, path: &Path) {
+        if self.positions.contains_key(path) {
+            return;
+        }
+        if let Ok(meta) = std::fs::metadata(path) {
+            self.positions.insert(path.to_path_buf(), meta.len());
+            self.dirty = true;
+        }
+    }
+
+    /// Write positions to the state file, if any changed. Call after the
+    /// lines read have been handed on, so a crash replays rather than
+    /// loses them.
+    pub fn save(&mut self) {
+        let Some(state_file) = &self.state_file else {
+            return;
+        };
+        if !self.dirty {
+            return;
+        }
+        // Forget files that have been deleted
+        self.positions.retain(|path, _| path.exists());
+        let result = (|| -> anyhow::Result<()> {
+            if let Some(dir) = state_file.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let tmp = state_file.with_extension("tmp");
+            std::fs::write(&tmp, serde_json::to_vec(&self.positions)?)?;
+            std::fs::rename(&tmp, state_file)?;
+            Ok(())
+        })();
+        match result {
+            Ok(()) => self.dirty = false,
+            Err(e) => warn!("Failed to save read positions to {:?}: {}", state_file, e),
+        }
+    }
+
+    /// Return complete lines appended since the last read.
+    /// A trailing partial line is left for the next call.
+    pub fn read_new_lines(&mut self, path: &Path) -> Vec<String> {
+        self.read_lines(path, usize::MAX)
+    }
+
+    /// Like `read_new_lines`, but stop after `max` non-empty lines; the
+    /// rest is returned by the next call
+    pub fn read_lines(&mut self, path: &Path, max: usize) -> Vec<String> {
+        let Ok(mut file) = File::open(path) else {
+            return vec![];
+        };
+        let Ok(meta) = file.metadata() else {
+            return vec![];
+        };
+
+        let mut pos = self.positions.get(path).copied().unwrap_or(0);
+        // Truncated or rotated
+        if meta.len() < pos {
+            pos = 0;
+        }
+        if meta.len() == pos || file.seek(SeekFrom::Start(pos)).is_err() {
+            return vec![];
+        }
+
+        let mut buf = Vec::new();
+        if file.read_to_end(&mut buf).is_err() {
+            return vec![];
+        }
+
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (i, _) in buf.iter().enumerate().filter(|(_, &b)| b == b'\n') {
+            if lines.len() >= max {
+                break;
+            }
+            let line = String::from_utf8_lossy(&buf[start..i]);
+            let line = line.trim_end_matches('\r');
+            if !line.trim().is_empty() {
+                lines.push(line.to_string());
+            }
+            start = i + 1;
+        }
+        if start > 0 {
+            self.positions
+                .insert(path.to_path_buf(), pos + start as u64);
+            self.dirty = true;
+        }
+        lines
+    }
+}
+
+/// Find files under `dir` (up to `max_depth` levels deep) accepted by `filter`
+pub fn find_files(dir: &Path, max_depth: usize, filter: &dyn Fn(&Path) -> bool) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if max_depth > 0 {
+                files.extend(find_files(&path, max_depth - 1, filter));
+            }
+        } else if filter(&path) {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_tailer_skips_partial_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+        std::fs::write(&path, "old\n").unwrap();
+
+        let mut tailer = JsonlTailer::new();
+        tailer.prime(&path);
+        assert!(tailer.read_new_lines(&path).is_empty());
+
+        let mut f = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        write!(f, "one\ntw").unwrap();
+        assert_eq!(tailer.read_new_lines(&path), vec!["one"]);
+
+        writeln!(f, "o").unwrap();
+        assert_eq!(tailer.read_new_lines(&path), vec!["two"]);
+
+        // Batches pick up where the last one stopped
+        write!(f, "3\n\n4\n5\n").unwrap();
+        assert_eq!(tailer.read_lines(&path, 2), vec!["3", "4"]);
+        assert_eq!(tailer.read_lines(&path, 2), vec!["5"]);
+    }
+
+    #[test]
+    fn test_tailer_resumes_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.jsonl");
+        let state = dir.path().join("state/tailer.json");
+        std::fs::write(&path, "seen\n").unwrap();
+
+        let mut tailer = JsonlTailer::persistent(state.clone());
+        tailer.prime(&path);
+        std::fs::write(&path, "seen\nread\n").unwrap();
+        assert_eq!(tailer.read_new_lines(&path), vec!["read"]);
+        tailer.save();
+
+        // Written while the daemon was down
+        std::fs::write(&path, "seen\nread\nmissed\n").unwrap();
+        let mut tailer = JsonlTailer::persistent(state);
+        tailer.prime(&path);
+        assert_eq!(tailer.read_new_lines(&path), vec!["missed"]);
+    }
+}
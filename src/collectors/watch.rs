@@ -0,0 +1,257 @@
+//! How collectors notice new log data
+//!
+//! Every collector either polls its log files on an interval (the default,
+//! works everywhere) or waits for filesystem events (inotify/FSEvents via
+//! `notify`), which catches bursts quickly without touching the disk while
+//! the agent is idle. Settings are layered, most specific first:
+//!
+//! 1. `OPENCLAW_HARNESS_<COLLECTOR>_{WATCH,POLL_MS,DEBOUNCE_MS,MAX_BATCH}`,
+//!    e.g. `OPENCLAW_HARNESS_CLAUDE_CODE_WATCH=notify`
+//! 2. `OPENCLAW_HARNESS_COLLECTOR_{WATCH,POLL_MS,DEBOUNCE_MS,MAX_BATCH}`
+//! 3. `collectors.watch_overrides.<collector>` in the config
+//! 4. `collectors.watch` in the config
+//! 5. built-in defaults (poll, the collector's own interval, 100 ms
+//!    debounce, 500 lines per file per wake-up)
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+const DEFAULT_DEBOUNCE_MS: u64 = 100;
+const DEFAULT_MAX_BATCH: usize = 500;
+/// Longest wait for an event before rescanning anyway, in case the
+/// platform drops events (network filesystems, overflowed queues)
+const NOTIFY_RESCAN: Duration = Duration::from_secs(30);
+/// Debounce periods to wait out at most, so a log written non-stop is
+/// still read
+const MAX_DEBOUNCE_ROUNDS: u32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchStrategy {
+    /// Re-read on a fixed interval
+    Poll,
+    /// Wait for filesystem events
+    Notify,
+}
+
+impl std::str::FromStr for WatchStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "poll" => Ok(WatchStrategy::Poll),
+            "notify" | "watch" | "inotify" | "fsevents" => Ok(WatchStrategy::Notify),
+            other => anyhow::bail!("unknown watch strategy '{}' (poll or notify)", other),
+        }
+    }
+}
+
+/// Watch settings for one collector; unset fields fall back to the next
+/// layer
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchSettings {
+    pub strategy: Option<WatchStrategy>,
+    /// Poll interval; in notify mode only used if the watcher stops
+    pub poll_interval_ms: Option<u64>,
+    /// Quiet time after an event before reading, so a burst of writes is
+    /// read in one go
+    pub debounce_ms: Option<u64>,
+    /// Lines (or files, for one-event-per-file logs) handled per file per
+    /// wake-up; the rest is read right after without waiting
+    pub max_batch: Option<usize>,
+}
+
+impl WatchSettings {
+    /// From `<prefix>_WATCH`, `<prefix>_POLL_MS`, `<prefix>_DEBOUNCE_MS`
+    /// and `<prefix>_MAX_BATCH`
+    pub fn from_env(prefix: &str) -> Self {
+        let var = |name: &str| std::env::var(format!("{}_{}", prefix, name)).ok();
+        Self {
+            strategy: var("WATCH").and_then(|v| match v.parse() {
+                Ok(strategy) => Some(strategy),
+                Err(e) => {
+                    warn!("Ignoring {}_WATCH: {}", prefix, e);
+                    None
+                }
+            }),
+            poll_interval_ms: var("POLL_MS")
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|ms| *ms > 0),
+            debounce_ms: var("DEBOUNCE_MS").and_then(|v| v.trim().parse().ok()),
+            max_batch: var("MAX_BATCH")
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|n| *n > 0),
+        }
+    }
+
+    /// Fill unset fields from `fallback`
+    pub fn or(self, fallback: &WatchSettings) -> Self {
+        Self {
+            strategy: self.strategy.or(fallback.strategy),
+            poll_interval_ms: self.poll_interval_ms.or(fallback.poll_interval_ms),
+            debounce_ms: self.debounce_ms.or(fallback.debounce_ms),
+            max_batch: self.max_batch.or(fallback.max_batch),
+        }
+    }
+
+    pub fn max_batch(&self) -> usize {
+        self.max_batch.unwrap_or(DEFAULT_MAX_BATCH)
+    }
+}
+
+/// Waits until a collector should look at its files again
+pub struct LogWatcher {
+    poll: Duration,
+    debounce: Duration,
+    /// Set in notify mode; the watcher must stay alive to keep sending
+    events: Option<(RecommendedWatcher, mpsc::UnboundedReceiver<()>)>,
+}
+
+impl LogWatcher {
+    /// Watch `paths` (recursively when `recursive`). `default_poll_ms` is
+    /// the collector's own interval when none is configured. Falls back to
+    /// polling if the platform watcher can't be set up.
+    pub fn new(
+        settings: &WatchSettings,
+        default_poll_ms: u64,
+        paths: &[&Path],
+        recursive: bool,
+    ) -> Self {
+        let mut watcher = Self {
+            poll: Duration::from_millis(settings.poll_interval_ms.unwrap_or(default_poll_ms)),
+            debounce: Duration::from_millis(settings.debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS)),
+            events: None,
+        };
+        if settings.strategy == Some(WatchStrategy::Notify) {
+            match Self::notify_watcher(paths, recursive) {
+                Ok(events) => watcher.events = Some(events),
+                Err(e) => warn!("File watching unavailable, polling instead: {}", e),
+            }
+        }
+        watcher
+    }
+
+    fn notify_watcher(
+        paths: &[&Path],
+        recursive: bool,
+    ) -> anyhow::Result<(RecommendedWatcher, mpsc::UnboundedReceiver<()>)> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut watcher =
+            notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                Ok(event) if !event.kind.is_access() => {
+                    let _ = tx.send(());
+                }
+                Ok(_) => {}
+                Err(e) => debug!("File watch error: {}", e),
+            })?;
+        let mode = if recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        for path in paths {
+            watcher.watch(path, mode)?;
+        }
+        Ok((watcher, rx))
+    }
+
+    pub fn is_notify(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Also watch `path` (non-recursively), e.g. a newly found project
+    pub fn add(&mut self, path: &Path) {
+        if let Some((watcher, _)) = &mut self.events {
+            if let Err(e) = watcher.watch(path, RecursiveMode::NonRecursive) {
+                debug!("Failed to watch {:?}: {}", path, e);
+            }
+        }
+    }
+
+    /// Sleep for the poll interval, or until files changed and then stayed
+    /// quiet for the debounce time
+    pub async fn wait(&mut self) {
+        let Some((_, rx)) = &mut self.events else {
+            tokio::time::sleep(self.poll).await;
+            return;
+        };
+        if let Ok(None) = tokio::time::timeout(NOTIFY_RESCAN, rx.recv()).await {
+            // Watcher gone; don't spin
+            tokio::time::sleep(self.poll).await;
+            return;
+        }
+        for _ in 0..MAX_DEBOUNCE_ROUNDS {
+            tokio::time::sleep(self.debounce).await;
+            let mut more = false;
+            while rx.try_recv().is_ok() {
+                more = true;
+            }
+            if !more {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watch_settings_layering() {
+        let config = WatchSettings {
+            strategy: Some(WatchStrategy::Poll),
+            poll_interval_ms: Some(2000),
+            ..Default::default()
+        };
+        let collector = WatchSettings {
+            strategy: Some(WatchStrategy::Notify),
+            max_batch: Some(50),
+            ..Default::default()
+        };
+        let merged = collector.or(&config);
+        assert_eq!(merged.strategy, Some(WatchStrategy::Notify));
+        assert_eq!(merged.poll_interval_ms, Some(2000));
+        assert_eq!(merged.max_batch(), 50);
+        assert_eq!(WatchSettings::default().max_batch(), DEFAULT_MAX_BATCH);
+
+        assert_eq!(
+            "FSEvents".parse::<WatchStrategy>().unwrap(),
+            WatchStrategy::Notify
+        );
+        assert!("sometimes".parse::<WatchStrategy>().is_err());
+
+        let yaml: WatchSettings =
+            serde_yaml::from_str("strategy: notify\ndebounce_ms: 250").unwrap();
+        assert_eq!(yaml.strategy, Some(WatchStrategy::Notify));
+        assert_eq!(yaml.debounce_ms, Some(250));
+    }
+
+    #[tokio::test]
+    async fn test_notify_watcher_wakes_on_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = WatchSettings {
+            strategy: Some(WatchStrategy::Notify),
+            debounce_ms: Some(10),
+            ..Default::default()
+        };
+        let mut watcher = LogWatcher::new(&settings, 60_000, &[dir.path()], true);
+        if !watcher.is_notify() {
+            // No inotify in this sandbox
+            return;
+        }
+        let path = dir.path().join("log.jsonl");
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            std::fs::write(path, "line\n").unwrap();
+        });
+        tokio::time::timeout(Duration::from_secs(10), watcher.wait())
+            .await
+            .expect("write should wake the watcher");
+    }
+}
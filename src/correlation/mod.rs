@@ -0,0 +1,224 @@
+//! Correlation between proxy intercepts and collector events
+//!
+//! The same tool call is often seen twice: by the proxy when the model emits
+//! it, and by a log collector once the agent runs it. Before an action is
+//! stored we look for an event from the *other* source with the same
+//! fingerprint, a compatible session and a timestamp within the window. A
+//! match is recorded as an extra source of the existing action instead of a
+//! new row, so stats and alerts count it once.
+//!
+//! A collector restarted without its saved read positions can also re-emit
+//! log lines it already handled. Those are recognized by id, or by a hash
+//! of what the action is (see `replay_hash`) when the id isn't stable, and
+//! dropped.
+
+use crate::db::Database;
+use crate::AgentAction;
+use chrono::Duration;
+use sha2::{Digest, Sha256};
+
+/// Environment variable overriding the correlation window (seconds)
+pub const WINDOW_ENV: &str = "OPENCLAW_HARNESS_CORRELATION_WINDOW_SECS";
+const DEFAULT_WINDOW_SECS: i64 = 10;
+
+/// Correlation window from the environment, or the default
+pub fn window() -> Duration {
+    let secs = std::env::var(WINDOW_ENV)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_WINDOW_SECS);
+    Duration::seconds(secs)
+}
+
+/// Where an action was observed
+pub fn source_of(action: &AgentAction) -> &'static str {
+    crate::ids::Source::of(&action.id).as_str()
+}
+
+fn normalize(s: &str) -> String {
+    s.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Source-independent identity of an action. File and URL actions are keyed
+/// by target, since each source renders their content differently.
+pub fn fingerprint(action: &AgentAction) -> String {
+    let key = match action.target.as_deref().filter(|t| !t.is_empty()) {
+        Some(target) => normalize(target),
+        None => normalize(action.content.trim_end_matches(';')),
+    };
+    format!("{:?}|{}", action.action_type, key)
+}
+
+/// Whether `a` and `b` are the same logical event seen by different sources
+pub fn is_same_event(a: &AgentAction, b: &AgentAction, window: Duration) -> bool {
+    if source_of(a) == source_of(b) {
+        return false;
+    }
+    if let (Some(sa), Some(sb)) = (&a.session_id, &b.session_id) {
+        if sa != sb {
+            return false;
+        }
+    }
+    (a.timestamp - b.timestamp).abs() <= window && fingerprint(a) == fingerprint(b)
+}
+
+/// Identity of an action independent of its id: agent, type, timestamp,
+/// content, target and session
+pub fn replay_hash(action: &AgentAction) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        action.agent.to_string(),
+        format!("{:?}", action.action_type),
+        action.timestamp.to_rfc3339(),
+        action.content.clone(),
+        action.target.clone().unwrap_or_default(),
+        action.session_id.clone().unwrap_or_default(),
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0]);
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Outcome of recording an action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Correlation {
+    /// First sighting; stored as a new action
+    New,
+    /// Already stored under `primary_id`; recorded as an extra source
+    Duplicate { primary_id: String },
+    /// The same action, from the same source, was stored before as
+    /// `primary_id` (a replayed log line); nothing recorded
+    Replay { primary_id: String },
+}
+
+/// Find an already stored action that `action` duplicates
+pub fn find_primary(
+    db: &Database,
+    action: &AgentAction,
+    window: Duration,
+) -> anyhow::Result<Option<String>> {
+    let candidates =
+        db.get_actions_between(action.timestamp - window, action.timestamp + window)?;
+    for candidate in candidates {
+        if candidate.id == action.id || !is_same_event(&candidate, action, window) {
+            continue;
+        }
+        // Already paired with a sighting from this source: this is a new run
+        if db
+            .get_action_sources(&candidate.id)?
+            .iter()
+            .any(|s| s == source_of(action))
+        {
+            continue;
+        }
+        return Ok(Some(candidate.id));
+    }
+    Ok(None)
+}
+
+/// Store `action`, or link it to the action it duplicates
+pub fn record(
+    db: &Database,
+    action: &AgentAction,
+    window: Duration,
+) -> anyhow::Result<Correlation> {
+    if let Some(primary_id) = db.find_replay(action)? {
+        return Ok(Correlation::Replay { primary_id });
+    }
+    if let Some(primary_id) = find_primary(db, action, window)? {
+        db.link_action(&primary_id, &action.id, source_of(action))?;
+        return Ok(Correlation::Duplicate { primary_id });
+    }
+    db.store_action(action)?;
+    Ok(Correlation::New)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentType};
+
+    fn action(id: &str, content: &str, secs_ago: i64) -> AgentAction {
+        AgentAction {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now() - Duration::seconds(secs_ago),
+            agent: AgentType::OpenClaw,
+            action_type: ActionType::Exec,
+            content: content.to_string(),
+            target: None,
+            session_id: Some("s1".to_string()),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_proxy_and_collector_events_are_linked() {
+        let db = Database::open_in_memory().unwrap();
+        let window = Duration::seconds(10);
+
+        let proxied = action("proxy-1", "rm  -rf /tmp/x", 3);
+        assert_eq!(record(&db, &proxied, window).unwrap(), Correlation::New);
+
+        let collected = action("tool-1", "rm -rf /tmp/x", 0);
+        assert_eq!(
+            record(&db, &collected, window).unwrap(),
+            Correlation::Duplicate {
+                primary_id: "proxy-1".to_string()
+            }
+        );
+        assert_eq!(db.get_stats().unwrap().total_actions, 1);
+        assert_eq!(
+            db.get_action_sources("proxy-1").unwrap(),
+            vec!["collector".to_string()]
+        );
+
+        // Same source twice is two real runs
+        let again = action("tool-2", "rm -rf /tmp/x", 0);
+        assert_eq!(record(&db, &again, window).unwrap(), Correlation::New);
+
+        // Outside the window, or another session: unrelated
+        let late = action("proxy-2", "ls", 0);
+        let mut old = action("tool-3", "ls", 60);
+        assert!(!is_same_event(&late, &old, window));
+        old.timestamp = late.timestamp;
+        old.session_id = Some("s2".to_string());
+        assert!(!is_same_event(&late, &old, window));
+    }
+
+    #[test]
+    fn test_replayed_actions_are_dropped() {
+        let db = Database::open_in_memory().unwrap();
+        let window = Duration::seconds(10);
+
+        let first = action("tool-1", "make deploy", 5);
+        assert_eq!(record(&db, &first, window).unwrap(), Correlation::New);
+
+        // Same line re-read after a restart, same id or a fresh one
+        let mut replayed = first.clone();
+        let expected = Correlation::Replay {
+            primary_id: "tool-1".to_string(),
+        };
+        assert_eq!(record(&db, &replayed, window).unwrap(), expected);
+        replayed.id = "generated-42".to_string();
+        assert_eq!(record(&db, &replayed, window).unwrap(), expected);
+        assert_eq!(db.get_stats().unwrap().total_actions, 1);
+
+        // Same command run again later is a new action
+        let mut rerun = first.clone();
+        rerun.id = "tool-2".to_string();
+        rerun.timestamp = first.timestamp + Duration::seconds(1);
+        assert_eq!(record(&db, &rerun, window).unwrap(), Correlation::New);
+
+        // Still recognized after the action itself was purged
+        db.delete_action("tool-1").unwrap();
+        assert!(db.find_replay(&first).unwrap().is_some());
+    }
+}
@@ -0,0 +1,519 @@
+//! Tamper-evident action log
+//!
+//! With `OPENCLAW_HARNESS_HASH_CHAIN=1` the daemon starts a hash chain over
+//! the stored actions and analyses. Every insert, redaction and deletion
+//! adds an entry to `log_chain` holding a digest of the row and the hash
+//! of the entry before it, so changing or deleting a row, or an entry,
+//! breaks the chain. Starting the chain takes in the rows already stored.
+//! Once started it is kept up for as long as the database exists, whatever
+//! the variable says later.
+//!
+//! Whoever can edit the database can also recompute the whole chain, so
+//! every maintenance run the daemon appends the latest entry's hash to an
+//! anchor file outside the database: `~/.openclaw-harness/chain-anchors.jsonl`,
+//! or `OPENCLAW_HARNESS_CHAIN_ANCHORS` (best on storage the agent can't
+//! write, such as an append-only mount). `openclaw-harness verify-log`
+//! checks the chain, the rows and the anchors.
+
+use super::Database;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Set to `1` to start the chain
+pub const HASH_CHAIN_ENV: &str = "OPENCLAW_HARNESS_HASH_CHAIN";
+/// Where anchors are written
+pub const ANCHORS_ENV: &str = "OPENCLAW_HARNESS_CHAIN_ANCHORS";
+
+/// What a chain entry records
+pub const GENESIS: &str = "genesis";
+pub const INSERT: &str = "insert";
+pub const UPDATE: &str = "update";
+pub const DELETE: &str = "delete";
+
+const ACTION_COLUMNS: &str =
+    "id, timestamp, agent, action_type, content, target, session_id, metadata";
+const ANALYSIS_COLUMNS: &str =
+    "id, action_id, timestamp, matched_rules, risk_level, recommendation, explanation";
+
+pub fn enabled_from_env() -> bool {
+    std::env::var(HASH_CHAIN_ENV)
+        .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+pub fn anchors_path() -> PathBuf {
+    std::env::var(ANCHORS_ENV)
+        .ok()
+        .filter(|p| !p.trim().is_empty())
+        .map(|p| crate::expand_home(p.trim()))
+        .unwrap_or_else(|| crate::harness_path("chain-anchors.jsonl"))
+}
+
+/// A row a chain entry is about
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Row<'a> {
+    Action(&'a str),
+    Analysis(i64),
+}
+
+impl Row<'_> {
+    fn key(&self) -> String {
+        match self {
+            Row::Action(id) => format!("action:{}", id),
+            Row::Analysis(id) => format!("analysis:{}", id),
+        }
+    }
+}
+
+/// Hash of the chain's latest entry, as written to the anchor file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub seq: u64,
+    pub hash: String,
+    pub at: String,
+}
+
+/// What `verify_chain` found
+#[derive(Debug, Default, Serialize)]
+pub struct ChainReport {
+    pub started: bool,
+    pub entries: u64,
+    pub rows: u64,
+    pub anchors: usize,
+    pub problems: Vec<String>,
+}
+
+impl ChainReport {
+    pub fn ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+fn entry_hash(
+    seq: u64,
+    recorded_at: &str,
+    kind: &str,
+    key: &str,
+    digest: Option<&str>,
+    prev: &str,
+) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        &seq.to_string(),
+        recorded_at,
+        kind,
+        key,
+        digest.unwrap_or(""),
+        prev,
+    ] {
+        hasher.update(part.as_bytes());
+        hasher.update([0x1f]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Digest of every column of a row, as stored
+fn row_digest(row: &rusqlite::Row<'_>, columns: usize) -> rusqlite::Result<String> {
+    let mut hasher = Sha256::new();
+    for i in 0..columns {
+        let value: rusqlite::types::Value = row.get(i)?;
+        hasher.update(format!("{:?}", value).as_bytes());
+        hasher.update([0x1f]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+impl Database {
+    /// Whether the chain has been started
+    pub fn chaining(&self) -> anyhow::Result<bool> {
+        Ok(self
+            .conn
+            .query_row("SELECT EXISTS(SELECT 1 FROM log_chain)", [], |row| {
+                row.get(0)
+            })?)
+    }
+
+    /// Start the chain, taking in the rows already stored; false if it
+    /// was started before
+    pub fn start_chain(&self) -> anyhow::Result<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+        if self.chaining()? {
+            return Ok(false);
+        }
+        let mut head = self.append_entry(None, GENESIS, "", None)?;
+        for (key, digest) in self.row_digests()? {
+            head = self.append_entry(Some(head), INSERT, &key, Some(&digest))?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Add entries for rows just inserted, updated or deleted. Call it in
+    /// the transaction that changed them, after the change, so no other
+    /// writer can append in between. Does nothing unless chaining.
+    pub(crate) fn chain(&self, kind: &str, rows: &[Row<'_>]) -> anyhow::Result<()> {
+        if rows.is_empty() || !self.chaining()? {
+            return Ok(());
+        }
+        let mut head = self.chain_head()?.map(|a| (a.seq, a.hash));
+        for row in rows {
+            let digest = match kind {
+                DELETE => None,
+                _ => self.current_digest(row)?,
+            };
+            head = Some(self.append_entry(head, kind, &row.key(), digest.as_deref())?);
+        }
+        Ok(())
+    }
+
+    fn append_entry(
+        &self,
+        prev: Option<(u64, String)>,
+        kind: &str,
+        key: &str,
+        digest: Option<&str>,
+    ) -> anyhow::Result<(u64, String)> {
+        let (prev_seq, prev_hash) = prev.unwrap_or_default();
+        let seq = prev_seq + 1;
+        let recorded_at = chrono::Utc::now().to_rfc3339();
+        let hash = entry_hash(seq, &recorded_at, kind, key, digest, &prev_hash);
+        self.conn.execute(
+            r#"
+            INSERT INTO log_chain (seq, recorded_at, kind, row_key, digest, prev_hash, hash)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            params![seq, recorded_at, kind, key, digest, prev_hash, hash],
+        )?;
+        Ok((seq, hash))
+    }
+
+    fn current_digest(&self, row: &Row<'_>) -> anyhow::Result<Option<String>> {
+        let digest = match row {
+            Row::Action(id) => self
+                .conn
+                .query_row(
+                    &format!("SELECT {} FROM actions WHERE id = ?1", ACTION_COLUMNS),
+                    [id],
+                    |row| row_digest(row, 8),
+                )
+                .optional()?,
+            Row::Analysis(id) => self
+                .conn
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM analysis_results WHERE id = ?1",
+                        ANALYSIS_COLUMNS
+                    ),
+                    [id],
+                    |row| row_digest(row, 7),
+                )
+                .optional()?,
+        };
+        Ok(digest)
+    }
+
+    /// Digest of every stored action and analysis, by chain key
+    fn row_digests(&self) -> anyhow::Result<Vec<(String, String)>> {
+        let mut digests = vec![];
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM actions ORDER BY rowid",
+            ACTION_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                Row::Action(&row.get::<_, String>(0)?).key(),
+                row_digest(row, 8)?,
+            ))
+        })?;
+        digests.extend(rows.collect::<rusqlite::Result<Vec<_>>>()?);
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM analysis_results ORDER BY id",
+            ANALYSIS_COLUMNS
+        ))?;
+        let rows = stmt.query_map([], |row| {
+            Ok((Row::Analysis(row.get(0)?).key(), row_digest(row, 7)?))
+        })?;
+        digests.extend(rows.collect::<rusqlite::Result<Vec<_>>>()?);
+        Ok(digests)
+    }
+
+    /// Ids of the analyses of an action, to chain their deletion
+    pub(crate) fn analysis_ids(&self, action_id: &str) -> anyhow::Result<Vec<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM analysis_results WHERE action_id = ?1")?;
+        let ids = stmt.query_map([action_id], |row| row.get(0))?;
+        Ok(ids.collect::<rusqlite::Result<_>>()?)
+    }
+
+    /// The latest entry
+    pub fn chain_head(&self) -> anyhow::Result<Option<Anchor>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT seq, hash, recorded_at FROM log_chain ORDER BY seq DESC LIMIT 1",
+                [],
+                |row| {
+                    Ok(Anchor {
+                        seq: row.get(0)?,
+                        hash: row.get(1)?,
+                        at: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Append the latest entry to the anchor file at `path`, unless it is
+    /// anchored already; returns the new anchor
+    pub fn anchor_chain(&self, path: &Path) -> anyhow::Result<Option<Anchor>> {
+        let Some(head) = self.chain_head()? else {
+            return Ok(None);
+        };
+        let (anchors, _) = read_anchors(path);
+        if anchors.last().is_some_and(|a| a.seq == head.seq) {
+            return Ok(None);
+        }
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut options = std::fs::OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        writeln!(file, "{}", serde_json::to_string(&head)?)?;
+        Ok(Some(head))
+    }
+
+    /// Check every entry's hash and link, every row against the entry
+    /// that last recorded it, and the chain against the anchors at
+    /// `anchors` (if given)
+    pub fn verify_chain(&self, anchors: Option<&Path>) -> anyhow::Result<ChainReport> {
+        let mut report = ChainReport {
+            started: self.chaining()?,
+            ..Default::default()
+        };
+        if !report.started {
+            return Ok(report);
+        }
+
+        // The entries, and what each row should look like
+        let mut expected: HashMap<String, Option<String>> = HashMap::new();
+        let mut hashes: HashMap<u64, String> = HashMap::new();
+        let mut prev: (u64, String) = (0, String::new());
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT seq, recorded_at, kind, row_key, digest, prev_hash, hash
+            FROM log_chain ORDER BY seq
+            "#,
+        )?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let seq: u64 = row.get(0)?;
+            let recorded_at: String = row.get(1)?;
+            let kind: String = row.get(2)?;
+            let key: String = row.get(3)?;
+            let digest: Option<String> = row.get(4)?;
+            let prev_hash: String = row.get(5)?;
+            let hash: String = row.get(6)?;
+            report.entries += 1;
+
+            if seq != prev.0 + 1 {
+                report.problems.push(format!(
+                    "entries {}-{} are missing from the chain",
+                    prev.0 + 1,
+                    seq - 1
+                ));
+            } else if prev_hash != prev.1 {
+                report
+                    .problems
+                    .push(format!("entry {} does not follow entry {}", seq, prev.0));
+            }
+            let recomputed = entry_hash(
+                seq,
+                &recorded_at,
+                &kind,
+                &key,
+                digest.as_deref(),
+                &prev_hash,
+            );
+            if recomputed != hash {
+                report
+                    .problems
+                    .push(format!("entry {} ({} {}) was altered", seq, kind, key));
+            }
+            if kind != GENESIS {
+                expected.insert(key, digest);
+            }
+            hashes.insert(seq, hash.clone());
+            prev = (seq, hash);
+        }
+
+        // The rows as they are now
+        for (key, digest) in self.row_digests()? {
+            report.rows += 1;
+            match expected.remove(&key) {
+                Some(Some(recorded)) if recorded == digest => {}
+                Some(Some(_)) => report
+                    .problems
+                    .push(format!("{} was modified outside the harness", key)),
+                Some(None) => report
+                    .problems
+                    .push(format!("{} was deleted but is back", key)),
+                None => report
+                    .problems
+                    .push(format!("{} was inserted outside the harness", key)),
+            }
+        }
+        let mut deleted: Vec<String> = expected
+            .into_iter()
+            .filter(|(_, digest)| digest.is_some())
+            .map(|(key, _)| key)
+            .collect();
+        deleted.sort();
+        for key in deleted {
+            report
+                .problems
+                .push(format!("{} was deleted outside the harness", key));
+        }
+
+        if let Some(path) = anchors {
+            let (anchors, unreadable) = read_anchors(path);
+            report.anchors = anchors.len();
+            for line in unreadable {
+                report
+                    .problems
+                    .push(format!("{} line {} is unreadable", path.display(), line));
+            }
+            for anchor in &anchors {
+                match hashes.get(&anchor.seq) {
+                    Some(hash) if *hash == anchor.hash => {}
+                    Some(_) => report.problems.push(format!(
+                        "entry {} differs from its anchor of {}: the chain was rewritten",
+                        anchor.seq, anchor.at
+                    )),
+                    None => report.problems.push(format!(
+                        "entry {} (anchored {}) is gone: the chain was cut short",
+                        anchor.seq, anchor.at
+                    )),
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Anchors in the file at `path`, and the line numbers that don't parse
+fn read_anchors(path: &Path) -> (Vec<Anchor>, Vec<usize>) {
+    let content = std::fs::read_to_string(path).unwrap_or_default();
+    let mut anchors = vec![];
+    let mut unreadable = vec![];
+    for (i, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(line) {
+            Ok(anchor) => anchors.push(anchor),
+            Err(_) => unreadable.push(i + 1),
+        }
+    }
+    (anchors, unreadable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::privacy::{self, PurgeMode, PurgeScope};
+    use crate::{ActionType, AgentAction, AgentType, AnalysisResult, Recommendation, RiskLevel};
+
+    fn store(db: &Database, id: &str, session: &str) {
+        let action = AgentAction {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: format!("echo {}", id),
+            target: None,
+            session_id: Some(session.to_string()),
+            metadata: None,
+        };
+        db.store_action(&action).unwrap();
+        db.store_analysis(&AnalysisResult {
+            action,
+            matched_rules: vec![],
+            risk_level: RiskLevel::Info,
+            recommendation: Recommendation::LogOnly,
+            explanation: String::new(),
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_chain_detects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("harness.db");
+        let anchors = dir.path().join("anchors.jsonl");
+        let db = Database::open(&db_path).unwrap();
+        store(&db, "before-chain", "s1");
+        assert!(!db.verify_chain(None).unwrap().started);
+
+        assert!(db.start_chain().unwrap());
+        assert!(!db.start_chain().unwrap());
+        store(&db, "a1", "s1");
+        store(&db, "a2", "s2");
+        store(&db, "a3", "s3");
+        assert!(db.anchor_chain(&anchors).unwrap().is_some());
+        assert!(db.anchor_chain(&anchors).unwrap().is_none());
+
+        // Purges and redactions by the harness are recorded, not flagged
+        let scope = |session: &str| PurgeScope {
+            session_id: Some(session.to_string()),
+            ..Default::default()
+        };
+        let data = dir.path().join("data");
+        let pool = crate::db::DbPool::open(&db_path).unwrap();
+        privacy::purge(&pool, &data, &scope("s2"), PurgeMode::Delete, false, "test").unwrap();
+        privacy::purge(
+            &pool,
+            &data,
+            &scope("s3"),
+            PurgeMode::Anonymize,
+            false,
+            "test",
+        )
+        .unwrap();
+        let report = db.verify_chain(Some(&anchors)).unwrap();
+        assert!(report.ok(), "{:?}", report.problems);
+        assert_eq!(report.anchors, 1);
+
+        // A compromised agent edits and deletes history directly
+        db.conn
+            .execute("UPDATE actions SET content = 'ls' WHERE id = 'a1'", [])
+            .unwrap();
+        db.conn
+            .execute(
+                "DELETE FROM analysis_results WHERE action_id = 'before-chain'",
+                [],
+            )
+            .unwrap();
+        let problems = db.verify_chain(None).unwrap().problems;
+        assert_eq!(problems.len(), 2, "{:?}", problems);
+        assert!(problems[0].contains("action:a1 was modified"));
+        assert!(problems[1].contains("deleted outside"));
+
+        // ...and then rebuilds the chain to cover its tracks
+        db.conn.execute("DELETE FROM log_chain", []).unwrap();
+        db.start_chain().unwrap();
+        let problems = db.verify_chain(Some(&anchors)).unwrap().problems;
+        assert_eq!(problems.len(), 1, "{:?}", problems);
+        assert!(problems[0].contains("chain was"));
+    }
+}
@@ -0,0 +1,269 @@
+//! Keeping the database file in shape
+//!
+//! The daemon calls `Database::maintain` every `MAINTENANCE_INTERVAL`. It
+//! refreshes the query planner's statistics (`PRAGMA optimize`), hands
+//! free pages back to the filesystem (incremental vacuum) and, when the
+//! data outgrows `OPENCLAW_HARNESS_DB_MAX_SIZE_MB`, deletes the oldest
+//! Info events early until it fits again. Warning and Critical events are
+//! never pruned for size.
+//!
+//! `Database::health` reports the sizes for `/api/status` and
+//! `openclaw-harness status`.
+
+use super::Database;
+use crate::RiskLevel;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::info;
+
+/// Size limit in MiB; unset means no limit
+pub const DB_MAX_SIZE_ENV: &str = "OPENCLAW_HARNESS_DB_MAX_SIZE_MB";
+pub const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Counter of Info events pruned to stay under the size limit
+pub const INFO_EVENTS_PRUNED: &str = "db_info_events_pruned";
+/// Counter holding the time of the last maintenance run (unix seconds)
+const LAST_MAINTENANCE: &str = "db_last_maintenance";
+/// Info events deleted per round while over the limit
+const PRUNE_BATCH: usize = 1000;
+const MAX_PRUNE_ROUNDS: usize = 100;
+/// Events this recent are kept even when over the limit
+const PRUNE_MIN_AGE_HOURS: i64 = 24;
+
+/// Size limit from the environment, in bytes
+pub fn max_size_bytes() -> Option<u64> {
+    std::env::var(DB_MAX_SIZE_ENV)
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|mb| *mb > 0)
+        .map(|mb| mb * 1024 * 1024)
+}
+
+/// Database file sizes and upkeep
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbHealth {
+    /// Main database file
+    pub size_bytes: u64,
+    /// Part of the file holding data; the rest is free pages
+    pub used_bytes: u64,
+    /// Write-ahead log not yet checkpointed into the file
+    pub wal_bytes: u64,
+    pub max_size_bytes: Option<u64>,
+    pub actions: u64,
+    /// Info events deleted early to stay under the limit, ever
+    pub info_events_pruned: u64,
+    pub last_maintenance: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl DbHealth {
+    pub fn over_limit(&self) -> bool {
+        self.max_size_bytes
+            .is_some_and(|max| self.used_bytes + self.wal_bytes > max)
+    }
+}
+
+/// What one maintenance run did
+#[derive(Debug, Default, PartialEq)]
+pub struct MaintenanceReport {
+    pub pruned: usize,
+    pub reclaimed_bytes: u64,
+}
+
+impl Database {
+    fn pragma(&self, name: &str) -> anyhow::Result<u64> {
+        let value: i64 = self
+            .conn
+            .query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))?;
+        Ok(value.max(0) as u64)
+    }
+
+    /// File the connection is on; None in memory
+    fn file(&self) -> anyhow::Result<Option<PathBuf>> {
+        let file: String = self
+            .conn
+            .query_row("PRAGMA database_list", [], |row| row.get(2))?;
+        Ok(Some(PathBuf::from(file)).filter(|p| !p.as_os_str().is_empty()))
+    }
+
+    pub fn health(&self) -> anyhow::Result<DbHealth> {
+        let page_size = self.pragma("page_size")?;
+        let pages = self.pragma("page_count")?;
+        let free = self.pragma("freelist_count")?;
+        let wal_bytes = self
+            .file()?
+            .and_then(|f| std::fs::metadata(format!("{}-wal", f.display())).ok())
+            .map_or(0, |m| m.len());
+        let last = self.get_counter(LAST_MAINTENANCE)?;
+        Ok(DbHealth {
+            size_bytes: pages * page_size,
+            used_bytes: pages.saturating_sub(free) * page_size,
+            wal_bytes,
+            max_size_bytes: max_size_bytes(),
+            actions: self.count_actions()?,
+            info_events_pruned: self.get_counter(INFO_EVENTS_PRUNED)?,
+            last_maintenance: (last > 0)
+                .then(|| chrono::DateTime::from_timestamp(last as i64, 0))
+                .flatten(),
+        })
+    }
+
+    fn count_actions(&self) -> anyhow::Result<u64> {
+        let n: i64 = self
+            .conn
+            .query_row("SELECT COUNT(*) FROM actions", [], |row| row.get(0))?;
+        Ok(n.max(0) as u64)
+    }
+
+    /// Optimize, prune Info events while over `max_size` bytes, and
+    /// release free pages
+    pub fn maintain(&self, max_size: Option<u64>) -> anyhow::Result<MaintenanceReport> {
+        let before = self.health()?.size_bytes;
+        self.conn.execute_batch("PRAGMA optimize;")?;
+
+        let mut report = MaintenanceReport::default();
+        if let Some(max) = max_size {
+            for _ in 0..MAX_PRUNE_ROUNDS {
+                let health = self.health()?;
+                if health.used_bytes + health.wal_bytes <= max {
+                    break;
+                }
+                let pruned = self.prune_oldest_info(PRUNE_BATCH)?;
+                if pruned == 0 {
+                    break;
+                }
+                report.pruned += pruned;
+                // Fold the WAL back in so its size stops counting
+                self.conn
+                    .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+            }
+            if report.pruned > 0 {
+                self.add_to_counter(INFO_EVENTS_PRUNED, report.pruned as u64)?;
+                info!(
+                    "🧹 Database over {} MiB, pruned {} old info event(s)",
+                    max / (1024 * 1024),
+                    report.pruned
+                );
+            }
+        }
+
+        self.release_free_pages()?;
+        report.reclaimed_bytes = before.saturating_sub(self.health()?.size_bytes);
+        self.set_counter(
+            LAST_MAINTENANCE,
+            chrono::Utc::now().timestamp().max(0) as u64,
+        )?;
+        Ok(report)
+    }
+
+    /// Shrink the file by its free pages. Databases created before
+    /// incremental vacuum was enabled need one full VACUUM to switch,
+    /// done only once a quarter of the file is free.
+    fn release_free_pages(&self) -> anyhow::Result<()> {
+        const INCREMENTAL: u64 = 2;
+        if self.pragma("auto_vacuum")? == INCREMENTAL {
+            self.conn.execute_batch("PRAGMA incremental_vacuum;")?;
+            return Ok(());
+        }
+        let free = self.pragma("freelist_count")?;
+        if free > 0 && free * 4 >= self.pragma("page_count")? {
+            info!("🧹 Compacting database ({} free pages)", free);
+            self.conn
+                .execute_batch("PRAGMA auto_vacuum = INCREMENTAL; VACUUM;")?;
+        }
+        Ok(())
+    }
+
+    /// Delete up to `limit` of the oldest events that no rule flagged,
+    /// with their analyses. Returns how many were deleted.
+    fn prune_oldest_info(&self, limit: usize) -> anyhow::Result<usize> {
+        let cutoff = chrono::Utc::now() - chrono::Duration::hours(PRUNE_MIN_AGE_HOURS);
+        let tx = self.conn.unchecked_transaction()?;
+        let ids: Vec<String> = {
+            let mut stmt = tx.prepare(
+                r#"
+                SELECT a.id FROM actions a
+                WHERE a.timestamp < ?1
+                  AND NOT EXISTS (
+                      SELECT 1 FROM analysis_results r
+                      WHERE r.action_id = a.id AND r.risk_level != ?3
+                  )
+                ORDER BY a.timestamp
+                LIMIT ?2
+                "#,
+            )?;
+            let rows = stmt.query_map(
+                rusqlite::params![cutoff.to_rfc3339(), limit as i64, RiskLevel::Info.as_str()],
+                |row| row.get(0),
+            )?;
+            rows.collect::<rusqlite::Result<_>>()?
+        };
+        for id in &ids {
+            let analysis_ids = self.analysis_ids(id)?;
+            tx.execute("DELETE FROM analysis_results WHERE action_id = ?1", [id])?;
+            tx.execute(
+                "DELETE FROM action_sources WHERE action_id = ?1 OR source_action_id = ?1",
+                [id],
+            )?;
+            tx.execute("DELETE FROM actions WHERE id = ?1", [id])?;
+            self.chain_deletion(id, true, &analysis_ids)?;
+        }
+        tx.commit()?;
+        Ok(ids.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentAction, AgentType, AnalysisResult, Recommendation};
+
+    fn store(db: &Database, id: &str, days_ago: i64, risk: RiskLevel) {
+        let action = AgentAction {
+            id: id.to_string(),
+            timestamp: chrono::Utc::now() - chrono::Duration::days(days_ago),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "x".repeat(4096),
+            target: None,
+            session_id: None,
+            metadata: None,
+        };
+        db.store_action(&action).unwrap();
+        db.store_analysis(&AnalysisResult {
+            action,
+            matched_rules: vec![],
+            risk_level: risk,
+            recommendation: Recommendation::LogOnly,
+            explanation: String::new(),
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_over_limit_prunes_old_info_events_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&dir.path().join("harness.db")).unwrap();
+        for i in 0..200 {
+            store(&db, &format!("old-info-{}", i), 10, RiskLevel::Info);
+        }
+        store(&db, "old-critical", 10, RiskLevel::Critical);
+        store(&db, "new-info", 0, RiskLevel::Info);
+
+        let health = db.health().unwrap();
+        assert_eq!(health.actions, 202);
+        assert!(health.size_bytes > 200 * 4096);
+
+        // No limit: nothing is deleted
+        assert_eq!(db.maintain(None).unwrap().pruned, 0);
+
+        let report = db.maintain(Some(64 * 1024)).unwrap();
+        assert_eq!(report.pruned, 200);
+        assert!(db.get_action("old-critical").unwrap().is_some());
+        assert!(db.get_action("new-info").unwrap().is_some());
+
+        let health = db.health().unwrap();
+        assert_eq!(health.info_events_pruned, 200);
+        assert!(health.last_maintenance.is_some());
+        assert!(health.size_bytes < 200 * 4096);
+    }
+}
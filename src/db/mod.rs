@@ -1,19 +1,143 @@
 //! SQLite database for storing action logs and analysis results
 
-use super::{ActionType, AgentAction, AgentType, AnalysisResult};
-use rusqlite::{params, Connection};
+use super::{ActionType, AgentAction, AgentType, AnalysisResult, Recommendation, RiskLevel};
+use crate::diagnostics::{Diagnostic, Source};
+use crate::proxy::health::UpstreamHealth;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::HashMap;
 use std::path::Path;
 use tracing::info;
 
+pub mod chain;
+pub mod maintenance;
+pub mod pool;
+pub mod rule_history;
+pub use maintenance::DbHealth;
+pub use pool::DbPool;
+pub use rule_history::RuleVersion;
+
+/// Counter of actions not stored because their id was already taken
+pub const ACTION_ID_CONFLICTS: &str = "action_id_conflicts";
+
+/// Schema changes after the base schema in `initialize`, applied in order.
+/// `PRAGMA user_version` holds how many have run; only ever append.
+const MIGRATIONS: &[&str] = &[
+    // 1: analysis lookups by action (latest analysis, joins, deletes) and
+    // risk/time filters (reports, brain) scanned the whole table
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_analysis_action ON analysis_results(action_id);
+    CREATE INDEX IF NOT EXISTS idx_analysis_risk_time ON analysis_results(risk_level, timestamp);
+    DROP INDEX IF EXISTS idx_analysis_risk;
+    "#,
+    // 2: risk levels were stored capitalized ("Warning"); store
+    // RiskLevel::as_str instead
+    r#"
+    UPDATE analysis_results SET risk_level = lower(risk_level)
+    WHERE risk_level != lower(risk_level);
+    "#,
+    // 3: versions of rules changed through the API (`rule_history`)
+    r#"
+    CREATE TABLE IF NOT EXISTS rule_history (
+        rule_name TEXT NOT NULL,
+        version INTEGER NOT NULL,
+        changed_at TEXT NOT NULL,
+        changed_by TEXT NOT NULL,
+        change TEXT NOT NULL,
+        diff TEXT NOT NULL,
+        rule TEXT,
+        PRIMARY KEY (rule_name, version)
+    );
+    "#,
+    // 4: hash chain over actions and analyses (see chain)
+    r#"
+    CREATE TABLE IF NOT EXISTS log_chain (
+        seq INTEGER PRIMARY KEY,
+        recorded_at TEXT NOT NULL,
+        kind TEXT NOT NULL,
+        row_key TEXT NOT NULL,
+        digest TEXT,
+        prev_hash TEXT NOT NULL,
+        hash TEXT NOT NULL
+    );
+    "#,
+    // 5: decision notes written when a session ends (see brain::notes)
+    r#"
+    CREATE TABLE IF NOT EXISTS decision_notes (
+        session_id TEXT PRIMARY KEY,
+        agent TEXT NOT NULL,
+        started_at TEXT NOT NULL,
+        ended_at TEXT NOT NULL,
+        title TEXT NOT NULL,
+        path TEXT NOT NULL,
+        created_at TEXT NOT NULL
+    );
+    "#,
+    // 6: behaviour records that adaptive campaigns plan from, posted or
+    // derived from actions (see campaign::behaviours)
+    r#"
+    CREATE TABLE IF NOT EXISTS Behaviours (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        user_id TEXT NOT NULL,
+        event_type TEXT NOT NULL,
+        success INTEGER NOT NULL,
+        duration_minutes INTEGER NOT NULL,
+        created_at TEXT NOT NULL,
+        source TEXT NOT NULL,
+        action_id TEXT UNIQUE
+    );
+    CREATE INDEX IF NOT EXISTS idx_behaviours_user_time ON Behaviours(user_id, created_at);
+    CREATE INDEX IF NOT EXISTS idx_actions_session_time ON actions(session_id, timestamp);
+    "#,
+    // 7: the workspace each action belongs to (see workspace); the column
+    // itself is added by `migrate`, as ADD COLUMN can't be repeated
+    r#"
+    CREATE INDEX IF NOT EXISTS idx_actions_workspace_time ON actions(workspace, timestamp);
+    "#,
+    // 8: harness-internal failures per source and component (see
+    // diagnostics)
+    r#"
+    CREATE TABLE IF NOT EXISTS diagnostics (
+        source TEXT NOT NULL,
+        component TEXT NOT NULL,
+        count INTEGER NOT NULL,
+        first_seen TEXT NOT NULL,
+        last_seen TEXT NOT NULL,
+        last_message TEXT NOT NULL,
+        PRIMARY KEY (source, component)
+    );
+    "#,
+];
+
 pub struct Database {
-    conn: Connection,
+    conn: Conn,
+}
+
+/// A connection of its own, or one borrowed from a `DbPool`
+enum Conn {
+    Owned(Connection),
+    Pooled(r2d2::PooledConnection<pool::SqliteManager>),
+}
+
+impl std::ops::Deref for Conn {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        match self {
+            Conn::Owned(conn) => conn,
+            Conn::Pooled(conn) => conn,
+        }
+    }
 }
 
 impl Database {
-    /// Open or create the database
+    /// Open or create the database. Long-running components should take
+    /// connections from a shared `DbPool` instead.
     pub fn open(path: &Path) -> anyhow::Result<Self> {
         let conn = Connection::open(path)?;
-        let db = Self { conn };
+        pool::configure(&conn)?;
+        let db = Self {
+            conn: Conn::Owned(conn),
+        };
         db.initialize()?;
         Ok(db)
     }
@@ -21,15 +145,27 @@ impl Database {
     /// Open an in-memory database (for testing)
     pub fn open_in_memory() -> anyhow::Result<Self> {
         let conn = Connection::open_in_memory()?;
-        let db = Self { conn };
+        let db = Self {
+            conn: Conn::Owned(conn),
+        };
         db.initialize()?;
         Ok(db)
     }
 
+    /// Wrap a pooled connection; the pool has already set up the schema
+    fn pooled(conn: r2d2::PooledConnection<pool::SqliteManager>) -> Self {
+        Self {
+            conn: Conn::Pooled(conn),
+        }
+    }
+
     /// Initialize database schema
     fn initialize(&self) -> anyhow::Result<()> {
         self.conn.execute_batch(
             r#"
+            -- Only takes effect on a new database (see maintenance)
+            PRAGMA auto_vacuum = INCREMENTAL;
+
             CREATE TABLE IF NOT EXISTS actions (
                 id TEXT PRIMARY KEY,
                 timestamp TEXT NOT NULL,
@@ -52,22 +188,173 @@ impl Database {
                 FOREIGN KEY (action_id) REFERENCES actions(id)
             );
 
+            -- Extra sightings of an action by other sources (see correlation)
+            CREATE TABLE IF NOT EXISTS action_sources (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                action_id TEXT NOT NULL,
+                source_action_id TEXT NOT NULL,
+                source TEXT NOT NULL,
+                linked_at TEXT NOT NULL,
+                FOREIGN KEY (action_id) REFERENCES actions(id)
+            );
+
+            -- Event bus journal and per-consumer progress (see events)
+            CREATE TABLE IF NOT EXISTS event_log (
+                seq INTEGER PRIMARY KEY AUTOINCREMENT,
+                published_at TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                payload TEXT NOT NULL
+            );
+
+            CREATE TABLE IF NOT EXISTS consumer_offsets (
+                consumer TEXT PRIMARY KEY,
+                seq INTEGER NOT NULL
+            );
+
+            -- One row per data purge/anonymization (see privacy)
+            CREATE TABLE IF NOT EXISTS purge_audit (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                timestamp TEXT NOT NULL,
+                mode TEXT NOT NULL,
+                scope TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                requested_by TEXT NOT NULL
+            );
+
+            -- Cumulative proxy counters, flushed periodically by the proxy
+            CREATE TABLE IF NOT EXISTS counters (
+                name TEXT PRIMARY KEY,
+                value INTEGER NOT NULL
+            );
+
+            -- Latest upstream health reported by the proxy
+            CREATE TABLE IF NOT EXISTS provider_health (
+                target TEXT PRIMARY KEY,
+                position INTEGER NOT NULL,
+                health TEXT NOT NULL
+            );
+
+            -- Token usage per day/session/provider/model (see proxy::usage)
+            CREATE TABLE IF NOT EXISTS usage (
+                day TEXT NOT NULL,
+                session_id TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                requests INTEGER NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cost_usd REAL NOT NULL,
+                PRIMARY KEY (day, session_id, provider, model)
+            );
+
+            -- Request body bytes sent per day/session/provider (see proxy::egress)
+            CREATE TABLE IF NOT EXISTS egress (
+                day TEXT NOT NULL,
+                session_id TEXT NOT NULL,
+                provider TEXT NOT NULL,
+                requests INTEGER NOT NULL,
+                bytes INTEGER NOT NULL,
+                PRIMARY KEY (day, session_id, provider)
+            );
+
+            -- Canary tokens embedded in proxy block messages (see canary)
+            CREATE TABLE IF NOT EXISTS canary_tokens (
+                token TEXT PRIMARY KEY,
+                created_at TEXT NOT NULL,
+                rule_name TEXT NOT NULL,
+                tool_name TEXT NOT NULL,
+                agent TEXT NOT NULL,
+                session_id TEXT,
+                trigger_count INTEGER NOT NULL DEFAULT 0,
+                last_triggered TEXT
+            );
+
+            -- Matches per rule and day, split by outcome (see record_rule_match)
+            CREATE TABLE IF NOT EXISTS rule_stats (
+                rule_name TEXT NOT NULL,
+                day TEXT NOT NULL,
+                blocked INTEGER NOT NULL,
+                monitored INTEGER NOT NULL,
+                last_matched TEXT NOT NULL,
+                PRIMARY KEY (rule_name, day)
+            );
+
+            -- PauseAndAsk tool calls waiting for a human decision (see slack
+            -- and enforcer::confirm)
+            CREATE TABLE IF NOT EXISTS approvals (
+                id TEXT PRIMARY KEY,
+                fingerprint TEXT NOT NULL,
+                summary TEXT NOT NULL,
+                requested_at TEXT NOT NULL,
+                status TEXT NOT NULL,
+                decided_by TEXT,
+                decided_at TEXT,
+                announced INTEGER NOT NULL DEFAULT 0
+            );
+
+            -- Content hash of every stored action, to recognize a log line
+            -- replayed under a new id (see correlation::replay_hash). Kept
+            -- when an action is purged, so a replay doesn't bring it back.
+            CREATE TABLE IF NOT EXISTS action_hashes (
+                hash TEXT PRIMARY KEY,
+                action_id TEXT NOT NULL,
+                timestamp TEXT NOT NULL
+            );
+
             CREATE INDEX IF NOT EXISTS idx_actions_timestamp ON actions(timestamp);
+            CREATE INDEX IF NOT EXISTS idx_action_sources_action ON action_sources(action_id);
             CREATE INDEX IF NOT EXISTS idx_actions_agent ON actions(agent);
-            CREATE INDEX IF NOT EXISTS idx_analysis_risk ON analysis_results(risk_level);
             "#,
         )?;
+        self.migrate()?;
 
         info!("Database initialized");
         Ok(())
     }
 
-    /// Store an action
-    pub fn store_action(&self, action: &AgentAction) -> anyhow::Result<()> {
-        self.conn.execute(
+    /// Apply the migrations this database hasn't seen yet
+    fn migrate(&self) -> anyhow::Result<()> {
+        self.add_column("actions", "workspace", "TEXT NOT NULL DEFAULT 'default'")?;
+        let version: i64 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version.max(0) as usize) {
+            let tx = self.conn.unchecked_transaction()?;
+            tx.execute_batch(migration)?;
+            tx.execute_batch(&format!("PRAGMA user_version = {}", i + 1))?;
+            tx.commit()?;
+            info!("Database migrated to version {}", i + 1);
+        }
+        Ok(())
+    }
+
+    /// Add a column unless the table already has it
+    fn add_column(&self, table: &str, column: &str, definition: &str) -> anyhow::Result<()> {
+        let exists: bool = self.conn.query_row(
+            &format!(
+                "SELECT EXISTS (SELECT 1 FROM pragma_table_info('{}') WHERE name = ?1)",
+                table
+            ),
+            [column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            self.conn.execute_batch(&format!(
+                "ALTER TABLE {} ADD COLUMN {} {}",
+                table, column, definition
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Store an action; returns false, and counts an id conflict, if one
+    /// with the same id is already stored
+    pub fn store_action(&self, action: &AgentAction) -> anyhow::Result<bool> {
+        let tx = self.conn.unchecked_transaction()?;
+        let inserted = self.conn.execute(
             r#"
-            INSERT INTO actions (id, timestamp, agent, action_type, content, target, session_id, metadata)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            INSERT OR IGNORE INTO actions (id, timestamp, agent, action_type, content, target, session_id, metadata, workspace)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
             "#,
             params![
                 action.id,
@@ -78,14 +365,49 @@ impl Database {
                 action.target,
                 action.session_id,
                 action.metadata.as_ref().map(|m| m.to_string()),
+                crate::workspace::registry().resolve(action),
             ],
         )?;
+        if inserted == 0 {
+            self.increment_counter(ACTION_ID_CONFLICTS)?;
+            tx.commit()?;
+            return Ok(false);
+        }
+        self.conn.execute(
+            "INSERT OR IGNORE INTO action_hashes (hash, action_id, timestamp) VALUES (?1, ?2, ?3)",
+            params![
+                crate::correlation::replay_hash(action),
+                action.id,
+                action.timestamp.to_rfc3339()
+            ],
+        )?;
+        self.chain(chain::INSERT, &[chain::Row::Action(&action.id)])?;
+        tx.commit()?;
 
-        Ok(())
+        Ok(true)
+    }
+
+    /// Id of an already stored action that `action` repeats: same id, or
+    /// same content hash
+    pub fn find_replay(&self, action: &AgentAction) -> anyhow::Result<Option<String>> {
+        Ok(self
+            .conn
+            .query_row(
+                r#"
+                SELECT id FROM actions WHERE id = ?1
+                UNION ALL
+                SELECT action_id FROM action_hashes WHERE hash = ?2
+                LIMIT 1
+                "#,
+                params![action.id, crate::correlation::replay_hash(action)],
+                |row| row.get(0),
+            )
+            .optional()?)
     }
 
     /// Store an analysis result
     pub fn store_analysis(&self, result: &AnalysisResult) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
         self.conn.execute(
             r#"
             INSERT INTO analysis_results (action_id, timestamp, matched_rules, risk_level, recommendation, explanation)
@@ -95,15 +417,83 @@ impl Database {
                 result.action.id,
                 chrono::Utc::now().to_rfc3339(),
                 result.matched_rules.join(","),
-                format!("{:?}", result.risk_level),
+                result.risk_level.as_str(),
                 format!("{:?}", result.recommendation),
                 result.explanation,
             ],
         )?;
+        let id = self.conn.last_insert_rowid();
+        self.chain(chain::INSERT, &[chain::Row::Analysis(id)])?;
+
+        // The daemon sees actions after the fact; only a critical alert tries to stop one
+        let blocked = result.recommendation == Recommendation::CriticalAlert;
+        for rule in &result.matched_rules {
+            self.record_rule_match(rule, result.action.timestamp, blocked)?;
+        }
+        tx.commit()?;
 
         Ok(())
     }
 
+    /// Count a match of `rule_name` at `at`, blocked or only monitored
+    pub fn record_rule_match(
+        &self,
+        rule_name: &str,
+        at: chrono::DateTime<chrono::Utc>,
+        blocked: bool,
+    ) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO rule_stats (rule_name, day, blocked, monitored, last_matched)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            ON CONFLICT(rule_name, day) DO UPDATE SET
+                blocked = blocked + excluded.blocked,
+                monitored = monitored + excluded.monitored,
+                last_matched = MAX(last_matched, excluded.last_matched)
+            "#,
+            params![
+                rule_name,
+                at.format("%Y-%m-%d").to_string(),
+                blocked as i64,
+                !blocked as i64,
+                at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Match statistics per rule name, with daily counts from `since_day`
+    /// (YYYY-MM-DD) onwards. Rules that never matched are absent.
+    pub fn rule_stats(&self, since_day: &str) -> anyhow::Result<HashMap<String, RuleStats>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT rule_name, day, blocked, monitored, last_matched FROM rule_stats ORDER BY day",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)? as u64,
+                row.get::<_, i64>(3)? as u64,
+                row.get::<_, String>(4)?,
+            ))
+        })?;
+        let mut stats: HashMap<String, RuleStats> = HashMap::new();
+        for row in rows {
+            let (rule, day, blocked, monitored, last_matched) = row?;
+            let entry = stats.entry(rule).or_default();
+            entry.total += blocked + monitored;
+            entry.blocked += blocked;
+            entry.monitored += monitored;
+            if entry.last_matched.as_deref() < Some(last_matched.as_str()) {
+                entry.last_matched = Some(last_matched);
+            }
+            if day.as_str() >= since_day {
+                entry.by_day.insert(day, blocked + monitored);
+            }
+        }
+        Ok(stats)
+    }
+
     /// Get recent actions
     pub fn get_recent_actions(&self, limit: usize) -> anyhow::Result<Vec<AgentAction>> {
         let mut stmt = self.conn.prepare(
@@ -116,28 +506,302 @@ impl Database {
         )?;
 
         let actions = stmt
-            .query_map([limit], |row| {
-                Ok(AgentAction {
-                    id: row.get(0)?,
-                    timestamp: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
-                        .unwrap_or_default()
-                        .with_timezone(&chrono::Utc),
-                    agent: parse_agent_type(&row.get::<_, String>(2)?),
-                    action_type: parse_action_type(&row.get::<_, String>(3)?),
-                    content: row.get(4)?,
-                    target: row.get(5)?,
-                    session_id: row.get(6)?,
-                    metadata: row
-                        .get::<_, Option<String>>(7)?
-                        .and_then(|s| serde_json::from_str(&s).ok()),
-                })
-            })?
+            .query_map([limit], row_to_action)?
             .filter_map(|r| r.ok())
             .collect();
 
         Ok(actions)
     }
 
+    /// Get a single action by id
+    pub fn get_action(&self, id: &str) -> anyhow::Result<Option<AgentAction>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, timestamp, agent, action_type, content, target, session_id, metadata
+            FROM actions
+            WHERE id = ?1
+            "#,
+        )?;
+
+        let mut rows = stmt.query_map([id], row_to_action)?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Get actions with timestamps in `[from, to]`, oldest first
+    pub fn get_actions_between(
+        &self,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<AgentAction>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, timestamp, agent, action_type, content, target, session_id, metadata
+            FROM actions
+            WHERE timestamp >= ?1 AND timestamp <= ?2
+            ORDER BY timestamp ASC
+            "#,
+        )?;
+
+        let actions = stmt
+            .query_map([from.to_rfc3339(), to.to_rfc3339()], row_to_action)?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(actions)
+    }
+
+    /// Visit actions (oldest first) joined with their latest analysis, for export.
+    /// Rows are read one at a time; returns the number visited.
+    pub fn for_each_export_row(
+        &self,
+        since: Option<chrono::DateTime<chrono::Utc>>,
+        mut f: impl FnMut(crate::export::ExportRow) -> anyhow::Result<()>,
+    ) -> anyhow::Result<usize> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT a.id, a.timestamp, a.agent, a.action_type, a.content, a.target,
+                   a.session_id, a.metadata,
+                   r.risk_level, r.matched_rules, r.recommendation, r.explanation
+            FROM actions a
+            LEFT JOIN analysis_results r ON r.id = (
+                SELECT MAX(id) FROM analysis_results WHERE action_id = a.id
+            )
+            WHERE a.timestamp >= ?1
+            ORDER BY a.timestamp ASC
+            "#,
+        )?;
+
+        let since = since.map(|t| t.to_rfc3339()).unwrap_or_default();
+        let mut rows = stmt.query([since])?;
+        let mut count = 0;
+        while let Some(row) = rows.next()? {
+            f(crate::export::ExportRow {
+                id: row.get(0)?,
+                timestamp: row.get(1)?,
+                agent: row.get(2)?,
+                action_type: row.get(3)?,
+                content: row.get(4)?,
+                target: row.get(5)?,
+                session_id: row.get(6)?,
+                metadata: row
+                    .get::<_, Option<String>>(7)?
+                    .and_then(|s| serde_json::from_str(&s).ok()),
+                risk_level: row.get(8)?,
+                matched_rules: row
+                    .get::<_, Option<String>>(9)?
+                    .map(|r| {
+                        r.split(',')
+                            .filter(|s| !s.is_empty())
+                            .map(String::from)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                recommendation: row.get(10)?,
+                explanation: row.get(11)?,
+            })?;
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Record that `source_action_id` (seen by `source`) is the same event as `action_id`
+    pub fn link_action(
+        &self,
+        action_id: &str,
+        source_action_id: &str,
+        source: &str,
+    ) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO action_sources (action_id, source_action_id, source, linked_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+            params![
+                action_id,
+                source_action_id,
+                source,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Latest analysis stored for an action
+    pub fn get_analysis(&self, action_id: &str) -> anyhow::Result<Option<StoredAnalysis>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT matched_rules, risk_level, recommendation, explanation
+            FROM analysis_results
+            WHERE action_id = ?1
+            ORDER BY id DESC
+            LIMIT 1
+            "#,
+        )?;
+
+        let mut rows = stmt.query_map([action_id], |row| {
+            Ok(StoredAnalysis {
+                matched_rules: row
+                    .get::<_, String>(0)?
+                    .split(',')
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect(),
+                risk_level: row.get::<_, String>(1)?.parse().unwrap_or_default(),
+                recommendation: row.get(2)?,
+                explanation: row.get(3)?,
+            })
+        })?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Additional sources an action was seen by
+    pub fn get_action_sources(&self, action_id: &str) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT source FROM action_sources WHERE action_id = ?1 ORDER BY id")?;
+        let sources = stmt
+            .query_map([action_id], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(sources)
+    }
+
+    // ============================================
+    // Event bus journal
+    // ============================================
+
+    /// Append an event to the journal, returning its sequence number
+    pub fn append_event(
+        &self,
+        kind: &str,
+        payload: &str,
+        published_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<u64> {
+        self.conn.execute(
+            "INSERT INTO event_log (published_at, kind, payload) VALUES (?1, ?2, ?3)",
+            params![published_at.to_rfc3339(), kind, payload],
+        )?;
+        Ok(self.conn.last_insert_rowid() as u64)
+    }
+
+    /// Journaled events after `seq` as (seq, published_at, payload), oldest first
+    pub fn events_after(
+        &self,
+        seq: u64,
+        limit: usize,
+    ) -> anyhow::Result<Vec<(u64, chrono::DateTime<chrono::Utc>, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT seq, published_at, payload FROM event_log WHERE seq > ?1 ORDER BY seq LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![seq as i64, limit as i64], |row| {
+                let published_at: String = row.get(1)?;
+                Ok((
+                    row.get::<_, i64>(0)? as u64,
+                    chrono::DateTime::parse_from_rfc3339(&published_at)
+                        .map(|dt| dt.with_timezone(&chrono::Utc))
+                        .unwrap_or_else(|_| chrono::Utc::now()),
+                    row.get(2)?,
+                ))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(rows)
+    }
+
+    /// Highest journaled sequence number (0 if empty)
+    pub fn last_event_seq(&self) -> anyhow::Result<u64> {
+        let seq: Option<i64> =
+            self.conn
+                .query_row("SELECT MAX(seq) FROM event_log", [], |row| row.get(0))?;
+        Ok(seq.unwrap_or(0) as u64)
+    }
+
+    pub fn get_consumer_offset(&self, consumer: &str) -> anyhow::Result<Option<u64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT seq FROM consumer_offsets WHERE consumer = ?1")?;
+        let mut rows = stmt.query_map([consumer], |row| row.get::<_, i64>(0))?;
+        Ok(rows.next().transpose()?.map(|s| s as u64))
+    }
+
+    pub fn set_consumer_offset(&self, consumer: &str, seq: u64) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO consumer_offsets (consumer, seq) VALUES (?1, ?2)
+            ON CONFLICT(consumer) DO UPDATE SET seq = excluded.seq
+            "#,
+            params![consumer, seq as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Lowest acknowledged offset across consumers
+    pub fn min_consumer_offset(&self) -> anyhow::Result<Option<u64>> {
+        let seq: Option<i64> =
+            self.conn
+                .query_row("SELECT MIN(seq) FROM consumer_offsets", [], |row| {
+                    row.get(0)
+                })?;
+        Ok(seq.map(|s| s as u64))
+    }
+
+    /// Delete journaled events up to and including `seq` that were published
+    /// before `published_before`
+    pub fn prune_events(
+        &self,
+        seq: u64,
+        published_before: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<usize> {
+        Ok(self.conn.execute(
+            "DELETE FROM event_log WHERE seq <= ?1 AND published_at < ?2",
+            params![seq as i64, published_before.to_rfc3339()],
+        )?)
+    }
+
+    fn increment_counter(&self, name: &str) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO counters (name, value) VALUES (?1, 1)
+            ON CONFLICT(name) DO UPDATE SET value = value + 1
+            "#,
+            [name],
+        )?;
+        Ok(())
+    }
+
+    fn add_to_counter(&self, name: &str, n: u64) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO counters (name, value) VALUES (?1, ?2)
+            ON CONFLICT(name) DO UPDATE SET value = value + ?2
+            "#,
+            params![name, n as i64],
+        )?;
+        Ok(())
+    }
+
+    fn set_counter(&self, name: &str, value: u64) -> anyhow::Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO counters (name, value) VALUES (?1, ?2)",
+            params![name, value as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Value of the counter `name` (0 if never counted)
+    pub fn get_counter(&self, name: &str) -> anyhow::Result<u64> {
+        let value: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT value FROM counters WHERE name = ?1",
+                [name],
+                |row| row.get(0),
+            )
+            .optional()?;
+        Ok(value.unwrap_or(0).max(0) as u64)
+    }
+
     /// Get statistics
     pub fn get_stats(&self) -> anyhow::Result<Stats> {
         let total_actions: i64 =
@@ -151,8 +815,8 @@ impl Database {
         )?;
 
         let warnings: i64 = self.conn.query_row(
-            "SELECT COUNT(*) FROM analysis_results WHERE risk_level = 'Warning'",
-            [],
+            "SELECT COUNT(*) FROM analysis_results WHERE risk_level = ?1",
+            [RiskLevel::Warning.as_str()],
             |row| row.get(0),
         )?;
 
@@ -163,28 +827,708 @@ impl Database {
         })
     }
 
+    /// Dashboard statistics, classifying each action by its latest analysis.
+    /// `today_start` bounds `today_events` and `today_blocked`.
+    pub fn get_dashboard_stats(
+        &self,
+        today_start: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<DashboardStats> {
+        let stats = self.conn.query_row(
+            r#"
+            SELECT COUNT(*),
+                   COALESCE(SUM(r.risk_level = ?2), 0),
+                   COALESCE(SUM(r.risk_level = ?3), 0),
+                   COALESCE(SUM(r.risk_level = ?4), 0),
+                   COALESCE(SUM(a.timestamp >= ?1), 0),
+                   COALESCE(SUM(r.recommendation = 'CriticalAlert'
+                                OR a.metadata LIKE '%"blocked":true%'), 0),
+                   COALESCE(SUM(a.timestamp >= ?1
+                                AND (r.recommendation = 'CriticalAlert'
+                                     OR a.metadata LIKE '%"blocked":true%')), 0)
+            FROM actions a
+            LEFT JOIN analysis_results r ON r.id = (
+                SELECT MAX(id) FROM analysis_results WHERE action_id = a.id
+            )
+            "#,
+            params![
+                today_start.to_rfc3339(),
+                RiskLevel::Critical.as_str(),
+                RiskLevel::Warning.as_str(),
+                RiskLevel::Info.as_str(),
+            ],
+            |row| {
+                Ok(DashboardStats {
+                    total_events: row.get::<_, i64>(0)? as u64,
+                    critical_count: row.get::<_, i64>(1)? as u64,
+                    warning_count: row.get::<_, i64>(2)? as u64,
+                    info_count: row.get::<_, i64>(3)? as u64,
+                    today_events: row.get::<_, i64>(4)? as u64,
+                    blocked_count: row.get::<_, i64>(5)? as u64,
+                    today_blocked: row.get::<_, i64>(6)? as u64,
+                })
+            },
+        )?;
+        Ok(stats)
+    }
+
+    /// Add `delta` to the persisted proxy counters
+    pub fn add_event_counters(&self, delta: &EventCounters) -> anyhow::Result<()> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            INSERT INTO counters (name, value) VALUES (?1, ?2)
+            ON CONFLICT(name) DO UPDATE SET value = value + excluded.value
+            "#,
+        )?;
+        for (name, value) in delta.entries() {
+            if value > 0 {
+                stmt.execute(params![name, value as i64])?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Persisted proxy counters
+    pub fn get_event_counters(&self) -> anyhow::Result<EventCounters> {
+        let mut stmt = self.conn.prepare("SELECT name, value FROM counters")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?;
+        let mut counters = EventCounters::default();
+        for row in rows {
+            let (name, value) = row?;
+            counters.set(&name, value.max(0) as u64);
+        }
+        Ok(counters)
+    }
+
+    /// Add recorded failures to the stored counts
+    pub fn add_diagnostics(&self, entries: &[Diagnostic]) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for entry in entries {
+            tx.execute(
+                r#"
+                INSERT INTO diagnostics (source, component, count, first_seen, last_seen, last_message)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                ON CONFLICT(source, component) DO UPDATE SET
+                    count = count + excluded.count,
+                    first_seen = MIN(first_seen, excluded.first_seen),
+                    last_seen = MAX(last_seen, excluded.last_seen),
+                    last_message = excluded.last_message
+                "#,
+                params![
+                    entry.source.as_str(),
+                    entry.component,
+                    entry.count as i64,
+                    entry.first_seen.to_rfc3339(),
+                    entry.last_seen.to_rfc3339(),
+                    entry.last_message
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Stored failure counts, most recent first
+    pub fn diagnostics(&self) -> anyhow::Result<Vec<Diagnostic>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT source, component, count, first_seen, last_seen, last_message
+            FROM diagnostics ORDER BY last_seen DESC
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+            ))
+        })?;
+        let time = |t: &str| {
+            chrono::DateTime::parse_from_rfc3339(t)
+                .map(|t| t.with_timezone(&chrono::Utc))
+                .unwrap_or_default()
+        };
+        let mut entries = vec![];
+        for row in rows {
+            let (source, component, count, first_seen, last_seen, last_message) = row?;
+            let Some(source) = Source::from_name(&source) else {
+                continue;
+            };
+            entries.push(Diagnostic {
+                source,
+                component,
+                count: count.max(0) as u64,
+                first_seen: time(&first_seen),
+                last_seen: time(&last_seen),
+                last_message,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Replace the stored upstream health with `upstreams` (in failover order)
+    pub fn replace_provider_health(&self, upstreams: &[UpstreamHealth]) -> anyhow::Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("DELETE FROM provider_health", [])?;
+        for (position, upstream) in upstreams.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO provider_health (target, position, health) VALUES (?1, ?2, ?3)",
+                params![
+                    upstream.target,
+                    position as i64,
+                    serde_json::to_string(upstream)?
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Upstream health last reported by the proxy, in failover order
+    pub fn get_provider_health(&self) -> anyhow::Result<Vec<UpstreamHealth>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT health FROM provider_health ORDER BY position")?;
+        let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
+        let mut health = vec![];
+        for row in rows {
+            if let Ok(h) = serde_json::from_str(&row?) {
+                health.push(h);
+            }
+        }
+        Ok(health)
+    }
+
+    /// Add one response's token usage to its day/session/provider/model row
+    pub fn record_usage(&self, entry: &UsageEntry) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO usage (day, session_id, provider, model, requests, input_tokens, output_tokens, cost_usd)
+            VALUES (?1, ?2, ?3, ?4, 1, ?5, ?6, ?7)
+            ON CONFLICT(day, session_id, provider, model) DO UPDATE SET
+                requests = requests + 1,
+                input_tokens = input_tokens + excluded.input_tokens,
+                output_tokens = output_tokens + excluded.output_tokens,
+                cost_usd = cost_usd + excluded.cost_usd
+            "#,
+            params![
+                entry.day,
+                entry.session_id.as_deref().unwrap_or_default(),
+                entry.provider,
+                entry.model,
+                entry.input_tokens as i64,
+                entry.output_tokens as i64,
+                entry.cost_usd,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Estimated spend on `day`, optionally for one session
+    pub fn usage_spend(&self, day: &str, session_id: Option<&str>) -> anyhow::Result<f64> {
+        Ok(self.conn.query_row(
+            r#"
+            SELECT COALESCE(SUM(cost_usd), 0) FROM usage
+            WHERE day = ?1 AND (?2 IS NULL OR session_id = ?2)
+            "#,
+            params![day, session_id],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// Usage from `since_day` onwards grouped by `group_by` (day, session,
+    /// provider or model), largest spend first
+    pub fn usage_summary(
+        &self,
+        since_day: &str,
+        group_by: &str,
+    ) -> anyhow::Result<Vec<UsageSummary>> {
+        let column = match group_by {
+            "day" => "day",
+            "session" => "session_id",
+            "provider" => "provider",
+            "model" => "model",
+            other => anyhow::bail!(
+                "Unknown grouping '{}' (day, session, provider, model)",
+                other
+            ),
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT {column}, SUM(requests), SUM(input_tokens), SUM(output_tokens), SUM(cost_usd)
+            FROM usage WHERE day >= ?1
+            GROUP BY {column}
+            ORDER BY SUM(cost_usd) DESC, SUM(input_tokens + output_tokens) DESC
+            "#
+        ))?;
+        let rows = stmt.query_map([since_day], |row| {
+            Ok(UsageSummary {
+                key: row.get(0)?,
+                requests: row.get::<_, i64>(1)? as u64,
+                input_tokens: row.get::<_, i64>(2)? as u64,
+                output_tokens: row.get::<_, i64>(3)? as u64,
+                cost_usd: row.get(4)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Add one request body's size to its day/session/provider row
+    pub fn record_egress(
+        &self,
+        day: &str,
+        session_id: Option<&str>,
+        provider: &str,
+        bytes: u64,
+    ) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO egress (day, session_id, provider, requests, bytes)
+            VALUES (?1, ?2, ?3, 1, ?4)
+            ON CONFLICT(day, session_id, provider) DO UPDATE SET
+                requests = requests + 1,
+                bytes = bytes + excluded.bytes
+            "#,
+            params![day, session_id.unwrap_or_default(), provider, bytes as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Bytes sent from `since_day` onwards grouped by `group_by` (day,
+    /// session or provider), largest first
+    pub fn egress_summary(
+        &self,
+        since_day: &str,
+        group_by: &str,
+    ) -> anyhow::Result<Vec<EgressSummary>> {
+        let column = match group_by {
+            "day" => "day",
+            "session" => "session_id",
+            "provider" => "provider",
+            other => anyhow::bail!("Unknown grouping '{}' (day, session, provider)", other),
+        };
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT {column}, SUM(requests), SUM(bytes)
+            FROM egress WHERE day >= ?1
+            GROUP BY {column}
+            ORDER BY SUM(bytes) DESC
+            "#
+        ))?;
+        let rows = stmt.query_map([since_day], |row| {
+            Ok(EgressSummary {
+                key: row.get(0)?,
+                requests: row.get::<_, i64>(1)? as u64,
+                bytes: row.get::<_, i64>(2)? as u64,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Remember a canary token handed out in a block message
+    pub fn insert_canary_token(&self, token: &CanaryToken) -> anyhow::Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT OR IGNORE INTO canary_tokens (token, created_at, rule_name, tool_name, agent, session_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            params![
+                token.token,
+                token.created_at,
+                token.rule_name,
+                token.tool_name,
+                token.agent,
+                token.session_id,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Record that `token` was seen again; returns the block it came from,
+    /// or None if this database never issued it
+    pub fn trigger_canary_token(&self, token: &str) -> anyhow::Result<Option<CanaryToken>> {
+        let now = chrono::Utc::now().to_rfc3339();
+        self.conn.execute(
+            "UPDATE canary_tokens SET trigger_count = trigger_count + 1, last_triggered = ?2 WHERE token = ?1",
+            params![token, now],
+        )?;
+        Ok(self
+            .conn
+            .query_row(
+                r#"
+                SELECT token, created_at, rule_name, tool_name, agent, session_id, trigger_count, last_triggered
+                FROM canary_tokens WHERE token = ?1
+                "#,
+                [token],
+                |row| {
+                    Ok(CanaryToken {
+                        token: row.get(0)?,
+                        created_at: row.get(1)?,
+                        rule_name: row.get(2)?,
+                        tool_name: row.get(3)?,
+                        agent: row.get(4)?,
+                        session_id: row.get(5)?,
+                        trigger_count: row.get::<_, i64>(6)? as u64,
+                        last_triggered: row.get(7)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Ask for approval of the tool call with `fingerprint`, returning the
+    /// approval id. Repeated requests for the same call share one id.
+    pub fn request_approval(&self, fingerprint: &str, summary: &str) -> anyhow::Result<String> {
+        let existing: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT id FROM approvals WHERE fingerprint = ?1 AND status = 'pending'",
+                [fingerprint],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if let Some(id) = existing {
+            return Ok(id);
+        }
+        let id = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+        self.conn.execute(
+            r#"
+            INSERT INTO approvals (id, fingerprint, summary, requested_at, status)
+            VALUES (?1, ?2, ?3, ?4, 'pending')
+            "#,
+            params![id, fingerprint, summary, chrono::Utc::now().to_rfc3339()],
+        )?;
+        Ok(id)
+    }
+
+    /// Approve or deny a pending request. Returns the updated approval, or
+    /// None if there is no pending request `id`.
+    pub fn decide_approval(
+        &self,
+        id: &str,
+        approved: bool,
+        decided_by: &str,
+    ) -> anyhow::Result<Option<Approval>> {
+        let updated = self.conn.execute(
+            r#"
+            UPDATE approvals SET status = ?2, decided_by = ?3, decided_at = ?4
+            WHERE id = ?1 AND status = 'pending'
+            "#,
+            params![
+                id,
+                if approved { "approved" } else { "denied" },
+                decided_by,
+                chrono::Utc::now().to_rfc3339()
+            ],
+        )?;
+        if updated == 0 {
+            return Ok(None);
+        }
+        self.approval(id)
+    }
+
+    /// Use up an approval of the tool call with `fingerprint` given in the
+    /// last `max_age`; returns whether there was one
+    pub fn take_approval(
+        &self,
+        fingerprint: &str,
+        max_age: chrono::Duration,
+    ) -> anyhow::Result<bool> {
+        let since = (chrono::Utc::now() - max_age).to_rfc3339();
+        let updated = self.conn.execute(
+            r#"
+            UPDATE approvals SET status = 'used'
+            WHERE id = (
+                SELECT id FROM approvals
+                WHERE fingerprint = ?1 AND status = 'approved' AND decided_at >= ?2
+                LIMIT 1
+            )
+            "#,
+            params![fingerprint, since],
+        )?;
+        Ok(updated > 0)
+    }
+
+    pub fn approval(&self, id: &str) -> anyhow::Result<Option<Approval>> {
+        Ok(self
+            .get_approvals("WHERE id = ?1", params![id])?
+            .into_iter()
+            .next())
+    }
+
+    /// Pending approvals, oldest first; with `unannounced`, only those not
+    /// yet posted anywhere
+    pub fn pending_approvals(&self, unannounced: bool) -> anyhow::Result<Vec<Approval>> {
+        self.get_approvals(
+            "WHERE status = 'pending' AND (?1 = 0 OR announced = 0) ORDER BY requested_at",
+            params![unannounced],
+        )
+    }
+
+    pub fn mark_approval_announced(&self, id: &str) -> anyhow::Result<()> {
+        self.conn
+            .execute("UPDATE approvals SET announced = 1 WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    fn get_approvals(
+        &self,
+        filter: &str,
+        args: impl rusqlite::Params,
+    ) -> anyhow::Result<Vec<Approval>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT id, fingerprint, summary, requested_at, status, decided_by, decided_at FROM approvals {}",
+            filter
+        ))?;
+        let rows = stmt.query_map(args, |row| {
+            Ok(Approval {
+                id: row.get(0)?,
+                fingerprint: row.get(1)?,
+                summary: row.get(2)?,
+                requested_at: row.get(3)?,
+                status: row.get(4)?,
+                decided_by: row.get(5)?,
+                decided_at: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// IDs of actions matching every given filter. `path_prefix` matches the
+    /// target's prefix or anywhere in the content (commands mention paths).
+    pub fn find_action_ids(
+        &self,
+        session_id: Option<&str>,
+        path_prefix: Option<&str>,
+        from: Option<chrono::DateTime<chrono::Utc>>,
+        to: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> anyhow::Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id FROM actions
+            WHERE (?1 IS NULL OR session_id = ?1)
+              AND (?2 IS NULL OR substr(target, 1, length(?2)) = ?2 OR instr(content, ?2) > 0)
+              AND (?3 IS NULL OR timestamp >= ?3)
+              AND (?4 IS NULL OR timestamp <= ?4)
+            "#,
+        )?;
+
+        let ids = stmt
+            .query_map(
+                params![
+                    session_id,
+                    path_prefix,
+                    from.map(|t| t.to_rfc3339()),
+                    to.map(|t| t.to_rfc3339()),
+                ],
+                |row| row.get(0),
+            )?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(ids)
+    }
+
+    /// Whether any stored action still points at offloaded content `hash`
+    pub fn blob_referenced(&self, hash: &str) -> anyhow::Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM actions WHERE instr(metadata, ?1) > 0",
+            [hash],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Journaled bus events carrying an action, which would otherwise keep a copy
+    fn delete_journaled(&self, action_id: &str) -> anyhow::Result<usize> {
+        let needle = format!("\"id\":{}", serde_json::to_string(action_id)?);
+        Ok(self.conn.execute(
+            "DELETE FROM event_log WHERE instr(payload, ?1) > 0",
+            [needle],
+        )?)
+    }
+
+    /// Delete an action and everything derived from it.
+    /// Returns the number of analysis results removed.
+    pub fn delete_action(&self, id: &str) -> anyhow::Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let analysis_ids = self.analysis_ids(id)?;
+        let analyses = self
+            .conn
+            .execute("DELETE FROM analysis_results WHERE action_id = ?1", [id])?;
+        self.conn.execute(
+            "DELETE FROM action_sources WHERE action_id = ?1 OR source_action_id = ?1",
+            [id],
+        )?;
+        self.delete_journaled(id)?;
+        self.conn
+            .execute("DELETE FROM Behaviours WHERE action_id = ?1", [id])?;
+        let deleted = self
+            .conn
+            .execute("DELETE FROM actions WHERE id = ?1", [id])?;
+        self.chain_deletion(id, deleted > 0, &analysis_ids)?;
+        tx.commit()?;
+        Ok(analyses)
+    }
+
+    /// Strip an action's content, keeping what stats need (time, agent, type,
+    /// risk). The session is replaced by `session_alias` so sessions still group.
+    pub fn anonymize_action(&self, id: &str, session_alias: Option<&str>) -> anyhow::Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let updated = self.conn.execute(
+            r#"
+            UPDATE actions
+            SET content = '[redacted]',
+                target = CASE WHEN target IS NULL THEN NULL ELSE '[redacted]' END,
+                session_id = ?2,
+                metadata = NULL
+            WHERE id = ?1
+            "#,
+            params![id, session_alias],
+        )?;
+        self.delete_journaled(id)?;
+        let analyses = self.conn.execute(
+            "UPDATE analysis_results SET explanation = '[redacted]' WHERE action_id = ?1",
+            [id],
+        )?;
+        let mut rows: Vec<chain::Row> = self
+            .analysis_ids(id)?
+            .into_iter()
+            .map(chain::Row::Analysis)
+            .collect();
+        if updated > 0 {
+            rows.push(chain::Row::Action(id));
+        }
+        self.chain(chain::UPDATE, &rows)?;
+        tx.commit()?;
+        Ok(analyses)
+    }
+
+    /// Chain the deletion of an action (if it was there) and its analyses
+    pub(crate) fn chain_deletion(
+        &self,
+        id: &str,
+        action: bool,
+        analysis_ids: &[i64],
+    ) -> anyhow::Result<()> {
+        let mut rows: Vec<chain::Row> = analysis_ids
+            .iter()
+            .copied()
+            .map(chain::Row::Analysis)
+            .collect();
+        if action {
+            rows.push(chain::Row::Action(id));
+        }
+        self.chain(chain::DELETE, &rows)
+    }
+
+    /// Record a purge in the audit log, returning its ID
+    pub fn record_purge(
+        &self,
+        mode: &str,
+        scope: &str,
+        summary: &str,
+        requested_by: &str,
+    ) -> anyhow::Result<i64> {
+        self.conn.execute(
+            r#"
+            INSERT INTO purge_audit (timestamp, mode, scope, summary, requested_by)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![
+                chrono::Utc::now().to_rfc3339(),
+                mode,
+                scope,
+                summary,
+                requested_by
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// Most recent purge audit records
+    pub fn get_purge_audit(&self, limit: usize) -> anyhow::Result<Vec<PurgeAuditEntry>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, timestamp, mode, scope, summary, requested_by
+            FROM purge_audit
+            ORDER BY id DESC
+            LIMIT ?1
+            "#,
+        )?;
+
+        let entries = stmt
+            .query_map([limit], |row| {
+                Ok(PurgeAuditEntry {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    mode: row.get(2)?,
+                    scope: row.get(3)?,
+                    summary: row.get(4)?,
+                    requested_by: row.get(5)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(entries)
+    }
+
     /// Clean up old entries
     pub fn cleanup(&self, retention_days: u32) -> anyhow::Result<usize> {
         let cutoff = chrono::Utc::now() - chrono::Duration::days(retention_days as i64);
 
+        let tx = self.conn.unchecked_transaction()?;
+        let ids: Vec<String> = if self.chaining()? {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id FROM actions WHERE timestamp < ?1")?;
+            let ids = stmt.query_map([cutoff.to_rfc3339()], |row| row.get(0))?;
+            ids.collect::<rusqlite::Result<_>>()?
+        } else {
+            vec![]
+        };
         let deleted = self.conn.execute(
             "DELETE FROM actions WHERE timestamp < ?1",
             [cutoff.to_rfc3339()],
         )?;
+        for id in &ids {
+            self.chain_deletion(id, true, &[])?;
+        }
+        self.conn.execute(
+            "DELETE FROM action_sources WHERE linked_at < ?1",
+            [cutoff.to_rfc3339()],
+        )?;
+        self.conn.execute(
+            "DELETE FROM action_hashes WHERE timestamp < ?1",
+            [cutoff.to_rfc3339()],
+        )?;
+        tx.commit()?;
 
         info!("Cleaned up {} old action records", deleted);
         Ok(deleted)
     }
 }
 
-fn parse_agent_type(s: &str) -> AgentType {
-    match s.to_lowercase().as_str() {
-        "openclaw" => AgentType::OpenClaw,
-        "claude_code" => AgentType::ClaudeCode,
-        "cursor" => AgentType::Cursor,
-        "ralph" => AgentType::Ralph,
-        _ => AgentType::Unknown,
-    }
+fn row_to_action(row: &rusqlite::Row<'_>) -> rusqlite::Result<AgentAction> {
+    Ok(AgentAction {
+        id: row.get(0)?,
+        timestamp: chrono::DateTime::parse_from_rfc3339(&row.get::<_, String>(1)?)
+            .unwrap_or_default()
+            .with_timezone(&chrono::Utc),
+        agent: AgentType::from_name(&row.get::<_, String>(2)?),
+        action_type: parse_action_type(&row.get::<_, String>(3)?),
+        content: row.get(4)?,
+        target: row.get(5)?,
+        session_id: row.get(6)?,
+        metadata: row
+            .get::<_, Option<String>>(7)?
+            .and_then(|s| serde_json::from_str(&s).ok()),
+    })
 }
 
 fn parse_action_type(s: &str) -> ActionType {
@@ -197,6 +1541,7 @@ fn parse_action_type(s: &str) -> ActionType {
         "BrowserAction" => ActionType::BrowserAction,
         "MessageSend" => ActionType::MessageSend,
         "GitOperation" => ActionType::GitOperation,
+        "ToolResult" => ActionType::ToolResult,
         _ => ActionType::Unknown,
     }
 }
@@ -208,6 +1553,199 @@ pub struct Stats {
     pub warnings: i64,
 }
 
+/// Token usage of one response, for `record_usage`
+#[derive(Debug, Clone)]
+pub struct UsageEntry {
+    /// YYYY-MM-DD (UTC)
+    pub day: String,
+    pub session_id: Option<String>,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Aggregated token usage for one group
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UsageSummary {
+    pub key: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Aggregated request bytes for one group
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct EgressSummary {
+    pub key: String,
+    pub requests: u64,
+    pub bytes: u64,
+}
+
+/// How often a rule matched
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct RuleStats {
+    pub total: u64,
+    /// Matches the proxy blocked or the daemon tried to interrupt
+    pub blocked: u64,
+    /// Matches that were only logged or alerted on
+    pub monitored: u64,
+    pub last_matched: Option<String>,
+    /// Matches per day (YYYY-MM-DD, UTC) in the requested window
+    pub by_day: std::collections::BTreeMap<String, u64>,
+}
+
+/// A request to let a PauseAndAsk tool call through
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Approval {
+    /// Short id people type in `/harness approve <id>`
+    pub id: String,
+    /// Identifies the tool call (see `InterceptResult::fingerprint`)
+    pub fingerprint: String,
+    pub summary: String,
+    pub requested_at: String,
+    /// pending, approved, denied or used
+    pub status: String,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<String>,
+}
+
+/// A canary token issued in a block message
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CanaryToken {
+    pub token: String,
+    pub created_at: String,
+    /// Rule that caused the block
+    pub rule_name: String,
+    /// Tool call that was blocked
+    pub tool_name: String,
+    pub agent: String,
+    pub session_id: Option<String>,
+    /// Times the token was seen in later actions
+    pub trigger_count: u64,
+    pub last_triggered: Option<String>,
+}
+
+/// Action counts for the dashboard
+#[derive(Debug, Clone, Default)]
+pub struct DashboardStats {
+    pub total_events: u64,
+    pub critical_count: u64,
+    pub warning_count: u64,
+    pub info_count: u64,
+    pub today_events: u64,
+    /// Actions the daemon tried to interrupt or the proxy blocked
+    pub blocked_count: u64,
+    /// Of those, the ones since the start of today
+    pub today_blocked: u64,
+}
+
+/// Proxy request counters, persisted in the `counters` table
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct EventCounters {
+    pub total_requests: u64,
+    pub blocked_count: u64,
+    pub warning_count: u64,
+    pub passed_count: u64,
+    pub by_provider: std::collections::HashMap<String, u64>,
+}
+
+const PROVIDER_PREFIX: &str = "provider:";
+
+impl EventCounters {
+    pub fn is_empty(&self) -> bool {
+        self.entries().all(|(_, v)| v == 0)
+    }
+
+    /// Count one API request to `provider`. A request is blocked if any of
+    /// its tool calls were, a warning if any were flagged, otherwise passed.
+    pub fn record_request(&mut self, provider: &str, blocked: bool, flagged: bool) {
+        self.total_requests += 1;
+        *self.by_provider.entry(provider.to_string()).or_default() += 1;
+        if blocked {
+            self.blocked_count += 1;
+        } else if flagged {
+            self.warning_count += 1;
+        } else {
+            self.passed_count += 1;
+        }
+    }
+
+    /// Add another set of counters to this one
+    pub fn merge(&mut self, other: &EventCounters) {
+        for (name, value) in other.entries() {
+            let current = self.get(&name);
+            self.set(&name, current + value);
+        }
+    }
+
+    fn get(&self, name: &str) -> u64 {
+        match name {
+            "total_requests" => self.total_requests,
+            "blocked" => self.blocked_count,
+            "warning" => self.warning_count,
+            "passed" => self.passed_count,
+            other => other
+                .strip_prefix(PROVIDER_PREFIX)
+                .and_then(|p| self.by_provider.get(p).copied())
+                .unwrap_or(0),
+        }
+    }
+
+    fn set(&mut self, name: &str, value: u64) {
+        match name {
+            "total_requests" => self.total_requests = value,
+            "blocked" => self.blocked_count = value,
+            "warning" => self.warning_count = value,
+            "passed" => self.passed_count = value,
+            other => {
+                if let Some(provider) = other.strip_prefix(PROVIDER_PREFIX) {
+                    self.by_provider.insert(provider.to_string(), value);
+                }
+            }
+        }
+    }
+
+    /// Counters as (row name, value) pairs
+    fn entries(&self) -> impl Iterator<Item = (String, u64)> + '_ {
+        [
+            ("total_requests".to_string(), self.total_requests),
+            ("blocked".to_string(), self.blocked_count),
+            ("warning".to_string(), self.warning_count),
+            ("passed".to_string(), self.passed_count),
+        ]
+        .into_iter()
+        .chain(
+            self.by_provider
+                .iter()
+                .map(|(p, v)| (format!("{}{}", PROVIDER_PREFIX, p), *v)),
+        )
+    }
+}
+
+/// An analysis result as stored (without its action)
+#[derive(Debug, Clone)]
+pub struct StoredAnalysis {
+    pub matched_rules: Vec<String>,
+    pub risk_level: RiskLevel,
+    /// `Recommendation` variant name
+    pub recommendation: String,
+    pub explanation: String,
+}
+
+/// A recorded data purge; `scope` and `summary` are JSON
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PurgeAuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub mode: String,
+    pub scope: String,
+    pub summary: String,
+    pub requested_by: String,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -233,5 +1771,320 @@ mod tests {
         let actions = db.get_recent_actions(10).unwrap();
         assert_eq!(actions.len(), 1);
         assert_eq!(actions[0].id, "test-1");
+
+        let found = db.get_action("test-1").unwrap().unwrap();
+        assert_eq!(found.action_type, ActionType::Exec);
+        assert!(db.get_action("missing").unwrap().is_none());
+
+        // Storing the same id again is a counted no-op
+        let mut again = action.clone();
+        again.content = "rm -rf /".to_string();
+        assert!(!db.store_action(&again).unwrap());
+        assert_eq!(db.get_action("test-1").unwrap().unwrap().content, "ls -la");
+        assert_eq!(db.get_counter(ACTION_ID_CONFLICTS).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_custom_agent_round_trip() {
+        let db = Database::open_in_memory().unwrap();
+
+        let action = AgentAction {
+            id: "custom-1".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::Custom("build-bot".to_string()),
+            action_type: ActionType::Exec,
+            content: "make".to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        };
+        db.store_action(&action).unwrap();
+
+        let found = db.get_action("custom-1").unwrap().unwrap();
+        assert_eq!(found.agent, AgentType::Custom("build-bot".to_string()));
+    }
+
+    #[test]
+    fn test_dashboard_stats_and_counters() {
+        let db = Database::open_in_memory().unwrap();
+        let now = chrono::Utc::now();
+        for (id, days_ago, risk, rec) in [
+            (
+                "a1",
+                0,
+                RiskLevel::Critical,
+                crate::Recommendation::CriticalAlert,
+            ),
+            ("a2", 0, RiskLevel::Warning, crate::Recommendation::Alert),
+            ("a3", 2, RiskLevel::Info, crate::Recommendation::LogOnly),
+        ] {
+            let action = AgentAction {
+                id: id.to_string(),
+                timestamp: now - chrono::Duration::days(days_ago),
+                agent: AgentType::OpenClaw,
+                action_type: ActionType::Exec,
+                content: "x".to_string(),
+                target: None,
+                session_id: None,
+                metadata: None,
+            };
+            db.store_action(&action).unwrap();
+            db.store_analysis(&AnalysisResult {
+                action,
+                matched_rules: vec![],
+                risk_level: risk,
+                recommendation: rec,
+                explanation: String::new(),
+            })
+            .unwrap();
+        }
+
+        let stats = db
+            .get_dashboard_stats(now - chrono::Duration::hours(1))
+            .unwrap();
+        assert_eq!(stats.total_events, 3);
+        assert_eq!(
+            (stats.critical_count, stats.warning_count, stats.info_count),
+            (1, 1, 1)
+        );
+        assert_eq!(stats.today_events, 2);
+        assert_eq!(stats.blocked_count, 1);
+        assert_eq!(stats.today_blocked, 1);
+
+        let mut delta = EventCounters::default();
+        delta.record_request("anthropic", true, true);
+        delta.record_request("openai", false, false);
+        db.add_event_counters(&delta).unwrap();
+        db.add_event_counters(&delta).unwrap();
+        let counters = db.get_event_counters().unwrap();
+        assert_eq!(counters.total_requests, 4);
+        assert_eq!(counters.blocked_count, 2);
+        assert_eq!(counters.passed_count, 2);
+        assert_eq!(counters.by_provider["anthropic"], 2);
+    }
+
+    #[test]
+    fn test_usage_accounting() {
+        let db = Database::open_in_memory().unwrap();
+        let entry = |day: &str, session: &str, model: &str, cost: f64| UsageEntry {
+            day: day.to_string(),
+            session_id: Some(session.to_string()),
+            provider: "anthropic".to_string(),
+            model: model.to_string(),
+            input_tokens: 100,
+            output_tokens: 10,
+            cost_usd: cost,
+        };
+        db.record_usage(&entry("2026-03-01", "s1", "claude-a", 0.5))
+            .unwrap();
+        db.record_usage(&entry("2026-03-01", "s1", "claude-a", 0.5))
+            .unwrap();
+        db.record_usage(&entry("2026-03-02", "s2", "claude-b", 0.25))
+            .unwrap();
+
+        assert_eq!(db.usage_spend("2026-03-01", None).unwrap(), 1.0);
+        assert_eq!(db.usage_spend("2026-03-01", Some("s2")).unwrap(), 0.0);
+
+        let by_model = db.usage_summary("2026-03-01", "model").unwrap();
+        assert_eq!(by_model[0].key, "claude-a");
+        assert_eq!((by_model[0].requests, by_model[0].input_tokens), (2, 200));
+        assert_eq!(db.usage_summary("2026-03-02", "session").unwrap().len(), 1);
+        assert!(db.usage_summary("2026-03-01", "bogus").is_err());
+
+        db.record_egress("2026-03-01", Some("s1"), "anthropic", 1000)
+            .unwrap();
+        db.record_egress("2026-03-01", Some("s1"), "anthropic", 3000)
+            .unwrap();
+        db.record_egress("2026-03-02", None, "openai", 500).unwrap();
+        let by_session = db.egress_summary("2026-03-01", "session").unwrap();
+        assert_eq!(
+            (
+                by_session[0].key.as_str(),
+                by_session[0].requests,
+                by_session[0].bytes
+            ),
+            ("s1", 2, 4000)
+        );
+        assert_eq!(
+            db.egress_summary("2026-03-02", "provider").unwrap()[0].bytes,
+            500
+        );
+        assert!(db.egress_summary("2026-03-01", "model").is_err());
+    }
+
+    #[test]
+    fn test_rule_stats() {
+        use chrono::TimeZone;
+        let db = Database::open_in_memory().unwrap();
+        let at = |day: u32, hour: u32| {
+            chrono::Utc
+                .with_ymd_and_hms(2026, 3, day, hour, 0, 0)
+                .unwrap()
+        };
+        db.record_rule_match("dangerous_rm", at(1, 9), true)
+            .unwrap();
+        db.record_rule_match("dangerous_rm", at(2, 12), false)
+            .unwrap();
+        db.record_rule_match("dangerous_rm", at(2, 8), true)
+            .unwrap();
+
+        let action = AgentAction {
+            id: "a1".to_string(),
+            timestamp: at(3, 10),
+            agent: AgentType::OpenClaw,
+            action_type: ActionType::Exec,
+            content: "sudo ls".to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        };
+        db.store_action(&action).unwrap();
+        db.store_analysis(&AnalysisResult {
+            action,
+            matched_rules: vec!["sudo_command".to_string()],
+            risk_level: RiskLevel::Warning,
+            recommendation: Recommendation::Alert,
+            explanation: String::new(),
+        })
+        .unwrap();
+
+        let stats = db.rule_stats("2026-03-02").unwrap();
+        let rm = &stats["dangerous_rm"];
+        assert_eq!((rm.total, rm.blocked, rm.monitored), (3, 2, 1));
+        assert_eq!(
+            rm.last_matched.as_deref(),
+            Some("2026-03-02T12:00:00+00:00")
+        );
+        // Only days in the window
+        assert_eq!(rm.by_day.len(), 1);
+        assert_eq!(rm.by_day["2026-03-02"], 2);
+        assert_eq!(stats["sudo_command"].monitored, 1);
+        assert!(!stats.contains_key("ssh_key_access"));
+    }
+
+    /// Query plan steps that read `table` (aliased `alias`) without an
+    /// index; a bare SEARCH is a rowid walk that checks every row
+    fn full_scans(db: &Database, sql: &str, table: &str, alias: &str) -> Vec<String> {
+        let mut stmt = db
+            .conn
+            .prepare(&format!("EXPLAIN QUERY PLAN {}", sql))
+            .unwrap();
+        let details: Vec<String> = stmt
+            .query_map([], |row| row.get(3))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        details
+            .into_iter()
+            .filter(|d| {
+                [table, alias]
+                    .iter()
+                    .any(|t| d == &format!("SCAN {}", t) || d == &format!("SEARCH {}", t))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_analysis_queries_use_indexes() {
+        let db = Database::open_in_memory().unwrap();
+        let version: i64 = db
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version as usize, MIGRATIONS.len());
+
+        let queries = [
+            // Latest analysis of each action (stats, export)
+            r#"SELECT a.id, r.risk_level FROM actions a
+               LEFT JOIN analysis_results r ON r.id = (
+                   SELECT MAX(id) FROM analysis_results WHERE action_id = a.id
+               )"#,
+            // Brain: risky commands
+            r#"SELECT a.content, COUNT(*) FROM analysis_results r
+               JOIN actions a ON a.id = r.action_id
+               WHERE r.risk_level IN ('warning','critical')
+               GROUP BY a.content"#,
+            // Weekly report
+            r#"SELECT COUNT(*) FROM analysis_results
+               WHERE timestamp BETWEEN '2026-01-01' AND '2026-01-08'
+               AND risk_level = 'critical'"#,
+            "DELETE FROM analysis_results WHERE action_id = 'a'",
+        ];
+        for sql in queries {
+            let scans = full_scans(&db, sql, "analysis_results", "r");
+            assert!(
+                scans.is_empty(),
+                "{} scans analysis_results: {:?}",
+                sql,
+                scans
+            );
+        }
+    }
+
+    #[test]
+    fn test_risk_levels_round_trip() {
+        for level in RiskLevel::ALL {
+            assert_eq!(level.as_str().parse::<RiskLevel>().unwrap(), level);
+            assert_eq!(level.to_string().parse::<RiskLevel>().unwrap(), level);
+            assert_eq!(format!("{:?}", level).parse::<RiskLevel>().unwrap(), level);
+            let json = serde_json::to_string(&level).unwrap();
+            assert_eq!(json, format!("\"{}\"", level.as_str()));
+            assert_eq!(serde_json::from_str::<RiskLevel>(&json).unwrap(), level);
+        }
+        assert!("severe".parse::<RiskLevel>().is_err());
+
+        let db = Database::open_in_memory().unwrap();
+        for (i, level) in RiskLevel::ALL.into_iter().enumerate() {
+            let action = AgentAction {
+                id: format!("risk-{}", i),
+                timestamp: chrono::Utc::now(),
+                agent: AgentType::ClaudeCode,
+                action_type: ActionType::Exec,
+                content: format!("cmd {}", i),
+                target: None,
+                session_id: None,
+                metadata: None,
+            };
+            db.store_action(&action).unwrap();
+            db.store_analysis(&AnalysisResult {
+                action,
+                matched_rules: vec!["rule".to_string()],
+                risk_level: level,
+                recommendation: Recommendation::Alert,
+                explanation: String::new(),
+            })
+            .unwrap();
+            let stored = db.get_analysis(&format!("risk-{}", i)).unwrap().unwrap();
+            assert_eq!(stored.risk_level, level);
+        }
+
+        // Rows written before risk levels were stored lowercase
+        db.conn
+            .execute_batch(
+                "UPDATE analysis_results SET risk_level = 'Warning' WHERE risk_level = 'warning';
+                 UPDATE analysis_results SET risk_level = 'Critical' WHERE risk_level = 'critical';
+                 PRAGMA user_version = 1;",
+            )
+            .unwrap();
+        db.migrate().unwrap();
+        let stored: Vec<String> = db
+            .conn
+            .prepare("SELECT risk_level FROM analysis_results ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(stored, ["info", "warning", "critical"]);
+
+        let stats = db.get_dashboard_stats(chrono::Utc::now()).unwrap();
+        assert_eq!(
+            (stats.info_count, stats.warning_count, stats.critical_count),
+            (1, 1, 1)
+        );
+        assert_eq!(db.get_stats().unwrap().warnings, 1);
+        let (nodes, _) = crate::brain::build_ontology_from_db(&db.conn).unwrap();
+        let incidents = nodes.iter().filter(|n| n.kind == "Incident").count();
+        assert_eq!(incidents, 2);
     }
 }
@@ -0,0 +1,164 @@
+//! Connection pool shared by the daemon's components
+//!
+//! The web routes, the analyzer's writer and the event journal all use the
+//! same SQLite file. Each pooled connection runs in WAL mode, so readers
+//! never block the writer, and waits up to `BUSY_TIMEOUT` for a lock
+//! instead of failing with "database is locked".
+//!
+//! Pool size is `OPENCLAW_HARNESS_DB_POOL_SIZE` (default 8).
+
+use super::Database;
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub const DEFAULT_POOL_SIZE: u32 = 8;
+/// How long a connection waits for another one's lock
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long `get` waits for a free connection
+const CHECKOUT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pool size from the environment, or the default
+pub fn pool_size() -> u32 {
+    std::env::var("OPENCLAW_HARNESS_DB_POOL_SIZE")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|n| *n > 0)
+        .unwrap_or(DEFAULT_POOL_SIZE)
+}
+
+/// Settings every connection to the database file gets
+pub(crate) fn configure(conn: &Connection) -> rusqlite::Result<()> {
+    // First, so switching the journal mode also waits out other writers
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    // Returns the resulting mode, so it can't go through execute
+    let _: String = conn.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+    conn.execute_batch("PRAGMA synchronous = NORMAL;")
+}
+
+/// Opens configured connections to one database file
+#[derive(Debug)]
+pub struct SqliteManager {
+    path: PathBuf,
+}
+
+impl r2d2::ManageConnection for SqliteManager {
+    type Connection = Connection;
+    type Error = rusqlite::Error;
+
+    fn connect(&self) -> rusqlite::Result<Connection> {
+        let conn = Connection::open(&self.path)?;
+        configure(&conn)?;
+        Ok(conn)
+    }
+
+    fn is_valid(&self, conn: &mut Connection) -> rusqlite::Result<()> {
+        conn.execute_batch("SELECT 1")
+    }
+
+    fn has_broken(&self, _conn: &mut Connection) -> bool {
+        false
+    }
+}
+
+/// Cheap to clone; every clone hands out connections from the same pool
+#[derive(Clone, Debug)]
+pub struct DbPool {
+    pool: r2d2::Pool<SqliteManager>,
+    path: PathBuf,
+}
+
+impl DbPool {
+    /// Open a pool on `path`, creating the schema if needed
+    pub fn open(path: &Path) -> anyhow::Result<Self> {
+        Self::with_size(path, pool_size())
+    }
+
+    pub fn with_size(path: &Path, size: u32) -> anyhow::Result<Self> {
+        let pool = r2d2::Pool::builder()
+            .max_size(size)
+            .min_idle(Some(1))
+            .connection_timeout(CHECKOUT_TIMEOUT)
+            .build(SqliteManager {
+                path: path.to_path_buf(),
+            })?;
+        let db = Self {
+            pool,
+            path: path.to_path_buf(),
+        };
+        db.get()?.initialize()?;
+        Ok(db)
+    }
+
+    /// The database file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// A `Database` on a pooled connection; the connection goes back to
+    /// the pool when it is dropped
+    pub fn get(&self) -> anyhow::Result<Database> {
+        Ok(Database::pooled(self.connection()?))
+    }
+
+    /// A raw pooled connection, for queries that don't go through
+    /// `Database`
+    pub fn connection(&self) -> anyhow::Result<r2d2::PooledConnection<SqliteManager>> {
+        Ok(self.pool.get()?)
+    }
+
+    /// Connections open and idle
+    pub fn state(&self) -> r2d2::State {
+        self.pool.state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentAction, AgentType};
+
+    #[test]
+    fn test_concurrent_writers_and_readers_share_the_pool() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = DbPool::with_size(&dir.path().join("harness.db"), 4).unwrap();
+        let mode: String = pool
+            .connection()
+            .unwrap()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mode, "wal");
+
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let pool = pool.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25 {
+                        let db = pool.get().unwrap();
+                        db.store_action(&AgentAction {
+                            id: format!("collector-test-{}-{}", t, i),
+                            timestamp: chrono::Utc::now(),
+                            agent: AgentType::ClaudeCode,
+                            action_type: ActionType::Exec,
+                            content: format!("echo {} {}", t, i),
+                            target: None,
+                            session_id: None,
+                            metadata: None,
+                        })
+                        .unwrap();
+                        db.get_recent_actions(10).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(
+            pool.get().unwrap().get_recent_actions(1000).unwrap().len(),
+            200
+        );
+        assert!(pool.state().connections <= 4);
+    }
+}
@@ -0,0 +1,193 @@
+//! Versioned history of rule changes
+//!
+//! Every create, update, delete and rollback of a rule through the API is
+//! stored as a new version with who made it, when, and which fields
+//! changed, so a bad edit (say, a pattern that no longer matches anything)
+//! can be found and rolled back. The first change to a rule loaded from
+//! config also records the state it started from, as version 1.
+
+use super::Database;
+use crate::rules::Rule;
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+/// What kind of change a version records
+pub const INITIAL: &str = "initial";
+pub const CREATE: &str = "create";
+pub const UPDATE: &str = "update";
+pub const DELETE: &str = "delete";
+pub const ROLLBACK: &str = "rollback";
+
+/// One version of a rule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleVersion {
+    pub version: u32,
+    pub changed_at: String,
+    pub changed_by: String,
+    pub change: String,
+    /// Fields that changed from the previous version, e.g.
+    /// `pattern: "rm -rf" → "rm -r"`
+    pub diff: Vec<String>,
+    /// The rule after the change; None once deleted
+    pub rule: Option<Rule>,
+}
+
+/// Field-by-field differences between two versions of a rule
+pub fn diff(before: Option<&Rule>, after: Option<&Rule>) -> Vec<String> {
+    let fields = |rule: Option<&Rule>| match rule.map(serde_json::to_value) {
+        Some(Ok(serde_json::Value::Object(map))) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (before, after) = (fields(before), fields(after));
+    let mut names: Vec<&String> = before.keys().chain(after.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let old = before.get(name).unwrap_or(&serde_json::Value::Null);
+            let new = after.get(name).unwrap_or(&serde_json::Value::Null);
+            (old != new).then(|| format!("{}: {} → {}", name, old, new))
+        })
+        .collect()
+}
+
+impl Database {
+    /// Record a change to `name`; `before` is the rule as it was, `after`
+    /// as it is now (None when deleted). Returns the new version.
+    pub fn record_rule_change(
+        &self,
+        name: &str,
+        change: &str,
+        changed_by: &str,
+        before: Option<&Rule>,
+        after: Option<&Rule>,
+    ) -> anyhow::Result<u32> {
+        let tx = self.conn.unchecked_transaction()?;
+        let latest: u32 = tx.query_row(
+            "SELECT COALESCE(MAX(version), 0) FROM rule_history WHERE rule_name = ?1",
+            [name],
+            |row| row.get(0),
+        )?;
+        let insert = |version: u32,
+                      change: &str,
+                      by: &str,
+                      diff: Vec<String>,
+                      rule: Option<&Rule>| {
+            tx.execute(
+                r#"
+                INSERT INTO rule_history (rule_name, version, changed_at, changed_by, change, diff, rule)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                "#,
+                params![
+                    name,
+                    version,
+                    chrono::Utc::now().to_rfc3339(),
+                    by,
+                    change,
+                    serde_json::to_string(&diff)?,
+                    rule.map(serde_json::to_string).transpose()?,
+                ],
+            )?;
+            anyhow::Ok(())
+        };
+
+        let mut version = latest;
+        // A rule from config, changed for the first time
+        if latest == 0 && before.is_some() {
+            version += 1;
+            insert(version, INITIAL, "config", vec![], before)?;
+        }
+        version += 1;
+        insert(version, change, changed_by, diff(before, after), after)?;
+        tx.commit()?;
+        Ok(version)
+    }
+
+    /// All versions of `name`, oldest first
+    pub fn rule_history(&self, name: &str) -> anyhow::Result<Vec<RuleVersion>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT version, changed_at, changed_by, change, diff, rule FROM rule_history
+            WHERE rule_name = ?1 ORDER BY version
+            "#,
+        )?;
+        let rows = stmt.query_map([name], row_to_version)?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+
+    pub fn rule_version(&self, name: &str, version: u32) -> anyhow::Result<Option<RuleVersion>> {
+        Ok(self
+            .conn
+            .query_row(
+                r#"
+                SELECT version, changed_at, changed_by, change, diff, rule FROM rule_history
+                WHERE rule_name = ?1 AND version = ?2
+                "#,
+                params![name, version],
+                row_to_version,
+            )
+            .optional()?)
+    }
+}
+
+fn row_to_version(row: &rusqlite::Row<'_>) -> rusqlite::Result<RuleVersion> {
+    let diff: String = row.get(4)?;
+    let rule: Option<String> = row.get(5)?;
+    Ok(RuleVersion {
+        version: row.get(0)?,
+        changed_at: row.get(1)?,
+        changed_by: row.get(2)?,
+        change: row.get(3)?,
+        diff: serde_json::from_str(&diff).unwrap_or_default(),
+        rule: rule.and_then(|r| serde_json::from_str(&r).ok()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleAction;
+    use crate::RiskLevel;
+
+    #[test]
+    fn test_rule_history_versions() {
+        let db = Database::open_in_memory().unwrap();
+        let original = Rule::new(
+            "no_rm",
+            "No rm",
+            r"rm\s+-rf",
+            RiskLevel::Critical,
+            RuleAction::Block,
+        );
+        let mut edited = original.clone();
+        edited.pattern = "rm -rf /nonexistent".to_string();
+
+        let version = db
+            .record_rule_change("no_rm", UPDATE, "api", Some(&original), Some(&edited))
+            .unwrap();
+        assert_eq!(version, 2);
+        let version = db
+            .record_rule_change("no_rm", DELETE, "api", Some(&edited), None)
+            .unwrap();
+        assert_eq!(version, 3);
+
+        let history = db.rule_history("no_rm").unwrap();
+        let changes: Vec<&str> = history.iter().map(|v| v.change.as_str()).collect();
+        assert_eq!(changes, [INITIAL, UPDATE, DELETE]);
+        assert_eq!(history[1].diff.len(), 1);
+        assert!(history[1].diff[0].starts_with("pattern: "));
+        assert!(history[2].rule.is_none());
+
+        let first = db.rule_version("no_rm", 1).unwrap().unwrap();
+        assert_eq!(first.rule.unwrap().pattern, original.pattern);
+        assert!(db.rule_version("no_rm", 9).unwrap().is_none());
+
+        // A rule created through the API starts at version 1
+        assert_eq!(
+            db.record_rule_change("new", CREATE, "api", None, Some(&original))
+                .unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,254 @@
+//! Harness-internal failures, counted so silent degradation shows up
+//!
+//! Some failures are only logged: a collector line that doesn't parse, an
+//! alert that wasn't delivered, an API call the proxy couldn't forward, a
+//! panic in a background task. Each one is counted here in memory, per
+//! source and component, with its latest message. The daemon and the proxy
+//! flush the counts into the `diagnostics` table. `/api/v1/status`
+//! (`diagnostics`) and `openclaw-harness doctor` report them.
+
+use crate::db::Database;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Longest message kept per entry, in characters
+const MAX_MESSAGE_CHARS: usize = 500;
+
+/// What failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Panic,
+    Collector,
+    Alert,
+    Proxy,
+}
+
+impl Source {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Panic => "panic",
+            Self::Collector => "collector",
+            Self::Alert => "alert",
+            Self::Proxy => "proxy",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "panic" => Some(Self::Panic),
+            "collector" => Some(Self::Collector),
+            "alert" => Some(Self::Alert),
+            "proxy" => Some(Self::Proxy),
+            _ => None,
+        }
+    }
+}
+
+/// Failures of one component: a collector, an alert channel, an upstream,
+/// or the place a panic happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub source: Source,
+    pub component: String,
+    pub count: u64,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub last_message: String,
+}
+
+/// Failure counts per source, for status reports
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DiagnosticCounts {
+    pub total: u64,
+    pub panic: u64,
+    pub collector: u64,
+    pub alert: u64,
+    pub proxy: u64,
+    pub last_seen: Option<DateTime<Utc>>,
+}
+
+impl DiagnosticCounts {
+    pub fn of(entries: &[Diagnostic]) -> Self {
+        let mut counts = Self::default();
+        for entry in entries {
+            counts.total += entry.count;
+            *match entry.source {
+                Source::Panic => &mut counts.panic,
+                Source::Collector => &mut counts.collector,
+                Source::Alert => &mut counts.alert,
+                Source::Proxy => &mut counts.proxy,
+            } += entry.count;
+            counts.last_seen = counts.last_seen.max(Some(entry.last_seen));
+        }
+        counts
+    }
+}
+
+/// Failures recorded and not yet flushed to the database
+pub struct Recorder {
+    pending: Mutex<BTreeMap<(Source, String), Diagnostic>>,
+}
+
+impl Recorder {
+    pub const fn new() -> Self {
+        Self {
+            pending: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    fn pending(&self) -> std::sync::MutexGuard<'_, BTreeMap<(Source, String), Diagnostic>> {
+        // Recording must keep working after a panic elsewhere
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count a failure of `component`
+    pub fn record(&self, source: Source, component: &str, message: impl std::fmt::Display) {
+        let now = Utc::now();
+        let message: String = message
+            .to_string()
+            .chars()
+            .take(MAX_MESSAGE_CHARS)
+            .collect();
+        self.pending()
+            .entry((source, component.to_string()))
+            .and_modify(|entry| {
+                entry.count += 1;
+                entry.last_seen = now;
+                entry.last_message = message.clone();
+            })
+            .or_insert_with(|| Diagnostic {
+                source,
+                component: component.to_string(),
+                count: 1,
+                first_seen: now,
+                last_seen: now,
+                last_message: message.clone(),
+            });
+    }
+
+    /// Failures not yet flushed, leaving them in place
+    pub fn snapshot(&self) -> Vec<Diagnostic> {
+        self.pending().values().cloned().collect()
+    }
+
+    /// Move the recorded failures into `db`; returns how many entries were
+    /// written. They are kept for the next attempt if that fails.
+    pub fn flush(&self, db: &Database) -> anyhow::Result<usize> {
+        let entries: Vec<Diagnostic> = std::mem::take(&mut *self.pending()).into_values().collect();
+        if entries.is_empty() {
+            return Ok(0);
+        }
+        if let Err(e) = db.add_diagnostics(&entries) {
+            let mut pending = self.pending();
+            for entry in entries {
+                let key = (entry.source, entry.component.clone());
+                match pending.get_mut(&key) {
+                    // Recorded again meanwhile: fold the older counts in
+                    Some(newer) => {
+                        newer.count += entry.count;
+                        newer.first_seen = entry.first_seen;
+                    }
+                    None => {
+                        pending.insert(key, entry);
+                    }
+                }
+            }
+            return Err(e);
+        }
+        Ok(entries.len())
+    }
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The process's failures
+static RECORDER: Recorder = Recorder::new();
+
+/// Count a failure of `component`
+pub fn record(source: Source, component: &str, message: impl std::fmt::Display) {
+    RECORDER.record(source, component, message);
+}
+
+/// Move the process's recorded failures into `db` (see [`Recorder::flush`])
+pub fn flush(db: &Database) -> anyhow::Result<usize> {
+    RECORDER.flush(db)
+}
+
+/// Record panics (then report them as before) and store them in the
+/// database at `db_path` at once, as the process may be going down
+pub fn install_panic_hook(db_path: PathBuf) {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "panic".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_else(|| "unknown".to_string());
+        let thread = std::thread::current();
+        record(
+            Source::Panic,
+            &location,
+            format!(
+                "thread '{}': {}",
+                thread.name().unwrap_or("unnamed"),
+                message
+            ),
+        );
+        if let Ok(db) = Database::open(&db_path) {
+            let _ = flush(&db);
+        }
+        previous(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_flush() {
+        let db = Database::open_in_memory().unwrap();
+        let recorder = Recorder::new();
+
+        recorder.record(Source::Alert, "telegram", "timed out");
+        recorder.record(Source::Alert, "telegram", "HTTP 500");
+        recorder.record(Source::Collector, "openclaw", "expected value");
+        let pending = recorder.snapshot();
+        assert_eq!(pending.len(), 2);
+        let telegram = pending.iter().find(|d| d.component == "telegram").unwrap();
+        assert_eq!(telegram.count, 2);
+        assert_eq!(telegram.last_message, "HTTP 500");
+
+        assert_eq!(recorder.flush(&db).unwrap(), 2);
+        assert!(recorder.snapshot().is_empty());
+        recorder.record(Source::Alert, "telegram", "connection refused");
+        recorder.flush(&db).unwrap();
+
+        let stored = db.diagnostics().unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].source, Source::Alert);
+        assert_eq!(stored[0].count, 3);
+        assert_eq!(stored[0].last_message, "connection refused");
+        assert!(stored[0].first_seen <= stored[0].last_seen);
+
+        let counts = DiagnosticCounts::of(&stored);
+        assert_eq!(
+            (counts.total, counts.alert, counts.collector, counts.panic),
+            (4, 3, 1, 0)
+        );
+        assert_eq!(counts.last_seen, Some(stored[0].last_seen));
+    }
+}
@@ -1,8 +1,16 @@
 //! Alert sending to various channels
 
-use super::super::{AlertConfig, AnalysisResult, DiscordConfig, SlackConfig, TelegramConfig};
+use super::super::{
+    AlertConfig, AnalysisResult, DesktopConfig, DiscordConfig, RiskLevel, SlackConfig,
+    TelegramConfig,
+};
+use super::desktop::send_desktop;
+use super::schedule::QuietGate;
+use super::secrets::{self, Seal};
+use crate::diagnostics::{self, Source};
 use reqwest::Client;
 use serde_json::json;
+use std::path::PathBuf;
 use tracing::{error, info};
 
 pub struct Alerter {
@@ -10,62 +18,240 @@ pub struct Alerter {
     telegram: Option<TelegramConfig>,
     slack: Option<SlackConfig>,
     discord: Option<DiscordConfig>,
+    desktop: Option<DesktopConfig>,
+    telegram_gate: QuietGate,
+    slack_gate: QuietGate,
+    discord_gate: QuietGate,
+    desktop_gate: QuietGate,
+    /// File the channels were loaded from, checked before each send
+    config_file: Option<PathBuf>,
 }
 
 impl Alerter {
     pub fn new(config: AlertConfig) -> Self {
         Self {
-            client: Client::new(),
+            client: crate::network::client(),
+            telegram_gate: QuietGate::for_channel(&config, "telegram"),
+            slack_gate: QuietGate::for_channel(&config, "slack"),
+            discord_gate: QuietGate::for_channel(&config, "discord"),
+            desktop_gate: QuietGate::for_channel(&config, "desktop"),
             telegram: config.telegram,
             slack: config.slack,
             discord: config.discord,
+            desktop: config.desktop,
+            config_file: None,
         }
     }
 
-    /// Send an alert to all configured channels
+    /// Check `path` (the sealed alert config the channels came from) before
+    /// every send
+    pub fn with_config_file(mut self, path: PathBuf) -> Self {
+        self.config_file = Some(path);
+        self
+    }
+
+    /// A warning to put on alerts if the config file changed outside the
+    /// harness. Alerts still go to the channels loaded before, so a
+    /// tampered file can neither redirect nor silence them.
+    fn integrity_warning(&self) -> Option<String> {
+        let path = self.config_file.as_ref()?;
+        (secrets::check_seal(path) == Seal::Broken).then(|| {
+            error!(
+                "🚨 {} was changed outside OpenClaw Harness; alerting the channels loaded at start",
+                path.display()
+            );
+            format!(
+                "🚨 *{} was changed outside OpenClaw Harness.* Check who changed it.",
+                path.display()
+            )
+        })
+    }
+
+    /// Send an alert to all configured channels, holding it back on
+    /// channels in their quiet hours. The desktop only shows Warning and
+    /// Critical alerts.
     pub async fn send_alert(&self, result: &AnalysisResult) -> anyhow::Result<()> {
         let message = self.format_message(result);
+        let summary = format!(
+            "{} `{}` ({})",
+            result.action.action_type,
+            truncate(&result.action.content, 60),
+            result.matched_rules.join(", ")
+        );
+        let admit = |gate: &QuietGate| gate.admit(result.risk_level, &summary);
+        self.send_all(
+            &message,
+            self.telegram.is_some() && admit(&self.telegram_gate),
+            self.slack.is_some() && admit(&self.slack_gate),
+            self.discord.is_some() && admit(&self.discord_gate),
+            Some(result.risk_level).filter(|risk| {
+                self.desktop.is_some() && *risk >= RiskLevel::Warning && admit(&self.desktop_gate)
+            }),
+        )
+        .await
+    }
+
+    /// Hold alerts on every channel until `until` (None resumes)
+    pub fn pause(&self, until: Option<chrono::DateTime<chrono::Utc>>) {
+        for gate in [
+            &self.telegram_gate,
+            &self.slack_gate,
+            &self.discord_gate,
+            &self.desktop_gate,
+        ] {
+            gate.pause(until);
+        }
+    }
 
-        // Send to all configured channels concurrently
+    pub fn paused_until(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.telegram_gate.paused_until()
+    }
+
+    /// Send the digest of held alerts on channels whose quiet hours are over
+    pub async fn send_digests(&self) -> anyhow::Result<()> {
+        for (gate, telegram, slack, discord, desktop) in [
+            (&self.telegram_gate, true, false, false, None),
+            (&self.slack_gate, false, true, false, None),
+            (&self.discord_gate, false, false, true, None),
+            (
+                &self.desktop_gate,
+                false,
+                false,
+                false,
+                Some(RiskLevel::Warning),
+            ),
+        ] {
+            if let Some(digest) = gate.digest() {
+                self.send_all(&digest, telegram, slack, discord, desktop)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Send `message` to the selected channels concurrently; `desktop` is
+    /// the risk to show it with on the desktop, if at all
+    async fn send_all(
+        &self,
+        message: &str,
+        telegram: bool,
+        slack: bool,
+        discord: bool,
+        desktop: Option<RiskLevel>,
+    ) -> anyhow::Result<()> {
+        let message = match self.integrity_warning() {
+            Some(warning) => format!("{}\n\n{}", warning, message),
+            None => message.to_string(),
+        };
+        let message = message.as_str();
         let mut handles = vec![];
 
-        if let Some(ref tg) = self.telegram {
-            let msg = message.clone();
+        if let Some(tg) = self.telegram.as_ref().filter(|_| telegram) {
+            let msg = message.to_string();
             let client = self.client.clone();
             let config = tg.clone();
-            handles.push(tokio::spawn(async move {
-                send_telegram(&client, &config, &msg).await
-            }));
+            handles.push((
+                "telegram",
+                tokio::spawn(async move { send_telegram(&client, &config, &msg).await }),
+            ));
         }
 
-        if let Some(ref slack) = self.slack {
-            let msg = message.clone();
+        if let Some(slack) = self.slack.as_ref().filter(|_| slack) {
+            let msg = message.to_string();
             let client = self.client.clone();
             let config = slack.clone();
-            handles.push(tokio::spawn(async move {
-                send_slack(&client, &config, &msg).await
-            }));
+            handles.push((
+                "slack",
+                tokio::spawn(async move { send_slack(&client, &config, &msg).await }),
+            ));
         }
 
-        if let Some(ref discord) = self.discord {
-            let msg = message.clone();
+        if let Some(discord) = self.discord.as_ref().filter(|_| discord) {
+            let msg = message.to_string();
             let client = self.client.clone();
             let config = discord.clone();
-            handles.push(tokio::spawn(async move {
-                send_discord(&client, &config, &msg).await
-            }));
+            handles.push((
+                "discord",
+                tokio::spawn(async move { send_discord(&client, &config, &msg).await }),
+            ));
+        }
+
+        if let Some((config, risk)) = self.desktop.as_ref().zip(desktop) {
+            let msg = message.to_string();
+            let config = config.clone();
+            handles.push((
+                "desktop",
+                tokio::spawn(async move { send_desktop(&config, risk, &msg).await }),
+            ));
         }
 
         // Wait for all to complete
-        for handle in handles {
+        for (channel, handle) in handles {
             if let Err(e) = handle.await? {
-                error!("Failed to send alert: {}", e);
+                error!("Failed to send {} alert: {}", channel, e);
+                diagnostics::record(Source::Alert, channel, &e);
             }
         }
 
         Ok(())
     }
 
+    /// Send a test message through each configured channel (or just `only`),
+    /// ignoring quiet hours, and report how each delivery went
+    pub async fn test_channels(&self, only: Option<&str>) -> anyhow::Result<Vec<ChannelTest>> {
+        let configured: Vec<&str> = [
+            self.telegram.as_ref().map(|_| "telegram"),
+            self.slack.as_ref().map(|_| "slack"),
+            self.discord.as_ref().map(|_| "discord"),
+            self.desktop.as_ref().map(|_| "desktop"),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let channels: Vec<&str> = match only {
+            Some(name) if configured.contains(&name) => vec![name],
+            Some(name) if CHANNELS.contains(&name) => {
+                anyhow::bail!("The {} channel is not configured", name)
+            }
+            Some(name) => anyhow::bail!(
+                "Unknown channel '{}' (expected one of: {})",
+                name,
+                CHANNELS.join(", ")
+            ),
+            None if configured.is_empty() => anyhow::bail!("No alert channels are configured"),
+            None => configured,
+        };
+
+        let mut results = vec![];
+        for channel in channels {
+            let message = format!(
+                "🧪 *OpenClaw Harness test alert*
+
+If you can read this, {} alerts from {} work.",
+                channel,
+                hostname()
+            );
+            let started = std::time::Instant::now();
+            let sent = match channel {
+                "telegram" => {
+                    send_telegram(&self.client, self.telegram.as_ref().unwrap(), &message).await
+                }
+                "slack" => send_slack(&self.client, self.slack.as_ref().unwrap(), &message).await,
+                "desktop" => {
+                    send_desktop(self.desktop.as_ref().unwrap(), RiskLevel::Warning, &message).await
+                }
+                _ => send_discord(&self.client, self.discord.as_ref().unwrap(), &message).await,
+            };
+            results.push(ChannelTest {
+                channel: channel.to_string(),
+                ok: sent.is_ok(),
+                latency_ms: started.elapsed().as_millis() as u64,
+                error: sent.err().map(|e| e.to_string()),
+            });
+        }
+        Ok(results)
+    }
+
     fn format_message(&self, result: &AnalysisResult) -> String {
         format!(
             "🛡️ *OpenClaw Harness Alert*\n\n\
@@ -85,6 +271,27 @@ impl Alerter {
     }
 }
 
+/// Alert channel names, as used in `alerts test --channel` and quiet hours
+pub const CHANNELS: [&str; 4] = ["telegram", "slack", "discord", "desktop"];
+
+/// Outcome of a test message on one channel
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ChannelTest {
+    pub channel: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "this machine".to_string())
+}
+
 fn truncate(s: &str, max_len: usize) -> String {
     if s.len() > max_len {
         let mut end = max_len;
@@ -115,7 +322,10 @@ async fn send_telegram(
             "parse_mode": "Markdown"
         }))
         .send()
-        .await?;
+        .await
+        .and_then(|resp| resp.error_for_status())
+        // The URL carries the bot token
+        .map_err(|e| e.without_url())?;
 
     info!("Sent Telegram alert");
     Ok(())
@@ -128,7 +338,8 @@ async fn send_slack(client: &Client, config: &SlackConfig, message: &str) -> any
             "text": message
         }))
         .send()
-        .await?;
+        .await?
+        .error_for_status()?;
 
     info!("Sent Slack alert");
     Ok(())
@@ -145,8 +356,70 @@ async fn send_discord(
             "content": message
         }))
         .send()
-        .await?;
+        .await?
+        .error_for_status()?;
 
     info!("Sent Discord alert");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::post, Router};
+
+    #[tokio::test]
+    async fn test_channel_verification() {
+        let app = Router::new()
+            .route("/ok", post(|| async { StatusCode::OK }))
+            .route("/gone", post(|| async { StatusCode::NOT_FOUND }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let alerter = Alerter::new(AlertConfig {
+            telegram: None,
+            slack: Some(SlackConfig {
+                webhook_url: format!("http://{}/ok", addr),
+            }),
+            discord: Some(DiscordConfig {
+                webhook_url: format!("http://{}/gone", addr),
+            }),
+            desktop: None,
+            quiet_hours: Default::default(),
+        });
+
+        let results = alerter.test_channels(None).await.unwrap();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].ok && results[0].channel == "slack");
+        assert!(!results[1].ok);
+        assert!(results[1].error.as_ref().unwrap().contains("404"));
+
+        assert_eq!(alerter.test_channels(Some("slack")).await.unwrap().len(), 1);
+        assert!(alerter.test_channels(Some("telegram")).await.is_err());
+        assert!(alerter.test_channels(Some("pager")).await.is_err());
+    }
+
+    #[test]
+    fn test_tampered_config_file_is_flagged() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.json");
+        secrets::write_private(&path, b"{}").unwrap();
+        secrets::seal(&path).unwrap();
+        let alerter = Alerter::new(AlertConfig {
+            telegram: None,
+            slack: None,
+            discord: None,
+            desktop: None,
+            quiet_hours: Default::default(),
+        })
+        .with_config_file(path.clone());
+        assert_eq!(alerter.integrity_warning(), None);
+
+        std::fs::write(&path, br#"{"slack_webhook": "https://attacker"}"#).unwrap();
+        assert!(alerter
+            .integrity_warning()
+            .unwrap()
+            .contains("changed outside"));
+    }
+}
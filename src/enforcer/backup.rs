@@ -0,0 +1,193 @@
+//! Pre-write backups for approved PauseAndAsk file actions
+//!
+//! When a PauseAndAsk write is allowed to proceed (monitor-mode proxy, an
+//! approved call in the PreToolUse hook, or the plugin hook), the current
+//! file is copied into a timestamped directory first so the approval stays
+//! a recoverable mistake.
+
+use crate::proxy::interceptor::InterceptResult;
+use crate::rules::RuleAction;
+use crate::ActionType;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
+
+/// Environment variable overriding the retention period (days)
+pub const BACKUP_RETENTION_ENV: &str = "OPENCLAW_HARNESS_BACKUP_RETENTION_DAYS";
+
+const DEFAULT_RETENTION_DAYS: u32 = 7;
+
+/// Timestamp format used for backup directory names
+const STAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3f";
+
+pub struct BackupStore {
+    root: PathBuf,
+    retention_days: u32,
+}
+
+impl Default for BackupStore {
+    fn default() -> Self {
+        let retention_days = std::env::var(BACKUP_RETENTION_ENV)
+            .ok()
+            .and_then(|d| d.parse().ok())
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+        Self::new(crate::harness_path("backups"), retention_days)
+    }
+}
+
+impl BackupStore {
+    pub fn new(root: impl Into<PathBuf>, retention_days: u32) -> Self {
+        Self {
+            root: root.into(),
+            retention_days,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Copy `path` into `<root>/<timestamp>/<absolute path>`.
+    ///
+    /// Returns `None` if there is nothing to back up (file doesn't exist yet).
+    pub fn backup(&self, path: &Path) -> anyhow::Result<Option<PathBuf>> {
+        if !path.is_file() {
+            debug!("No existing file to back up at {}", path.display());
+            return Ok(None);
+        }
+
+        let relative: PathBuf = path
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)))
+            .collect();
+        let stamp = chrono::Utc::now().format(STAMP_FORMAT).to_string();
+        let dest = self.root.join(stamp).join(relative);
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(path, &dest)?;
+        info!("💾 Backed up {} -> {}", path.display(), dest.display());
+        Ok(Some(dest))
+    }
+
+    /// Remove backup directories older than the retention period
+    pub fn prune(&self) -> anyhow::Result<usize> {
+        let entries = match fs::read_dir(&self.root) {
+            Ok(e) => e,
+            Err(_) => return Ok(0),
+        };
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(self.retention_days as i64);
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Ok(stamp) = chrono::NaiveDateTime::parse_from_str(&name, STAMP_FORMAT) else {
+                continue;
+            };
+            if stamp.and_utc() < cutoff {
+                fs::remove_dir_all(entry.path())?;
+                removed += 1;
+            }
+        }
+
+        if removed > 0 {
+            info!("🧹 Pruned {} expired backup(s)", removed);
+        }
+        Ok(removed)
+    }
+}
+
+/// Back up the targets of PauseAndAsk file writes and deletes that are
+/// about to go through, then prune; returns the number of files copied
+pub fn backup_approved_writes(store: &BackupStore, intercepts: &[InterceptResult]) -> usize {
+    let pending: Vec<&str> = intercepts
+        .iter()
+        .filter(|i| i.action == RuleAction::PauseAndAsk)
+        .filter(|i| {
+            matches!(
+                i.action_type,
+                ActionType::FileWrite | ActionType::FileDelete
+            )
+        })
+        .filter_map(|i| i.target.as_deref())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if pending.is_empty() {
+        return 0;
+    }
+
+    let mut copied = 0;
+    for target in pending {
+        match store.backup(&crate::expand_home(target)) {
+            Ok(Some(_)) => copied += 1,
+            Ok(None) => {}
+            Err(e) => warn!("Failed to back up {} before approved write: {}", target, e),
+        }
+    }
+    if let Err(e) = store.prune() {
+        warn!("Failed to prune backups: {}", e);
+    }
+    copied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::attribution::Attribution;
+    use crate::proxy::interceptor::check_tool_use;
+    use crate::rules::Rule;
+    use crate::RiskLevel;
+
+    #[test]
+    fn test_backup_and_prune() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BackupStore::new(dir.path().join("backups"), 7);
+
+        let file = dir.path().join("config.toml");
+        assert!(store.backup(&file).unwrap().is_none());
+
+        fs::write(&file, "before").unwrap();
+        let copy = store.backup(&file).unwrap().unwrap();
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "before");
+
+        // An expired backup directory gets pruned, the fresh one stays
+        let old = dir.path().join("backups/20000101T000000.000");
+        fs::create_dir_all(&old).unwrap();
+        assert_eq!(store.prune().unwrap(), 1);
+        assert!(!old.exists());
+        assert!(copy.exists());
+    }
+
+    #[test]
+    fn test_backup_approved_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BackupStore::new(dir.path().join("backups"), 7);
+        let file = dir.path().join("deploy.toml");
+        fs::write(&file, "before").unwrap();
+
+        let rules = [Rule::new(
+            "ask_deploy",
+            "Ask before changing deploy config",
+            r"deploy\.toml",
+            RiskLevel::Warning,
+            RuleAction::PauseAndAsk,
+        )];
+        let write = serde_json::json!({ "file_path": file, "content": "after" });
+        let hit = check_tool_use(0, "Write", &write, &rules, &Attribution::default()).unwrap();
+        assert_eq!(hit.action_type, ActionType::FileWrite);
+        assert_eq!(
+            backup_approved_writes(&store, std::slice::from_ref(&hit)),
+            1
+        );
+        let stamps: Vec<_> = fs::read_dir(store.root()).unwrap().collect();
+        assert_eq!(stamps.len(), 1);
+
+        // Only PauseAndAsk hits that were let through
+        let blocked = InterceptResult {
+            action: RuleAction::Block,
+            ..hit
+        };
+        assert_eq!(backup_approved_writes(&store, &[blocked]), 0);
+    }
+}
@@ -0,0 +1,370 @@
+//! Terminal confirmation of PauseAndAsk actions
+//!
+//! When the daemon runs in the foreground on an interactive terminal
+//! (`start --foreground`), PauseAndAsk actions are put to whoever is at it:
+//! `Approve …? [y/N]`. Anything but `y`/`yes` denies, and so does no answer
+//! within the timeout (`OPENCLAW_HARNESS_APPROVAL_TIMEOUT_SECS`, default
+//! 30). `OPENCLAW_HARNESS_TERMINAL_APPROVAL=0` turns the prompt off.
+//!
+//! The terminal is one more surface of the approval table, next to the
+//! Slack app: actions the daemon sees are filed there, requests filed by
+//! `hook-exec` are asked about too, and a decision made in Slack while the
+//! prompt is up ends it.
+
+use crate::db::{Approval, DbPool};
+use crate::{AgentAction, AnalysisResult};
+use std::collections::HashSet;
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Environment variable turning the terminal prompt off (`0`)
+pub const TERMINAL_APPROVAL_ENV: &str = "OPENCLAW_HARNESS_TERMINAL_APPROVAL";
+/// Environment variable with the seconds to wait for an answer
+pub const TIMEOUT_ENV: &str = "OPENCLAW_HARNESS_APPROVAL_TIMEOUT_SECS";
+/// `decided_by` of decisions made at the terminal
+pub const DECIDED_BY: &str = "terminal";
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Fingerprint prefix of requests for actions the daemon saw
+const ACTION_PREFIX: &str = "action:";
+/// How often the approval table is checked for new requests and for
+/// decisions made elsewhere
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How a request put to the terminal was settled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Decision {
+    Approved,
+    Denied,
+    /// No answer in time; denied
+    TimedOut,
+    /// Decided on another surface while the prompt was up
+    Elsewhere {
+        approved: bool,
+        by: String,
+    },
+}
+
+impl Decision {
+    pub fn approved(&self) -> bool {
+        matches!(
+            self,
+            Decision::Approved | Decision::Elsewhere { approved: true, .. }
+        )
+    }
+}
+
+pub struct TerminalApprover {
+    pool: DbPool,
+    timeout: Duration,
+    /// Lines typed at the terminal; one prompt reads them at a time
+    input: tokio::sync::Mutex<mpsc::UnboundedReceiver<String>>,
+    /// Requests already put to the terminal
+    asked: Mutex<HashSet<String>>,
+}
+
+impl TerminalApprover {
+    pub fn new(pool: DbPool, timeout: Duration, input: mpsc::UnboundedReceiver<String>) -> Self {
+        Self {
+            pool,
+            timeout,
+            input: tokio::sync::Mutex::new(input),
+            asked: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// An approver reading this process's terminal, if stdin and stderr are
+    /// one and the prompt isn't turned off
+    pub fn from_terminal(pool: DbPool) -> Option<Arc<Self>> {
+        if std::env::var(TERMINAL_APPROVAL_ENV).is_ok_and(|v| v.trim() == "0")
+            || !std::io::stdin().is_terminal()
+            || !std::io::stderr().is_terminal()
+        {
+            return None;
+        }
+        let timeout = std::env::var(TIMEOUT_ENV)
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .filter(|&secs| secs > 0)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        let (tx, rx) = mpsc::unbounded_channel();
+        // Blocking reads, so on a thread of its own
+        std::thread::spawn(move || {
+            for line in std::io::stdin().lines() {
+                let Ok(line) = line else { break };
+                if tx.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Some(Arc::new(Self::new(pool, Duration::from_secs(timeout), rx)))
+    }
+
+    /// File an approval request for an action the daemon saw
+    pub fn request(&self, result: &AnalysisResult) -> anyhow::Result<Approval> {
+        let db = self.pool.get()?;
+        let id = db.request_approval(&fingerprint(&result.action), &summary(result))?;
+        db.approval(&id)?
+            .ok_or_else(|| anyhow::anyhow!("approval {} vanished", id))
+    }
+
+    /// Ask at the terminal about `approval` and record the answer. None if
+    /// it was asked about before or is no longer pending.
+    pub async fn confirm(&self, approval: &Approval) -> anyhow::Result<Option<Decision>> {
+        if !self
+            .asked
+            .lock()
+            .map_err(|_| anyhow::anyhow!("approval state poisoned"))?
+            .insert(approval.id.clone())
+        {
+            return Ok(None);
+        }
+        let mut input = self.input.lock().await;
+        // Typed before there was anything to answer
+        while input.try_recv().is_ok() {}
+        if let Some(decision) = self.decided_elsewhere(&approval.id)? {
+            return Ok(Some(decision));
+        }
+
+        eprint!(
+            "\n⏸️  Approve `{}` {}? [y/N] ({}s, no answer denies) ",
+            printable(&approval.id),
+            printable(&approval.summary),
+            self.timeout.as_secs()
+        );
+        let deadline = tokio::time::Instant::now() + self.timeout;
+        let mut poll = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            tokio::select! {
+                line = input.recv() => {
+                    let approved = line.as_deref().is_some_and(is_yes);
+                    let decision = if approved { Decision::Approved } else { Decision::Denied };
+                    return self.record(approval, decision);
+                }
+                _ = poll.tick() => {
+                    if let Some(decision) = self.decided_elsewhere(&approval.id)? {
+                        if let Decision::Elsewhere { approved, by } = &decision {
+                            eprintln!(
+                                "\n{} by {}",
+                                if *approved { "✅ Approved" } else { "🚫 Denied" },
+                                printable(by)
+                            );
+                        }
+                        return Ok(Some(decision));
+                    }
+                }
+                _ = tokio::time::sleep_until(deadline) => {
+                    eprintln!();
+                    return self.record(approval, Decision::TimedOut);
+                }
+            }
+        }
+    }
+
+    /// Ask about requests filed by other processes (`hook-exec`), forever;
+    /// the daemon's own are asked about where they are filed
+    pub async fn run(self: Arc<Self>) {
+        loop {
+            let pending = self.pool.get().and_then(|db| db.pending_approvals(false));
+            match pending {
+                Ok(pending) => {
+                    let foreign = pending
+                        .into_iter()
+                        .filter(|a| !a.fingerprint.starts_with(ACTION_PREFIX));
+                    for approval in foreign {
+                        if let Err(e) = self.confirm(&approval).await {
+                            warn!("⚠️ Failed to record the answer for {}: {}", approval.id, e);
+                        }
+                    }
+                }
+                Err(e) => warn!("⚠️ Failed to read pending approvals: {}", e),
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    fn record(&self, approval: &Approval, decision: Decision) -> anyhow::Result<Option<Decision>> {
+        let db = self.pool.get()?;
+        match db.decide_approval(&approval.id, decision.approved(), DECIDED_BY)? {
+            Some(_) => {
+                eprintln!(
+                    "{}",
+                    match decision {
+                        Decision::Approved => "✅ Approved",
+                        Decision::TimedOut => "🚫 No answer; denied",
+                        _ => "🚫 Denied",
+                    }
+                );
+                Ok(Some(decision))
+            }
+            // Decided elsewhere just before
+            None => self.decided_elsewhere(&approval.id),
+        }
+    }
+
+    fn decided_elsewhere(&self, id: &str) -> anyhow::Result<Option<Decision>> {
+        let Some(approval) = self.pool.get()?.approval(id)? else {
+            return Ok(None);
+        };
+        Ok(match approval.status.as_str() {
+            "pending" => None,
+            status => Some(Decision::Elsewhere {
+                approved: status != "denied",
+                by: approval.decided_by.unwrap_or_default(),
+            }),
+        })
+    }
+}
+
+/// `text` safe to show at the terminal: control characters (escape
+/// sequences, `\r`) and bidi overrides, which could rewrite what the
+/// approver sees, are shown escaped
+fn printable(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            let bidi = matches!(
+                c,
+                '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}'
+            );
+            if c.is_control() || bidi {
+                c.escape_unicode().to_string()
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+fn is_yes(line: &str) -> bool {
+    matches!(line.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+/// Approval fingerprint of an action the daemon saw
+fn fingerprint(action: &AgentAction) -> String {
+    format!("{}{}", ACTION_PREFIX, action.id)
+}
+
+fn summary(result: &AnalysisResult) -> String {
+    let action = &result.action;
+    let mut content = action.content.clone();
+    if content.len() > 200 {
+        let mut cut = 200;
+        while !content.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        content.truncate(cut);
+        content.push('…');
+    }
+    format!(
+        "{} {}: {} (rules: {})",
+        action.agent,
+        action.action_type,
+        content,
+        result.matched_rules.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentType, Recommendation, RiskLevel};
+
+    fn result(id: &str) -> AnalysisResult {
+        AnalysisResult {
+            action: AgentAction {
+                id: id.to_string(),
+                timestamp: chrono::Utc::now(),
+                agent: AgentType::ClaudeCode,
+                action_type: ActionType::Exec,
+                content: "npm publish".to_string(),
+                target: None,
+                session_id: None,
+                metadata: None,
+            },
+            matched_rules: vec!["publish".to_string()],
+            risk_level: RiskLevel::Warning,
+            recommendation: Recommendation::PauseAndAsk,
+            explanation: String::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_terminal_confirmation() {
+        let dir = tempfile::tempdir().unwrap();
+        let pool = DbPool::open(&dir.path().join("harness.db")).unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let approver = Arc::new(TerminalApprover::new(
+            pool.clone(),
+            Duration::from_millis(300),
+            rx,
+        ));
+
+        // Typed ahead of the prompt: ignored
+        tx.send("y".to_string()).unwrap();
+        let approval = approver.request(&result("a1")).unwrap();
+        assert!(approval.summary.contains("npm publish"));
+        let asking = {
+            let approver = approver.clone();
+            let approval = approval.clone();
+            tokio::spawn(async move { approver.confirm(&approval).await.unwrap() })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        tx.send(" Yes ".to_string()).unwrap();
+        assert_eq!(asking.await.unwrap(), Some(Decision::Approved));
+        let db = pool.get().unwrap();
+        let decided = db.approval(&approval.id).unwrap().unwrap();
+        assert_eq!(
+            (decided.status.as_str(), decided.decided_by.as_deref()),
+            ("approved", Some(DECIDED_BY))
+        );
+        // Asked once only
+        assert_eq!(approver.confirm(&approval).await.unwrap(), None);
+
+        // No answer denies
+        let approval = approver.request(&result("a2")).unwrap();
+        assert_eq!(
+            approver.confirm(&approval).await.unwrap(),
+            Some(Decision::TimedOut)
+        );
+        assert_eq!(db.approval(&approval.id).unwrap().unwrap().status, "denied");
+
+        // Decided in Slack while the prompt is up
+        let (_tx, rx) = mpsc::unbounded_channel();
+        let approver = Arc::new(TerminalApprover::new(
+            pool.clone(),
+            Duration::from_secs(30),
+            rx,
+        ));
+        let approval = approver.request(&result("a3")).unwrap();
+        let asking = {
+            let approver = approver.clone();
+            let approval = approval.clone();
+            tokio::spawn(async move { approver.confirm(&approval).await.unwrap() })
+        };
+        db.decide_approval(&approval.id, true, "ana").unwrap();
+        assert_eq!(
+            asking.await.unwrap(),
+            Some(Decision::Elsewhere {
+                approved: true,
+                by: "ana".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_prompt_escapes_control_characters() {
+        // Content that would clear the line and print a harmless summary
+        // over the real one
+        let summary = "rm -rf ~\r\x1b[2K\x1b[1Acodex exec: ls\u{202e}";
+        let shown = printable(summary);
+        assert!(!shown.chars().any(|c| c.is_control() || c == '\u{202e}'));
+        assert_eq!(
+            shown,
+            "rm -rf ~\\u{d}\\u{1b}[2K\\u{1b}[1Acodex exec: ls\\u{202e}"
+        );
+        assert_eq!(printable("npm publish — ok ✅"), "npm publish — ok ✅");
+    }
+}
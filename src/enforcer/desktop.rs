@@ -0,0 +1,222 @@
+//! Native desktop notifications
+//!
+//! Warning and Critical alerts can pop up on the machine the harness runs
+//! on, with no chat integration to set up. The notification is shown by the
+//! platform's own tool, so no extra library is linked:
+//! - macOS: `osascript` (`display notification`)
+//! - Linux and the BSDs: `notify-send` (libnotify), Critical alerts with
+//!   critical urgency
+//! - Windows: a PowerShell toast, with the text passed in environment
+//!   variables rather than in the script
+//!
+//! The channel is on when a graphical session is present (always on macOS
+//! and Windows; `DISPLAY` or `WAYLAND_DISPLAY` elsewhere) unless it is
+//! turned off in the alert config. `OPENCLAW_HARNESS_DESKTOP_NOTIFY=0`
+//! turns it off, `=1` turns it on without looking for a session.
+
+use crate::{DesktopConfig, RiskLevel};
+use std::process::Stdio;
+use tracing::info;
+
+/// Environment variable turning the desktop channel on or off
+pub const DESKTOP_ENV: &str = "OPENCLAW_HARNESS_DESKTOP_NOTIFY";
+
+const APP_NAME: &str = "OpenClaw Harness";
+const MAX_BODY_CHARS: usize = 240;
+/// Windows only shows toasts from registered apps; PowerShell is one
+const POWERSHELL_APP_ID: &str =
+    r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+/// Environment variables the toast script reads its text from. Alert text
+/// comes from agents; kept out of the script, no quoting can go wrong.
+const TITLE_ENV: &str = "OPENCLAW_HARNESS_NOTIFY_TITLE";
+const BODY_ENV: &str = "OPENCLAW_HARNESS_NOTIFY_BODY";
+
+/// Whether notifications would be seen: a desktop session on this machine
+pub fn graphical_session() -> bool {
+    match std::env::consts::OS {
+        "macos" | "windows" => true,
+        _ => ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty())),
+    }
+}
+
+/// The desktop channel, if it is on; `enabled` is the alert config's
+/// setting
+pub fn configured(enabled: bool) -> Option<DesktopConfig> {
+    let forced = std::env::var(DESKTOP_ENV)
+        .ok()
+        .map(|v| v.trim().to_ascii_lowercase());
+    let on = match forced.as_deref() {
+        Some("0" | "false" | "off" | "no") => false,
+        Some("1" | "true" | "on" | "yes") => true,
+        _ => enabled && graphical_session(),
+    };
+    on.then(DesktopConfig::default)
+}
+
+/// A program run to show a notification
+#[derive(Debug)]
+struct Notify {
+    program: String,
+    args: Vec<String>,
+    /// Variables set for the program
+    env: Vec<(&'static str, String)>,
+}
+
+/// How to show a notification on `os`
+fn command(os: &str, title: &str, body: &str, critical: bool, sound: bool) -> Notify {
+    match os {
+        "macos" => {
+            let mut script = format!(
+                "display notification \"{}\" with title \"{}\"",
+                applescript_escape(body),
+                applescript_escape(title)
+            );
+            if sound {
+                script.push_str(" sound name \"Sosumi\"");
+            }
+            Notify {
+                program: "osascript".to_string(),
+                args: vec!["-e".to_string(), script],
+                env: vec![],
+            }
+        }
+        "windows" => {
+            let script = format!(
+                "$t = [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, \
+                 ContentType = WindowsRuntime]::GetTemplateContent(\
+                 [Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+                 $x = $t.GetElementsByTagName('text'); \
+                 [void]$x.Item(0).AppendChild($t.CreateTextNode($env:{})); \
+                 [void]$x.Item(1).AppendChild($t.CreateTextNode($env:{})); \
+                 [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier('{}')\
+                 .Show([Windows.UI.Notifications.ToastNotification]::new($t))",
+                TITLE_ENV, BODY_ENV, POWERSHELL_APP_ID
+            );
+            Notify {
+                program: "powershell".to_string(),
+                args: ["-NoProfile", "-NonInteractive", "-Command", &script]
+                    .map(String::from)
+                    .to_vec(),
+                env: vec![(TITLE_ENV, title.to_string()), (BODY_ENV, body.to_string())],
+            }
+        }
+        _ => {
+            let urgency = if critical { "critical" } else { "normal" };
+            Notify {
+                program: "notify-send".to_string(),
+                args: vec![
+                    format!("--urgency={}", urgency),
+                    format!("--app-name={}", APP_NAME),
+                    "--".to_string(),
+                    title.to_string(),
+                    body.to_string(),
+                ],
+                env: vec![],
+            }
+        }
+    }
+}
+
+fn applescript_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Alert text as a notification shows it: no chat markup, on one short
+/// paragraph
+fn plain(message: &str) -> String {
+    let text = message
+        .replace(['*', '`'], "")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    match text.char_indices().nth(MAX_BODY_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// Show `message` as a desktop notification for an alert of `risk`
+pub async fn send_desktop(
+    config: &DesktopConfig,
+    risk: RiskLevel,
+    message: &str,
+) -> anyhow::Result<()> {
+    let title = format!("🛡️ {}: {}", APP_NAME, risk);
+    let critical = risk == RiskLevel::Critical;
+    let Notify { program, args, env } = command(
+        std::env::consts::OS,
+        &title,
+        &plain(message),
+        critical,
+        critical && !config.silent,
+    );
+    let output = tokio::process::Command::new(&program)
+        .args(&args)
+        .envs(env)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("failed to run {}: {}", program, e))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} exited with {}: {}",
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    info!("Sent desktop alert");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_commands() {
+        let body = plain("🛡️ *Alert*\n\n*Content:* `say \"hi\" it's \\ here`");
+        assert_eq!(body, "🛡️ Alert Content: say \"hi\" it's \\ here");
+        assert!(plain(&"x".repeat(1000)).chars().count() <= MAX_BODY_CHARS + 3);
+
+        let Notify { program, args, .. } = command("macos", "T", &body, true, true);
+        assert_eq!(program, "osascript");
+        assert_eq!(
+            args[1],
+            "display notification \"🛡️ Alert Content: say \\\"hi\\\" it's \\\\ here\" \
+             with title \"T\" sound name \"Sosumi\""
+        );
+
+        let Notify { program, args, .. } = command("linux", "T", "-rf body", true, false);
+        assert_eq!(program, "notify-send");
+        // The body can't be taken for an option
+        assert_eq!(
+            args,
+            [
+                "--urgency=critical",
+                "--app-name=OpenClaw Harness",
+                "--",
+                "T",
+                "-rf body"
+            ]
+        );
+        let args = command("freebsd", "T", "b", false, false).args;
+        assert_eq!(args[0], "--urgency=normal");
+
+        // PowerShell also ends strings at curly quotes: the text stays out
+        // of the script
+        let body = "done‘); Remove-Item -Recurse C:\\; (’";
+        let Notify { program, args, env } = command("windows", "T", body, false, false);
+        assert_eq!(program, "powershell");
+        assert!(args[3].contains("CreateTextNode($env:OPENCLAW_HARNESS_NOTIFY_BODY)"));
+        assert!(!args[3].contains("Remove-Item") && !args[3].contains('‘'));
+        assert_eq!(
+            env,
+            [(TITLE_ENV, "T".to_string()), (BODY_ENV, body.to_string())]
+        );
+    }
+}
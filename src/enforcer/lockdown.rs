@@ -0,0 +1,352 @@
+//! After-hours lockdown
+//!
+//! Agents left running overnight are the ones whose damage goes unnoticed
+//! longest. With `OPENCLAW_HARNESS_WORKING_HOURS` set, the proxy is put in
+//! enforce mode with stricter rules outside working hours, and lifted again
+//! when they start. The schedule is comma-separated tokens, local time:
+//!
+//! - `09:00-18:00` — working hours (may wrap past midnight)
+//! - `weekdays` — Saturday and Sunday are off hours
+//! - `screen-lock` — also lock down while the screen is locked or nobody
+//!   has touched the machine for `OPENCLAW_HARNESS_LOCKDOWN_IDLE_MINUTES`
+//!   (default 15): `loginctl` on Linux, or `xprintidle` under X; the HID
+//!   idle time on macOS
+//!
+//! The stricter rules are the profile named by
+//! `OPENCLAW_HARNESS_LOCKDOWN_PROFILE` (see `rules::profiles`); without one,
+//! warning-level rules become critical and block.
+
+use crate::proxy::control::ProxyControl;
+use crate::rules::profiles::{self, SeverityOverride};
+use crate::rules::RuleAction;
+use crate::RiskLevel;
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeZone, Weekday};
+use std::process::Command;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Environment variable with the working hours
+pub const WORKING_HOURS_ENV: &str = "OPENCLAW_HARNESS_WORKING_HOURS";
+/// Environment variable naming the profile applied during a lockdown
+pub const LOCKDOWN_PROFILE_ENV: &str = "OPENCLAW_HARNESS_LOCKDOWN_PROFILE";
+/// Environment variable with the idle minutes that count as unattended
+pub const IDLE_MINUTES_ENV: &str = "OPENCLAW_HARNESS_LOCKDOWN_IDLE_MINUTES";
+
+const DEFAULT_IDLE_MINUTES: u64 = 15;
+/// How often the schedule and the screen are checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingHours {
+    window: Option<(NaiveTime, NaiveTime)>,
+    weekdays: bool,
+    screen_lock: bool,
+}
+
+impl WorkingHours {
+    pub fn parse(spec: &str) -> anyhow::Result<Self> {
+        let mut hours = Self {
+            window: None,
+            weekdays: false,
+            screen_lock: false,
+        };
+        for token in spec.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            match token {
+                "weekdays" => hours.weekdays = true,
+                "screen-lock" => hours.screen_lock = true,
+                range => {
+                    let (start, end) = range.split_once('-').ok_or_else(|| {
+                        anyhow::anyhow!("Unknown working hours setting '{}'", token)
+                    })?;
+                    let time = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M");
+                    hours.window = Some((time(start)?, time(end)?));
+                }
+            }
+        }
+        if hours.window.is_none() && !hours.weekdays && !hours.screen_lock {
+            anyhow::bail!("Working hours '{}' never lock down", spec);
+        }
+        Ok(hours)
+    }
+
+    pub fn is_working_time<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> bool {
+        if self.weekdays && matches!(now.weekday(), Weekday::Sat | Weekday::Sun) {
+            return false;
+        }
+        let Some((start, end)) = self.window else {
+            return true;
+        };
+        let time = now.time();
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Whether someone seems to be at the machine
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Presence {
+    pub screen_locked: bool,
+    /// Time since the last keyboard or mouse input, when it can be told
+    pub idle: Option<Duration>,
+}
+
+impl Presence {
+    /// Ask the desktop; unknown parts are left unset
+    pub fn detect() -> Self {
+        if cfg!(target_os = "macos") {
+            return Self {
+                screen_locked: false,
+                idle: macos_idle(),
+            };
+        }
+        let session = logind_session();
+        let hint = |name: &str| {
+            session
+                .as_deref()
+                .and_then(|id| logind_property(id, name))
+                .is_some_and(|v| v == "yes")
+        };
+        Self {
+            screen_locked: hint("LockedHint"),
+            // logind only knows the session is idle, not for how long
+            idle: xprintidle().or_else(|| hint("IdleHint").then_some(Duration::MAX)),
+        }
+    }
+}
+
+/// Why the proxy is locked down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    OffHours,
+    ScreenLocked,
+    Idle,
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::OffHours => write!(f, "outside working hours"),
+            Reason::ScreenLocked => write!(f, "screen locked"),
+            Reason::Idle => write!(f, "nobody at the machine"),
+        }
+    }
+}
+
+pub struct LockdownPolicy {
+    pub hours: WorkingHours,
+    /// Applied to the rules while locked down
+    pub overrides: Vec<SeverityOverride>,
+    pub idle_limit: Duration,
+}
+
+impl LockdownPolicy {
+    /// The policy in the environment, if working hours are set
+    pub fn from_env() -> Option<Self> {
+        let spec = std::env::var(WORKING_HOURS_ENV).ok()?;
+        let hours = match WorkingHours::parse(&spec) {
+            Ok(hours) => hours,
+            Err(e) => {
+                warn!("Ignoring {}: {}", WORKING_HOURS_ENV, e);
+                return None;
+            }
+        };
+        let idle_minutes = std::env::var(IDLE_MINUTES_ENV)
+            .ok()
+            .and_then(|m| m.trim().parse().ok())
+            .unwrap_or(DEFAULT_IDLE_MINUTES);
+        Some(Self {
+            hours,
+            overrides: lockdown_overrides(),
+            idle_limit: Duration::from_secs(idle_minutes * 60),
+        })
+    }
+
+    /// Why to lock down at `now`, if at all; `presence` is only asked for
+    /// with `screen-lock`
+    pub fn reason_at<Tz: TimeZone>(
+        &self,
+        now: &DateTime<Tz>,
+        presence: impl FnOnce() -> Presence,
+    ) -> Option<Reason> {
+        if !self.hours.is_working_time(now) {
+            return Some(Reason::OffHours);
+        }
+        if !self.hours.screen_lock {
+            return None;
+        }
+        let presence = presence();
+        if presence.screen_locked {
+            Some(Reason::ScreenLocked)
+        } else if presence.idle.is_some_and(|idle| idle >= self.idle_limit) {
+            Some(Reason::Idle)
+        } else {
+            None
+        }
+    }
+
+    /// Lock `control` down and lift it again as the policy says, checking
+    /// every minute
+    pub fn spawn(self, control: Arc<ProxyControl>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+            let mut current = None;
+            loop {
+                interval.tick().await;
+                let now = Local::now();
+                let presence = if self.hours.screen_lock && self.hours.is_working_time(&now) {
+                    tokio::task::spawn_blocking(Presence::detect)
+                        .await
+                        .unwrap_or_default()
+                } else {
+                    Presence::default()
+                };
+                let reason = self.reason_at(&now, || presence);
+                if reason == current {
+                    continue;
+                }
+                match reason {
+                    Some(reason) => {
+                        info!("🔒 Lockdown ({}): enforcing stricter rules", reason);
+                        control.lock_down(self.overrides.clone());
+                    }
+                    None => {
+                        info!("🔓 Lockdown lifted");
+                        control.lift_lockdown();
+                    }
+                }
+                current = reason;
+            }
+        });
+    }
+}
+
+/// The profile named by `OPENCLAW_HARNESS_LOCKDOWN_PROFILE`, else the
+/// built-in escalation
+fn lockdown_overrides() -> Vec<SeverityOverride> {
+    let escalate = vec![SeverityOverride {
+        from: Some(RiskLevel::Warning),
+        risk_level: Some(RiskLevel::Critical),
+        action: Some(RuleAction::CriticalAlert),
+        ..Default::default()
+    }];
+    let Some(name) = std::env::var(LOCKDOWN_PROFILE_ENV)
+        .ok()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+    else {
+        return escalate;
+    };
+    let found = profiles::profiles_path()
+        .and_then(|path| profiles::load_profiles(&path).ok())
+        .and_then(|mut profiles| profiles.remove(&name));
+    found.unwrap_or_else(|| {
+        warn!(
+            "⚠️ Lockdown profile '{}' not found in profiles.yaml, escalating warnings instead",
+            name
+        );
+        escalate
+    })
+}
+
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The logind session of the graphical login
+fn logind_session() -> Option<String> {
+    std::env::var("XDG_SESSION_ID")
+        .ok()
+        .filter(|id| !id.is_empty())
+        .or_else(|| {
+            let user = std::env::var("USER").ok()?;
+            command_output(
+                "loginctl",
+                &["show-user", &user, "-p", "Display", "--value"],
+            )
+            .filter(|id| !id.is_empty())
+        })
+}
+
+fn logind_property(session: &str, name: &str) -> Option<String> {
+    command_output(
+        "loginctl",
+        &["show-session", session, "-p", name, "--value"],
+    )
+}
+
+fn xprintidle() -> Option<Duration> {
+    let ms = command_output("xprintidle", &[])?.parse().ok()?;
+    Some(Duration::from_millis(ms))
+}
+
+fn macos_idle() -> Option<Duration> {
+    let out = command_output("ioreg", &["-c", "IOHIDSystem", "-d", "4"])?;
+    out.lines().find_map(|line| {
+        let (_, value) = line.split_once("\"HIDIdleTime\" = ")?;
+        Some(Duration::from_nanos(value.trim().parse().ok()?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_lockdown_outside_hours_and_when_away() {
+        // 2026-03-04 is a Wednesday, 2026-03-07 a Saturday
+        let at = |day: u32, hour: u32| Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap();
+        let policy = LockdownPolicy {
+            hours: WorkingHours::parse("09:00-18:00, weekdays").unwrap(),
+            overrides: vec![],
+            idle_limit: Duration::from_secs(15 * 60),
+        };
+        let present = Presence::default;
+        assert_eq!(policy.reason_at(&at(4, 12), present), None);
+        assert_eq!(
+            policy.reason_at(&at(4, 22), present),
+            Some(Reason::OffHours)
+        );
+        assert_eq!(
+            policy.reason_at(&at(7, 12), present),
+            Some(Reason::OffHours)
+        );
+        // The screen is only checked with screen-lock
+        let locked = || Presence {
+            screen_locked: true,
+            idle: None,
+        };
+        assert_eq!(policy.reason_at(&at(4, 12), locked), None);
+
+        let watched = LockdownPolicy {
+            hours: WorkingHours::parse("screen-lock").unwrap(),
+            ..policy
+        };
+        assert_eq!(
+            watched.reason_at(&at(7, 3), locked),
+            Some(Reason::ScreenLocked)
+        );
+        let idle = |minutes: u64| {
+            move || Presence {
+                screen_locked: false,
+                idle: Some(Duration::from_secs(minutes * 60)),
+            }
+        };
+        assert_eq!(watched.reason_at(&at(4, 12), idle(5)), None);
+        assert_eq!(watched.reason_at(&at(4, 12), idle(20)), Some(Reason::Idle));
+
+        assert!(WorkingHours::parse("22:00-06:00")
+            .unwrap()
+            .is_working_time(&at(4, 2)));
+        assert!(WorkingHours::parse("weekends").is_err());
+        assert!(WorkingHours::parse("").is_err());
+    }
+}
@@ -3,6 +3,12 @@
 //! Handles the actual response to risky actions.
 
 pub mod alerter;
+pub mod backup;
+pub mod confirm;
+pub mod desktop;
+pub mod lockdown;
+pub mod schedule;
+pub mod secrets;
 
 use super::{AlertConfig, AnalysisResult, Recommendation};
 use tracing::{info, warn};
@@ -0,0 +1,214 @@
+//! Quiet hours for alert channels
+//!
+//! A channel can be told to stay quiet at night and/or at weekends, or be
+//! paused for a while (Slack `/harness pause`). Alerts that arrive during
+//! quiet time are held and delivered as one digest once
+//! it ends; Critical alerts still go out immediately unless the schedule
+//! says `hold-critical`.
+//!
+//! Schedules are written as comma-separated tokens, local time:
+//!
+//! - `22:00-07:00` — quiet between those times (may wrap past midnight)
+//! - `weekends` — quiet all Saturday and Sunday
+//! - `hold-critical` — hold Critical alerts too
+//!
+//! and come from `alerts.quiet_hours.<channel>` in the config,
+//! `OPENCLAW_HARNESS_QUIET_HOURS_<CHANNEL>` or, for every channel,
+//! `OPENCLAW_HARNESS_QUIET_HOURS`.
+
+use crate::{AlertConfig, RiskLevel};
+use chrono::{DateTime, Datelike, Local, NaiveTime, TimeZone, Utc, Weekday};
+use std::sync::Mutex;
+use tracing::warn;
+
+/// Environment variable with the schedule for all channels
+pub const QUIET_HOURS_ENV: &str = "OPENCLAW_HARNESS_QUIET_HOURS";
+
+/// Most alerts listed in one digest; the rest are counted
+const DIGEST_MAX_LINES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertSchedule {
+    /// Quiet from the first time until the second
+    window: Option<(NaiveTime, NaiveTime)>,
+    weekends: bool,
+    hold_critical: bool,
+}
+
+impl AlertSchedule {
+    pub fn parse(spec: &str) -> anyhow::Result<Self> {
+        let mut schedule = Self {
+            window: None,
+            weekends: false,
+            hold_critical: false,
+        };
+        for token in spec.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            match token {
+                "weekends" => schedule.weekends = true,
+                "hold-critical" => schedule.hold_critical = true,
+                range => {
+                    let (start, end) = range.split_once('-').ok_or_else(|| {
+                        anyhow::anyhow!("Unknown quiet hours setting '{}'", token)
+                    })?;
+                    let time = |t: &str| NaiveTime::parse_from_str(t.trim(), "%H:%M");
+                    schedule.window = Some((time(start)?, time(end)?));
+                }
+            }
+        }
+        if schedule.window.is_none() && !schedule.weekends {
+            anyhow::bail!("Quiet hours '{}' never apply", spec);
+        }
+        Ok(schedule)
+    }
+
+    /// Schedule for `channel` (telegram, slack, discord, desktop), if any
+    pub fn for_channel(config: &AlertConfig, channel: &str) -> Option<Self> {
+        let spec = config.quiet_hours.get(channel).cloned().or_else(|| {
+            std::env::var(format!("{}_{}", QUIET_HOURS_ENV, channel.to_uppercase()))
+                .or_else(|_| std::env::var(QUIET_HOURS_ENV))
+                .ok()
+        })?;
+        match Self::parse(&spec) {
+            Ok(schedule) => Some(schedule),
+            Err(e) => {
+                warn!("Ignoring quiet hours for {}: {}", channel, e);
+                None
+            }
+        }
+    }
+
+    pub fn is_quiet<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> bool {
+        if self.weekends && matches!(now.weekday(), Weekday::Sat | Weekday::Sun) {
+            return true;
+        }
+        let Some((start, end)) = self.window else {
+            return false;
+        };
+        let time = now.time();
+        if start <= end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Holds a channel's alerts during its quiet hours, or while paused
+pub struct QuietGate {
+    schedule: Option<AlertSchedule>,
+    paused_until: Mutex<Option<DateTime<Utc>>>,
+    held: Mutex<Vec<String>>,
+}
+
+impl QuietGate {
+    pub fn new(schedule: Option<AlertSchedule>) -> Self {
+        Self {
+            schedule,
+            paused_until: Mutex::new(None),
+            held: Mutex::new(vec![]),
+        }
+    }
+
+    /// Treat everything until `until` as quiet time
+    pub fn pause(&self, until: Option<DateTime<Utc>>) {
+        if let Ok(mut paused) = self.paused_until.lock() {
+            *paused = until;
+        }
+    }
+
+    pub fn paused_until(&self) -> Option<DateTime<Utc>> {
+        let paused = (*self.paused_until.lock().ok()?)?;
+        (paused > Utc::now()).then_some(paused)
+    }
+
+    fn is_quiet<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> bool {
+        let paused = self
+            .paused_until
+            .lock()
+            .ok()
+            .and_then(|p| *p)
+            .is_some_and(|until| now.with_timezone(&Utc) < until);
+        paused || self.schedule.as_ref().is_some_and(|s| s.is_quiet(now))
+    }
+
+    pub fn for_channel(config: &AlertConfig, channel: &str) -> Self {
+        Self::new(AlertSchedule::for_channel(config, channel))
+    }
+
+    /// Whether an alert should be sent now; if not, `summary` is kept for the digest
+    pub fn admit(&self, risk: RiskLevel, summary: &str) -> bool {
+        self.admit_at(risk, summary, &Local::now())
+    }
+
+    fn admit_at<Tz: TimeZone>(&self, risk: RiskLevel, summary: &str, now: &DateTime<Tz>) -> bool {
+        let hold_critical = self.schedule.as_ref().is_some_and(|s| s.hold_critical);
+        if !self.is_quiet(now) || (risk == RiskLevel::Critical && !hold_critical) {
+            return true;
+        }
+        if let Ok(mut held) = self.held.lock() {
+            held.push(format!("[{}] {}", risk, summary));
+        }
+        false
+    }
+
+    /// Digest of the held alerts, once quiet hours are over
+    pub fn digest(&self) -> Option<String> {
+        self.digest_at(&Local::now())
+    }
+
+    fn digest_at<Tz: TimeZone>(&self, now: &DateTime<Tz>) -> Option<String> {
+        if self.is_quiet(now) {
+            return None;
+        }
+        let held = std::mem::take(&mut *self.held.lock().ok()?);
+        if held.is_empty() {
+            return None;
+        }
+        let mut digest = format!("🌙 {} alert(s) held during quiet hours:\n", held.len());
+        for line in held.iter().take(DIGEST_MAX_LINES) {
+            digest.push_str(&format!("• {}\n", line));
+        }
+        if held.len() > DIGEST_MAX_LINES {
+            digest.push_str(&format!("… and {} more\n", held.len() - DIGEST_MAX_LINES));
+        }
+        Some(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quiet_hours_hold_and_digest() {
+        let schedule = AlertSchedule::parse("22:00-07:00, weekends").unwrap();
+        // 2026-03-04 is a Wednesday, 2026-03-07 a Saturday
+        let at = |day: u32, hour: u32| Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap();
+        assert!(schedule.is_quiet(&at(4, 23)));
+        assert!(schedule.is_quiet(&at(4, 3)));
+        assert!(!schedule.is_quiet(&at(4, 12)));
+        assert!(schedule.is_quiet(&at(7, 12)));
+        assert!(AlertSchedule::parse("hold-critical").is_err());
+        assert!(AlertSchedule::parse("late").is_err());
+
+        let gate = QuietGate::new(Some(schedule));
+        assert!(!gate.admit_at(RiskLevel::Warning, "sudo ls", &at(4, 23)));
+        assert!(gate.admit_at(RiskLevel::Critical, "rm -rf /", &at(4, 23)));
+        assert!(gate.digest_at(&at(4, 23)).is_none());
+
+        let digest = gate.digest_at(&at(4, 8)).unwrap();
+        assert!(digest.starts_with("🌙 1 alert(s)"));
+        assert!(digest.contains("sudo ls"));
+        assert!(gate.digest_at(&at(4, 9)).is_none());
+
+        let held = QuietGate::new(Some(
+            AlertSchedule::parse("weekends,hold-critical").unwrap(),
+        ));
+        assert!(!held.admit_at(RiskLevel::Critical, "rm -rf /", &at(7, 12)));
+        let paused = QuietGate::new(None);
+        assert!(paused.admit_at(RiskLevel::Info, "ls", &at(7, 12)));
+        paused.pause(Some(at(7, 13)));
+        assert!(!paused.admit_at(RiskLevel::Info, "ls", &at(7, 12)));
+        assert!(paused.digest_at(&at(7, 14)).is_some());
+    }
+}
@@ -0,0 +1,441 @@
+//! Alert credentials in the OS keyring
+//!
+//! The Telegram bot token and the Slack and Discord webhook URLs are
+//! enough to send (or suppress) alerts in the user's name. When an OS
+//! keyring is available (macOS Keychain, Linux Secret Service, Windows
+//! Credential Manager) they are kept there under the service
+//! `openclaw-harness`, and the alert config file is written without them.
+//! Credentials found in the file or the environment are moved into the
+//! keyring on first use, or with `openclaw-harness alerts migrate`. The
+//! SMTP URL of the weekly report email, which carries a password, is kept
+//! the same way.
+//!
+//! Without a keyring (built without the `keyring` feature, no Secret
+//! Service on a headless machine, or `OPENCLAW_HARNESS_KEYRING=0`) they
+//! stay in the file and the environment as before.
+//!
+//! The file itself lives in `~/.openclaw-harness/`, outside the workspace
+//! the agent works in, readable by the owner only. Every write by the
+//! harness records an HMAC-SHA256 of it next to it (`alerts.json.sha256`),
+//! so a file changed behind the harness's back (say, an agent pointing
+//! alerts at its own chat) is noticed before alerts are sent. The HMAC key
+//! is kept in the keyring (`seal_key`), where an agent able to edit the
+//! file can't read it to forge a digest; without a keyring it is in
+//! `~/.openclaw-harness/seal.key`, which only guards against careless edits.
+//! Once files have been sealed, a digest that disappears counts as
+//! tampering too.
+
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::info;
+
+/// Keyring service the credentials are stored under
+pub const SERVICE: &str = "openclaw-harness";
+/// Set to `0` to keep credentials out of the OS keyring
+pub const KEYRING_ENV: &str = "OPENCLAW_HARNESS_KEYRING";
+
+/// Keyring entries
+pub const TELEGRAM_BOT_TOKEN: &str = "telegram_bot_token";
+pub const SLACK_WEBHOOK: &str = "slack_webhook";
+pub const DISCORD_WEBHOOK: &str = "discord_webhook";
+/// SMTP server for the weekly report email (see `web::report_email`)
+pub const SMTP_URL: &str = "smtp_url";
+/// Key of the digests sealing the alert config (see `seal`)
+pub const SEAL_KEY: &str = "seal_key";
+
+/// Environment variables a credential may be set in, in order
+pub fn env_vars(key: &str) -> &'static [&'static str] {
+    match key {
+        TELEGRAM_BOT_TOKEN => &[
+            "OPENCLAW_HARNESS_TELEGRAM_BOT_TOKEN",
+            "SAFEBOT_TELEGRAM_BOT_TOKEN",
+        ],
+        SMTP_URL => &["OPENCLAW_HARNESS_SMTP_URL"],
+        _ => &[],
+    }
+}
+
+/// Somewhere credentials can be kept
+pub trait SecretStore: Send + Sync {
+    /// For messages, e.g. "OS keyring"
+    fn name(&self) -> &'static str;
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>>;
+    fn set(&self, key: &str, value: &str) -> anyhow::Result<()>;
+    /// Remove `key`; not an error if it isn't there
+    fn delete(&self, key: &str) -> anyhow::Result<()>;
+}
+
+/// The platform keyring, through the `keyring` crate
+#[cfg(feature = "keyring")]
+pub struct OsKeyring;
+
+#[cfg(feature = "keyring")]
+impl SecretStore for OsKeyring {
+    fn name(&self) -> &'static str {
+        "OS keyring"
+    }
+
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        match keyring::Entry::new(SERVICE, key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        Ok(keyring::Entry::new(SERVICE, key)?.set_password(value)?)
+    }
+
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        match keyring::Entry::new(SERVICE, key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Credentials held in memory only, for tests
+#[derive(Default)]
+pub struct MemoryStore(Mutex<HashMap<String, String>>);
+
+impl SecretStore for MemoryStore {
+    fn name(&self) -> &'static str {
+        "memory"
+    }
+
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    fn set(&self, key: &str, value: &str) -> anyhow::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn delete(&self, key: &str) -> anyhow::Result<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+/// Whether `OPENCLAW_HARNESS_KEYRING` turns the keyring off
+fn disabled_by_env() -> bool {
+    std::env::var(KEYRING_ENV)
+        .map(|v| matches!(v.trim(), "0" | "false" | "no" | "off"))
+        .unwrap_or(false)
+}
+
+/// The OS keyring, if this build has one, it isn't turned off and it
+/// answers
+pub fn keyring() -> Option<&'static dyn SecretStore> {
+    if disabled_by_env() {
+        return None;
+    }
+    os_keyring()
+}
+
+/// Checked once per process: a machine without a Secret Service stays
+/// without one
+#[cfg(feature = "keyring")]
+fn os_keyring() -> Option<&'static dyn SecretStore> {
+    static AVAILABLE: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    let available = *AVAILABLE.get_or_init(|| match OsKeyring.get("probe") {
+        Ok(_) => true,
+        Err(e) => {
+            tracing::warn!(
+                "⚠️ OS keyring unavailable, alert credentials stay in the alert config file: {}",
+                e
+            );
+            false
+        }
+    });
+    available.then_some(&OsKeyring as &dyn SecretStore)
+}
+
+#[cfg(not(feature = "keyring"))]
+fn os_keyring() -> Option<&'static dyn SecretStore> {
+    None
+}
+
+/// The alert config, in the harness's own directory
+pub fn alerts_path() -> PathBuf {
+    crate::harness_path("alerts.json")
+}
+
+/// Where the alert config used to be, inside the workspace
+pub const LEGACY_ALERTS_FILE: &str = "config/alerts.json";
+
+/// Write `contents` to `path` so only the owner can read it
+pub fn write_private(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, contents)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    Ok(())
+}
+
+/// How a file compares with the digest recorded when the harness wrote it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seal {
+    Intact,
+    /// Nothing sealed yet (a file from before sealing, or no file)
+    Missing,
+    /// Changed, or removed, since the harness wrote it, or its digest was
+    /// removed
+    Broken,
+}
+
+fn seal_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// HMAC-SHA256 (RFC 2104)
+fn hmac(key: &[u8], data: &[u8]) -> [u8; 32] {
+    const BLOCK: usize = 64;
+    let mut block = [0u8; BLOCK];
+    if key.len() > BLOCK {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let pad = |byte: u8| block.map(|b| b ^ byte);
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(data)
+        .finalize();
+    Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize()
+        .into()
+}
+
+fn digest(path: &Path, key: &[u8]) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    Some(
+        hmac(key, &data)
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect(),
+    )
+}
+
+/// Where the seal key is kept without a keyring
+#[cfg(not(test))]
+fn seal_key_file() -> PathBuf {
+    crate::harness_path("seal.key")
+}
+
+/// The key files are sealed with, if any was made; with `create`, one is
+/// made when there is none. Held in the keyring when there is one.
+#[cfg(not(test))]
+fn seal_key(create: bool) -> anyhow::Result<Option<Vec<u8>>> {
+    let store = keyring();
+    let stored = match store {
+        Some(store) => store.get(SEAL_KEY)?,
+        None => std::fs::read_to_string(seal_key_file())
+            .ok()
+            .filter(|k| !k.trim().is_empty()),
+    };
+    if let Some(key) = stored {
+        return Ok(Some(key.trim().as_bytes().to_vec()));
+    }
+    if !create {
+        return Ok(None);
+    }
+    let key = format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    );
+    match store {
+        Some(store) => store.set(SEAL_KEY, &key)?,
+        None => write_private(&seal_key_file(), key.as_bytes())?,
+    }
+    info!("🔏 Created the key alert config digests are made with");
+    Ok(Some(key.into_bytes()))
+}
+
+/// Tests seal with a fixed key, leaving the keyring and home alone
+#[cfg(test)]
+fn seal_key(_create: bool) -> anyhow::Result<Option<Vec<u8>>> {
+    Ok(Some(b"test seal key".to_vec()))
+}
+
+/// Record the digest of `path` as it is now
+pub fn seal(path: &Path) -> anyhow::Result<()> {
+    let key = seal_key(true)?.ok_or_else(|| anyhow::anyhow!("no seal key"))?;
+    seal_with(path, &key)
+}
+
+fn seal_with(path: &Path, key: &[u8]) -> anyhow::Result<()> {
+    let digest =
+        digest(path, key).ok_or_else(|| anyhow::anyhow!("cannot read {}", path.display()))?;
+    write_private(&seal_path(path), digest.as_bytes())
+}
+
+pub fn check_seal(path: &Path) -> Seal {
+    match seal_key(false) {
+        Ok(key) => check_seal_with(path, key.as_deref()),
+        Err(e) => {
+            tracing::warn!("⚠️ Cannot read the seal key: {}", e);
+            Seal::Broken
+        }
+    }
+}
+
+/// `key` is `None` when nothing was ever sealed
+fn check_seal_with(path: &Path, key: Option<&[u8]>) -> Seal {
+    let Some(key) = key else {
+        return Seal::Missing;
+    };
+    let Ok(recorded) = std::fs::read_to_string(seal_path(path)) else {
+        // Files are sealed as they are written, so a sealed file's digest
+        // can only be missing if someone removed it
+        return if path.exists() {
+            Seal::Broken
+        } else {
+            Seal::Missing
+        };
+    };
+    match digest(path, key) {
+        Some(digest) if digest == recorded.trim() => Seal::Intact,
+        _ => Seal::Broken,
+    }
+}
+
+/// Whether `value` is a masked credential as shown by the API, which
+/// means "keep the stored one"
+pub fn is_masked(value: &str) -> bool {
+    value.contains("****")
+}
+
+/// A credential from the environment, else from `store`
+pub fn lookup(store: Option<&dyn SecretStore>, key: &str) -> Option<String> {
+    env_vars(key)
+        .iter()
+        .find_map(|var| std::env::var(var).ok())
+        .or_else(|| store.and_then(|s| s.get(key).ok().flatten()))
+        .filter(|v| !v.is_empty())
+}
+
+/// Copy credentials set in the environment into `store`; returns the
+/// variables copied, which can then be unset
+pub fn migrate_env(store: &dyn SecretStore) -> anyhow::Result<Vec<&'static str>> {
+    let mut migrated = vec![];
+    for key in [TELEGRAM_BOT_TOKEN, SLACK_WEBHOOK, DISCORD_WEBHOOK, SMTP_URL] {
+        let Some((var, value)) = env_vars(key)
+            .iter()
+            .find_map(|var| std::env::var(var).ok().map(|v| (*var, v)))
+            .filter(|(_, v)| !v.is_empty())
+        else {
+            continue;
+        };
+        if store.get(key)?.as_deref() != Some(value.as_str()) {
+            store.set(key, &value)?;
+            info!("🔐 Copied {} into the {}", var, store.name());
+        }
+        migrated.push(var);
+    }
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memory_store_and_masking() {
+        let store = MemoryStore::default();
+        assert_eq!(store.get(SLACK_WEBHOOK).unwrap(), None);
+        store
+            .set(SLACK_WEBHOOK, "https://hooks.slack.com/x")
+            .unwrap();
+        assert_eq!(
+            lookup(Some(&store), SLACK_WEBHOOK).as_deref(),
+            Some("https://hooks.slack.com/x")
+        );
+        store.delete(SLACK_WEBHOOK).unwrap();
+        store.delete(SLACK_WEBHOOK).unwrap();
+        assert_eq!(lookup(Some(&store), SLACK_WEBHOOK), None);
+
+        assert!(is_masked("1234****abcd"));
+        assert!(!is_masked("1234:abcd"));
+    }
+
+    #[test]
+    fn test_seal_detects_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.json");
+        assert_eq!(check_seal(&path), Seal::Missing);
+
+        write_private(&path, br#"{"telegram_chat_id": "42"}"#).unwrap();
+        // A file from before anything was sealed
+        assert_eq!(check_seal_with(&path, None), Seal::Missing);
+        seal(&path).unwrap();
+        assert_eq!(check_seal(&path), Seal::Intact);
+        // A digest made without the key doesn't pass
+        assert_eq!(check_seal_with(&path, Some(b"other key")), Seal::Broken);
+        let plain = format!("{:x}", Sha256::digest(std::fs::read(&path).unwrap()));
+        std::fs::write(seal_path(&path), plain).unwrap();
+        assert_eq!(check_seal(&path), Seal::Broken);
+        seal(&path).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        std::fs::write(&path, br#"{"telegram_chat_id": "666"}"#).unwrap();
+        assert_eq!(check_seal(&path), Seal::Broken);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(check_seal(&path), Seal::Broken);
+    }
+
+    #[test]
+    fn test_removed_digest_breaks_seal() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("alerts.json");
+        write_private(&path, br#"{"telegram_chat_id": "42"}"#).unwrap();
+        seal(&path).unwrap();
+
+        // Deleting the digest and then editing the file must not get the
+        // edit sealed
+        std::fs::remove_file(seal_path(&path)).unwrap();
+        std::fs::write(&path, br#"{"telegram_chat_id": "666"}"#).unwrap();
+        assert_eq!(check_seal(&path), Seal::Broken);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(check_seal(&path), Seal::Missing);
+    }
+
+    #[test]
+    fn test_hmac_matches_rfc_4231() {
+        let hex =
+            |bytes: [u8; 32]| -> String { bytes.iter().map(|b| format!("{:02x}", b)).collect() };
+        assert_eq!(
+            hex(hmac(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hex(hmac(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+}
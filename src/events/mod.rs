@@ -0,0 +1,305 @@
+//! In-process event bus
+//!
+//! Producers (collectors via the daemon loop, the analyzer, the patch
+//! monitor) publish typed events; consumers (DB writer, alerter, WebSocket
+//! bridge, exporters) subscribe by name. Events are journaled in the
+//! database before being broadcast, and each named consumer's offset is
+//! persisted on `ack`, so a consumer that lags behind or restarts catches up
+//! from the journal instead of losing events.
+
+use crate::db::Database;
+use crate::{AgentAction, AnalysisResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// Live channel capacity; slower consumers fall back to the journal
+const CHANNEL_CAPACITY: usize = 1024;
+/// Journal rows fetched per replay batch
+const REPLAY_BATCH: usize = 256;
+/// How long the daemon keeps delivered events, so SSE clients can resume
+/// from their last `seq`
+pub const RESUME_WINDOW_MINUTES: i64 = 10;
+
+/// Everything that flows through the bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "data", rename_all = "snake_case")]
+pub enum Event {
+    /// An agent action was observed (after correlation)
+    Action(AgentAction),
+    /// An action was analyzed against the rules
+    Analysis(AnalysisResult),
+}
+
+impl Event {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Event::Action(_) => "action",
+            Event::Analysis(_) => "analysis",
+        }
+    }
+}
+
+/// An event with its position in the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    pub seq: u64,
+    pub published_at: DateTime<Utc>,
+    pub event: Event,
+}
+
+/// Typed publish/subscribe bus with an optional persistent journal
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<Arc<Envelope>>,
+    journal: Option<Arc<Mutex<Database>>>,
+    /// Sequence counter when running without a journal
+    next_seq: Arc<AtomicU64>,
+}
+
+impl EventBus {
+    /// Bus journaling to `db`; offsets survive restarts
+    pub fn with_journal(db: Database) -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            journal: Some(Arc::new(Mutex::new(db))),
+            next_seq: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Bus without persistence (live delivery only)
+    pub fn in_memory() -> Self {
+        let (tx, _) = broadcast::channel(CHANNEL_CAPACITY);
+        Self {
+            tx,
+            journal: None,
+            next_seq: Arc::new(AtomicU64::new(1)),
+        }
+    }
+
+    /// Publish an event, returning its sequence number
+    pub fn publish(&self, event: Event) -> u64 {
+        let published_at = Utc::now();
+        let seq = match self.append(&event, published_at) {
+            Some(seq) => seq,
+            None => self.next_seq.fetch_add(1, Ordering::SeqCst),
+        };
+        let _ = self.tx.send(Arc::new(Envelope {
+            seq,
+            published_at,
+            event,
+        }));
+        seq
+    }
+
+    fn append(&self, event: &Event, at: DateTime<Utc>) -> Option<u64> {
+        let journal = self.journal.as_ref()?;
+        let payload = match serde_json::to_string(event) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to serialize {} event: {}", event.kind(), e);
+                return None;
+            }
+        };
+        let db = journal.lock().ok()?;
+        match db.append_event(event.kind(), &payload, at) {
+            Ok(seq) => Some(seq),
+            Err(e) => {
+                warn!("Failed to journal {} event: {}", event.kind(), e);
+                None
+            }
+        }
+    }
+
+    /// Subscribe as `consumer`, resuming after its last acknowledged event.
+    /// A consumer seen for the first time starts with new events only.
+    pub fn subscribe(&self, consumer: &str) -> Subscriber {
+        // Subscribe to the live channel before reading the journal so no
+        // event can slip between the two
+        let rx = self.tx.subscribe();
+        let offset = self.journal.as_ref().and_then(|j| {
+            let db = j.lock().ok()?;
+            match db.get_consumer_offset(consumer).ok()? {
+                Some(seq) => Some(seq),
+                None => db.last_event_seq().ok(),
+            }
+        });
+        Subscriber {
+            name: consumer.to_string(),
+            rx,
+            journal: self.journal.clone(),
+            offset: offset.unwrap_or(0),
+            replaying: offset.is_some(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Drop journaled events every consumer has acknowledged
+    pub fn compact(&self) -> usize {
+        self.compact_keeping(chrono::Duration::zero())
+    }
+
+    /// `compact`, except for events published in the last `window`
+    pub fn compact_keeping(&self, window: chrono::Duration) -> usize {
+        let Some(journal) = &self.journal else {
+            return 0;
+        };
+        let Ok(db) = journal.lock() else { return 0 };
+        match db.min_consumer_offset() {
+            Ok(Some(min)) => db.prune_events(min, Utc::now() - window).unwrap_or(0),
+            _ => 0,
+        }
+    }
+}
+
+/// A named consumer's view of the bus
+pub struct Subscriber {
+    name: String,
+    rx: broadcast::Receiver<Arc<Envelope>>,
+    journal: Option<Arc<Mutex<Database>>>,
+    /// Last event handed out
+    offset: u64,
+    /// Reading from the journal rather than the live channel
+    replaying: bool,
+    pending: VecDeque<Envelope>,
+}
+
+impl Subscriber {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Next event, or `None` once the bus is gone
+    pub async fn recv(&mut self) -> Option<Envelope> {
+        loop {
+            if let Some(env) = self.pending.pop_front() {
+                self.offset = env.seq;
+                return Some(env);
+            }
+
+            if self.replaying {
+                self.pending = self.read_journal();
+                if self.pending.is_empty() {
+                    self.replaying = false;
+                }
+                continue;
+            }
+
+            match self.rx.recv().await {
+                // Already delivered from the journal
+                Ok(env) if env.seq <= self.offset => continue,
+                Ok(env) => {
+                    self.offset = env.seq;
+                    return Some((*env).clone());
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    if self.journal.is_some() {
+                        self.replaying = true;
+                    } else {
+                        warn!("Consumer {} missed {} events", self.name, n);
+                    }
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn read_journal(&self) -> VecDeque<Envelope> {
+        let Some(journal) = &self.journal else {
+            return VecDeque::new();
+        };
+        let Ok(db) = journal.lock() else {
+            return VecDeque::new();
+        };
+        match db.events_after(self.offset, REPLAY_BATCH) {
+            Ok(rows) => rows
+                .into_iter()
+                .filter_map(|(seq, published_at, payload)| {
+                    let event = serde_json::from_str(&payload).ok()?;
+                    Some(Envelope {
+                        seq,
+                        published_at,
+                        event,
+                    })
+                })
+                .collect(),
+            Err(e) => {
+                warn!("Failed to replay events for {}: {}", self.name, e);
+                VecDeque::new()
+            }
+        }
+    }
+
+    /// Persist that everything up to the last received event is handled
+    pub fn ack(&self) {
+        let Some(journal) = &self.journal else { return };
+        let Ok(db) = journal.lock() else { return };
+        if let Err(e) = db.set_consumer_offset(&self.name, self.offset) {
+            warn!("Failed to save offset for {}: {}", self.name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentType};
+
+    fn action(id: &str) -> Event {
+        Event::Action(AgentAction {
+            id: id.to_string(),
+            timestamp: Utc::now(),
+            agent: AgentType::OpenClaw,
+            action_type: ActionType::Exec,
+            content: "ls".to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        })
+    }
+
+    fn id_of(env: &Envelope) -> &str {
+        match &env.event {
+            Event::Action(a) => &a.id,
+            Event::Analysis(r) => &r.action.id,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_consumer_resumes_from_offset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.db");
+        let bus = EventBus::with_journal(Database::open(&path).unwrap());
+
+        let mut sub = bus.subscribe("writer");
+        bus.publish(action("a1"));
+        bus.publish(action("a2"));
+
+        assert_eq!(id_of(&sub.recv().await.unwrap()), "a1");
+        sub.ack();
+        drop(sub);
+
+        // Published while the consumer was away
+        bus.publish(action("a3"));
+
+        // A restarted bus (same journal) resumes after the acked event
+        let bus = EventBus::with_journal(Database::open(&path).unwrap());
+        let mut sub = bus.subscribe("writer");
+        assert_eq!(id_of(&sub.recv().await.unwrap()), "a2");
+        assert_eq!(id_of(&sub.recv().await.unwrap()), "a3");
+
+        bus.publish(action("a4"));
+        assert_eq!(id_of(&sub.recv().await.unwrap()), "a4");
+        sub.ack();
+
+        // Kept for resuming clients while recent
+        assert_eq!(bus.compact_keeping(chrono::Duration::minutes(10)), 0);
+        // Only the acked consumer exists, so everything is compactable
+        assert_eq!(bus.compact(), 4);
+    }
+}
@@ -0,0 +1,218 @@
+//! Export stored actions and their analysis for offline analysis
+//!
+//! Each exported row is one action joined with its latest analysis result.
+//! Rows are produced one at a time from the database so large exports (CLI
+//! or the `/api/export` endpoint) stream instead of being buffered.
+
+use crate::db::Database;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Output format of an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Jsonl,
+    Csv,
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_lowercase().as_str() {
+            "jsonl" | "ndjson" | "json" => Ok(Self::Jsonl),
+            "csv" => Ok(Self::Csv),
+            other => anyhow::bail!("Unknown export format '{}' (jsonl, csv)", other),
+        }
+    }
+}
+
+impl ExportFormat {
+    /// Guess the format from an output file name
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        path.extension()?.to_str()?.parse().ok()
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Jsonl => "application/x-ndjson",
+            Self::Csv => "text/csv",
+        }
+    }
+}
+
+/// One action with its latest analysis
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportRow {
+    pub id: String,
+    pub timestamp: String,
+    pub agent: String,
+    pub action_type: String,
+    pub content: String,
+    pub target: Option<String>,
+    pub session_id: Option<String>,
+    pub metadata: Option<Value>,
+    pub risk_level: Option<String>,
+    pub matched_rules: Vec<String>,
+    pub recommendation: Option<String>,
+    pub explanation: Option<String>,
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "id",
+    "timestamp",
+    "agent",
+    "action_type",
+    "content",
+    "target",
+    "session_id",
+    "metadata",
+    "risk_level",
+    "matched_rules",
+    "recommendation",
+    "explanation",
+];
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+impl ExportRow {
+    /// Encode as one line (including the trailing newline)
+    pub fn encode(&self, format: ExportFormat) -> anyhow::Result<String> {
+        match format {
+            ExportFormat::Jsonl => Ok(format!("{}\n", serde_json::to_string(self)?)),
+            ExportFormat::Csv => {
+                let metadata = self.metadata.as_ref().map(|m| m.to_string());
+                let rules = self.matched_rules.join(";");
+                let fields = [
+                    Some(self.id.as_str()),
+                    Some(self.timestamp.as_str()),
+                    Some(self.agent.as_str()),
+                    Some(self.action_type.as_str()),
+                    Some(self.content.as_str()),
+                    self.target.as_deref(),
+                    self.session_id.as_deref(),
+                    metadata.as_deref(),
+                    self.risk_level.as_deref(),
+                    Some(rules.as_str()),
+                    self.recommendation.as_deref(),
+                    self.explanation.as_deref(),
+                ];
+                let line: Vec<String> = fields
+                    .iter()
+                    .map(|f| csv_field(f.unwrap_or_default()))
+                    .collect();
+                Ok(format!("{}\n", line.join(",")))
+            }
+        }
+    }
+}
+
+/// Header written before the first row, if the format has one
+pub fn header(format: ExportFormat) -> Option<String> {
+    (format == ExportFormat::Csv).then(|| format!("{}\n", CSV_COLUMNS.join(",")))
+}
+
+/// Parse `--since`: a relative age (`30d`, `12h`, `45m`, `2w`) or a date/RFC 3339 time
+pub fn parse_since(s: &str) -> anyhow::Result<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    let (num, unit) = s.split_at(s.len().saturating_sub(1));
+    let n: i64 = num
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid --since value '{}'", s))?;
+    let age = match unit {
+        "m" => Duration::minutes(n),
+        "h" => Duration::hours(n),
+        "d" => Duration::days(n),
+        "w" => Duration::weeks(n),
+        _ => anyhow::bail!("Invalid --since unit in '{}' (use m, h, d or w)", s),
+    };
+    Ok(Utc::now() - age)
+}
+
+/// Write all rows since `since` to `out`, returning the row count
+pub fn export(
+    db: &Database,
+    since: Option<DateTime<Utc>>,
+    format: ExportFormat,
+    out: &mut impl Write,
+) -> anyhow::Result<usize> {
+    if let Some(h) = header(format) {
+        out.write_all(h.as_bytes())?;
+    }
+    db.for_each_export_row(since, |row| {
+        out.write_all(row.encode(format)?.as_bytes())?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentAction, AgentType, AnalysisResult, Recommendation, RiskLevel};
+
+    #[test]
+    fn test_export_csv_and_jsonl() {
+        let db = Database::open_in_memory().unwrap();
+        let action = AgentAction {
+            id: "a1".to_string(),
+            timestamp: Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "echo \"hi\", there".to_string(),
+            target: None,
+            session_id: Some("s1".to_string()),
+            metadata: None,
+        };
+        db.store_action(&action).unwrap();
+        db.store_analysis(&AnalysisResult {
+            action: action.clone(),
+            matched_rules: vec!["r1".to_string(), "r2".to_string()],
+            risk_level: RiskLevel::Warning,
+            recommendation: Recommendation::Alert,
+            explanation: "x".to_string(),
+        })
+        .unwrap();
+
+        let mut csv = Vec::new();
+        assert_eq!(export(&db, None, ExportFormat::Csv, &mut csv).unwrap(), 1);
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert!(lines.next().unwrap().starts_with("id,timestamp,agent"));
+        let row = lines.next().unwrap();
+        assert!(row.contains("\"echo \"\"hi\"\", there\""));
+        assert!(row.contains("r1;r2"));
+
+        let mut jsonl = Vec::new();
+        let since = parse_since("1d").unwrap();
+        export(&db, Some(since), ExportFormat::Jsonl, &mut jsonl).unwrap();
+        let row: Value = serde_json::from_slice(&jsonl).unwrap();
+        assert_eq!(row["risk_level"], "warning");
+        assert_eq!(row["matched_rules"][1], "r2");
+
+        // Nothing newer than the future
+        let mut none = Vec::new();
+        let future = Utc::now() + Duration::hours(1);
+        assert_eq!(
+            export(&db, Some(future), ExportFormat::Jsonl, &mut none).unwrap(),
+            0
+        );
+
+        assert!("parquet".parse::<ExportFormat>().is_err());
+        assert!(parse_since("3x").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+//! Action IDs
+//!
+//! Every action id begins with the source that observed it, so two sources
+//! can never produce the same id and `correlation` can tell them apart:
+//!
+//! - `collector-<agent>-<id>`: read from an agent's logs. `<id>` is the
+//!   agent's own tool call id when the log has one, so reading the same
+//!   line twice gives the same id.
+//! - `proxy-<ULID>`: intercepted by the API proxy
+//! - `hook-<ULID>`: reported by an agent hook
+//! - `harness-<ULID>`: raised by the daemon itself (stale collectors,
+//!   tampering)
+//!
+//! ULIDs sort by creation time, so generated ids list in order.
+
+use crate::AgentType;
+
+/// What observed an action
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Collector,
+    Proxy,
+    Hook,
+    Harness,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Source::Collector => "collector",
+            Source::Proxy => "proxy",
+            Source::Hook => "hook",
+            Source::Harness => "harness",
+        }
+    }
+
+    /// Source of an action id. Ids written before sources were part of
+    /// the id have no prefix and came from collectors.
+    pub fn of(id: &str) -> Self {
+        match id.split('-').next() {
+            Some("proxy") => Source::Proxy,
+            Some("hook") => Source::Hook,
+            Some("harness") => Source::Harness,
+            _ => Source::Collector,
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A new id for an action seen by `source`
+pub fn generate(source: Source) -> String {
+    format!("{}-{}", source, ulid::Ulid::new())
+}
+
+/// Id of an action read from `agent`'s logs, from the agent's own id for
+/// it when there is one
+pub fn collected(agent: &AgentType, native: Option<&str>) -> String {
+    match native.filter(|n| !n.is_empty()) {
+        Some(native) => format!("{}-{}-{}", Source::Collector, agent, native),
+        None => format!("{}-{}-{}", Source::Collector, agent, ulid::Ulid::new()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_carry_their_source() {
+        let proxy = generate(Source::Proxy);
+        assert!(proxy.starts_with("proxy-"));
+        assert_eq!(proxy.len(), "proxy-".len() + 26);
+        assert_eq!(Source::of(&proxy), Source::Proxy);
+        assert_eq!(Source::of(&generate(Source::Hook)), Source::Hook);
+
+        let tool = collected(&AgentType::ClaudeCode, Some("toolu_01"));
+        assert_eq!(tool, "collector-claude_code-toolu_01");
+        assert_eq!(Source::of(&tool), Source::Collector);
+        assert_ne!(
+            collected(&AgentType::Aider, None),
+            collected(&AgentType::Aider, None)
+        );
+
+        // Ids from before the namespace
+        assert_eq!(Source::of("toolu_01"), Source::Collector);
+        assert_eq!(
+            Source::of("proxy-9b2e6c1e-8f0a-4d7e-a1c2-3e4f5a6b7c8d"),
+            Source::Proxy
+        );
+    }
+}
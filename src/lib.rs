@@ -3,15 +3,37 @@
 //! Core components for AI agent monitoring.
 
 pub mod analyzer;
+pub mod blob;
 pub mod brain;
 pub mod campaign;
+pub mod canary;
 pub mod collectors;
+pub mod correlation;
 pub mod db;
+pub mod diagnostics;
 pub mod enforcer;
+pub mod events;
+pub mod export;
+pub mod ids;
+pub mod listen;
+pub mod llm;
+pub mod logfile;
+pub mod metadata;
+pub mod network;
 pub mod patcher;
+pub mod policy;
+pub mod privacy;
 pub mod proxy;
+pub mod rollback;
 pub mod rules;
+pub mod slack;
+#[cfg(feature = "test-support")]
+pub mod testing;
+pub mod trace;
+pub mod tray;
+pub mod tripwire;
 pub mod web;
+pub mod workspace;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -38,30 +60,91 @@ pub struct AgentAction {
 }
 
 /// Supported AI agents
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
+///
+/// Agents we don't know about can be named via `Custom` (e.g. from a proxy's
+/// configured agent name) instead of collapsing into `Unknown`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum AgentType {
     OpenClaw,
     ClaudeCode,
     Cursor,
     Ralph,
+    GeminiCli,
+    CopilotCli,
+    Aider,
+    OpenHands,
+    Custom(String),
     Unknown,
 }
 
-impl std::fmt::Display for AgentType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl AgentType {
+    /// Parse an agent name. Accepts the display names, the legacy serde
+    /// names (`open_claw`) and hyphenated forms; anything else non-empty
+    /// becomes `Custom`.
+    pub fn from_name(name: &str) -> Self {
+        let name = name.trim().to_lowercase();
+        match name.replace('-', "_").as_str() {
+            "openclaw" | "open_claw" => AgentType::OpenClaw,
+            "claude_code" => AgentType::ClaudeCode,
+            "cursor" => AgentType::Cursor,
+            "ralph" => AgentType::Ralph,
+            "gemini_cli" => AgentType::GeminiCli,
+            "copilot_cli" => AgentType::CopilotCli,
+            "aider" => AgentType::Aider,
+            "openhands" | "open_hands" => AgentType::OpenHands,
+            "" | "unknown" => AgentType::Unknown,
+            _ => AgentType::Custom(name),
+        }
+    }
+
+    /// Name used in serialized form. Built-in agents keep the snake_case
+    /// variant names earlier versions wrote.
+    fn serde_name(&self) -> &str {
+        match self {
+            AgentType::OpenClaw => "open_claw",
+            AgentType::OpenHands => "open_hands",
+            AgentType::Custom(name) => name,
+            _ => self.display_name(),
+        }
+    }
+
+    fn display_name(&self) -> &str {
         match self {
-            AgentType::OpenClaw => write!(f, "openclaw"),
-            AgentType::ClaudeCode => write!(f, "claude_code"),
-            AgentType::Cursor => write!(f, "cursor"),
-            AgentType::Ralph => write!(f, "ralph"),
-            AgentType::Unknown => write!(f, "unknown"),
+            AgentType::OpenClaw => "openclaw",
+            AgentType::ClaudeCode => "claude_code",
+            AgentType::Cursor => "cursor",
+            AgentType::Ralph => "ralph",
+            AgentType::GeminiCli => "gemini_cli",
+            AgentType::CopilotCli => "copilot_cli",
+            AgentType::Aider => "aider",
+            AgentType::OpenHands => "openhands",
+            AgentType::Custom(name) => name,
+            AgentType::Unknown => "unknown",
         }
     }
 }
 
+impl std::fmt::Display for AgentType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+impl Serialize for AgentType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.serde_name())
+    }
+}
+
+impl<'de> Deserialize<'de> for AgentType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(AgentType::from_name(&name))
+    }
+}
+
 /// Types of actions agents can perform
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum ActionType {
     /// Shell command execution
@@ -80,6 +163,9 @@ pub enum ActionType {
     MessageSend,
     /// Git operation
     GitOperation,
+    /// Output of an earlier tool call, linked to it by the `tool_call_id`
+    /// metadata field. Only rules with `tool_result` in `applies_to` check these.
+    ToolResult,
     /// Unknown action
     Unknown,
 }
@@ -95,24 +181,58 @@ impl std::fmt::Display for ActionType {
             ActionType::BrowserAction => write!(f, "browser"),
             ActionType::MessageSend => write!(f, "message"),
             ActionType::GitOperation => write!(f, "git"),
+            ActionType::ToolResult => write!(f, "result"),
             ActionType::Unknown => write!(f, "unknown"),
         }
     }
 }
 
 /// Risk level of an action
+///
+/// `as_str` (lowercase) is the one stored and serialized form; `FromStr`
+/// accepts any case. `Display` is the uppercase label for people.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum RiskLevel {
     /// Informational, just logged
     #[default]
+    #[serde(alias = "Info", alias = "INFO")]
     Info,
     /// Warning, may require attention
+    #[serde(alias = "Warning", alias = "WARNING")]
     Warning,
     /// Critical, should be blocked or require approval
+    #[serde(alias = "Critical", alias = "CRITICAL")]
     Critical,
 }
 
+impl RiskLevel {
+    /// Every level, lowest first
+    pub const ALL: [RiskLevel; 3] = [RiskLevel::Info, RiskLevel::Warning, RiskLevel::Critical];
+
+    /// Name as stored in the database and serialized
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            RiskLevel::Info => "info",
+            RiskLevel::Warning => "warning",
+            RiskLevel::Critical => "critical",
+        }
+    }
+}
+
+impl std::str::FromStr for RiskLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "info" => Ok(RiskLevel::Info),
+            "warning" | "warn" => Ok(RiskLevel::Warning),
+            "critical" => Ok(RiskLevel::Critical),
+            other => anyhow::bail!("unknown risk level '{}' (info, warning or critical)", other),
+        }
+    }
+}
+
 impl std::fmt::Display for RiskLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -163,6 +283,16 @@ pub struct Config {
     pub db_path: String,
     /// Log retention days
     pub log_retention_days: u32,
+    /// Proxy to run inside the daemon; without one, `proxy start` runs it
+    /// as a separate process
+    #[serde(default)]
+    pub proxy: Option<proxy::config::ProxyConfig>,
+    /// Corporate proxy for the harness's own outbound requests
+    #[serde(default)]
+    pub network: network::NetworkConfig,
+    /// Where the web server listens
+    #[serde(default = "web::WebConfig::from_env")]
+    pub web: web::WebConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -170,6 +300,37 @@ pub struct CollectorConfig {
     pub openclaw: bool,
     pub claude_code: bool,
     pub cursor: bool,
+    #[serde(default)]
+    pub gemini_cli: bool,
+    #[serde(default)]
+    pub copilot_cli: bool,
+    #[serde(default)]
+    pub aider: bool,
+    #[serde(default)]
+    pub openhands: bool,
+    /// Poll vs. file watching, debounce and batch size for every collector
+    #[serde(default)]
+    pub watch: collectors::watch::WatchSettings,
+    /// Per-collector watch settings, keyed by collector name
+    #[serde(default)]
+    pub watch_overrides: std::collections::HashMap<String, collectors::watch::WatchSettings>,
+}
+
+impl CollectorConfig {
+    /// Watch settings for collector `name`, environment first (see
+    /// `collectors::watch`)
+    pub fn watch_for(&self, name: &str) -> collectors::watch::WatchSettings {
+        use collectors::watch::WatchSettings;
+        let configured = self
+            .watch_overrides
+            .get(name)
+            .cloned()
+            .unwrap_or_default()
+            .or(&self.watch);
+        WatchSettings::from_env(&format!("OPENCLAW_HARNESS_{}", name.to_uppercase()))
+            .or(&WatchSettings::from_env("OPENCLAW_HARNESS_COLLECTOR"))
+            .or(&configured)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -177,6 +338,13 @@ pub struct AlertConfig {
     pub telegram: Option<TelegramConfig>,
     pub slack: Option<SlackConfig>,
     pub discord: Option<DiscordConfig>,
+    /// Notifications on this machine's desktop (see `enforcer::desktop`)
+    #[serde(default)]
+    pub desktop: Option<DesktopConfig>,
+    /// Quiet hours per channel name, e.g. `telegram: "22:00-07:00,weekends"`
+    /// (see `enforcer::schedule`)
+    #[serde(default)]
+    pub quiet_hours: std::collections::HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -195,6 +363,13 @@ pub struct DiscordConfig {
     pub webhook_url: String,
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DesktopConfig {
+    /// Don't play a sound with Critical alerts
+    #[serde(default)]
+    pub silent: bool,
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -202,14 +377,60 @@ impl Default for Config {
                 openclaw: true,
                 claude_code: true,
                 cursor: false,
+                gemini_cli: true,
+                copilot_cli: true,
+                aider: true,
+                openhands: true,
+                watch: Default::default(),
+                watch_overrides: Default::default(),
             },
             alerts: AlertConfig {
                 telegram: None,
                 slack: None,
                 discord: None,
+                desktop: None,
+                quiet_hours: Default::default(),
             },
-            db_path: "~/.openclaw-harness/openclaw-harness.db".to_string(),
+            db_path: harness_path("openclaw-harness.db")
+                .to_string_lossy()
+                .to_string(),
             log_retention_days: 30,
+            proxy: proxy::config::ProxyConfig::embedded_from_env(),
+            network: network::NetworkConfig::from_env(),
+            web: web::WebConfig::from_env(),
+        }
+    }
+}
+
+/// Environment variable moving the harness's own directory
+pub const HOME_ENV: &str = "OPENCLAW_HARNESS_HOME";
+
+/// Directory for everything the harness keeps: database, ontology, reports,
+/// alert config, backups and the rest (`OPENCLAW_HARNESS_HOME`, default
+/// `~/.openclaw-harness`)
+pub fn harness_dir() -> std::path::PathBuf {
+    std::env::var(HOME_ENV)
+        .ok()
+        .filter(|d| !d.trim().is_empty())
+        .map(|d| expand_home(d.trim()))
+        .unwrap_or_else(|| expand_home("~/.openclaw-harness"))
+}
+
+/// `name` in the harness's directory
+pub fn harness_path(name: &str) -> std::path::PathBuf {
+    harness_dir().join(name)
+}
+
+/// Expand a leading `~` to the user's home directory
+pub fn expand_home(path: &str) -> std::path::PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    } else if path == "~" {
+        if let Some(home) = dirs::home_dir() {
+            return home;
         }
     }
+    std::path::PathBuf::from(path)
 }
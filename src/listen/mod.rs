@@ -0,0 +1,113 @@
+//! Where the web server and proxy accept connections
+//!
+//! Both listen on loopback unless told otherwise. Neither has any
+//! authentication: anyone who can reach the web API can disable rules, and
+//! anyone who can reach the proxy can spend the API keys it forwards. So a
+//! non-loopback address (e.g. `0.0.0.0:8380` for a container) is refused
+//! unless exposing is asked for explicitly, with `--expose`, `expose: true`
+//! in the config, or `OPENCLAW_HARNESS_EXPOSE=1`.
+
+use std::net::{IpAddr, SocketAddr};
+
+/// Set to `1` to allow listening on non-loopback addresses
+pub const EXPOSE_ENV: &str = "OPENCLAW_HARNESS_EXPOSE";
+
+/// Whether `OPENCLAW_HARNESS_EXPOSE` allows external addresses
+pub fn expose_from_env() -> bool {
+    std::env::var(EXPOSE_ENV)
+        .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// `host:port` from a port or an address; a bare port means loopback
+pub fn normalize(spec: &str) -> String {
+    let spec = spec.trim();
+    match spec.parse::<u16>() {
+        Ok(port) => format!("127.0.0.1:{}", port),
+        Err(_) => spec.to_string(),
+    }
+}
+
+/// Host part of `host:port` (brackets of IPv6 hosts removed)
+fn host(addr: &str) -> &str {
+    let host = addr.rsplit_once(':').map_or(addr, |(host, _)| host);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// Whether `addr` only accepts connections from this machine
+pub fn is_loopback(addr: &str) -> bool {
+    if let Ok(socket) = addr.parse::<SocketAddr>() {
+        return socket.ip().is_loopback();
+    }
+    let host = host(addr);
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Refuse a non-loopback `addr` unless `expose` is set
+pub fn check(addr: &str, expose: bool) -> anyhow::Result<()> {
+    if expose || is_loopback(addr) {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "refusing to listen on {}: it is reachable from other machines and has no \
+         authentication. Use a 127.0.0.1 address, or pass --expose (or set {}=1) \
+         if that is intended",
+        addr,
+        EXPOSE_ENV
+    )
+}
+
+/// What listening on `addr` means, for status output
+pub fn exposure_note(addr: &str) -> &'static str {
+    if is_loopback(addr) {
+        "loopback only: reachable from this machine"
+    } else {
+        "exposed: reachable from the network without authentication; \
+         restrict it with a firewall or a reverse proxy"
+    }
+}
+
+/// Address to connect to a server listening on `addr`; a wildcard
+/// address is reached over loopback
+pub fn connect_addr(addr: &str) -> String {
+    match addr.parse::<SocketAddr>() {
+        Ok(socket) if socket.ip().is_unspecified() => {
+            format!("127.0.0.1:{}", socket.port())
+        }
+        _ => addr.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_loopback_without_expose() {
+        for addr in [
+            "127.0.0.1:8380",
+            "localhost:8380",
+            "[::1]:9090",
+            "127.0.0.2:1",
+        ] {
+            assert!(is_loopback(addr), "{}", addr);
+            assert!(check(addr, false).is_ok());
+        }
+        for addr in [
+            "0.0.0.0:8380",
+            "[::]:8380",
+            "192.168.1.10:9090",
+            "myhost:80",
+        ] {
+            assert!(!is_loopback(addr), "{}", addr);
+            assert!(check(addr, false).is_err());
+            assert!(check(addr, true).is_ok());
+        }
+
+        assert_eq!(normalize("8380"), "127.0.0.1:8380");
+        assert_eq!(normalize("0.0.0.0:8380"), "0.0.0.0:8380");
+        assert_eq!(connect_addr("0.0.0.0:8380"), "127.0.0.1:8380");
+        assert_eq!(connect_addr("127.0.0.1:8380"), "127.0.0.1:8380");
+    }
+}
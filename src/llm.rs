@@ -0,0 +1,109 @@
+//! Chat completions client for the LLM features
+//!
+//! Mission planning (`campaign`) and report insights talk to an
+//! OpenAI-compatible `/chat/completions` endpoint configured by:
+//!
+//! - `SAFEBOT_LLM_API_KEY` (required)
+//! - `SAFEBOT_LLM_BASE_URL` (optional, default https://api.openai.com/v1)
+//! - `SAFEBOT_LLM_MODEL` (optional, default gpt-4o-mini)
+//!
+//! Requests go through the outbound proxy (see `network`).
+
+use anyhow::Context;
+use serde_json::Value;
+use std::time::Duration;
+
+pub const API_KEY_ENV: &str = "SAFEBOT_LLM_API_KEY";
+pub const BASE_URL_ENV: &str = "SAFEBOT_LLM_BASE_URL";
+pub const MODEL_ENV: &str = "SAFEBOT_LLM_MODEL";
+
+const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+const DEFAULT_MODEL: &str = "gpt-4o-mini";
+/// Per-request timeout
+pub const TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    pub api_key: String,
+    pub base_url: String,
+    pub model: String,
+}
+
+impl LlmConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let api_key = std::env::var(API_KEY_ENV)
+            .ok()
+            .filter(|k| !k.trim().is_empty())
+            .with_context(|| format!("missing {} for LLM features", API_KEY_ENV))?;
+        let base_url = std::env::var(BASE_URL_ENV).unwrap_or_else(|_| DEFAULT_BASE_URL.to_string());
+        let model = std::env::var(MODEL_ENV).unwrap_or_else(|_| DEFAULT_MODEL.to_string());
+        Ok(Self {
+            api_key,
+            base_url,
+            model,
+        })
+    }
+
+    pub fn chat_url(&self) -> String {
+        format!("{}/chat/completions", self.base_url.trim_end_matches('/'))
+    }
+
+    /// A request asking for a JSON object
+    pub fn chat_body(&self, system: &str, prompt: &str) -> Value {
+        serde_json::json!({
+            "model": self.model,
+            "temperature": 0.2,
+            "response_format": {"type":"json_object"},
+            "messages": [
+                {"role":"system","content":system},
+                {"role":"user","content":prompt}
+            ]
+        })
+    }
+}
+
+/// The assistant's text in a chat completions response
+pub fn response_content(response: &Value) -> anyhow::Result<String> {
+    response["choices"][0]["message"]["content"]
+        .as_str()
+        .map(String::from)
+        .ok_or_else(|| anyhow::anyhow!("LLM response missing content"))
+}
+
+/// Async client, for use from the daemon and web handlers
+pub struct LlmClient {
+    config: LlmConfig,
+    client: reqwest::Client,
+}
+
+impl LlmClient {
+    pub fn new(config: LlmConfig) -> anyhow::Result<Self> {
+        Ok(Self {
+            config,
+            client: crate::network::client_builder().timeout(TIMEOUT).build()?,
+        })
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::new(LlmConfig::from_env()?)
+    }
+
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    /// Send one exchange and return the reply, which should be JSON
+    pub async fn chat_json(&self, system: &str, prompt: &str) -> anyhow::Result<String> {
+        let response: Value = self
+            .client
+            .post(self.config.chat_url())
+            .bearer_auth(&self.config.api_key)
+            .json(&self.config.chat_body(system, prompt))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        response_content(&response)
+    }
+}
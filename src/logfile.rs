@@ -0,0 +1,303 @@
+//! The daemon's own log, kept on disk (`openclaw-harness logs --self`)
+//!
+//! `openclaw-harness start` writes everything it logs to
+//! `~/.openclaw-harness/logs/harness.log` as well as stdout. The file is
+//! rotated at the start of each day (`OPENCLAW_HARNESS_LOG_ROTATION`:
+//! `hourly`, `daily` or `never`) and whenever it would pass 10 MiB
+//! (`OPENCLAW_HARNESS_LOG_MAX_MB`). Rotated files are renamed
+//! `harness.<time>.log`, and only the newest 7 are kept
+//! (`OPENCLAW_HARNESS_LOG_KEEP`). Set `OPENCLAW_HARNESS_LOG_FILE=0` to log to
+//! stdout only.
+//!
+//! These are the harness's internal logs; what agents did is in the
+//! database.
+
+use chrono::{DateTime, Local};
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use tracing::Level;
+
+/// Environment variable turning the log file off (`0`)
+pub const FILE_ENV: &str = "OPENCLAW_HARNESS_LOG_FILE";
+/// Environment variable with the time-based rotation
+pub const ROTATION_ENV: &str = "OPENCLAW_HARNESS_LOG_ROTATION";
+/// Environment variable with the size a log file is rotated at, in MiB
+pub const MAX_MB_ENV: &str = "OPENCLAW_HARNESS_LOG_MAX_MB";
+/// Environment variable with the number of rotated files kept
+pub const KEEP_ENV: &str = "OPENCLAW_HARNESS_LOG_KEEP";
+
+/// Name of the file being written
+pub const FILE_NAME: &str = "harness.log";
+const PREFIX: &str = "harness.";
+const SUFFIX: &str = ".log";
+
+const DEFAULT_MAX_MB: u64 = 10;
+const DEFAULT_KEEP: usize = 7;
+
+/// When the log file is rotated regardless of its size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rotation {
+    Hourly,
+    Daily,
+    Never,
+}
+
+impl Rotation {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "hourly" => Some(Self::Hourly),
+            "daily" => Some(Self::Daily),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Period `time` falls in; the file is rotated when it changes
+    fn period(self, time: &DateTime<Local>) -> Option<String> {
+        match self {
+            Self::Hourly => Some(time.format("%Y-%m-%d %H").to_string()),
+            Self::Daily => Some(time.format("%Y-%m-%d").to_string()),
+            Self::Never => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LogFileConfig {
+    pub dir: PathBuf,
+    pub rotation: Rotation,
+    /// A file is rotated before it would grow past this
+    pub max_bytes: u64,
+    /// Rotated files kept besides the current one
+    pub keep: usize,
+}
+
+impl LogFileConfig {
+    /// Settings from the environment, in [`log_dir`]; `None` when the log
+    /// file is turned off
+    pub fn from_env() -> Option<Self> {
+        let off =
+            std::env::var(FILE_ENV).is_ok_and(|v| matches!(v.trim(), "0" | "false" | "off" | "no"));
+        if off {
+            return None;
+        }
+        let rotation = std::env::var(ROTATION_ENV)
+            .ok()
+            .and_then(|v| Rotation::from_name(&v))
+            .unwrap_or(Rotation::Daily);
+        let max_mb = std::env::var(MAX_MB_ENV)
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|mb| *mb > 0)
+            .unwrap_or(DEFAULT_MAX_MB);
+        let keep = std::env::var(KEEP_ENV)
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_KEEP);
+        Some(Self {
+            dir: log_dir(),
+            rotation,
+            max_bytes: max_mb * 1024 * 1024,
+            keep,
+        })
+    }
+
+    /// The file being written
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(FILE_NAME)
+    }
+}
+
+/// Directory of the harness's own log files
+pub fn log_dir() -> PathBuf {
+    crate::harness_path("logs")
+}
+
+/// Log file that rotates itself by time and size as it is written
+pub struct RollingFile {
+    config: LogFileConfig,
+    file: File,
+    size: u64,
+    period: Option<String>,
+}
+
+impl RollingFile {
+    /// Open (or continue) the log file; one left from an earlier period is
+    /// rotated on the first write
+    pub fn open(config: LogFileConfig) -> io::Result<Self> {
+        std::fs::create_dir_all(&config.dir)?;
+        let file = append(&config.path())?;
+        let meta = file.metadata()?;
+        let written = meta
+            .modified()
+            .ok()
+            .filter(|_| meta.len() > 0)
+            .map(DateTime::<Local>::from)
+            .unwrap_or_else(Local::now);
+        Ok(Self {
+            period: config.rotation.period(&written),
+            size: meta.len(),
+            file,
+            config,
+        })
+    }
+
+    fn write_at(&mut self, buf: &[u8], now: &DateTime<Local>) -> io::Result<usize> {
+        let period = self.config.rotation.period(now);
+        let full = self.size > 0 && self.size + buf.len() as u64 > self.config.max_bytes;
+        if full || (self.size > 0 && period != self.period) {
+            self.rotate(now)?;
+        }
+        self.period = period;
+        let n = self.file.write(buf)?;
+        self.size += n as u64;
+        Ok(n)
+    }
+
+    /// Move the current file aside, start a new one and drop the oldest
+    fn rotate(&mut self, now: &DateTime<Local>) -> io::Result<()> {
+        self.file.flush()?;
+        let rotated = self.config.dir.join(format!(
+            "{}{}{}",
+            PREFIX,
+            now.format("%Y%m%d-%H%M%S%.3f"),
+            SUFFIX
+        ));
+        std::fs::rename(self.config.path(), rotated)?;
+        self.file = append(&self.config.path())?;
+        self.size = 0;
+        for old in rotated_files(&self.config.dir)
+            .into_iter()
+            .skip(self.config.keep)
+        {
+            let _ = std::fs::remove_file(old);
+        }
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_at(buf, &Local::now())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+fn append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+/// Rotated files in `dir`, newest first
+fn rotated_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n != FILE_NAME && n.starts_with(PREFIX) && n.ends_with(SUFFIX))
+        })
+        .collect();
+    // The timestamps in the names sort in time order
+    files.sort();
+    files.reverse();
+    files
+}
+
+/// Log files in `dir`, newest first
+pub fn files(dir: &Path) -> Vec<PathBuf> {
+    let current = dir.join(FILE_NAME);
+    current
+        .exists()
+        .then_some(current)
+        .into_iter()
+        .chain(rotated_files(dir))
+        .collect()
+}
+
+/// Level a log line was written at
+fn line_level(line: &str) -> Option<Level> {
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+/// The last `count` lines logged in `dir`, oldest first, keeping only lines
+/// at `level` or more severe when given
+pub fn tail(dir: &Path, count: usize, level: Option<Level>) -> io::Result<Vec<String>> {
+    let mut lines = vec![];
+    for file in files(dir) {
+        let mut kept: Vec<String> = BufReader::new(File::open(&file)?)
+            .lines()
+            .collect::<io::Result<Vec<String>>>()?
+            .into_iter()
+            .filter(|line| match level {
+                Some(level) => line_level(line).is_some_and(|l| l <= level),
+                None => true,
+            })
+            .collect();
+        let needed = count - lines.len();
+        if kept.len() > needed {
+            kept.drain(..kept.len() - needed);
+        }
+        kept.append(&mut lines);
+        lines = kept;
+        if lines.len() == count {
+            break;
+        }
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_rotation_and_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = LogFileConfig {
+            dir: dir.path().to_path_buf(),
+            rotation: Rotation::Daily,
+            max_bytes: 100,
+            keep: 2,
+        };
+        let mut log = RollingFile::open(config).unwrap();
+        let day1 = Local.with_ymd_and_hms(2026, 10, 16, 23, 59, 0).unwrap();
+        let day2 = Local.with_ymd_and_hms(2026, 10, 17, 0, 1, 0).unwrap();
+        let line = |level: &str, n: usize| format!("2026-10-16T23:59:00Z  {} line {}\n", level, n);
+
+        log.write_at(line("INFO", 1).as_bytes(), &day1).unwrap();
+        log.write_at(line("WARN", 2).as_bytes(), &day1).unwrap();
+        assert!(rotated_files(dir.path()).is_empty());
+        // A new day starts a new file
+        log.write_at(line("ERROR", 3).as_bytes(), &day2).unwrap();
+        assert_eq!(rotated_files(dir.path()).len(), 1);
+        // So does running out of room
+        log.write_at(line("INFO", 4).as_bytes(), &day2).unwrap();
+        let later = day2 + chrono::Duration::seconds(1);
+        log.write_at(line("INFO", 5).as_bytes(), &later).unwrap();
+        let later = later + chrono::Duration::seconds(1);
+        log.write_at(line("INFO", 6).as_bytes(), &later).unwrap();
+        let later = later + chrono::Duration::seconds(1);
+        log.write_at(line("INFO", 7).as_bytes(), &later).unwrap();
+        log.flush().unwrap();
+        // Only the two newest rotated files are kept
+        assert_eq!(rotated_files(dir.path()).len(), 2);
+        assert_eq!(files(dir.path()).len(), 3);
+
+        let all = tail(dir.path(), 10, None).unwrap();
+        let numbers: Vec<&str> = all.iter().map(|l| &l[l.len() - 1..]).collect();
+        assert_eq!(numbers, ["3", "4", "5", "6", "7"]);
+        let last = tail(dir.path(), 2, None).unwrap();
+        assert!(last[0].ends_with("line 6") && last[1].ends_with("line 7"));
+        let errors = tail(dir.path(), 10, Some(Level::WARN)).unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("ERROR line 3"));
+    }
+}
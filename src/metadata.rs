@@ -0,0 +1,192 @@
+//! Standard fields of action metadata
+//!
+//! Collectors and the proxy record the same facts about a tool call under
+//! the same keys, so rules (`metadata:` conditions such as
+//! `params.command` or `tool_name: Bash`) and analytics can read them
+//! instead of re-parsing `content`:
+//!
+//! - `tool_name`: the agent's name for the tool (`Bash`, `exec`, `Write`)
+//! - `params`: the tool's arguments as the agent sent them
+//! - `provider`: API provider (`anthropic`, `openai`, `gemini`)
+//! - `model`: model that made the call
+//! - `latency_ms`: time until the provider's response headers arrived
+//! - `blocked`: whether the harness stopped the call
+//! - `source`: what recorded the action, e.g. `proxy`
+//! - `request_id`: the proxied API call it came from (see `crate::trace`)
+//!
+//! Each is present when known. Other keys (`git`, `network`,
+//! `tool_call_id`, `rule`, `workspace`, ...) sit alongside them.
+
+use crate::AgentAction;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const TOOL_NAME: &str = "tool_name";
+pub const PARAMS: &str = "params";
+pub const PROVIDER: &str = "provider";
+pub const MODEL: &str = "model";
+pub const LATENCY_MS: &str = "latency_ms";
+pub const BLOCKED: &str = "blocked";
+pub const SOURCE: &str = "source";
+pub const REQUEST_ID: &str = "request_id";
+
+/// Keys earlier versions used: the proxy wrote `tool`, the Claude Code
+/// collector `input`
+const LEGACY_TOOL_NAME: &str = "tool";
+const LEGACY_PARAMS: &str = "input";
+
+/// The standard fields, typed
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ActionMetadata {
+    /// A tool call and its arguments
+    pub fn tool(name: &str, params: Value) -> Self {
+        Self {
+            tool_name: Some(name.to_string()),
+            params: (!params.is_null()).then_some(params),
+            ..Default::default()
+        }
+    }
+
+    /// As a metadata object
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).unwrap_or_else(|_| serde_json::json!({}))
+    }
+
+    /// Set the fields that are present in `metadata`, keeping its other keys
+    pub fn apply(&self, metadata: &mut Option<Value>) {
+        let metadata = metadata.get_or_insert_with(|| serde_json::json!({}));
+        let (Some(object), Value::Object(fields)) = (metadata.as_object_mut(), self.to_value())
+        else {
+            return;
+        };
+        object.extend(fields);
+    }
+}
+
+impl AgentAction {
+    fn metadata_field(&self, key: &str) -> Option<&Value> {
+        self.metadata.as_ref()?.get(key).filter(|v| !v.is_null())
+    }
+
+    fn metadata_str(&self, key: &str) -> Option<&str> {
+        self.metadata_field(key)?.as_str()
+    }
+
+    pub fn tool_name(&self) -> Option<&str> {
+        self.metadata_str(TOOL_NAME)
+            .or_else(|| self.metadata_str(LEGACY_TOOL_NAME))
+    }
+
+    pub fn params(&self) -> Option<&Value> {
+        self.metadata_field(PARAMS)
+            .or_else(|| self.metadata_field(LEGACY_PARAMS))
+    }
+
+    pub fn provider(&self) -> Option<&str> {
+        self.metadata_str(PROVIDER)
+    }
+
+    pub fn model(&self) -> Option<&str> {
+        self.metadata_str(MODEL)
+    }
+
+    pub fn latency_ms(&self) -> Option<u64> {
+        self.metadata_field(LATENCY_MS)?.as_u64()
+    }
+
+    pub fn blocked(&self) -> Option<bool> {
+        self.metadata_field(BLOCKED)?.as_bool()
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        self.metadata_str(REQUEST_ID)
+    }
+
+    /// All the standard fields at once
+    pub fn action_metadata(&self) -> ActionMetadata {
+        ActionMetadata {
+            tool_name: self.tool_name().map(String::from),
+            params: self.params().cloned(),
+            provider: self.provider().map(String::from),
+            model: self.model().map(String::from),
+            latency_ms: self.latency_ms(),
+            blocked: self.blocked(),
+            source: self.metadata_str(SOURCE).map(String::from),
+            request_id: self.request_id().map(String::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentType};
+    use serde_json::json;
+
+    fn action(metadata: Option<Value>) -> AgentAction {
+        AgentAction {
+            id: "test".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "ls".to_string(),
+            target: None,
+            session_id: None,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_metadata_fields() {
+        let mut metadata = Some(json!({"git": {"branch": "main"}}));
+        ActionMetadata {
+            provider: Some("anthropic".to_string()),
+            latency_ms: Some(420),
+            blocked: Some(false),
+            request_id: Some("req-1".to_string()),
+            ..ActionMetadata::tool("Bash", json!({"command": "ls"}))
+        }
+        .apply(&mut metadata);
+        let recorded = action(metadata);
+        assert_eq!(recorded.tool_name(), Some("Bash"));
+        assert_eq!(recorded.params().unwrap()["command"], "ls");
+        assert_eq!(recorded.provider(), Some("anthropic"));
+        assert_eq!(recorded.latency_ms(), Some(420));
+        assert_eq!(recorded.blocked(), Some(false));
+        assert_eq!(recorded.request_id(), Some("req-1"));
+        assert_eq!(recorded.model(), None);
+        // Other keys are kept
+        assert_eq!(recorded.metadata.as_ref().unwrap()["git"]["branch"], "main");
+        // Absent fields are left out rather than written as null
+        assert!(recorded.metadata.as_ref().unwrap().get(MODEL).is_none());
+
+        let round_trip = recorded.action_metadata();
+        assert_eq!(round_trip.tool_name.as_deref(), Some("Bash"));
+        assert_eq!(round_trip.source, None);
+
+        // Actions stored by earlier versions
+        let legacy = action(Some(json!({"tool": "exec", "input": {"command": "ls"}})));
+        assert_eq!(legacy.tool_name(), Some("exec"));
+        assert_eq!(legacy.params().unwrap()["command"], "ls");
+        assert_eq!(action(None).action_metadata(), ActionMetadata::default());
+    }
+}
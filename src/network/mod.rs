@@ -0,0 +1,183 @@
+//! Outbound HTTP through a corporate proxy
+//!
+//! Requests the harness makes itself — forwarded API calls from the proxy,
+//! alert deliveries, the Slack app, LLM planning — go through
+//! `network.proxy` when set (`http://`, `https://`, `socks5://` or
+//! `socks5h://`, credentials in the URL; a SOCKS proxy's own host name must
+//! resolve when the client is built). Hosts listed in `network.no_proxy`
+//! are reached directly; entries are host names, domains (`.corp.example`),
+//! IPs or CIDRs, or provider presets such as `openai` or `anthropic`.
+//! Loopback is always direct, so local upstreams like Ollama keep working.
+//!
+//! From the environment: `OPENCLAW_HARNESS_OUTBOUND_PROXY` and
+//! `OPENCLAW_HARNESS_OUTBOUND_NO_PROXY` (comma-separated).
+
+use crate::proxy::config::preset_target;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// Environment variable with the proxy URL for outbound requests
+pub const PROXY_ENV: &str = "OPENCLAW_HARNESS_OUTBOUND_PROXY";
+/// Environment variable listing destinations that bypass the proxy
+pub const NO_PROXY_ENV: &str = "OPENCLAW_HARNESS_OUTBOUND_NO_PROXY";
+
+const LOOPBACK: [&str; 3] = ["localhost", "127.0.0.0/8", "::1"];
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Proxy for outbound requests; direct (or the system proxy) if unset
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Destinations reached without the proxy
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl NetworkConfig {
+    pub fn from_env() -> Self {
+        Self {
+            proxy: std::env::var(PROXY_ENV)
+                .ok()
+                .filter(|p| !p.trim().is_empty()),
+            no_proxy: std::env::var(NO_PROXY_ENV)
+                .map(|list| {
+                    list.split(',')
+                        .map(str::trim)
+                        .filter(|s| !s.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// `no_proxy` as reqwest expects it, with presets resolved to their host
+    fn no_proxy_list(&self) -> String {
+        let hosts = self.no_proxy.iter().map(|entry| {
+            preset_target(entry)
+                .and_then(|url| reqwest::Url::parse(url).ok())
+                .and_then(|url| url.host_str().map(str::to_string))
+                .unwrap_or_else(|| entry.clone())
+        });
+        LOOPBACK
+            .iter()
+            .map(|h| h.to_string())
+            .chain(hosts)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn proxy(&self) -> anyhow::Result<Option<reqwest::Proxy>> {
+        let Some(url) = &self.proxy else {
+            return Ok(None);
+        };
+        let scheme = url.split("://").next().unwrap_or_default();
+        if !matches!(scheme, "http" | "https" | "socks5" | "socks5h") {
+            anyhow::bail!(
+                "Outbound proxy '{}' must be an http(s):// or socks5(h):// URL",
+                url
+            );
+        }
+        let no_proxy = reqwest::NoProxy::from_string(&self.no_proxy_list());
+        Ok(Some(reqwest::Proxy::all(url)?.no_proxy(no_proxy)))
+    }
+
+    pub fn apply(&self, builder: reqwest::ClientBuilder) -> anyhow::Result<reqwest::ClientBuilder> {
+        Ok(match self.proxy()? {
+            Some(proxy) => builder.proxy(proxy),
+            None => builder,
+        })
+    }
+
+    pub fn apply_blocking(
+        &self,
+        builder: reqwest::blocking::ClientBuilder,
+    ) -> anyhow::Result<reqwest::blocking::ClientBuilder> {
+        Ok(match self.proxy()? {
+            Some(proxy) => builder.proxy(proxy),
+            None => builder,
+        })
+    }
+}
+
+/// Client builder for outbound requests, set up with the configured proxy;
+/// a broken proxy setting is logged and ignored
+pub fn client_builder() -> reqwest::ClientBuilder {
+    let network = crate::Config::default().network;
+    network
+        .apply(reqwest::Client::builder())
+        .unwrap_or_else(|e| {
+            warn!("Ignoring outbound proxy: {}", e);
+            reqwest::Client::builder()
+        })
+}
+
+/// Blocking counterpart of `client_builder`
+pub fn blocking_client_builder() -> reqwest::blocking::ClientBuilder {
+    let network = crate::Config::default().network;
+    network
+        .apply_blocking(reqwest::blocking::Client::builder())
+        .unwrap_or_else(|e| {
+            warn!("Ignoring outbound proxy: {}", e);
+            reqwest::blocking::Client::builder()
+        })
+}
+
+/// Client for outbound requests
+pub fn client() -> reqwest::Client {
+    client_builder().build().unwrap_or_else(|e| {
+        warn!("Cannot build HTTP client with outbound proxy: {}", e);
+        reqwest::Client::new()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::Uri, routing::any, Router};
+
+    #[tokio::test]
+    async fn test_outbound_proxy_and_no_proxy() {
+        // A forward proxy receives the absolute URL; echo it back
+        let app = Router::new().fallback(any(|uri: Uri| async move { uri.to_string() }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let network = NetworkConfig {
+            proxy: Some(format!("http://{}", addr)),
+            no_proxy: vec!["openai".to_string(), "direct.invalid".to_string()],
+        };
+        assert!(network
+            .no_proxy_list()
+            .ends_with("api.openai.com,direct.invalid"));
+
+        let client = network
+            .apply(reqwest::Client::builder())
+            .unwrap()
+            .build()
+            .unwrap();
+        let echoed = client
+            .get("http://api.example.invalid/v1/models")
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        assert_eq!(echoed, "http://api.example.invalid/v1/models");
+        // Bypassed hosts are resolved directly, which fails for .invalid
+        assert!(client.get("http://direct.invalid/").send().await.is_err());
+
+        let bad = NetworkConfig {
+            proxy: Some("ftp://proxy.corp:21".to_string()),
+            no_proxy: vec![],
+        };
+        assert!(bad.apply(reqwest::Client::builder()).is_err());
+        let socks = NetworkConfig {
+            proxy: Some("socks5h://127.0.0.1:1080".to_string()),
+            no_proxy: vec![],
+        };
+        assert!(socks.apply(reqwest::Client::builder()).is_ok());
+    }
+}
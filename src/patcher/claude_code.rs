@@ -0,0 +1,35 @@
+//! Claude Code patcher — registers a PreToolUse hook in ~/.claude/settings.json
+//!
+//! Claude Code runs every configured PreToolUse hook command before a tool
+//! call, passing the call as JSON on stdin. We register `openclaw-harness
+//! hook-exec`, which checks the call against the rules and exits with code 2
+//! to block it.
+
+use super::settings_hook;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+pub use super::settings_hook::hook_command;
+
+const HOOK_EVENT: &str = "PreToolUse";
+
+/// Location of the user-level Claude Code settings file.
+pub fn settings_file() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Cannot determine home directory")?;
+    Ok(home.join(".claude/settings.json"))
+}
+
+/// Check if the PreToolUse hook is registered.
+pub fn is_patched(file: &Path) -> Result<bool> {
+    settings_hook::is_patched(file, HOOK_EVENT)
+}
+
+/// Register the hook, backing up the original settings to settings.json.orig.
+pub fn apply_patch(file: &Path, command: &str) -> Result<()> {
+    settings_hook::apply_patch(file, HOOK_EVENT, command)
+}
+
+/// Remove the hook and delete the backup.
+pub fn revert_patch(file: &Path) -> Result<()> {
+    settings_hook::revert_patch(file, HOOK_EVENT)
+}
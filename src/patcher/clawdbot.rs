@@ -5,6 +5,8 @@
 //!   - `dist/agents/pi-tools.js` — write/edit tool hooks (v2)
 //!
 //! Supports both OpenClaw (2026.1.29+, including 2026.1.30) and legacy Clawdbot (2026.1.24-3).
+//! Injection points are looked up in a versioned anchor registry with a
+//! whitespace-insensitive fallback; `patch --probe` reports what matched.
 
 use anyhow::{bail, Context, Result};
 use std::fs;
@@ -119,6 +121,238 @@ const WRITE_EDIT_REPLACEMENT: &str = r#"if (tool.name === "write") {
             return [_editTool];
         }"#;
 
+// ============================================================
+// Anchor registry
+// ============================================================
+
+/// Compact single-line form of the exec anchor, as emitted by minifying bundlers.
+const ANCHOR_TEXT_COMPACT: &str =
+    r#"if (!params.command) throw new Error("Provide a command to start.");"#;
+
+/// File an anchor lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchFile {
+    /// agents/bash-tools.exec.js (v1)
+    Exec,
+    /// agents/pi-tools.js (v2)
+    PiTools,
+}
+
+/// How the patch is applied once an anchor is found
+#[derive(Debug, Clone, Copy)]
+enum AnchorPatch {
+    /// Insert code right after the anchor
+    InsertAfter(&'static str),
+    /// Replace the anchor entirely
+    Replace(&'static str),
+}
+
+/// One known shape of an injection point, valid for a version range
+#[derive(Debug, Clone, Copy)]
+pub struct AnchorSpec {
+    pub id: &'static str,
+    pub file: PatchFile,
+    /// Oldest version known to use this anchor (inclusive)
+    pub min_version: Option<&'static str>,
+    /// Newest version known to use this anchor (inclusive)
+    pub max_version: Option<&'static str>,
+    text: &'static str,
+    patch: AnchorPatch,
+}
+
+/// Candidate anchors, most specific first. Versions outside every range
+/// still try all candidates, then fall back to whitespace-insensitive matching.
+const ANCHOR_REGISTRY: &[AnchorSpec] = &[
+    AnchorSpec {
+        id: "exec-params-guard",
+        file: PatchFile::Exec,
+        min_version: Some("2026.1.24-3"),
+        max_version: Some("2026.2.12"),
+        text: ANCHOR_TEXT,
+        patch: AnchorPatch::InsertAfter(PATCH_CODE),
+    },
+    AnchorSpec {
+        id: "exec-params-guard-compact",
+        file: PatchFile::Exec,
+        min_version: None,
+        max_version: None,
+        text: ANCHOR_TEXT_COMPACT,
+        patch: AnchorPatch::InsertAfter(PATCH_CODE),
+    },
+    AnchorSpec {
+        id: "pi-tools-write-edit",
+        file: PatchFile::PiTools,
+        min_version: Some("2026.1.29"),
+        max_version: Some("2026.2.12"),
+        text: WRITE_EDIT_ANCHOR,
+        patch: AnchorPatch::Replace(WRITE_EDIT_REPLACEMENT),
+    },
+];
+
+/// How an anchor was located
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Exact,
+    /// Matched after collapsing whitespace (re-indented or CRLF builds)
+    Fuzzy,
+}
+
+/// A located anchor: byte range in the file content
+#[derive(Debug, Clone, Copy)]
+pub struct AnchorMatch {
+    pub spec: &'static AnchorSpec,
+    pub kind: MatchKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Parse "2026.1.24-3" into comparable parts: [2026, 1, 24, 3]
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches('v')
+        .split(['.', '-'])
+        .map(|p| {
+            p.chars()
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+                .parse()
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+impl AnchorSpec {
+    /// Whether `version` falls inside this anchor's known range
+    pub fn covers(&self, version: &str) -> bool {
+        let v = version_key(version);
+        let above_min = self.min_version.is_none_or(|m| v >= version_key(m));
+        let below_max = self.max_version.is_none_or(|m| v <= version_key(m));
+        above_min && below_max
+    }
+}
+
+/// Find `needle` in `haystack` ignoring whitespace differences.
+/// Returns the byte range in the original haystack.
+fn fuzzy_find(haystack: &str, needle: &str) -> Option<(usize, usize)> {
+    let needle: String = needle.split_whitespace().collect::<Vec<_>>().join(" ");
+    if needle.is_empty() {
+        return None;
+    }
+
+    // Collapse whitespace runs to a single space, remembering original offsets
+    let mut normalized = String::with_capacity(haystack.len());
+    let mut offsets = Vec::with_capacity(haystack.len());
+    let mut in_space = false;
+    for (i, c) in haystack.char_indices() {
+        if c.is_whitespace() {
+            if !in_space {
+                normalized.push(' ');
+                offsets.push(i);
+            }
+            in_space = true;
+        } else {
+            for _ in 0..c.len_utf8() {
+                offsets.push(i);
+            }
+            normalized.push(c);
+            in_space = false;
+        }
+    }
+
+    let pos = normalized.find(&needle)?;
+    let last = pos + needle.len() - 1;
+    let start = offsets[pos];
+    let last_char = haystack[offsets[last]..].chars().next()?;
+    Some((start, offsets[last] + last_char.len_utf8()))
+}
+
+/// Locate the best anchor for `file` in `content`.
+///
+/// Anchors whose version range covers `version` are tried first; exact
+/// matches always win over fuzzy ones.
+pub fn locate_anchor(content: &str, file: PatchFile, version: Option<&str>) -> Option<AnchorMatch> {
+    let mut candidates: Vec<&'static AnchorSpec> =
+        ANCHOR_REGISTRY.iter().filter(|a| a.file == file).collect();
+    if let Some(v) = version {
+        candidates.sort_by_key(|a| !a.covers(v));
+    }
+
+    for spec in &candidates {
+        if let Some(start) = content.find(spec.text) {
+            return Some(AnchorMatch {
+                spec,
+                kind: MatchKind::Exact,
+                start,
+                end: start + spec.text.len(),
+            });
+        }
+    }
+    for spec in &candidates {
+        if let Some((start, end)) = fuzzy_find(content, spec.text) {
+            return Some(AnchorMatch {
+                spec,
+                kind: MatchKind::Fuzzy,
+                start,
+                end,
+            });
+        }
+    }
+    None
+}
+
+/// Apply the anchor's patch to `content`
+fn patch_at(content: &str, m: &AnchorMatch) -> String {
+    match m.spec.patch {
+        AnchorPatch::InsertAfter(code) => {
+            format!("{}{}{}", &content[..m.end], code, &content[m.end..])
+        }
+        AnchorPatch::Replace(code) => {
+            format!("{}{}{}", &content[..m.start], code, &content[m.end..])
+        }
+    }
+}
+
+/// Result of probing one patch file
+#[derive(Debug, Clone)]
+pub struct ProbeResult {
+    pub file: PathBuf,
+    pub exists: bool,
+    pub already_patched: bool,
+    pub anchor: Option<(&'static str, MatchKind)>,
+}
+
+/// Report which anchors match in the installed dist, without modifying it
+pub fn probe(dist: &Path, version: Option<&str>) -> Vec<ProbeResult> {
+    [
+        (PatchFile::Exec, exec_file(dist), PATCH_MARKER),
+        (PatchFile::PiTools, pi_tools_file(dist), PATCH_V2_MARKER),
+    ]
+    .into_iter()
+    .map(|(kind, file, marker)| match fs::read_to_string(&file) {
+        Ok(content) => ProbeResult {
+            exists: true,
+            already_patched: content.contains(marker),
+            anchor: locate_anchor(&content, kind, version).map(|m| (m.spec.id, m.kind)),
+            file,
+        },
+        Err(_) => ProbeResult {
+            file,
+            exists: false,
+            already_patched: false,
+            anchor: None,
+        },
+    })
+    .collect()
+}
+
+/// Whether `version` is covered by any registry entry
+pub fn version_in_registry(version: &str) -> bool {
+    ANCHOR_REGISTRY
+        .iter()
+        .any(|a| (a.min_version.is_some() || a.max_version.is_some()) && a.covers(version))
+}
+
 // ============================================================
 // Dist directory discovery
 // ============================================================
@@ -263,6 +497,14 @@ pub fn is_v2_patched(dist: &Path) -> Result<bool> {
     Ok(content.contains(PATCH_V2_MARKER))
 }
 
+/// Check whether the unpatched injection anchors are present, i.e. the
+/// current dist can be (re-)patched safely.
+pub fn anchors_present(dist: &Path) -> bool {
+    fs::read_to_string(exec_file(dist))
+        .map(|c| locate_anchor(&c, PatchFile::Exec, None).is_some())
+        .unwrap_or(false)
+}
+
 // ============================================================
 // Version detection
 // ============================================================
@@ -298,7 +540,8 @@ pub fn detect_clawdbot_version() -> Option<String> {
 /// Apply both v1 and v2 patches.
 pub fn apply_patch(dist: &Path) -> Result<()> {
     // Version compatibility check
-    if let Some(version) = detect_clawdbot_version() {
+    let version = detect_clawdbot_version();
+    if let Some(ref version) = version {
         println!("📌 Detected OpenClaw version: {}", version);
         if SUPPORTED_VERSIONS.contains(&version.as_str()) {
             println!("✅ Version {} is supported", version);
@@ -321,10 +564,10 @@ pub fn apply_patch(dist: &Path) -> Result<()> {
     }
 
     // === V1 Patch: exec tool ===
-    apply_v1_patch(dist)?;
+    apply_v1_patch(dist, version.as_deref())?;
 
     // === V2 Patch: write/edit tools ===
-    apply_v2_patch(dist)?;
+    apply_v2_patch(dist, version.as_deref())?;
 
     println!();
     println!("🎉 All patches applied! Restart OpenClaw to activate:");
@@ -333,7 +576,7 @@ pub fn apply_patch(dist: &Path) -> Result<()> {
     Ok(())
 }
 
-fn apply_v1_patch(dist: &Path) -> Result<()> {
+fn apply_v1_patch(dist: &Path, version: Option<&str>) -> Result<()> {
     let file = exec_file(dist);
     if !file.exists() {
         bail!("Exec tool file not found: {}", file.display());
@@ -347,14 +590,20 @@ fn apply_v1_patch(dist: &Path) -> Result<()> {
         return Ok(());
     }
 
-    if !content.contains(ANCHOR_TEXT) {
+    let Some(anchor) = locate_anchor(&content, PatchFile::Exec, version) else {
         bail!(
             "Cannot find injection anchor in {}. \
              OpenClaw version may be incompatible. \
-             Supported versions: {:?}",
+             Supported versions: {:?} (run with --probe for details)",
             file.display(),
             SUPPORTED_VERSIONS,
         );
+    };
+    if anchor.kind == MatchKind::Fuzzy {
+        println!(
+            "⚠️  [v1] Anchor '{}' matched only after ignoring whitespace",
+            anchor.spec.id
+        );
     }
 
     // Backup original
@@ -365,7 +614,7 @@ fn apply_v1_patch(dist: &Path) -> Result<()> {
         println!("📦 [v1] Backed up original to {}", backup.display());
     }
 
-    let patched = content.replacen(ANCHOR_TEXT, &format!("{}{}", ANCHOR_TEXT, PATCH_CODE), 1);
+    let patched = patch_at(&content, &anchor);
 
     fs::write(&file, &patched)
         .with_context(|| format!("Cannot write patched file {}", file.display()))?;
@@ -374,7 +623,7 @@ fn apply_v1_patch(dist: &Path) -> Result<()> {
     Ok(())
 }
 
-fn apply_v2_patch(dist: &Path) -> Result<()> {
+fn apply_v2_patch(dist: &Path, version: Option<&str>) -> Result<()> {
     let file = pi_tools_file(dist);
     if !file.exists() {
         println!(
@@ -392,7 +641,7 @@ fn apply_v2_patch(dist: &Path) -> Result<()> {
         return Ok(());
     }
 
-    if !content.contains(WRITE_EDIT_ANCHOR) {
+    let Some(anchor) = locate_anchor(&content, PatchFile::PiTools, version) else {
         println!(
             "⚠️  [v2] Cannot find write/edit anchor in {}.",
             file.display()
@@ -400,6 +649,12 @@ fn apply_v2_patch(dist: &Path) -> Result<()> {
         println!("   OpenClaw version may have changed the write/edit tool structure.");
         println!("   Skipping v2 patch. Exec hook (v1) still works.");
         return Ok(());
+    };
+    if anchor.kind == MatchKind::Fuzzy {
+        println!(
+            "⚠️  [v2] Anchor '{}' matched only after ignoring whitespace",
+            anchor.spec.id
+        );
     }
 
     // Backup original
@@ -411,7 +666,7 @@ fn apply_v2_patch(dist: &Path) -> Result<()> {
     }
 
     // Replace the anchor with hooked version
-    let patched = content.replacen(WRITE_EDIT_ANCHOR, WRITE_EDIT_REPLACEMENT, 1);
+    let patched = patch_at(&content, &anchor);
 
     fs::write(&file, &patched)
         .with_context(|| format!("Cannot write patched file {}", file.display()))?;
@@ -483,3 +738,53 @@ fn revert_v2_patch(dist: &Path) -> Result<()> {
     println!("✅ [v2] Reverted write/edit hooks. Backup removed.");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_ranges() {
+        let spec = &ANCHOR_REGISTRY[0];
+        assert!(spec.covers("2026.1.24-3"));
+        assert!(spec.covers("2026.2.6-3"));
+        assert!(!spec.covers("2026.1.24"));
+        assert!(!spec.covers("2026.3.1"));
+        assert!(version_in_registry("2026.1.30"));
+        assert!(!version_in_registry("2027.1.1"));
+    }
+
+    #[test]
+    fn test_exact_anchor_and_insert() {
+        let content = format!(
+            "async function run(params) {{\n            {}\n}}",
+            ANCHOR_TEXT
+        );
+        let m = locate_anchor(&content, PatchFile::Exec, Some("2026.2.9")).unwrap();
+        assert_eq!(m.kind, MatchKind::Exact);
+        assert_eq!(m.spec.id, "exec-params-guard");
+        let patched = patch_at(&content, &m);
+        assert!(patched.contains(PATCH_MARKER));
+        assert!(patched.find(ANCHOR_TEXT).unwrap() < patched.find(PATCH_MARKER).unwrap());
+    }
+
+    #[test]
+    fn test_fuzzy_anchor_reindented() {
+        let content = "x();\r\nif (!params.command) {\r\n  throw new Error(\"Provide a command to start.\");\r\n}\r\ny();";
+        let m = locate_anchor(content, PatchFile::Exec, None).unwrap();
+        assert_eq!(m.kind, MatchKind::Fuzzy);
+        assert!(content[m.start..m.end].starts_with("if (!params.command)"));
+        assert!(content[m.start..m.end].ends_with('}'));
+        let patched = patch_at(content, &m);
+        assert!(patched.contains(PATCH_MARKER));
+        assert!(patched.ends_with("END OPENCLAW_HARNESS_PATCH_v1\r\ny();"));
+    }
+
+    #[test]
+    fn test_compact_anchor_candidate() {
+        let content = format!("function f(params){{{}return 1}}", ANCHOR_TEXT_COMPACT);
+        let m = locate_anchor(&content, PatchFile::Exec, Some("2030.1.1")).unwrap();
+        assert_eq!(m.spec.id, "exec-params-guard-compact");
+        assert_eq!(m.kind, MatchKind::Exact);
+    }
+}
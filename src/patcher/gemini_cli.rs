@@ -0,0 +1,33 @@
+//! Gemini CLI patcher — registers a BeforeTool hook in ~/.gemini/settings.json
+//!
+//! Gemini CLI hooks use the same settings layout and stdin/exit-code contract
+//! as Claude Code, so `openclaw-harness hook-exec` serves both.
+
+use super::settings_hook;
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+pub use super::settings_hook::hook_command;
+
+const HOOK_EVENT: &str = "BeforeTool";
+
+/// Location of the user-level Gemini CLI settings file.
+pub fn settings_file() -> Result<PathBuf> {
+    let home = dirs::home_dir().context("Cannot determine home directory")?;
+    Ok(home.join(".gemini/settings.json"))
+}
+
+/// Check if the BeforeTool hook is registered.
+pub fn is_patched(file: &Path) -> Result<bool> {
+    settings_hook::is_patched(file, HOOK_EVENT)
+}
+
+/// Register the hook, backing up the original settings to settings.json.orig.
+pub fn apply_patch(file: &Path, command: &str) -> Result<()> {
+    settings_hook::apply_patch(file, HOOK_EVENT, command)
+}
+
+/// Remove the hook and delete the backup.
+pub fn revert_patch(file: &Path) -> Result<()> {
+    settings_hook::revert_patch(file, HOOK_EVENT)
+}
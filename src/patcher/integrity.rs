@@ -0,0 +1,150 @@
+//! Patch integrity re-verification
+//!
+//! Agents upgrade themselves silently, and an upgrade replaces the patched
+//! files — the most common way protection quietly disappears. The daemon
+//! calls [`IntegrityMonitor::check`] periodically to notice when a target
+//! that was patched no longer is.
+
+use super::{claude_code, clawdbot, gemini_cli};
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Environment variable enabling automatic re-patching
+pub const AUTO_REPATCH_ENV: &str = "OPENCLAW_HARNESS_AUTO_REPATCH";
+
+/// Current patch state of one target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatchStatus {
+    pub target: &'static str,
+    pub patched: bool,
+    /// The injection anchors still match, so re-applying is safe
+    pub can_repatch: bool,
+}
+
+/// Probe every installed patch target. Targets that aren't installed are skipped.
+pub fn probe_targets() -> Vec<PatchStatus> {
+    let mut statuses = Vec::new();
+
+    if let Ok(dist) = clawdbot::find_clawdbot_dist() {
+        let builtin = clawdbot::has_builtin_before_tool_call(&dist).unwrap_or(false);
+        let patched = builtin
+            || (clawdbot::is_patched(&dist).unwrap_or(false)
+                && clawdbot::is_v2_patched(&dist).unwrap_or(false));
+        statuses.push(PatchStatus {
+            target: "openclaw",
+            patched,
+            can_repatch: !builtin && clawdbot::anchors_present(&dist),
+        });
+    }
+
+    if let Ok(settings) = claude_code::settings_file() {
+        if settings.parent().map(|p| p.is_dir()).unwrap_or(false) {
+            statuses.push(PatchStatus {
+                target: "claude-code",
+                patched: claude_code::is_patched(&settings).unwrap_or(false),
+                can_repatch: true,
+            });
+        }
+    }
+
+    if let Ok(settings) = gemini_cli::settings_file() {
+        if settings.parent().map(|p| p.is_dir()).unwrap_or(false) {
+            statuses.push(PatchStatus {
+                target: "gemini-cli",
+                patched: gemini_cli::is_patched(&settings).unwrap_or(false),
+                can_repatch: true,
+            });
+        }
+    }
+
+    statuses
+}
+
+/// Re-apply the patch for a target
+pub fn repatch(target: &str) -> anyhow::Result<()> {
+    match target {
+        "openclaw" => clawdbot::apply_patch(&clawdbot::find_clawdbot_dist()?),
+        "claude-code" => {
+            claude_code::apply_patch(&claude_code::settings_file()?, &claude_code::hook_command())
+        }
+        "gemini-cli" => {
+            gemini_cli::apply_patch(&gemini_cli::settings_file()?, &gemini_cli::hook_command())
+        }
+        _ => anyhow::bail!("Unknown patch target: {}", target),
+    }
+}
+
+/// Whether `OPENCLAW_HARNESS_AUTO_REPATCH` is set to a truthy value
+pub fn auto_repatch_enabled() -> bool {
+    std::env::var(AUTO_REPATCH_ENV)
+        .map(|v| matches!(v.as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Remembers which targets were patched and reports when that changes
+#[derive(Default)]
+pub struct IntegrityMonitor {
+    last: HashMap<&'static str, bool>,
+}
+
+impl IntegrityMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record new statuses; returns targets that were patched but no longer are
+    pub fn observe(&mut self, statuses: &[PatchStatus]) -> Vec<PatchStatus> {
+        let mut drifted = Vec::new();
+        for status in statuses {
+            let was_patched = self.last.insert(status.target, status.patched);
+            if was_patched == Some(true) && !status.patched {
+                warn!("🩹 Patch for {} disappeared", status.target);
+                drifted.push(status.clone());
+            } else if was_patched == Some(false) && status.patched {
+                info!("🩹 Patch for {} is active again", status.target);
+            }
+        }
+        drifted
+    }
+
+    /// Probe installed targets and report drift
+    pub fn check(&mut self) -> Vec<PatchStatus> {
+        let statuses = probe_targets();
+        self.observe(&statuses)
+    }
+
+    /// Mark a target as patched again (after a successful re-apply)
+    pub fn mark_patched(&mut self, target: &'static str) {
+        self.last.insert(target, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status(patched: bool) -> PatchStatus {
+        PatchStatus {
+            target: "openclaw",
+            patched,
+            can_repatch: true,
+        }
+    }
+
+    #[test]
+    fn test_detects_patch_loss_only_after_patched() {
+        let mut monitor = IntegrityMonitor::new();
+
+        // Never patched: nothing to report
+        assert!(monitor.observe(&[status(false)]).is_empty());
+        assert!(monitor.observe(&[status(true)]).is_empty());
+
+        // Upgrade wiped the patch
+        let drifted = monitor.observe(&[status(false)]);
+        assert_eq!(drifted.len(), 1);
+        assert_eq!(drifted[0].target, "openclaw");
+
+        // Reported once, not on every check
+        assert!(monitor.observe(&[status(false)]).is_empty());
+    }
+}
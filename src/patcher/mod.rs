@@ -1,6 +1,11 @@
-//! Clawdbot auto-patcher module
+//! Agent auto-patcher module
 //!
 //! Patches Clawdbot's internal code to wire up `before_tool_call` hooks
-//! that aren't connected by default.
+//! that aren't connected by default, and registers hooks in the settings
+//! of agents that support them (Claude Code, Gemini CLI).
 
+pub mod claude_code;
 pub mod clawdbot;
+pub mod gemini_cli;
+pub mod integrity;
+pub mod settings_hook;
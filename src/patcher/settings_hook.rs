@@ -0,0 +1,236 @@
+//! Shared logic for agents configured through a JSON settings file with
+//! Claude-Code-style hook entries:
+//!
+//! ```json
+//! { "hooks": { "<Event>": [ { "matcher": "*", "hooks": [ { "type": "command", "command": "..." } ] } ] } }
+//! ```
+//!
+//! Settings files are edited by users, so revert removes only our hook entry
+//! instead of restoring the backup wholesale.
+
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+/// Subcommand identifying our hook command in settings files
+pub const HOOK_MARKER: &str = "hook-exec";
+
+/// Command the agent should run for each tool call. The path is quoted,
+/// as the agents run it through a shell.
+pub fn hook_command() -> String {
+    let exe = std::env::current_exe()
+        .ok()
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| "openclaw-harness".to_string());
+    format!("\"{}\" {}", exe, HOOK_MARKER)
+}
+
+fn is_our_hook(hook: &Value) -> bool {
+    hook.get("command")
+        .and_then(|c| c.as_str())
+        .map(|c| c.trim_end().ends_with(&format!(" {}", HOOK_MARKER)))
+        .unwrap_or(false)
+}
+
+fn read_settings(file: &Path) -> Result<Value> {
+    if !file.exists() {
+        return Ok(json!({}));
+    }
+    let content =
+        fs::read_to_string(file).with_context(|| format!("Cannot read {}", file.display()))?;
+    if content.trim().is_empty() {
+        return Ok(json!({}));
+    }
+    serde_json::from_str(&content).with_context(|| format!("Invalid JSON in {}", file.display()))
+}
+
+fn write_settings(file: &Path, settings: &Value) -> Result<()> {
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(settings)?;
+    fs::write(file, content + "\n").with_context(|| format!("Cannot write {}", file.display()))
+}
+
+// ============================================================
+// Settings manipulation
+// ============================================================
+
+/// Return the command of our hook for `event`, if registered.
+pub fn find_hook(settings: &Value, event: &str) -> Option<String> {
+    settings
+        .get("hooks")?
+        .get(event)?
+        .as_array()?
+        .iter()
+        .filter_map(|entry| entry.get("hooks").and_then(|h| h.as_array()))
+        .flatten()
+        .find(|hook| is_our_hook(hook))
+        .and_then(|hook| hook.get("command"))
+        .and_then(|c| c.as_str())
+        .map(String::from)
+}
+
+/// Insert (or update) our hook. Returns true if settings changed.
+pub fn add_hook(settings: &mut Value, event: &str, command: &str) -> Result<bool> {
+    if find_hook(settings, event).as_deref() == Some(command) {
+        return Ok(false);
+    }
+    remove_hook(settings, event);
+
+    let Some(root) = settings.as_object_mut() else {
+        bail!("settings file is not a JSON object");
+    };
+    let hooks = root.entry("hooks").or_insert_with(|| json!({}));
+    let Some(hooks) = hooks.as_object_mut() else {
+        bail!("`hooks` in settings file is not an object");
+    };
+    let entries = hooks.entry(event).or_insert_with(|| json!([]));
+    let Some(entries) = entries.as_array_mut() else {
+        bail!("`hooks.{}` in settings file is not an array", event);
+    };
+
+    entries.push(json!({
+        "matcher": "*",
+        "hooks": [{ "type": "command", "command": command }]
+    }));
+    Ok(true)
+}
+
+/// Remove our hook entries, leaving the user's other hooks alone.
+/// Returns the number of hook commands removed.
+pub fn remove_hook(settings: &mut Value, event: &str) -> usize {
+    let Some(entries) = settings
+        .get_mut("hooks")
+        .and_then(|h| h.get_mut(event))
+        .and_then(|e| e.as_array_mut())
+    else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.iter_mut() {
+        if let Some(hooks) = entry.get_mut("hooks").and_then(|h| h.as_array_mut()) {
+            let before = hooks.len();
+            hooks.retain(|hook| !is_our_hook(hook));
+            removed += before - hooks.len();
+        }
+    }
+    // Drop matcher groups we emptied
+    entries.retain(|entry| {
+        entry
+            .get("hooks")
+            .and_then(|h| h.as_array())
+            .map(|h| !h.is_empty())
+            .unwrap_or(true)
+    });
+    removed
+}
+
+// ============================================================
+// Check / apply / revert
+// ============================================================
+
+/// Check if our hook is registered for `event`.
+pub fn is_patched(file: &Path, event: &str) -> Result<bool> {
+    Ok(find_hook(&read_settings(file)?, event).is_some())
+}
+
+/// Register the hook, backing up the original settings to `<file>.orig`.
+pub fn apply_patch(file: &Path, event: &str, command: &str) -> Result<()> {
+    let mut settings = read_settings(file)?;
+
+    if !add_hook(&mut settings, event, command)? {
+        println!("✅ {} hook already registered.", event);
+        return Ok(());
+    }
+
+    let backup = file.with_extension("json.orig");
+    if file.exists() && !backup.exists() {
+        fs::copy(file, &backup)
+            .with_context(|| format!("Cannot backup to {}", backup.display()))?;
+        println!("📦 Backed up original to {}", backup.display());
+    }
+
+    write_settings(file, &settings)?;
+    println!("✅ Registered {} hook: {}", event, command);
+    println!("   in {}", file.display());
+    Ok(())
+}
+
+/// Remove the hook and delete the backup.
+pub fn revert_patch(file: &Path, event: &str) -> Result<()> {
+    if !file.exists() {
+        println!("✅ {} not found, nothing to revert.", file.display());
+        return Ok(());
+    }
+
+    let mut settings = read_settings(file)?;
+    let removed = remove_hook(&mut settings, event);
+    if removed == 0 {
+        println!("✅ Not patched, nothing to revert.");
+    } else {
+        write_settings(file, &settings)?;
+        println!("✅ Removed {} {} hook(s).", removed, event);
+    }
+
+    let backup = file.with_extension("json.orig");
+    if backup.exists() {
+        fs::remove_file(&backup)?;
+        println!("   Backup removed.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CMD: &str = "/usr/local/bin/openclaw-harness hook-exec";
+
+    #[test]
+    fn test_add_and_remove_hook_preserves_user_hooks() {
+        let mut settings = json!({
+            "model": "opus",
+            "hooks": {
+                "PreToolUse": [
+                    { "matcher": "Bash", "hooks": [{ "type": "command", "command": "my-linter" }] }
+                ]
+            }
+        });
+
+        assert!(add_hook(&mut settings, "PreToolUse", CMD).unwrap());
+        assert_eq!(find_hook(&settings, "PreToolUse").as_deref(), Some(CMD));
+        // Idempotent
+        assert!(!add_hook(&mut settings, "PreToolUse", CMD).unwrap());
+        // Other events are untouched
+        assert!(find_hook(&settings, "BeforeTool").is_none());
+
+        assert_eq!(remove_hook(&mut settings, "PreToolUse"), 1);
+        assert!(find_hook(&settings, "PreToolUse").is_none());
+        let entries = settings["hooks"]["PreToolUse"].as_array().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0]["hooks"][0]["command"], "my-linter");
+        assert_eq!(settings["model"], "opus");
+    }
+
+    #[test]
+    fn test_apply_and_revert_patch_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("settings.json");
+
+        apply_patch(&file, "BeforeTool", CMD).unwrap();
+        assert!(is_patched(&file, "BeforeTool").unwrap());
+        // Re-patching moves an older unquoted command to the quoted one
+        apply_patch(&file, "BeforeTool", &hook_command()).unwrap();
+        let settings = read_settings(&file).unwrap();
+        let command = find_hook(&settings, "BeforeTool").unwrap();
+        assert!(command.starts_with('"'));
+        assert!(command.ends_with("\" hook-exec"));
+        assert_eq!(remove_hook(&mut settings.clone(), "BeforeTool"), 1);
+
+        revert_patch(&file, "BeforeTool").unwrap();
+        assert!(!is_patched(&file, "BeforeTool").unwrap());
+    }
+}
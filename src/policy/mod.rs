@@ -0,0 +1,247 @@
+//! Offline policy checks — the rule engine outside an agent session
+//!
+//! `openclaw-harness check` runs a list of planned commands through the
+//! analyzer so CI can reject an agent-generated script before anything runs.
+//!
+//! Input is one action per line. Plain lines are shell commands; lines
+//! starting with `{` are JSON objects with `action_type`, `content` and an
+//! optional `target`, for planned file writes, fetches and so on. Blank lines
+//! and `#` comments are skipped.
+//!
+//! The git hooks (`openclaw-harness githook`) use `diff_actions` instead: each
+//! file in a diff becomes a write of its added lines (or a delete), so secret
+//! and protected-path rules apply to commits the same way they do to agents.
+
+pub mod suite;
+
+use crate::analyzer::Analyzer;
+use crate::{ActionType, AgentAction, AgentType, RiskLevel};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// One action read from a commands file
+#[derive(Debug, Clone)]
+pub struct PlannedAction {
+    /// 1-based line number in the input
+    pub line: usize,
+    pub action: AgentAction,
+}
+
+#[derive(Deserialize)]
+struct ActionLine {
+    #[serde(default = "exec")]
+    action_type: ActionType,
+    content: String,
+    #[serde(default)]
+    target: Option<String>,
+}
+
+fn exec() -> ActionType {
+    ActionType::Exec
+}
+
+fn planned(
+    line: usize,
+    action_type: ActionType,
+    content: String,
+    target: Option<String>,
+) -> PlannedAction {
+    PlannedAction {
+        line,
+        action: AgentAction {
+            id: format!("check-{}", line),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::Unknown,
+            action_type,
+            content,
+            target,
+            session_id: None,
+            metadata: None,
+        },
+    }
+}
+
+/// Parse a commands file
+pub fn parse_actions(text: &str) -> anyhow::Result<Vec<PlannedAction>> {
+    let mut actions = vec![];
+    for (i, raw) in text.lines().enumerate() {
+        let line = raw.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with('{') {
+            let parsed: ActionLine = serde_json::from_str(line)
+                .map_err(|e| anyhow::anyhow!("line {}: invalid action: {}", i + 1, e))?;
+            actions.push(planned(
+                i + 1,
+                parsed.action_type,
+                parsed.content,
+                parsed.target,
+            ));
+        } else {
+            actions.push(planned(i + 1, ActionType::Exec, line.to_string(), None));
+        }
+    }
+    Ok(actions)
+}
+
+/// File writes/deletes for a unified diff (`git diff -U0` style), with paths
+/// made absolute under `root`
+pub fn diff_actions(diff: &str, root: &Path) -> Vec<PlannedAction> {
+    struct FileChange {
+        path: String,
+        deleted: bool,
+        added: Vec<String>,
+    }
+    let mut files: Vec<FileChange> = vec![];
+    for line in diff.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            // "a/<path> b/<path>"; the +++ line refines it when present
+            let path = rest
+                .rsplit_once(" b/")
+                .map(|(_, p)| p)
+                .unwrap_or(rest)
+                .to_string();
+            files.push(FileChange {
+                path,
+                deleted: false,
+                added: vec![],
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("deleted file mode") {
+            file.deleted = true;
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file.path = path.to_string();
+        } else if line.starts_with("+++ ") || line.starts_with("--- ") {
+            continue;
+        } else if let Some(added) = line.strip_prefix('+') {
+            file.added.push(added.to_string());
+        }
+    }
+
+    files
+        .into_iter()
+        .enumerate()
+        .map(|(i, f)| {
+            let target = root.join(&f.path).to_string_lossy().to_string();
+            let (action_type, content) = if f.deleted {
+                (ActionType::FileDelete, format!("rm {}", target))
+            } else {
+                (ActionType::FileWrite, f.added.join("\n"))
+            };
+            planned(i + 1, action_type, content, Some(target))
+        })
+        .collect()
+}
+
+/// Lowest risk level that fails a check ("info", "warning" or "critical")
+pub fn parse_fail_on(s: &str) -> anyhow::Result<RiskLevel> {
+    match s.to_lowercase().as_str() {
+        "info" => Ok(RiskLevel::Info),
+        "warning" => Ok(RiskLevel::Warning),
+        "critical" => Ok(RiskLevel::Critical),
+        other => anyhow::bail!("Unknown level '{}' (info, warning, critical)", other),
+    }
+}
+
+/// An action at or above the failure threshold
+#[derive(Debug, Clone, Serialize)]
+pub struct Violation {
+    pub line: usize,
+    pub content: String,
+    pub risk_level: RiskLevel,
+    pub matched_rules: Vec<String>,
+    pub explanation: String,
+}
+
+/// Actions whose analysis matched a rule at `fail_on` or above
+pub fn evaluate(
+    analyzer: &Analyzer,
+    actions: &[PlannedAction],
+    fail_on: RiskLevel,
+) -> Vec<Violation> {
+    actions
+        .iter()
+        .filter_map(|planned| {
+            let result = analyzer.analyze(&planned.action);
+            (!result.matched_rules.is_empty() && result.risk_level >= fail_on).then(|| Violation {
+                line: planned.line,
+                content: planned.action.content.clone(),
+                risk_level: result.risk_level,
+                matched_rules: result.matched_rules,
+                explanation: result.explanation,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::default_rules;
+
+    #[test]
+    fn test_check_commands_file() {
+        let text = r#"
+# build script
+cargo build --release
+sudo systemctl restart app
+{"action_type": "file_write", "content": "x", "target": "/etc/hosts"}
+rm -rf /
+"#;
+        let actions = parse_actions(text).unwrap();
+        assert_eq!(actions.len(), 4);
+        assert_eq!(actions[2].action.action_type, ActionType::FileWrite);
+        assert_eq!(actions[3].line, 6);
+
+        let analyzer = Analyzer::new(default_rules());
+        let critical = evaluate(&analyzer, &actions, RiskLevel::Critical);
+        assert!(critical.iter().any(|v| v.line == 6));
+        assert!(critical.iter().all(|v| v.risk_level == RiskLevel::Critical));
+        let warnings = evaluate(&analyzer, &actions, parse_fail_on("warning").unwrap());
+        assert!(warnings.len() > critical.len());
+        assert!(!warnings.iter().any(|v| v.line == 3));
+
+        assert!(parse_actions("{not json").is_err());
+        assert!(parse_fail_on("loud").is_err());
+    }
+
+    #[test]
+    fn test_diff_actions() {
+        let diff = r#"diff --git a/src/config.rs b/src/config.rs
+index 1111111..2222222 100644
+--- a/src/config.rs
++++ b/src/config.rs
+@@ -1,0 +2 @@
++let api_key = "sk-live-abcdefghijklmnopqrstuvwxyz0123456789";
+diff --git a/old.txt b/old.txt
+deleted file mode 100644
+index 3333333..0000000
+--- a/old.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+"#;
+        let actions = diff_actions(diff, Path::new("/repo"));
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].action.action_type, ActionType::FileWrite);
+        assert_eq!(
+            actions[0].action.target.as_deref(),
+            Some("/repo/src/config.rs")
+        );
+        assert!(actions[0].action.content.contains("sk-live-"));
+        assert_eq!(actions[1].action.action_type, ActionType::FileDelete);
+        assert_eq!(actions[1].action.target.as_deref(), Some("/repo/old.txt"));
+
+        let analyzer = Analyzer::new(default_rules());
+        let violations = evaluate(&analyzer, &actions, RiskLevel::Critical);
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0]
+            .matched_rules
+            .contains(&"api_key_exposure".to_string()));
+    }
+}
@@ -0,0 +1,280 @@
+//! Rule pack linting and expectation tests
+//!
+//! `rules lint` catches rules that can't work (bad regex, unknown template,
+//! missing parameters, duplicate names). `rules test-suite` runs YAML
+//! expectation files against a rule set so custom packs can be
+//! regression-tested as they change:
+//!
+//! ```yaml
+//! cases:
+//!   - name: blocks recursive delete of home
+//!     content: rm -rf ~/
+//!     expect:
+//!       matches: [dangerous_rm]
+//!       risk: critical
+//!   - name: plain listing is fine
+//!     content: ls -la
+//!     expect:
+//!       no_match: true
+//! ```
+
+use crate::analyzer::Analyzer;
+use crate::rules::{all_templates, self_protection_rules, MatchType, Rule};
+use crate::{ActionType, AgentAction, AgentType, RiskLevel};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A problem found by `lint`
+#[derive(Debug, Clone, Serialize)]
+pub struct LintIssue {
+    pub rule: String,
+    /// The rule can't work as written (as opposed to a warning)
+    pub error: bool,
+    pub message: String,
+}
+
+/// Check uncompiled rules (as parsed from YAML) for mistakes
+pub fn lint(rules: &[Rule]) -> Vec<LintIssue> {
+    let mut issues = vec![];
+    let mut issue = |rule: &Rule, error: bool, message: String| {
+        issues.push(LintIssue {
+            rule: rule.name.clone(),
+            error,
+            message,
+        })
+    };
+    let protected: HashSet<String> = self_protection_rules()
+        .into_iter()
+        .map(|r| r.name)
+        .collect();
+    let mut seen = HashSet::new();
+
+    for rule in rules {
+        if rule.name.trim().is_empty() {
+            issue(rule, true, "rule has no name".to_string());
+        } else if !seen.insert(rule.name.as_str()) {
+            issue(rule, true, "duplicate rule name".to_string());
+        }
+        if protected.contains(&rule.name) {
+            issue(
+                rule,
+                false,
+                "shares a name with a self-protection rule and will be replaced by it".to_string(),
+            );
+        }
+
+        match rule.match_type {
+            MatchType::Regex if rule.pattern.is_empty() => issue(
+                rule,
+                true,
+                "regex rule has an empty pattern and never matches".to_string(),
+            ),
+            MatchType::Regex => {
+                if let Err(e) = regex::Regex::new(&rule.pattern) {
+                    issue(rule, true, format!("invalid regex: {}", e));
+                }
+            }
+            MatchType::Keyword => match &rule.keyword {
+                None => issue(
+                    rule,
+                    true,
+                    "keyword rule has no keyword section".to_string(),
+                ),
+                Some(kw) => {
+                    let lists = [
+                        &kw.contains,
+                        &kw.starts_with,
+                        &kw.ends_with,
+                        &kw.glob,
+                        &kw.any_of,
+                    ];
+                    if lists.iter().all(|l| l.is_empty()) {
+                        issue(rule, true, "keyword rule has no keywords".to_string());
+                    }
+                    for g in &kw.glob {
+                        if let Err(e) = glob::Pattern::new(g) {
+                            issue(rule, true, format!("invalid glob '{}': {}", g, e));
+                        }
+                    }
+                }
+            },
+            MatchType::Template => {
+                let Some(name) = &rule.template else {
+                    issue(rule, true, "template rule names no template".to_string());
+                    continue;
+                };
+                let Some(template) = all_templates().into_iter().find(|t| t.name == name) else {
+                    issue(rule, true, format!("unknown template '{}'", name));
+                    continue;
+                };
+                let params = rule.params.clone().unwrap_or_default();
+                for required in template.required_params {
+                    let present = match *required {
+                        "path" => params.path.is_some() || !params.paths.is_empty(),
+                        "commands" => !params.commands.is_empty(),
+                        other => params.extra.contains_key(other),
+                    };
+                    if !present {
+                        issue(
+                            rule,
+                            true,
+                            format!("template '{}' needs parameter '{}'", name, required),
+                        );
+                    }
+                }
+            }
+        }
+
+        if !rule.enabled {
+            issue(rule, false, "rule is disabled".to_string());
+        }
+    }
+    issues
+}
+
+/// An expectation file
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestSuite {
+    #[serde(default)]
+    pub cases: Vec<TestCase>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TestCase {
+    pub name: String,
+    pub content: String,
+    #[serde(default = "exec")]
+    pub action_type: ActionType,
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Agent the action comes from (matters for agent-scoped rules)
+    #[serde(default)]
+    pub agent: Option<String>,
+    pub expect: Expectation,
+}
+
+fn exec() -> ActionType {
+    ActionType::Exec
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Expectation {
+    /// Rules that must match
+    #[serde(default)]
+    pub matches: Vec<String>,
+    /// Rules that must not match
+    #[serde(default)]
+    pub not_matches: Vec<String>,
+    /// Overall risk level
+    #[serde(default)]
+    pub risk: Option<RiskLevel>,
+    /// No rule may match
+    #[serde(default)]
+    pub no_match: bool,
+}
+
+/// Outcome of one case; passed when `failures` is empty
+#[derive(Debug, Clone, Serialize)]
+pub struct CaseResult {
+    pub name: String,
+    pub failures: Vec<String>,
+}
+
+/// Run every case of `suite` through `analyzer`
+pub fn run_suite(analyzer: &Analyzer, suite: &TestSuite) -> Vec<CaseResult> {
+    suite
+        .cases
+        .iter()
+        .map(|case| {
+            let action = AgentAction {
+                id: "suite".to_string(),
+                timestamp: chrono::Utc::now(),
+                agent: case
+                    .agent
+                    .as_deref()
+                    .map(AgentType::from_name)
+                    .unwrap_or(AgentType::Unknown),
+                action_type: case.action_type.clone(),
+                content: case.content.clone(),
+                target: case.target.clone(),
+                session_id: None,
+                metadata: None,
+            };
+            let result = analyzer.analyze(&action);
+            let matched = |name: &String| result.matched_rules.contains(name);
+
+            let mut failures = vec![];
+            for name in case.expect.matches.iter().filter(|n| !matched(n)) {
+                failures.push(format!("expected {} to match", name));
+            }
+            for name in case.expect.not_matches.iter().filter(|n| matched(n)) {
+                failures.push(format!("expected {} not to match", name));
+            }
+            if case.expect.no_match && !result.matched_rules.is_empty() {
+                failures.push(format!(
+                    "expected no match, got {}",
+                    result.matched_rules.join(", ")
+                ));
+            }
+            if let Some(risk) = case.expect.risk {
+                if result.risk_level != risk {
+                    failures.push(format!("expected risk {}, got {}", risk, result.risk_level));
+                }
+            }
+            CaseResult {
+                name: case.name.clone(),
+                failures,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::default_rules;
+
+    #[test]
+    fn test_lint_and_suite() {
+        let pack: Vec<Rule> = serde_yaml::from_str(
+            r#"
+- name: bad_regex
+  pattern: "rm -rf ("
+- name: no_params
+  match_type: template
+  template: protect_path
+- name: bad_regex
+  match_type: keyword
+  keyword: {}
+"#,
+        )
+        .unwrap();
+        let issues = lint(&pack);
+        let messages: Vec<&str> = issues.iter().map(|i| i.message.as_str()).collect();
+        assert!(messages.iter().any(|m| m.starts_with("invalid regex")));
+        assert!(messages.contains(&"template 'protect_path' needs parameter 'path'"));
+        assert!(messages.contains(&"duplicate rule name"));
+        assert!(messages.contains(&"keyword rule has no keywords"));
+        assert!(lint(&default_rules()).is_empty());
+
+        let suite: TestSuite = serde_yaml::from_str(
+            r#"
+cases:
+  - name: rm
+    content: rm -rf /
+    expect: {matches: [dangerous_rm], risk: critical}
+  - name: ls
+    content: ls -la
+    expect: {no_match: true}
+  - name: wrong
+    content: ls -la
+    expect: {matches: [dangerous_rm], risk: warning}
+"#,
+        )
+        .unwrap();
+        let results = run_suite(&Analyzer::new(default_rules()), &suite);
+        assert!(results[0].failures.is_empty());
+        assert!(results[1].failures.is_empty());
+        assert_eq!(results[2].failures.len(), 2);
+    }
+}
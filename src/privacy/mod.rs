@@ -0,0 +1,457 @@
+//! Data deletion and anonymization
+//!
+//! Purges everything stored about a session, a project path prefix or a
+//! time range: actions with their analyses, offloaded content, correlation
+//! links and journaled bus events, ontology snapshots derived from them, and weekly reports and
+//! decision notes that mention them. Every purge (but not a dry run) leaves an audit record
+//! describing the request and what was removed, never the removed data.
+
+use crate::blob::{self, BlobStore};
+use crate::brain::notes;
+use crate::brain::{
+    build_ontology_scoped, build_ontology_v2_scoped, persist_ontology, persist_ontology_v2,
+};
+use crate::db::DbPool;
+use crate::workspace;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+const REDACTED: &str = "[redacted]";
+
+/// What to purge; all given filters must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PurgeScope {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl PurgeScope {
+    pub fn is_empty(&self) -> bool {
+        self.session_id.is_none()
+            && self.path_prefix.is_none()
+            && self.from.is_none()
+            && self.to.is_none()
+    }
+
+    /// Identifying strings to remove from free-form files
+    fn needles(&self) -> Vec<&str> {
+        [self.session_id.as_deref(), self.path_prefix.as_deref()]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .collect()
+    }
+
+    fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.from.is_none_or(|from| end >= from) && self.to.is_none_or(|to| start <= to)
+    }
+}
+
+/// Delete rows outright, or keep them for statistics with content stripped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PurgeMode {
+    #[default]
+    Delete,
+    Anonymize,
+}
+
+impl std::fmt::Display for PurgeMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PurgeMode::Delete => write!(f, "delete"),
+            PurgeMode::Anonymize => write!(f, "anonymize"),
+        }
+    }
+}
+
+/// Outcome of a purge
+#[derive(Debug, Clone, Serialize)]
+pub struct PurgeSummary {
+    pub mode: PurgeMode,
+    pub dry_run: bool,
+    pub actions: usize,
+    pub analyses: usize,
+    /// Ontology snapshots rebuilt from the purged database
+    pub ontology_rebuilt: Vec<String>,
+    pub reports: usize,
+    /// Decision notes deleted or redacted
+    pub notes: usize,
+    pub audit_id: Option<i64>,
+}
+
+/// Stable pseudonym so anonymized actions still group by session
+fn session_alias(session_id: &str) -> String {
+    let digest = Sha256::digest(session_id.as_bytes());
+    format!(
+        "anon-{}",
+        digest[..6]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    )
+}
+
+/// Purge (or, with `dry_run`, count) everything in `scope`
+pub fn purge(
+    pool: &DbPool,
+    data_dir: &Path,
+    scope: &PurgeScope,
+    mode: PurgeMode,
+    dry_run: bool,
+    requested_by: &str,
+) -> anyhow::Result<PurgeSummary> {
+    if scope.is_empty() {
+        anyhow::bail!("Refusing to purge everything: give a session, path prefix or time range");
+    }
+
+    let db = pool.get()?;
+    let conn = pool.connection()?;
+    let ids = db.find_action_ids(
+        scope.session_id.as_deref(),
+        scope.path_prefix.as_deref(),
+        scope.from,
+        scope.to,
+    )?;
+
+    let mut summary = PurgeSummary {
+        mode,
+        dry_run,
+        actions: ids.len(),
+        analyses: 0,
+        ontology_rebuilt: vec![],
+        reports: workspace::partitions(data_dir)
+            .iter()
+            .map(|(_, dir)| purge_reports(dir, scope, mode, true))
+            .sum::<anyhow::Result<usize>>()?,
+        notes: purge_notes(&conn, scope, mode, true)?,
+        audit_id: None,
+    };
+    if dry_run {
+        return Ok(summary);
+    }
+
+    let mut blobs = vec![];
+    for id in &ids {
+        if let Some(hash) = db.get_action(id)?.as_ref().and_then(blob::blob_hash) {
+            blobs.push(hash.to_string());
+        }
+        summary.analyses += match mode {
+            PurgeMode::Delete => db.delete_action(id)?,
+            PurgeMode::Anonymize => {
+                let alias = db
+                    .get_action(id)?
+                    .and_then(|a| a.session_id)
+                    .map(|s| session_alias(&s));
+                db.anonymize_action(id, alias.as_deref())?
+            }
+        };
+    }
+
+    // Blobs are shared by identical content; keep those still referenced
+    let store = BlobStore::for_db(pool.path());
+    for hash in blobs {
+        if !db.blob_referenced(&hash)? {
+            store.remove(&hash);
+        }
+    }
+
+    // Before the rebuild, so deleted notes drop out of the ontology
+    summary.notes = purge_notes(&conn, scope, mode, false)?;
+    if !ids.is_empty() || summary.notes > 0 {
+        summary.ontology_rebuilt = rebuild_ontology(&conn, data_dir)?;
+    }
+    summary.reports = workspace::partitions(data_dir)
+        .iter()
+        .map(|(_, dir)| purge_reports(dir, scope, mode, false))
+        .sum::<anyhow::Result<usize>>()?;
+
+    summary.audit_id = Some(db.record_purge(
+        &mode.to_string(),
+        &serde_json::to_string(scope)?,
+        &serde_json::to_string(&summary)?,
+        requested_by,
+    )?);
+    Ok(summary)
+}
+
+/// Regenerate existing ontology snapshots, every workspace's included, so
+/// purged actions drop out
+fn rebuild_ontology(conn: &rusqlite::Connection, data_dir: &Path) -> anyhow::Result<Vec<String>> {
+    let mut rebuilt = vec![];
+    for (workspace, dir) in workspace::partitions(data_dir) {
+        let name = |version: &str| match &workspace {
+            Some(id) => format!("{}/{}", id, version),
+            None => version.to_string(),
+        };
+        let workspace = workspace.as_deref();
+        if dir.join("ontology/v1/nodes.jsonl").exists() {
+            let (nodes, edges) = build_ontology_scoped(conn, workspace)?;
+            persist_ontology(&dir, &nodes, &edges)?;
+            rebuilt.push(name("v1"));
+        }
+        if dir.join("ontology/v2/nodes.jsonl").exists() {
+            let (nodes, edges, insights) = build_ontology_v2_scoped(conn, workspace)?;
+            persist_ontology_v2(&dir, &nodes, &edges, &insights)?;
+            rebuilt.push(name("v2"));
+        }
+    }
+    Ok(rebuilt)
+}
+
+/// Weekly reports in scope: those overlapping the time range (if any) and
+/// mentioning the session or path (if given). Deleting removes the report
+/// and its ontology entries; anonymizing redacts the identifying strings,
+/// leaving the aggregate counts. Returns the number of reports affected.
+fn purge_reports(
+    data_dir: &Path,
+    scope: &PurgeScope,
+    mode: PurgeMode,
+    dry_run: bool,
+) -> anyhow::Result<usize> {
+    let needles = scope.needles();
+    if mode == PurgeMode::Anonymize && needles.is_empty() {
+        return Ok(0);
+    }
+    let Ok(entries) = fs::read_dir(data_dir.join("reports/weekly")) else {
+        return Ok(0);
+    };
+
+    let mut affected = 0;
+    let mut removed_ids = vec![];
+    for entry in entries.flatten() {
+        let json_path = entry.path();
+        if json_path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let text = fs::read_to_string(&json_path)?;
+        let Ok(report) = serde_json::from_str::<serde_json::Value>(&text) else {
+            continue;
+        };
+        let time = |key: &str| {
+            report[key]
+                .as_str()
+                .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.with_timezone(&Utc))
+        };
+        let in_range = match (time("week_start"), time("week_end")) {
+            (Some(start), Some(end)) => scope.overlaps(start, end),
+            _ => scope.from.is_none() && scope.to.is_none(),
+        };
+        if !in_range || !needles.iter().all(|n| text.contains(n)) {
+            continue;
+        }
+
+        affected += 1;
+        if dry_run {
+            continue;
+        }
+        let md_path = json_path.with_extension("md");
+        match mode {
+            PurgeMode::Delete => {
+                fs::remove_file(&json_path)?;
+                let _ = fs::remove_file(&md_path);
+                if let Some(id) = report["report_id"].as_str() {
+                    removed_ids.push(format!("report:{}", id));
+                }
+            }
+            PurgeMode::Anonymize => {
+                redact_file(&json_path, &needles)?;
+                if md_path.exists() {
+                    redact_file(&md_path, &needles)?;
+                }
+            }
+        }
+    }
+
+    if !dry_run && affected > 0 {
+        // Ontology entries materialized alongside weekly reports
+        for name in ["nodes.jsonl", "edges.jsonl"] {
+            let path = data_dir.join("ontology").join(name);
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let kept: Vec<String> = content
+                .lines()
+                .filter(|l| {
+                    !removed_ids
+                        .iter()
+                        .any(|id| l.contains(&format!("\"{}\"", id)))
+                })
+                .filter(|l| mode == PurgeMode::Anonymize || !needles.iter().any(|n| l.contains(n)))
+                .map(|l| redact(l, &needles))
+                .collect();
+            fs::write(&path, kept.join("\n") + "\n")?;
+        }
+    }
+    Ok(affected)
+}
+
+/// Decision notes in scope: those overlapping the time range (if any), of
+/// the session (if given) and mentioning the path (if given). Deleting
+/// removes the note and its record; anonymizing redacts the identifying
+/// strings and moves a purged session's note to its alias.
+fn purge_notes(
+    conn: &rusqlite::Connection,
+    scope: &PurgeScope,
+    mode: PurgeMode,
+    dry_run: bool,
+) -> anyhow::Result<usize> {
+    let needles = scope.needles();
+    let time = |t: &str| {
+        DateTime::parse_from_rfc3339(t)
+            .ok()
+            .map(|t| t.with_timezone(&Utc))
+    };
+    let mut affected = 0;
+    for note in notes::stored_notes(conn)? {
+        let text = fs::read_to_string(&note.path).unwrap_or_default();
+        let in_range = match (time(&note.started_at), time(&note.ended_at)) {
+            (Some(start), Some(end)) => scope.overlaps(start, end),
+            _ => scope.from.is_none() && scope.to.is_none(),
+        };
+        let of_session = scope
+            .session_id
+            .as_ref()
+            .is_none_or(|s| *s == note.session_id);
+        let mentions_path = scope
+            .path_prefix
+            .as_ref()
+            .is_none_or(|p| text.contains(p.as_str()));
+        if !in_range || !of_session || !mentions_path {
+            continue;
+        }
+
+        affected += 1;
+        if dry_run {
+            continue;
+        }
+        match mode {
+            PurgeMode::Delete => {
+                let _ = fs::remove_file(&note.path);
+                notes::forget_note(conn, &note.session_id)?;
+            }
+            PurgeMode::Anonymize => {
+                if note.path.exists() {
+                    redact_file(&note.path, &needles)?;
+                }
+                if scope.session_id.is_some() {
+                    notes::rename_note(conn, &note.session_id, &session_alias(&note.session_id))?;
+                }
+            }
+        }
+    }
+    Ok(affected)
+}
+
+fn redact(text: &str, needles: &[&str]) -> String {
+    needles
+        .iter()
+        .fold(text.to_string(), |acc, n| acc.replace(n, REDACTED))
+}
+
+fn redact_file(path: &Path, needles: &[&str]) -> anyhow::Result<()> {
+    let text = fs::read_to_string(path)?;
+    fs::write(path, redact(&text, needles))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentAction, AgentType};
+
+    fn action(id: &str, session: &str, target: &str) -> AgentAction {
+        AgentAction {
+            id: id.to_string(),
+            timestamp: Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::FileWrite,
+            content: format!("write {}", target),
+            target: Some(target.to_string()),
+            session_id: Some(session.to_string()),
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_purge_and_anonymize() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("h.db");
+        let data_dir = dir.path().join("data");
+        let pool = DbPool::open(&db_path).unwrap();
+        let db = pool.get().unwrap();
+        db.store_action(&action("a1", "s1", "/work/secret/a.txt"))
+            .unwrap();
+        db.store_action(&action("a2", "s1", "/work/app/b.txt"))
+            .unwrap();
+        db.store_action(&action("a3", "s2", "/work/secret/c.txt"))
+            .unwrap();
+
+        let conn = rusqlite::Connection::open(&db_path).unwrap();
+        let note = notes::build_note(&conn, "s1").unwrap().unwrap();
+        let note_path = notes::write_note(&conn, &data_dir, &note).unwrap();
+
+        let weekly = data_dir.join("reports/weekly");
+        fs::create_dir_all(&weekly).unwrap();
+        fs::write(
+            weekly.join("2026-W09.json"),
+            r#"{"report_id":"2026-W09","activity":{"projects":[{"project_id":"/work/secret"}]}}"#,
+        )
+        .unwrap();
+
+        let scope = PurgeScope {
+            session_id: Some("s1".to_string()),
+            ..Default::default()
+        };
+        assert!(purge(
+            &pool,
+            &data_dir,
+            &PurgeScope::default(),
+            PurgeMode::Delete,
+            false,
+            "t"
+        )
+        .is_err());
+
+        let dry = purge(&pool, &data_dir, &scope, PurgeMode::Delete, true, "t").unwrap();
+        assert_eq!((dry.actions, dry.notes), (2, 1));
+        assert!(dry.audit_id.is_none());
+        assert!(db.get_action("a1").unwrap().is_some());
+
+        let done = purge(&pool, &data_dir, &scope, PurgeMode::Delete, false, "t").unwrap();
+        assert_eq!((done.actions, done.notes), (2, 1));
+        assert!(!note_path.exists());
+        assert!(notes::stored_notes(&conn).unwrap().is_empty());
+        assert!(db.get_action("a1").unwrap().is_none());
+        assert!(db.get_action("a3").unwrap().is_some());
+        // The report doesn't mention s1
+        assert!(weekly.join("2026-W09.json").exists());
+
+        let scope = PurgeScope {
+            path_prefix: Some("/work/secret".to_string()),
+            ..Default::default()
+        };
+        let anon = purge(&pool, &data_dir, &scope, PurgeMode::Anonymize, false, "t").unwrap();
+        assert_eq!((anon.actions, anon.reports), (1, 1));
+        let a3 = db.get_action("a3").unwrap().unwrap();
+        assert_eq!(a3.content, REDACTED);
+        assert_eq!(a3.session_id, Some(session_alias("s2")));
+        let report = fs::read_to_string(weekly.join("2026-W09.json")).unwrap();
+        assert!(!report.contains("/work/secret"));
+
+        let audit = db.get_purge_audit(10).unwrap();
+        assert_eq!(audit.len(), 2);
+        assert_eq!(audit[0].mode, "anonymize");
+        assert!(audit[1].scope.contains("s1"));
+    }
+}
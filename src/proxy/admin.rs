@@ -0,0 +1,129 @@
+//! Admin endpoint of a running proxy
+//!
+//! Every other path is forwarded upstream, so the proxy reports on itself
+//! under a reserved prefix. `proxy status` reads it to show what the live
+//! process is doing instead of guessing from config.
+
+use super::config::ProxyMode;
+use super::health::UpstreamHealth;
+use super::interceptor::InterceptResult;
+use crate::db::EventCounters;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// Path of the status endpoint (never forwarded upstream)
+pub const STATUS_PATH: &str = "/_harness/status";
+
+/// Intercepts kept for the status endpoint
+const RECENT_CAPACITY: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyStatus {
+    pub version: String,
+    pub listen: String,
+    pub mode: ProxyMode,
+    pub started_at: String,
+    pub uptime_secs: u64,
+    /// Upstream currently receiving traffic
+    pub active_target: String,
+    pub upstreams: Vec<UpstreamHealth>,
+    /// Requests since this process started
+    pub counters: EventCounters,
+    /// Latest intercepts, newest first
+    pub recent_intercepts: Vec<RecentIntercept>,
+}
+
+/// Summary of an intercept; content is left out since the endpoint has no auth
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentIntercept {
+    pub timestamp: String,
+    pub tool_name: String,
+    pub rule_name: String,
+    pub risk_level: String,
+    pub blocked: bool,
+}
+
+#[derive(Default)]
+pub struct RecentIntercepts(Mutex<VecDeque<RecentIntercept>>);
+
+impl RecentIntercepts {
+    pub fn push(&self, intercept: &InterceptResult, blocked: bool) {
+        let Ok(mut recent) = self.0.lock() else {
+            return;
+        };
+        if recent.len() == RECENT_CAPACITY {
+            recent.pop_back();
+        }
+        recent.push_front(RecentIntercept {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            tool_name: intercept.tool_name.clone(),
+            rule_name: intercept.rule_name.clone(),
+            risk_level: intercept.risk_level.to_string(),
+            blocked,
+        });
+    }
+
+    pub fn list(&self) -> Vec<RecentIntercept> {
+        self.0
+            .lock()
+            .map(|r| r.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Status of the proxy listening on `addr`; None when nothing is listening
+pub async fn fetch(addr: &str) -> anyhow::Result<Option<ProxyStatus>> {
+    let url = format!("http://{}{}", addr, STATUS_PATH);
+    let resp = match reqwest::Client::new()
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(3))
+        .send()
+        .await
+    {
+        Ok(resp) => resp,
+        Err(e) if e.is_connect() => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    let status = resp.error_for_status()?.json().await.map_err(|_| {
+        anyhow::anyhow!("{} answered, but not like an OpenClaw Harness proxy", addr)
+    })?;
+    Ok(Some(status))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::RuleAction;
+    use crate::{ActionType, AgentType, RiskLevel};
+
+    #[test]
+    fn test_recent_intercepts_are_bounded() {
+        let intercept = |n: usize| InterceptResult {
+            block_index: 0,
+            tool_name: "exec".to_string(),
+            rule_name: format!("rule_{}", n),
+            action: RuleAction::CriticalAlert,
+            risk_level: RiskLevel::Critical,
+            reason: String::new(),
+            action_type: ActionType::Exec,
+            content: "secret".to_string(),
+            target: None,
+            agent: AgentType::Unknown,
+            session_id: None,
+            canary_token: None,
+            guidance: None,
+            field: None,
+            params: None,
+        };
+        let recent = RecentIntercepts::default();
+        for n in 0..RECENT_CAPACITY + 5 {
+            recent.push(&intercept(n), n % 2 == 0);
+        }
+        let list = recent.list();
+        assert_eq!(list.len(), RECENT_CAPACITY);
+        assert_eq!(list[0].rule_name, format!("rule_{}", RECENT_CAPACITY + 4));
+        assert!(list[0].blocked);
+        assert!(!serde_json::to_string(&list).unwrap().contains("secret"));
+    }
+}
@@ -0,0 +1,169 @@
+//! Attribute proxied requests to an agent and session
+//!
+//! Sources, strongest first:
+//! 1. Our own headers: `x-openclaw-agent`, `x-openclaw-session`
+//! 2. The proxy's configured agent (`--agent`)
+//! 3. User-Agent of well-known CLIs
+//! 4. Request body metadata (Anthropic `metadata.user_id`, OpenAI `user`)
+//! 5. A short hash of the API key, so requests from one credential group
+//!    together even when nothing else identifies them
+
+use crate::AgentType;
+use axum::http::HeaderMap;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// Header naming the agent explicitly
+pub const AGENT_HEADER: &str = "x-openclaw-agent";
+/// Header carrying the agent's session ID
+pub const SESSION_HEADER: &str = "x-openclaw-session";
+
+/// Who a proxied request (and the tool calls in its response) belongs to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attribution {
+    pub agent: AgentType,
+    pub session_id: Option<String>,
+}
+
+impl Default for Attribution {
+    fn default() -> Self {
+        Self {
+            agent: AgentType::Unknown,
+            session_id: None,
+        }
+    }
+}
+
+fn header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+}
+
+/// Guess the agent from a User-Agent string
+fn agent_from_user_agent(ua: &str) -> Option<AgentType> {
+    let ua = ua.to_lowercase();
+    let agent = if ua.contains("claude-cli") || ua.contains("claude-code") {
+        AgentType::ClaudeCode
+    } else if ua.contains("geminicli") || ua.contains("gemini-cli") {
+        AgentType::GeminiCli
+    } else if ua.contains("copilot") {
+        AgentType::CopilotCli
+    } else if ua.contains("openclaw") || ua.contains("clawdbot") {
+        AgentType::OpenClaw
+    } else if ua.contains("aider") {
+        AgentType::Aider
+    } else if ua.contains("openhands") {
+        AgentType::OpenHands
+    } else {
+        return None;
+    };
+    Some(agent)
+}
+
+/// Session from request body metadata.
+///
+/// Claude Code sends `metadata.user_id` as `user_<hash>_account_<id>_session_<uuid>`;
+/// we keep just the session part when present.
+fn session_from_body(body: &[u8]) -> Option<String> {
+    let json: Value = serde_json::from_slice(body).ok()?;
+    let id = json
+        .pointer("/metadata/user_id")
+        .or_else(|| json.get("user"))
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())?;
+    Some(match id.rsplit_once("_session_") {
+        Some((_, session)) => session.to_string(),
+        None => id.to_string(),
+    })
+}
+
+/// Stable pseudonymous ID for the request's API credential
+fn credential_hash(headers: &HeaderMap) -> Option<String> {
+    let key = header(headers, "x-api-key")
+        .or_else(|| header(headers, "x-goog-api-key"))
+        .or_else(|| header(headers, "authorization"))?;
+    let digest = Sha256::digest(key.as_bytes());
+    Some(format!(
+        "key-{}",
+        digest[..6]
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+    ))
+}
+
+/// Attribute a request using its headers and body.
+/// `configured` is the proxy-wide agent name, if one was set.
+pub fn attribute(headers: &HeaderMap, body: &[u8], configured: &AgentType) -> Attribution {
+    let agent = header(headers, AGENT_HEADER)
+        .map(AgentType::from_name)
+        .or_else(|| (*configured != AgentType::Unknown).then(|| configured.clone()))
+        .or_else(|| header(headers, "user-agent").and_then(agent_from_user_agent))
+        // Only Anthropic clients send this; still better than nothing
+        .or_else(|| header(headers, "anthropic-version").map(|_| AgentType::ClaudeCode))
+        .unwrap_or(AgentType::Unknown);
+
+    let session_id = header(headers, SESSION_HEADER)
+        .map(String::from)
+        .or_else(|| session_from_body(body))
+        .or_else(|| credential_hash(headers));
+
+    Attribution { agent, session_id }
+}
+
+/// Whether a header is ours and should not be forwarded upstream
+pub fn is_attribution_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case(AGENT_HEADER) || name.eq_ignore_ascii_case(SESSION_HEADER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut map = HeaderMap::new();
+        for (k, v) in pairs {
+            map.insert(*k, v.parse().unwrap());
+        }
+        map
+    }
+
+    #[test]
+    fn test_explicit_headers_win() {
+        let h = headers(&[
+            ("x-openclaw-agent", "build-bot"),
+            ("x-openclaw-session", "s-42"),
+            ("user-agent", "claude-cli/1.0.80 (external, cli)"),
+        ]);
+        let a = attribute(&h, b"{}", &AgentType::Unknown);
+        assert_eq!(a.agent, AgentType::Custom("build-bot".to_string()));
+        assert_eq!(a.session_id.as_deref(), Some("s-42"));
+    }
+
+    #[test]
+    fn test_user_agent_and_body_metadata() {
+        let h = headers(&[("user-agent", "claude-cli/1.0.80 (external, cli)")]);
+        let body = br#"{"metadata":{"user_id":"user_ab12_account_cd34_session_5f6e"}}"#;
+        let a = attribute(&h, body, &AgentType::Unknown);
+        assert_eq!(a.agent, AgentType::ClaudeCode);
+        assert_eq!(a.session_id.as_deref(), Some("5f6e"));
+    }
+
+    #[test]
+    fn test_falls_back_to_configured_agent_and_key_hash() {
+        let h = headers(&[("x-api-key", "sk-secret"), ("user-agent", "curl/8.0")]);
+        let a = attribute(&h, b"", &AgentType::OpenClaw);
+        assert_eq!(a.agent, AgentType::OpenClaw);
+        let session = a.session_id.unwrap();
+        assert!(session.starts_with("key-"));
+        assert!(!session.contains("secret"));
+        // Same key, same session
+        assert_eq!(
+            attribute(&h, b"", &AgentType::OpenClaw).session_id,
+            Some(session)
+        );
+    }
+}
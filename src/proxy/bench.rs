@@ -0,0 +1,343 @@
+//! End-to-end proxy overhead benchmark (`openclaw-harness bench proxy`)
+//!
+//! Starts a local mock provider that streams an Anthropic response (text,
+//! then a tool call no rule blocks, split across deltas the way Claude
+//! sends it) and drives the same concurrent load three ways: straight at
+//! the mock, through a proxy in monitor mode, and through one in enforce
+//! mode. Each request is timed until the last byte of its stream arrives.
+//! The proxies log to an in-memory database, so nothing on disk is touched.
+//!
+//! The mock answers instantly, so the numbers are the harness's own cost;
+//! against a real provider it is added to the model's latency.
+
+use super::config::{ProxyConfig, ProxyMode};
+use super::control::ProxyControl;
+use super::{bind_proxy, ProxyStorage};
+use crate::db::DbPool;
+use crate::rules::Rule;
+use axum::{body::Body, http::header, response::Response, routing::post, Router};
+use futures_util::StreamExt;
+use serde::Serialize;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+
+/// Requests sent before timing starts, per target
+const WARMUP_REQUESTS: usize = 10;
+/// Text deltas in each mock response
+const TEXT_DELTAS: usize = 40;
+/// Pieces the tool call's input is split into
+const INPUT_DELTAS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Timed requests per target
+    pub requests: usize,
+    /// Requests in flight at once
+    pub concurrency: usize,
+    pub rules: Vec<Rule>,
+}
+
+/// Latency and throughput of one target
+#[derive(Debug, Clone, Serialize)]
+pub struct TargetStats {
+    pub target: String,
+    pub requests: usize,
+    pub errors: usize,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+    pub requests_per_sec: f64,
+}
+
+/// What the proxy adds to the direct latency, at each percentile
+#[derive(Debug, Clone, Serialize)]
+pub struct Overhead {
+    pub mode: ProxyMode,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    /// Proxy throughput as a share of the direct throughput
+    pub throughput_ratio: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub requests: usize,
+    pub concurrency: usize,
+    /// SSE events in each response
+    pub events_per_response: usize,
+    pub direct: TargetStats,
+    pub monitor: TargetStats,
+    pub enforce: TargetStats,
+    pub overhead: Vec<Overhead>,
+}
+
+/// Run the benchmark
+pub async fn run(options: BenchOptions) -> anyhow::Result<BenchReport> {
+    if options.requests == 0 || options.concurrency == 0 {
+        anyhow::bail!("requests and concurrency must be at least 1");
+    }
+    let events = mock_stream();
+    let events_per_response = events.len();
+    let upstream = start_mock(events).await?;
+    let client = reqwest::Client::builder()
+        .pool_max_idle_per_host(options.concurrency)
+        .build()?;
+    // The database and its blobs, removed when done
+    let dir = std::env::temp_dir().join(format!("openclaw-harness-bench-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+
+    let direct = drive(&client, "direct", &upstream, &options).await;
+    let mut proxied = vec![];
+    for mode in [ProxyMode::Monitor, ProxyMode::Enforce] {
+        let proxy = start_proxy(mode, &upstream, &options.rules, dir.clone()).await?;
+        proxied.push(
+            drive(
+                &client,
+                &format!("{:?}", mode).to_lowercase(),
+                &proxy,
+                &options,
+            )
+            .await,
+        );
+    }
+    let _ = std::fs::remove_dir_all(&dir);
+    let enforce = proxied.pop().unwrap_or_else(|| direct.clone());
+    let monitor = proxied.pop().unwrap_or_else(|| direct.clone());
+
+    let overhead = [
+        (ProxyMode::Monitor, &monitor),
+        (ProxyMode::Enforce, &enforce),
+    ]
+    .into_iter()
+    .map(|(mode, stats)| Overhead {
+        mode,
+        p50_ms: stats.p50_ms - direct.p50_ms,
+        p90_ms: stats.p90_ms - direct.p90_ms,
+        p99_ms: stats.p99_ms - direct.p99_ms,
+        throughput_ratio: if direct.requests_per_sec > 0.0 {
+            stats.requests_per_sec / direct.requests_per_sec
+        } else {
+            0.0
+        },
+    })
+    .collect();
+    Ok(BenchReport {
+        requests: options.requests,
+        concurrency: options.concurrency,
+        events_per_response,
+        direct,
+        monitor,
+        enforce,
+        overhead,
+    })
+}
+
+/// SSE events of the mock response, each with its blank line
+fn mock_stream() -> Vec<String> {
+    let event =
+        |name: &str, data: serde_json::Value| format!("event: {}\ndata: {}\n\n", name, data);
+    let mut events = vec![
+        event(
+            "message_start",
+            json!({"type": "message_start", "message": {
+                "id": "msg_bench", "type": "message", "role": "assistant",
+                "model": "bench", "content": [], "stop_reason": null,
+                "usage": {"input_tokens": 1200, "output_tokens": 1}
+            }}),
+        ),
+        event(
+            "content_block_start",
+            json!({"type": "content_block_start", "index": 0,
+                   "content_block": {"type": "text", "text": ""}}),
+        ),
+    ];
+    for i in 0..TEXT_DELTAS {
+        events.push(event(
+            "content_block_delta",
+            json!({"type": "content_block_delta", "index": 0,
+                   "delta": {"type": "text_delta", "text": format!("Checking part {} of the build. ", i)}}),
+        ));
+    }
+    events.push(event(
+        "content_block_stop",
+        json!({"type": "content_block_stop", "index": 0}),
+    ));
+    events.push(event(
+        "content_block_start",
+        json!({"type": "content_block_start", "index": 1, "content_block": {
+            "type": "tool_use", "id": "toolu_bench", "name": "Bash", "input": {}
+        }}),
+    ));
+    let input =
+        json!({"command": "cargo test --workspace -- --nocapture", "description": "Run the tests"})
+            .to_string();
+    let chars: Vec<char> = input.chars().collect();
+    for piece in chars.chunks(chars.len().div_ceil(INPUT_DELTAS)) {
+        events.push(event(
+            "content_block_delta",
+            json!({"type": "content_block_delta", "index": 1, "delta": {
+                "type": "input_json_delta", "partial_json": piece.iter().collect::<String>()
+            }}),
+        ));
+    }
+    events.push(event(
+        "content_block_stop",
+        json!({"type": "content_block_stop", "index": 1}),
+    ));
+    events.push(event(
+        "message_delta",
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"},
+               "usage": {"output_tokens": 180}}),
+    ));
+    events.push(event("message_stop", json!({"type": "message_stop"})));
+    events
+}
+
+/// A provider streaming `events` to every POST, one chunk per event;
+/// returns its base URL
+async fn start_mock(events: Vec<String>) -> anyhow::Result<String> {
+    let events = Arc::new(events);
+    let app = Router::new().fallback(post(move || {
+        let events = events.clone();
+        async move {
+            let chunks = (0..events.len())
+                .map(move |i| Ok::<_, std::convert::Infallible>(events[i].clone()));
+            Response::builder()
+                .header(header::CONTENT_TYPE, "text/event-stream")
+                .body(Body::from_stream(futures_util::stream::iter(chunks)))
+                .unwrap_or_default()
+        }
+    }));
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let url = format!("http://{}", listener.local_addr()?);
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok(url)
+}
+
+/// A proxy in `mode` in front of `upstream`; returns its base URL
+async fn start_proxy(
+    mode: ProxyMode,
+    upstream: &str,
+    rules: &[Rule],
+    dir: PathBuf,
+) -> anyhow::Result<String> {
+    let config = ProxyConfig {
+        listen: "127.0.0.1:0".to_string(),
+        target: upstream.to_string(),
+        mode,
+        health_check_interval_secs: 0,
+        expose: false,
+        ..Default::default()
+    };
+    let storage = ProxyStorage::shared(DbPool::open(&dir.join("bench.db"))?);
+    let control = ProxyControl::new(mode, rules.to_vec());
+    let proxy = bind_proxy(config, None, control, None, storage).await?;
+    let url = format!("http://{}", proxy.addrs[0]);
+    tokio::spawn(proxy.serve());
+    Ok(url)
+}
+
+/// One streaming request, read to the end
+async fn request(client: &reqwest::Client, base_url: &str) -> anyhow::Result<Duration> {
+    let body = json!({
+        "model": "bench",
+        "stream": true,
+        "max_tokens": 1024,
+        "messages": [{"role": "user", "content": "Run the tests."}]
+    });
+    let started = Instant::now();
+    let response = client
+        .post(format!("{}/v1/messages", base_url))
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        chunk?;
+    }
+    Ok(started.elapsed())
+}
+
+/// Send the timed load at `base_url`
+async fn drive(
+    client: &reqwest::Client,
+    target: &str,
+    base_url: &str,
+    options: &BenchOptions,
+) -> TargetStats {
+    for _ in 0..WARMUP_REQUESTS {
+        let _ = request(client, base_url).await;
+    }
+    let started = Instant::now();
+    let results: Vec<anyhow::Result<Duration>> = futures_util::stream::iter(0..options.requests)
+        .map(|_| request(client, base_url))
+        .buffer_unordered(options.concurrency)
+        .collect()
+        .await;
+    let elapsed = started.elapsed();
+
+    let mut latencies: Vec<Duration> = results
+        .iter()
+        .filter_map(|r| r.as_ref().ok().copied())
+        .collect();
+    latencies.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    TargetStats {
+        target: target.to_string(),
+        requests: options.requests,
+        errors: options.requests - latencies.len(),
+        p50_ms: ms(percentile(&latencies, 50.0)),
+        p90_ms: ms(percentile(&latencies, 90.0)),
+        p99_ms: ms(percentile(&latencies, 99.0)),
+        max_ms: ms(latencies.last().copied().unwrap_or_default()),
+        requests_per_sec: latencies.len() as f64 / elapsed.as_secs_f64(),
+    }
+}
+
+/// Nearest-rank percentile of sorted `latencies`
+fn percentile(latencies: &[Duration], p: f64) -> Duration {
+    if latencies.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((p / 100.0) * latencies.len() as f64).ceil() as usize;
+    latencies[rank.clamp(1, latencies.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::default_rules;
+
+    #[test]
+    fn test_percentile() {
+        let latencies: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(percentile(&latencies, 50.0), Duration::from_millis(50));
+        assert_eq!(percentile(&latencies, 99.0), Duration::from_millis(99));
+        assert_eq!(percentile(&latencies[..1], 90.0), Duration::from_millis(1));
+        assert_eq!(percentile(&[], 50.0), Duration::ZERO);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_bench_proxy() {
+        let report = run(BenchOptions {
+            requests: 20,
+            concurrency: 4,
+            rules: default_rules(),
+        })
+        .await
+        .unwrap();
+        for stats in [&report.direct, &report.monitor, &report.enforce] {
+            assert_eq!((stats.requests, stats.errors), (20, 0), "{}", stats.target);
+            assert!(stats.p50_ms > 0.0 && stats.p50_ms <= stats.p99_ms);
+            assert!(stats.requests_per_sec > 0.0);
+        }
+        assert_eq!(report.overhead.len(), 2);
+        assert!(report.events_per_response > TEXT_DELTAS + 7);
+    }
+}
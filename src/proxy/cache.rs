@@ -0,0 +1,138 @@
+//! Response cache for idempotent upstream GETs
+//!
+//! Agents poll provider metadata (model lists and the like) often; caching
+//! successful GET responses for a short TTL saves latency and rate limit.
+//! Entries are keyed by path, query and a hash of the credentials, so one
+//! API key never sees responses fetched with another.
+
+use axum::http::HeaderMap;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Most responses kept at once
+const MAX_ENTRIES: usize = 256;
+/// Largest response body cached
+const MAX_BODY: usize = 1024 * 1024;
+/// Headers that carry credentials
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "x-api-key", "x-goog-api-key", "api-key"];
+
+/// A cached upstream response
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: bytes::Bytes,
+}
+
+pub struct ResponseCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, CachedResponse)>>,
+}
+
+impl ResponseCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cache key for a GET of `path_and_query` with these request headers
+    pub fn key(path_and_query: &str, headers: &HeaderMap) -> String {
+        let mut hasher = Sha256::new();
+        for name in CREDENTIAL_HEADERS {
+            if let Some(v) = headers.get(*name) {
+                hasher.update(name.as_bytes());
+                hasher.update(v.as_bytes());
+            }
+        }
+        let digest = hasher.finalize();
+        let creds: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        format!("{} {}", creds, path_and_query)
+    }
+
+    pub fn get(&self, key: &str) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some((expires, resp)) if *expires > Instant::now() => Some(resp.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a response if it's cacheable: 200 OK, not too large, and not
+    /// marked `no-store`/`private` by the upstream
+    pub fn insert(&self, key: String, response: CachedResponse) {
+        if response.status != 200 || response.body.len() > MAX_BODY {
+            return;
+        }
+        let no_store = response.headers.iter().any(|(name, value)| {
+            name.eq_ignore_ascii_case("cache-control")
+                && (value.contains("no-store") || value.contains("private"))
+        });
+        if no_store {
+            return;
+        }
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        let now = Instant::now();
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, (expires, _)| *expires > now);
+        }
+        if entries.len() >= MAX_ENTRIES {
+            // Still full: drop whatever expires soonest
+            if let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, (expires, _))| *expires)
+                .map(|(k, _)| k.clone())
+            {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, (now + self.ttl, response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(cache_control: Option<&str>) -> CachedResponse {
+        CachedResponse {
+            status: 200,
+            headers: cache_control
+                .map(|v| vec![("cache-control".to_string(), v.to_string())])
+                .unwrap_or_default(),
+            body: bytes::Bytes::from_static(b"{\"data\":[]}"),
+        }
+    }
+
+    #[test]
+    fn test_cache_keys_and_expiry() {
+        let mut alice = HeaderMap::new();
+        alice.insert("x-api-key", "key-a".parse().unwrap());
+        let mut bob = HeaderMap::new();
+        bob.insert("x-api-key", "key-b".parse().unwrap());
+        let key_a = ResponseCache::key("/v1/models", &alice);
+        assert_ne!(key_a, ResponseCache::key("/v1/models", &bob));
+        assert!(!key_a.contains("key-a"));
+
+        let cache = ResponseCache::new(Duration::from_secs(60));
+        cache.insert(key_a.clone(), response(None));
+        assert!(cache.get(&key_a).is_some());
+        assert!(cache.get(&ResponseCache::key("/v1/models", &bob)).is_none());
+
+        cache.insert("nostore".to_string(), response(Some("no-store")));
+        assert!(cache.get("nostore").is_none());
+
+        let expired = ResponseCache::new(Duration::ZERO);
+        expired.insert(key_a.clone(), response(None));
+        assert!(expired.get(&key_a).is_none());
+    }
+}
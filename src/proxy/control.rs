@@ -0,0 +1,191 @@
+//! Settings a running proxy picks up without a restart
+//!
+//! The proxy reads its mode and rules from a `ProxyControl` on every
+//! request. The web server holds the same handle when both run in one
+//! process, so `PUT /api/proxy/config` and rule edits apply to the next
+//! request.
+//!
+//! A lockdown (see `enforcer::lockdown`) puts every listener in enforce
+//! mode and applies stricter overrides to the rules until it is lifted;
+//! mode switches and rule edits made meanwhile take effect afterwards.
+
+use super::config::ProxyMode;
+use crate::rules::profiles::{self, SeverityOverride};
+use crate::rules::Rule;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+
+pub struct ProxyControl {
+    mode: watch::Sender<ProxyMode>,
+    rules: Arc<RwLock<RuleState>>,
+}
+
+/// Rules as configured, and as in effect
+struct RuleState {
+    configured: Arc<Vec<Rule>>,
+    effective: Arc<Vec<Rule>>,
+    /// Overrides of the current lockdown, if one is on
+    lockdown: Option<Vec<SeverityOverride>>,
+}
+
+impl RuleState {
+    fn update(&mut self) {
+        self.effective = match &self.lockdown {
+            Some(overrides) => {
+                let mut rules = self.configured.to_vec();
+                profiles::apply(&mut rules, overrides);
+                Arc::new(rules)
+            }
+            None => self.configured.clone(),
+        };
+    }
+}
+
+impl ProxyControl {
+    /// `rules` must already be compiled
+    pub fn new(mode: ProxyMode, rules: Vec<Rule>) -> Arc<Self> {
+        let rules = Arc::new(rules);
+        Arc::new(Self {
+            mode: watch::Sender::new(mode),
+            rules: Arc::new(RwLock::new(RuleState {
+                configured: rules.clone(),
+                effective: rules,
+                lockdown: None,
+            })),
+        })
+    }
+
+    /// Control with its own mode, sharing (and seeing changes to) these rules
+    pub fn with_mode(&self, mode: ProxyMode) -> Arc<Self> {
+        Arc::new(Self {
+            mode: watch::Sender::new(mode),
+            rules: self.rules.clone(),
+        })
+    }
+
+    /// Mode in effect: enforce during a lockdown
+    pub fn mode(&self) -> ProxyMode {
+        if self.locked_down() {
+            ProxyMode::Enforce
+        } else {
+            *self.mode.borrow()
+        }
+    }
+
+    pub fn enforce(&self) -> bool {
+        self.mode() == ProxyMode::Enforce
+    }
+
+    /// Switch mode; returns the previous one. A lockdown still enforces
+    /// until it is lifted.
+    pub fn set_mode(&self, mode: ProxyMode) -> ProxyMode {
+        self.mode.send_replace(mode)
+    }
+
+    /// Notified on every mode switch
+    pub fn watch_mode(&self) -> watch::Receiver<ProxyMode> {
+        self.mode.subscribe()
+    }
+
+    /// Rules in effect; requests in flight keep the set they started with
+    pub fn rules(&self) -> Arc<Vec<Rule>> {
+        self.read(|state| state.effective.clone())
+    }
+
+    /// Replace the rules (compiled) used for subsequent requests
+    pub fn set_rules(&self, rules: Vec<Rule>) {
+        self.write(|state| {
+            state.configured = Arc::new(rules);
+            state.update();
+        });
+    }
+
+    /// Enforce, with `overrides` applied to the rules, on every listener
+    /// until `lift_lockdown`
+    pub fn lock_down(&self, overrides: Vec<SeverityOverride>) {
+        self.write(|state| {
+            state.lockdown = Some(overrides);
+            state.update();
+        });
+    }
+
+    pub fn lift_lockdown(&self) {
+        self.write(|state| {
+            state.lockdown = None;
+            state.update();
+        });
+    }
+
+    pub fn locked_down(&self) -> bool {
+        self.read(|state| state.lockdown.is_some())
+    }
+
+    fn read<T>(&self, f: impl FnOnce(&RuleState) -> T) -> T {
+        match self.rules.read() {
+            Ok(state) => f(&state),
+            Err(e) => f(&e.into_inner()),
+        }
+    }
+
+    fn write(&self, f: impl FnOnce(&mut RuleState)) {
+        match self.rules.write() {
+            Ok(mut state) => f(&mut state),
+            Err(e) => f(&mut e.into_inner()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::default_rules;
+
+    #[test]
+    fn test_switch_mode_and_rules() {
+        let control = ProxyControl::new(ProxyMode::Monitor, default_rules());
+        let mut watcher = control.watch_mode();
+        let in_flight = control.rules();
+
+        assert!(!control.enforce());
+        assert_eq!(control.set_mode(ProxyMode::Enforce), ProxyMode::Monitor);
+        assert!(control.enforce());
+        assert!(watcher.has_changed().unwrap());
+        assert_eq!(*watcher.borrow_and_update(), ProxyMode::Enforce);
+
+        let pinned = control.with_mode(ProxyMode::Monitor);
+        control.set_rules(vec![]);
+        assert!(control.rules().is_empty());
+        assert!(pinned.rules().is_empty());
+        assert!(!pinned.enforce());
+        assert_eq!(in_flight.len(), default_rules().len());
+    }
+
+    #[test]
+    fn test_lockdown_overrides_mode_and_rules() {
+        let control = ProxyControl::new(ProxyMode::Monitor, default_rules());
+        let listener = control.with_mode(ProxyMode::Monitor);
+        let stricter: Vec<SeverityOverride> =
+            serde_yaml::from_str("- from: warning\n  risk_level: critical\n").unwrap();
+        let warnings = |c: &ProxyControl| {
+            c.rules()
+                .iter()
+                .filter(|r| r.risk_level == crate::RiskLevel::Warning)
+                .count()
+        };
+        assert!(warnings(&control) > 0);
+
+        control.lock_down(stricter);
+        assert!(listener.locked_down());
+        assert!(control.enforce() && listener.enforce());
+        assert_eq!(warnings(&listener), 0);
+        // Edits while locked down keep the overrides, and take effect after
+        control.set_mode(ProxyMode::Monitor);
+        control.set_rules(default_rules());
+        assert!(control.enforce());
+        assert_eq!(warnings(&control), 0);
+
+        control.lift_lockdown();
+        assert!(!control.enforce() && !listener.enforce());
+        assert!(warnings(&control) > 0);
+    }
+}
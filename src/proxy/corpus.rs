@@ -0,0 +1,301 @@
+//! Simulation corpus — recorded provider responses replayed through the
+//! interceptor (`openclaw-harness replay --corpus`)
+//!
+//! Each fixture is an anonymized API response an agent received, with the
+//! intercepts the proxy is expected to make. Replaying the corpus after a
+//! rule or parser change shows any decision that moved. Fixtures are YAML
+//! files, one per response, found anywhere under the corpus directory:
+//!
+//! ```yaml
+//! name: anthropic wipes the home directory
+//! provider: anthropic        # anthropic, openai or gemini
+//! streaming: false           # response is raw SSE text when true
+//! mode: enforce              # or monitor (default enforce)
+//! response: |
+//!   {"type": "message", "content": [...]}
+//! expect:                    # in response order; empty: nothing intercepted
+//!   - tool: Bash
+//!     rule: dangerous_rm
+//!     behavior: replace      # replace or flag
+//! ```
+//!
+//! Besides the intercepts, a replay checks that the provider is recognized
+//! and that what the agent would receive carries a block message exactly
+//! when a tool call was replaced.
+
+use super::config::ProxyMode;
+use super::interceptor::{
+    behavior, detect_provider, intercept_response, ApiProvider, InterceptResult, MessageOptions,
+    ProxyBehavior,
+};
+use super::streaming::{parse_sse_events, SseLineBuffer, StreamInterceptor};
+use crate::rules::Rule;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Text every block message starts with
+const BLOCK_MARKER: &str = "OpenClaw Harness blocked";
+
+/// One recorded response
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fixture {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub provider: ApiProvider,
+    #[serde(default)]
+    pub streaming: bool,
+    #[serde(default = "enforce")]
+    pub mode: ProxyMode,
+    /// Response body as received: JSON, or SSE text when streaming
+    pub response: String,
+    #[serde(default)]
+    pub expect: Vec<ExpectedIntercept>,
+}
+
+fn enforce() -> ProxyMode {
+    ProxyMode::Enforce
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExpectedIntercept {
+    pub tool: String,
+    pub rule: String,
+    pub behavior: ProxyBehavior,
+}
+
+impl std::fmt::Display for ExpectedIntercept {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let behavior = match self.behavior {
+            ProxyBehavior::Replace => "replace",
+            ProxyBehavior::Flag => "flag",
+            ProxyBehavior::Passthrough => "passthrough",
+        };
+        write!(f, "{} by {} ({})", self.tool, self.rule, behavior)
+    }
+}
+
+/// Outcome of one fixture; passed when `failures` is empty
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayResult {
+    pub name: String,
+    pub file: PathBuf,
+    pub intercepts: Vec<ExpectedIntercept>,
+    pub failures: Vec<String>,
+}
+
+/// Fixtures under `path` (a directory, searched recursively, or one file),
+/// in path order
+pub fn load(path: &Path) -> anyhow::Result<Vec<(PathBuf, Fixture)>> {
+    let mut files = vec![];
+    if path.is_dir() {
+        collect(path, &mut files)?;
+        files.sort();
+    } else {
+        files.push(path.to_path_buf());
+    }
+    files
+        .into_iter()
+        .map(|file| {
+            let content = std::fs::read_to_string(&file)
+                .map_err(|e| anyhow::anyhow!("Cannot read {}: {}", file.display(), e))?;
+            let fixture: Fixture = serde_yaml::from_str(&content)
+                .map_err(|e| anyhow::anyhow!("{}: {}", file.display(), e))?;
+            Ok((file, fixture))
+        })
+        .collect()
+}
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|ext| ext == "yaml" || ext == "yml")
+        {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Run one fixture through the interceptor with `rules`
+pub fn replay(file: &Path, fixture: &Fixture, rules: &[Rule]) -> ReplayResult {
+    let enforce = fixture.mode == ProxyMode::Enforce;
+    let (provider, output, intercepts) = if fixture.streaming {
+        replay_stream(&fixture.response, rules, enforce)
+    } else {
+        let body = fixture.response.trim().as_bytes();
+        let (output, intercepts) = intercept_response(
+            body,
+            rules,
+            enforce,
+            MessageOptions::default(),
+            &Default::default(),
+        );
+        (
+            Some(detect_provider(body)),
+            String::from_utf8_lossy(&output).into_owned(),
+            intercepts,
+        )
+    };
+    let got: Vec<ExpectedIntercept> = intercepts
+        .iter()
+        .map(|i| ExpectedIntercept {
+            tool: i.tool_name.clone(),
+            rule: i.rule_name.clone(),
+            behavior: behavior(Some(i), enforce),
+        })
+        .collect();
+
+    let mut failures = vec![];
+    if provider != Some(fixture.provider) {
+        failures.push(format!(
+            "provider: expected {:?}, detected {:?}",
+            fixture.provider, provider
+        ));
+    }
+    if !fixture.streaming && serde_json::from_str::<serde_json::Value>(&output).is_err() {
+        failures.push("the rewritten response is not valid JSON".to_string());
+    }
+    failures.extend(compare(&fixture.expect, &got));
+    let replaced = got.iter().any(|i| i.behavior == ProxyBehavior::Replace);
+    match (replaced, output.contains(BLOCK_MARKER)) {
+        (true, false) => {
+            failures.push("a tool call was replaced but no block message was sent".to_string())
+        }
+        (false, true) => {
+            failures.push("a block message was sent but nothing was replaced".to_string())
+        }
+        _ => {}
+    }
+
+    ReplayResult {
+        name: fixture.name.clone(),
+        file: file.to_path_buf(),
+        intercepts: got,
+        failures,
+    }
+}
+
+/// Feed SSE text through the stream interceptor the way the proxy does;
+/// returns the provider it recognized, what the agent receives, and the
+/// intercepts
+fn replay_stream(
+    raw: &str,
+    rules: &[Rule],
+    enforce: bool,
+) -> (Option<ApiProvider>, String, Vec<InterceptResult>) {
+    let mut interceptor = StreamInterceptor::new(rules.to_vec(), enforce);
+    let mut lines = SseLineBuffer::new();
+    let mut output = String::new();
+    let blocks = lines.feed(raw).into_iter().chain(lines.finish());
+    for block in blocks {
+        for event in parse_sse_events(&block) {
+            for out in interceptor.process_event(event) {
+                output.push_str(&String::from_utf8_lossy(&out.to_sse_bytes()));
+            }
+        }
+    }
+    (interceptor.provider(), output, interceptor.intercepts)
+}
+
+/// Differences between expected and actual intercepts, in response order
+fn compare(expected: &[ExpectedIntercept], got: &[ExpectedIntercept]) -> Vec<String> {
+    if expected == got {
+        return vec![];
+    }
+    let mut failures = vec![];
+    for (i, want) in expected.iter().enumerate() {
+        match got.get(i) {
+            Some(have) if have == want => {}
+            Some(have) => failures.push(format!(
+                "intercept {}: expected {}, got {}",
+                i + 1,
+                want,
+                have
+            )),
+            None => failures.push(format!("intercept {}: expected {}, got none", i + 1, want)),
+        }
+    }
+    for (i, have) in got.iter().enumerate().skip(expected.len()) {
+        failures.push(format!("intercept {}: unexpected {}", i + 1, have));
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::default_rules;
+
+    const RM: &str = r#"{"type":"message","role":"assistant","content":[
+        {"type":"tool_use","id":"toolu_1","name":"Bash","input":{"command":"rm -rf ~/"}}]}"#;
+
+    fn fixture(yaml: &str) -> Fixture {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn test_replay_fixture() {
+        let rules = default_rules();
+        let mut rm = fixture(&format!(
+            "name: rm\nprovider: anthropic\nresponse: '{}'\nexpect:\n  \
+             - {{tool: Bash, rule: dangerous_rm, behavior: replace}}\n",
+            RM.replace('\n', " ")
+        ));
+        let result = replay(Path::new("rm.yaml"), &rm, &rules);
+        assert!(result.failures.is_empty(), "{:?}", result.failures);
+
+        // Monitor mode only flags it
+        rm.mode = ProxyMode::Monitor;
+        let result = replay(Path::new("rm.yaml"), &rm, &rules);
+        assert_eq!(
+            result.failures,
+            ["intercept 1: expected Bash by dangerous_rm (replace), got Bash by dangerous_rm (flag)"]
+        );
+
+        rm.provider = ApiProvider::OpenAI;
+        rm.expect.clear();
+        let result = replay(Path::new("rm.yaml"), &rm, &rules);
+        assert_eq!(result.failures.len(), 2);
+        assert_eq!(
+            result.failures[0],
+            "provider: expected OpenAI, detected Some(Anthropic)"
+        );
+        assert_eq!(
+            result.failures[1],
+            "intercept 1: unexpected Bash by dangerous_rm (flag)"
+        );
+
+        // The same call streamed
+        let stream = fixture(
+            r#"
+name: streamed
+provider: anthropic
+streaming: true
+response: |
+  event: message_start
+  data: {"type":"message_start","message":{"id":"msg_1","role":"assistant","content":[]}}
+
+  event: content_block_start
+  data: {"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_1","name":"Bash","input":{}}}
+
+  event: content_block_delta
+  data: {"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"command\": \"rm -rf ~/\"}"}}
+
+  event: content_block_stop
+  data: {"type":"content_block_stop","index":0}
+
+  event: message_stop
+  data: {"type":"message_stop"}
+expect:
+  - {tool: Bash, rule: dangerous_rm, behavior: replace}
+"#,
+        );
+        let result = replay(Path::new("streamed.yaml"), &stream, &rules);
+        assert!(result.failures.is_empty(), "{:?}", result.failures);
+    }
+}
@@ -0,0 +1,236 @@
+//! Outbound request volume per session
+//!
+//! Every API request carries the conversation so far, so request bodies grow
+//! steadily over a session. Bulk data pushed out through prompts shows up as
+//! a session sending far more than usual, or as one request growing much
+//! faster than the session's previous ones. Alerts, never blocks:
+//!
+//! - volume: the session's bytes today crossed
+//!   `OPENCLAW_HARNESS_SESSION_EGRESS_MB`
+//! - spike: a request grew by at least 1 MiB and by more than
+//!   `OPENCLAW_HARNESS_EGRESS_SPIKE_FACTOR` (default 10, 0 disables) times
+//!   the session's average growth
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Smallest growth reported as a spike, so short sessions don't alert on
+/// a pasted log file
+const SPIKE_MIN_BYTES: u64 = 1024 * 1024;
+/// Requests needed before a session has a baseline
+const BASELINE_REQUESTS: u64 = 3;
+/// Sessions kept in memory; the least recently seen go first
+const MAX_SESSIONS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+pub struct EgressLimits {
+    pub session_bytes: Option<u64>,
+    pub spike_factor: f64,
+}
+
+impl Default for EgressLimits {
+    fn default() -> Self {
+        Self {
+            session_bytes: None,
+            spike_factor: 10.0,
+        }
+    }
+}
+
+impl EgressLimits {
+    /// From `OPENCLAW_HARNESS_SESSION_EGRESS_MB` and
+    /// `OPENCLAW_HARNESS_EGRESS_SPIKE_FACTOR`
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| *v >= 0.0)
+        };
+        let defaults = Self::default();
+        Self {
+            session_bytes: var("OPENCLAW_HARNESS_SESSION_EGRESS_MB")
+                .filter(|mb| *mb > 0.0)
+                .map(|mb| (mb * 1024.0 * 1024.0) as u64),
+            spike_factor: var("OPENCLAW_HARNESS_EGRESS_SPIKE_FACTOR")
+                .unwrap_or(defaults.spike_factor),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EgressAlert {
+    /// The session's bytes crossed the limit
+    Volume { total: u64, limit: u64 },
+    /// One request grew far beyond the session's usual growth
+    Spike {
+        size: u64,
+        growth: u64,
+        baseline: u64,
+    },
+}
+
+impl EgressAlert {
+    pub fn message(&self, session: &str) -> String {
+        match self {
+            EgressAlert::Volume { total, limit } => format!(
+                "📤 Session {} sent {} to the provider (limit {})",
+                session,
+                human_bytes(*total),
+                human_bytes(*limit)
+            ),
+            EgressAlert::Spike {
+                size,
+                growth,
+                baseline,
+            } => format!(
+                "📤 Session {} request jumped by {} to {} (usually +{} per request) — possible bulk upload through the prompt",
+                session,
+                human_bytes(*growth),
+                human_bytes(*size),
+                human_bytes(*baseline)
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct SessionEgress {
+    day: String,
+    /// Bytes sent on `day`
+    bytes: u64,
+    requests: u64,
+    last_size: u64,
+    /// Sum of request growth, for the average
+    growth_sum: u64,
+    last_seen: u64,
+}
+
+/// Per-session volume and growth baseline
+#[derive(Debug, Default)]
+pub struct EgressTracker {
+    limits: EgressLimits,
+    sessions: Mutex<HashMap<String, SessionEgress>>,
+    seen: std::sync::atomic::AtomicU64,
+}
+
+impl EgressTracker {
+    pub fn new(limits: EgressLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Count one request body of `size` bytes sent on `day` (YYYY-MM-DD)
+    pub fn record(&self, session: &str, day: &str, size: u64) -> Vec<EgressAlert> {
+        let seen = self.seen.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        let Ok(mut sessions) = self.sessions.lock() else {
+            return vec![];
+        };
+        if sessions.len() >= MAX_SESSIONS && !sessions.contains_key(session) {
+            if let Some(oldest) = sessions
+                .iter()
+                .min_by_key(|(_, s)| s.last_seen)
+                .map(|(k, _)| k.clone())
+            {
+                sessions.remove(&oldest);
+            }
+        }
+        let entry = sessions.entry(session.to_string()).or_default();
+        entry.last_seen = seen;
+        if entry.day != day {
+            entry.day = day.to_string();
+            entry.bytes = 0;
+        }
+
+        let mut alerts = vec![];
+        let before = entry.bytes;
+        entry.bytes += size;
+        if let Some(limit) = self.limits.session_bytes {
+            if before < limit && entry.bytes >= limit {
+                alerts.push(EgressAlert::Volume {
+                    total: entry.bytes,
+                    limit,
+                });
+            }
+        }
+
+        let growth = size.saturating_sub(entry.last_size);
+        if entry.requests >= BASELINE_REQUESTS && self.limits.spike_factor > 0.0 {
+            // Growth of the requests after the first
+            let baseline = entry.growth_sum / (entry.requests - 1);
+            if growth >= SPIKE_MIN_BYTES
+                && growth as f64 > baseline as f64 * self.limits.spike_factor
+            {
+                alerts.push(EgressAlert::Spike {
+                    size,
+                    growth,
+                    baseline,
+                });
+            }
+        }
+        if entry.requests > 0 {
+            entry.growth_sum += growth;
+        }
+        entry.requests += 1;
+        entry.last_size = size;
+        alerts
+    }
+}
+
+fn human_bytes(bytes: u64) -> String {
+    const KIB: f64 = 1024.0;
+    let b = bytes as f64;
+    if b >= KIB * KIB {
+        format!("{:.1} MiB", b / (KIB * KIB))
+    } else if b >= KIB {
+        format!("{:.1} KiB", b / KIB)
+    } else {
+        format!("{} B", bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_egress_volume_and_spike() {
+        let tracker = EgressTracker::new(EgressLimits {
+            session_bytes: Some(10 * 1024 * 1024),
+            spike_factor: 10.0,
+        });
+        let day = "2026-03-01";
+
+        // Steady growth of ~20 KiB per request: no alerts
+        for i in 1..=5 {
+            assert!(tracker.record("s1", day, i * 20 * 1024).is_empty());
+        }
+        // A 2 MiB file pasted into the prompt
+        let alerts = tracker.record("s1", day, 100 * 1024 + 2 * 1024 * 1024);
+        assert!(matches!(
+            alerts[..],
+            [EgressAlert::Spike { baseline, .. }] if baseline == 20 * 1024
+        ));
+        assert!(alerts[0].message("s1").contains("2.0 MiB"));
+
+        // Too few requests for a baseline: no spike, but the volume limit
+        // still applies
+        assert!(tracker.record("s2", day, 1024).is_empty());
+        let alerts = tracker.record("s2", day, 11 * 1024 * 1024);
+        assert_eq!(
+            alerts,
+            [EgressAlert::Volume {
+                total: 11 * 1024 * 1024 + 1024,
+                limit: 10 * 1024 * 1024
+            }]
+        );
+        // Reported once, then again the next day
+        assert!(tracker.record("s2", day, 11 * 1024 * 1024).is_empty());
+        assert_eq!(
+            tracker.record("s2", "2026-03-02", 11 * 1024 * 1024).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! Upstream health tracking and failover
+//!
+//! The proxy forwards to the first healthy upstream in order: the primary
+//! target, then any configured fallbacks. An upstream is marked unhealthy
+//! after `failover_threshold` consecutive failures (connection errors or
+//! 5xx responses, from traffic or health checks) and only becomes healthy
+//! again after a successful health check, so traffic returns to the
+//! primary once it recovers.
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Timeout for one health check request
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Health of one upstream, as reported in `/api/providers`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpstreamHealth {
+    pub target: String,
+    /// "primary" or "fallback"
+    pub role: String,
+    pub healthy: bool,
+    /// Whether traffic is currently routed here
+    pub active: bool,
+    pub consecutive_failures: u32,
+    pub latency_ms: Option<u64>,
+    pub last_error: Option<String>,
+    pub last_checked: Option<String>,
+}
+
+/// Upstream targets in failover order
+pub struct Upstreams {
+    threshold: u32,
+    targets: Mutex<Vec<UpstreamHealth>>,
+}
+
+impl Upstreams {
+    pub fn new(primary: &str, fallbacks: &[String], failover_threshold: u32) -> Self {
+        let targets = std::iter::once((primary, "primary"))
+            .chain(fallbacks.iter().map(|f| (f.as_str(), "fallback")))
+            .map(|(target, role)| UpstreamHealth {
+                target: target.trim_end_matches('/').to_string(),
+                role: role.to_string(),
+                healthy: true,
+                active: false,
+                consecutive_failures: 0,
+                latency_ms: None,
+                last_error: None,
+                last_checked: None,
+            })
+            .collect();
+        Self {
+            threshold: failover_threshold.max(1),
+            targets: Mutex::new(targets),
+        }
+    }
+
+    /// All targets, in failover order
+    pub fn targets(&self) -> Vec<String> {
+        self.snapshot().into_iter().map(|t| t.target).collect()
+    }
+
+    /// Target to send the next request to: the first healthy one, or the
+    /// primary when none are
+    pub fn active(&self) -> String {
+        let targets = self.targets.lock().unwrap_or_else(|e| e.into_inner());
+        targets
+            .iter()
+            .find(|t| t.healthy)
+            .unwrap_or(&targets[0])
+            .target
+            .clone()
+    }
+
+    /// Current health, with `active` filled in
+    pub fn snapshot(&self) -> Vec<UpstreamHealth> {
+        let active = self.active();
+        let targets = self.targets.lock().unwrap_or_else(|e| e.into_inner());
+        targets
+            .iter()
+            .cloned()
+            .map(|mut t| {
+                t.active = t.target == active;
+                t
+            })
+            .collect()
+    }
+
+    fn update(&self, target: &str, f: impl FnOnce(&mut UpstreamHealth)) {
+        let mut targets = self.targets.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(t) = targets.iter_mut().find(|t| t.target == target) {
+            f(t);
+        }
+    }
+
+    /// A request or health check succeeded. Only health checks (which pass
+    /// a latency) bring an unhealthy upstream back.
+    pub fn record_success(&self, target: &str, latency: Option<Duration>) {
+        self.update(target, |t| {
+            t.consecutive_failures = 0;
+            if let Some(latency) = latency {
+                t.healthy = true;
+                t.latency_ms = Some(latency.as_millis() as u64);
+                t.last_error = None;
+                t.last_checked = Some(chrono::Utc::now().to_rfc3339());
+            }
+        });
+    }
+
+    /// A request or health check failed; returns true if this marked the
+    /// upstream unhealthy
+    pub fn record_failure(&self, target: &str, error: &str, checked: bool) -> bool {
+        let threshold = self.threshold;
+        let mut tripped = false;
+        self.update(target, |t| {
+            t.consecutive_failures += 1;
+            t.last_error = Some(error.to_string());
+            if checked {
+                t.last_checked = Some(chrono::Utc::now().to_rfc3339());
+            }
+            if t.healthy && t.consecutive_failures >= threshold {
+                t.healthy = false;
+                tripped = true;
+            }
+        });
+        tripped
+    }
+}
+
+/// Whether an upstream response status counts as a failure
+pub fn is_failure_status(status: u16) -> bool {
+    status >= 500
+}
+
+/// Send one lightweight request to `target`. Any non-5xx answer (including
+/// 401/404 for an unauthenticated GET) means the upstream is reachable.
+pub async fn check(client: &Client, target: &str) -> Result<Duration, String> {
+    let started = Instant::now();
+    match client.get(target).timeout(CHECK_TIMEOUT).send().await {
+        Ok(resp) if is_failure_status(resp.status().as_u16()) => {
+            Err(format!("HTTP {}", resp.status().as_u16()))
+        }
+        Ok(_) => Ok(started.elapsed()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failover_and_recovery() {
+        let upstreams = Upstreams::new(
+            "https://primary.example/",
+            &["https://backup.example".to_string()],
+            2,
+        );
+        assert_eq!(upstreams.active(), "https://primary.example");
+
+        assert!(!upstreams.record_failure("https://primary.example", "timeout", false));
+        assert_eq!(upstreams.active(), "https://primary.example");
+        assert!(upstreams.record_failure("https://primary.example", "timeout", false));
+        assert_eq!(upstreams.active(), "https://backup.example");
+
+        // Traffic alone doesn't restore it
+        upstreams.record_success("https://primary.example", None);
+        assert_eq!(upstreams.active(), "https://backup.example");
+
+        upstreams.record_success("https://primary.example", Some(Duration::from_millis(20)));
+        let snapshot = upstreams.snapshot();
+        assert!(snapshot[0].active && snapshot[0].healthy);
+        assert_eq!(snapshot[0].latency_ms, Some(20));
+        assert!(!snapshot[1].active);
+
+        // Nothing healthy: fall back to the primary
+        upstreams.record_failure("https://primary.example", "x", true);
+        upstreams.record_failure("https://primary.example", "x", true);
+        upstreams.record_failure("https://backup.example", "x", true);
+        upstreams.record_failure("https://backup.example", "x", true);
+        assert_eq!(upstreams.active(), "https://primary.example");
+    }
+}
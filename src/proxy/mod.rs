@@ -2,37 +2,140 @@
 //!
 //! Intercepts responses and blocks dangerous tool_use actions.
 
+pub mod admin;
+pub mod attribution;
+pub mod bench;
+pub mod cache;
+pub mod capture;
 pub mod config;
+pub mod control;
+pub mod corpus;
+pub mod egress;
+pub mod health;
 pub mod interceptor;
+pub mod partial_json;
+pub mod request_guard;
 pub mod streaming;
+pub mod telemetry;
+pub mod tool_results;
+pub mod tools;
+pub mod usage;
 
+use self::admin::{ProxyStatus, RecentIntercepts};
+use self::attribution::{attribute, is_attribution_header};
+use self::cache::{CachedResponse, ResponseCache};
+use self::capture::{CaptureStore, Exchange};
 use self::config::{ProxyConfig, ProxyMode};
-use self::interceptor::{format_telegram_alert, intercept_response, InterceptResult};
-use self::streaming::{parse_sse_events, SseLineBuffer, StreamInterceptor};
+use self::control::ProxyControl;
+use self::egress::{EgressLimits, EgressTracker};
+use self::health::{is_failure_status, Upstreams};
+use self::interceptor::{
+    format_telegram_alert, intercept_response, InterceptResult, MessageOptions,
+};
+use self::request_guard::{RequestFinding, RequestGuard};
+use self::streaming::{parse_sse_events, SseLineBuffer, StreamInterceptor, StreamLimits};
+use self::telemetry::Telemetry;
+use self::usage::{crossed, Budget, Usage};
+use crate::blob::BlobStore;
+use crate::db::{CanaryToken, Database, DbPool, EventCounters, UsageEntry};
+use crate::diagnostics::{self, Source};
+use crate::enforcer::backup::{self, BackupStore};
+use crate::enforcer::lockdown::LockdownPolicy;
+use crate::enforcer::schedule::{AlertSchedule, QuietGate};
+use crate::events::{Event, EventBus};
+use crate::metadata::ActionMetadata;
 use crate::rules::{default_rules, Rule, RuleAction};
-use crate::{AlertConfig, TelegramConfig};
+use crate::{canary, correlation, trace};
+use crate::{AgentType, AlertConfig, Config, RiskLevel, TelegramConfig};
 
 use axum::{
     body::Body,
     extract::State,
     http::{HeaderMap, Method, StatusCode, Uri},
-    response::{IntoResponse, Response},
-    routing::any,
-    Router,
+    response::Response,
+    routing::{any, get},
+    Json, Router,
 };
+use futures_util::future::BoxFuture;
 use futures_util::StreamExt;
 use reqwest::Client;
-use std::sync::Arc;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use tokio::net::TcpListener;
-use tracing::{error, info};
+use tracing::{error, info, warn, Instrument, Span};
 
 /// Shared state for the proxy
 struct ProxyState {
     client: Client,
-    target: String,
-    rules: Vec<Rule>,
-    mode: ProxyMode,
+    /// Primary target and fallbacks
+    upstreams: Upstreams,
+    /// Mode and rules, switchable while running
+    control: Arc<ProxyControl>,
     telegram: Option<TelegramConfig>,
+    /// Telegram alerts held during quiet hours
+    telegram_gate: Arc<QuietGate>,
+    /// Configured agent for requests that don't identify themselves
+    agent: AgentType,
+    /// Shared action log, when the database is reachable
+    db: Option<DbPool>,
+    /// Where oversized intercept content is offloaded
+    blobs: BlobStore,
+    /// Request counts not yet flushed to the database
+    counters: Mutex<EventCounters>,
+    /// Cache for upstream GETs, when enabled
+    cache: Option<ResponseCache>,
+    /// Spend limits for budget alerts
+    budget: Budget,
+    /// Outbound bytes per session, for volume and spike alerts
+    egress: EgressTracker,
+    /// Request-direction checks, when enabled
+    request_guard: Option<RequestGuard>,
+    /// Canary tokens and guidance in block messages
+    messages: MessageOptions,
+    /// Address the proxy listens on
+    listen: String,
+    started_at: chrono::DateTime<chrono::Utc>,
+    /// Request counts since start (`counters` is reset on every flush)
+    totals: Mutex<EventCounters>,
+    /// Latest intercepts, for the status endpoint
+    recent: RecentIntercepts,
+    /// The daemon's event bus, when running inside it
+    bus: Option<EventBus>,
+    /// Where API exchanges are captured, when enabled
+    capture: Option<CaptureStore>,
+    /// Bounds on buffering streamed tool calls
+    stream_limits: StreamLimits,
+    /// Report rule matches in thinking blocks
+    scan_thinking: bool,
+    /// Say in responses what the harness did (see `telemetry`)
+    telemetry: bool,
+}
+
+impl ProxyState {
+    /// A connection to the action log, if it is reachable
+    fn db(&self) -> Option<Database> {
+        match self.db.as_ref()?.get() {
+            Ok(db) => Some(db),
+            Err(e) => {
+                warn!("Cannot reach the action log: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// How often request counters are written to the database
+const COUNTER_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Rules the proxy enforces by default: built-in plus runtime rules, compiled
+pub fn load_rules() -> anyhow::Result<Vec<Rule>> {
+    let mut rules = default_rules();
+    rules.extend(crate::rules::runtime_rules());
+    for r in &mut rules {
+        r.compile()?;
+    }
+    Ok(rules)
 }
 
 /// Start the proxy server
@@ -40,33 +143,247 @@ pub async fn start_proxy(
     config: ProxyConfig,
     alert_config: Option<AlertConfig>,
 ) -> anyhow::Result<()> {
-    let mut rules = default_rules();
-    for r in &mut rules {
-        r.compile()?;
+    let control = ProxyControl::new(config.mode, load_rules()?);
+    if let Some(policy) = LockdownPolicy::from_env() {
+        policy.spawn(control.clone());
+    }
+    start_proxy_with(config, alert_config, control, None, None).await
+}
+
+/// Start the proxy server, taking mode and rules from `control` so that
+/// whoever else holds it (the web server) can switch them while it runs.
+/// Inside the daemon, intercepts are also published on its `bus` and logged
+/// through its pool `db`; on its own the proxy opens a connection of its
+/// own.
+///
+/// Each of `config.effective_listeners()` gets its own upstreams, mode and
+/// counters; rules, alerting and the action log are shared.
+pub async fn start_proxy_with(
+    config: ProxyConfig,
+    alert_config: Option<AlertConfig>,
+    control: Arc<ProxyControl>,
+    bus: Option<EventBus>,
+    db: Option<DbPool>,
+) -> anyhow::Result<()> {
+    let storage = match db {
+        Some(pool) => ProxyStorage::shared(pool),
+        None => ProxyStorage::standalone(),
+    };
+    bind_proxy(config, alert_config, control, bus, storage)
+        .await?
+        .serve()
+        .await
+}
+
+/// Where the proxy logs intercepts
+#[derive(Clone)]
+pub struct ProxyStorage {
+    /// The action log; blobs and captures are kept next to it
+    pub db_path: PathBuf,
+    /// Pool to log through instead of opening the file at `db_path`,
+    /// shared by all listeners
+    pub db: Option<DbPool>,
+}
+
+impl ProxyStorage {
+    /// The daemon's database, for a proxy running on its own
+    pub fn standalone() -> Self {
+        let db_path = crate::expand_home(&Config::default().db_path);
+        if let Some(parent) = db_path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        Self { db_path, db: None }
+    }
+
+    /// Log through `pool`, e.g. the daemon's
+    pub fn shared(pool: DbPool) -> Self {
+        Self {
+            db_path: pool.path().to_path_buf(),
+            db: Some(pool),
+        }
+    }
+
+    fn open(&self) -> Option<DbPool> {
+        if let Some(db) = &self.db {
+            return Some(db.clone());
+        }
+        // One connection, as the proxy used to hold before it shared a pool
+        match DbPool::with_size(&self.db_path, 1) {
+            Ok(db) => Some(db),
+            Err(e) => {
+                warn!(
+                    "Intercepts won't be logged; cannot open {}: {}",
+                    self.db_path.display(),
+                    e
+                );
+                None
+            }
+        }
+    }
+}
+
+/// A proxy whose listeners are bound but not yet serving
+pub struct BoundProxy {
+    /// Where each of `config.effective_listeners()` listens, in order
+    /// (the actual port when one was given as 0)
+    pub addrs: Vec<SocketAddr>,
+    servers: Vec<BoxFuture<'static, std::io::Result<()>>>,
+}
+
+impl BoundProxy {
+    /// Serve until a listener fails
+    pub async fn serve(self) -> anyhow::Result<()> {
+        futures_util::future::try_join_all(self.servers).await?;
+        Ok(())
     }
+}
 
+/// Bind every listener, so a taken port fails the whole start, and set up
+/// their state; see `start_proxy_with`
+pub async fn bind_proxy(
+    config: ProxyConfig,
+    alert_config: Option<AlertConfig>,
+    control: Arc<ProxyControl>,
+    bus: Option<EventBus>,
+    storage: ProxyStorage,
+) -> anyhow::Result<BoundProxy> {
+    let telegram_gate = Arc::new(QuietGate::new(
+        alert_config
+            .as_ref()
+            .and_then(|a| AlertSchedule::for_channel(a, "telegram")),
+    ));
     let telegram = alert_config.and_then(|a| a.telegram);
+    let db_path = &storage.db_path;
+    let db = storage.open();
 
-    let state = Arc::new(ProxyState {
-        client: Client::new(),
-        target: config.target.trim_end_matches('/').to_string(),
-        rules,
-        mode: config.mode,
-        telegram,
+    let mut addrs = vec![];
+    let mut servers: Vec<BoxFuture<'static, std::io::Result<()>>> = vec![];
+    for listener in config.effective_listeners() {
+        crate::listen::check(&listener.listen, config.expose)?;
+        let tcp = TcpListener::bind(&listener.listen).await?;
+        addrs.push(tcp.local_addr()?);
+        let control = match listener.mode {
+            Some(mode) => control.with_mode(mode),
+            None => control.clone(),
+        };
+        info!("🛡️ OpenClaw Harness proxy listening on {}", listener.listen);
+        info!("   Target: {}", listener.target);
+        for fallback in &listener.fallback_targets {
+            info!("   Fallback: {}", fallback);
+        }
+        info!("   Mode: {:?}", control.mode());
+        if !crate::listen::is_loopback(&listener.listen) {
+            warn!(
+                "⚠️  Proxy is {}",
+                crate::listen::exposure_note(&listener.listen)
+            );
+        }
+
+        let state = Arc::new(ProxyState {
+            client: crate::network::client(),
+            upstreams: Upstreams::new(
+                &listener.target,
+                &listener.fallback_targets,
+                config.failover_threshold,
+            ),
+            control,
+            telegram: telegram.clone(),
+            telegram_gate: telegram_gate.clone(),
+            agent: config
+                .agent
+                .as_deref()
+                .map(AgentType::from_name)
+                .unwrap_or(AgentType::Unknown),
+            db: db.clone(),
+            blobs: BlobStore::for_db(db_path),
+            counters: Mutex::new(EventCounters::default()),
+            cache: (config.cache_ttl_secs > 0)
+                .then(|| ResponseCache::new(std::time::Duration::from_secs(config.cache_ttl_secs))),
+            budget: Budget::from_env(),
+            egress: EgressTracker::new(EgressLimits::from_env()),
+            request_guard: config.inspect_requests.then(RequestGuard::new),
+            messages: MessageOptions {
+                canary_tokens: config.canary_tokens,
+                coaching: config.coaching,
+            },
+            listen: listener.listen.clone(),
+            started_at: chrono::Utc::now(),
+            totals: Mutex::new(EventCounters::default()),
+            recent: RecentIntercepts::default(),
+            bus: bus.clone(),
+            capture: config.capture.then(|| CaptureStore::for_db(db_path)),
+            stream_limits: config.stream_limits,
+            scan_thinking: config.scan_thinking,
+            telemetry: config.telemetry,
+        });
+        spawn_listener_tasks(&state, config.health_check_interval_secs);
+
+        let app = Router::new()
+            .route(admin::STATUS_PATH, get(status_handler))
+            .route("/", any(proxy_handler))
+            .route("/*path", any(proxy_handler))
+            .with_state(state);
+        servers.push(Box::pin(async move { axum::serve(tcp, app).await }));
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(COUNTER_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Some(digest) = telegram_gate.digest() {
+                send_telegram_text(telegram.clone(), &digest).await;
+            }
+        }
     });
 
-    let app = Router::new()
-        .route("/", any(proxy_handler))
-        .route("/*path", any(proxy_handler))
-        .with_state(state);
+    Ok(BoundProxy { addrs, servers })
+}
 
-    let listener = TcpListener::bind(&config.listen).await?;
-    info!("🛡️ OpenClaw Harness proxy listening on {}", config.listen);
-    info!("   Target: {}", config.target);
-    info!("   Mode: {:?}", config.mode);
+/// Mode-switch logging, counter flushing and health checks for one listener
+fn spawn_listener_tasks(state: &Arc<ProxyState>, health_check_interval_secs: u64) {
+    let mut mode_switches = state.control.watch_mode();
+    let listen = state.listen.clone();
+    tokio::spawn(async move {
+        while mode_switches.changed().await.is_ok() {
+            let mode = *mode_switches.borrow_and_update();
+            info!("🔀 Proxy on {} now in {:?} mode", listen, mode);
+        }
+    });
+
+    let flush_state = state.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(COUNTER_FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            flush_counters(&flush_state);
+        }
+    });
 
-    axum::serve(listener, app).await?;
-    Ok(())
+    if health_check_interval_secs > 0 {
+        let health_state = state.clone();
+        let every = std::time::Duration::from_secs(health_check_interval_secs);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            loop {
+                interval.tick().await;
+                check_upstreams(&health_state).await;
+            }
+        });
+    }
+}
+
+async fn status_handler(State(state): State<Arc<ProxyState>>) -> Json<ProxyStatus> {
+    Json(ProxyStatus {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        listen: state.listen.clone(),
+        mode: state.control.mode(),
+        started_at: state.started_at.to_rfc3339(),
+        uptime_secs: (chrono::Utc::now() - state.started_at).num_seconds().max(0) as u64,
+        active_target: state.upstreams.active(),
+        upstreams: state.upstreams.snapshot(),
+        counters: state.totals.lock().map(|t| t.clone()).unwrap_or_default(),
+        recent_intercepts: state.recent.list(),
+    })
 }
 
 async fn proxy_handler(
@@ -75,13 +392,41 @@ async fn proxy_handler(
     uri: Uri,
     headers: HeaderMap,
     body: Body,
-) -> impl IntoResponse {
+) -> Response {
+    let request_id = trace::request_id();
+    let span = trace::proxy_span(&request_id, method.as_str(), uri.path());
+    forward_request(state, request_id, method, uri, headers, body)
+        .instrument(span)
+        .await
+}
+
+async fn forward_request(
+    state: Arc<ProxyState>,
+    request_id: String,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Body,
+) -> Response {
     let path = uri.path();
     let query = uri.query().map(|q| format!("?{}", q)).unwrap_or_default();
-    let url = format!("{}{}{}", state.target, path, query);
+    let target = state.upstreams.active();
+    let url = format!("{}{}{}", target, path, query);
 
     info!("📥 {} {} → {}", method, path, url);
 
+    let cache_key = match &state.cache {
+        Some(cache) if method == Method::GET => {
+            let key = ResponseCache::key(&format!("{}{}", path, query), &headers);
+            if let Some(hit) = cache.get(&key) {
+                info!("💾 Cache hit: {}", path);
+                return cached_response(hit);
+            }
+            Some(key)
+        }
+        _ => None,
+    };
+
     // Build upstream request
     let mut req_builder = match method {
         Method::GET => state.client.get(&url),
@@ -92,9 +437,10 @@ async fn proxy_handler(
         _ => state.client.get(&url),
     };
 
-    // Forward headers (except host)
+    // Forward headers (except host, our attribution headers, and the length,
+    // which changes if tool results are redacted)
     for (name, value) in headers.iter() {
-        if name == "host" {
+        if name == "host" || name == "content-length" || is_attribution_header(name.as_str()) {
             continue;
         }
         if let Ok(v) = value.to_str() {
@@ -103,7 +449,7 @@ async fn proxy_handler(
     }
 
     // Forward body
-    let body_bytes = match axum::body::to_bytes(body, 10 * 1024 * 1024).await {
+    let mut body_bytes = match axum::body::to_bytes(body, 10 * 1024 * 1024).await {
         Ok(b) => b,
         Err(e) => {
             error!("Failed to read request body: {}", e);
@@ -114,15 +460,49 @@ async fn proxy_handler(
         }
     };
 
+    let source = attribute(&headers, &body_bytes, &state.agent);
+    if let Some(session_id) = &source.session_id {
+        Span::current().record("session_id", session_id.as_str());
+    }
+
+    if let Some(guard) = &state.request_guard {
+        if method == Method::POST && provider_for_path(path) != "other" {
+            let findings = guard.inspect(&body_bytes, &source);
+            if !findings.is_empty() {
+                if let Some(blocked) = handle_request_findings(&state, path, &request_id, findings)
+                {
+                    return blocked;
+                }
+            }
+            let enforce = state.control.enforce();
+            let (findings, redacted) = guard.inspect_tool_results(&body_bytes, &source, enforce);
+            report_request_findings(&state, path, &request_id, findings, enforce);
+            if let Some(redacted) = redacted {
+                body_bytes = redacted.into();
+            }
+        }
+    }
+
+    if method == Method::POST && provider_for_path(path) != "other" {
+        record_egress(
+            &state,
+            path,
+            source.session_id.as_deref(),
+            body_bytes.len() as u64,
+        );
+    }
+
     if !body_bytes.is_empty() {
         req_builder = req_builder.body(body_bytes.to_vec());
     }
 
     // Send upstream
+    let sent_at = std::time::Instant::now();
     let upstream_resp = match req_builder.send().await {
         Ok(r) => r,
         Err(e) => {
             error!("Upstream request failed: {}", e);
+            record_upstream_failure(&state, &target, &e.to_string(), false);
             return Response::builder()
                 .status(StatusCode::BAD_GATEWAY)
                 .body(Body::from(format!("Upstream error: {}", e)))
@@ -131,7 +511,20 @@ async fn proxy_handler(
     };
 
     let status = upstream_resp.status();
+    if is_failure_status(status.as_u16()) {
+        record_upstream_failure(&state, &target, &format!("HTTP {}", status.as_u16()), false);
+    } else {
+        state.upstreams.record_success(&target, None);
+    }
     let resp_headers = upstream_resp.headers().clone();
+    // What stored intercepts record about this call; the model is added
+    // once the body has been read
+    let mut call = ActionMetadata {
+        provider: Some(provider_for_path(path).to_string()),
+        latency_ms: Some(sent_at.elapsed().as_millis() as u64),
+        request_id: Some(request_id),
+        ..Default::default()
+    };
     let is_api_post = method == Method::POST
         && (
             path.contains("/v1/messages") ||           // Anthropic
@@ -150,54 +543,108 @@ async fn proxy_handler(
     // Streaming responses: intercept SSE events on the fly
     if is_messages_post && is_streaming {
         info!("📡 Streaming response detected — intercepting SSE events");
-        let enforce = state.mode == ProxyMode::Enforce;
-        let rules = state.rules.clone();
-        let telegram = state.telegram.clone();
+        let mode = state.control.mode();
+        let enforce = mode == ProxyMode::Enforce;
+        let rules = state.control.rules().to_vec();
+        let log_state = state.clone();
+        let path_owned = path.to_string();
+        let session_id = source.session_id.clone();
+        let capture_request = log_state.capture.is_some().then(|| {
+            (
+                method.to_string(),
+                headers.clone(),
+                body_bytes.clone(),
+                target.clone(),
+            )
+        });
 
         let upstream_stream = upstream_resp.bytes_stream();
+        // The stream is read after this handler returns, outside its span
+        let span = Span::current();
 
         let intercepted_stream = async_stream::stream! {
-            let mut interceptor = StreamInterceptor::new(rules, enforce);
+            let mut interceptor = StreamInterceptor::new(rules, enforce)
+                .with_attribution(source)
+                .with_canary_tokens(log_state.messages.canary_tokens)
+                .with_coaching(log_state.messages.coaching)
+                .with_limits(log_state.stream_limits)
+                .with_thinking_scan(log_state.scan_thinking);
             let mut line_buf = SseLineBuffer::new();
+            let mut backed_up = 0;
+            let mut usage = Usage::default();
+            let mut captured: Vec<u8> = vec![];
 
             tokio::pin!(upstream_stream);
 
-            while let Some(chunk_result) = upstream_stream.next().await {
-                let chunk: bytes::Bytes = match chunk_result {
-                    Ok(c) => c,
-                    Err(e) => {
-                        error!("Upstream stream error: {}", e);
+            let mut ended = false;
+            while !ended {
+                let event_blocks = match upstream_stream.next().await {
+                    Some(Ok(chunk)) => {
+                        if capture_request.is_some() && captured.len() <= capture::MAX_BODY_BYTES {
+                            captured.extend_from_slice(&chunk);
+                        }
+                        line_buf.feed_bytes(&chunk)
+                    }
+                    Some(Err(e)) => {
+                        span.in_scope(|| error!("Upstream stream error: {}", e));
+                        diagnostics::record(Source::Proxy, &target, format!("stream: {}", e));
                         break;
                     }
-                };
-
-                let text = match std::str::from_utf8(&chunk) {
-                    Ok(t) => t.to_string(),
-                    Err(_) => {
-                        yield Ok::<bytes::Bytes, std::io::Error>(chunk);
-                        continue;
+                    // A last event without a blank line after it still
+                    // goes through the interceptor
+                    None => {
+                        ended = true;
+                        line_buf.finish().into_iter().collect()
                     }
                 };
-
-                let event_blocks = line_buf.feed(&text);
                 for block in event_blocks {
                     let sse_events = parse_sse_events(&block);
                     for sse_event in sse_events {
-                        let output_events = interceptor.process_event(sse_event);
+                        if let Ok(data) = serde_json::from_str(&sse_event.data) {
+                            usage.observe(&data);
+                        }
+                        let output_events = span.in_scope(|| interceptor.process_event(sse_event));
+                        // Back up before the tool_use reaches the agent
+                        if !enforce && interceptor.intercepts.len() > backed_up {
+                            span.in_scope(|| backup_approved_writes(&interceptor.intercepts[backed_up..]));
+                            backed_up = interceptor.intercepts.len();
+                        }
                         for out in output_events {
                             yield Ok::<bytes::Bytes, std::io::Error>(bytes::Bytes::from(out.to_sse_bytes()));
                         }
                     }
                 }
             }
+            if log_state.telemetry {
+                let telemetry = Telemetry::new(mode, &interceptor.intercepts);
+                yield Ok(bytes::Bytes::from(telemetry.sse_comment()));
+            }
 
-            // Send alerts for any intercepts
-            if !interceptor.intercepts.is_empty() {
-                let intercepts = interceptor.intercepts.clone();
-                tokio::spawn(async move {
-                    send_intercept_alerts(telegram, &intercepts).await;
+            let _entered = span.enter();
+            count_request(&log_state, path_owned.as_str(), &interceptor.intercepts, enforce);
+            if let Some((method, headers, body, target)) = &capture_request {
+                capture_exchange(&log_state, Exchange {
+                    method,
+                    path: &path_owned,
+                    target,
+                    mode: log_state.control.mode(),
+                    request_headers: headers,
+                    request_body: body,
+                    status: status.as_u16(),
+                    response_body: &captured,
+                    intercepts: &interceptor.intercepts,
                 });
             }
+            if !usage.is_empty() {
+                record_usage(&log_state, &path_owned, session_id.as_deref(), &usage);
+            }
+
+            // Log and send alerts for any intercepts
+            if !interceptor.intercepts.is_empty() {
+                call.model = usage.model.clone();
+                log_intercepts(&log_state, &interceptor.intercepts, enforce, &call);
+                alert_intercepts(&log_state, interceptor.intercepts.clone());
+            }
         };
 
         let mut builder = Response::builder()
@@ -211,6 +658,10 @@ async fn proxy_handler(
                 builder = builder.header(name.as_str(), v);
             }
         }
+        // The rest is only known once the stream has been read
+        if state.telemetry {
+            builder = builder.header(telemetry::MODE_HEADER, telemetry::mode_name(mode));
+        }
 
         return builder.body(Body::from_stream(intercepted_stream)).unwrap();
     }
@@ -220,6 +671,7 @@ async fn proxy_handler(
         Ok(b) => b,
         Err(e) => {
             error!("Failed to read upstream response: {}", e);
+            diagnostics::record(Source::Proxy, &target, format!("response: {}", e));
             return Response::builder()
                 .status(StatusCode::BAD_GATEWAY)
                 .body(Body::from("Failed to read upstream response"))
@@ -228,19 +680,54 @@ async fn proxy_handler(
     };
 
     // Intercept /v1/messages POST non-streaming responses
+    let mut telemetry = None;
     let final_body = if is_messages_post {
-        let enforce = state.mode == ProxyMode::Enforce;
-        let (modified, intercepts) = intercept_response(&resp_body, &state.rules, enforce);
+        let mode = state.control.mode();
+        let enforce = mode == ProxyMode::Enforce;
+        let (modified, intercepts) = intercept_response(
+            &resp_body,
+            &state.control.rules(),
+            enforce,
+            state.messages,
+            &source,
+        );
+
+        if !enforce {
+            backup_approved_writes(&intercepts);
+        }
+        count_request(&state, path, &intercepts, enforce);
+        capture_exchange(
+            &state,
+            Exchange {
+                method: method.as_str(),
+                path,
+                target: &target,
+                mode: state.control.mode(),
+                request_headers: &headers,
+                request_body: &body_bytes,
+                status: status.as_u16(),
+                response_body: &resp_body,
+                intercepts: &intercepts,
+            },
+        );
+        if let Some(usage) = usage::extract_usage(&resp_body) {
+            record_usage(&state, path, source.session_id.as_deref(), &usage);
+            call.model = usage.model;
+        }
 
         if !intercepts.is_empty() {
-            let telegram = state.telegram.clone();
-            let intercepts_clone = intercepts.clone();
-            tokio::spawn(async move {
-                send_intercept_alerts(telegram, &intercepts_clone).await;
-            });
+            log_intercepts(&state, &intercepts, enforce, &call);
+            alert_intercepts(&state, intercepts.clone());
         }
 
-        modified
+        if state.telemetry {
+            let report = Telemetry::new(mode, &intercepts);
+            let modified = report.add_to_body(&modified).unwrap_or(modified);
+            telemetry = Some(report);
+            modified
+        } else {
+            modified
+        }
     } else {
         resp_body.to_vec()
     };
@@ -257,24 +744,394 @@ async fn proxy_handler(
             builder = builder.header(name.as_str(), v);
         }
     }
+    for (name, value) in telemetry.iter().flat_map(Telemetry::headers) {
+        builder = builder.header(name, value);
+    }
+
+    if let (Some(cache), Some(key), false) = (&state.cache, cache_key, is_streaming) {
+        cache.insert(
+            key,
+            CachedResponse {
+                status: status.as_u16(),
+                headers: resp_headers
+                    .iter()
+                    .filter_map(|(n, v)| Some((n.to_string(), v.to_str().ok()?.to_string())))
+                    .collect(),
+                body: bytes::Bytes::from(final_body.clone()),
+            },
+        );
+        builder = builder.header(CACHE_HEADER, "MISS");
+    }
 
     builder = builder.header("content-length", final_body.len().to_string());
 
     builder.body(Body::from(final_body)).unwrap()
 }
 
-async fn send_intercept_alerts(telegram: Option<TelegramConfig>, intercepts: &[InterceptResult]) {
-    let Some(tg) = telegram else { return };
-    let client = Client::new();
-    let url = format!("https://api.telegram.org/bot{}/sendMessage", tg.bot_token);
+/// Response header reporting whether a GET was served from the cache
+const CACHE_HEADER: &str = "x-openclaw-cache";
+
+fn cached_response(cached: CachedResponse) -> Response {
+    let mut builder =
+        Response::builder().status(StatusCode::from_u16(cached.status).unwrap_or(StatusCode::OK));
+    for (name, value) in &cached.headers {
+        if name == "transfer-encoding" || name == "content-length" {
+            continue;
+        }
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder
+        .header(CACHE_HEADER, "HIT")
+        .header("content-length", cached.body.len().to_string())
+        .body(Body::from(cached.body))
+        .unwrap()
+}
 
+/// Store intercepts in the shared action log, linking them to collector
+/// events for the same call.
+fn log_intercepts(
+    state: &ProxyState,
+    intercepts: &[InterceptResult],
+    enforce: bool,
+    call: &ActionMetadata,
+) {
+    for intercept in intercepts {
+        state
+            .recent
+            .push(intercept, enforce && intercept.action != RuleAction::Alert);
+    }
+    let Some(db) = state.db() else { return };
+    let window = correlation::window();
     for intercept in intercepts {
-        if !matches!(
-            intercept.action,
-            RuleAction::CriticalAlert | RuleAction::PauseAndAsk
-        ) {
+        let blocked = enforce && intercept.action != RuleAction::Alert;
+        let action = state.blobs.offload(&intercept.to_action(blocked, call));
+        match correlation::record(&db, &action, window) {
+            Ok(correlation::Correlation::New) => {
+                if let Some(bus) = &state.bus {
+                    bus.publish(Event::Action(action.clone()));
+                }
+            }
+            Ok(
+                correlation::Correlation::Duplicate { .. }
+                | correlation::Correlation::Replay { .. },
+            ) => {}
+            Err(e) => warn!("Failed to log intercept: {}", e),
+        }
+        if let Err(e) = db.record_rule_match(&intercept.rule_name, action.timestamp, blocked) {
+            warn!("Failed to update rule stats: {}", e);
+        }
+        if let Some(token) = &intercept.canary_token {
+            let issued = CanaryToken {
+                token: token.clone(),
+                created_at: chrono::Utc::now().to_rfc3339(),
+                rule_name: intercept.rule_name.clone(),
+                tool_name: intercept.tool_name.clone(),
+                agent: intercept.agent.to_string(),
+                session_id: intercept.session_id.clone(),
+                trigger_count: 0,
+                last_triggered: None,
+            };
+            if let Err(e) = db.insert_canary_token(&issued) {
+                warn!("Failed to store canary token: {}", e);
+            }
+        }
+        if intercept.rule_name == canary::RULE_NAME {
+            let text = format!(
+                "{} {}",
+                intercept.content,
+                intercept.target.as_deref().unwrap_or_default()
+            );
+            for token in canary::find_tokens(&text) {
+                if let Ok(Some(origin)) = db.trigger_canary_token(&token) {
+                    warn!(
+                        "🐤 Canary {} reused: it was issued when {} was blocked by {} at {}",
+                        token, origin.tool_name, origin.rule_name, origin.created_at
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn capture_exchange(state: &ProxyState, exchange: Exchange<'_>) {
+    let Some(store) = &state.capture else { return };
+    match store.record(exchange) {
+        Ok(id) => info!("📼 Captured exchange {}", id),
+        Err(e) => warn!("Failed to capture exchange: {}", e),
+    }
+}
+
+fn record_upstream_failure(state: &ProxyState, target: &str, error: &str, checked: bool) {
+    diagnostics::record(Source::Proxy, target, error);
+    if state.upstreams.record_failure(target, error, checked) {
+        warn!("⚠️  Upstream {} marked unhealthy: {}", target, error);
+        let next = state.upstreams.active();
+        if next != target {
+            warn!("🔀 Failing over to {}", next);
+        }
+        persist_health(state);
+    }
+}
+
+/// Probe every upstream and record the results
+async fn check_upstreams(state: &ProxyState) {
+    let before = state.upstreams.active();
+    for target in state.upstreams.targets() {
+        match health::check(&state.client, &target).await {
+            Ok(latency) => state.upstreams.record_success(&target, Some(latency)),
+            Err(e) => record_upstream_failure(state, &target, &e, true),
+        }
+    }
+    let after = state.upstreams.active();
+    if after != before {
+        info!("🔀 Routing traffic to {}", after);
+    }
+    persist_health(state);
+}
+
+/// Publish upstream health for the web UI
+fn persist_health(state: &ProxyState) {
+    let Some(db) = state.db() else { return };
+    if let Err(e) = db.replace_provider_health(&state.upstreams.snapshot()) {
+        warn!("Failed to store upstream health: {}", e);
+    }
+}
+
+/// Log and alert on suspicious request content (once per session); in
+/// enforce mode, critical findings stop the request with a 403 every time
+fn handle_request_findings(
+    state: &ProxyState,
+    path: &str,
+    request_id: &str,
+    findings: Vec<RequestFinding>,
+) -> Option<Response> {
+    let enforce = state.control.enforce();
+    let blocked: Vec<String> = findings
+        .iter()
+        .filter(|f| enforce && f.result.action != RuleAction::Alert)
+        .map(|f| format!("[{}] {}", f.result.rule_name, f.result.reason))
+        .collect();
+    report_request_findings(state, path, request_id, findings, enforce);
+
+    if blocked.is_empty() {
+        return None;
+    }
+    let message = format!(
+        "🛡️ OpenClaw Harness blocked this request: {}",
+        blocked.join("; ")
+    );
+    let body = serde_json::json!({
+        "type": "error",
+        "error": { "type": "permission_error", "message": message }
+    });
+    Some(
+        Response::builder()
+            .status(StatusCode::FORBIDDEN)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+    )
+}
+
+/// Log and alert the findings not yet reported for their session
+fn report_request_findings(
+    state: &ProxyState,
+    path: &str,
+    request_id: &str,
+    findings: Vec<RequestFinding>,
+    enforce: bool,
+) {
+    let new: Vec<InterceptResult> = findings
+        .into_iter()
+        .filter(|f| f.new)
+        .map(|f| f.result)
+        .collect();
+    if new.is_empty() {
+        return;
+    }
+    for f in &new {
+        warn!("🚩 Request check {}: {}", f.rule_name, f.reason);
+    }
+    let call = ActionMetadata {
+        provider: Some(provider_for_path(path).to_string()),
+        request_id: Some(request_id.to_string()),
+        ..Default::default()
+    };
+    log_intercepts(state, &new, enforce, &call);
+    alert_intercepts(state, new);
+}
+
+/// Provider an API request path belongs to
+fn provider_for_path(path: &str) -> &'static str {
+    if path.contains("/v1/messages") {
+        "anthropic"
+    } else if path.contains("/chat/completions") {
+        "openai"
+    } else if path.contains("/generateContent") {
+        "gemini"
+    } else {
+        "other"
+    }
+}
+
+fn count_request(state: &ProxyState, path: &str, intercepts: &[InterceptResult], enforce: bool) {
+    let blocked = enforce && intercepts.iter().any(|i| i.action != RuleAction::Alert);
+    for counters in [&state.counters, &state.totals] {
+        if let Ok(mut counters) = counters.lock() {
+            counters.record_request(provider_for_path(path), blocked, !intercepts.is_empty());
+        }
+    }
+}
+
+/// Add a response's token usage to the usage table and alert when it
+/// pushes the day's or the session's spend over budget
+fn record_usage(state: &ProxyState, path: &str, session_id: Option<&str>, usage: &Usage) {
+    let Some(db) = state.db() else { return };
+    let entry = UsageEntry {
+        day: chrono::Utc::now().format("%Y-%m-%d").to_string(),
+        session_id: session_id.map(String::from),
+        provider: provider_for_path(path).to_string(),
+        model: usage.model.clone().unwrap_or_else(|| "unknown".to_string()),
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cost_usd: usage.cost_usd(),
+    };
+
+    let spend = |session: Option<&str>| db.usage_spend(&entry.day, session).unwrap_or(0.0);
+    let day_before = spend(None);
+    let session_before = session_id.map(|s| spend(Some(s)));
+    if let Err(e) = db.record_usage(&entry) {
+        warn!("Failed to record token usage: {}", e);
+        return;
+    }
+
+    let mut alerts = vec![];
+    let day_after = day_before + entry.cost_usd;
+    if crossed(state.budget.daily_usd, day_before, day_after) {
+        alerts.push(format!(
+            "💸 Daily spend estimate ${:.2} reached the ${:.2} budget",
+            day_after,
+            state.budget.daily_usd.unwrap_or_default()
+        ));
+    }
+    if let (Some(session), Some(before)) = (session_id, session_before) {
+        let after = before + entry.cost_usd;
+        if crossed(state.budget.session_usd, before, after) {
+            alerts.push(format!(
+                "💸 Session {} spend estimate ${:.2} reached the ${:.2} budget",
+                session,
+                after,
+                state.budget.session_usd.unwrap_or_default()
+            ));
+        }
+    }
+    send_warnings(state, alerts);
+}
+
+/// Add a request body's size to the egress table and alert when the
+/// session's outbound volume crosses the limit or spikes
+fn record_egress(state: &ProxyState, path: &str, session_id: Option<&str>, bytes: u64) {
+    let day = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    if let Some(db) = state.db() {
+        if let Err(e) = db.record_egress(&day, session_id, provider_for_path(path), bytes) {
+            warn!("Failed to record request size: {}", e);
+        }
+    }
+    // Requests without a session mix several agents; no baseline to compare
+    let Some(session) = session_id else { return };
+    let alerts = state
+        .egress
+        .record(session, &day, bytes)
+        .iter()
+        .map(|alert| alert.message(session))
+        .collect();
+    send_warnings(state, alerts);
+}
+
+/// Log warning-level alerts and send them to Telegram outside quiet hours
+fn send_warnings(state: &ProxyState, alerts: Vec<String>) {
+    for alert in alerts {
+        warn!("{}", alert);
+        if !state.telegram_gate.admit(RiskLevel::Warning, &alert) {
             continue;
         }
+        let telegram = state.telegram.clone();
+        tokio::spawn(async move {
+            send_telegram_text(telegram, &alert).await;
+        });
+    }
+}
+
+/// Persist pending request counters so totals survive restarts, and the
+/// failures recorded since the last flush
+fn flush_counters(state: &ProxyState) {
+    let Some(db) = state.db() else { return };
+    if let Err(e) = diagnostics::flush(&db) {
+        warn!("Failed to persist diagnostics: {}", e);
+    }
+    let delta = match state.counters.lock() {
+        Ok(mut counters) if !counters.is_empty() => std::mem::take(&mut *counters),
+        _ => return,
+    };
+    if let Err(e) = db.add_event_counters(&delta) {
+        warn!("Failed to persist request counters: {}", e);
+        // Keep them for the next attempt
+        if let Ok(mut counters) = state.counters.lock() {
+            counters.merge(&delta);
+        }
+    }
+}
+
+/// In monitor mode PauseAndAsk file writes are let through; copy the
+/// current target first so the write can be undone.
+fn backup_approved_writes(intercepts: &[InterceptResult]) {
+    backup::backup_approved_writes(&BackupStore::default(), intercepts);
+}
+
+async fn send_telegram_text(telegram: Option<TelegramConfig>, text: &str) {
+    let Some(tg) = telegram else { return };
+    let url = format!("https://api.telegram.org/bot{}/sendMessage", tg.bot_token);
+    if let Err(e) = crate::network::client()
+        .post(&url)
+        .json(&serde_json::json!({ "chat_id": tg.chat_id, "text": text }))
+        .send()
+        .await
+    {
+        error!("Failed to send Telegram alert: {}", e);
+        diagnostics::record(Source::Alert, "telegram", &e);
+    }
+}
+
+/// Alert on intercepts whose rule asks for it, holding them during quiet hours
+fn alert_intercepts(state: &ProxyState, intercepts: Vec<InterceptResult>) {
+    let Some(telegram) = state.telegram.clone() else {
+        return;
+    };
+    let due: Vec<InterceptResult> = intercepts
+        .into_iter()
+        .filter(|i| {
+            matches!(
+                i.action,
+                RuleAction::CriticalAlert | RuleAction::PauseAndAsk
+            )
+        })
+        .filter(|i| {
+            let summary = format!("{} blocked by {}", i.tool_name, i.rule_name);
+            state.telegram_gate.admit(i.risk_level, &summary)
+        })
+        .collect();
+    if !due.is_empty() {
+        tokio::spawn(async move {
+            send_intercept_alerts(telegram, &due).await;
+        });
+    }
+}
+
+async fn send_intercept_alerts(tg: TelegramConfig, intercepts: &[InterceptResult]) {
+    let client = crate::network::client();
+    let url = format!("https://api.telegram.org/bot{}/sendMessage", tg.bot_token);
+
+    for intercept in intercepts {
         let message = format_telegram_alert(intercept);
         if let Err(e) = client
             .post(&url)
@@ -287,6 +1144,7 @@ async fn send_intercept_alerts(telegram: Option<TelegramConfig>, intercepts: &[I
             .await
         {
             error!("Failed to send Telegram alert: {}", e);
+            diagnostics::record(Source::Alert, "telegram", &e);
         }
     }
 }
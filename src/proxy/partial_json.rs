@@ -0,0 +1,219 @@
+//! Incremental scan of a tool call's streamed JSON input
+//!
+//! Anthropic streams tool input as `partial_json` fragments. `FieldScanner`
+//! is fed them one by one and reports a top-level string field as soon as
+//! its closing quote arrives, without re-parsing what came before. A value
+//! is only reported once complete: a prefix like `rm -rf /` may still turn
+//! into `rm -rf /tmp/build`, so checking it early would block safe calls.
+//!
+//! With fine-grained tool streaming, Anthropic no longer validates tool input
+//! and a block can end mid-JSON (e.g. at `max_tokens`). `complete` closes
+//! such input so what did arrive is still checked.
+
+use serde_json::Value;
+
+/// Finds the value of one top-level string field in streamed JSON
+#[derive(Debug)]
+pub struct FieldScanner {
+    field: String,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    /// Raw text (quotes included) of the top-level string being read
+    raw: Option<String>,
+    /// Last top-level key, while its value is being read
+    key: Option<String>,
+    after_colon: bool,
+    /// Found value, or None forever once the field turned out not to be a string
+    found: Option<Option<String>>,
+}
+
+impl FieldScanner {
+    pub fn new(field: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            depth: 0,
+            in_string: false,
+            escaped: false,
+            raw: None,
+            key: None,
+            after_colon: false,
+            found: None,
+        }
+    }
+
+    /// Feed the next fragment; returns the field's value the first time it
+    /// is complete
+    pub fn feed(&mut self, fragment: &str) -> Option<String> {
+        if self.found.is_some() {
+            return None;
+        }
+        for c in fragment.chars() {
+            if self.in_string {
+                if let Some(raw) = &mut self.raw {
+                    raw.push(c);
+                }
+                if self.escaped {
+                    self.escaped = false;
+                } else if c == '\\' {
+                    self.escaped = true;
+                } else if c == '"' {
+                    self.in_string = false;
+                    if let Some(value) = self.end_string() {
+                        return Some(value);
+                    }
+                }
+                continue;
+            }
+            if self.depth == 1 && self.after_colon && !c.is_whitespace() && c != '"' {
+                if self.key.as_deref() == Some(&self.field) {
+                    // Not a string; nothing to report
+                    self.found = Some(None);
+                    return None;
+                }
+                self.after_colon = false;
+            }
+            match c {
+                '"' => {
+                    self.in_string = true;
+                    if self.depth == 1 {
+                        self.raw = Some(String::from('"'));
+                    }
+                }
+                '{' | '[' => self.depth += 1,
+                '}' | ']' => self.depth = self.depth.saturating_sub(1),
+                ':' if self.depth == 1 => self.after_colon = true,
+                ',' if self.depth == 1 => {
+                    self.key = None;
+                    self.after_colon = false;
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// A top-level string closed: remember it as the key, or report it if
+    /// it is the field's value
+    fn end_string(&mut self) -> Option<String> {
+        let text: String = serde_json::from_str(&self.raw.take()?).ok()?;
+        if !self.after_colon {
+            self.key = Some(text);
+            return None;
+        }
+        self.after_colon = false;
+        if self.key.as_deref() != Some(&self.field) {
+            return None;
+        }
+        self.found = Some(Some(text.clone()));
+        Some(text)
+    }
+}
+
+/// Parse JSON that may have been cut off, closing open strings, arrays and
+/// objects; None if it is malformed in some other way
+pub fn complete(text: &str) -> Option<Value> {
+    if let Ok(value) = serde_json::from_str(text) {
+        return Some(value);
+    }
+    let mut closers = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => closers.push('}'),
+            '[' => closers.push(']'),
+            '}' | ']' => {
+                closers.pop();
+            }
+            _ => {}
+        }
+    }
+
+    let mut fixed = text.to_string();
+    if escaped {
+        fixed.pop();
+    }
+    if in_string {
+        fixed.push('"');
+    }
+    let trimmed = fixed.trim_end().trim_end_matches(',').len();
+    fixed.truncate(trimmed);
+    if fixed.ends_with(':') {
+        fixed.push_str("null");
+    }
+    fixed.extend(closers.iter().rev());
+    serde_json::from_str(&fixed).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(fragments: &[&str]) -> Option<String> {
+        let mut scanner = FieldScanner::new("command");
+        let mut found = None;
+        for fragment in fragments {
+            if let Some(value) = scanner.feed(fragment) {
+                assert!(found.is_none(), "reported twice");
+                found = Some(value);
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_field_reported_once_complete() {
+        assert_eq!(
+            scan(&[
+                r#"{"comm"#,
+                r#"and": "rm -rf "#,
+                r#"/tmp/bu"#,
+                r#"ild", "timeout": 5}"#
+            ]),
+            Some("rm -rf /tmp/build".to_string())
+        );
+        // Incomplete value: nothing yet
+        assert_eq!(scan(&[r#"{"command": "rm -rf /"#]), None);
+        // Escapes are decoded and quotes inside the value don't end it
+        assert_eq!(
+            scan(&[r#"{"command": "echo \"a\"", "x": 1}"#]),
+            Some(r#"echo "a""#.to_string())
+        );
+        // Nested or non-string fields of the same name don't count
+        assert_eq!(
+            scan(&[r#"{"env": {"command": "rm -rf /"}, "command": "ls"}"#]),
+            Some("ls".to_string())
+        );
+        assert_eq!(scan(&[r#"{"command": ["rm", "-rf", "/"]}"#]), None);
+        assert_eq!(scan(&[r#"{"description": "command", "cwd": "/"}"#]), None);
+    }
+
+    #[test]
+    fn test_complete_cut_off_input() {
+        let cut = complete(r#"{"command": "rm -rf /", "args": ["-v", "--no-pre"#).unwrap();
+        assert_eq!(cut["command"], "rm -rf /");
+        assert_eq!(cut["args"][1], "--no-pre");
+        assert_eq!(
+            complete(r#"{"command": "echo \"#).unwrap()["command"],
+            "echo "
+        );
+        assert_eq!(
+            complete(r#"{"path": "/etc/passwd", "#).unwrap()["path"],
+            "/etc/passwd"
+        );
+        assert!(complete(r#"{"timeout":"#).unwrap()["timeout"].is_null());
+        assert!(complete(r#"{"a" "b"}"#).is_none());
+    }
+}
@@ -1,12 +1,121 @@
 //! Streaming (SSE) interception for Anthropic API responses.
 //!
 //! Buffers tool_use blocks until complete, then checks against rules.
-//! Text blocks and other events pass through immediately.
-
-use super::interceptor::{check_tool_use, ApiProvider, InterceptResult};
+//! Text, thinking and other blocks pass through immediately. With
+//! fine-grained tool streaming a tool_use block may end in invalid JSON; it
+//! is completed as far as possible (`partial_json::complete`) and checked.
+//! With `with_thinking_scan`, thinking text is also checked once its block
+//! ends, for reporting only: it has already reached the client, and
+//! altering it would break the block's signature.
+//!
+//! Buffering is bounded by `StreamLimits`, per tool call and per message.
+//! A tool call that outgrows them can't be checked: in enforce mode it is
+//! replaced by a block message (fail closed), in monitor mode it is passed
+//! through unchecked. Either way a Warning intercept is recorded.
+//!
+//! In enforce mode, shell tool calls are checked as soon as their `command`
+//! string is complete (see `partial_json`), so a dangerous command is cut
+//! off without waiting for the rest of the input. Shell tools are checked on
+//! `command` alone, so the early verdict is the one the full input gets;
+//! calls that pass are still checked again at `content_block_stop`.
+
+use super::attribution::Attribution;
+use super::interceptor::{
+    block_message, check_action, check_tool_use, is_exec_tool, ApiProvider, InterceptResult,
+    MessageOptions,
+};
+use super::partial_json::{self, FieldScanner};
 use crate::rules::Rule;
 use crate::rules::RuleAction;
+use crate::{ActionType, AgentAction, RiskLevel};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use tracing::{info, warn};
+
+/// Rule name of intercepts for tool calls that outgrew the stream buffer
+pub const BUFFER_LIMIT_RULE: &str = "stream_buffer_limit";
+
+/// Environment variables overriding the default `StreamLimits`
+pub const MAX_BLOCK_BYTES_ENV: &str = "OPENCLAW_HARNESS_SSE_MAX_BLOCK_BYTES";
+pub const MAX_BLOCK_EVENTS_ENV: &str = "OPENCLAW_HARNESS_SSE_MAX_BLOCK_EVENTS";
+pub const MAX_MESSAGE_BYTES_ENV: &str = "OPENCLAW_HARNESS_SSE_MAX_MESSAGE_BYTES";
+pub const MAX_MESSAGE_EVENTS_ENV: &str = "OPENCLAW_HARNESS_SSE_MAX_MESSAGE_EVENTS";
+
+/// Intercept content keeps this much of an oversized tool call's input
+const OVERFLOW_CONTENT_CHARS: usize = 500;
+
+/// How much of a stream may be held back while tool calls are checked
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StreamLimits {
+    /// Bytes of event data buffered for one tool call
+    pub max_block_bytes: usize,
+    /// Events buffered for one tool call
+    pub max_block_events: usize,
+    /// Bytes of event data buffered over a whole response
+    pub max_message_bytes: usize,
+    /// Events buffered over a whole response
+    pub max_message_events: usize,
+}
+
+impl Default for StreamLimits {
+    fn default() -> Self {
+        Self {
+            max_block_bytes: 16 * 1024 * 1024,
+            max_block_events: 200_000,
+            max_message_bytes: 64 * 1024 * 1024,
+            max_message_events: 1_000_000,
+        }
+    }
+}
+
+impl StreamLimits {
+    /// Defaults, overridden by `OPENCLAW_HARNESS_SSE_MAX_*`
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: usize| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|&v: &usize| v > 0)
+                .unwrap_or(default)
+        };
+        let defaults = Self::default();
+        Self {
+            max_block_bytes: var(MAX_BLOCK_BYTES_ENV, defaults.max_block_bytes),
+            max_block_events: var(MAX_BLOCK_EVENTS_ENV, defaults.max_block_events),
+            max_message_bytes: var(MAX_MESSAGE_BYTES_ENV, defaults.max_message_bytes),
+            max_message_events: var(MAX_MESSAGE_EVENTS_ENV, defaults.max_message_events),
+        }
+    }
+
+    /// The limit buffered amounts go over, if any
+    fn exceeded(&self, block: BufferUsage, message: BufferUsage) -> Option<String> {
+        if block.bytes > self.max_block_bytes {
+            Some(format!("{} bytes per tool call", self.max_block_bytes))
+        } else if block.events > self.max_block_events {
+            Some(format!("{} events per tool call", self.max_block_events))
+        } else if message.bytes > self.max_message_bytes {
+            Some(format!("{} bytes per message", self.max_message_bytes))
+        } else if message.events > self.max_message_events {
+            Some(format!("{} events per message", self.max_message_events))
+        } else {
+            None
+        }
+    }
+}
+
+/// Event data held back from the client
+#[derive(Debug, Clone, Copy, Default)]
+struct BufferUsage {
+    bytes: usize,
+    events: usize,
+}
+
+impl BufferUsage {
+    fn add(&mut self, event: &SseEvent) {
+        self.bytes += event.data.len();
+        self.events += 1;
+    }
+}
 /// A parsed SSE event
 #[derive(Debug, Clone)]
 pub struct SseEvent {
@@ -25,6 +134,10 @@ impl SseEvent {
 pub struct StreamInterceptor {
     rules: Vec<Rule>,
     enforce: bool,
+    /// How block messages are worded
+    messages: MessageOptions,
+    /// Agent/session the checked tool calls are attributed to
+    source: Attribution,
     provider: Option<ApiProvider>,
     /// Index of the tool_use block currently being buffered (Anthropic)
     buffering_index: Option<usize>,
@@ -36,6 +149,20 @@ pub struct StreamInterceptor {
     tool_id: String,
     /// Accumulated JSON fragments
     input_json_parts: Vec<String>,
+    /// Watches a shell tool's input for its complete `command` (enforce only)
+    command_scan: Option<FieldScanner>,
+    /// Buffering limits and what the current block / whole response hold
+    limits: StreamLimits,
+    block_usage: BufferUsage,
+    message_usage: BufferUsage,
+    /// Tool_use block already replaced (cut off early or outgrew the buffer);
+    /// the rest of it is dropped
+    dropping_index: Option<usize>,
+    /// Whether thinking blocks are checked against rules
+    scan_thinking: bool,
+    /// Index and text so far of the thinking block being scanned
+    thinking_index: Option<usize>,
+    thinking_text: String,
     /// Collected intercept results for alerting
     pub intercepts: Vec<InterceptResult>,
     // --- OpenAI streaming state ---
@@ -47,6 +174,9 @@ pub struct StreamInterceptor {
     openai_buffering: bool,
     /// OpenAI: last seen chunk id for generating replacement events
     openai_chunk_id: String,
+    /// OpenAI: tool calls outgrew the buffer; further tool_call chunks are
+    /// dropped (enforce) or passed through unchecked (monitor)
+    openai_overflowed: bool,
 }
 
 /// Accumulated OpenAI streaming tool call
@@ -55,6 +185,7 @@ struct OpenAiToolCallAccum {
     id: String,
     name: String,
     arguments: String,
+    usage: BufferUsage,
 }
 
 impl StreamInterceptor {
@@ -62,20 +193,66 @@ impl StreamInterceptor {
         Self {
             rules,
             enforce,
+            messages: MessageOptions::default(),
+            source: Attribution::default(),
             provider: None,
             buffering_index: None,
             buffer: Vec::new(),
             tool_name: String::new(),
             tool_id: String::new(),
             input_json_parts: Vec::new(),
+            command_scan: None,
+            limits: StreamLimits::default(),
+            block_usage: BufferUsage::default(),
+            message_usage: BufferUsage::default(),
+            dropping_index: None,
+            scan_thinking: false,
+            thinking_index: None,
+            thinking_text: String::new(),
             intercepts: Vec::new(),
             openai_tool_calls: std::collections::HashMap::new(),
             openai_buffer: Vec::new(),
             openai_buffering: false,
             openai_chunk_id: String::new(),
+            openai_overflowed: false,
         }
     }
 
+    /// Report rule matches in thinking blocks (never blocks or alters them)
+    pub fn with_thinking_scan(mut self, scan: bool) -> Self {
+        self.scan_thinking = scan;
+        self
+    }
+
+    /// Bound how much of the stream is buffered for checking
+    pub fn with_limits(mut self, limits: StreamLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Attribute checked tool calls to an agent and session
+    pub fn with_attribution(mut self, source: Attribution) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Embed canary tokens in block messages (see `crate::canary`)
+    pub fn with_canary_tokens(mut self, canary: bool) -> Self {
+        self.messages.canary_tokens = canary;
+        self
+    }
+
+    /// Add rule guidance to block messages (coaching mode)
+    pub fn with_coaching(mut self, coaching: bool) -> Self {
+        self.messages.coaching = coaching;
+        self
+    }
+
+    /// Provider recognized from the events so far
+    pub fn provider(&self) -> Option<ApiProvider> {
+        self.provider
+    }
+
     /// Detect provider from the first meaningful SSE event
     fn detect_provider(&mut self, event: &SseEvent) {
         if self.provider.is_some() {
@@ -160,8 +337,14 @@ impl StreamInterceptor {
             .pointer("/choices/0/finish_reason")
             .and_then(|f| f.as_str());
 
+        if self.openai_overflowed && (has_tool_calls || finish_reason == Some("tool_calls")) {
+            return if self.enforce { vec![] } else { vec![event] };
+        }
+
         if has_tool_calls {
             self.openai_buffering = true;
+            self.message_usage.add(&event);
+            let mut over_limit = None;
             // Accumulate tool call fragments
             if let Some(tool_calls) = parsed
                 .pointer("/choices/0/delta/tool_calls")
@@ -180,9 +363,19 @@ impl StreamInterceptor {
                     if let Some(args) = tc.pointer("/function/arguments").and_then(|a| a.as_str()) {
                         entry.arguments.push_str(args);
                     }
+                    entry.usage.add(&event);
+                    if over_limit.is_none() {
+                        over_limit = self
+                            .limits
+                            .exceeded(entry.usage, self.message_usage)
+                            .map(|limit| (index, limit));
+                    }
                 }
             }
             self.openai_buffer.push(event);
+            if let Some((index, limit)) = over_limit {
+                return self.openai_overflow(index, &limit);
+            }
             return vec![];
         }
 
@@ -201,9 +394,19 @@ impl StreamInterceptor {
             return events;
         }
 
-        let mut blocked_indices = std::collections::HashSet::new();
+        if !self.check_openai_tool_calls() || !self.enforce {
+            // Flush all buffered events
+            let events = std::mem::take(&mut self.openai_buffer);
+            self.openai_tool_calls.clear();
+            return events;
+        }
+
+        self.openai_replacement()
+    }
 
-        // Check each accumulated tool call
+    /// Check each accumulated tool call; returns whether any should be blocked
+    fn check_openai_tool_calls(&mut self) -> bool {
+        let mut blocked = false;
         let mut sorted_indices: Vec<usize> = self.openai_tool_calls.keys().cloned().collect();
         sorted_indices.sort();
 
@@ -211,41 +414,47 @@ impl StreamInterceptor {
             let tc = &self.openai_tool_calls[&idx];
             let input: Value =
                 serde_json::from_str(&tc.arguments).unwrap_or(Value::Object(Default::default()));
-            if let Some(result) = check_tool_use(idx, &tc.name, &input, &self.rules) {
-                let should_block = matches!(
+            if let Some(result) = check_tool_use(idx, &tc.name, &input, &self.rules, &self.source) {
+                blocked |= matches!(
                     result.action,
                     RuleAction::CriticalAlert | RuleAction::PauseAndAsk
                 );
                 self.intercepts.push(result);
-                if should_block {
-                    blocked_indices.insert(idx);
-                }
             }
         }
+        blocked
+    }
 
-        if blocked_indices.is_empty() || !self.enforce {
-            // Flush all buffered events
-            let events = std::mem::take(&mut self.openai_buffer);
+    /// A tool call outgrew the buffer: the complete ones before it are
+    /// still checked, then everything buffered is replaced or flushed
+    fn openai_overflow(&mut self, index: usize, limit: &str) -> Vec<SseEvent> {
+        self.openai_overflowed = true;
+        let tc = self.openai_tool_calls.remove(&index).unwrap_or_default();
+        self.check_openai_tool_calls();
+        let intercept = self.overflow_intercept(index, &tc.name, &tc.arguments, limit);
+        self.intercepts.push(intercept);
+
+        if self.enforce {
+            self.openai_replacement()
+        } else {
             self.openai_tool_calls.clear();
-            return events;
+            std::mem::take(&mut self.openai_buffer)
         }
+    }
 
-        // Generate replacement events: drop all buffered tool_call events, emit content message
+    /// Drop all buffered tool_call events and emit a content message instead
+    fn openai_replacement(&mut self) -> Vec<SseEvent> {
+        let messages = self.messages;
         let block_msgs: Vec<String> = self
             .intercepts
-            .iter()
+            .iter_mut()
             .filter(|i| {
                 matches!(
                     i.action,
                     RuleAction::CriticalAlert | RuleAction::PauseAndAsk
                 )
             })
-            .map(|i| {
-                format!(
-                    "🛡️ OpenClaw Harness blocked this action: [{}] {} (rule: {})",
-                    i.tool_name, i.reason, i.rule_name
-                )
-            })
+            .map(|i| block_message(i, messages))
             .collect();
 
         let replacement = serde_json::json!({
@@ -274,6 +483,47 @@ impl StreamInterceptor {
         ]
     }
 
+    /// Warning intercept for a tool call too large to buffer and check
+    fn overflow_intercept(
+        &self,
+        index: usize,
+        tool_name: &str,
+        input: &str,
+        limit: &str,
+    ) -> InterceptResult {
+        warn!(
+            "⚠️  {} tool call outgrew the stream buffer (over {}), {}",
+            tool_name,
+            limit,
+            if self.enforce {
+                "blocking it"
+            } else {
+                "passing it through unchecked"
+            }
+        );
+        InterceptResult {
+            block_index: index,
+            tool_name: tool_name.to_string(),
+            rule_name: BUFFER_LIMIT_RULE.to_string(),
+            action: if self.enforce {
+                RuleAction::CriticalAlert
+            } else {
+                RuleAction::Alert
+            },
+            risk_level: RiskLevel::Warning,
+            reason: format!("Tool call too large to check (over {})", limit),
+            action_type: ActionType::Unknown,
+            content: input.chars().take(OVERFLOW_CONTENT_CHARS).collect(),
+            target: None,
+            agent: self.source.agent.clone(),
+            session_id: self.source.session_id.clone(),
+            canary_token: None,
+            guidance: None,
+            field: None,
+            params: None,
+        }
+    }
+
     // --- Gemini processing ---
     fn process_gemini_event(&mut self, event: SseEvent) -> Vec<SseEvent> {
         let parsed: Value = match serde_json::from_str(&event.data) {
@@ -311,19 +561,19 @@ impl StreamInterceptor {
                     .unwrap_or(Value::Object(Default::default()));
 
                 let block_index = ci * 1000 + pi;
-                if let Some(result) = check_tool_use(block_index, name, &args, &self.rules) {
+                if let Some(result) =
+                    check_tool_use(block_index, name, &args, &self.rules, &self.source)
+                {
                     let should_block = matches!(
                         result.action,
                         RuleAction::CriticalAlert | RuleAction::PauseAndAsk
                     );
-                    self.intercepts.push(result.clone());
+                    self.intercepts.push(result);
 
                     if should_block && self.enforce {
                         has_blocked = true;
-                        let block_msg = format!(
-                            "🛡️ OpenClaw Harness blocked this action: [{}] {} (rule: {})",
-                            result.tool_name, result.reason, result.rule_name
-                        );
+                        let intercept = self.intercepts.last_mut().unwrap();
+                        let block_msg = block_message(intercept, self.messages);
                         modified
                             .as_object_mut()
                             .unwrap()
@@ -373,46 +623,173 @@ impl StreamInterceptor {
 
                 self.buffering_index = Some(index);
                 self.buffer.clear();
+                self.block_usage = BufferUsage::default();
+                self.block_usage.add(&event);
+                self.message_usage.add(&event);
                 self.buffer.push(event);
+                self.command_scan =
+                    (self.enforce && is_exec_tool(&name)).then(|| FieldScanner::new("command"));
                 self.tool_name = name;
                 self.tool_id = id;
                 self.input_json_parts.clear();
                 return vec![];
             }
+            if block_type == "thinking" && self.scan_thinking {
+                let index = parsed.get("index").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+                self.thinking_index = Some(index);
+                self.thinking_text.clear();
+            }
         }
         // Not tool_use → passthrough
         vec![event]
     }
 
     fn handle_block_delta(&mut self, event: SseEvent) -> Vec<SseEvent> {
-        if self.buffering_index.is_some() {
+        if self.dropping_index.is_some() {
+            return vec![];
+        }
+        if let Some(index) = self.buffering_index {
             // Accumulate JSON fragment
+            let mut command = None;
             if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(&event.data) {
                 if let Some(partial) = parsed
                     .pointer("/delta/partial_json")
                     .and_then(|v| v.as_str())
                 {
+                    command = self.command_scan.as_mut().and_then(|s| s.feed(partial));
                     self.input_json_parts.push(partial.to_string());
                 }
             }
+            self.block_usage.add(&event);
+            self.message_usage.add(&event);
             self.buffer.push(event);
-            vec![]
+            if let Some(command) = command {
+                self.command_scan = None;
+                if let Some(events) = self.check_command_early(index, command) {
+                    return events;
+                }
+            }
+            match self.limits.exceeded(self.block_usage, self.message_usage) {
+                Some(limit) => self.block_overflow(index, &limit),
+                None => vec![],
+            }
         } else {
+            if self.thinking_index.is_some() {
+                self.collect_thinking(&event);
+            }
             vec![event]
         }
     }
 
+    fn collect_thinking(&mut self, event: &SseEvent) {
+        let Ok(parsed) = serde_json::from_str::<Value>(&event.data) else {
+            return;
+        };
+        if let Some(text) = parsed.pointer("/delta/thinking").and_then(|v| v.as_str()) {
+            // Only what fits in a tool call's buffer is checked
+            if self.thinking_text.len() + text.len() <= self.limits.max_block_bytes {
+                self.thinking_text.push_str(text);
+            }
+        }
+    }
+
+    /// Check a finished thinking block; matches are reported as alerts only
+    fn check_thinking(&mut self, index: usize) {
+        let text = std::mem::take(&mut self.thinking_text);
+        if text.is_empty() {
+            return;
+        }
+        let action = AgentAction {
+            id: crate::ids::generate(crate::ids::Source::Proxy),
+            timestamp: chrono::Utc::now(),
+            agent: self.source.agent.clone(),
+            action_type: ActionType::Unknown,
+            content: text,
+            target: None,
+            session_id: self.source.session_id.clone(),
+            metadata: None,
+        };
+        if let Some(mut result) = check_action(index, "thinking", &action, &self.rules) {
+            result.action = RuleAction::Alert;
+            self.intercepts.push(result);
+        }
+    }
+
+    /// Check a shell tool call on its complete `command` before the rest of
+    /// its input arrives; replacement events if it is blocked
+    fn check_command_early(&mut self, index: usize, command: String) -> Option<Vec<SseEvent>> {
+        let input = serde_json::json!({ "command": command });
+        let result = check_tool_use(index, &self.tool_name, &input, &self.rules, &self.source)?;
+        if !matches!(
+            result.action,
+            RuleAction::CriticalAlert | RuleAction::PauseAndAsk
+        ) {
+            // Recorded by the full check at content_block_stop
+            return None;
+        }
+        info!(
+            "✂️  Cut off {} tool call early ({})",
+            self.tool_name, result.rule_name
+        );
+        self.intercepts.push(result);
+        self.buffering_index = None;
+        self.dropping_index = Some(index);
+        self.buffer.clear();
+        self.input_json_parts.clear();
+        let intercept = self.intercepts.last_mut().unwrap();
+        let block_msg = block_message(intercept, self.messages);
+        Some(replacement_block(index, &block_msg))
+    }
+
+    /// The tool_use block outgrew the buffer: replace it (enforce) or flush
+    /// it and let the rest through unchecked (monitor)
+    fn block_overflow(&mut self, index: usize, limit: &str) -> Vec<SseEvent> {
+        self.buffering_index = None;
+        let input: String = self.input_json_parts.drain(..).collect();
+        let mut intercept = self.overflow_intercept(index, &self.tool_name, &input, limit);
+
+        if self.enforce {
+            self.dropping_index = Some(index);
+            self.buffer.clear();
+            let block_msg = block_message(&mut intercept, self.messages);
+            self.intercepts.push(intercept);
+            replacement_block(index, &block_msg)
+        } else {
+            self.intercepts.push(intercept);
+            std::mem::take(&mut self.buffer)
+        }
+    }
+
     fn handle_block_stop(&mut self, event: SseEvent) -> Vec<SseEvent> {
+        if self.dropping_index.take().is_some() {
+            // The replacement block was already closed
+            return vec![];
+        }
         if let Some(index) = self.buffering_index.take() {
             self.buffer.push(event);
 
-            // Assemble full input JSON
+            // Assemble full input JSON; fine-grained streaming may leave it cut off
             let full_json_str: String = self.input_json_parts.drain(..).collect();
-            let input_value: serde_json::Value = serde_json::from_str(&full_json_str)
-                .unwrap_or(serde_json::Value::Object(Default::default()));
+            let input_value = if full_json_str.trim().is_empty() {
+                Value::Object(Default::default())
+            } else {
+                partial_json::complete(&full_json_str).unwrap_or_else(|| {
+                    warn!(
+                        "⚠️  Unparseable input for {} tool call, checking it as empty",
+                        self.tool_name
+                    );
+                    Value::Object(Default::default())
+                })
+            };
 
             // Check against rules
-            let result = check_tool_use(index, &self.tool_name, &input_value, &self.rules);
+            let result = check_tool_use(
+                index,
+                &self.tool_name,
+                &input_value,
+                &self.rules,
+                &self.source,
+            );
 
             let should_block = match &result {
                 Some(r) => matches!(
@@ -427,53 +804,56 @@ impl StreamInterceptor {
             }
 
             if should_block && self.enforce {
-                let intercept = self.intercepts.last().unwrap();
-                let block_msg = format!(
-                    "🛡️ OpenClaw Harness blocked this action: [{}] {} (rule: {})",
-                    intercept.tool_name, intercept.reason, intercept.rule_name
-                );
-
-                // Return replacement text block events with same index
-                let start_data = serde_json::json!({
-                    "type": "content_block_start",
-                    "index": index,
-                    "content_block": {"type": "text", "text": ""}
-                });
-                let delta_data = serde_json::json!({
-                    "type": "content_block_delta",
-                    "index": index,
-                    "delta": {"type": "text_delta", "text": block_msg}
-                });
-                let stop_data = serde_json::json!({
-                    "type": "content_block_stop",
-                    "index": index
-                });
-
+                let intercept = self.intercepts.last_mut().unwrap();
+                let block_msg = block_message(intercept, self.messages);
                 self.buffer.clear();
-                vec![
-                    SseEvent {
-                        event_type: "content_block_start".into(),
-                        data: start_data.to_string(),
-                    },
-                    SseEvent {
-                        event_type: "content_block_delta".into(),
-                        data: delta_data.to_string(),
-                    },
-                    SseEvent {
-                        event_type: "content_block_stop".into(),
-                        data: stop_data.to_string(),
-                    },
-                ]
+                replacement_block(index, &block_msg)
             } else {
                 // Safe or monitor mode → flush buffer
                 std::mem::take(&mut self.buffer)
             }
         } else {
+            if let Some(index) = self.thinking_index.take() {
+                self.check_thinking(index);
+            }
             vec![event]
         }
     }
 }
 
+/// Text block events standing in for a blocked tool_use block at `index`
+fn replacement_block(index: usize, text: &str) -> Vec<SseEvent> {
+    let start_data = serde_json::json!({
+        "type": "content_block_start",
+        "index": index,
+        "content_block": {"type": "text", "text": ""}
+    });
+    let delta_data = serde_json::json!({
+        "type": "content_block_delta",
+        "index": index,
+        "delta": {"type": "text_delta", "text": text}
+    });
+    let stop_data = serde_json::json!({
+        "type": "content_block_stop",
+        "index": index
+    });
+
+    vec![
+        SseEvent {
+            event_type: "content_block_start".into(),
+            data: start_data.to_string(),
+        },
+        SseEvent {
+            event_type: "content_block_delta".into(),
+            data: delta_data.to_string(),
+        },
+        SseEvent {
+            event_type: "content_block_stop".into(),
+            data: stop_data.to_string(),
+        },
+    ]
+}
+
 /// Parse a raw SSE text chunk into events.
 /// SSE events are separated by blank lines. Each event has optional `event:` and `data:` lines.
 pub fn parse_sse_events(raw: &str) -> Vec<SseEvent> {
@@ -529,8 +909,21 @@ pub fn parse_sse_events(raw: &str) -> Vec<SseEvent> {
 
 /// Line buffer for accumulating SSE chunks across network boundaries.
 /// Yields complete SSE event text blocks (delimited by blank lines).
+///
+/// Lines may end in `\n`, `\r\n` or `\r`, as the SSE spec allows; they
+/// are normalized to `\n` so an upstream can't hide events from the
+/// interceptor by framing them with `\r\n\r\n`.
+///
+/// Raw chunks go in through `feed_bytes`, which decodes UTF-8 across chunk
+/// boundaries: a character split between two chunks is held until it is
+/// complete, and invalid bytes become U+FFFD. Every byte reaches the
+/// interceptor, so splitting a character can't smuggle a tool call past it.
 pub struct SseLineBuffer {
     buf: String,
+    /// The last chunk ended in `\r`, so a leading `\n` belongs to it
+    pending_cr: bool,
+    /// Start of a multi-byte character the last chunk ended in
+    partial_char: Vec<u8>,
 }
 
 impl Default for SseLineBuffer {
@@ -541,12 +934,60 @@ impl Default for SseLineBuffer {
 
 impl SseLineBuffer {
     pub fn new() -> Self {
-        Self { buf: String::new() }
+        Self {
+            buf: String::new(),
+            pending_cr: false,
+            partial_char: Vec::new(),
+        }
+    }
+
+    /// Feed a chunk of raw bytes, decoding UTF-8 across chunks. Returns
+    /// complete SSE event blocks ready for parsing.
+    pub fn feed_bytes(&mut self, chunk: &[u8]) -> Vec<String> {
+        let mut bytes = std::mem::take(&mut self.partial_char);
+        bytes.extend_from_slice(chunk);
+        let mut text = String::with_capacity(bytes.len());
+        let mut rest = bytes.as_slice();
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    // Checked by from_utf8
+                    text.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // Cut off mid-character; the next chunk has the rest
+                        None => {
+                            self.partial_char = after.to_vec();
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+        self.feed(&text)
     }
 
     /// Feed a chunk of bytes. Returns complete SSE event blocks ready for parsing.
     pub fn feed(&mut self, chunk: &str) -> Vec<String> {
-        self.buf.push_str(chunk);
+        for c in chunk.chars() {
+            if std::mem::take(&mut self.pending_cr) && c == '\n' {
+                continue;
+            }
+            if c == '\r' {
+                self.buf.push('\n');
+                self.pending_cr = true;
+            } else {
+                self.buf.push(c);
+            }
+        }
         let mut results = Vec::new();
 
         // Split on double newline (SSE event boundary)
@@ -561,6 +1002,16 @@ impl SseLineBuffer {
 
         results
     }
+
+    /// The last event, if the stream ended without a blank line after it
+    pub fn finish(&mut self) -> Option<String> {
+        if !std::mem::take(&mut self.partial_char).is_empty() {
+            self.buf.push(char::REPLACEMENT_CHARACTER);
+        }
+        let rest = std::mem::take(&mut self.buf);
+        self.pending_cr = false;
+        (!rest.trim().is_empty()).then(|| format!("{}\n\n", rest))
+    }
 }
 
 #[cfg(test)]
@@ -805,6 +1256,58 @@ mod tests {
         // Complete the event
         let r2 = buf.feed(":\"message_start\"}\n\nevent: ping\ndata: {}\n\n");
         assert_eq!(r2.len(), 2);
+
+        // CRLF framing, split between \r and \n, and no final blank line
+        assert!(buf.feed("event: ping\r\ndata: {}\r").is_empty());
+        assert_eq!(
+            buf.feed("\n\r\nevent: message_stop\r\n"),
+            ["event: ping\ndata: {}\n\n"]
+        );
+        assert_eq!(buf.finish().as_deref(), Some("event: message_stop\n\n\n"));
+        assert_eq!(buf.finish(), None);
+    }
+
+    #[test]
+    fn test_sse_line_buffer_split_utf8() {
+        // A tool call whose input has a character split across chunks
+        let stream = concat!(
+            "event: content_block_start\n",
+            r#"data: {"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_1","name":"exec"}}"#,
+            "\n\nevent: content_block_delta\n",
+            r#"data: {"type":"content_block_delta","index":0,"delta":{"type":"input_json_delta","partial_json":"{\"command\": \"echo 🦀 && rm -rf /\"}"}}"#,
+            "\n\nevent: content_block_stop\n",
+            r#"data: {"type":"content_block_stop","index":0}"#,
+            "\n\n",
+        )
+        .as_bytes();
+        let crab = stream
+            .windows(4)
+            .position(|w| w == "🦀".as_bytes())
+            .unwrap();
+
+        let mut buf = SseLineBuffer::new();
+        let mut interceptor = StreamInterceptor::new(get_rules(), true);
+        let mut output = vec![];
+        for chunk in [
+            &stream[..crab + 1],
+            &stream[crab + 1..crab + 3],
+            &stream[crab + 3..],
+        ] {
+            for block in buf.feed_bytes(chunk) {
+                for event in parse_sse_events(&block) {
+                    output.extend(interceptor.process_event(event));
+                }
+            }
+        }
+        assert_eq!(interceptor.intercepts.len(), 1);
+        assert!(output.iter().all(|e| !e.data.contains("rm -rf")));
+
+        // Invalid bytes are replaced, not passed through
+        let mut buf = SseLineBuffer::new();
+        let blocks = buf.feed_bytes(b"data: a\xffb\n\n");
+        assert_eq!(blocks, ["data: a\u{fffd}b\n\n"]);
+        assert!(buf.feed_bytes(b"data: \xf0\x9f").is_empty());
+        assert_eq!(buf.finish().as_deref(), Some("data: \u{fffd}\n\n"));
     }
 
     // --- OpenAI streaming tests ---
@@ -959,4 +1462,213 @@ mod tests {
         // But intercept is still recorded
         assert_eq!(interceptor.intercepts.len(), 1);
     }
+
+    #[test]
+    fn test_buffer_limits_fail_closed() {
+        let limits = StreamLimits {
+            max_block_events: 3,
+            ..Default::default()
+        };
+        let start = make_event(
+            "content_block_start",
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"toolu_1","name":"exec"}}"#,
+        );
+        let delta = make_event(
+            "content_block_delta",
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\": \"echo "}}"#,
+        );
+        let stop = make_event(
+            "content_block_stop",
+            r#"{"type":"content_block_stop","index":1}"#,
+        );
+        let stream = || {
+            let mut events = vec![start.clone()];
+            events.extend(std::iter::repeat_n(delta.clone(), 5));
+            events.push(stop.clone());
+            events.push(make_event("message_stop", r#"{"type":"message_stop"}"#));
+            events
+        };
+
+        // Enforce: replaced once the block outgrows the limit, the rest dropped
+        let mut interceptor = StreamInterceptor::new(get_rules(), true).with_limits(limits);
+        let output: Vec<SseEvent> = stream()
+            .into_iter()
+            .flat_map(|e| interceptor.process_event(e))
+            .collect();
+        let types: Vec<&str> = output.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(
+            types,
+            [
+                "content_block_start",
+                "content_block_delta",
+                "content_block_stop",
+                "message_stop"
+            ]
+        );
+        assert!(output[1].data.contains(BUFFER_LIMIT_RULE));
+        assert_eq!(interceptor.intercepts.len(), 1);
+        assert_eq!(interceptor.intercepts[0].risk_level, RiskLevel::Warning);
+        assert_eq!(interceptor.intercepts[0].action, RuleAction::CriticalAlert);
+
+        // Monitor: everything passes through, unchecked but recorded
+        let mut interceptor = StreamInterceptor::new(get_rules(), false).with_limits(limits);
+        let output: Vec<SseEvent> = stream()
+            .into_iter()
+            .flat_map(|e| interceptor.process_event(e))
+            .collect();
+        assert_eq!(output.len(), stream().len());
+        assert_eq!(interceptor.intercepts.len(), 1);
+        assert_eq!(interceptor.intercepts[0].action, RuleAction::Alert);
+
+        // OpenAI: the message limit covers all tool calls together
+        let limits = StreamLimits {
+            max_message_bytes: 300,
+            ..Default::default()
+        };
+        let mut interceptor = StreamInterceptor::new(get_rules(), true).with_limits(limits);
+        let chunk = |index: usize| {
+            make_event(
+                "message",
+                &format!(
+                    r#"{{"id":"chatcmpl-1","choices":[{{"delta":{{"tool_calls":[{{"index":{},"function":{{"name":"exec","arguments":"{{}}"}}}}]}},"index":0}}]}}"#,
+                    index
+                ),
+            )
+        };
+        let mut output = Vec::new();
+        for index in 0..4 {
+            output.extend(interceptor.process_event(chunk(index)));
+        }
+        output.extend(interceptor.process_event(make_event(
+            "message",
+            r#"{"id":"chatcmpl-1","choices":[{"delta":{},"index":0,"finish_reason":"tool_calls"}]}"#,
+        )));
+        output.extend(interceptor.process_event(make_event("message", "[DONE]")));
+        assert_eq!(output.len(), 3);
+        assert!(output[0].data.contains(BUFFER_LIMIT_RULE));
+        assert!(output[1].data.contains(r#""finish_reason":"stop""#));
+        assert_eq!(output[2].data, "[DONE]");
+        assert!(!output.iter().any(|e| e.data.contains("tool_calls")));
+    }
+
+    #[test]
+    fn test_command_checked_before_block_stop() {
+        let delta = |partial: &str| {
+            make_event(
+                "content_block_delta",
+                &serde_json::json!({
+                    "type": "content_block_delta",
+                    "index": 0,
+                    "delta": {"type": "input_json_delta", "partial_json": partial}
+                })
+                .to_string(),
+            )
+        };
+        let start = make_event(
+            "content_block_start",
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"tool_use","id":"toolu_1","name":"Bash"}}"#,
+        );
+        let stop = make_event(
+            "content_block_stop",
+            r#"{"type":"content_block_stop","index":0}"#,
+        );
+
+        // Blocked on the delta that completes the command; the rest is dropped
+        let mut interceptor = StreamInterceptor::new(get_rules(), true);
+        assert!(interceptor.process_event(start.clone()).is_empty());
+        assert!(interceptor
+            .process_event(delta(r#"{"command": "rm -rf /"#))
+            .is_empty());
+        // Not checked while the command may still go on
+        assert!(interceptor.intercepts.is_empty());
+        let cut = interceptor.process_event(delta(r#"", "description": "Clean up"#));
+        assert_eq!(cut.len(), 3);
+        assert!(cut[1].data.contains("OpenClaw Harness blocked"));
+        assert!(interceptor
+            .process_event(delta(r#" everything"}"#))
+            .is_empty());
+        assert!(interceptor.process_event(stop.clone()).is_empty());
+        assert_eq!(interceptor.intercepts.len(), 1);
+
+        // Safe commands are held until content_block_stop, then flushed whole
+        let mut interceptor = StreamInterceptor::new(get_rules(), true);
+        let mut output = interceptor.process_event(start);
+        for partial in [r#"{"command": "ls -la""#, r#", "cwd": "/"}"#] {
+            output.extend(interceptor.process_event(delta(partial)));
+        }
+        assert!(output.is_empty());
+        output.extend(interceptor.process_event(stop));
+        assert_eq!(output.len(), 4);
+        assert!(interceptor.intercepts.is_empty());
+    }
+
+    #[test]
+    fn test_thinking_and_fine_grained_tool_streaming() {
+        let events = vec![
+            make_event(
+                "content_block_start",
+                r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            ),
+            make_event(
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Simplest is to run rm -rf / and"}}"#,
+            ),
+            make_event(
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"EqQBCgIYAhIM"}}"#,
+            ),
+            make_event(
+                "content_block_stop",
+                r#"{"type":"content_block_stop","index":0}"#,
+            ),
+            make_event(
+                "content_block_start",
+                r#"{"type":"content_block_start","index":1,"content_block":{"type":"redacted_thinking","data":"EmwKAhgB"}}"#,
+            ),
+            make_event(
+                "content_block_stop",
+                r#"{"type":"content_block_stop","index":1}"#,
+            ),
+            // Fine-grained streaming: input ends mid-JSON
+            make_event(
+                "content_block_start",
+                r#"{"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"exec","input":{}}}"#,
+            ),
+            make_event(
+                "content_block_delta",
+                r#"{"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"cwd\": \"/\", \"command\": \"sudo rm -rf /var/li"}}"#,
+            ),
+            make_event(
+                "content_block_stop",
+                r#"{"type":"content_block_stop","index":2}"#,
+            ),
+        ];
+
+        let mut interceptor = StreamInterceptor::new(get_rules(), true).with_thinking_scan(true);
+        let output: Vec<SseEvent> = events
+            .iter()
+            .cloned()
+            .flat_map(|e| interceptor.process_event(e))
+            .collect();
+
+        // Thinking and redacted thinking pass through byte for byte
+        for (out, original) in output.iter().zip(&events[..6]) {
+            assert_eq!(out.data, original.data);
+        }
+        assert_eq!(output.len(), 9);
+        assert!(output[7].data.contains("OpenClaw Harness blocked"));
+
+        assert_eq!(interceptor.intercepts.len(), 2);
+        let thinking = &interceptor.intercepts[0];
+        assert_eq!(thinking.tool_name, "thinking");
+        assert_eq!(thinking.action, RuleAction::Alert);
+        assert!(interceptor.intercepts[1].content.starts_with("sudo rm -rf"));
+
+        // Without the scan, thinking is not checked
+        let mut interceptor = StreamInterceptor::new(get_rules(), true);
+        for e in events.into_iter().take(4) {
+            interceptor.process_event(e);
+        }
+        assert!(interceptor.intercepts.is_empty());
+    }
 }
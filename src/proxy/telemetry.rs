@@ -0,0 +1,173 @@
+//! Telling downstream tooling what the proxy did
+//!
+//! With `telemetry: true` (or `proxy start --telemetry`), responses to API
+//! calls report how the harness acted, so agent wrappers and CI can react
+//! without string-matching block messages:
+//!
+//! - `x-harness-mode`: `enforce` or `monitor`, on every API response
+//! - `x-harness-intercepts`, `x-harness-blocked` and `x-harness-rules`
+//!   (comma-separated) on non-streaming responses
+//! - an `x_harness` field in JSON bodies with intercepts, listing each match
+//! - for streams, whose headers go out before anything is checked, a final
+//!   SSE comment `: x_harness {...}` with the same JSON (SSE clients ignore
+//!   comments)
+
+use super::config::ProxyMode;
+use super::interceptor::{behavior, InterceptResult, ProxyBehavior};
+use crate::rules::RuleAction;
+use crate::RiskLevel;
+use serde::Serialize;
+
+pub const MODE_HEADER: &str = "x-harness-mode";
+pub const INTERCEPTS_HEADER: &str = "x-harness-intercepts";
+pub const BLOCKED_HEADER: &str = "x-harness-blocked";
+pub const RULES_HEADER: &str = "x-harness-rules";
+/// Field added to JSON bodies, and name of the closing SSE comment
+pub const BODY_FIELD: &str = "x_harness";
+
+/// What the proxy did with one response
+#[derive(Debug, Clone, Serialize)]
+pub struct Telemetry {
+    pub mode: ProxyMode,
+    pub intercepts: usize,
+    pub blocked: usize,
+    pub matches: Vec<RuleMatch>,
+}
+
+/// One tool call a rule matched
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub tool: String,
+    pub risk_level: RiskLevel,
+    pub action: RuleAction,
+    /// Replaced by a block message
+    pub blocked: bool,
+}
+
+impl Telemetry {
+    pub fn new(mode: ProxyMode, intercepts: &[InterceptResult]) -> Self {
+        let enforce = mode == ProxyMode::Enforce;
+        let matches: Vec<RuleMatch> = intercepts
+            .iter()
+            .map(|i| RuleMatch {
+                rule: i.rule_name.clone(),
+                tool: i.tool_name.clone(),
+                risk_level: i.risk_level,
+                action: i.action,
+                blocked: behavior(Some(i), enforce) == ProxyBehavior::Replace,
+            })
+            .collect();
+        Self {
+            mode,
+            intercepts: matches.len(),
+            blocked: matches.iter().filter(|m| m.blocked).count(),
+            matches,
+        }
+    }
+
+    /// Headers for a response whose body has been checked
+    pub fn headers(&self) -> Vec<(&'static str, String)> {
+        let mut rules: Vec<&str> = self.matches.iter().map(|m| m.rule.as_str()).collect();
+        rules.sort();
+        rules.dedup();
+        let mut headers = vec![
+            (MODE_HEADER, mode_name(self.mode).to_string()),
+            (INTERCEPTS_HEADER, self.intercepts.to_string()),
+            (BLOCKED_HEADER, self.blocked.to_string()),
+        ];
+        if !rules.is_empty() {
+            headers.push((RULES_HEADER, rules.join(",")));
+        }
+        headers
+    }
+
+    /// `body` with the `x_harness` field, if there were intercepts and it
+    /// is a JSON object
+    pub fn add_to_body(&self, body: &[u8]) -> Option<Vec<u8>> {
+        if self.matches.is_empty() {
+            return None;
+        }
+        let mut json: serde_json::Value = serde_json::from_slice(body).ok()?;
+        json.as_object_mut()?
+            .insert(BODY_FIELD.to_string(), serde_json::to_value(self).ok()?);
+        serde_json::to_vec(&json).ok()
+    }
+
+    /// The closing comment of a stream
+    pub fn sse_comment(&self) -> Vec<u8> {
+        format!(
+            ": {} {}\n\n",
+            BODY_FIELD,
+            serde_json::to_string(self).unwrap_or_default()
+        )
+        .into_bytes()
+    }
+}
+
+pub fn mode_name(mode: ProxyMode) -> &'static str {
+    match mode {
+        ProxyMode::Enforce => "enforce",
+        ProxyMode::Monitor => "monitor",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proxy::attribution::Attribution;
+    use crate::proxy::interceptor::{intercept_response, MessageOptions};
+    use crate::proxy::streaming::parse_sse_events;
+
+    #[test]
+    fn test_telemetry_reports_intercepts() {
+        let mut rules = crate::rules::default_rules();
+        for rule in &mut rules {
+            rule.compile().unwrap();
+        }
+        let body = serde_json::json!({
+            "type": "message",
+            "content": [
+                {"type": "tool_use", "id": "t1", "name": "exec", "input": {"command": "rm -rf ~/"}},
+                {"type": "tool_use", "id": "t2", "name": "exec", "input": {"command": "ls"}}
+            ]
+        });
+        let bytes = serde_json::to_vec(&body).unwrap();
+        let (modified, intercepts) = intercept_response(
+            &bytes,
+            &rules,
+            true,
+            MessageOptions::default(),
+            &Attribution::default(),
+        );
+
+        let telemetry = Telemetry::new(ProxyMode::Enforce, &intercepts);
+        let headers = telemetry.headers();
+        assert!(headers.contains(&(MODE_HEADER, "enforce".to_string())));
+        assert!(headers.contains(&(INTERCEPTS_HEADER, "1".to_string())));
+        assert!(headers.contains(&(BLOCKED_HEADER, "1".to_string())));
+        assert!(headers
+            .iter()
+            .any(|(h, v)| *h == RULES_HEADER && v == &intercepts[0].rule_name));
+
+        let with_field: serde_json::Value =
+            serde_json::from_slice(&telemetry.add_to_body(&modified).unwrap()).unwrap();
+        assert_eq!(with_field[BODY_FIELD]["blocked"], 1);
+        assert_eq!(with_field[BODY_FIELD]["matches"][0]["tool"], "exec");
+        assert_eq!(with_field["content"][1]["type"], "tool_use");
+
+        // Monitor mode flags without blocking
+        let monitor = Telemetry::new(ProxyMode::Monitor, &intercepts);
+        assert_eq!((monitor.intercepts, monitor.blocked), (1, 0));
+
+        // Nothing matched: headers say so, the body is left alone
+        let quiet = Telemetry::new(ProxyMode::Enforce, &[]);
+        assert!(quiet.add_to_body(&bytes).is_none());
+        assert!(!quiet.headers().iter().any(|(h, _)| *h == RULES_HEADER));
+
+        // The stream comment is invisible to SSE parsers
+        let comment = String::from_utf8(telemetry.sse_comment()).unwrap();
+        assert!(comment.starts_with(": x_harness {"));
+        assert!(parse_sse_events(&comment).is_empty());
+    }
+}
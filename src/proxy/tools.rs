@@ -0,0 +1,174 @@
+//! Tool name mappings for in-house agent tools
+//!
+//! Built-in tool names (`exec`, `Write`, `web_fetch`, ...) are classified by
+//! `interceptor`. Other tools can be declared in `config/tools.yaml` or
+//! `~/.openclaw-harness/tools.yaml` (first file that loads), saying which
+//! action type they are and which input fields hold the checked content and
+//! the target path or URL:
+//!
+//! ```yaml
+//! - name: run_sql
+//!   action_type: exec
+//!   content: [query]
+//! - name: upload_artifact
+//!   action_type: file_write
+//!   content: [body, data]
+//!   target: [dest.path]
+//! ```
+//!
+//! Fields are tried in order, the first string found wins; nested fields
+//! use dots. A declared mapping takes precedence over the built-in one.
+
+use crate::ActionType;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolMapping {
+    /// Tool name as the model calls it
+    pub name: String,
+    pub action_type: ActionType,
+    /// Fields holding the command / content to check
+    #[serde(default)]
+    pub content: Vec<String>,
+    /// Fields holding the file path or URL the tool acts on
+    #[serde(default)]
+    pub target: Vec<String>,
+}
+
+impl ToolMapping {
+    /// (action type, content, target) of a call to this tool; a tool with
+    /// only a target is checked on the target
+    fn classify(&self, input: &Value) -> (ActionType, String, Option<String>) {
+        let target = first_string(input, &self.target);
+        let content = first_string(input, &self.content)
+            .or_else(|| self.content.is_empty().then(|| target.clone()).flatten())
+            .unwrap_or_default();
+        (self.action_type.clone(), content, target)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToolRegistry {
+    tools: Vec<ToolMapping>,
+}
+
+impl ToolRegistry {
+    pub fn new(tools: Vec<ToolMapping>) -> Self {
+        Self { tools }
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(Self::new(serde_yaml::from_str(&content)?))
+    }
+
+    /// Mappings from the first tools file that loads
+    pub fn load() -> Self {
+        for path in candidate_paths().iter().filter(|p| p.exists()) {
+            match Self::from_file(path) {
+                Ok(registry) => {
+                    info!(
+                        "🧰 Loaded {} tool mapping(s) from {}",
+                        registry.tools.len(),
+                        path.display()
+                    );
+                    return registry;
+                }
+                Err(e) => warn!("Ignoring {}: {}", path.display(), e),
+            }
+        }
+        Self::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ToolMapping> {
+        self.tools.iter().find(|t| t.name == name)
+    }
+
+    /// Classify a call if the tool is declared
+    pub fn classify(
+        &self,
+        name: &str,
+        input: &Value,
+    ) -> Option<(ActionType, String, Option<String>)> {
+        self.get(name).map(|tool| tool.classify(input))
+    }
+}
+
+fn candidate_paths() -> [PathBuf; 2] {
+    [
+        PathBuf::from("config/tools.yaml"),
+        crate::harness_path("tools.yaml"),
+    ]
+}
+
+/// Declared tools, loaded once per process
+pub fn registry() -> &'static ToolRegistry {
+    static REGISTRY: OnceLock<ToolRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(ToolRegistry::load)
+}
+
+fn first_string(input: &Value, fields: &[String]) -> Option<String> {
+    fields.iter().find_map(|field| {
+        field
+            .split('.')
+            .try_fold(input, |value, key| value.get(key))
+            .and_then(|v| v.as_str())
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_declared_tools_are_classified() {
+        let registry = ToolRegistry::new(
+            serde_yaml::from_str(
+                r#"
+- name: run_sql
+  action_type: exec
+  content: [query]
+- name: upload_artifact
+  action_type: file_write
+  content: [body, data]
+  target: [dest.path]
+- name: open_page
+  action_type: http_request
+  target: [link]
+"#,
+            )
+            .unwrap(),
+        );
+
+        let (action_type, content, target) = registry
+            .classify("run_sql", &serde_json::json!({"query": "DROP TABLE users"}))
+            .unwrap();
+        assert_eq!(action_type, ActionType::Exec);
+        assert_eq!(content, "DROP TABLE users");
+        assert_eq!(target, None);
+
+        let (action_type, content, target) = registry
+            .classify(
+                "upload_artifact",
+                &serde_json::json!({"data": "AKIA...", "dest": {"path": "/etc/cron.d/x"}}),
+            )
+            .unwrap();
+        assert_eq!(action_type, ActionType::FileWrite);
+        assert_eq!(content, "AKIA...");
+        assert_eq!(target.as_deref(), Some("/etc/cron.d/x"));
+
+        let (_, content, _) = registry
+            .classify(
+                "open_page",
+                &serde_json::json!({"link": "https://pastebin.com/x"}),
+            )
+            .unwrap();
+        assert_eq!(content, "https://pastebin.com/x");
+        assert!(registry.classify("exec", &serde_json::json!({})).is_none());
+    }
+}
@@ -0,0 +1,183 @@
+//! Token usage and cost accounting
+//!
+//! Usage is read from provider responses as they pass through the proxy:
+//!
+//! - Anthropic: `usage.input_tokens` / `usage.output_tokens` (streams carry
+//!   input on `message_start` and output on `message_delta`)
+//! - OpenAI: `usage.prompt_tokens` / `usage.completion_tokens` (streams only
+//!   when the client asks for `stream_options.include_usage`)
+//! - Gemini: `usageMetadata.promptTokenCount` / `candidatesTokenCount`
+//!   (cumulative in every stream chunk)
+//!
+//! Costs are estimates from list prices per million tokens; unknown models
+//! are counted in tokens with no cost.
+
+use serde_json::Value;
+
+/// Tokens used by one response
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Usage {
+    pub model: Option<String>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl Usage {
+    pub fn is_empty(&self) -> bool {
+        self.input_tokens == 0 && self.output_tokens == 0
+    }
+
+    /// Estimated cost in USD
+    pub fn cost_usd(&self) -> f64 {
+        let Some((input, output)) = self.model.as_deref().and_then(price_per_million) else {
+            return 0.0;
+        };
+        (self.input_tokens as f64 * input + self.output_tokens as f64 * output) / 1_000_000.0
+    }
+
+    /// Fold the usage fields of one response body or stream event into this
+    pub fn observe(&mut self, body: &Value) {
+        let model = body
+            .get("model")
+            .or_else(|| body.pointer("/message/model"))
+            .or_else(|| body.get("modelVersion"))
+            .and_then(|m| m.as_str());
+        if let Some(model) = model {
+            self.model = Some(model.to_string());
+        }
+
+        let u64_at = |v: &Value, key: &str| v.get(key).and_then(|n| n.as_u64());
+        let usage = body
+            .get("usage")
+            .or_else(|| body.pointer("/message/usage"))
+            .filter(|u| u.is_object());
+        if let Some(u) = usage {
+            if let Some(n) = u64_at(u, "input_tokens").or_else(|| u64_at(u, "prompt_tokens")) {
+                self.input_tokens = n;
+            }
+            if let Some(n) = u64_at(u, "output_tokens").or_else(|| u64_at(u, "completion_tokens")) {
+                self.output_tokens = n;
+            }
+        }
+        if let Some(u) = body.get("usageMetadata") {
+            if let Some(n) = u64_at(u, "promptTokenCount") {
+                self.input_tokens = n;
+            }
+            if let Some(n) = u64_at(u, "candidatesTokenCount") {
+                self.output_tokens = n;
+            }
+        }
+    }
+}
+
+/// Usage in a complete (non-streaming) response body
+pub fn extract_usage(body: &[u8]) -> Option<Usage> {
+    let value: Value = serde_json::from_slice(body).ok()?;
+    let mut usage = Usage::default();
+    usage.observe(&value);
+    (!usage.is_empty()).then_some(usage)
+}
+
+/// (input, output) USD per million tokens, matched by model name prefix
+fn price_per_million(model: &str) -> Option<(f64, f64)> {
+    const PRICES: &[(&str, f64, f64)] = &[
+        ("claude-opus-4", 15.0, 75.0),
+        ("claude-3-opus", 15.0, 75.0),
+        ("claude-sonnet-4", 3.0, 15.0),
+        ("claude-3-7-sonnet", 3.0, 15.0),
+        ("claude-3-5-sonnet", 3.0, 15.0),
+        ("claude-3-5-haiku", 0.8, 4.0),
+        ("claude-3-haiku", 0.25, 1.25),
+        ("gpt-4o-mini", 0.15, 0.6),
+        ("gpt-4o", 2.5, 10.0),
+        ("gpt-4.1-nano", 0.1, 0.4),
+        ("gpt-4.1-mini", 0.4, 1.6),
+        ("gpt-4.1", 2.0, 8.0),
+        ("o3-mini", 1.1, 4.4),
+        ("o4-mini", 1.1, 4.4),
+        ("o3", 2.0, 8.0),
+        ("gemini-2.5-pro", 1.25, 10.0),
+        ("gemini-2.5-flash", 0.3, 2.5),
+        ("gemini-2.0-flash", 0.1, 0.4),
+        ("gemini-1.5-pro", 1.25, 5.0),
+        ("gemini-1.5-flash", 0.075, 0.3),
+    ];
+    let model = model.to_lowercase();
+    let model = model.trim_start_matches("models/");
+    PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|(_, input, output)| (*input, *output))
+}
+
+/// Spend limits that trigger an alert when crossed
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Budget {
+    pub daily_usd: Option<f64>,
+    pub session_usd: Option<f64>,
+}
+
+impl Budget {
+    /// From `OPENCLAW_HARNESS_DAILY_BUDGET_USD` and
+    /// `OPENCLAW_HARNESS_SESSION_BUDGET_USD`
+    pub fn from_env() -> Self {
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .and_then(|v| v.trim().parse::<f64>().ok())
+                .filter(|v| *v > 0.0)
+        };
+        Self {
+            daily_usd: var("OPENCLAW_HARNESS_DAILY_BUDGET_USD"),
+            session_usd: var("OPENCLAW_HARNESS_SESSION_BUDGET_USD"),
+        }
+    }
+}
+
+/// Whether spend went from below `limit` to at or above it
+pub fn crossed(limit: Option<f64>, before: f64, after: f64) -> bool {
+    limit.is_some_and(|l| before < l && after >= l)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_usage() {
+        let anthropic = extract_usage(
+            br#"{"model":"claude-sonnet-4-20250514","usage":{"input_tokens":1000,"output_tokens":500}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            (anthropic.input_tokens, anthropic.output_tokens),
+            (1000, 500)
+        );
+        assert!((anthropic.cost_usd() - 0.0105).abs() < 1e-9);
+
+        let openai = extract_usage(
+            br#"{"model":"gpt-4o-mini","usage":{"prompt_tokens":10,"completion_tokens":5}}"#,
+        )
+        .unwrap();
+        assert_eq!((openai.input_tokens, openai.output_tokens), (10, 5));
+        assert!(extract_usage(br#"{"model":"x"}"#).is_none());
+
+        // Anthropic stream: input on message_start, output on message_delta
+        let mut stream = Usage::default();
+        stream.observe(&json!({"type":"message_start","message":{"model":"claude-3-5-haiku-latest","usage":{"input_tokens":20,"output_tokens":1}}}));
+        stream.observe(&json!({"type":"content_block_delta","delta":{"text":"hi"}}));
+        stream.observe(&json!({"type":"message_delta","usage":{"output_tokens":42}}));
+        assert_eq!(stream.model.as_deref(), Some("claude-3-5-haiku-latest"));
+        assert_eq!((stream.input_tokens, stream.output_tokens), (20, 42));
+
+        let mut gemini = Usage::default();
+        gemini.observe(&json!({"modelVersion":"gemini-2.0-flash","usageMetadata":{"promptTokenCount":7,"candidatesTokenCount":3}}));
+        assert_eq!((gemini.input_tokens, gemini.output_tokens), (7, 3));
+        assert!(gemini.cost_usd() > 0.0);
+
+        assert!(crossed(Some(1.0), 0.9, 1.1));
+        assert!(!crossed(Some(1.0), 1.1, 1.2));
+        assert!(!crossed(None, 0.0, 5.0));
+    }
+}
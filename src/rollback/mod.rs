@@ -0,0 +1,356 @@
+//! Best-effort rollback for file actions detected after the fact
+//!
+//! Log collectors only see a FileWrite/FileDelete once it has already run.
+//! When that action turns out to be critical we try to undo it:
+//!
+//! 1. If the target is tracked in a git repo, save the current diff next to
+//!    the shadow directory and restore the file from the index.
+//! 2. Otherwise, restore the most recent copy from the shadow directory,
+//!    which the daemon refreshes periodically for protected paths.
+
+use crate::{ActionType, AgentAction};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info};
+
+/// Environment variable with extra paths to snapshot (colon-separated)
+pub const SNAPSHOT_PATHS_ENV: &str = "OPENCLAW_HARNESS_SNAPSHOT_PATHS";
+
+/// Files larger than this are never copied into the shadow directory
+const MAX_SNAPSHOT_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Maximum directory depth walked when snapshotting a directory
+const MAX_SNAPSHOT_DEPTH: usize = 8;
+
+/// How a file was (or would be) restored
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RollbackMethod {
+    /// Restored from the git index
+    Git,
+    /// Restored from the shadow directory
+    Shadow,
+}
+
+/// Outcome of a rollback attempt
+#[derive(Debug, Clone, Serialize)]
+pub struct RollbackOutcome {
+    pub path: PathBuf,
+    pub method: RollbackMethod,
+    /// False for dry runs
+    pub restored: bool,
+    /// Where the discarded changes were saved (git only)
+    pub saved_diff: Option<PathBuf>,
+    pub detail: String,
+}
+
+/// Shadow directory holding periodic copies of protected paths
+pub struct ShadowStore {
+    root: PathBuf,
+}
+
+impl ShadowStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Default location: `shadow` in the harness directory
+    pub fn default_root() -> PathBuf {
+        crate::harness_path("shadow")
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Location of the shadow copy for an absolute path
+    fn shadow_path(&self, path: &Path) -> PathBuf {
+        let relative: PathBuf = path
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)))
+            .collect();
+        self.root.join("files").join(relative)
+    }
+
+    pub fn has_snapshot(&self, path: &Path) -> bool {
+        self.shadow_path(path).is_file()
+    }
+
+    /// Copy a file or directory tree into the shadow directory.
+    ///
+    /// Unchanged files (same size, shadow copy not older) are skipped.
+    /// Returns the number of files copied.
+    pub fn snapshot(&self, path: &Path) -> anyhow::Result<usize> {
+        self.snapshot_inner(path, 0)
+    }
+
+    fn snapshot_inner(&self, path: &Path, depth: usize) -> anyhow::Result<usize> {
+        let meta = match fs::symlink_metadata(path) {
+            Ok(m) => m,
+            Err(_) => return Ok(0),
+        };
+
+        if meta.is_dir() {
+            if depth >= MAX_SNAPSHOT_DEPTH {
+                return Ok(0);
+            }
+            let mut copied = 0;
+            for entry in fs::read_dir(path)?.flatten() {
+                copied += self.snapshot_inner(&entry.path(), depth + 1)?;
+            }
+            return Ok(copied);
+        }
+
+        if !meta.is_file() || meta.len() > MAX_SNAPSHOT_FILE_BYTES {
+            return Ok(0);
+        }
+
+        let dest = self.shadow_path(path);
+        if let Ok(existing) = fs::metadata(&dest) {
+            let fresh = match (existing.modified(), meta.modified()) {
+                (Ok(shadow_time), Ok(src_time)) => shadow_time >= src_time,
+                _ => false,
+            };
+            if existing.len() == meta.len() && fresh {
+                return Ok(0);
+            }
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(path, &dest)?;
+        debug!("Snapshot {} -> {}", path.display(), dest.display());
+        Ok(1)
+    }
+
+    /// Restore a file from its shadow copy
+    pub fn restore(&self, path: &Path) -> anyhow::Result<()> {
+        let src = self.shadow_path(path);
+        if !src.is_file() {
+            anyhow::bail!("No shadow snapshot for {}", path.display());
+        }
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&src, path)?;
+        Ok(())
+    }
+}
+
+/// Paths snapshotted by the daemon: ~/.ssh, ~/.aws, ~/.gnupg plus
+/// anything listed in `OPENCLAW_HARNESS_SNAPSHOT_PATHS`
+pub fn protected_snapshot_paths() -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = ["~/.ssh", "~/.aws", "~/.gnupg"]
+        .iter()
+        .map(|p| crate::expand_home(p))
+        .collect();
+
+    if let Ok(extra) = std::env::var(SNAPSHOT_PATHS_ENV) {
+        paths.extend(
+            extra
+                .split(':')
+                .filter(|p| !p.trim().is_empty())
+                .map(|p| crate::expand_home(p.trim())),
+        );
+    }
+
+    paths
+}
+
+/// Roll back the file touched by a FileWrite/FileDelete action
+pub fn rollback_action(
+    action: &AgentAction,
+    shadow: &ShadowStore,
+    dry_run: bool,
+) -> anyhow::Result<RollbackOutcome> {
+    if !matches!(
+        action.action_type,
+        ActionType::FileWrite | ActionType::FileDelete
+    ) {
+        anyhow::bail!(
+            "Action {} is a {} action; only write/delete can be rolled back",
+            action.id,
+            action.action_type
+        );
+    }
+
+    let target = action
+        .target
+        .as_deref()
+        .ok_or_else(|| anyhow::anyhow!("Action {} has no target path", action.id))?;
+
+    rollback_path(&crate::expand_home(target), shadow, dry_run)
+}
+
+/// Roll back a single path using git if possible, else the shadow directory
+pub fn rollback_path(
+    path: &Path,
+    shadow: &ShadowStore,
+    dry_run: bool,
+) -> anyhow::Result<RollbackOutcome> {
+    if let Some((repo, relative)) = git_toplevel(path) {
+        if git_is_tracked(&repo, &relative) {
+            let mut saved_diff = None;
+            if !dry_run {
+                saved_diff = save_git_diff(&repo, &relative, shadow)?;
+                let output = Command::new("git")
+                    .arg("-C")
+                    .arg(&repo)
+                    .args(["checkout", "--"])
+                    .arg(&relative)
+                    .output()?;
+                if !output.status.success() {
+                    anyhow::bail!(
+                        "git checkout failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                info!("↩️  Restored {} from git index", path.display());
+            }
+            return Ok(RollbackOutcome {
+                path: path.to_path_buf(),
+                method: RollbackMethod::Git,
+                restored: !dry_run,
+                saved_diff,
+                detail: format!("restored from git index in {}", repo.display()),
+            });
+        }
+    }
+
+    if shadow.has_snapshot(path) {
+        if !dry_run {
+            shadow.restore(path)?;
+            info!("↩️  Restored {} from shadow snapshot", path.display());
+        }
+        return Ok(RollbackOutcome {
+            path: path.to_path_buf(),
+            method: RollbackMethod::Shadow,
+            restored: !dry_run,
+            saved_diff: None,
+            detail: format!("restored from {}", shadow.root().display()),
+        });
+    }
+
+    anyhow::bail!(
+        "{} is neither tracked by git nor snapshotted; nothing to restore",
+        path.display()
+    )
+}
+
+/// Find the git work tree containing `path` (the file itself may be gone).
+/// Returns the repo root and the path relative to it.
+fn git_toplevel(path: &Path) -> Option<(PathBuf, PathBuf)> {
+    let dir = path.ancestors().skip(1).find(|p| p.is_dir())?;
+    let rest = path.strip_prefix(dir).ok()?;
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["rev-parse", "--show-toplevel"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let top = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // Canonicalize both sides so symlinked dirs (e.g. /tmp on macOS) line up
+    let top = fs::canonicalize(top).ok()?;
+    let canonical = fs::canonicalize(dir).ok()?.join(rest);
+    let relative = canonical.strip_prefix(&top).ok()?.to_path_buf();
+    Some((top, relative))
+}
+
+fn git_is_tracked(repo: &Path, relative: &Path) -> bool {
+    Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["ls-files", "--error-unmatch", "--"])
+        .arg(relative)
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
+
+/// Save `git diff` of the file so the rolled-back change isn't lost
+fn save_git_diff(
+    repo: &Path,
+    relative: &Path,
+    shadow: &ShadowStore,
+) -> anyhow::Result<Option<PathBuf>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["diff", "--"])
+        .arg(relative)
+        .output()?;
+    if !output.status.success() || output.stdout.is_empty() {
+        return Ok(None);
+    }
+
+    let dir = shadow.root().join("diffs");
+    fs::create_dir_all(&dir)?;
+    let name = relative.to_string_lossy().replace(['/', '\\'], "_");
+    let file = dir.join(format!(
+        "{}-{}.patch",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S"),
+        name
+    ));
+    fs::write(&file, &output.stdout)?;
+    Ok(Some(file))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_action(action_type: ActionType, target: &Path) -> AgentAction {
+        AgentAction {
+            id: "rb-1".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: crate::AgentType::OpenClaw,
+            action_type,
+            content: String::new(),
+            target: Some(target.to_string_lossy().to_string()),
+            session_id: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_shadow_snapshot_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let shadow = ShadowStore::new(dir.path().join("shadow"));
+        let file = dir.path().join("data/secret.txt");
+        fs::create_dir_all(file.parent().unwrap()).unwrap();
+        fs::write(&file, "original").unwrap();
+
+        assert_eq!(shadow.snapshot(&dir.path().join("data")).unwrap(), 1);
+        // Unchanged file is skipped
+        assert_eq!(shadow.snapshot(&file).unwrap(), 0);
+
+        fs::remove_file(&file).unwrap();
+        let outcome =
+            rollback_action(&file_action(ActionType::FileDelete, &file), &shadow, false).unwrap();
+        assert_eq!(outcome.method, RollbackMethod::Shadow);
+        assert!(outcome.restored);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "original");
+    }
+
+    #[test]
+    fn test_rollback_rejects_non_file_actions() {
+        let dir = tempfile::tempdir().unwrap();
+        let shadow = ShadowStore::new(dir.path());
+        let action = file_action(ActionType::Exec, Path::new("/tmp/x"));
+        assert!(rollback_action(&action, &shadow, true).is_err());
+    }
+
+    #[test]
+    fn test_rollback_without_snapshot_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let shadow = ShadowStore::new(dir.path().join("shadow"));
+        let file = dir.path().join("nothing.txt");
+        assert!(rollback_path(&file, &shadow, true).is_err());
+    }
+}
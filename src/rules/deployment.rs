@@ -0,0 +1,290 @@
+//! Self-protection for the instance that is actually running
+//!
+//! The built-in self-protection rules name the paths of a development
+//! checkout (`openclaw-harness/target/release/`, `config/rules.yaml`,
+//! port 8380). An installed harness lives elsewhere, so at startup the
+//! rules below are generated from where this instance really is: its
+//! executable, its config directories, its database, its alert secrets and
+//! the port of its web API.
+
+use super::{MatchType, Rule, RuleAction};
+use crate::{ActionType, RiskLevel};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Where this instance lives
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deployment {
+    pub executable: Option<PathBuf>,
+    /// `crate::harness_dir`, where the alert config and seal key are kept
+    pub harness_dir: PathBuf,
+    /// The harness directory (`crate::harness_dir`), and `config/` when the
+    /// harness runs from a directory that has one
+    pub config_dirs: Vec<PathBuf>,
+    pub db_path: PathBuf,
+    pub web_port: u16,
+}
+
+impl Deployment {
+    pub fn detect() -> Self {
+        let harness_dir = crate::harness_dir();
+        let mut config_dirs = vec![harness_dir.clone()];
+        if let Ok(local) = std::fs::canonicalize("config") {
+            config_dirs.push(local);
+        }
+        let web = crate::web::WebConfig::from_env();
+        Self {
+            executable: std::env::current_exe()
+                .ok()
+                .map(|exe| exe.canonicalize().unwrap_or(exe)),
+            harness_dir,
+            config_dirs,
+            db_path: crate::expand_home(&crate::Config::default().db_path),
+            web_port: web
+                .listen
+                .rsplit_once(':')
+                .and_then(|(_, port)| port.parse().ok())
+                .unwrap_or(crate::web::DEFAULT_PORT),
+        }
+    }
+
+    /// Detected once, at first use
+    pub fn current() -> &'static Deployment {
+        static CURRENT: OnceLock<Deployment> = OnceLock::new();
+        CURRENT.get_or_init(Self::detect)
+    }
+
+    /// Self-protection rules for this instance
+    pub fn rules(&self) -> Vec<Rule> {
+        let mut rules = vec![];
+        if let Some(exe) = &self.executable {
+            rules.push(protect(
+                "self_protect_deployed_binary",
+                "Block modification of the running OpenClaw Harness binary",
+                modifying(&alternatives(&[exe.as_path()]), "$"),
+                &[
+                    ActionType::FileWrite,
+                    ActionType::FileDelete,
+                    ActionType::Exec,
+                ],
+            ));
+        }
+        let dirs: Vec<&Path> = self.config_dirs.iter().map(PathBuf::as_path).collect();
+        rules.push(protect(
+            "self_protect_deployed_config",
+            "Block modification of OpenClaw Harness config directories",
+            modifying(&alternatives(&dirs), "/"),
+            &[
+                ActionType::FileWrite,
+                ActionType::FileDelete,
+                ActionType::Exec,
+            ],
+        ));
+        // The WAL and shared-memory files start with the same path
+        rules.push(protect(
+            "self_protect_deployed_db",
+            "Block access to the OpenClaw Harness database",
+            alternatives(&[self.db_path.as_path()]),
+            &[
+                ActionType::FileRead,
+                ActionType::FileWrite,
+                ActionType::FileDelete,
+                ActionType::Exec,
+            ],
+        ));
+        // The built-in self_protect_alert_secrets only knows the default
+        // harness directory
+        let alerts = self.harness_dir.join("alerts.json");
+        let seal_key = self.harness_dir.join("seal.key");
+        rules.push(protect(
+            "self_protect_deployed_alert_secrets",
+            "Block access to OpenClaw Harness alert credentials",
+            alternatives(&[alerts.as_path(), seal_key.as_path()]),
+            &[
+                ActionType::FileRead,
+                ActionType::FileWrite,
+                ActionType::FileDelete,
+                ActionType::Exec,
+            ],
+        ));
+        // The built-in self_protect_api covers the default port
+        if self.web_port != crate::web::DEFAULT_PORT {
+            rules.push(protect(
+                "self_protect_deployed_api",
+                "Block disabling rules via the running harness API",
+                format!(
+                    r"(curl|http|fetch|wget)\s+.*(localhost|127\.0\.0\.1|\[::1\]):{}\b.*(rules|disable|delete)",
+                    self.web_port
+                ),
+                &[ActionType::Exec],
+            ));
+        }
+        for rule in &mut rules {
+            let _ = rule.compile();
+        }
+        rules
+    }
+}
+
+/// Commands that change a file, up to where the path starts
+const MODIFYING_COMMAND: &str =
+    r"(\b(rm|mv|cp|ln|chmod|chown|chattr|truncate|tee|dd|install|sed\s+-i|rsync)\b.*|>\s*)";
+
+/// Regex for a write or delete of `path`: a command changing it, or a
+/// file action whose target is the path. `end` follows the path when it
+/// starts the text (`$` for a file, `/` for a directory).
+fn modifying(path: &str, end: &str) -> String {
+    format!("^{}{}|{}{}", path, end, MODIFYING_COMMAND, path)
+}
+
+/// Regex for any of `paths`, also written from `~` or `$HOME`
+fn alternatives(paths: &[&Path]) -> String {
+    let home = dirs::home_dir();
+    let mut forms = vec![];
+    for path in paths {
+        forms.push(regex::escape(&path.to_string_lossy()));
+        if let Some(rest) = home.as_deref().and_then(|h| path.strip_prefix(h).ok()) {
+            let rest = regex::escape(&rest.to_string_lossy());
+            forms.push(format!(r"(~|\$HOME|\$\{{HOME\}})/{}", rest));
+        }
+    }
+    format!("({})", forms.join("|"))
+}
+
+fn protect(name: &str, description: &str, pattern: String, applies_to: &[ActionType]) -> Rule {
+    Rule {
+        name: name.to_string(),
+        description: format!("🔒 SELF-PROTECTION: {}", description),
+        match_type: MatchType::Regex,
+        pattern,
+        applies_to: applies_to.to_vec(),
+        risk_level: RiskLevel::Critical,
+        action: RuleAction::Block,
+        enabled: true,
+        protected: true,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AgentAction, AgentType};
+
+    fn action(action_type: ActionType, content: &str) -> AgentAction {
+        let file = action_type != ActionType::Exec;
+        AgentAction {
+            id: "test".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::OpenClaw,
+            action_type,
+            content: if file {
+                "data".to_string()
+            } else {
+                content.to_string()
+            },
+            target: file.then(|| content.to_string()),
+            session_id: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_rules_follow_the_deployment() {
+        let home = dirs::home_dir().unwrap();
+        let deployment = Deployment {
+            executable: Some(PathBuf::from("/opt/harness/bin/openclaw-harness")),
+            harness_dir: home.join(".openclaw-harness"),
+            config_dirs: vec![
+                home.join(".openclaw-harness"),
+                PathBuf::from("/etc/harness"),
+            ],
+            db_path: home.join(".openclaw-harness/openclaw-harness.db"),
+            web_port: 9380,
+        };
+        let rules = deployment.rules();
+        assert_eq!(rules.len(), 5);
+        let rule = |name: &str| rules.iter().find(|r| r.name == name).unwrap();
+        let exec = |content: &str| action(ActionType::Exec, content);
+
+        let binary = rule("self_protect_deployed_binary");
+        assert!(binary.matches(&exec("rm /opt/harness/bin/openclaw-harness")));
+        assert!(binary.matches(&exec("cp evil /opt/harness/bin/openclaw-harness")));
+        assert!(binary.matches(&action(
+            ActionType::FileWrite,
+            "/opt/harness/bin/openclaw-harness"
+        )));
+        // Running it is fine
+        assert!(!binary.matches(&exec("/opt/harness/bin/openclaw-harness status")));
+
+        let config = rule("self_protect_deployed_config");
+        assert!(config.matches(&exec("echo '[]' > ~/.openclaw-harness/rules.yaml")));
+        assert!(config.matches(&exec("rm -rf $HOME/.openclaw-harness/")));
+        assert!(config.matches(&action(ActionType::FileWrite, "/etc/harness/profiles.yaml")));
+        assert!(!config.matches(&exec("cat ~/.openclaw-harness/rules.yaml")));
+
+        let db = rule("self_protect_deployed_db");
+        assert!(db.matches(&exec(
+            "sqlite3 ~/.openclaw-harness/openclaw-harness.db 'DELETE FROM actions'"
+        )));
+        assert!(db.matches(&action(
+            ActionType::FileDelete,
+            &format!(
+                "{}/.openclaw-harness/openclaw-harness.db-wal",
+                home.display()
+            )
+        )));
+
+        let secrets = rule("self_protect_deployed_alert_secrets");
+        assert!(secrets.matches(&exec("cat ~/.openclaw-harness/alerts.json")));
+        assert!(secrets.matches(&action(
+            ActionType::FileRead,
+            &format!("{}/.openclaw-harness/seal.key", home.display())
+        )));
+
+        let api = rule("self_protect_deployed_api");
+        assert!(api.matches(&exec(
+            "curl -X DELETE http://127.0.0.1:9380/api/rules/dangerous_rm"
+        )));
+        assert!(!api.matches(&exec("curl http://127.0.0.1:93800/api/rules")));
+
+        // The default port is left to the built-in rule
+        let default_port = Deployment {
+            web_port: crate::web::DEFAULT_PORT,
+            ..deployment
+        };
+        assert_eq!(default_port.rules().len(), 4);
+    }
+
+    #[test]
+    fn test_alert_secrets_follow_a_relocated_home() {
+        let deployment = Deployment {
+            executable: None,
+            harness_dir: PathBuf::from("/srv/harness"),
+            config_dirs: vec![PathBuf::from("/srv/harness")],
+            db_path: PathBuf::from("/srv/harness/openclaw-harness.db"),
+            web_port: crate::web::DEFAULT_PORT,
+        };
+        let rules = deployment.rules();
+        let secrets = rules
+            .iter()
+            .find(|r| r.name == "self_protect_deployed_alert_secrets")
+            .unwrap();
+        for read in [
+            action(ActionType::FileRead, "/srv/harness/alerts.json"),
+            action(ActionType::FileRead, "/srv/harness/seal.key"),
+            action(ActionType::Exec, "base64 /srv/harness/seal.key"),
+        ] {
+            assert!(secrets.matches(&read), "{}", read.content);
+        }
+        assert!(!secrets.matches(&action(ActionType::FileRead, "/srv/other/alerts.json")));
+
+        // Out of reach of the built-in rule
+        let mut builtin = crate::rules::self_protection_rules()
+            .into_iter()
+            .find(|r| r.name == "self_protect_alert_secrets")
+            .unwrap();
+        builtin.compile().unwrap();
+        assert!(!builtin.matches(&action(ActionType::FileRead, "/srv/harness/seal.key")));
+    }
+}
@@ -5,10 +5,13 @@
 //! 2. Keyword - simple string matching (contains, starts_with, ends_with, glob, any_of)
 //! 3. Template - predefined scenario templates with parameters
 
-use super::{ActionType, AgentAction, RiskLevel};
+pub mod deployment;
+pub mod profiles;
+
+use super::{ActionType, AgentAction, AgentType, RiskLevel};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Match type for a rule
 #[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
@@ -89,6 +92,13 @@ pub struct Rule {
     /// Action types this rule applies to
     #[serde(default)]
     pub applies_to: Vec<ActionType>,
+    /// Agents this rule applies to (empty = all, custom names allowed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agents: Vec<AgentType>,
+    /// Metadata conditions that must all hold, keyed by dotted path
+    /// (e.g. `git.branch: main`, `git.tracked: "true"`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
     /// Risk level
     #[serde(default = "default_risk")]
     pub risk_level: RiskLevel,
@@ -101,6 +111,21 @@ pub struct Rule {
     /// Protected rules cannot be disabled/deleted via API or CLI
     #[serde(default)]
     pub protected: bool,
+    /// What the agent should do instead, added to block messages in
+    /// coaching mode (templates and built-in rules have a default)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guidance: Option<String>,
+    /// Group the rule is listed under (templates and built-in rules have
+    /// a default, see `category_name`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Free-form labels for filtering (e.g. `secrets`, `ci`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Higher priority rules are checked first; equal ones keep file order.
+    /// Decides the outcome under first-match evaluation.
+    #[serde(default, skip_serializing_if = "is_default_priority")]
+    pub priority: i32,
     /// Compiled regex (not serialized)
     #[serde(skip)]
     compiled_pattern: Option<Regex>,
@@ -116,6 +141,10 @@ fn default_enabled() -> bool {
     true
 }
 
+fn is_default_priority(priority: &i32) -> bool {
+    *priority == 0
+}
+
 fn default_risk() -> RiskLevel {
     RiskLevel::Warning
 }
@@ -158,10 +187,16 @@ impl Rule {
             template: None,
             params: None,
             applies_to: vec![],
+            agents: vec![],
+            metadata: BTreeMap::new(),
             risk_level,
             action,
             enabled: true,
             protected: false,
+            guidance: None,
+            category: None,
+            tags: Vec::new(),
+            priority: 0,
             compiled_pattern: compiled,
             compiled_globs: vec![],
             expanded_patterns: vec![],
@@ -185,10 +220,16 @@ impl Rule {
             template: None,
             params: None,
             applies_to: vec![],
+            agents: vec![],
+            metadata: BTreeMap::new(),
             risk_level,
             action,
             enabled: true,
             protected: false,
+            guidance: None,
+            category: None,
+            tags: Vec::new(),
+            priority: 0,
             compiled_pattern: None,
             compiled_globs: vec![],
             expanded_patterns: vec![],
@@ -216,10 +257,16 @@ impl Rule {
             template: Some(template_name),
             params: Some(params),
             applies_to: vec![],
+            agents: vec![],
+            metadata: BTreeMap::new(),
             risk_level,
             action,
             enabled: true,
             protected: false,
+            guidance: None,
+            category: None,
+            tags: Vec::new(),
+            priority: 0,
             compiled_pattern: None,
             compiled_globs: vec![],
             expanded_patterns: vec![],
@@ -234,8 +281,23 @@ impl Rule {
             return false;
         }
 
-        // Check action type filter
-        if !self.applies_to.is_empty() && !self.applies_to.contains(&action.action_type) {
+        // Check action type filter; tool results are output, not actions,
+        // so rules for every type skip them
+        let applies = if self.applies_to.is_empty() {
+            action.action_type != ActionType::ToolResult
+        } else {
+            self.applies_to.contains(&action.action_type)
+        };
+        if !applies {
+            return false;
+        }
+
+        // Check agent filter
+        if !self.agents.is_empty() && !self.agents.contains(&action.agent) {
+            return false;
+        }
+
+        if !self.metadata_matches(action) {
             return false;
         }
 
@@ -246,6 +308,22 @@ impl Rule {
         }
     }
 
+    fn metadata_matches(&self, action: &AgentAction) -> bool {
+        self.metadata.iter().all(|(path, expected)| {
+            let mut value = action.metadata.as_ref();
+            for key in path.split('.') {
+                value = value.and_then(|v| v.get(key));
+            }
+            match value {
+                Some(serde_json::Value::String(s)) => s == expected,
+                Some(v) => {
+                    serde_json::from_str::<serde_json::Value>(expected).is_ok_and(|e| &e == v)
+                }
+                None => false,
+            }
+        })
+    }
+
     fn matches_regex(&self, action: &AgentAction) -> bool {
         if let Some(ref regex) = self.compiled_pattern {
             if regex.is_match(&action.content) {
@@ -353,6 +431,54 @@ impl Rule {
         false
     }
 
+    /// Guidance for an agent this rule blocked: the rule's own, else one
+    /// derived from its template or a built-in rule of the same name
+    pub fn guidance_text(&self) -> Option<String> {
+        if let Some(g) = self.guidance.as_deref().filter(|g| !g.trim().is_empty()) {
+            return Some(g.to_string());
+        }
+        match &self.template {
+            Some(template) if self.match_type == MatchType::Template => {
+                template_guidance(template, &self.params.clone().unwrap_or_default())
+            }
+            _ => builtin_guidance(&self.name).map(str::to_string),
+        }
+    }
+
+    /// Category the rule is listed under: its own, else its template's or
+    /// a built-in rule's, else "Other"
+    pub fn category_name(&self) -> String {
+        if let Some(c) = self.category.as_deref().filter(|c| !c.trim().is_empty()) {
+            return c.trim().to_string();
+        }
+        let template = self
+            .template
+            .as_deref()
+            .filter(|_| self.match_type == MatchType::Template)
+            .map(get_template_definition);
+        match template {
+            Some(def) => def.category.to_string(),
+            None => builtin_category(&self.name)
+                .unwrap_or(OTHER_CATEGORY)
+                .to_string(),
+        }
+    }
+
+    /// Whether the rule has `tag` (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag.trim()))
+    }
+
+    /// The regexes a regex or template rule matches with (against content
+    /// or target), or `None` for keyword rules
+    pub(crate) fn match_patterns(&self) -> Option<Vec<&Regex>> {
+        match self.match_type {
+            MatchType::Regex => Some(self.compiled_pattern.iter().collect()),
+            MatchType::Template => Some(self.expanded_patterns.iter().collect()),
+            MatchType::Keyword => None,
+        }
+    }
+
     /// Compile the rule (regex, globs, or template expansion)
     pub fn compile(&mut self) -> anyhow::Result<()> {
         match self.match_type {
@@ -419,6 +545,109 @@ impl TemplateDefinition {
     pub fn expand(&self, params: &TemplateParams) -> (Vec<String>, Vec<ActionType>, String) {
         (self.expand_fn)(params)
     }
+
+    /// The template's parameters, each with whether it is required
+    pub fn params(&self) -> Vec<(&'static ParamSpec, bool)> {
+        let required = self.required_params.iter().map(|p| (p, true));
+        let optional = self.optional_params.iter().map(|p| (p, false));
+        required
+            .chain(optional)
+            .filter_map(|(name, required)| Some((param_spec(name)?, required)))
+            .collect()
+    }
+
+    /// Parameters filled in with example values
+    pub fn example_params(&self) -> TemplateParams {
+        let mut params = TemplateParams::default();
+        for (spec, _) in self.params() {
+            let values = spec.example.iter().map(|v| v.to_string());
+            match spec.name {
+                "path" => params.path = spec.example.first().map(|v| v.to_string()),
+                "operations" => params.operations = values.collect(),
+                "commands" => params.commands = values.collect(),
+                _ => {}
+            }
+        }
+        params
+    }
+
+    /// Problems with `params` for this template: missing required ones
+    /// and values outside what a parameter allows
+    pub fn check_params(&self, params: &TemplateParams) -> Vec<String> {
+        let mut problems = vec![];
+        for (spec, required) in self.params() {
+            let values: Vec<&String> = match spec.name {
+                "path" => params.path.iter().chain(&params.paths).collect(),
+                "operations" => params.operations.iter().collect(),
+                "commands" => params.commands.iter().chain(&params.patterns).collect(),
+                _ => continue,
+            };
+            if required && values.iter().all(|v| v.trim().is_empty()) {
+                problems.push(format!("'{}' needs parameter '{}'", self.name, spec.name));
+            }
+            if !spec.allowed.is_empty() {
+                for value in values {
+                    if !spec.allowed.contains(&value.as_str()) {
+                        problems.push(format!(
+                            "'{}' is not a valid {} (expected one of {})",
+                            value,
+                            spec.name,
+                            spec.allowed.join(", ")
+                        ));
+                    }
+                }
+            }
+        }
+        problems
+    }
+}
+
+/// A template parameter, as shown in the template catalog
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    /// `string` or `list`
+    pub kind: &'static str,
+    pub description: &'static str,
+    /// Other keys accepted for it in `params`
+    pub aliases: &'static [&'static str],
+    /// Accepted values; empty means any
+    pub allowed: &'static [&'static str],
+    pub example: &'static [&'static str],
+}
+
+const PARAM_SPECS: &[ParamSpec] = &[
+    ParamSpec {
+        name: "path",
+        kind: "string",
+        description: "Path to protect; a trailing * covers everything below it",
+        aliases: &["paths"],
+        allowed: &[],
+        example: &["~/Documents/*"],
+    },
+    ParamSpec {
+        name: "operations",
+        kind: "list",
+        description: "Operations to block (default: all)",
+        aliases: &[],
+        allowed: &["read", "write", "delete"],
+        example: &["write", "delete"],
+    },
+    ParamSpec {
+        name: "commands",
+        kind: "list",
+        description: "Commands to block",
+        aliases: &["patterns"],
+        allowed: &[],
+        example: &["shutdown", "reboot"],
+    },
+];
+
+/// Spec of the template parameter called `name` (or one of its aliases)
+pub fn param_spec(name: &str) -> Option<&'static ParamSpec> {
+    PARAM_SPECS
+        .iter()
+        .find(|spec| spec.name == name || spec.aliases.contains(&name))
 }
 
 fn escape_for_regex(s: &str) -> String {
@@ -1092,6 +1321,113 @@ pub fn get_template_definition(name: &str) -> TemplateDefinition {
         })
 }
 
+/// Default guidance for a template rule
+fn template_guidance(template: &str, params: &TemplateParams) -> Option<String> {
+    let paths = collect_paths(params).join(", ");
+    let text = match template {
+        "protect_path" | "prevent_overwrite" => format!(
+            "Leave {} alone; write to a path under the current workspace instead.",
+            paths
+        ),
+        "prevent_delete" => format!(
+            "Don't delete anything under {}; move files aside or ask the user.",
+            paths
+        ),
+        "block_hidden_files" => {
+            "Hidden files often hold configuration or credentials; work with regular project files instead.".to_string()
+        }
+        "block_command" => format!(
+            "Don't run {}; find another way to do this step or ask the user.",
+            params.commands.join(", ")
+        ),
+        "block_sudo" => {
+            "Run the command without sudo, or ask the user to run it for you.".to_string()
+        }
+        "block_package_install" => {
+            "Don't install packages; use what is already available, or list what's missing for the user.".to_string()
+        }
+        "prevent_exfiltration" | "block_network_tools" => {
+            "Don't send data to external hosts; keep results local and report them to the user.".to_string()
+        }
+        "protect_secrets" => {
+            "Don't read or print credentials; reference them by environment variable name instead.".to_string()
+        }
+        "protect_git" => {
+            "Don't rewrite git history or force-push; make a new commit on a branch instead.".to_string()
+        }
+        "block_kill_process" | "block_service_control" => {
+            "Don't stop processes or services; ask the user to do it if it's really needed.".to_string()
+        }
+        _ => return None,
+    };
+    Some(text)
+}
+
+/// Category of rules that have none and no template
+pub const OTHER_CATEGORY: &str = "Other";
+
+/// Category of the rule named `name` in `rules`; a rule no longer there
+/// gets its built-in category, if any
+pub fn category_of(name: &str, rules: &[Rule]) -> String {
+    match rules.iter().find(|r| r.name == name) {
+        Some(rule) => rule.category_name(),
+        None => builtin_category(name).unwrap_or(OTHER_CATEGORY).to_string(),
+    }
+}
+
+/// Default categories for the built-in rules, named like the template
+/// categories
+fn builtin_category(rule_name: &str) -> Option<&'static str> {
+    if rule_name.starts_with("self_protect_") {
+        return Some("Self-Protection");
+    }
+    if rule_name.starts_with("tripwire_") || rule_name == crate::canary::RULE_NAME {
+        return Some("Tripwires & Canaries");
+    }
+    Some(match rule_name {
+        "dangerous_rm" | "mass_delete" | "block_destructive_keywords" => "File/Folder Protection",
+        "api_key_exposure" | "ssh_key_access" | "wallet_access" | "env_secrets" => {
+            "Data Protection"
+        }
+        "system_config" | "sudo_command" => "System Protection",
+        "package_install" | "npm_install" | "git_push" | "git_force_push" | "database_drop" => {
+            "Command Restriction"
+        }
+        "block_curl_upload" => "Network",
+        _ => return None,
+    })
+}
+
+/// Default guidance for the built-in rules
+fn builtin_guidance(rule_name: &str) -> Option<&'static str> {
+    if rule_name.starts_with("self_protect_") {
+        return Some("OpenClaw Harness guards itself; leave it and its files alone and carry on with the task.");
+    }
+    Some(match rule_name {
+        "dangerous_rm" | "block_destructive_keywords" => {
+            "Delete specific files by path instead of removing a directory tree recursively."
+        }
+        "mass_delete" => "Delete files by explicit name instead of by wildcard.",
+        "api_key_exposure" => {
+            "Don't put secrets in commands or requests; reference them by environment variable name."
+        }
+        "ssh_key_access" | "wallet_access" | "env_secrets" => {
+            "This file holds credentials; don't read it. Ask the user for what you need."
+        }
+        "system_config" => "Leave system configuration alone; change files in the project instead.",
+        "sudo_command" => "Run the command without sudo, or ask the user to run it for you.",
+        "package_install" => {
+            "Don't install system packages; use what is available, or list what's missing for the user."
+        }
+        "database_drop" => "Don't drop or truncate data; write a reversible migration instead.",
+        "git_force_push" => "Push without --force; rebase onto the remote branch or push a new branch.",
+        "block_curl_upload" => {
+            "Don't upload data to external hosts; keep results local and report them to the user."
+        }
+        _ => return None,
+    })
+}
+
 /// Load default rules
 pub fn default_rules() -> Vec<Rule> {
     vec![
@@ -1191,6 +1527,26 @@ pub fn self_protection_rules() -> Vec<Rule> {
             protected: true,
             ..Default::default()
         },
+        // Block reading alert credentials: the alert config, the key it is
+        // sealed with, the keyring entries and the token variable. An agent
+        // that has them can send fake alerts or silence real ones.
+        Rule {
+            name: "self_protect_alert_secrets".to_string(),
+            description: "🔒 SELF-PROTECTION: Block access to OpenClaw Harness alert credentials".to_string(),
+            match_type: MatchType::Regex,
+            pattern: r#"(\.openclaw-harness|config)/alerts\.json|\.openclaw-harness/seal\.key|(find-generic-password|secret-tool\s+lookup|keyring\s+get)\s.*openclaw-harness|TELEGRAM_BOT_TOKEN"#.to_string(),
+            applies_to: vec![
+                ActionType::FileRead,
+                ActionType::FileWrite,
+                ActionType::FileDelete,
+                ActionType::Exec,
+            ],
+            risk_level: RiskLevel::Critical,
+            action: RuleAction::Block,
+            enabled: true,
+            protected: true,
+            ..Default::default()
+        },
         // Block modification of harness source code
         Rule {
             name: "self_protect_source".to_string(),
@@ -1311,10 +1667,45 @@ pub fn self_protection_rules() -> Vec<Rule> {
     for rule in &mut rules {
         let _ = rule.compile();
     }
+    // And cover where this instance is actually installed
+    rules.extend(deployment::Deployment::current().rules());
+    rules
+}
+
+/// Rules added to every rule set at load time: planted tripwires and the
+/// canary token check
+pub fn runtime_rules() -> Vec<Rule> {
+    let mut rules = crate::tripwire::rules();
+    rules.push(crate::canary::rule());
+    rules
+}
+
+/// Rules used when no config loads: the defaults with the active profile
+/// applied (see `profiles`), self-protection and the runtime rules
+pub fn fallback_rules() -> Vec<Rule> {
+    let mut rules = default_rules();
+    rules.extend(self_protection_rules());
+    rules.extend(runtime_rules());
+    profiles::apply_active(&mut rules);
     rules
 }
 
-/// Load rules from a YAML file
+/// Rules from the first config that loads (`config/rules.yaml`, then
+/// `~/.openclaw-harness/rules.yaml`), else the defaults
+pub fn load_configured_rules() -> Vec<Rule> {
+    let candidates = [
+        std::path::PathBuf::from("config/rules.yaml"),
+        crate::harness_path("rules.yaml"),
+    ];
+    for path in candidates.iter().filter(|p| p.exists()) {
+        if let Ok(rules) = load_rules_from_file(path) {
+            return rules;
+        }
+    }
+    fallback_rules()
+}
+
+/// Load rules from a YAML file, with the active profile applied
 pub fn load_rules_from_file(path: &std::path::Path) -> anyhow::Result<Vec<Rule>> {
     let content = std::fs::read_to_string(path)?;
     let mut rules: Vec<Rule> = serde_yaml::from_str(&content)?;
@@ -1329,6 +1720,8 @@ pub fn load_rules_from_file(path: &std::path::Path) -> anyhow::Result<Vec<Rule>>
     let sp_names: Vec<&str> = sp_rules.iter().map(|r| r.name.as_str()).collect();
     rules.retain(|r| !sp_names.contains(&r.name.as_str()));
     rules.extend(sp_rules);
+    rules.extend(runtime_rules());
+    profiles::apply_active(&mut rules);
 
     Ok(rules)
 }
@@ -1485,4 +1878,94 @@ mod tests {
         assert!(rule.matches(&test_action("docker system prune")));
         assert!(!rule.matches(&test_action("docker ps")));
     }
+
+    #[test]
+    fn test_agent_scoped_rule() {
+        let yaml = r#"
+- name: build_bot_no_push
+  match_type: keyword
+  keyword:
+    contains: ["git push"]
+  agents: [build-bot, open_claw]
+  action: block
+"#;
+        let mut rules: Vec<Rule> = serde_yaml::from_str(yaml).unwrap();
+        let rule = &mut rules[0];
+        rule.compile().unwrap();
+        assert_eq!(
+            rule.agents,
+            vec![
+                AgentType::Custom("build-bot".to_string()),
+                AgentType::OpenClaw
+            ]
+        );
+
+        let mut action = test_action("git push origin main");
+        assert!(rule.matches(&action));
+        action.agent = AgentType::Custom("build-bot".to_string());
+        assert!(rule.matches(&action));
+        action.agent = AgentType::Unknown;
+        assert!(!rule.matches(&action));
+    }
+
+    #[test]
+    fn test_self_protect_alert_secrets() {
+        let rule = self_protection_rules()
+            .into_iter()
+            .find(|r| r.name == "self_protect_alert_secrets")
+            .unwrap();
+        let read = |path: &str| AgentAction {
+            action_type: ActionType::FileRead,
+            target: Some(path.to_string()),
+            ..test_action(path)
+        };
+        assert!(rule.matches(&read("/home/me/.openclaw-harness/alerts.json")));
+        assert!(rule.matches(&read("/home/me/.openclaw-harness/alerts.json.sha256")));
+        assert!(rule.matches(&read("/home/me/.openclaw-harness/seal.key")));
+        assert!(rule.matches(&test_action("cat config/alerts.json")));
+        assert!(rule.matches(&test_action(
+            "security find-generic-password -s openclaw-harness -w"
+        )));
+        assert!(rule.matches(&test_action("secret-tool lookup service openclaw-harness")));
+        assert!(rule.matches(&test_action("echo $OPENCLAW_HARNESS_TELEGRAM_BOT_TOKEN")));
+        assert!(!rule.matches(&read("/home/me/project/alerts.ts")));
+        assert!(!rule.matches(&test_action("openclaw-harness alerts test")));
+    }
+
+    #[test]
+    fn test_rule_category_and_tags() {
+        let yaml = r#"
+- name: no_prod_deploy
+  pattern: 'deploy\.sh\s+prod'
+  category: Deployment
+  tags: [ci, Prod]
+- name: plain
+  pattern: 'x'
+"#;
+        let rules: Vec<Rule> = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rules[0].category_name(), "Deployment");
+        assert!(rules[0].has_tag("prod"));
+        assert!(!rules[0].has_tag("staging"));
+        assert_eq!(rules[1].category_name(), OTHER_CATEGORY);
+        assert!(!serde_yaml::to_string(&rules[1]).unwrap().contains("tags"));
+
+        // Defaults from templates and built-in rules
+        let template = Rule::new_template(
+            "no_sudo",
+            "block_sudo",
+            TemplateParams::default(),
+            RiskLevel::Warning,
+            RuleAction::Block,
+        );
+        assert_eq!(
+            template.category_name(),
+            get_template_definition("block_sudo").category
+        );
+        for rule in default_rules() {
+            assert_ne!(rule.category_name(), OTHER_CATEGORY, "{}", rule.name);
+        }
+        assert!(self_protection_rules()
+            .iter()
+            .all(|r| r.category_name() == "Self-Protection"));
+    }
 }
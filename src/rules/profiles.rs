@@ -0,0 +1,208 @@
+//! Severity overrides per deployment environment
+//!
+//! One rule pack can serve several environments: `profiles.yaml` (next to
+//! `rules.yaml`, in `config/` or `~/.openclaw-harness/`) maps a profile
+//! name to overrides, and `OPENCLAW_HARNESS_PROFILE` picks the one applied
+//! when rules are loaded:
+//!
+//! ```yaml
+//! production:
+//!   # Every warning-level template rule becomes critical
+//!   - match_type: template
+//!     from: warning
+//!     risk_level: critical
+//! dev:
+//!   - rules: [git_push]
+//!     action: log_only
+//! ```
+//!
+//! An override applies to the rules its selectors (`rules`, `match_type`,
+//! `from`, `category`, `tag`) all match; no selector means every rule.
+//! Overrides run in order. Protected rules are never changed.
+
+use super::{MatchType, Rule, RuleAction};
+use crate::RiskLevel;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+/// Environment variable naming the active profile
+pub const PROFILE_ENV: &str = "OPENCLAW_HARNESS_PROFILE";
+
+/// One change to the rules a profile makes
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeverityOverride {
+    /// Only these rules, by name
+    #[serde(default)]
+    pub rules: Vec<String>,
+    #[serde(default)]
+    pub match_type: Option<MatchType>,
+    /// Only rules at this risk level
+    #[serde(default)]
+    pub from: Option<RiskLevel>,
+    /// Only rules in this category (case-insensitive)
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tag: Option<String>,
+    /// New risk level
+    #[serde(default)]
+    pub risk_level: Option<RiskLevel>,
+    /// New action
+    #[serde(default)]
+    pub action: Option<RuleAction>,
+}
+
+impl SeverityOverride {
+    fn selects(&self, rule: &Rule) -> bool {
+        !rule.protected
+            && (self.rules.is_empty() || self.rules.contains(&rule.name))
+            && self
+                .match_type
+                .as_ref()
+                .is_none_or(|m| *m == rule.match_type)
+            && self.from.is_none_or(|r| r == rule.risk_level)
+            && self
+                .category
+                .as_deref()
+                .is_none_or(|c| rule.category_name().eq_ignore_ascii_case(c.trim()))
+            && self.tag.as_deref().is_none_or(|t| rule.has_tag(t))
+    }
+
+    /// Apply to `rule` if selected; whether it changed
+    fn apply(&self, rule: &mut Rule) -> bool {
+        if !self.selects(rule) {
+            return false;
+        }
+        let before = (rule.risk_level, rule.action);
+        if let Some(risk_level) = self.risk_level {
+            rule.risk_level = risk_level;
+        }
+        if let Some(action) = self.action {
+            rule.action = action;
+        }
+        before != (rule.risk_level, rule.action)
+    }
+}
+
+/// Overrides by profile name
+pub type Profiles = BTreeMap<String, Vec<SeverityOverride>>;
+
+/// Apply `overrides` in order; returns how many rules changed
+pub fn apply(rules: &mut [Rule], overrides: &[SeverityOverride]) -> usize {
+    let mut changed = 0;
+    for rule in rules {
+        let mut rule_changed = false;
+        for o in overrides {
+            rule_changed |= o.apply(rule);
+        }
+        changed += usize::from(rule_changed);
+    }
+    changed
+}
+
+/// The active profile's name, if any
+pub fn active_profile() -> Option<String> {
+    std::env::var(PROFILE_ENV)
+        .ok()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+}
+
+/// `profiles.yaml` in `config/`, else in `~/.openclaw-harness/`
+pub fn profiles_path() -> Option<PathBuf> {
+    [
+        PathBuf::from("config/profiles.yaml"),
+        crate::harness_path("profiles.yaml"),
+    ]
+    .into_iter()
+    .find(|p| p.exists())
+}
+
+pub fn load_profiles(path: &Path) -> anyhow::Result<Profiles> {
+    Ok(serde_yaml::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+/// Apply the profile named by `OPENCLAW_HARNESS_PROFILE`, if any
+pub fn apply_active(rules: &mut [Rule]) {
+    let Some(name) = active_profile() else {
+        return;
+    };
+    let Some(path) = profiles_path() else {
+        warn!("⚠️ {}={} but no profiles.yaml was found", PROFILE_ENV, name);
+        return;
+    };
+    let profiles = match load_profiles(&path) {
+        Ok(profiles) => profiles,
+        Err(e) => {
+            warn!("⚠️ Failed to load {}: {}", path.display(), e);
+            return;
+        }
+    };
+    match profiles.get(&name) {
+        Some(overrides) => {
+            let changed = apply(rules, overrides);
+            info!("🎚️ Profile '{}' changed {} rule(s)", name, changed);
+        }
+        None => warn!("⚠️ Profile '{}' is not in {}", name, path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{default_rules, self_protection_rules, TemplateParams};
+
+    #[test]
+    fn test_profile_overrides() {
+        let yaml = r#"
+production:
+  - match_type: template
+    from: warning
+    risk_level: critical
+dev:
+  - rules: [git_push]
+    action: log_only
+  - from: critical
+    risk_level: warning
+"#;
+        let profiles: Profiles = serde_yaml::from_str(yaml).unwrap();
+        let rules = || {
+            let mut rules = default_rules();
+            rules.push(Rule::new_template(
+                "no_sudo",
+                "block_sudo",
+                TemplateParams::default(),
+                RiskLevel::Warning,
+                RuleAction::Block,
+            ));
+            rules.extend(self_protection_rules());
+            rules
+        };
+        let find =
+            |rules: &[Rule], name: &str| rules.iter().find(|r| r.name == name).unwrap().clone();
+
+        let mut prod = rules();
+        assert_eq!(apply(&mut prod, &profiles["production"]), 1);
+        assert_eq!(find(&prod, "no_sudo").risk_level, RiskLevel::Critical);
+        // Regex rules at warning are left alone
+        assert_eq!(
+            find(&prod, "sudo_command").risk_level,
+            find(&rules(), "sudo_command").risk_level
+        );
+
+        let mut dev = rules();
+        apply(&mut dev, &profiles["dev"]);
+        assert_eq!(find(&dev, "git_push").action, RuleAction::LogOnly);
+        assert_eq!(find(&dev, "dangerous_rm").risk_level, RiskLevel::Warning);
+        // Self-protection keeps its severity
+        assert!(dev
+            .iter()
+            .filter(|r| r.protected)
+            .all(|r| r.risk_level == RiskLevel::Critical));
+
+        assert!(serde_yaml::from_str::<Profiles>("dev:\n  - rule: git_push\n").is_err());
+    }
+}
@@ -0,0 +1,478 @@
+//! Two-way Slack app (Socket Mode)
+//!
+//! Webhooks can only post. With an app-level token the daemon also opens a
+//! Socket Mode connection and answers:
+//!
+//! - `/harness status` — event counts, pending approvals, alert pause
+//! - `/harness approve <id>` / `/harness deny <id>` — decide a pending request
+//! - `/harness pause 30m` / `/harness resume` — hold alerts for a while
+//!
+//! When the PreToolUse hook stops a PauseAndAsk tool call it files an
+//! approval request; the app posts each new request to the channel with
+//! Approve/Deny buttons. An approved call goes through the next time the
+//! agent makes it (within `APPROVAL_VALID_MINUTES`).
+//!
+//! Only the Slack users listed in `OPENCLAW_HARNESS_SLACK_ALLOWED_USERS`
+//! (member IDs, `U0123ABCD`, comma-separated) may use the commands and
+//! buttons; everyone else in the workspace only gets the help text.
+
+use crate::db::{Approval, DbPool};
+use crate::enforcer::alerter::Alerter;
+use futures_util::{SinkExt, StreamExt};
+use reqwest::Client;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio_tungstenite::tungstenite::Message;
+use tracing::{info, warn};
+
+/// App-level token (`xapp-...`) with `connections:write`
+pub const APP_TOKEN_ENV: &str = "OPENCLAW_HARNESS_SLACK_APP_TOKEN";
+/// Bot token (`xoxb-...`) with `chat:write`
+pub const BOT_TOKEN_ENV: &str = "OPENCLAW_HARNESS_SLACK_BOT_TOKEN";
+/// Channel approval requests are posted to
+pub const CHANNEL_ENV: &str = "OPENCLAW_HARNESS_SLACK_CHANNEL";
+/// Slack member IDs allowed to use the app, comma-separated
+pub const ALLOWED_USERS_ENV: &str = "OPENCLAW_HARNESS_SLACK_ALLOWED_USERS";
+
+/// How long an approval stays usable
+pub const APPROVAL_VALID_MINUTES: i64 = 15;
+
+/// How often new approval requests are looked for
+const ANNOUNCE_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+/// Wait before reconnecting after the socket drops
+const RECONNECT_DELAY: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Status,
+    Approve(String),
+    Deny(String),
+    Pause(chrono::Duration),
+    Resume,
+    Help,
+}
+
+impl Command {
+    /// Parse the text after `/harness`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut words = text.split_whitespace();
+        let command = match (words.next(), words.next()) {
+            (Some("status"), None) => Command::Status,
+            (Some("approve"), Some(id)) => Command::Approve(id.to_string()),
+            (Some("deny"), Some(id)) => Command::Deny(id.to_string()),
+            (Some("pause"), Some(duration)) => Command::Pause(parse_duration(duration)?),
+            (Some("resume"), None) => Command::Resume,
+            (None | Some("help"), None) => Command::Help,
+            _ => return Err(format!("Unknown command '{}'. Try `/harness help`.", text)),
+        };
+        if words.next().is_some() {
+            return Err(format!("Too many arguments in '{}'", text));
+        }
+        Ok(command)
+    }
+}
+
+/// `30m`, `2h`, `1d`, `90s`
+pub fn parse_duration(text: &str) -> Result<chrono::Duration, String> {
+    let invalid = || format!("Invalid duration '{}' (e.g. 30m, 2h)", text);
+    let split = text.char_indices().last().ok_or_else(invalid)?.0;
+    let (number, unit) = text.split_at(split);
+    let n: i64 = number.parse().map_err(|_| invalid())?;
+    match unit {
+        "s" => Ok(chrono::Duration::seconds(n)),
+        "m" => Ok(chrono::Duration::minutes(n)),
+        "h" => Ok(chrono::Duration::hours(n)),
+        "d" => Ok(chrono::Duration::days(n)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Message with Approve/Deny buttons for a pending request
+pub fn approval_message(channel: &str, approval: &Approval) -> Value {
+    let text = format!("⏸️ Approval needed `{}`: {}", approval.id, approval.summary);
+    json!({
+        "channel": channel,
+        "text": text,
+        "blocks": [
+            { "type": "section", "text": { "type": "mrkdwn", "text": text } },
+            {
+                "type": "actions",
+                "elements": [
+                    {
+                        "type": "button",
+                        "style": "primary",
+                        "text": { "type": "plain_text", "text": "Approve" },
+                        "action_id": "approve",
+                        "value": approval.id,
+                    },
+                    {
+                        "type": "button",
+                        "style": "danger",
+                        "text": { "type": "plain_text", "text": "Deny" },
+                        "action_id": "deny",
+                        "value": approval.id,
+                    },
+                ],
+            },
+        ],
+    })
+}
+
+pub struct SlackApp {
+    client: Client,
+    app_token: String,
+    bot_token: Option<String>,
+    channel: Option<String>,
+    /// Member IDs of the users who may use commands and buttons
+    allowed_users: Vec<String>,
+    db: DbPool,
+    alerter: Option<Arc<Alerter>>,
+}
+
+/// Who sent a command or pressed a button
+pub struct SlackUser<'a> {
+    /// Member ID, checked against the allowlist
+    pub id: &'a str,
+    /// Shown in replies
+    pub name: &'a str,
+}
+
+impl SlackApp {
+    /// App configured in the environment, if any
+    pub fn from_env(db: DbPool, alerter: Option<Arc<Alerter>>) -> Option<Self> {
+        let app_token = std::env::var(APP_TOKEN_ENV)
+            .ok()
+            .filter(|t| !t.is_empty())?;
+        let bot_token = std::env::var(BOT_TOKEN_ENV).ok().filter(|t| !t.is_empty());
+        let channel = std::env::var(CHANNEL_ENV).ok().filter(|c| !c.is_empty());
+        if bot_token.is_none() || channel.is_none() {
+            warn!(
+                "⚠️  Slack approval requests won't be posted (set {} and {})",
+                BOT_TOKEN_ENV, CHANNEL_ENV
+            );
+        }
+        let allowed_users: Vec<String> = std::env::var(ALLOWED_USERS_ENV)
+            .unwrap_or_default()
+            .split(',')
+            .map(|u| u.trim().to_string())
+            .filter(|u| !u.is_empty())
+            .collect();
+        if allowed_users.is_empty() {
+            warn!(
+                "⚠️  No Slack user may approve, deny or pause (set {} to their member IDs)",
+                ALLOWED_USERS_ENV
+            );
+        }
+        Some(Self {
+            client: crate::network::client(),
+            app_token,
+            bot_token,
+            channel,
+            allowed_users,
+            db,
+            alerter,
+        })
+    }
+
+    /// Serve slash commands and buttons, and post approval requests, forever
+    pub async fn run(self) {
+        let app = Arc::new(self);
+        let announcer = app.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = announcer.announce_pending().await {
+                    warn!("Failed to post Slack approval requests: {}", e);
+                }
+                tokio::time::sleep(ANNOUNCE_INTERVAL).await;
+            }
+        });
+        loop {
+            match app.serve_connection().await {
+                Ok(()) => info!("Slack asked to reconnect"),
+                Err(e) => warn!("Slack connection lost: {}", e),
+            }
+            tokio::time::sleep(RECONNECT_DELAY).await;
+        }
+    }
+
+    /// One Socket Mode connection, until Slack or the network drops it
+    async fn serve_connection(&self) -> anyhow::Result<()> {
+        let opened: Value = self
+            .client
+            .post("https://slack.com/api/apps.connections.open")
+            .bearer_auth(&self.app_token)
+            .send()
+            .await?
+            .json()
+            .await?;
+        let Some(url) = opened["url"].as_str() else {
+            anyhow::bail!("apps.connections.open failed: {}", opened["error"]);
+        };
+        let (socket, _) = tokio_tungstenite::connect_async(url).await?;
+        let (mut sink, mut stream) = socket.split();
+        info!("💬 Slack app connected");
+
+        while let Some(message) = stream.next().await {
+            let text = match message? {
+                Message::Text(text) => text,
+                Message::Ping(data) => {
+                    sink.send(Message::Pong(data)).await?;
+                    continue;
+                }
+                Message::Close(_) => break,
+                _ => continue,
+            };
+            let Ok(envelope) = serde_json::from_str::<Value>(&text) else {
+                continue;
+            };
+            if envelope["type"] == "disconnect" {
+                break;
+            }
+            let Some(envelope_id) = envelope["envelope_id"].as_str() else {
+                continue;
+            };
+            let payload = &envelope["payload"];
+            let ack = match envelope["type"].as_str() {
+                Some("slash_commands") => {
+                    let user = SlackUser {
+                        id: payload["user_id"].as_str().unwrap_or_default(),
+                        name: payload["user_name"].as_str().unwrap_or("slack"),
+                    };
+                    let reply = match Command::parse(payload["text"].as_str().unwrap_or_default()) {
+                        Ok(command) => self.execute(command, &user),
+                        Err(e) => e,
+                    };
+                    json!({ "envelope_id": envelope_id, "payload": { "text": reply } })
+                }
+                Some("interactive") => {
+                    self.handle_button(payload).await;
+                    json!({ "envelope_id": envelope_id })
+                }
+                _ => json!({ "envelope_id": envelope_id }),
+            };
+            sink.send(Message::Text(ack.to_string())).await?;
+        }
+        Ok(())
+    }
+
+    /// Reply to a slash command from `user`; only allowed users get more
+    /// than the help text
+    pub fn execute(&self, command: Command, user: &SlackUser) -> String {
+        if command != Command::Help && !self.allowed(user) {
+            warn!(
+                "🚫 Slack user {} ({}) is not allowed to use the harness app",
+                user.name, user.id
+            );
+            return format!(
+                "🚫 You are not allowed to do this; ask an admin to add your member ID to {}",
+                ALLOWED_USERS_ENV
+            );
+        }
+        match self.try_execute(command, user.name) {
+            Ok(reply) => reply,
+            Err(e) => format!("⚠️ {}", e),
+        }
+    }
+
+    fn allowed(&self, user: &SlackUser) -> bool {
+        self.allowed_users.iter().any(|u| u == user.id)
+    }
+
+    fn try_execute(&self, command: Command, user: &str) -> anyhow::Result<String> {
+        let decide = |id: &str, approved: bool| -> anyhow::Result<String> {
+            let db = self.db.get()?;
+            Ok(match db.decide_approval(id, approved, user)? {
+                Some(a) if approved => format!(
+                    "✅ {} approved by {}; it will go through if retried within {} minutes",
+                    a.summary, user, APPROVAL_VALID_MINUTES
+                ),
+                Some(a) => format!("🚫 {} denied by {}", a.summary, user),
+                None => format!("No pending approval `{}`", id),
+            })
+        };
+        match command {
+            Command::Status => {
+                let db = self.db.get()?;
+                let stats = db.get_stats()?;
+                let pending = db.pending_approvals(false)?;
+                let mut reply = format!(
+                    "🛡️ OpenClaw Harness v{}: {} actions, {} blocked, {} warnings, {} pending approval(s)",
+                    env!("CARGO_PKG_VERSION"),
+                    stats.total_actions,
+                    stats.blocked,
+                    stats.warnings,
+                    pending.len()
+                );
+                for approval in pending.iter().take(5) {
+                    reply.push_str(&format!("\n• `{}` {}", approval.id, approval.summary));
+                }
+                if let Some(until) = self.alerter.as_ref().and_then(|a| a.paused_until()) {
+                    reply.push_str(&format!("\n⏸️ Alerts paused until {}", until.to_rfc3339()));
+                }
+                Ok(reply)
+            }
+            Command::Approve(id) => decide(&id, true),
+            Command::Deny(id) => decide(&id, false),
+            Command::Pause(duration) => {
+                let Some(alerter) = &self.alerter else {
+                    anyhow::bail!("No alert channels are configured");
+                };
+                let until = chrono::Utc::now() + duration;
+                alerter.pause(Some(until));
+                Ok(format!(
+                    "⏸️ Alerts paused by {} until {} (Critical alerts still go out)",
+                    user,
+                    until.to_rfc3339()
+                ))
+            }
+            Command::Resume => {
+                if let Some(alerter) = &self.alerter {
+                    alerter.pause(None);
+                }
+                Ok(format!("▶️ Alerts resumed by {}", user))
+            }
+            Command::Help => Ok(
+                "`/harness status` · `/harness approve <id>` · `/harness deny <id>` · `/harness pause 30m` · `/harness resume`"
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// Approve/Deny button on an approval message
+    async fn handle_button(&self, payload: &Value) {
+        let Some(action) = payload["actions"].get(0) else {
+            return;
+        };
+        let id = action["value"].as_str().unwrap_or_default().to_string();
+        let user = SlackUser {
+            id: payload["user"]["id"].as_str().unwrap_or_default(),
+            name: payload["user"]["username"]
+                .as_str()
+                .or(payload["user"]["name"].as_str())
+                .unwrap_or("slack"),
+        };
+        let command = match action["action_id"].as_str() {
+            Some("approve") => Command::Approve(id),
+            Some("deny") => Command::Deny(id),
+            _ => return,
+        };
+        // Someone else pressing the button leaves the request in place
+        let allowed = self.allowed(&user);
+        let reply = self.execute(command, &user);
+        if let Some(response_url) = payload["response_url"].as_str() {
+            let body = if allowed {
+                json!({ "replace_original": true, "text": reply })
+            } else {
+                json!({ "replace_original": false, "response_type": "ephemeral", "text": reply })
+            };
+            if let Err(e) = self.client.post(response_url).json(&body).send().await {
+                warn!("Failed to update Slack approval message: {}", e);
+            }
+        }
+    }
+
+    /// Post approval requests nobody has seen yet
+    async fn announce_pending(&self) -> anyhow::Result<()> {
+        let (Some(bot_token), Some(channel)) = (&self.bot_token, &self.channel) else {
+            return Ok(());
+        };
+        let pending = self.db.get()?.pending_approvals(true)?;
+        for approval in pending {
+            let resp: Value = self
+                .client
+                .post("https://slack.com/api/chat.postMessage")
+                .bearer_auth(bot_token)
+                .json(&approval_message(channel, &approval))
+                .send()
+                .await?
+                .json()
+                .await?;
+            if resp["ok"] != true {
+                anyhow::bail!("chat.postMessage failed: {}", resp["error"]);
+            }
+            self.db.get()?.mark_approval_announced(&approval.id)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slash_commands_and_approvals() {
+        assert_eq!(Command::parse("status"), Ok(Command::Status));
+        assert_eq!(Command::parse(""), Ok(Command::Help));
+        assert_eq!(
+            Command::parse("pause 30m"),
+            Ok(Command::Pause(chrono::Duration::minutes(30)))
+        );
+        assert!(Command::parse("pause soon").is_err());
+        assert!(Command::parse("pause 3é").is_err());
+        assert!(Command::parse("approve").is_err());
+        assert!(Command::parse("approve a b").is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("harness.db");
+        let pool = DbPool::open(&db_path).unwrap();
+        let db = pool.get().unwrap();
+        let id = db.request_approval("fp1", "Bash: npm publish").unwrap();
+        assert_eq!(db.request_approval("fp1", "Bash: npm publish").unwrap(), id);
+
+        let message = approval_message("#ops", &db.pending_approvals(true).unwrap()[0]);
+        assert_eq!(message["blocks"][1]["elements"][0]["value"], id.as_str());
+
+        let alerter = Arc::new(Alerter::new(crate::Config::default().alerts));
+        let app = SlackApp {
+            client: crate::network::client(),
+            app_token: "xapp-test".to_string(),
+            bot_token: None,
+            channel: None,
+            allowed_users: vec!["U01ANA".to_string()],
+            db: pool,
+            alerter: Some(alerter.clone()),
+        };
+        let ana = SlackUser {
+            id: "U01ANA",
+            name: "ana",
+        };
+        // Anyone else in the workspace gets help and nothing more
+        let mallory = SlackUser {
+            id: "U02MAL",
+            name: "mallory",
+        };
+        assert!(app
+            .execute(Command::Approve(id.clone()), &mallory)
+            .starts_with("🚫"));
+        assert!(app.execute(Command::Status, &mallory).starts_with("🚫"));
+        app.execute(Command::Pause(chrono::Duration::minutes(30)), &mallory);
+        assert!(alerter.paused_until().is_none());
+        assert!(app
+            .execute(Command::Help, &mallory)
+            .contains("/harness status"));
+
+        assert!(app.execute(Command::Status, &ana).contains("1 pending"));
+        assert!(!db
+            .take_approval("fp1", chrono::Duration::minutes(5))
+            .unwrap());
+        assert!(app
+            .execute(Command::Approve(id.clone()), &ana)
+            .starts_with("✅"));
+        assert!(app
+            .execute(Command::Deny(id), &ana)
+            .starts_with("No pending"));
+        assert!(db
+            .take_approval("fp1", chrono::Duration::minutes(5))
+            .unwrap());
+        // Used up
+        assert!(!db
+            .take_approval("fp1", chrono::Duration::minutes(5))
+            .unwrap());
+
+        app.execute(Command::Pause(chrono::Duration::minutes(30)), &ana);
+        assert!(alerter.paused_until().is_some());
+        app.execute(Command::Resume, &ana);
+        assert!(alerter.paused_until().is_none());
+    }
+}
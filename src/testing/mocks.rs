@@ -0,0 +1,265 @@
+//! Stand-ins for the LLM provider and the alert channels
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{StatusCode, Uri},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// A scripted provider response
+#[derive(Debug, Clone)]
+pub struct ProviderResponse {
+    pub status: u16,
+    pub content_type: String,
+    pub body: String,
+}
+
+impl ProviderResponse {
+    pub fn json(body: Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json".to_string(),
+            body: body.to_string(),
+        }
+    }
+
+    /// A server-sent event stream of `(event, data)` pairs
+    pub fn sse(events: &[(&str, Value)]) -> Self {
+        let body = events
+            .iter()
+            .map(|(event, data)| format!("event: {}\ndata: {}\n\n", event, data))
+            .collect();
+        Self {
+            status: 200,
+            content_type: "text/event-stream".to_string(),
+            body,
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            ..Self::json(json!({"type": "error", "error": {"message": message}}))
+        }
+    }
+
+    /// An Anthropic Messages response with just text
+    pub fn anthropic_text(text: &str) -> Self {
+        Self::json(json!({
+            "id": "msg_test",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "text", "text": text}],
+            "stop_reason": "end_turn"
+        }))
+    }
+
+    /// An Anthropic Messages response calling `tool` with `input`
+    pub fn anthropic_tool_use(tool: &str, input: Value) -> Self {
+        Self::json(json!({
+            "id": "msg_test",
+            "type": "message",
+            "role": "assistant",
+            "content": [{"type": "tool_use", "id": "toolu_test", "name": tool, "input": input}],
+            "stop_reason": "tool_use"
+        }))
+    }
+
+    /// The same, streamed, with the input split across two deltas as
+    /// providers do
+    pub fn anthropic_tool_use_stream(tool: &str, input: Value) -> Self {
+        let input = input.to_string();
+        let (first, second) = input.split_at(input.len() / 2);
+        let delta = |partial: &str| {
+            json!({
+                "type": "content_block_delta",
+                "index": 0,
+                "delta": {"type": "input_json_delta", "partial_json": partial}
+            })
+        };
+        Self::sse(&[
+            (
+                "message_start",
+                json!({"type": "message_start", "message": {
+                    "id": "msg_test", "type": "message", "role": "assistant",
+                    "content": [], "stop_reason": null,
+                    "usage": {"input_tokens": 10, "output_tokens": 0}
+                }}),
+            ),
+            (
+                "content_block_start",
+                json!({"type": "content_block_start", "index": 0, "content_block": {
+                    "type": "tool_use", "id": "toolu_test", "name": tool
+                }}),
+            ),
+            ("content_block_delta", delta(first)),
+            ("content_block_delta", delta(second)),
+            (
+                "content_block_stop",
+                json!({"type": "content_block_stop", "index": 0}),
+            ),
+            ("message_stop", json!({"type": "message_stop"})),
+        ])
+    }
+
+    /// An OpenAI Chat Completions response calling `tool` with `arguments`
+    pub fn openai_tool_call(tool: &str, arguments: Value) -> Self {
+        Self::json(json!({
+            "id": "chatcmpl-test",
+            "choices": [{"index": 0, "message": {
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [{"id": "call_test", "type": "function", "function": {
+                    "name": tool,
+                    "arguments": arguments.to_string()
+                }}]
+            }, "finish_reason": "tool_calls"}]
+        }))
+    }
+}
+
+/// A request the mock provider received
+#[derive(Debug, Clone)]
+pub struct ProviderRequest {
+    pub path: String,
+    pub body: String,
+}
+
+#[derive(Default)]
+struct ProviderState {
+    responses: Mutex<VecDeque<ProviderResponse>>,
+    requests: Mutex<Vec<ProviderRequest>>,
+}
+
+/// An LLM provider that answers with scripted responses, in order
+#[derive(Clone)]
+pub struct MockProvider {
+    addr: SocketAddr,
+    state: Arc<ProviderState>,
+}
+
+impl MockProvider {
+    pub async fn start() -> anyhow::Result<Self> {
+        let state = Arc::new(ProviderState::default());
+        let app = Router::new()
+            .fallback(provider_handler)
+            .with_state(state.clone());
+        let addr = serve(app).await?;
+        Ok(Self { addr, state })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Answer the next request with `response`
+    pub fn respond_with(&self, response: ProviderResponse) {
+        self.state.responses.lock().unwrap().push_back(response);
+    }
+
+    /// Requests received so far, as forwarded by the proxy
+    pub fn requests(&self) -> Vec<ProviderRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+async fn provider_handler(
+    State(state): State<Arc<ProviderState>>,
+    uri: Uri,
+    body: Bytes,
+) -> Response {
+    state.requests.lock().unwrap().push(ProviderRequest {
+        path: uri.path().to_string(),
+        body: String::from_utf8_lossy(&body).to_string(),
+    });
+    let Some(response) = state.responses.lock().unwrap().pop_front() else {
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "mock provider: no scripted response left",
+        )
+            .into_response();
+    };
+    Response::builder()
+        .status(response.status)
+        .header("content-type", response.content_type)
+        .body(response.body.into())
+        .unwrap()
+}
+
+/// An alert as a channel received it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SentAlert {
+    /// `slack` or `discord`
+    pub channel: String,
+    pub text: String,
+}
+
+/// Slack and Discord webhooks that record what they are sent
+#[derive(Clone)]
+pub struct MockChannels {
+    addr: SocketAddr,
+    sent: Arc<Mutex<Vec<SentAlert>>>,
+}
+
+impl MockChannels {
+    pub async fn start() -> anyhow::Result<Self> {
+        let sent = Arc::new(Mutex::new(vec![]));
+        let app = Router::new()
+            .route("/slack", post(slack_handler))
+            .route("/discord", post(discord_handler))
+            .with_state(sent.clone());
+        let addr = serve(app).await?;
+        Ok(Self { addr, sent })
+    }
+
+    pub fn slack_url(&self) -> String {
+        format!("http://{}/slack", self.addr)
+    }
+
+    pub fn discord_url(&self) -> String {
+        format!("http://{}/discord", self.addr)
+    }
+
+    /// Everything sent so far
+    pub fn sent(&self) -> Vec<SentAlert> {
+        self.sent.lock().unwrap().clone()
+    }
+
+    pub fn clear(&self) {
+        self.sent.lock().unwrap().clear();
+    }
+}
+
+type Sent = Arc<Mutex<Vec<SentAlert>>>;
+
+async fn slack_handler(State(sent): State<Sent>, Json(body): Json<Value>) -> StatusCode {
+    record(&sent, "slack", &body["text"])
+}
+
+async fn discord_handler(State(sent): State<Sent>, Json(body): Json<Value>) -> StatusCode {
+    record(&sent, "discord", &body["content"])
+}
+
+fn record(sent: &Sent, channel: &str, text: &Value) -> StatusCode {
+    sent.lock().unwrap().push(SentAlert {
+        channel: channel.to_string(),
+        text: text.as_str().unwrap_or_default().to_string(),
+    });
+    StatusCode::OK
+}
+
+/// Serve `app` on an ephemeral loopback port
+async fn serve(app: Router) -> anyhow::Result<SocketAddr> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let addr = listener.local_addr()?;
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    Ok(addr)
+}
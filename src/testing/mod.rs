@@ -0,0 +1,193 @@
+//! The whole pipeline in one process, for end-to-end tests of rule packs
+//!
+//! Behind the `test-support` feature. `TestHarness::start` wires up what
+//! the daemon runs, with nothing touching the user's machine: the analyzer
+//! with the given rules, a database in a temporary directory, an in-memory
+//! event bus, the enforcer
+//! with its Slack and Discord channels pointed at `MockChannels`, and the
+//! API proxy on an ephemeral port in front of a `MockProvider` that
+//! replays scripted responses.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use openclaw_harness::testing::{ProviderResponse, TestHarness};
+//!
+//! let harness = TestHarness::start(openclaw_harness::rules::default_rules()).await?;
+//! harness.provider().respond_with(ProviderResponse::anthropic_tool_use(
+//!     "exec",
+//!     serde_json::json!({"command": "rm -rf /"}),
+//! ));
+//! let reply = harness.post("/v1/messages", &serde_json::json!({})).await?;
+//! assert!(reply.body.contains("OpenClaw Harness blocked"));
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod mocks;
+
+pub use mocks::{MockChannels, MockProvider, ProviderRequest, ProviderResponse, SentAlert};
+
+use crate::analyzer::Analyzer;
+use crate::blob::BlobStore;
+use crate::correlation;
+use crate::db::{Database, DbPool};
+use crate::enforcer::Enforcer;
+use crate::events::{Event, EventBus};
+use crate::proxy::config::{ProxyConfig, ProxyMode};
+use crate::proxy::control::ProxyControl;
+use crate::proxy::{bind_proxy, ProxyStorage};
+use crate::rules::Rule;
+use crate::{AgentAction, AlertConfig, AnalysisResult, DiscordConfig, SlackConfig};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// A running pipeline; everything stops when it is dropped with the runtime
+pub struct TestHarness {
+    analyzer: Analyzer,
+    enforcer: Enforcer,
+    db: DbPool,
+    bus: EventBus,
+    blobs: BlobStore,
+    control: Arc<ProxyControl>,
+    proxy_addr: SocketAddr,
+    provider: MockProvider,
+    channels: MockChannels,
+    client: reqwest::Client,
+    /// Database, blobs and captures; removed on drop
+    _dir: tempfile::TempDir,
+}
+
+/// What the proxy answered
+#[derive(Debug, Clone)]
+pub struct ProxyReply {
+    pub status: u16,
+    pub content_type: String,
+    pub body: String,
+}
+
+impl ProxyReply {
+    pub fn json(&self) -> anyhow::Result<serde_json::Value> {
+        Ok(serde_json::from_str(&self.body)?)
+    }
+}
+
+impl TestHarness {
+    /// Start the pipeline with `rules`, the proxy in enforce mode
+    pub async fn start(mut rules: Vec<Rule>) -> anyhow::Result<Self> {
+        for rule in &mut rules {
+            rule.compile()?;
+        }
+        let dir = tempfile::tempdir()?;
+        let db_path = dir.path().join("openclaw-harness.db");
+        let db = DbPool::open(&db_path)?;
+        let bus = EventBus::in_memory();
+
+        let channels = MockChannels::start().await?;
+        let enforcer = Enforcer::new(AlertConfig {
+            telegram: None,
+            slack: Some(SlackConfig {
+                webhook_url: channels.slack_url(),
+            }),
+            discord: Some(DiscordConfig {
+                webhook_url: channels.discord_url(),
+            }),
+            desktop: None,
+            quiet_hours: Default::default(),
+        });
+
+        let provider = MockProvider::start().await?;
+        let control = ProxyControl::new(ProxyMode::Enforce, rules.clone());
+        let config = ProxyConfig {
+            listen: "127.0.0.1:0".to_string(),
+            target: provider.url(),
+            health_check_interval_secs: 0,
+            expose: false,
+            ..Default::default()
+        };
+        let storage = ProxyStorage::shared(db.clone());
+        let proxy = bind_proxy(config, None, control.clone(), Some(bus.clone()), storage).await?;
+        let proxy_addr = proxy.addrs[0];
+        tokio::spawn(proxy.serve());
+
+        Ok(Self {
+            analyzer: Analyzer::new(rules),
+            enforcer,
+            db,
+            bus,
+            blobs: BlobStore::for_db(&db_path),
+            control,
+            proxy_addr,
+            provider,
+            channels,
+            client: reqwest::Client::new(),
+            _dir: dir,
+        })
+    }
+
+    /// Base URL of the proxy, to point an agent or HTTP client at
+    pub fn proxy_url(&self) -> String {
+        format!("http://{}", self.proxy_addr)
+    }
+
+    pub fn provider(&self) -> &MockProvider {
+        &self.provider
+    }
+
+    pub fn channels(&self) -> &MockChannels {
+        &self.channels
+    }
+
+    /// The proxy's mode and rules, switchable while it runs
+    pub fn control(&self) -> &Arc<ProxyControl> {
+        &self.control
+    }
+
+    /// The bus actions and analyses are published on
+    pub fn bus(&self) -> &EventBus {
+        &self.bus
+    }
+
+    /// A connection to the database the pipeline logs to
+    pub fn db(&self) -> Database {
+        self.db.get().unwrap()
+    }
+
+    /// Handle an action as the daemon handles one from a collector: analyze
+    /// it, store it and its analysis, publish both and alert on it
+    pub async fn process(&self, action: AgentAction) -> anyhow::Result<AnalysisResult> {
+        let mut result = self.analyzer.analyze(&action);
+        let stored = self.blobs.offload(&result.action);
+        {
+            let db = self.db();
+            correlation::record(&db, &stored, correlation::window())?;
+            result.action = stored.clone();
+            db.store_analysis(&result)?;
+        }
+        self.bus.publish(Event::Action(stored));
+        self.bus.publish(Event::Analysis(result.clone()));
+        if !result.matched_rules.is_empty() {
+            self.enforcer.enforce(&result).await?;
+        }
+        Ok(result)
+    }
+
+    /// POST `body` to `path` through the proxy
+    pub async fn post(&self, path: &str, body: &serde_json::Value) -> anyhow::Result<ProxyReply> {
+        let response = self
+            .client
+            .post(format!("{}{}", self.proxy_url(), path))
+            .json(body)
+            .send()
+            .await?;
+        Ok(ProxyReply {
+            status: response.status().as_u16(),
+            content_type: response
+                .headers()
+                .get("content-type")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+            body: response.text().await?,
+        })
+    }
+}
@@ -0,0 +1,264 @@
+//! Structured tracing of the harness's own work
+//!
+//! Log lines are written inside spans saying what they are about, so each
+//! one carries the ids needed to follow a call or an action through the
+//! harness:
+//!
+//! - `proxy_request{request_id, method, path}`: one API call through the
+//!   proxy, with `session_id` once the call is attributed. Intercepts
+//!   stored from it keep the `request_id` in their metadata.
+//! - `action{action_id, agent, action_type}`: one action on its way
+//!   through the analyzer, storage and the enforcer, with `session_id` and
+//!   the `request_id` it came from when known
+//! - `analyze{action_id}`: the rule check, recording the `matched` rules
+//!   and `risk`
+//! - `enforce{action_id, risk}`: alerting and blocking
+//!
+//! Built with the `otel` feature, spans are also exported over OTLP/HTTP
+//! when `OPENCLAW_HARNESS_OTLP_ENDPOINT` is set to a collector's base URL
+//! (Jaeger: `http://localhost:4318`). The standard
+//! `OTEL_EXPORTER_OTLP_ENDPOINT` / `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT`
+//! variables work too, with `OTEL_EXPORTER_OTLP_HEADERS` and the rest.
+
+use crate::logfile::{LogFileConfig, RollingFile};
+use crate::{AgentAction, AnalysisResult};
+use tracing::field::Empty;
+use tracing::{info_span, Level, Span};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Environment variable with the OTLP/HTTP collector to export spans to
+pub const OTLP_ENDPOINT_ENV: &str = "OPENCLAW_HARNESS_OTLP_ENDPOINT";
+/// Service name spans are exported under
+pub const SERVICE_NAME: &str = "openclaw-harness";
+
+const STANDARD_ENDPOINT_ENVS: [&str; 2] = [
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+];
+
+/// A new id for an API call through the proxy
+pub fn request_id() -> String {
+    format!("req-{}", ulid::Ulid::new())
+}
+
+/// Span of one API call through the proxy
+pub fn proxy_span(request_id: &str, method: &str, path: &str) -> Span {
+    info_span!(
+        "proxy_request",
+        request_id,
+        method,
+        path,
+        session_id = Empty
+    )
+}
+
+/// Span of one action through analysis, storage and enforcement
+pub fn action_span(action: &AgentAction) -> Span {
+    let span = info_span!(
+        "action",
+        action_id = %action.id,
+        agent = %action.agent,
+        action_type = %action.action_type,
+        session_id = Empty,
+        request_id = Empty
+    );
+    if let Some(session_id) = &action.session_id {
+        span.record("session_id", session_id.as_str());
+    }
+    if let Some(request_id) = action.request_id() {
+        span.record("request_id", request_id);
+    }
+    span
+}
+
+/// Span of alerting on and blocking an analyzed action
+pub fn enforce_span(result: &AnalysisResult) -> Span {
+    info_span!(
+        "enforce",
+        action_id = %result.action.id,
+        risk = %result.risk_level
+    )
+}
+
+/// Where spans go besides the log
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Export {
+    Off,
+    /// Endpoint and options from the standard `OTEL_EXPORTER_OTLP_*` variables
+    Standard,
+    /// Traces URL from `OPENCLAW_HARNESS_OTLP_ENDPOINT`
+    Endpoint(String),
+}
+
+fn export() -> Export {
+    if let Some(base) = std::env::var(OTLP_ENDPOINT_ENV)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+    {
+        return Export::Endpoint(traces_url(&base));
+    }
+    if STANDARD_ENDPOINT_ENVS
+        .iter()
+        .any(|var| std::env::var_os(var).is_some_and(|v| !v.is_empty()))
+    {
+        return Export::Standard;
+    }
+    Export::Off
+}
+
+/// OTLP/HTTP traces URL for a collector's base URL
+fn traces_url(base: &str) -> String {
+    let base = base.trim().trim_end_matches('/');
+    if base.ends_with("/v1/traces") {
+        base.to_string()
+    } else {
+        format!("{}/v1/traces", base)
+    }
+}
+
+/// Flushes the log file and exported spans when dropped; keep it until the
+/// process exits
+#[cfg_attr(not(feature = "otel"), derive(Default))]
+pub struct TraceGuard {
+    _log_file: Option<tracing_appender::non_blocking::WorkerGuard>,
+    #[cfg(feature = "otel")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Drop for TraceGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if let Some(provider) = self.provider.take() {
+            let _ = provider.shutdown();
+        }
+    }
+}
+
+/// Install the global subscriber: the log on stdout at `level`, also
+/// written to `log_file` when given, plus OTLP export when configured
+pub fn init(level: Level, log_file: Option<LogFileConfig>) -> anyhow::Result<TraceGuard> {
+    let export = export();
+    // Reported once the subscriber is up
+    let mut opened = None;
+    let (file_writer, file_guard) = match log_file {
+        Some(config) => {
+            let path = config.path();
+            match RollingFile::open(config) {
+                Ok(file) => {
+                    opened = Some(Ok(path));
+                    let (writer, guard) = tracing_appender::non_blocking(file);
+                    (Some(writer), Some(guard))
+                }
+                Err(e) => {
+                    opened = Some(Err(format!("{}: {}", path.display(), e)));
+                    (None, None)
+                }
+            }
+        }
+        None => (None, None),
+    };
+    let registry = tracing_subscriber::registry()
+        .with(LevelFilter::from_level(level))
+        .with(tracing_subscriber::fmt::layer().with_target(false))
+        .with(file_writer.map(|writer| {
+            tracing_subscriber::fmt::layer()
+                .with_target(false)
+                .with_ansi(false)
+                .with_writer(writer)
+        }));
+
+    #[cfg(feature = "otel")]
+    {
+        let provider = otlp_provider(&export)?;
+        let layer = provider.as_ref().map(|provider| {
+            use opentelemetry::trace::TracerProvider as _;
+            tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME))
+        });
+        registry.with(layer).try_init()?;
+        report_log_file(opened);
+        if let Export::Endpoint(url) = &export {
+            tracing::info!("🔭 Exporting traces to {}", url);
+        }
+        Ok(TraceGuard {
+            _log_file: file_guard,
+            provider,
+        })
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        registry.try_init()?;
+        report_log_file(opened);
+        if export != Export::Off {
+            tracing::warn!(
+                "⚠️  An OTLP endpoint is set but this build can't export traces; \
+                 rebuild with --features otel"
+            );
+        }
+        Ok(TraceGuard {
+            _log_file: file_guard,
+        })
+    }
+}
+
+fn report_log_file(opened: Option<Result<std::path::PathBuf, String>>) {
+    match opened {
+        Some(Ok(path)) => tracing::info!("📝 Logging to {}", path.display()),
+        Some(Err(e)) => tracing::warn!("⚠️  Cannot write the log file {}", e),
+        None => {}
+    }
+}
+
+#[cfg(feature = "otel")]
+fn otlp_provider(
+    export: &Export,
+) -> anyhow::Result<Option<opentelemetry_sdk::trace::SdkTracerProvider>> {
+    use opentelemetry_otlp::{Protocol, WithExportConfig};
+
+    let mut builder = opentelemetry_otlp::SpanExporter::builder()
+        .with_http()
+        .with_protocol(Protocol::HttpBinary);
+    match export {
+        Export::Off => return Ok(None),
+        Export::Standard => {}
+        Export::Endpoint(url) => builder = builder.with_endpoint(url.clone()),
+    }
+    let exporter = builder
+        .build()
+        .map_err(|e| anyhow::anyhow!("OTLP exporter: {}", e))?;
+    let resource = opentelemetry_sdk::Resource::builder()
+        .with_service_name(SERVICE_NAME)
+        .build();
+    Ok(Some(
+        opentelemetry_sdk::trace::SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(resource)
+            .build(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_traces_url_and_ids() {
+        assert_eq!(
+            traces_url("http://localhost:4318"),
+            "http://localhost:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_url(" http://collector:4318/ "),
+            "http://collector:4318/v1/traces"
+        );
+        assert_eq!(
+            traces_url("https://otel.example.com/v1/traces"),
+            "https://otel.example.com/v1/traces"
+        );
+
+        let (a, b) = (request_id(), request_id());
+        assert!(a.starts_with("req-"));
+        assert_ne!(a, b);
+    }
+}
@@ -0,0 +1,427 @@
+//! System tray companion (`openclaw-harness tray`)
+//!
+//! Talks to the daemon through its web API on this machine, like `status`
+//! does, so it runs alongside the daemon and keeps working across restarts.
+//! Every few seconds it reads `/status`, `/stats` and `/alerts/pause`. The
+//! icon shows whether the daemon is running and whether alerts are paused;
+//! the menu shows today's events and blocks, pauses or resumes alerts, and
+//! opens the dashboard (also on a left click). With
+//! `OPENCLAW_HARNESS_API_TOKEN` set, it sends the token like the CLI does.
+//!
+//! The icon needs the `tray` feature and a StatusNotifierItem host (KDE,
+//! most Linux panels, GNOME with the AppIndicator extension).
+
+use crate::web::auth;
+use chrono::{DateTime, Local, Utc};
+use reqwest::{Client, Method, RequestBuilder};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// How often the tray asks the daemon for its status
+pub const REFRESH: Duration = Duration::from_secs(5);
+/// How long "Pause alerts" holds them
+pub const PAUSE_MINUTES: u32 = 30;
+
+const TIMEOUT: Duration = Duration::from_secs(3);
+
+/// What the tray shows about the daemon
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrayStatus {
+    pub running: bool,
+    pub version: Option<String>,
+    pub today_events: u64,
+    pub today_blocked: u64,
+    /// Alerts other than Critical are held until then
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+impl TrayStatus {
+    /// Freedesktop icon name for the state
+    pub fn icon_name(&self) -> &'static str {
+        match (self.running, self.paused_until.is_some()) {
+            (false, _) => "security-low",
+            (true, true) => "security-medium",
+            (true, false) => "security-high",
+        }
+    }
+
+    /// One line for the menu header and tooltip
+    pub fn headline(&self) -> String {
+        match (&self.version, self.running) {
+            (_, false) => "🔴 Daemon not running".to_string(),
+            (Some(version), true) => format!("🟢 Running (v{})", version),
+            (None, true) => "🟢 Running".to_string(),
+        }
+    }
+
+    /// Today's counts, or None while the daemon is down
+    pub fn today(&self) -> Option<String> {
+        self.running.then(|| {
+            format!(
+                "Today: {} event{}, {} blocked",
+                self.today_events,
+                if self.today_events == 1 { "" } else { "s" },
+                self.today_blocked
+            )
+        })
+    }
+
+    /// When paused alerts resume, in local time
+    pub fn pause_note(&self) -> Option<String> {
+        self.paused_until.map(|until| {
+            format!(
+                "⏸️ Alerts paused until {}",
+                until.with_timezone(&Local).format("%H:%M")
+            )
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct StatusBody {
+    version: String,
+}
+
+#[derive(Deserialize)]
+struct StatsBody {
+    today_events: u64,
+    #[serde(default)]
+    today_blocked_count: u64,
+}
+
+#[derive(Deserialize)]
+struct PauseBody {
+    paused_until: Option<DateTime<Utc>>,
+}
+
+/// Client for the daemon's web API at `base_url`
+#[derive(Clone)]
+pub struct TrayClient {
+    client: Client,
+    base_url: String,
+    /// Bearer token for the routes that change something
+    token: Option<String>,
+}
+
+impl TrayClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: auth::token_from_env(),
+        }
+    }
+
+    /// The dashboard, served by the daemon's web server
+    pub fn dashboard_url(&self) -> String {
+        format!("{}/", self.base_url)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/api/v1{}", self.base_url, path)
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let request = self.client.request(method, self.url(path)).timeout(TIMEOUT);
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str) -> reqwest::Result<T> {
+        self.request(Method::GET, path)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+
+    /// The daemon's status; not running when it can't be reached
+    pub async fn status(&self) -> TrayStatus {
+        let Ok(status) = self.get::<StatusBody>("/status").await else {
+            return TrayStatus::default();
+        };
+        let stats = self.get::<StatsBody>("/stats").await.ok();
+        let pause = self.get::<PauseBody>("/alerts/pause").await.ok();
+        TrayStatus {
+            running: true,
+            version: Some(status.version),
+            today_events: stats.as_ref().map_or(0, |s| s.today_events),
+            today_blocked: stats.map_or(0, |s| s.today_blocked_count),
+            paused_until: pause.and_then(|p| p.paused_until),
+        }
+    }
+
+    /// Hold alerts other than Critical for `minutes`
+    pub async fn pause(&self, minutes: u32) -> anyhow::Result<()> {
+        let resp = self
+            .request(Method::POST, "/alerts/pause")
+            .json(&serde_json::json!({ "minutes": minutes }))
+            .send()
+            .await?;
+        if resp.status() == reqwest::StatusCode::CONFLICT {
+            anyhow::bail!("the daemon has no alert channels configured");
+        }
+        resp.error_for_status()?;
+        Ok(())
+    }
+
+    pub async fn resume(&self) -> anyhow::Result<()> {
+        self.request(Method::DELETE, "/alerts/pause")
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Show the tray icon for the daemon at `base_url` until quit
+#[cfg(all(feature = "tray", target_os = "linux"))]
+pub async fn run(base_url: &str) -> anyhow::Result<()> {
+    sni::run(TrayClient::new(base_url)).await
+}
+
+/// Show the tray icon for the daemon at `base_url` until quit
+#[cfg(not(all(feature = "tray", target_os = "linux")))]
+pub async fn run(_base_url: &str) -> anyhow::Result<()> {
+    anyhow::bail!(
+        "this build has no tray icon; it needs Linux and the `tray` feature \
+         (cargo install openclaw-harness --features tray)"
+    )
+}
+
+/// The icon, as a StatusNotifierItem on the session bus
+#[cfg(all(feature = "tray", target_os = "linux"))]
+mod sni {
+    use super::{TrayClient, TrayStatus, PAUSE_MINUTES, REFRESH};
+    use ksni::menu::StandardItem;
+    use ksni::{MenuItem, ToolTip, TrayMethods};
+    use tokio::sync::mpsc;
+    use tracing::{info, warn};
+
+    enum Command {
+        Pause,
+        Resume,
+        OpenDashboard,
+        Quit,
+    }
+
+    struct HarnessTray {
+        status: TrayStatus,
+        commands: mpsc::UnboundedSender<Command>,
+    }
+
+    impl HarnessTray {
+        fn send(&self, command: Command) {
+            let _ = self.commands.send(command);
+        }
+    }
+
+    fn label(text: String) -> MenuItem<HarnessTray> {
+        StandardItem {
+            label: text,
+            enabled: false,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    fn action(text: &str, enabled: bool, command: fn() -> Command) -> MenuItem<HarnessTray> {
+        StandardItem {
+            label: text.to_string(),
+            enabled,
+            activate: Box::new(move |tray: &mut HarnessTray| tray.send(command())),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    impl ksni::Tray for HarnessTray {
+        fn id(&self) -> String {
+            "openclaw-harness".to_string()
+        }
+
+        fn title(&self) -> String {
+            "OpenClaw Harness".to_string()
+        }
+
+        fn icon_name(&self) -> String {
+            self.status.icon_name().to_string()
+        }
+
+        fn tool_tip(&self) -> ToolTip {
+            let description = [self.status.today(), self.status.pause_note()]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<_>>()
+                .join("\n");
+            ToolTip {
+                title: format!("OpenClaw Harness: {}", self.status.headline()),
+                description,
+                ..Default::default()
+            }
+        }
+
+        fn activate(&mut self, _x: i32, _y: i32) {
+            self.send(Command::OpenDashboard);
+        }
+
+        fn menu(&self) -> Vec<MenuItem<Self>> {
+            let running = self.status.running;
+            let mut items = vec![label(self.status.headline())];
+            items.extend(self.status.today().map(label));
+            items.extend(self.status.pause_note().map(label));
+            items.push(MenuItem::Separator);
+            items.push(if self.status.paused_until.is_some() {
+                action("Resume alerts", running, || Command::Resume)
+            } else {
+                action(
+                    &format!("Pause alerts for {} minutes", PAUSE_MINUTES),
+                    running,
+                    || Command::Pause,
+                )
+            });
+            items.push(action("Open dashboard", running, || Command::OpenDashboard));
+            items.push(MenuItem::Separator);
+            items.push(action("Quit", true, || Command::Quit));
+            items
+        }
+    }
+
+    fn open(url: &str) {
+        if let Err(e) = std::process::Command::new("xdg-open").arg(url).spawn() {
+            warn!("Failed to open {}: {}", url, e);
+        }
+    }
+
+    pub async fn run(client: TrayClient) -> anyhow::Result<()> {
+        let (tx, mut commands) = mpsc::unbounded_channel();
+        let tray = HarnessTray {
+            status: client.status().await,
+            commands: tx,
+        };
+        let handle = tray
+            .spawn()
+            .await
+            .map_err(|e| anyhow::anyhow!("no system tray available: {}", e))?;
+        info!("🛡️ Tray icon up for {}", client.dashboard_url());
+
+        let mut refresh = tokio::time::interval(REFRESH);
+        loop {
+            tokio::select! {
+                _ = refresh.tick() => {}
+                command = commands.recv() => {
+                    let result = match command {
+                        Some(Command::Pause) => client.pause(PAUSE_MINUTES).await,
+                        Some(Command::Resume) => client.resume().await,
+                        Some(Command::OpenDashboard) => {
+                            open(&client.dashboard_url());
+                            Ok(())
+                        }
+                        Some(Command::Quit) | None => break,
+                    };
+                    if let Err(e) = result {
+                        warn!("⚠️ {}", e);
+                    }
+                }
+            }
+            let status = client.status().await;
+            if handle
+                .update(|tray: &mut HarnessTray| tray.status = status)
+                .await
+                .is_none()
+            {
+                break;
+            }
+        }
+        handle.shutdown().await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::{HeaderMap, StatusCode};
+    use axum::{routing::get, Json, Router};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_tray_status_from_api() {
+        let until = Utc::now() + chrono::Duration::minutes(30);
+        let app = Router::new()
+            .route(
+                "/api/v1/status",
+                get(|| async { Json(json!({"running": true, "version": "1.2.3"})) }),
+            )
+            .route(
+                "/api/v1/stats",
+                get(|| async { Json(json!({"today_events": 1, "today_blocked_count": 2})) }),
+            )
+            .route(
+                "/api/v1/alerts/pause",
+                get(move || async move { Json(json!({ "paused_until": until })) }),
+            );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let status = TrayClient::new(&format!("http://{}/", addr)).status().await;
+        assert_eq!(
+            status,
+            TrayStatus {
+                running: true,
+                version: Some("1.2.3".to_string()),
+                today_events: 1,
+                today_blocked: 2,
+                paused_until: Some(until),
+            }
+        );
+        assert_eq!(status.headline(), "🟢 Running (v1.2.3)");
+        assert_eq!(status.today().unwrap(), "Today: 1 event, 2 blocked");
+        assert!(status.pause_note().is_some());
+        assert_eq!(status.icon_name(), "security-medium");
+
+        // Nothing listening: the daemon is down
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", closed.local_addr().unwrap());
+        drop(closed);
+        let status = TrayClient::new(&url).status().await;
+        assert_eq!(status, TrayStatus::default());
+        assert_eq!(status.today(), None);
+        assert_eq!(status.icon_name(), "security-low");
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_send_the_token() {
+        async fn check(headers: HeaderMap) -> StatusCode {
+            match headers.get("authorization").and_then(|v| v.to_str().ok()) {
+                Some("Bearer s3cret") => StatusCode::OK,
+                _ => StatusCode::UNAUTHORIZED,
+            }
+        }
+        let app = Router::new().route(
+            "/api/v1/alerts/pause",
+            axum::routing::post(check).delete(check),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let client = TrayClient {
+            token: Some("s3cret".to_string()),
+            ..TrayClient::new(&url)
+        };
+        client.pause(PAUSE_MINUTES).await.unwrap();
+        client.resume().await.unwrap();
+
+        let anonymous = TrayClient {
+            token: None,
+            ..client
+        };
+        assert!(anonymous.pause(PAUSE_MINUTES).await.is_err());
+        assert!(anonymous.resume().await.is_err());
+    }
+}
@@ -0,0 +1,258 @@
+//! Optional bearer token for the web API, and which browser origins may
+//! use it
+//!
+//! With `OPENCLAW_HARNESS_API_TOKEN` set, every route that changes something
+//! (rules, proxy mode, alert pauses, purges, rollbacks, backups...) needs
+//! `Authorization: Bearer <token>`, and so do the routes serving what agents
+//! did in full (export, event content, approvals, purge audit) or derived
+//! from it (brain, campaign, behaviour and report routes, which can also
+//! spend LLM credits). Without a token the API is open to local clients, as
+//! it listens on loopback unless exposed (see `listen`).
+//!
+//! Web pages can read the rest of the API from anywhere, but only the
+//! dashboard's own origin, the UI dev server and those in
+//! `OPENCLAW_HARNESS_WEB_ORIGINS` may read the token routes or change
+//! anything: other origins get no CORS grant for it, and their changes are
+//! refused even when the browser sends them without asking.
+//! The dashboard's own origin counts only when it is addressed by a
+//! loopback name or the listen address, so a page whose DNS name is rebound
+//! to this machine doesn't pass as it.
+
+use super::AppState;
+use axum::extract::{Request, State};
+use axum::http::{header, request::Parts, HeaderMap, HeaderValue, Method, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use std::net::IpAddr;
+use std::sync::Arc;
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+/// Environment variable with the API token
+pub const TOKEN_ENV: &str = "OPENCLAW_HARNESS_API_TOKEN";
+/// Environment variable with more origins allowed to change settings from a
+/// browser, comma-separated (`https://harness.example.com`)
+pub const ORIGINS_ENV: &str = "OPENCLAW_HARNESS_WEB_ORIGINS";
+
+/// Origins of the UI dev server (`ui/`, `npm run dev`)
+const DEV_UI_ORIGINS: &[&str] = &["http://localhost:3000", "http://127.0.0.1:3000"];
+
+pub fn token_from_env() -> Option<String> {
+    std::env::var(TOKEN_ENV)
+        .ok()
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.trim().split_once(' ')?;
+    scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+}
+
+/// Compare without returning early, so timing doesn't reveal the token
+fn token_matches(expected: &str, given: &str) -> bool {
+    expected.len() == given.len()
+        && expected
+            .bytes()
+            .zip(given.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Whether `headers` carry the configured token, if there is one
+pub fn authorized(expected: Option<&str>, headers: &HeaderMap) -> bool {
+    match expected {
+        Some(expected) => bearer(headers).is_some_and(|given| token_matches(expected, given)),
+        None => true,
+    }
+}
+
+/// Origins trusted besides the server's own: the UI dev server and those
+/// in `OPENCLAW_HARNESS_WEB_ORIGINS`
+pub fn trusted_origins() -> Vec<String> {
+    let extra = std::env::var(ORIGINS_ENV).unwrap_or_default();
+    DEV_UI_ORIGINS
+        .iter()
+        .map(|o| o.to_string())
+        .chain(
+            extra
+                .split(',')
+                .map(|o| o.trim().trim_end_matches('/').to_string())
+                .filter(|o| !o.is_empty()),
+        )
+        .collect()
+}
+
+/// Whether `host` (a `Host` header) addresses this machine: a loopback
+/// name or IP, or the address the server listens on
+fn local_host(host: &str, listen: &str) -> bool {
+    if host.eq_ignore_ascii_case(listen) {
+        return true;
+    }
+    let name = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next().unwrap_or_default(),
+        None => host.rsplit_once(':').map_or(host, |(name, _)| name),
+    };
+    name.eq_ignore_ascii_case("localhost")
+        || name.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Whether a browser at `origin` may change things: it is the server's own
+/// origin (as addressed in a local `Host`, see [`local_host`]) or a trusted
+/// one
+fn origin_allowed(origin: &str, headers: &HeaderMap, listen: &str, trusted: &[String]) -> bool {
+    let own = headers
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .filter(|host| local_host(host, listen))
+        .is_some_and(|host| {
+            origin
+                .split_once("://")
+                .is_some_and(|(scheme, rest)| matches!(scheme, "http" | "https") && rest == host)
+        });
+    own || trusted.iter().any(|t| t == origin)
+}
+
+/// Methods that only read
+fn is_safe(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+/// Method a request uses, or asks to use when it is a CORS preflight
+fn requested_method(parts: &Parts) -> Method {
+    if parts.method == Method::OPTIONS {
+        if let Some(method) = parts
+            .headers
+            .get(header::ACCESS_CONTROL_REQUEST_METHOD)
+            .and_then(|m| Method::from_bytes(m.as_bytes()).ok())
+        {
+            return method;
+        }
+    }
+    parts.method.clone()
+}
+
+/// CORS for the API served at `listen`: any origin may read, only allowed
+/// ones may change anything
+pub fn cors(listen: &str) -> CorsLayer {
+    cors_layer(listen, true)
+}
+
+/// CORS for the token routes served at `listen`: only allowed origins may
+/// read them either
+pub fn private_cors(listen: &str) -> CorsLayer {
+    cors_layer(listen, false)
+}
+
+fn cors_layer(listen: &str, open_reads: bool) -> CorsLayer {
+    let trusted = trusted_origins();
+    let listen = listen.to_string();
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::predicate(
+            move |origin: &HeaderValue, parts: &Parts| {
+                (open_reads && is_safe(&requested_method(parts)))
+                    || origin
+                        .to_str()
+                        .is_ok_and(|o| origin_allowed(o, &parts.headers, &listen, &trusted))
+            },
+        ))
+        .allow_methods(Any)
+        .allow_headers(Any)
+}
+
+/// Layer on the whole API: requests that change something must come from
+/// an allowed origin (when sent by a browser) and carry the API token
+pub async fn guard_changes(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if is_safe(request.method()) {
+        return next.run(request).await;
+    }
+    if let Some(origin) = request.headers().get(header::ORIGIN) {
+        let allowed = origin.to_str().is_ok_and(|o| {
+            origin_allowed(o, request.headers(), &state.web.listen, &trusted_origins())
+        });
+        if !allowed {
+            return (StatusCode::FORBIDDEN, "origin not allowed").into_response();
+        }
+    }
+    require_token(State(state), request, next).await
+}
+
+/// Route layer rejecting requests without the API token
+pub async fn require_token(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if authorized(state.web.api_token.as_deref(), request.headers()) {
+        next.run(request).await
+    } else {
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bearer_token_check() {
+        let headers = |value: &str| {
+            let mut h = HeaderMap::new();
+            h.insert(header::AUTHORIZATION, value.parse().unwrap());
+            h
+        };
+        assert!(authorized(None, &HeaderMap::new()));
+        assert!(!authorized(Some("s3cret"), &HeaderMap::new()));
+        assert!(authorized(Some("s3cret"), &headers("Bearer s3cret")));
+        assert!(authorized(Some("s3cret"), &headers("bearer  s3cret ")));
+        assert!(!authorized(Some("s3cret"), &headers("Bearer s3cre")));
+        assert!(!authorized(Some("s3cret"), &headers("Basic s3cret")));
+        assert!(!authorized(Some("s3cret"), &headers("s3cret")));
+    }
+
+    #[test]
+    fn test_origin_check() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::HOST, "127.0.0.1:8380".parse().unwrap());
+        let listen = "127.0.0.1:8380";
+        let trusted = vec!["http://localhost:3000".to_string()];
+        let allowed =
+            |origin: &str, headers: &HeaderMap| origin_allowed(origin, headers, listen, &trusted);
+        assert!(allowed("http://127.0.0.1:8380", &headers));
+        assert!(allowed("http://localhost:3000", &headers));
+        assert!(!allowed("http://localhost:8380", &headers));
+        assert!(!allowed("https://evil.example", &headers));
+        assert!(!allowed("null", &headers));
+        assert!(!allowed("http://127.0.0.1:8380", &HeaderMap::new()));
+
+        // Other loopback names and the listen address are the server's own
+        let host = |value: &str| {
+            let mut h = HeaderMap::new();
+            h.insert(header::HOST, value.parse().unwrap());
+            h
+        };
+        assert!(allowed("http://localhost:8380", &host("localhost:8380")));
+        assert!(allowed("http://[::1]:8380", &host("[::1]:8380")));
+        assert!(origin_allowed(
+            "http://192.168.1.5:8380",
+            &host("192.168.1.5:8380"),
+            "192.168.1.5:8380",
+            &trusted
+        ));
+        // DNS rebinding: evil.example resolved to this machine, so the page
+        // and the API share its origin
+        assert!(!allowed("http://evil.example", &host("evil.example")));
+        assert!(!allowed(
+            "http://evil.example:8380",
+            &host("evil.example:8380")
+        ));
+    }
+}
@@ -0,0 +1,69 @@
+//! Bundled Control Center UI
+//!
+//! A small dependency-free dashboard compiled into the binary, served when
+//! no static directory is given. It uses the same REST and WebSocket API as
+//! the full UI in `ui/`.
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse, Response},
+};
+
+const INDEX_HTML: &str = include_str!("static/index.html");
+
+/// Embedded assets: (file name, content type, content)
+const ASSETS: &[(&str, &str, &str)] = &[
+    (
+        "app.js",
+        "application/javascript; charset=utf-8",
+        include_str!("static/app.js"),
+    ),
+    (
+        "style.css",
+        "text/css; charset=utf-8",
+        include_str!("static/style.css"),
+    ),
+];
+
+pub async fn index() -> Html<&'static str> {
+    Html(INDEX_HTML)
+}
+
+pub async fn asset(Path(file): Path<String>) -> Response {
+    match ASSETS.iter().find(|(name, _, _)| *name == file) {
+        Some((_, content_type, body)) => {
+            ([(header::CONTENT_TYPE, *content_type)], *body).into_response()
+        }
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::to_bytes;
+
+    #[tokio::test]
+    async fn test_index_and_assets() {
+        let Html(page) = index().await;
+        for (name, _, _) in ASSETS {
+            assert!(page.contains(&format!("/assets/{}", name)), "{}", name);
+        }
+
+        let response = asset(Path("app.js".to_string())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "application/javascript; charset=utf-8"
+        );
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        // The approvals page decides through the API, not just in the browser
+        assert!(String::from_utf8_lossy(&body).contains("/api/v1/approvals"));
+
+        for missing in ["nope.js", "../mod.rs", ""] {
+            let response = asset(Path(missing.to_string())).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
+}
@@ -2,7 +2,13 @@
 //!
 //! Provides REST API and WebSocket endpoints for the UI.
 
+pub mod auth;
+mod dashboard;
+pub mod openapi;
+pub mod report_email;
+pub mod report_insights;
 pub mod routes;
+pub mod sse;
 pub mod ws;
 
 #[allow(unused_imports)]
@@ -12,46 +18,133 @@ use axum::{
 };
 use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
-use tower_http::cors::{Any, CorsLayer};
 use tower_http::services::ServeDir;
-use tracing::info;
+use tracing::{info, warn};
 
+use crate::collectors::CollectorManager;
+use crate::db::DbPool;
+use crate::enforcer::alerter::Alerter;
+use crate::events::{Envelope, Event};
+use crate::listen;
 use crate::proxy::config::ProxyConfig;
+use crate::proxy::control::ProxyControl;
 use crate::rules::Rule;
-use crate::{AgentAction, AnalysisResult};
+use crate::{AgentAction, AnalysisResult, RiskLevel};
+
+/// Port of the web server unless configured otherwise
+pub const DEFAULT_PORT: u16 = 8380;
+
+/// Where the web server listens (see `listen`)
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct WebConfig {
+    /// `host:port`; loopback unless `expose` is set
+    #[serde(default = "default_listen")]
+    pub listen: String,
+    /// Allow a non-loopback `listen` address
+    #[serde(default)]
+    pub expose: bool,
+    /// Bearer token for changes and for the routes serving agent activity in
+    /// full or insights derived from it (see `auth`)
+    #[serde(default = "auth::token_from_env", skip_serializing)]
+    pub api_token: Option<String>,
+}
+
+fn default_listen() -> String {
+    format!("127.0.0.1:{}", DEFAULT_PORT)
+}
+
+impl WebConfig {
+    /// From `OPENCLAW_HARNESS_WEB_LISTEN` (a port or `host:port`), or
+    /// loopback on `OPENCLAW_HARNESS_WEB_PORT`, `OPENCLAW_HARNESS_EXPOSE` and
+    /// `OPENCLAW_HARNESS_API_TOKEN`
+    pub fn from_env() -> Self {
+        let listen = std::env::var("OPENCLAW_HARNESS_WEB_LISTEN")
+            .ok()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| listen::normalize(&l))
+            .or_else(|| {
+                std::env::var("OPENCLAW_HARNESS_WEB_PORT")
+                    .ok()
+                    .and_then(|p| p.parse::<u16>().ok())
+                    .map(|port| format!("127.0.0.1:{}", port))
+            })
+            .unwrap_or_else(default_listen);
+        Self {
+            listen,
+            expose: listen::expose_from_env(),
+            api_token: auth::token_from_env(),
+        }
+    }
+
+    /// Base URL for clients on this machine
+    pub fn local_url(&self) -> String {
+        format!("http://{}", listen::connect_addr(&self.listen))
+    }
+}
 
 /// Shared state for the web server
 pub struct AppState {
     /// Broadcast channel for real-time events
     pub event_tx: broadcast::Sender<WebEvent>,
-    /// Database path
+    /// Where the server listens
+    pub web: WebConfig,
+    /// Database path, for files kept next to the database (blobs)
     pub db_path: String,
+    /// Connections to the database, shared with the rest of the daemon
+    pub db: DbPool,
     /// Mutable rules list
     pub rules: RwLock<Vec<Rule>>,
     /// Proxy configuration
     pub proxy_config: RwLock<ProxyConfig>,
+    /// Mode and rules of the proxy, shared with it when it runs in this process
+    pub proxy: Arc<ProxyControl>,
+    /// Collectors running in this process
+    pub collectors: Arc<CollectorManager>,
+    /// The daemon's alerter, if any channel is configured (see
+    /// `routes::pause_alerts`)
+    pub alerter: Option<Arc<Alerter>>,
     /// Server start time
     pub started_at: chrono::DateTime<chrono::Utc>,
-    /// Event counters
-    pub counters: RwLock<EventCounters>,
+    /// Recently computed statistics (see `routes::get_stats`)
+    pub stats_cache: std::sync::Mutex<Option<routes::StatsSnapshot>>,
 }
 
-/// Runtime event counters
-#[derive(Debug, Clone, Default, serde::Serialize)]
-pub struct EventCounters {
-    pub total_requests: u64,
-    pub blocked_count: u64,
-    pub warning_count: u64,
-    pub passed_count: u64,
-    pub by_provider: std::collections::HashMap<String, u64>,
+pub use crate::db::EventCounters;
+
+/// How prominently a client should surface an analysis: `low` ones are
+/// just listed, `high` ones are worth a desktop notification, `urgent`
+/// ones a notification with a sound
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    High,
+    Urgent,
 }
 
-/// Events sent over WebSocket
+impl From<RiskLevel> for Priority {
+    fn from(risk: RiskLevel) -> Self {
+        match risk {
+            RiskLevel::Info => Priority::Low,
+            RiskLevel::Warning => Priority::High,
+            RiskLevel::Critical => Priority::Urgent,
+        }
+    }
+}
+
+/// Events sent over WebSocket.
+///
+/// Live actions and analyses carry `seq`, the event bus sequence number:
+/// it increases with every event, so clients can render in order and, on
+/// an unfiltered feed, spot gaps. Replayed events have none and come
+/// before any live one.
 #[derive(Clone, Debug, serde::Serialize)]
 #[serde(tag = "type")]
 pub enum WebEvent {
     #[serde(rename = "action")]
     Action {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<u64>,
         id: String,
         timestamp: String,
         agent: String,
@@ -61,9 +154,16 @@ pub enum WebEvent {
     },
     #[serde(rename = "analysis")]
     Analysis {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        seq: Option<u64>,
         action_id: String,
         risk_level: String,
+        /// `risk_level` as stored: `info`, `warning` or `critical`
+        severity: String,
+        priority: Priority,
         matched_rules: Vec<String>,
+        /// Categories of the matched rules, each once
+        categories: Vec<String>,
         recommendation: String,
         explanation: String,
     },
@@ -77,6 +177,7 @@ pub enum WebEvent {
 impl From<&AgentAction> for WebEvent {
     fn from(action: &AgentAction) -> Self {
         WebEvent::Action {
+            seq: None,
             id: action.id.clone(),
             timestamp: action.timestamp.to_rfc3339(),
             agent: action.agent.to_string(),
@@ -87,100 +188,240 @@ impl From<&AgentAction> for WebEvent {
     }
 }
 
-impl From<&AnalysisResult> for WebEvent {
-    fn from(result: &AnalysisResult) -> Self {
+impl WebEvent {
+    /// An analysis event; `rules` give the matched rules' categories
+    pub fn analysis(
+        action_id: String,
+        risk: RiskLevel,
+        matched_rules: Vec<String>,
+        recommendation: String,
+        explanation: String,
+        rules: &[Rule],
+    ) -> Self {
+        let mut categories: Vec<String> = vec![];
+        for name in &matched_rules {
+            let category = crate::rules::category_of(name, rules);
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
         WebEvent::Analysis {
-            action_id: result.action.id.clone(),
-            risk_level: result.risk_level.to_string(),
-            matched_rules: result.matched_rules.clone(),
-            recommendation: format!("{:?}", result.recommendation),
-            explanation: result.explanation.clone(),
+            seq: None,
+            action_id,
+            risk_level: risk.to_string(),
+            severity: risk.as_str().to_string(),
+            priority: Priority::from(risk),
+            matched_rules,
+            categories,
+            recommendation,
+            explanation,
         }
     }
-}
 
-/// Start the web server
-pub async fn start_server(
-    port: u16,
-    event_tx: broadcast::Sender<WebEvent>,
-    db_path: String,
-    static_dir: Option<String>,
-) -> anyhow::Result<()> {
-    let mut rules = crate::rules::default_rules();
-    for r in &mut rules {
-        r.compile()?;
+    /// The event for an analysis result
+    pub fn from_result(result: &AnalysisResult, rules: &[Rule]) -> Self {
+        Self::analysis(
+            result.action.id.clone(),
+            result.risk_level,
+            result.matched_rules.clone(),
+            format!("{:?}", result.recommendation),
+            result.explanation.clone(),
+            rules,
+        )
     }
 
-    let state = Arc::new(AppState {
-        event_tx,
-        db_path,
-        rules: RwLock::new(rules),
-        proxy_config: RwLock::new(ProxyConfig::default()),
-        started_at: chrono::Utc::now(),
-        counters: RwLock::new(EventCounters::default()),
-    });
+    /// The event for a bus event, stamped with its sequence number
+    pub fn from_envelope(env: &Envelope, rules: &[Rule]) -> Self {
+        let event = match &env.event {
+            Event::Action(action) => WebEvent::from(action),
+            Event::Analysis(result) => WebEvent::from_result(result, rules),
+        };
+        event.with_seq(env.seq)
+    }
 
-    // Build routes
-    let mut app = Router::new()
-        // API routes
-        .route("/api/status", get(routes::get_status))
-        .route("/api/stats", get(routes::get_stats))
-        .route("/api/stats/by-provider", get(routes::get_stats_by_provider))
-        .route("/api/events", get(routes::get_events))
-        .route("/api/events/recent", get(routes::get_recent_events))
-        .route("/api/events/:id", get(routes::get_event))
+    pub fn seq(&self) -> Option<u64> {
+        match self {
+            WebEvent::Action { seq, .. } | WebEvent::Analysis { seq, .. } => *seq,
+            WebEvent::Status { .. } => None,
+        }
+    }
+
+    /// The event stamped with its bus sequence number
+    pub fn with_seq(mut self, n: u64) -> Self {
+        match &mut self {
+            WebEvent::Action { seq, .. } | WebEvent::Analysis { seq, .. } => *seq = Some(n),
+            WebEvent::Status { .. } => {}
+        }
+        self
+    }
+}
+
+/// REST API routes, mounted under both `/api/v1` and the legacy `/api`
+fn api_routes(state: &Arc<AppState>) -> Router<Arc<AppState>> {
+    // What agents did in full, insights derived from it, and LLM calls:
+    // behind the API token when one is set, and readable only by allowed
+    // origins
+    let protected = Router::new()
+        .route("/events/:id/content", get(routes::get_event_content))
+        .route("/export", get(routes::export_actions))
+        .route("/privacy/audit", get(routes::get_purge_audit))
+        .route("/approvals", get(routes::get_approvals))
+        .route("/approvals/:id/approve", post(routes::approve_request))
+        .route("/approvals/:id/deny", post(routes::deny_request))
         .route(
-            "/api/rules",
-            get(routes::get_rules).post(routes::create_rule),
+            "/campaigns/adaptive/generate",
+            post(routes::generate_adaptive_campaign),
         )
         .route(
-            "/api/rules/:name",
-            put(routes::update_rule).delete(routes::delete_rule),
+            "/behaviours",
+            get(routes::get_behaviours)
+                .post(routes::ingest_behaviours)
+                .delete(routes::delete_behaviours),
         )
-        .route("/api/rules/test", post(routes::test_rule))
-        .route("/api/proxy/status", get(routes::get_proxy_status))
-        .route("/api/proxy/config", put(routes::update_proxy_config))
-        .route("/api/providers", get(routes::get_providers))
+        .route("/brain/ontology/build", post(routes::build_ontology_v1))
+        .route("/brain/ontology/v2/build", post(routes::build_ontology_v2))
+        .route("/brain/query", post(routes::query_brain_v2))
+        .route("/brain/graph", get(routes::get_brain_graph_v2))
+        .route("/brain/search", post(routes::search_brain_v2))
+        .route("/reports/weekly", get(routes::get_weekly_report))
         .route(
-            "/api/campaigns/adaptive/generate",
-            post(routes::generate_adaptive_campaign),
+            "/reports/weekly/generate",
+            post(routes::generate_weekly_report),
         )
-        .route("/api/brain/ontology/build", post(routes::build_ontology_v1))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_token,
+        ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::guard_changes,
+        ))
+        .layer(auth::private_cors(&state.web.listen));
+
+    Router::new()
+        .route("/status", get(routes::get_status))
+        .route("/collectors", get(routes::get_collectors))
+        .route("/collectors/queue", get(routes::get_collector_queue))
+        .route("/workspaces", get(routes::list_workspaces))
+        .route("/stats", get(routes::get_stats))
+        .route("/stats/by-provider", get(routes::get_stats_by_provider))
+        .route("/events", get(routes::get_events))
+        .route("/events/recent", get(routes::get_recent_events))
+        .route("/events/stream", get(sse::sse_handler))
+        .route("/events/:id", get(routes::get_event))
+        .route("/privacy/purge", post(routes::purge_data))
+        .route("/usage", get(routes::get_usage))
+        .route("/actions/:id/rollback", post(routes::rollback_action))
+        .route("/backups", post(routes::create_backup))
+        .route("/rules", get(routes::get_rules).post(routes::create_rule))
+        .route(
+            "/rules/:name",
+            put(routes::update_rule).delete(routes::delete_rule),
+        )
+        .route("/rules/:name/history", get(routes::get_rule_history))
+        .route("/rules/:name/rollback", post(routes::rollback_rule))
+        .route("/rules/test", post(routes::test_rule))
         .route(
-            "/api/brain/ontology/v2/build",
-            post(routes::build_ontology_v2),
+            "/rules/from-template",
+            post(routes::create_rule_from_template),
         )
-        .route("/api/brain/query", post(routes::query_brain_v2))
-        .route("/api/brain/graph", get(routes::get_brain_graph_v2))
-        .route("/api/brain/search", post(routes::search_brain_v2))
+        .route("/templates", get(routes::get_templates))
+        .route("/simulate", post(routes::simulate_action))
+        .route("/proxy/status", get(routes::get_proxy_status))
+        .route("/proxy/config", put(routes::update_proxy_config))
+        .route("/providers", get(routes::get_providers))
         .route(
-            "/api/alerts/config",
+            "/alerts/config",
             get(routes::get_alert_config).put(routes::update_alert_config),
         )
-        .route("/api/reports/weekly", get(routes::get_weekly_report))
+        .route("/alerts/test", post(routes::test_alerts))
         .route(
-            "/api/reports/weekly/generate",
-            post(routes::generate_weekly_report),
+            "/alerts/pause",
+            get(routes::get_alert_pause)
+                .post(routes::pause_alerts)
+                .delete(routes::resume_alerts),
         )
+        .route("/openapi.json", get(openapi_json))
+        // Anything that changes state needs the token, whatever the route
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::guard_changes,
+        ))
+        .layer(auth::cors(&state.web.listen))
+        .merge(protected)
+}
+
+/// API and WebSocket routes with their state, without the UI
+pub fn router(state: Arc<AppState>) -> Router {
+    let api = api_routes(&state);
+    Router::new()
+        .nest(openapi::API_PREFIX, api.clone())
+        // Unversioned alias kept for existing clients
+        .nest("/api", api)
         // WebSocket
         .route("/ws/events", get(ws::ws_handler))
         .with_state(state)
-        .layer(
-            CorsLayer::new()
-                .allow_origin(Any)
-                .allow_methods(Any)
-                .allow_headers(Any),
-        );
-
-    // Serve static files if directory provided
+}
+
+async fn openapi_json() -> axum::Json<serde_json::Value> {
+    axum::Json(openapi::spec())
+}
+
+/// Start the web server; rule and mode changes made through it go to `proxy`,
+/// alert pauses to `alerter`
+pub async fn start_server(
+    web: WebConfig,
+    event_tx: broadcast::Sender<WebEvent>,
+    db: DbPool,
+    static_dir: Option<String>,
+    proxy: Arc<ProxyControl>,
+    collectors: Arc<CollectorManager>,
+    alerter: Option<Arc<Alerter>>,
+) -> anyhow::Result<()> {
+    let proxy_config = ProxyConfig {
+        mode: proxy.mode(),
+        ..crate::Config::default().proxy.unwrap_or_default()
+    };
+
+    listen::check(&web.listen, web.expose)?;
+    let state = Arc::new(AppState {
+        event_tx,
+        web: web.clone(),
+        db_path: db.path().to_string_lossy().to_string(),
+        db,
+        rules: RwLock::new(proxy.rules().to_vec()),
+        proxy_config: RwLock::new(proxy_config),
+        proxy,
+        collectors,
+        alerter,
+        started_at: chrono::Utc::now(),
+        stats_cache: std::sync::Mutex::new(None),
+    });
+
+    // Build routes
+    let mut app = router(state);
+
+    // Serve static files if directory provided, otherwise the bundled UI
     if let Some(dir) = static_dir {
         app = app.fallback_service(ServeDir::new(dir));
+    } else {
+        app = app
+            .route("/", get(dashboard::index))
+            .route("/assets/:file", get(dashboard::asset));
     }
 
-    let addr = format!("0.0.0.0:{}", port);
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    let listener = tokio::net::TcpListener::bind(&web.listen).await?;
 
-    info!("🌐 Web server starting on http://{}", addr);
+    info!("🌐 Web server starting on http://{}", web.listen);
+    if !listen::is_loopback(&web.listen) {
+        warn!("⚠️  Web API is {}", listen::exposure_note(&web.listen));
+        if web.api_token.is_none() {
+            warn!(
+                "⚠️  Set {} to require a token for changes and for brain, campaign and report routes",
+                auth::TOKEN_ENV
+            );
+        }
+    }
 
     axum::serve(listener, app).await?;
 
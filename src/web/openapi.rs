@@ -0,0 +1,1318 @@
+//! OpenAPI 3 description of the REST API, served at `/api/v1/openapi.json`
+//!
+//! Schemas are declared next to the route table below with a compact field
+//! notation, mirroring the request/response structs in `routes`:
+//!
+//! - `string`, `integer`, `number`, `boolean`, `object` (free-form JSON)
+//! - `@Name` references another schema, `[T]` is an array of `T`
+//! - a trailing `?` marks an optional (nullable) field
+//!
+//! `responses_match_the_openapi_schemas` in the web route integration tests
+//! checks real responses against these, so a field added to a struct but
+//! not here fails the tests.
+
+use serde_json::{json, Map, Value};
+
+/// Current API version prefix
+pub const API_PREFIX: &str = "/api/v1";
+
+fn type_schema(ty: &str) -> Value {
+    if let Some(inner) = ty.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        return json!({ "type": "array", "items": type_schema(inner) });
+    }
+    if let Some(name) = ty.strip_prefix('@') {
+        return json!({ "$ref": format!("#/components/schemas/{}", name) });
+    }
+    match ty {
+        "object" => json!({ "type": "object", "additionalProperties": true }),
+        other => json!({ "type": other }),
+    }
+}
+
+fn object(fields: &[(&str, &str)]) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for (name, ty) in fields {
+        let (ty, optional) = match ty.strip_suffix('?') {
+            Some(t) => (t, true),
+            None => (*ty, false),
+        };
+        let mut schema = type_schema(ty);
+        if optional {
+            schema["nullable"] = json!(true);
+        } else {
+            required.push(*name);
+        }
+        properties.insert(name.to_string(), schema);
+    }
+    json!({ "type": "object", "properties": properties, "required": required })
+}
+
+fn schemas() -> Map<String, Value> {
+    let defs: &[(&str, &[(&str, &str)])] = &[
+        (
+            "CollectorStatus",
+            &[
+                ("name", "string"),
+                ("state", "string"),
+                ("started_at", "string?"),
+                ("last_event", "string?"),
+                ("idle_secs", "integer?"),
+                ("events", "integer"),
+                ("restarts", "integer"),
+                ("last_error", "string?"),
+                ("agent_running", "boolean?"),
+                ("stale", "boolean"),
+            ],
+        ),
+        (
+            "WorkspaceSummary",
+            &[
+                ("id", "string"),
+                ("roots", "[string]"),
+                ("actions", "integer"),
+                ("first_seen", "string?"),
+                ("last_seen", "string?"),
+            ],
+        ),
+        (
+            "WorkspacesResponse",
+            &[("workspaces", "[@WorkspaceSummary]")],
+        ),
+        (
+            "QueueMetrics",
+            &[
+                ("capacity", "integer"),
+                ("queued", "integer"),
+                ("dropped_info", "integer"),
+                ("spilled", "integer"),
+                ("spill_pending", "integer"),
+                ("blocked", "integer"),
+            ],
+        ),
+        (
+            "StatusResponse",
+            &[
+                ("running", "boolean"),
+                ("version", "string"),
+                ("uptime_seconds", "integer"),
+                ("monitoring", "[string]"),
+                ("database", "@DbHealth?"),
+                ("listen", "string"),
+                ("exposed", "boolean"),
+                ("exposure", "string"),
+                ("diagnostics", "@DiagnosticCounts?"),
+            ],
+        ),
+        (
+            "DiagnosticCounts",
+            &[
+                ("total", "integer"),
+                ("panic", "integer"),
+                ("collector", "integer"),
+                ("alert", "integer"),
+                ("proxy", "integer"),
+                ("last_seen", "string?"),
+            ],
+        ),
+        (
+            "DbHealth",
+            &[
+                ("size_bytes", "integer"),
+                ("used_bytes", "integer"),
+                ("wal_bytes", "integer"),
+                ("max_size_bytes", "integer?"),
+                ("actions", "integer"),
+                ("info_events_pruned", "integer"),
+                ("last_maintenance", "string?"),
+            ],
+        ),
+        (
+            "StatsResponse",
+            &[
+                ("total_events", "integer"),
+                ("critical_count", "integer"),
+                ("warning_count", "integer"),
+                ("info_count", "integer"),
+                ("today_events", "integer"),
+                ("rules_count", "integer"),
+                ("blocked_count", "integer"),
+                ("today_blocked_count", "integer"),
+                ("passed_count", "integer"),
+                ("proxy_requests", "integer"),
+                ("action_id_conflicts", "integer"),
+            ],
+        ),
+        (
+            "ProviderStats",
+            &[("provider", "string"), ("request_count", "integer")],
+        ),
+        (
+            "EventResponse",
+            &[
+                ("id", "string"),
+                ("timestamp", "string"),
+                ("agent", "string"),
+                ("action_type", "string"),
+                ("content", "string"),
+                ("target", "string?"),
+                ("risk_level", "string?"),
+                ("matched_rules", "[string]"),
+                ("provider", "string?"),
+                ("status", "string?"),
+            ],
+        ),
+        (
+            "EventsResponse",
+            &[("events", "[@EventResponse]"), ("total", "integer")],
+        ),
+        (
+            "PurgeRequest",
+            &[
+                ("session_id", "string?"),
+                ("path_prefix", "string?"),
+                ("from", "string?"),
+                ("to", "string?"),
+                ("mode", "string?"),
+                ("dry_run", "boolean?"),
+            ],
+        ),
+        (
+            "PurgeSummary",
+            &[
+                ("mode", "string"),
+                ("dry_run", "boolean"),
+                ("actions", "integer"),
+                ("analyses", "integer"),
+                ("ontology_rebuilt", "[string]"),
+                ("reports", "integer"),
+                ("notes", "integer"),
+                ("audit_id", "integer?"),
+            ],
+        ),
+        (
+            "PurgeAuditEntry",
+            &[
+                ("id", "integer"),
+                ("timestamp", "string"),
+                ("mode", "string"),
+                ("scope", "string"),
+                ("summary", "string"),
+                ("requested_by", "string"),
+            ],
+        ),
+        (
+            "UsageSummary",
+            &[
+                ("key", "string"),
+                ("requests", "integer"),
+                ("input_tokens", "integer"),
+                ("output_tokens", "integer"),
+                ("cost_usd", "number"),
+            ],
+        ),
+        (
+            "EgressSummary",
+            &[
+                ("key", "string"),
+                ("requests", "integer"),
+                ("bytes", "integer"),
+            ],
+        ),
+        (
+            "UsageResponse",
+            &[
+                ("since", "string"),
+                ("group_by", "string"),
+                ("rows", "[@UsageSummary]"),
+                ("total_requests", "integer"),
+                ("total_input_tokens", "integer"),
+                ("total_output_tokens", "integer"),
+                ("total_cost_usd", "number"),
+                ("today_cost_usd", "number"),
+                ("daily_budget_usd", "number?"),
+                ("session_budget_usd", "number?"),
+                ("egress", "[@EgressSummary]"),
+                ("total_egress_bytes", "integer"),
+            ],
+        ),
+        (
+            "RollbackOutcome",
+            &[
+                ("path", "string"),
+                ("method", "string"),
+                ("restored", "boolean"),
+                ("saved_diff", "string?"),
+                ("detail", "string"),
+            ],
+        ),
+        ("BackupRequest", &[("path", "string"), ("rule", "string?")]),
+        (
+            "BackupResponse",
+            &[("ok", "boolean"), ("backup", "string?")],
+        ),
+        (
+            "RuleResponse",
+            &[
+                ("name", "string"),
+                ("description", "string"),
+                ("match_type", "string"),
+                ("pattern", "string"),
+                ("keyword", "@KeywordMatch?"),
+                ("template", "string?"),
+                ("params", "@TemplateParams?"),
+                ("risk_level", "string"),
+                ("action", "string"),
+                ("enabled", "boolean"),
+                ("is_preset", "boolean"),
+                ("category", "string"),
+                ("tags", "[string]"),
+                ("priority", "integer"),
+                ("stats", "@RuleStats"),
+            ],
+        ),
+        (
+            "RuleStats",
+            &[
+                ("total", "integer"),
+                ("blocked", "integer"),
+                ("monitored", "integer"),
+                ("last_matched", "string?"),
+                ("by_day", "object"),
+            ],
+        ),
+        (
+            "KeywordMatch",
+            &[
+                ("contains", "[string]?"),
+                ("starts_with", "[string]?"),
+                ("ends_with", "[string]?"),
+                ("glob", "[string]?"),
+                ("any_of", "[string]?"),
+            ],
+        ),
+        (
+            "TemplateParams",
+            &[
+                ("path", "string?"),
+                ("paths", "[string]?"),
+                ("operations", "[string]?"),
+                ("commands", "[string]?"),
+                ("patterns", "[string]?"),
+                ("extra", "object?"),
+            ],
+        ),
+        (
+            "CreateRuleRequest",
+            &[
+                ("name", "string"),
+                ("description", "string?"),
+                ("match_type", "string?"),
+                ("pattern", "string?"),
+                ("keyword", "@KeywordMatch?"),
+                ("template", "string?"),
+                ("params", "@TemplateParams?"),
+                ("risk_level", "string"),
+                ("action", "string"),
+                ("enabled", "boolean?"),
+                ("category", "string?"),
+                ("tags", "[string]?"),
+                ("priority", "integer?"),
+            ],
+        ),
+        (
+            "UpdateRuleRequest",
+            &[
+                ("description", "string?"),
+                ("match_type", "string?"),
+                ("pattern", "string?"),
+                ("keyword", "@KeywordMatch?"),
+                ("template", "string?"),
+                ("params", "@TemplateParams?"),
+                ("risk_level", "string?"),
+                ("action", "string?"),
+                ("enabled", "boolean?"),
+                ("category", "string?"),
+                ("tags", "[string]?"),
+                ("priority", "integer?"),
+            ],
+        ),
+        (
+            "RuleVersion",
+            &[
+                ("version", "integer"),
+                ("changed_at", "string"),
+                ("changed_by", "string"),
+                ("change", "string"),
+                ("diff", "[string]"),
+                ("rule", "object?"),
+            ],
+        ),
+        ("RuleRollbackRequest", &[("version", "integer")]),
+        (
+            "TemplateResponse",
+            &[
+                ("name", "string"),
+                ("description", "string"),
+                ("category", "string"),
+                ("params", "[@TemplateParam]"),
+                ("example", "@TemplateExample"),
+            ],
+        ),
+        (
+            "TemplateParam",
+            &[
+                ("name", "string"),
+                ("kind", "string"),
+                ("description", "string"),
+                ("aliases", "[string]"),
+                ("allowed", "[string]"),
+                ("example", "[string]"),
+                ("required", "boolean"),
+            ],
+        ),
+        (
+            "TemplateExample",
+            &[
+                ("params", "@TemplateParams"),
+                ("description", "string"),
+                ("applies_to", "[string]"),
+            ],
+        ),
+        (
+            "FromTemplateRequest",
+            &[
+                ("name", "string"),
+                ("template", "string"),
+                ("params", "object?"),
+                ("description", "string?"),
+                ("risk_level", "string?"),
+                ("action", "string?"),
+                ("enabled", "boolean?"),
+                ("category", "string?"),
+                ("tags", "[string]?"),
+            ],
+        ),
+        (
+            "TestRuleRequest",
+            &[("pattern", "string"), ("input", "string")],
+        ),
+        (
+            "TestRuleResponse",
+            &[("matches", "boolean"), ("matched_text", "string?")],
+        ),
+        (
+            "SimulateRequest",
+            &[
+                ("action_type", "string"),
+                ("content", "string"),
+                ("target", "string?"),
+                ("agent", "string?"),
+                ("session_id", "string?"),
+                ("tool_name", "string?"),
+            ],
+        ),
+        (
+            "SimulatedProxy",
+            &[
+                ("behavior", "string"),
+                ("rule", "string?"),
+                ("message", "string?"),
+            ],
+        ),
+        (
+            "AnalysisResult",
+            &[
+                ("action", "object"),
+                ("matched_rules", "[string]"),
+                ("risk_level", "string"),
+                ("recommendation", "string"),
+                ("explanation", "string"),
+            ],
+        ),
+        (
+            "SimulateResponse",
+            &[
+                ("analysis", "@AnalysisResult"),
+                ("monitor", "@SimulatedProxy"),
+                ("enforce", "@SimulatedProxy"),
+                ("current_mode", "string"),
+            ],
+        ),
+        (
+            "ProxyStatusResponse",
+            &[
+                ("running", "boolean"),
+                ("mode", "string"),
+                ("listen", "string"),
+                ("target", "string"),
+                ("uptime_seconds", "integer"),
+            ],
+        ),
+        (
+            "UpdateProxyConfigRequest",
+            &[("mode", "string?"), ("enabled", "boolean?")],
+        ),
+        (
+            "ProviderResponse",
+            &[
+                ("name", "string"),
+                ("enabled", "boolean?"),
+                ("target_url", "string"),
+                ("health", "@UpstreamHealth?"),
+                ("fallbacks", "[@UpstreamHealth]"),
+            ],
+        ),
+        (
+            "UpstreamHealth",
+            &[
+                ("target", "string"),
+                ("role", "string"),
+                ("healthy", "boolean"),
+                ("active", "boolean"),
+                ("consecutive_failures", "integer"),
+                ("latency_ms", "integer?"),
+                ("last_error", "string?"),
+                ("last_checked", "string?"),
+            ],
+        ),
+        (
+            "AlertConfigResponse",
+            &[
+                ("telegram_enabled", "boolean"),
+                ("telegram_bot_token", "string?"),
+                ("telegram_chat_id", "string?"),
+                ("slack_enabled", "boolean"),
+                ("slack_webhook", "string?"),
+                ("discord_enabled", "boolean"),
+                ("discord_webhook", "string?"),
+                ("desktop_enabled", "boolean"),
+                ("notify_on_critical", "boolean"),
+                ("notify_on_warning", "boolean"),
+                ("notify_on_info", "boolean"),
+            ],
+        ),
+        ("AlertTestRequest", &[("channel", "string?")]),
+        ("AlertPauseRequest", &[("minutes", "integer?")]),
+        ("AlertPauseResponse", &[("paused_until", "string?")]),
+        (
+            "Approval",
+            &[
+                ("id", "string"),
+                ("fingerprint", "string"),
+                ("summary", "string"),
+                ("requested_at", "string"),
+                ("status", "string"),
+                ("decided_by", "string?"),
+                ("decided_at", "string?"),
+            ],
+        ),
+        ("AlertTestResponse", &[("results", "[@ChannelTest]")]),
+        (
+            "ChannelTest",
+            &[
+                ("channel", "string"),
+                ("ok", "boolean"),
+                ("latency_ms", "integer"),
+                ("error", "string?"),
+            ],
+        ),
+        (
+            "AdaptiveCampaignRequest",
+            &[
+                ("user_id", "string"),
+                ("max_points_per_mission", "integer"),
+                ("min_completion_probability", "number?"),
+                ("max_expected_hours", "number?"),
+                ("planner", "string?"),
+                ("timeout_secs", "integer?"),
+            ],
+        ),
+        (
+            "CampaignErrorResponse",
+            &[
+                ("ok", "boolean"),
+                ("error", "string"),
+                ("message", "string"),
+            ],
+        ),
+        (
+            "NewBehaviour",
+            &[
+                ("user_id", "string"),
+                ("event_type", "string"),
+                ("success", "boolean"),
+                ("duration_minutes", "integer?"),
+                ("created_at", "string?"),
+            ],
+        ),
+        ("IngestBehavioursRequest", &[("records", "[@NewBehaviour]")]),
+        (
+            "IngestBehavioursResponse",
+            &[("ok", "boolean"), ("inserted", "integer")],
+        ),
+        (
+            "BehaviourRecord",
+            &[
+                ("user_id", "string"),
+                ("event_type", "string"),
+                ("success", "boolean"),
+                ("duration_minutes", "integer"),
+                ("created_at", "string"),
+            ],
+        ),
+        (
+            "UserBehaviourStats",
+            &[
+                ("user_id", "string"),
+                ("total_events", "integer"),
+                ("success_count", "integer"),
+                ("success_rate", "number"),
+                ("avg_duration_minutes", "number"),
+                ("unique_event_types", "integer"),
+            ],
+        ),
+        (
+            "BehavioursResponse",
+            &[
+                ("stats", "@UserBehaviourStats"),
+                ("records", "[@BehaviourRecord]"),
+            ],
+        ),
+        (
+            "DeleteBehavioursResponse",
+            &[("ok", "boolean"), ("deleted", "integer")],
+        ),
+        (
+            "MissionRule",
+            &[
+                ("mission_type", "string"),
+                ("required_count", "integer"),
+                ("event_type", "string"),
+                ("window_hours", "integer"),
+            ],
+        ),
+        (
+            "MissionPlan",
+            &[
+                ("user_id", "string"),
+                ("title", "string"),
+                ("description", "string"),
+                ("rule", "@MissionRule"),
+                ("difficulty_score", "number"),
+                ("expected_completion_probability", "number"),
+                ("expected_hours", "number"),
+                ("recommended_points", "integer"),
+                ("final_points", "integer"),
+                ("analysis", "string"),
+                ("clamped", "boolean"),
+            ],
+        ),
+        (
+            "AdaptiveCampaignResponse",
+            &[
+                ("ok", "boolean"),
+                ("planner", "string"),
+                ("mission", "@MissionPlan"),
+            ],
+        ),
+        (
+            "OntologyBuildSummary",
+            &[("nodes", "integer"), ("edges", "integer")],
+        ),
+        (
+            "BrainInsights",
+            &[
+                ("repeated_patterns", "integer"),
+                ("decisions_detected", "integer"),
+                ("bottlenecks_detected", "integer"),
+                ("skills_inferred", "integer"),
+            ],
+        ),
+        (
+            "BuildOntologyResponse",
+            &[("ok", "boolean"), ("summary", "@OntologyBuildSummary")],
+        ),
+        (
+            "BuildOntologyV2Response",
+            &[
+                ("ok", "boolean"),
+                ("summary", "@OntologyBuildSummary"),
+                ("insights", "@BrainInsights"),
+            ],
+        ),
+        (
+            "BrainQueryRequest",
+            &[
+                ("query_type", "string"),
+                ("limit", "integer?"),
+                ("workspace_id", "string?"),
+            ],
+        ),
+        (
+            "BrainQueryResponse",
+            &[
+                ("ok", "boolean"),
+                ("query_type", "string"),
+                ("results", "[object]"),
+                ("insights", "object?"),
+            ],
+        ),
+        (
+            "BrainGraphResponse",
+            &[
+                ("ok", "boolean"),
+                ("nodes", "[object]"),
+                ("edges", "[object]"),
+                ("stats", "object"),
+            ],
+        ),
+        (
+            "BrainSearchRequest",
+            &[
+                ("keyword", "string"),
+                ("kinds", "[string]?"),
+                ("limit", "integer?"),
+                ("workspace_id", "string?"),
+            ],
+        ),
+        (
+            "BrainSearchResponse",
+            &[
+                ("ok", "boolean"),
+                ("keyword", "string"),
+                ("results", "[object]"),
+            ],
+        ),
+        (
+            "GenerateWeeklyReportRequest",
+            &[
+                ("workspace_id", "string?"),
+                ("week", "string?"),
+                ("timezone", "string?"),
+                ("force_regenerate", "boolean?"),
+            ],
+        ),
+        (
+            "WeeklyProjectActivity",
+            &[("project_id", "string"), ("events", "integer")],
+        ),
+        (
+            "WeeklyToolCount",
+            &[("tool", "string"), ("count", "integer")],
+        ),
+        (
+            "WeeklyPattern",
+            &[
+                ("name", "string"),
+                ("count", "integer"),
+                ("suggestion", "string"),
+            ],
+        ),
+        (
+            "WeeklyRisk",
+            &[
+                ("critical", "integer"),
+                ("warning", "integer"),
+                ("info", "integer"),
+            ],
+        ),
+        (
+            "WeeklyActivity",
+            &[
+                ("total_events", "integer"),
+                ("projects", "[@WeeklyProjectActivity]"),
+                ("top_tools", "[@WeeklyToolCount]"),
+            ],
+        ),
+        (
+            "WeeklyReportResponse",
+            &[
+                ("report_id", "string"),
+                ("workspace_id", "string"),
+                ("week_start", "string"),
+                ("week_end", "string"),
+                ("headline", "string"),
+                ("activity", "@WeeklyActivity"),
+                ("risk", "@WeeklyRisk"),
+                ("patterns", "[@WeeklyPattern]"),
+                ("next_actions", "[string]"),
+                ("markdown", "string"),
+                ("created_at", "string"),
+            ],
+        ),
+        (
+            "WebEvent",
+            &[
+                ("type", "string"),
+                ("seq", "integer?"),
+                ("id", "string?"),
+                ("timestamp", "string?"),
+                ("agent", "string?"),
+                ("action_type", "string?"),
+                ("content", "string?"),
+                ("target", "string?"),
+                ("action_id", "string?"),
+                ("risk_level", "string?"),
+                ("severity", "string?"),
+                ("priority", "string?"),
+                ("matched_rules", "[string]?"),
+                ("categories", "[string]?"),
+                ("recommendation", "string?"),
+                ("explanation", "string?"),
+                ("connected", "boolean?"),
+                ("monitoring", "[string]?"),
+            ],
+        ),
+    ];
+
+    defs.iter()
+        .map(|(name, fields)| (name.to_string(), object(fields)))
+        .collect()
+}
+
+/// One documented operation
+struct Operation {
+    method: &'static str,
+    /// Path below the version prefix, with `{param}` placeholders
+    path: &'static str,
+    summary: &'static str,
+    /// Query parameters (name, type)
+    query: &'static [(&'static str, &'static str)],
+    /// Request body type
+    body: Option<&'static str>,
+    /// Response type and content type; `None` for an empty success response
+    response: Option<(&'static str, &'static str)>,
+}
+
+const JSON: &str = "application/json";
+
+const fn op(
+    method: &'static str,
+    path: &'static str,
+    summary: &'static str,
+    body: Option<&'static str>,
+    response: Option<&'static str>,
+) -> Operation {
+    Operation {
+        method,
+        path,
+        summary,
+        query: &[],
+        body,
+        response: match response {
+            Some(r) => Some((r, JSON)),
+            None => None,
+        },
+    }
+}
+
+const FEED_QUERY: &[(&str, &str)] = &[
+    ("risk", "string"),
+    ("agent", "string"),
+    ("action_type", "string"),
+    ("since", "string"),
+];
+
+const OPERATIONS: &[Operation] = &[
+    op(
+        "get",
+        "/status",
+        "Daemon status",
+        None,
+        Some("@StatusResponse"),
+    ),
+    op(
+        "get",
+        "/collectors",
+        "Collector health",
+        None,
+        Some("[@CollectorStatus]"),
+    ),
+    op(
+        "get",
+        "/collectors/queue",
+        "Collector queue backlog and overflow counters",
+        None,
+        Some("@QueueMetrics"),
+    ),
+    op(
+        "get",
+        "/workspaces",
+        "Declared workspaces and those with stored actions",
+        None,
+        Some("@WorkspacesResponse"),
+    ),
+    op(
+        "get",
+        "/stats",
+        "Event statistics",
+        None,
+        Some("@StatsResponse"),
+    ),
+    op(
+        "get",
+        "/stats/by-provider",
+        "Requests per provider",
+        None,
+        Some("[@ProviderStats]"),
+    ),
+    Operation {
+        query: &[
+            ("limit", "integer"),
+            ("offset", "integer"),
+            ("risk_level", "string"),
+            ("agent", "string"),
+            ("provider", "string"),
+            ("status", "string"),
+        ],
+        ..op(
+            "get",
+            "/events",
+            "List events",
+            None,
+            Some("@EventsResponse"),
+        )
+    },
+    op(
+        "get",
+        "/events/recent",
+        "Most recent events",
+        None,
+        Some("[@EventResponse]"),
+    ),
+    Operation {
+        query: FEED_QUERY,
+        response: Some(("@WebEvent", "text/event-stream")),
+        ..op(
+            "get",
+            "/events/stream",
+            "Live event feed (Server-Sent Events)",
+            None,
+            None,
+        )
+    },
+    op(
+        "get",
+        "/events/{id}",
+        "Get one event",
+        None,
+        Some("@EventResponse"),
+    ),
+    Operation {
+        response: Some(("string", "text/plain")),
+        ..op(
+            "get",
+            "/events/{id}/content",
+            "Full content of an event (including offloaded content)",
+            None,
+            None,
+        )
+    },
+    Operation {
+        query: &[("since", "string"), ("format", "string")],
+        response: Some(("string", "application/x-ndjson")),
+        ..op(
+            "get",
+            "/export",
+            "Export actions with analysis (JSONL or CSV)",
+            None,
+            None,
+        )
+    },
+    op(
+        "post",
+        "/privacy/purge",
+        "Delete or anonymize stored data",
+        Some("@PurgeRequest"),
+        Some("@PurgeSummary"),
+    ),
+    op(
+        "get",
+        "/privacy/audit",
+        "Purge audit log",
+        None,
+        Some("[@PurgeAuditEntry]"),
+    ),
+    Operation {
+        query: &[("since", "string"), ("group_by", "string")],
+        ..op(
+            "get",
+            "/usage",
+            "Token usage and estimated spend",
+            None,
+            Some("@UsageResponse"),
+        )
+    },
+    Operation {
+        query: &[("dry_run", "boolean")],
+        ..op(
+            "post",
+            "/actions/{id}/rollback",
+            "Roll back a file change",
+            None,
+            Some("@RollbackOutcome"),
+        )
+    },
+    op(
+        "post",
+        "/backups",
+        "Back up a file before an approved write",
+        Some("@BackupRequest"),
+        Some("@BackupResponse"),
+    ),
+    Operation {
+        query: &[("category", "string"), ("tag", "string")],
+        ..op("get", "/rules", "List rules", None, Some("[@RuleResponse]"))
+    },
+    op(
+        "post",
+        "/rules",
+        "Create a rule",
+        Some("@CreateRuleRequest"),
+        Some("@RuleResponse"),
+    ),
+    op(
+        "put",
+        "/rules/{name}",
+        "Update a rule",
+        Some("@UpdateRuleRequest"),
+        Some("@RuleResponse"),
+    ),
+    op(
+        "delete",
+        "/rules/{name}",
+        "Delete a custom rule",
+        None,
+        None,
+    ),
+    op(
+        "get",
+        "/rules/{name}/history",
+        "Versions of a rule, oldest first",
+        None,
+        Some("[@RuleVersion]"),
+    ),
+    op(
+        "post",
+        "/rules/{name}/rollback",
+        "Restore a rule to an earlier version",
+        Some("@RuleRollbackRequest"),
+        Some("@RuleResponse"),
+    ),
+    op(
+        "post",
+        "/rules/from-template",
+        "Create a rule from a template",
+        Some("@FromTemplateRequest"),
+        Some("@RuleResponse"),
+    ),
+    op(
+        "get",
+        "/templates",
+        "Rule templates with their parameters",
+        None,
+        Some("[@TemplateResponse]"),
+    ),
+    op(
+        "post",
+        "/rules/test",
+        "Test a pattern against input",
+        Some("@TestRuleRequest"),
+        Some("@TestRuleResponse"),
+    ),
+    op(
+        "post",
+        "/simulate",
+        "Evaluate a hypothetical action (analysis and proxy behavior per mode)",
+        Some("@SimulateRequest"),
+        Some("@SimulateResponse"),
+    ),
+    op(
+        "get",
+        "/proxy/status",
+        "Proxy status",
+        None,
+        Some("@ProxyStatusResponse"),
+    ),
+    op(
+        "put",
+        "/proxy/config",
+        "Update proxy configuration",
+        Some("@UpdateProxyConfigRequest"),
+        Some("@ProxyStatusResponse"),
+    ),
+    op(
+        "get",
+        "/providers",
+        "Configured providers",
+        None,
+        Some("[@ProviderResponse]"),
+    ),
+    op(
+        "post",
+        "/campaigns/adaptive/generate",
+        "Generate an adaptive mission",
+        Some("@AdaptiveCampaignRequest"),
+        Some("@AdaptiveCampaignResponse"),
+    ),
+    Operation {
+        query: &[("user_id", "string"), ("limit", "integer")],
+        ..op(
+            "get",
+            "/behaviours",
+            "A user's behaviour records and stats",
+            None,
+            Some("@BehavioursResponse"),
+        )
+    },
+    op(
+        "post",
+        "/behaviours",
+        "Ingest behaviour records",
+        Some("@IngestBehavioursRequest"),
+        Some("@IngestBehavioursResponse"),
+    ),
+    Operation {
+        query: &[("user_id", "string")],
+        ..op(
+            "delete",
+            "/behaviours",
+            "Delete a user's behaviour records",
+            None,
+            Some("@DeleteBehavioursResponse"),
+        )
+    },
+    Operation {
+        query: &[("workspace_id", "string")],
+        ..op(
+            "post",
+            "/brain/ontology/build",
+            "Build the v1 ontology",
+            None,
+            Some("@BuildOntologyResponse"),
+        )
+    },
+    Operation {
+        query: &[("workspace_id", "string")],
+        ..op(
+            "post",
+            "/brain/ontology/v2/build",
+            "Build the v2 ontology",
+            None,
+            Some("@BuildOntologyV2Response"),
+        )
+    },
+    op(
+        "post",
+        "/brain/query",
+        "Query the brain",
+        Some("@BrainQueryRequest"),
+        Some("@BrainQueryResponse"),
+    ),
+    Operation {
+        query: &[("workspace_id", "string")],
+        ..op(
+            "get",
+            "/brain/graph",
+            "Ontology graph",
+            None,
+            Some("@BrainGraphResponse"),
+        )
+    },
+    op(
+        "post",
+        "/brain/search",
+        "Search ontology nodes",
+        Some("@BrainSearchRequest"),
+        Some("@BrainSearchResponse"),
+    ),
+    op(
+        "get",
+        "/alerts/config",
+        "Alert configuration",
+        None,
+        Some("@AlertConfigResponse"),
+    ),
+    op(
+        "put",
+        "/alerts/config",
+        "Update alert configuration",
+        Some("@AlertConfigResponse"),
+        None,
+    ),
+    op(
+        "post",
+        "/alerts/test",
+        "Send a test alert through each configured channel",
+        Some("@AlertTestRequest"),
+        Some("@AlertTestResponse"),
+    ),
+    op(
+        "get",
+        "/alerts/pause",
+        "Until when alerts are paused",
+        None,
+        Some("@AlertPauseResponse"),
+    ),
+    op(
+        "post",
+        "/alerts/pause",
+        "Hold alerts other than Critical for a while",
+        Some("@AlertPauseRequest"),
+        Some("@AlertPauseResponse"),
+    ),
+    op(
+        "delete",
+        "/alerts/pause",
+        "Resume alerts",
+        None,
+        Some("@AlertPauseResponse"),
+    ),
+    Operation {
+        query: &[("week", "string"), ("workspace_id", "string")],
+        ..op(
+            "get",
+            "/reports/weekly",
+            "Weekly report",
+            None,
+            Some("@WeeklyReportResponse"),
+        )
+    },
+    op(
+        "post",
+        "/reports/weekly/generate",
+        "Generate a weekly report",
+        Some("@GenerateWeeklyReportRequest"),
+        Some("@WeeklyReportResponse"),
+    ),
+    op(
+        "get",
+        "/approvals",
+        "Pending approval requests",
+        None,
+        Some("[@Approval]"),
+    ),
+    op(
+        "post",
+        "/approvals/{id}/approve",
+        "Let a paused tool call through",
+        None,
+        Some("@Approval"),
+    ),
+    op(
+        "post",
+        "/approvals/{id}/deny",
+        "Refuse a paused tool call",
+        None,
+        Some("@Approval"),
+    ),
+    op(
+        "get",
+        "/openapi.json",
+        "This document",
+        None,
+        Some("object"),
+    ),
+];
+
+fn operation(op: &Operation) -> Value {
+    let mut parameters: Vec<Value> = op
+        .path
+        .split('/')
+        .filter_map(|seg| seg.strip_prefix('{')?.strip_suffix('}'))
+        .map(|name| {
+            json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } })
+        })
+        .collect();
+    parameters.extend(op.query.iter().map(|(name, ty)| {
+        json!({ "name": name, "in": "query", "required": false, "schema": type_schema(ty) })
+    }));
+
+    let response = match op.response {
+        Some((ty, content_type)) => json!({
+            "description": "OK",
+            "content": { content_type: { "schema": type_schema(ty) } }
+        }),
+        None => json!({ "description": "OK" }),
+    };
+    let mut value = json!({
+        "summary": op.summary,
+        "parameters": parameters,
+        "responses": { "200": response }
+    });
+    if let Some(body) = op.body {
+        value["requestBody"] = json!({
+            "required": true,
+            "content": { JSON: { "schema": type_schema(body) } }
+        });
+    }
+    value
+}
+
+/// The OpenAPI document
+pub fn spec() -> Value {
+    let mut paths = Map::new();
+    for op in OPERATIONS {
+        let path = format!("{}{}", API_PREFIX, op.path);
+        let entry = paths.entry(path).or_insert_with(|| json!({}));
+        entry[op.method] = operation(op);
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "OpenClaw Harness API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "description": "Control Center REST API. Unversioned /api/... paths are aliases of /api/v1/... \
+                            and will be removed in a future release. The live feed is also available \
+                            as a WebSocket at /ws/events with the same query parameters as the SSE stream."
+        },
+        "paths": paths,
+        "components": { "schemas": schemas() }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Axum `:param` path to OpenAPI `{param}`
+    fn to_openapi(path: &str) -> String {
+        path.split('/')
+            .map(|seg| match seg.strip_prefix(':') {
+                Some(p) => format!("{{{}}}", p),
+                None => seg.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    #[test]
+    fn test_spec_covers_all_routes() {
+        let spec = spec();
+        let source = include_str!("mod.rs");
+        let api = &source[source.find("fn api_routes").unwrap()..];
+        let api = &api[..api.find("\n}").unwrap()];
+        for chunk in api.split(".route(").skip(1) {
+            let path = chunk.split('"').nth(1).unwrap();
+            let documented = format!("{}{}", API_PREFIX, to_openapi(path));
+            assert!(
+                spec["paths"].get(&documented).is_some(),
+                "{} missing from OpenAPI spec",
+                documented
+            );
+        }
+
+        // Every reference resolves
+        let text = spec.to_string();
+        for r in text.split("#/components/schemas/").skip(1) {
+            let name = r.split('"').next().unwrap();
+            assert!(
+                spec["components"]["schemas"].get(name).is_some(),
+                "{}",
+                name
+            );
+        }
+    }
+}
@@ -0,0 +1,257 @@
+//! LLM-written insights for the weekly report
+//!
+//! With an LLM configured (`SAFEBOT_LLM_API_KEY`, see `crate::llm`), the
+//! weekly report gets an executive summary and next actions drawn from the
+//! week's incidents instead of the fixed heuristic text. Without one, or if
+//! the call fails, the heuristic text stays. Insights for a week that has
+//! ended are cached in `reports/weekly/<report_id>.insights.json` so the LLM
+//! is asked once per report; `force_regenerate` asks again.
+
+use super::routes::{build_markdown, WeeklyReportResponse};
+use crate::db::DbPool;
+use crate::llm::LlmClient;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+pub const HEURISTIC: &str = "heuristic";
+pub const LLM: &str = "llm";
+
+/// Incidents sent to the LLM, most severe first
+const MAX_INCIDENTS: usize = 30;
+const MAX_NEXT_ACTIONS: usize = 5;
+const MAX_CONTENT_CHARS: usize = 200;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportInsights {
+    pub summary: String,
+    pub next_actions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedInsights {
+    workspace_id: String,
+    model: String,
+    #[serde(flatten)]
+    insights: ReportInsights,
+}
+
+/// A Warning or Critical finding of the week
+#[derive(Debug, Clone, Serialize)]
+pub struct Incident {
+    pub risk_level: String,
+    pub rules: Vec<String>,
+    pub explanation: String,
+    pub content: String,
+}
+
+fn cache_path(base_dir: &Path, report_id: &str) -> PathBuf {
+    base_dir
+        .join("reports")
+        .join("weekly")
+        .join(format!("{}.insights.json", report_id))
+}
+
+/// The week's Warning and Critical findings
+pub fn load_incidents(
+    pool: &DbPool,
+    report: &WeeklyReportResponse,
+) -> anyhow::Result<Vec<Incident>> {
+    let conn = pool.connection()?;
+    let mut stmt = conn.prepare(
+        "SELECT r.risk_level, r.matched_rules, r.explanation, COALESCE(a.content, '')
+         FROM analysis_results r LEFT JOIN actions a ON a.id = r.action_id
+         WHERE r.risk_level IN ('critical', 'warning') AND r.timestamp BETWEEN ?1 AND ?2
+         ORDER BY r.risk_level = 'critical' DESC, r.timestamp DESC
+         LIMIT ?3",
+    )?;
+    let rows = stmt.query_map(
+        rusqlite::params![report.week_start, report.week_end, MAX_INCIDENTS as i64],
+        |row| {
+            let rules: String = row.get(1)?;
+            let content: String = row.get(3)?;
+            Ok(Incident {
+                risk_level: row.get(0)?,
+                rules: rules
+                    .split(',')
+                    .filter(|r| !r.is_empty())
+                    .map(String::from)
+                    .collect(),
+                explanation: row.get(2)?,
+                content: content.chars().take(MAX_CONTENT_CHARS).collect(),
+            })
+        },
+    )?;
+    Ok(rows.filter_map(Result::ok).collect())
+}
+
+fn build_prompt(report: &WeeklyReportResponse, incidents: &[Incident]) -> String {
+    let stats = serde_json::json!({
+        "week": report.report_id,
+        "activity": report.activity,
+        "risk": report.risk,
+        "patterns": report.patterns,
+    });
+    format!(
+        "You review a week of AI coding agent activity recorded by a security harness.\n\
+         Write for an engineering lead. Return ONLY JSON:\n\
+         {{\"summary\": string (2-4 sentences), \"next_actions\": [string] (1-{MAX_NEXT_ACTIONS} concrete actions)}}\n\
+         Base the actions on the incidents: which rules to tighten or tune, which agents or projects to look at.\n\
+         Week statistics: {stats}\n\
+         Incidents (up to {MAX_INCIDENTS}, most severe first): {}",
+        serde_json::to_string(incidents).unwrap_or_else(|_| "[]".to_string())
+    )
+}
+
+/// Check the LLM's reply
+pub fn parse_insights(raw: &str) -> anyhow::Result<ReportInsights> {
+    let mut insights: ReportInsights = serde_json::from_str(raw.trim())?;
+    insights.summary = insights.summary.trim().to_string();
+    insights.next_actions = insights
+        .next_actions
+        .into_iter()
+        .map(|a| a.trim().to_string())
+        .filter(|a| !a.is_empty())
+        .take(MAX_NEXT_ACTIONS)
+        .collect();
+    if insights.summary.is_empty() || insights.next_actions.is_empty() {
+        anyhow::bail!("LLM insights missing summary or next actions");
+    }
+    Ok(insights)
+}
+
+fn apply(report: &mut WeeklyReportResponse, insights: ReportInsights) {
+    report.summary = Some(insights.summary);
+    report.next_actions = insights.next_actions;
+    report.insights = LLM.to_string();
+    report.markdown = build_markdown(report);
+}
+
+fn load_cached(base_dir: &Path, report: &WeeklyReportResponse) -> Option<ReportInsights> {
+    let text = std::fs::read_to_string(cache_path(base_dir, &report.report_id)).ok()?;
+    let cached: CachedInsights = serde_json::from_str(&text).ok()?;
+    (cached.workspace_id == report.workspace_id).then_some(cached.insights)
+}
+
+fn store_cached(
+    base_dir: &Path,
+    report: &WeeklyReportResponse,
+    model: &str,
+    insights: &ReportInsights,
+) -> anyhow::Result<()> {
+    let path = cache_path(base_dir, &report.report_id);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let cached = CachedInsights {
+        workspace_id: report.workspace_id.clone(),
+        model: model.to_string(),
+        insights: insights.clone(),
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&cached)?)?;
+    Ok(())
+}
+
+/// Whether the report's week is over, so its insights won't change
+fn week_ended(report: &WeeklyReportResponse) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&report.week_end).is_ok_and(|end| end < chrono::Utc::now())
+}
+
+/// Replace the heuristic summary and next actions with the LLM's, from the
+/// cache if there is one; the report is left as it is when no LLM is
+/// configured or the call fails
+pub async fn add_insights(
+    pool: &DbPool,
+    base_dir: &Path,
+    report: &mut WeeklyReportResponse,
+    force_regenerate: bool,
+) {
+    if !force_regenerate {
+        if let Some(insights) = load_cached(base_dir, report) {
+            apply(report, insights);
+            return;
+        }
+    }
+    let Ok(llm) = LlmClient::from_env() else {
+        return;
+    };
+    let incidents = match load_incidents(pool, report) {
+        Ok(incidents) => incidents,
+        Err(e) => {
+            warn!("⚠️ Cannot load incidents for report insights: {}", e);
+            return;
+        }
+    };
+    let prompt = build_prompt(report, &incidents);
+    let insights = match llm
+        .chat_json(
+            "You summarize agent security reports as strict JSON.",
+            &prompt,
+        )
+        .await
+        .and_then(|raw| parse_insights(&raw))
+    {
+        Ok(insights) => insights,
+        Err(e) => {
+            warn!(
+                "⚠️ LLM insights for {} failed, keeping heuristic text: {}",
+                report.report_id, e
+            );
+            return;
+        }
+    };
+    if week_ended(report) {
+        if let Err(e) = store_cached(base_dir, report, llm.model(), &insights) {
+            warn!("⚠️ Cannot cache report insights: {}", e);
+        }
+    }
+    info!("🧠 LLM insights written for report {}", report.report_id);
+    apply(report, insights);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::routes::{compute_weekly_report, persist_weekly_outputs};
+
+    #[tokio::test]
+    async fn test_report_insights_parse_and_cache() {
+        assert!(parse_insights(r#"{"summary": "", "next_actions": ["x"]}"#).is_err());
+        assert!(parse_insights(r#"{"summary": "ok", "next_actions": [" "]}"#).is_err());
+        assert!(parse_insights("not json").is_err());
+        let insights = parse_insights(
+            r#"{"summary": " Quiet week. ", "next_actions": ["a", "b", "c", "d", "e", "f"]}"#,
+        )
+        .unwrap();
+        assert_eq!(insights.summary, "Quiet week.");
+        assert_eq!(insights.next_actions.len(), MAX_NEXT_ACTIONS);
+
+        let tmp = tempfile::tempdir().unwrap();
+        let pool = DbPool::open(&tmp.path().join("harness.db")).unwrap();
+        let mut report = compute_weekly_report(&pool, Some("2026-W09".to_string()), None).unwrap();
+        assert_eq!(report.insights, HEURISTIC);
+        assert!(load_incidents(&pool, &report).unwrap().is_empty());
+        assert!(week_ended(&report));
+
+        // A cached answer is used without asking the LLM
+        store_cached(tmp.path(), &report, "test-model", &insights).unwrap();
+        add_insights(&pool, tmp.path(), &mut report, false).await;
+        assert_eq!(report.insights, LLM);
+        assert_eq!(report.summary.as_deref(), Some("Quiet week."));
+        assert!(report.markdown.contains("## Summary\nQuiet week."));
+        persist_weekly_outputs(tmp.path(), &report).unwrap();
+
+        // ...but not for another workspace
+        let mut other = compute_weekly_report(
+            &pool,
+            Some("2026-W09".to_string()),
+            Some("other".to_string()),
+        )
+        .unwrap();
+        assert!(load_cached(tmp.path(), &other).is_none());
+        std::env::remove_var(crate::llm::API_KEY_ENV);
+        add_insights(&pool, tmp.path(), &mut other, false).await;
+        assert_eq!(other.insights, HEURISTIC);
+        assert_eq!(other.summary, None);
+    }
+}
@@ -1,16 +1,48 @@
 //! REST API routes
 
-use super::AppState;
+use super::{report_insights, AppState};
+use crate::blob::{self, BlobStore};
 use crate::brain::{
-    build_ontology_from_db, build_ontology_v2_from_db, persist_ontology, persist_ontology_v2,
+    build_ontology_scoped, build_ontology_v2_scoped, persist_ontology, persist_ontology_v2,
     BrainInsights, OntologyBuildSummary,
 };
-use crate::campaign::{CampaignConstraints, CampaignEngine, LlmAiPlanner, MissionPlan};
-use crate::rules::{Rule, RuleAction};
-use crate::RiskLevel;
+use crate::campaign::behaviours::{self, NewBehaviour};
+use crate::campaign::{
+    compute_stats, load_history, BehaviourRecord, CampaignConstraints, CampaignEngine,
+    CampaignError, MissionPlan, PlannerKind, UserBehaviourStats,
+};
+use crate::collectors::{CollectorState, CollectorStatus, QueueMetrics};
+use crate::db::{
+    rule_history, Approval, DashboardStats, DbHealth, DbPool, EgressSummary, EventCounters,
+    PurgeAuditEntry, RuleStats, RuleVersion, UsageSummary,
+};
+use crate::enforcer::alerter::{Alerter, ChannelTest};
+use crate::enforcer::backup::BackupStore;
+use crate::enforcer::desktop;
+use crate::enforcer::secrets::{self, Seal, SecretStore};
+use crate::diagnostics::{self, DiagnosticCounts};
+use crate::export::{self, ExportFormat};
+use crate::privacy::{self, PurgeMode, PurgeScope, PurgeSummary};
+use crate::analyzer::Analyzer;
+use crate::proxy::health::UpstreamHealth;
+use crate::proxy::interceptor::{self, check_action, MessageOptions, ProxyBehavior};
+use crate::proxy::usage::Budget;
+use crate::rollback::{self, RollbackOutcome, ShadowStore};
+use crate::policy::suite;
+use crate::workspace::{self, WorkspaceSummary};
+use crate::rules::{
+    all_templates, param_spec, KeywordMatch, MatchType, ParamSpec, Rule, RuleAction,
+    TemplateParams,
+};
+use crate::{
+    ActionType, AgentAction, AgentType, AlertConfig, AnalysisResult, DiscordConfig, RiskLevel,
+    SlackConfig, TelegramConfig,
+};
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::Response,
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -29,6 +61,16 @@ pub struct StatusResponse {
     pub version: String,
     pub uptime_seconds: u64,
     pub monitoring: Vec<String>,
+    /// Database size and upkeep; None if it can't be read
+    pub database: Option<DbHealth>,
+    /// Address the web server listens on
+    pub listen: String,
+    /// Whether it accepts connections from other machines
+    pub exposed: bool,
+    /// What that means for the unauthenticated API
+    pub exposure: String,
+    /// Harness-internal failures so far; None if they can't be read
+    pub diagnostics: Option<DiagnosticCounts>,
 }
 
 pub async fn get_status(State(state): State<Arc<AppState>>) -> Json<StatusResponse> {
@@ -37,14 +79,52 @@ pub async fn get_status(State(state): State<Arc<AppState>>) -> Json<StatusRespon
         .num_seconds()
         .max(0) as u64;
 
+    let monitoring = state
+        .collectors
+        .statuses()
+        .into_iter()
+        .filter(|c| c.state == CollectorState::Running)
+        .map(|c| c.name)
+        .collect();
+
+    let pool = state.db.clone();
+    let (database, diagnostics) = tokio::task::spawn_blocking(move || {
+        let db = pool.get()?;
+        // Include what was recorded since the daemon's last flush
+        let _ = diagnostics::flush(&db);
+        Ok::<_, anyhow::Error>((
+            db.health().ok(),
+            db.diagnostics().ok().map(|d| DiagnosticCounts::of(&d)),
+        ))
+    })
+    .await
+    .ok()
+    .and_then(|r| r.ok())
+    .unwrap_or_default();
+
     Json(StatusResponse {
         running: true,
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: uptime,
-        monitoring: vec!["openclaw".to_string()],
+        monitoring,
+        database,
+        listen: state.web.listen.clone(),
+        exposed: !crate::listen::is_loopback(&state.web.listen),
+        exposure: crate::listen::exposure_note(&state.web.listen).to_string(),
+        diagnostics,
     })
 }
 
+/// Health of each collector in the daemon
+pub async fn get_collectors(State(state): State<Arc<AppState>>) -> Json<Vec<CollectorStatus>> {
+    Json(state.collectors.statuses())
+}
+
+/// Backlog and overflow counters of the queue between collectors and analyzer
+pub async fn get_collector_queue(State(state): State<Arc<AppState>>) -> Json<QueueMetrics> {
+    Json(state.collectors.queue_metrics().unwrap_or_default())
+}
+
 #[derive(Serialize)]
 pub struct StatsResponse {
     pub total_events: u64,
@@ -54,23 +134,82 @@ pub struct StatsResponse {
     pub today_events: u64,
     pub rules_count: usize,
     pub blocked_count: u64,
+    /// Blocked since local midnight
+    pub today_blocked_count: u64,
     pub passed_count: u64,
+    /// API requests seen by the proxy, across restarts
+    pub proxy_requests: u64,
+    /// Actions not stored because their id was already taken
+    pub action_id_conflicts: u64,
 }
 
-pub async fn get_stats(State(state): State<Arc<AppState>>) -> Json<StatsResponse> {
-    let rules = state.rules.read().await;
-    let counters = state.counters.read().await;
-
-    Json(StatsResponse {
-        total_events: counters.total_requests,
-        critical_count: counters.blocked_count,
-        warning_count: counters.warning_count,
-        info_count: counters.passed_count,
-        today_events: counters.total_requests,
-        rules_count: rules.len(),
-        blocked_count: counters.blocked_count,
-        passed_count: counters.passed_count,
+/// How long computed statistics are reused
+const STATS_CACHE_TTL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Statistics computed from the database at one point in time
+#[derive(Debug, Clone)]
+pub struct StatsSnapshot {
+    computed_at: std::time::Instant,
+    stats: DashboardStats,
+    counters: EventCounters,
+    action_id_conflicts: u64,
+}
+
+/// Cached statistics, recomputed from the database when stale
+async fn stats_snapshot(state: &AppState) -> Result<StatsSnapshot, StatusCode> {
+    if let Some(cached) = state.stats_cache.lock().ok().and_then(|c| c.clone()) {
+        if cached.computed_at.elapsed() < STATS_CACHE_TTL {
+            return Ok(cached);
+        }
+    }
+
+    let pool = state.db.clone();
+    let snapshot = tokio::task::spawn_blocking(move || -> anyhow::Result<StatsSnapshot> {
+        let db = pool.get()?;
+        let today_start = chrono::Local::now()
+            .date_naive()
+            .and_hms_opt(0, 0, 0)
+            .and_then(|t| t.and_local_timezone(chrono::Local).earliest())
+            .map(|t| t.with_timezone(&chrono::Utc))
+            .unwrap_or_else(chrono::Utc::now);
+        Ok(StatsSnapshot {
+            computed_at: std::time::Instant::now(),
+            stats: db.get_dashboard_stats(today_start)?,
+            counters: db.get_event_counters()?,
+            action_id_conflicts: db.get_counter(crate::db::ACTION_ID_CONFLICTS)?,
+        })
     })
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .map_err(|e| {
+        tracing::error!("Failed to compute stats: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    if let Ok(mut cache) = state.stats_cache.lock() {
+        *cache = Some(snapshot.clone());
+    }
+    Ok(snapshot)
+}
+
+pub async fn get_stats(State(state): State<Arc<AppState>>) -> Result<Json<StatsResponse>, StatusCode> {
+    let snapshot = stats_snapshot(&state).await?;
+    let stats = snapshot.stats;
+    let rules_count = state.rules.read().await.len();
+
+    Ok(Json(StatsResponse {
+        total_events: stats.total_events,
+        critical_count: stats.critical_count,
+        warning_count: stats.warning_count,
+        info_count: stats.info_count,
+        today_events: stats.today_events,
+        rules_count,
+        blocked_count: stats.blocked_count,
+        today_blocked_count: stats.today_blocked,
+        passed_count: stats.total_events.saturating_sub(stats.blocked_count),
+        proxy_requests: snapshot.counters.total_requests,
+        action_id_conflicts: snapshot.action_id_conflicts,
+    }))
 }
 
 #[derive(Serialize)]
@@ -79,17 +218,21 @@ pub struct ProviderStats {
     pub request_count: u64,
 }
 
-pub async fn get_stats_by_provider(State(state): State<Arc<AppState>>) -> Json<Vec<ProviderStats>> {
-    let counters = state.counters.read().await;
-    let stats: Vec<ProviderStats> = counters
+pub async fn get_stats_by_provider(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ProviderStats>>, StatusCode> {
+    let snapshot = stats_snapshot(&state).await?;
+    let mut stats: Vec<ProviderStats> = snapshot
+        .counters
         .by_provider
-        .iter()
-        .map(|(k, v)| ProviderStats {
-            provider: k.clone(),
-            request_count: *v,
+        .into_iter()
+        .map(|(provider, request_count)| ProviderStats {
+            provider,
+            request_count,
         })
         .collect();
-    Json(stats)
+    stats.sort_by(|a, b| b.request_count.cmp(&a.request_count).then(a.provider.cmp(&b.provider)));
+    Ok(Json(stats))
 }
 
 // ============================================================================
@@ -149,6 +292,362 @@ pub async fn get_event(
     Err(StatusCode::NOT_FOUND)
 }
 
+/// Full content of an action, reading offloaded content from the blob store
+pub async fn get_event_content(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Response, StatusCode> {
+    let db_path = PathBuf::from(&state.db_path);
+    let db = state.db.get().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let action = db
+        .get_action(&id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let content = match blob::blob_hash(&action) {
+        // Offloaded content that has since been purged is gone
+        Some(hash) => BlobStore::for_db(&db_path)
+            .get(hash)
+            .ok_or(StatusCode::GONE)?,
+        None => action.content,
+    };
+    Ok(Response::builder()
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(content))
+        .unwrap())
+}
+
+// ============================================================================
+// Export
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    /// Relative age (`30d`) or date/time
+    pub since: Option<String>,
+    /// jsonl (default) or csv
+    pub format: Option<String>,
+}
+
+/// Stream actions and their analysis as JSONL or CSV
+pub async fn export_actions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExportQuery>,
+) -> Result<Response, StatusCode> {
+    let format: ExportFormat = query
+        .format
+        .as_deref()
+        .unwrap_or("jsonl")
+        .parse()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let since = query
+        .since
+        .as_deref()
+        .map(export::parse_since)
+        .transpose()
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(64);
+    let pool = state.db.clone();
+    let task = tokio::task::spawn_blocking(move || -> anyhow::Result<usize> {
+        let db = pool.get()?;
+        if let Some(h) = export::header(format) {
+            tx.blocking_send(h)?;
+        }
+        db.for_each_export_row(since, |row| {
+            // Client went away: stop reading
+            tx.blocking_send(row.encode(format)?)?;
+            Ok(())
+        })
+    });
+
+    let body = async_stream::stream! {
+        while let Some(chunk) = rx.recv().await {
+            yield Ok::<_, std::io::Error>(bytes::Bytes::from(chunk));
+        }
+        // End with an error rather than a body that looks complete
+        let failed = match task.await {
+            Ok(Ok(_)) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(e) => Some(e.to_string()),
+        };
+        if let Some(e) = failed {
+            tracing::error!("❌ Export failed: {}", e);
+            yield Err(std::io::Error::other(e));
+        }
+    };
+    Response::builder()
+        .header(header::CONTENT_TYPE, format.content_type())
+        .body(Body::from_stream(body))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// ============================================================================
+// Privacy (purge / anonymize)
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct PurgeRequest {
+    pub session_id: Option<String>,
+    pub path_prefix: Option<String>,
+    /// Relative age (`30d`) or date/time
+    pub from: Option<String>,
+    pub to: Option<String>,
+    #[serde(default)]
+    pub mode: PurgeMode,
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+pub async fn purge_data(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<PurgeRequest>,
+) -> Result<Json<PurgeSummary>, StatusCode> {
+    let parse = |t: Option<String>| {
+        t.as_deref()
+            .map(export::parse_since)
+            .transpose()
+            .map_err(|_| StatusCode::BAD_REQUEST)
+    };
+    let scope = PurgeScope {
+        session_id: body.session_id,
+        path_prefix: body.path_prefix,
+        from: parse(body.from)?,
+        to: parse(body.to)?,
+    };
+    if scope.is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    privacy::purge(
+        &state.db,
+        &brain_data_base_dir(),
+        &scope,
+        body.mode,
+        body.dry_run,
+        "api",
+    )
+    .map(Json)
+    .map_err(|e| {
+        tracing::warn!("Purge failed: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
+pub async fn get_purge_audit(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<PurgeAuditEntry>>, StatusCode> {
+    let db = state
+        .db
+        .get()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.get_purge_audit(100)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+// ============================================================================
+// Usage
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct UsageQuery {
+    /// Start date: YYYY-MM-DD, RFC 3339, or a relative duration like "7d" (default)
+    pub since: Option<String>,
+    /// day (default), session, provider or model
+    pub group_by: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct UsageResponse {
+    pub since: String,
+    pub group_by: String,
+    pub rows: Vec<UsageSummary>,
+    pub total_requests: u64,
+    pub total_input_tokens: u64,
+    pub total_output_tokens: u64,
+    pub total_cost_usd: f64,
+    pub today_cost_usd: f64,
+    pub daily_budget_usd: Option<f64>,
+    pub session_budget_usd: Option<f64>,
+    /// Request bytes sent to providers, grouped like `rows` (empty when
+    /// grouped by model)
+    pub egress: Vec<EgressSummary>,
+    pub total_egress_bytes: u64,
+}
+
+/// Token usage and estimated spend recorded by the proxy
+pub async fn get_usage(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageResponse>, StatusCode> {
+    let since = export::parse_since(query.since.as_deref().unwrap_or("7d"))
+        .map_err(|_| StatusCode::BAD_REQUEST)?
+        .format("%Y-%m-%d")
+        .to_string();
+    let group_by = query.group_by.unwrap_or_else(|| "day".to_string());
+
+    let db = state
+        .db
+        .get()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let rows = db
+        .usage_summary(&since, &group_by)
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    let today_cost_usd = db
+        .usage_spend(&today, None)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let budget = Budget::from_env();
+    let egress = match group_by.as_str() {
+        "model" => vec![],
+        _ => db
+            .egress_summary(&since, &group_by)
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+    };
+
+    Ok(Json(UsageResponse {
+        total_requests: rows.iter().map(|r| r.requests).sum(),
+        total_input_tokens: rows.iter().map(|r| r.input_tokens).sum(),
+        total_output_tokens: rows.iter().map(|r| r.output_tokens).sum(),
+        total_cost_usd: rows.iter().map(|r| r.cost_usd).sum(),
+        since,
+        group_by,
+        rows,
+        today_cost_usd,
+        daily_budget_usd: budget.daily_usd,
+        session_budget_usd: budget.session_usd,
+        total_egress_bytes: egress.iter().map(|r| r.bytes).sum(),
+        egress,
+    }))
+}
+
+// ============================================================================
+// Rollback
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct RollbackQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Best-effort undo of a FileWrite/FileDelete that was detected too late
+pub async fn rollback_action(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<RollbackQuery>,
+) -> Result<Json<RollbackOutcome>, StatusCode> {
+    let db = state
+        .db
+        .get()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let action = db
+        .get_action(&id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let shadow = ShadowStore::new(ShadowStore::default_root());
+    rollback::rollback_action(&action, &shadow, query.dry_run)
+        .map(Json)
+        .map_err(|e| {
+            tracing::warn!("Rollback of {} failed: {}", id, e);
+            StatusCode::UNPROCESSABLE_ENTITY
+        })
+}
+
+#[derive(Deserialize)]
+pub struct BackupRequest {
+    pub path: String,
+    /// Rule that required approval (for logging)
+    pub rule: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct BackupResponse {
+    pub ok: bool,
+    pub backup: Option<String>,
+}
+
+/// Called by hooks right before an approved PauseAndAsk write proceeds
+pub async fn create_backup(
+    State(_state): State<Arc<AppState>>,
+    Json(body): Json<BackupRequest>,
+) -> Result<Json<BackupResponse>, StatusCode> {
+    if body.path.trim().is_empty() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let store = BackupStore::default();
+    let backup = store
+        .backup(&crate::expand_home(&body.path))
+        .map_err(|e| {
+            tracing::warn!(
+                "Backup of {} (rule: {}) failed: {}",
+                body.path,
+                body.rule.as_deref().unwrap_or("-"),
+                e
+            );
+            StatusCode::INTERNAL_SERVER_ERROR
+        })?;
+    let _ = store.prune();
+
+    Ok(Json(BackupResponse {
+        ok: true,
+        backup: backup.map(|p| p.to_string_lossy().to_string()),
+    }))
+}
+
+// ============================================================================
+// Approvals
+// ============================================================================
+
+/// Pending PauseAndAsk requests, oldest first
+pub async fn get_approvals(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<Approval>>, StatusCode> {
+    let db = state
+        .db
+        .get()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    db.pending_approvals(false)
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub async fn approve_request(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Approval>, StatusCode> {
+    decide(&state, &id, true)
+}
+
+pub async fn deny_request(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Approval>, StatusCode> {
+    decide(&state, &id, false)
+}
+
+/// Same decision as `/harness approve` in Slack or the terminal prompt
+fn decide(state: &AppState, id: &str, approved: bool) -> Result<Json<Approval>, StatusCode> {
+    let db = state
+        .db
+        .get()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let approval = db
+        .decide_approval(id, approved, "dashboard")
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    tracing::info!(
+        "{} {} in the dashboard: {}",
+        if approved { "✅ Approved" } else { "🚫 Denied" },
+        approval.id,
+        approval.summary
+    );
+    Ok(Json(approval))
+}
+
 // ============================================================================
 // Rules
 // ============================================================================
@@ -157,11 +656,25 @@ pub async fn get_event(
 pub struct RuleResponse {
     pub name: String,
     pub description: String,
+    pub match_type: MatchType,
+    /// For regex rules
     pub pattern: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keyword: Option<KeywordMatch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<TemplateParams>,
     pub risk_level: String,
     pub action: String,
     pub enabled: bool,
     pub is_preset: bool,
+    /// Explicit, or from the rule's template or built-in defaults
+    pub category: String,
+    pub tags: Vec<String>,
+    pub priority: i32,
+    /// Matches over all time, with daily counts for the last `RULE_STATS_DAYS`
+    pub stats: RuleStats,
 }
 
 impl RuleResponse {
@@ -169,11 +682,19 @@ impl RuleResponse {
         RuleResponse {
             name: rule.name.clone(),
             description: rule.description.clone(),
+            match_type: rule.match_type.clone(),
             pattern: rule.pattern.clone(),
+            keyword: rule.keyword.clone(),
+            template: rule.template.clone(),
+            params: rule.params.clone(),
             risk_level: format!("{:?}", rule.risk_level),
             action: format!("{:?}", rule.action),
             enabled: rule.enabled,
             is_preset: preset_names.contains(&rule.name.as_str()),
+            category: rule.category_name(),
+            tags: rule.tags.clone(),
+            priority: rule.priority,
+            stats: RuleStats::default(),
         }
     }
 }
@@ -190,12 +711,48 @@ const PRESET_RULE_NAMES: &[&str] = &[
     "npm_install",
 ];
 
-pub async fn get_rules(State(state): State<Arc<AppState>>) -> Json<Vec<RuleResponse>> {
+/// Days of per-day match counts returned with each rule
+const RULE_STATS_DAYS: i64 = 30;
+
+#[derive(Deserialize, Default)]
+pub struct RulesQuery {
+    /// Only rules in this category (case-insensitive)
+    pub category: Option<String>,
+    /// Only rules with this tag
+    pub tag: Option<String>,
+}
+
+impl RulesQuery {
+    fn matches(&self, rule: &Rule) -> bool {
+        self.category
+            .as_deref()
+            .is_none_or(|c| rule.category_name().eq_ignore_ascii_case(c.trim()))
+            && self.tag.as_deref().is_none_or(|t| rule.has_tag(t))
+    }
+}
+
+pub async fn get_rules(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RulesQuery>,
+) -> Json<Vec<RuleResponse>> {
+    let since = (chrono::Utc::now() - chrono::Duration::days(RULE_STATS_DAYS - 1))
+        .format("%Y-%m-%d")
+        .to_string();
+    // Stats are best effort; the rule list must load without a database
+    let stats = state
+        .db
+        .get()
+        .and_then(|db| db.rule_stats(&since))
+        .unwrap_or_default();
     let rules = state.rules.read().await;
     Json(
         rules
             .iter()
-            .map(|r| RuleResponse::from_rule(r, PRESET_RULE_NAMES))
+            .filter(|r| query.matches(r))
+            .map(|r| RuleResponse {
+                stats: stats.get(&r.name).cloned().unwrap_or_default(),
+                ..RuleResponse::from_rule(r, PRESET_RULE_NAMES)
+            })
             .collect(),
     )
 }
@@ -203,120 +760,387 @@ pub async fn get_rules(State(state): State<Arc<AppState>>) -> Json<Vec<RuleRespo
 #[derive(Deserialize)]
 pub struct CreateRuleRequest {
     pub name: String,
+    /// Template rules default to the template's description
+    #[serde(default)]
     pub description: String,
+    #[serde(default)]
+    pub match_type: MatchType,
+    /// For regex rules
+    #[serde(default)]
     pub pattern: String,
+    /// For keyword rules
+    #[serde(default)]
+    pub keyword: Option<KeywordMatch>,
+    /// For template rules
+    #[serde(default)]
+    pub template: Option<String>,
+    #[serde(default)]
+    pub params: Option<TemplateParams>,
     pub risk_level: String,
     pub action: String,
     #[serde(default = "default_true")]
     pub enabled: bool,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub priority: i32,
 }
 
 fn default_true() -> bool {
     true
 }
 
-fn parse_risk_level(s: &str) -> RiskLevel {
-    match s.to_lowercase().as_str() {
-        "critical" => RiskLevel::Critical,
-        "warning" => RiskLevel::Warning,
-        _ => RiskLevel::Info,
+/// A rule action as the UIs and the CLI name it; anything else is
+/// rejected rather than weakened to log-only
+fn parse_action(s: &str) -> Result<RuleAction, StatusCode> {
+    match s.trim().to_lowercase().as_str() {
+        "criticalalert" | "critical_alert" => Ok(RuleAction::CriticalAlert),
+        "pauseandask" | "pause_and_ask" => Ok(RuleAction::PauseAndAsk),
+        "alert" => Ok(RuleAction::Alert),
+        "block" => Ok(RuleAction::Block),
+        "logonly" | "log_only" => Ok(RuleAction::LogOnly),
+        other => {
+            tracing::warn!("Rejected unknown rule action '{}'", other);
+            Err(StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+fn parse_risk(s: &str) -> Result<RiskLevel, StatusCode> {
+    s.parse().map_err(|e| {
+        tracing::warn!("Rejected rule: {}", e);
+        StatusCode::BAD_REQUEST
+    })
+}
+
+/// Header naming who made a change, kept in rule history
+pub const CHANGED_BY_HEADER: &str = "x-openclaw-user";
+
+fn changed_by(headers: &HeaderMap) -> String {
+    headers
+        .get(CHANGED_BY_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .unwrap_or("api")
+        .to_string()
+}
+
+/// Add a version to the rule's history; a failure is logged, not fatal
+fn record_rule_change(
+    state: &AppState,
+    name: &str,
+    change: &str,
+    by: &str,
+    before: Option<&Rule>,
+    after: Option<&Rule>,
+) {
+    let recorded = state
+        .db
+        .get()
+        .and_then(|db| db.record_rule_change(name, change, by, before, after));
+    if let Err(e) = recorded {
+        tracing::warn!("Failed to record history of rule {}: {}", name, e);
     }
 }
 
-fn parse_action(s: &str) -> RuleAction {
-    match s.to_lowercase().as_str() {
-        "criticalalert" | "critical_alert" => RuleAction::CriticalAlert,
-        "pauseandask" | "pause_and_ask" => RuleAction::PauseAndAsk,
-        "alert" => RuleAction::Alert,
-        _ => RuleAction::LogOnly,
+/// Reject a rule `rules lint` reports errors for, then compile it
+fn validate_rule(rule: &mut Rule) -> Result<(), StatusCode> {
+    let errors: Vec<String> = suite::lint(std::slice::from_ref(rule))
+        .into_iter()
+        .filter(|issue| issue.error)
+        .map(|issue| issue.message)
+        .collect();
+    if !errors.is_empty() {
+        tracing::warn!("Rejected rule {}: {}", rule.name, errors.join("; "));
+        return Err(StatusCode::BAD_REQUEST);
     }
+    rule.compile().map_err(|_| StatusCode::BAD_REQUEST)
 }
 
 pub async fn create_rule(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(body): Json<CreateRuleRequest>,
 ) -> Result<Json<RuleResponse>, StatusCode> {
-    // Validate regex
-    if regex::Regex::new(&body.pattern).is_err() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
     let mut rule = Rule::new(
         &body.name,
         &body.description,
         &body.pattern,
-        parse_risk_level(&body.risk_level),
-        parse_action(&body.action),
+        parse_risk(&body.risk_level)?,
+        parse_action(&body.action)?,
     );
+    rule.match_type = body.match_type;
+    rule.keyword = body.keyword;
+    rule.template = body.template;
+    rule.params = body.params;
     rule.enabled = body.enabled;
-    if rule.compile().is_err() {
-        return Err(StatusCode::BAD_REQUEST);
-    }
-
+    rule.category = body.category.filter(|c| !c.trim().is_empty());
+    rule.tags = body.tags;
+    rule.priority = body.priority;
+    validate_rule(&mut rule)?;
+    add_rule(&state, &headers, rule).await
+}
+
+/// Add a validated rule unless one has its name
+async fn add_rule(
+    state: &AppState,
+    headers: &HeaderMap,
+    rule: Rule,
+) -> Result<Json<RuleResponse>, StatusCode> {
     let resp = RuleResponse::from_rule(&rule, PRESET_RULE_NAMES);
 
     let mut rules = state.rules.write().await;
     // Check duplicate
-    if rules.iter().any(|r| r.name == body.name) {
+    if rules.iter().any(|r| r.name == rule.name) {
         return Err(StatusCode::CONFLICT);
     }
+    record_rule_change(
+        state,
+        &rule.name,
+        rule_history::CREATE,
+        &changed_by(headers),
+        None,
+        Some(&rule),
+    );
     rules.push(rule);
+    state.proxy.set_rules(rules.clone());
 
     Ok(Json(resp))
 }
 
+// ============================================================================
+// Templates
+// ============================================================================
+
+#[derive(Serialize)]
+pub struct TemplateResponse {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub category: &'static str,
+    pub params: Vec<TemplateParamResponse>,
+    pub example: TemplateExample,
+}
+
+#[derive(Serialize)]
+pub struct TemplateParamResponse {
+    #[serde(flatten)]
+    pub spec: &'static ParamSpec,
+    pub required: bool,
+}
+
+/// The template instantiated with example parameters
+#[derive(Serialize)]
+pub struct TemplateExample {
+    pub params: TemplateParams,
+    pub description: String,
+    pub applies_to: Vec<ActionType>,
+}
+
+/// Every rule template, with its parameters and an example
+pub async fn get_templates() -> Json<Vec<TemplateResponse>> {
+    Json(
+        all_templates()
+            .into_iter()
+            .map(|t| {
+                let params = t.example_params();
+                let (_, applies_to, description) = t.expand(&params);
+                TemplateResponse {
+                    name: t.name,
+                    description: t.description,
+                    category: t.category,
+                    params: t
+                        .params()
+                        .into_iter()
+                        .map(|(spec, required)| TemplateParamResponse { spec, required })
+                        .collect(),
+                    example: TemplateExample {
+                        params,
+                        description,
+                        applies_to,
+                    },
+                }
+            })
+            .collect(),
+    )
+}
+
+#[derive(Deserialize)]
+pub struct FromTemplateRequest {
+    pub name: String,
+    pub template: String,
+    /// Keyed by the names in the template's `params`
+    #[serde(default)]
+    pub params: serde_json::Map<String, serde_json::Value>,
+    /// Defaults to the template's description
+    #[serde(default)]
+    pub description: String,
+    /// Default warning, as with `rules add --template`
+    pub risk_level: Option<String>,
+    /// Default block, as with `rules add --template`
+    pub action: Option<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Create a rule from a template, rejecting unknown templates, unknown or
+/// missing parameters and values a parameter doesn't allow
+pub async fn create_rule_from_template(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(body): Json<FromTemplateRequest>,
+) -> Result<Json<RuleResponse>, StatusCode> {
+    let template = all_templates()
+        .into_iter()
+        .find(|t| t.name == body.template)
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let accepted = template.params();
+    let mut problems: Vec<String> = body
+        .params
+        .keys()
+        .filter(|key| {
+            param_spec(key).is_none_or(|spec| !accepted.iter().any(|(p, _)| p.name == spec.name))
+        })
+        .map(|key| format!("'{}' takes no parameter '{}'", template.name, key))
+        .collect();
+    let params: TemplateParams = serde_json::from_value(serde_json::Value::Object(body.params))
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    problems.extend(template.check_params(&params));
+    if !problems.is_empty() {
+        tracing::warn!("Rejected rule {}: {}", body.name, problems.join("; "));
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut rule = Rule::new_template(
+        &body.name,
+        template.name,
+        params,
+        body.risk_level
+            .as_deref()
+            .map(parse_risk)
+            .transpose()?
+            .unwrap_or(RiskLevel::Warning),
+        parse_action(body.action.as_deref().unwrap_or("block"))?,
+    );
+    if !body.description.trim().is_empty() {
+        rule.description = body.description;
+    }
+    rule.enabled = body.enabled;
+    rule.category = body.category.filter(|c| !c.trim().is_empty());
+    rule.tags = body.tags;
+    validate_rule(&mut rule)?;
+    add_rule(&state, &headers, rule).await
+}
+
 #[derive(Deserialize)]
 pub struct UpdateRuleRequest {
     pub description: Option<String>,
+    pub match_type: Option<MatchType>,
     pub pattern: Option<String>,
+    pub keyword: Option<KeywordMatch>,
+    pub template: Option<String>,
+    pub params: Option<TemplateParams>,
     pub risk_level: Option<String>,
     pub action: Option<String>,
     pub enabled: Option<bool>,
+    /// Empty clears it back to the default
+    pub category: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub priority: Option<i32>,
 }
 
 pub async fn update_rule(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
+    headers: HeaderMap,
     Json(body): Json<UpdateRuleRequest>,
 ) -> Result<Json<RuleResponse>, StatusCode> {
     let mut rules = state.rules.write().await;
-    let rule = rules
+    let slot = rules
         .iter_mut()
         .find(|r| r.name == name)
         .ok_or(StatusCode::NOT_FOUND)?;
 
     // Block modification of protected (self-protection) rules
-    if rule.protected {
+    if slot.protected {
         return Err(StatusCode::FORBIDDEN);
     }
 
+    // Edit a copy so a rejected update leaves the rule as it was
+    let mut rule = slot.clone();
     if let Some(desc) = body.description {
         rule.description = desc;
     }
+    let matching_changed = body.match_type.is_some()
+        || body.pattern.is_some()
+        || body.keyword.is_some()
+        || body.template.is_some()
+        || body.params.is_some();
+    if let Some(match_type) = body.match_type {
+        rule.match_type = match_type;
+    }
     if let Some(pattern) = body.pattern {
-        if regex::Regex::new(&pattern).is_err() {
-            return Err(StatusCode::BAD_REQUEST);
-        }
         rule.pattern = pattern;
-        rule.compile().map_err(|_| StatusCode::BAD_REQUEST)?;
+    }
+    if let Some(keyword) = body.keyword {
+        rule.keyword = Some(keyword);
+    }
+    if body.template.is_some() || body.params.is_some() {
+        // Let the template choose its action types again
+        rule.applies_to.clear();
+    }
+    if let Some(template) = body.template {
+        rule.template = Some(template);
+    }
+    if let Some(params) = body.params {
+        rule.params = Some(params);
     }
     if let Some(rl) = body.risk_level {
-        rule.risk_level = parse_risk_level(&rl);
+        rule.risk_level = parse_risk(&rl)?;
     }
     if let Some(act) = body.action {
-        rule.action = parse_action(&act);
+        rule.action = parse_action(&act)?;
     }
     if let Some(en) = body.enabled {
         rule.enabled = en;
     }
+    if let Some(category) = body.category {
+        rule.category = Some(category).filter(|c| !c.trim().is_empty());
+    }
+    if let Some(tags) = body.tags {
+        rule.tags = tags;
+    }
+    if let Some(priority) = body.priority {
+        rule.priority = priority;
+    }
+    if matching_changed {
+        validate_rule(&mut rule)?;
+    }
 
-    let resp = RuleResponse::from_rule(rule, PRESET_RULE_NAMES);
+    let resp = RuleResponse::from_rule(&rule, PRESET_RULE_NAMES);
+    record_rule_change(
+        &state,
+        &name,
+        rule_history::UPDATE,
+        &changed_by(&headers),
+        Some(slot),
+        Some(&rule),
+    );
+    *slot = rule;
+    state.proxy.set_rules(rules.clone());
     Ok(Json(resp))
 }
 
 pub async fn delete_rule(
     State(state): State<Arc<AppState>>,
     Path(name): Path<String>,
+    headers: HeaderMap,
 ) -> StatusCode {
     // Prevent deleting preset or protected rules
     if PRESET_RULE_NAMES.contains(&name.as_str()) {
@@ -330,13 +1154,77 @@ pub async fn delete_rule(
     }
 
     let mut rules = state.rules.write().await;
-    let len_before = rules.len();
-    rules.retain(|r| r.name != name);
-    if rules.len() == len_before {
-        StatusCode::NOT_FOUND
-    } else {
-        StatusCode::NO_CONTENT
+    let Some(index) = rules.iter().position(|r| r.name == name) else {
+        return StatusCode::NOT_FOUND;
+    };
+    let removed = rules.remove(index);
+    record_rule_change(
+        &state,
+        &name,
+        rule_history::DELETE,
+        &changed_by(&headers),
+        Some(&removed),
+        None,
+    );
+    state.proxy.set_rules(rules.clone());
+    StatusCode::NO_CONTENT
+}
+
+/// Versions of a rule, oldest first
+pub async fn get_rule_history(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<RuleVersion>>, StatusCode> {
+    let db = state.db.get().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let history = db.rule_history(&name).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(history))
+}
+
+#[derive(Deserialize)]
+pub struct RuleRollbackRequest {
+    pub version: u32,
+}
+
+/// Put a rule back as it was at an earlier version, recreating it if it
+/// was deleted since
+pub async fn rollback_rule(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<RuleRollbackRequest>,
+) -> Result<Json<RuleResponse>, StatusCode> {
+    let version = state
+        .db
+        .get()
+        .and_then(|db| db.rule_version(&name, body.version))
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // A version recording a delete has no rule to go back to
+    let mut restored = version.rule.ok_or(StatusCode::BAD_REQUEST)?;
+    restored.compile().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let mut rules = state.rules.write().await;
+    let existing = rules.iter().position(|r| r.name == name);
+    if existing.is_some_and(|i| rules[i].protected) {
+        return Err(StatusCode::FORBIDDEN);
     }
+    let before = existing.map(|i| rules[i].clone());
+    record_rule_change(
+        &state,
+        &name,
+        rule_history::ROLLBACK,
+        &changed_by(&headers),
+        before.as_ref(),
+        Some(&restored),
+    );
+
+    let resp = RuleResponse::from_rule(&restored, PRESET_RULE_NAMES);
+    match existing {
+        Some(i) => rules[i] = restored,
+        None => rules.push(restored),
+    }
+    state.proxy.set_rules(rules.clone());
+    Ok(Json(resp))
 }
 
 #[derive(Deserialize)]
@@ -369,8 +1257,96 @@ pub async fn test_rule(
                 }))
             }
         }
-        Err(_) => Err(StatusCode::BAD_REQUEST),
-    }
+        Err(_) => Err(StatusCode::BAD_REQUEST),
+    }
+}
+
+// ============================================================================
+// Simulation ("what if")
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct SimulateRequest {
+    pub action_type: ActionType,
+    pub content: String,
+    pub target: Option<String>,
+    pub agent: Option<String>,
+    pub session_id: Option<String>,
+    /// Tool name shown in the block message (defaults to the action type)
+    pub tool_name: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SimulatedProxy {
+    pub behavior: ProxyBehavior,
+    /// Rule the proxy acted on
+    pub rule: Option<String>,
+    /// Text the tool call would be replaced with
+    pub message: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SimulateResponse {
+    pub analysis: AnalysisResult,
+    pub monitor: SimulatedProxy,
+    pub enforce: SimulatedProxy,
+    /// Mode the proxy is configured with now
+    pub current_mode: String,
+}
+
+/// Evaluate a hypothetical action against the current rules without
+/// storing or alerting anything
+pub async fn simulate_action(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<SimulateRequest>,
+) -> Json<SimulateResponse> {
+    let action = AgentAction {
+        id: format!("simulate-{}", uuid::Uuid::new_v4()),
+        timestamp: chrono::Utc::now(),
+        agent: body
+            .agent
+            .as_deref()
+            .map(AgentType::from_name)
+            .unwrap_or(AgentType::Unknown),
+        action_type: body.action_type,
+        content: body.content,
+        target: body.target,
+        session_id: body.session_id,
+        metadata: None,
+    };
+    let tool_name = body
+        .tool_name
+        .unwrap_or_else(|| action.action_type.to_string());
+
+    let rules = state.rules.read().await;
+    let analysis = Analyzer::new(rules.clone()).analyze(&action);
+    let intercept = check_action(0, &tool_name, &action, &rules);
+    drop(rules);
+    let messages = MessageOptions {
+        canary_tokens: false,
+        coaching: state.proxy_config.read().await.coaching,
+    };
+
+    let simulate = |enforce: bool| {
+        let behavior = interceptor::behavior(intercept.as_ref(), enforce);
+        let mut intercept = intercept.clone();
+        SimulatedProxy {
+            behavior,
+            rule: intercept.as_ref().map(|i| i.rule_name.clone()),
+            message: intercept
+                .as_mut()
+                .filter(|_| behavior == ProxyBehavior::Replace)
+                .map(|i| interceptor::block_message(i, messages)),
+        }
+    };
+    let current_mode = format!("{:?}", state.proxy.mode()).to_lowercase();
+
+    Json(SimulateResponse {
+        monitor: simulate(false),
+        enforce: simulate(true),
+        analysis,
+        current_mode,
+    })
 }
 
 // ============================================================================
@@ -395,7 +1371,7 @@ pub async fn get_proxy_status(State(state): State<Arc<AppState>>) -> Json<ProxyS
 
     Json(ProxyStatusResponse {
         running: config.enabled,
-        mode: format!("{:?}", config.mode).to_lowercase(),
+        mode: format!("{:?}", state.proxy.mode()).to_lowercase(),
         listen: config.listen.clone(),
         target: config.target.clone(),
         uptime_seconds: uptime,
@@ -411,13 +1387,19 @@ pub struct UpdateProxyConfigRequest {
 pub async fn update_proxy_config(
     State(state): State<Arc<AppState>>,
     Json(body): Json<UpdateProxyConfigRequest>,
-) -> Json<ProxyStatusResponse> {
+) -> Result<Json<ProxyStatusResponse>, StatusCode> {
+    // An unknown mode must not quietly turn enforcement off
+    let mode = match body.mode.as_deref().map(|m| m.trim().to_lowercase()) {
+        None => None,
+        Some(m) if m == "enforce" => Some(crate::proxy::config::ProxyMode::Enforce),
+        Some(m) if m == "monitor" => Some(crate::proxy::config::ProxyMode::Monitor),
+        Some(_) => return Err(StatusCode::BAD_REQUEST),
+    };
     let mut config = state.proxy_config.write().await;
-    if let Some(mode) = body.mode {
-        config.mode = match mode.to_lowercase().as_str() {
-            "enforce" => crate::proxy::config::ProxyMode::Enforce,
-            _ => crate::proxy::config::ProxyMode::Monitor,
-        };
+    if let Some(mode) = mode {
+        config.mode = mode;
+        // Takes effect on the proxy's next request
+        state.proxy.set_mode(config.mode);
     }
     if let Some(enabled) = body.enabled {
         config.enabled = enabled;
@@ -428,13 +1410,13 @@ pub async fn update_proxy_config(
         .num_seconds()
         .max(0) as u64;
 
-    Json(ProxyStatusResponse {
+    Ok(Json(ProxyStatusResponse {
         running: config.enabled,
-        mode: format!("{:?}", config.mode).to_lowercase(),
+        mode: format!("{:?}", state.proxy.mode()).to_lowercase(),
         listen: config.listen.clone(),
         target: config.target.clone(),
         uptime_seconds: uptime,
-    })
+    }))
 }
 
 // ============================================================================
@@ -446,26 +1428,63 @@ pub struct ProviderResponse {
     pub name: String,
     pub enabled: bool,
     pub target_url: String,
-}
-
-pub async fn get_providers(State(_state): State<Arc<AppState>>) -> Json<Vec<ProviderResponse>> {
-    Json(vec![
-        ProviderResponse {
-            name: "Anthropic".to_string(),
-            enabled: true,
-            target_url: "https://api.anthropic.com".to_string(),
-        },
-        ProviderResponse {
-            name: "OpenAI".to_string(),
-            enabled: false,
-            target_url: "https://api.openai.com".to_string(),
-        },
-        ProviderResponse {
-            name: "Gemini".to_string(),
+    /// Health of this upstream, when the proxy is using it
+    pub health: Option<UpstreamHealth>,
+    /// Fallback upstreams configured for it, in failover order
+    pub fallbacks: Vec<UpstreamHealth>,
+}
+
+pub async fn get_providers(State(state): State<Arc<AppState>>) -> Json<Vec<ProviderResponse>> {
+    let mut providers: Vec<ProviderResponse> = [
+        ("Anthropic", "anthropic"),
+        ("OpenAI", "openai"),
+        ("Gemini", "gemini"),
+    ]
+    .into_iter()
+    .filter_map(|(name, preset)| {
+        Some(ProviderResponse {
+            name: name.to_string(),
             enabled: false,
-            target_url: "https://generativelanguage.googleapis.com".to_string(),
-        },
-    ])
+            target_url: crate::proxy::config::preset_target(preset)?.to_string(),
+            health: None,
+            fallbacks: vec![],
+        })
+    })
+    .collect();
+
+    let mut upstreams = state
+        .db
+        .get()
+        .and_then(|db| db.get_provider_health())
+        .unwrap_or_default();
+    if upstreams.is_empty() {
+        // No proxy has reported yet: show the configured target
+        let target = state.proxy_config.read().await.target.clone();
+        for p in &mut providers {
+            p.enabled = p.target_url == target;
+        }
+        return Json(providers);
+    }
+
+    let primary = upstreams.remove(0);
+    let index = match providers.iter().position(|p| p.target_url == primary.target) {
+        Some(i) => i,
+        None => {
+            providers.push(ProviderResponse {
+                name: "Custom".to_string(),
+                enabled: false,
+                target_url: primary.target.clone(),
+                health: None,
+                fallbacks: vec![],
+            });
+            providers.len() - 1
+        }
+    };
+    let provider = &mut providers[index];
+    provider.enabled = true;
+    provider.health = Some(primary);
+    provider.fallbacks = upstreams;
+    Json(providers)
 }
 
 // ============================================================================
@@ -481,17 +1500,29 @@ pub struct AlertConfigResponse {
     pub slack_webhook: Option<String>,
     pub discord_enabled: bool,
     pub discord_webhook: Option<String>,
+    /// Desktop notifications, when a graphical session is present
+    #[serde(default = "default_true")]
+    pub desktop_enabled: bool,
     pub notify_on_critical: bool,
     pub notify_on_warning: bool,
     pub notify_on_info: bool,
 }
 
+impl AlertConfigResponse {
+    /// Credential fields, by keyring entry
+    fn secrets_mut(&mut self) -> [(&'static str, &mut Option<String>); 3] {
+        [
+            (secrets::TELEGRAM_BOT_TOKEN, &mut self.telegram_bot_token),
+            (secrets::SLACK_WEBHOOK, &mut self.slack_webhook),
+            (secrets::DISCORD_WEBHOOK, &mut self.discord_webhook),
+        ]
+    }
+}
+
 pub async fn get_alert_config(State(_state): State<Arc<AppState>>) -> Json<AlertConfigResponse> {
-    // Check env vars first, then config file
+    // Check env vars and the keyring first, then config file
 
-    let token = std::env::var("OPENCLAW_HARNESS_TELEGRAM_BOT_TOKEN")
-        .or_else(|_| std::env::var("SAFEBOT_TELEGRAM_BOT_TOKEN"))
-        .ok();
+    let token = secrets::lookup(secrets::keyring(), secrets::TELEGRAM_BOT_TOKEN);
     let chat_id = std::env::var("OPENCLAW_HARNESS_TELEGRAM_CHAT_ID")
         .or_else(|_| std::env::var("SAFEBOT_TELEGRAM_CHAT_ID"))
         .ok();
@@ -513,12 +1544,17 @@ pub async fn get_alert_config(State(_state): State<Arc<AppState>>) -> Json<Alert
             .as_ref()
             .map(|c| c.slack_enabled)
             .unwrap_or(false),
-        slack_webhook: file_config.as_ref().and_then(|c| c.slack_webhook.clone()),
+        slack_webhook: file_config
+            .as_ref()
+            .and_then(|c| c.slack_webhook.as_deref().map(mask_token)),
         discord_enabled: file_config
             .as_ref()
             .map(|c| c.discord_enabled)
             .unwrap_or(false),
-        discord_webhook: file_config.as_ref().and_then(|c| c.discord_webhook.clone()),
+        discord_webhook: file_config
+            .as_ref()
+            .and_then(|c| c.discord_webhook.as_deref().map(mask_token)),
+        desktop_enabled: file_config.as_ref().is_none_or(|c| c.desktop_enabled),
         notify_on_critical: true,
         notify_on_warning: true,
         notify_on_info: false,
@@ -548,6 +1584,122 @@ pub async fn update_alert_config(
     StatusCode::OK
 }
 
+/// Channels as alerts would be sent right now: environment and keyring
+/// first, then `alerts.json` in the harness directory
+pub fn configured_alerts() -> AlertConfig {
+    let file_config = load_alert_config_from_file();
+    let file = file_config.as_ref();
+    let telegram = secrets::lookup(secrets::keyring(), secrets::TELEGRAM_BOT_TOKEN)
+        .or_else(|| file.and_then(|c| c.telegram_bot_token.clone()))
+        .zip(
+            std::env::var("OPENCLAW_HARNESS_TELEGRAM_CHAT_ID")
+                .or_else(|_| std::env::var("SAFEBOT_TELEGRAM_CHAT_ID"))
+                .ok()
+                .or_else(|| file.and_then(|c| c.telegram_chat_id.clone())),
+        )
+        .filter(|(token, chat_id)| !token.is_empty() && !chat_id.is_empty())
+        .map(|(bot_token, chat_id)| TelegramConfig { bot_token, chat_id });
+    let webhook = |enabled: bool, url: &Option<String>| {
+        url.clone().filter(|u| enabled && !u.is_empty())
+    };
+    AlertConfig {
+        telegram,
+        slack: file
+            .and_then(|c| webhook(c.slack_enabled, &c.slack_webhook))
+            .map(|webhook_url| SlackConfig { webhook_url }),
+        discord: file
+            .and_then(|c| webhook(c.discord_enabled, &c.discord_webhook))
+            .map(|webhook_url| DiscordConfig { webhook_url }),
+        desktop: desktop::configured(file.is_none_or(|c| c.desktop_enabled)),
+        quiet_hours: Default::default(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct AlertTestRequest {
+    /// Only this channel (telegram, slack, discord, desktop); all configured
+    /// if absent
+    #[serde(default)]
+    pub channel: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct AlertTestResponse {
+    pub results: Vec<ChannelTest>,
+}
+
+/// Send a test message through the configured alert channels
+pub async fn test_alerts(
+    State(_state): State<Arc<AppState>>,
+    Json(body): Json<AlertTestRequest>,
+) -> Result<Json<AlertTestResponse>, StatusCode> {
+    let alerter = Alerter::new(configured_alerts());
+    let results = alerter
+        .test_channels(body.channel.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::warn!("Alert test rejected: {}", e);
+            StatusCode::BAD_REQUEST
+        })?;
+    Ok(Json(AlertTestResponse { results }))
+}
+
+/// How long `POST /alerts/pause` holds alerts without `minutes`
+const DEFAULT_PAUSE_MINUTES: u32 = 30;
+
+#[derive(Deserialize, Default)]
+pub struct AlertPauseRequest {
+    /// How long to hold alerts (default 30 minutes)
+    #[serde(default)]
+    pub minutes: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AlertPauseResponse {
+    /// Alerts other than Critical are held until then; None when not paused
+    pub paused_until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+pub async fn get_alert_pause(State(state): State<Arc<AppState>>) -> Json<AlertPauseResponse> {
+    Json(AlertPauseResponse {
+        paused_until: state.alerter.as_ref().and_then(|a| a.paused_until()),
+    })
+}
+
+/// Hold alerts for a while, as `/harness pause` does in Slack
+pub async fn pause_alerts(
+    State(state): State<Arc<AppState>>,
+    body: Option<Json<AlertPauseRequest>>,
+) -> Result<Json<AlertPauseResponse>, StatusCode> {
+    let Some(alerter) = state.alerter.as_ref() else {
+        tracing::warn!("Alert pause rejected: no alert channels are configured");
+        return Err(StatusCode::CONFLICT);
+    };
+    let minutes = body
+        .and_then(|Json(b)| b.minutes)
+        .unwrap_or(DEFAULT_PAUSE_MINUTES);
+    if minutes == 0 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let until = chrono::Utc::now() + chrono::Duration::minutes(minutes.into());
+    alerter.pause(Some(until));
+    tracing::info!(
+        "⏸️ Alerts paused until {} (Critical alerts still go out)",
+        until.to_rfc3339()
+    );
+    Ok(Json(AlertPauseResponse {
+        paused_until: Some(until),
+    }))
+}
+
+pub async fn resume_alerts(State(state): State<Arc<AppState>>) -> Json<AlertPauseResponse> {
+    if let Some(alerter) = state.alerter.as_ref() {
+        alerter.pause(None);
+        tracing::info!("▶️ Alerts resumed");
+    }
+    Json(AlertPauseResponse { paused_until: None })
+}
+
 fn mask_token(token: &str) -> String {
     if token.len() <= 8 {
         "****".to_string()
@@ -557,22 +1709,172 @@ fn mask_token(token: &str) -> String {
 }
 
 fn load_alert_config_from_file() -> Option<AlertConfigResponse> {
-    let path = std::path::Path::new("config/alerts.json");
-    let content = std::fs::read_to_string(path).ok()?;
-    serde_json::from_str(&content).ok()
+    let path = secrets::alerts_path();
+    let store = secrets::keyring();
+    let legacy = StdPath::new(secrets::LEGACY_ALERTS_FILE);
+    if let Err(e) = move_legacy_alert_config(legacy, &path, store) {
+        tracing::warn!("⚠️ Failed to move {}: {}", secrets::LEGACY_ALERTS_FILE, e);
+    }
+    load_alert_config(&path, store)
 }
 
 fn save_alert_config_to_file(config: &AlertConfigResponse) -> anyhow::Result<()> {
-    std::fs::create_dir_all("config")?;
-    let content = serde_json::to_string_pretty(config)?;
-    std::fs::write("config/alerts.json", content)?;
+    write_alert_config(&secrets::alerts_path(), config, secrets::keyring())
+}
+
+/// Move alert credentials from the environment and the alert config file
+/// into `store`; returns the environment variables that can now be unset
+pub fn migrate_alert_secrets(store: &dyn SecretStore) -> anyhow::Result<Vec<&'static str>> {
+    let path = secrets::alerts_path();
+    let vars = secrets::migrate_env(store)?;
+    move_legacy_alert_config(StdPath::new(secrets::LEGACY_ALERTS_FILE), &path, Some(store))?;
+    move_file_secrets(&path, store)?;
+    Ok(vars)
+}
+
+/// Move the alert config out of the workspace (`legacy`) to `path`,
+/// unless there is one at `path` already
+fn move_legacy_alert_config(
+    legacy: &StdPath,
+    path: &StdPath,
+    store: Option<&dyn SecretStore>,
+) -> anyhow::Result<()> {
+    let Some(config) = read_alert_config(legacy) else {
+        return Ok(());
+    };
+    if path.exists() {
+        tracing::warn!(
+            "⚠️ {} is no longer used (the alert config is {}); delete it, it may hold \
+             credentials",
+            legacy.display(),
+            path.display()
+        );
+        return Ok(());
+    }
+    write_alert_config(path, &config, store)?;
+    std::fs::remove_file(legacy)?;
+    tracing::info!("🔐 Moved {} to {}", legacy.display(), path.display());
     Ok(())
 }
 
+fn read_alert_config(path: &StdPath) -> Option<AlertConfigResponse> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// The alert config at `path`, with credentials kept in `store` filled in.
+/// Credentials still in the file are moved into `store` first. A file
+/// changed outside the harness is not used.
+fn load_alert_config(
+    path: &StdPath,
+    store: Option<&dyn SecretStore>,
+) -> Option<AlertConfigResponse> {
+    match secrets::check_seal(path) {
+        Seal::Broken => {
+            tracing::error!(
+                "🚨 {} was changed outside OpenClaw Harness; it is ignored until saved again",
+                path.display()
+            );
+            return None;
+        }
+        Seal::Missing if path.exists() => match secrets::seal(path) {
+            Ok(()) => tracing::info!("🔏 Recorded the digest of {}", path.display()),
+            Err(e) => tracing::warn!("⚠️ Failed to seal {}: {}", path.display(), e),
+        },
+        _ => {}
+    }
+    if let Some(store) = store {
+        if let Err(e) = move_file_secrets(path, store) {
+            tracing::warn!(
+                "⚠️ Failed to move alert credentials into the {}: {}",
+                store.name(),
+                e
+            );
+        }
+    }
+    let mut config = read_alert_config(path)?;
+    if let Some(store) = store {
+        for (key, value) in config.secrets_mut() {
+            if value.is_none() {
+                *value = store.get(key).ok().flatten();
+            }
+        }
+    }
+    Some(config)
+}
+
+/// Rewrite `path` without the raw credentials it holds, putting them in
+/// `store`; whether there were any
+fn move_file_secrets(path: &StdPath, store: &dyn SecretStore) -> anyhow::Result<bool> {
+    if secrets::check_seal(path) == Seal::Broken {
+        anyhow::bail!("{} was changed outside OpenClaw Harness", path.display());
+    }
+    let Some(mut config) = read_alert_config(path) else {
+        return Ok(false);
+    };
+    let raw = config
+        .secrets_mut()
+        .iter()
+        .any(|(_, v)| v.as_deref().is_some_and(|v| !secrets::is_masked(v)));
+    if raw {
+        write_alert_config(path, &config, Some(store))?;
+        tracing::info!(
+            "🔐 Moved alert credentials from {} into the {}",
+            path.display(),
+            store.name()
+        );
+    }
+    Ok(raw)
+}
+
+/// Write the alert config to `path`, readable by the owner only, and seal
+/// it. With a `store`, credentials go there and never into the file. A
+/// masked credential (as `GET` shows it) keeps the one stored.
+fn write_alert_config(
+    path: &StdPath,
+    config: &AlertConfigResponse,
+    store: Option<&dyn SecretStore>,
+) -> anyhow::Result<()> {
+    let previous = Some(path)
+        .filter(|p| secrets::check_seal(p) != Seal::Broken)
+        .and_then(read_alert_config)
+        .map_or([None, None, None], |mut p| p.secrets_mut().map(|(_, v)| v.take()));
+    let mut config = config.clone();
+    for ((key, value), stored) in config.secrets_mut().into_iter().zip(previous) {
+        if value.as_deref().is_some_and(secrets::is_masked) {
+            *value = stored.filter(|v| !secrets::is_masked(v));
+            if value.is_none() {
+                // Already in the keyring
+                continue;
+            }
+        }
+        if let Some(store) = store {
+            match value.take().filter(|v| !v.is_empty()) {
+                Some(v) => store.set(key, &v)?,
+                None => store.delete(key)?,
+            }
+        }
+    }
+    secrets::write_private(path, serde_json::to_string_pretty(&config)?.as_bytes())?;
+    secrets::seal(path)
+}
+
 fn brain_data_base_dir() -> PathBuf {
-    std::env::var("SAFEBOT_DATA_DIR")
-        .map(PathBuf::from)
-        .unwrap_or_else(|_| PathBuf::from("/Volumes/formac/proj/safebot-data"))
+    crate::brain::data_dir()
+}
+
+/// Where reports and ontology snapshots of `workspace` (or of every
+/// action) live
+fn brain_partition_dir(workspace: Option<&str>) -> PathBuf {
+    workspace::partition_dir(&brain_data_base_dir(), workspace)
+}
+
+/// A requested workspace; 400 if it is not a valid id
+fn workspace_param(workspace_id: &Option<String>) -> Result<Option<&str>, StatusCode> {
+    match workspace_id.as_deref().map(str::trim) {
+        Some(id) if !workspace::valid_id(id) => Err(StatusCode::BAD_REQUEST),
+        id => Ok(id),
+    }
 }
 
 // ============================================================================
@@ -585,34 +1887,171 @@ pub struct AdaptiveCampaignRequest {
     pub max_points_per_mission: u32,
     pub min_completion_probability: Option<f32>,
     pub max_expected_hours: Option<f32>,
+    /// `llm` or `heuristic`; defaults to `OPENCLAW_HARNESS_CAMPAIGN_PLANNER`
+    pub planner: Option<PlannerKind>,
+    /// Give up after this long, LLM retries included
+    pub timeout_secs: Option<u64>,
 }
 
 #[derive(Serialize)]
 pub struct AdaptiveCampaignResponse {
     pub ok: bool,
+    pub planner: PlannerKind,
     pub mission: MissionPlan,
 }
 
+#[derive(Serialize)]
+pub struct CampaignErrorResponse {
+    pub ok: bool,
+    /// `CampaignError::kind`, e.g. `planner_unavailable` or `timeout`
+    pub error: String,
+    pub message: String,
+}
+
+const CAMPAIGN_TIMEOUT_SECS: u64 = 90;
+const MAX_CAMPAIGN_TIMEOUT_SECS: u64 = 300;
+
+fn campaign_error(e: CampaignError) -> (StatusCode, Json<CampaignErrorResponse>) {
+    let status = match &e {
+        CampaignError::PlannerUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        CampaignError::NoHistory(_) | CampaignError::Rejected(_) => {
+            StatusCode::UNPROCESSABLE_ENTITY
+        }
+        CampaignError::Llm(_) | CampaignError::InvalidDraft { .. } => StatusCode::BAD_GATEWAY,
+        CampaignError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        CampaignError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    if status.is_server_error() {
+        tracing::warn!("Campaign generation failed: {}", e);
+    }
+    let body = CampaignErrorResponse {
+        ok: false,
+        error: e.kind().to_string(),
+        message: e.to_string(),
+    };
+    (status, Json(body))
+}
+
+/// Plans run on the async LLM client; a client that disconnects or a
+/// request that runs out of time cancels the LLM call in flight
 pub async fn generate_adaptive_campaign(
     State(state): State<Arc<AppState>>,
     Json(body): Json<AdaptiveCampaignRequest>,
-) -> Result<Json<AdaptiveCampaignResponse>, StatusCode> {
+) -> Result<Json<AdaptiveCampaignResponse>, (StatusCode, Json<CampaignErrorResponse>)> {
     let constraints = CampaignConstraints {
         max_points_per_mission: body.max_points_per_mission,
         min_completion_probability: body.min_completion_probability.unwrap_or(0.35),
         max_expected_hours: body.max_expected_hours.unwrap_or(3.0),
     };
 
-    let conn = rusqlite::Connection::open(&state.db_path)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let history = state
+        .db
+        .connection()
+        .and_then(|conn| load_history(&conn, &body.user_id))
+        .map_err(|e| campaign_error(e.into()))?;
 
-    let planner = LlmAiPlanner::from_env().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let kind = body.planner.unwrap_or_else(PlannerKind::from_env);
+    let planner = kind
+        .planner(Some(state.db.clone()))
+        .map_err(campaign_error)?;
     let engine = CampaignEngine::new(planner);
-    let mission = engine
-        .generate_mission(&conn, &body.user_id, &constraints)
-        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    let timeout = std::time::Duration::from_secs(
+        body.timeout_secs
+            .unwrap_or(CAMPAIGN_TIMEOUT_SECS)
+            .clamp(1, MAX_CAMPAIGN_TIMEOUT_SECS),
+    );
+    let mission = tokio::time::timeout(
+        timeout,
+        engine.generate_mission(&body.user_id, &history, &constraints),
+    )
+    .await
+    .unwrap_or(Err(CampaignError::Timeout(timeout)))
+    .map_err(campaign_error)?;
+
+    Ok(Json(AdaptiveCampaignResponse {
+        ok: true,
+        planner: kind,
+        mission,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct IngestBehavioursRequest {
+    pub records: Vec<NewBehaviour>,
+}
 
-    Ok(Json(AdaptiveCampaignResponse { ok: true, mission }))
+#[derive(Serialize)]
+pub struct IngestBehavioursResponse {
+    pub ok: bool,
+    pub inserted: usize,
+}
+
+pub async fn ingest_behaviours(
+    State(state): State<Arc<AppState>>,
+    Json(body): Json<IngestBehavioursRequest>,
+) -> Result<Json<IngestBehavioursResponse>, StatusCode> {
+    let conn = state
+        .db
+        .connection()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let inserted = behaviours::insert(&conn, &body.records, chrono::Utc::now()).map_err(|e| {
+        tracing::debug!("Rejected behaviours: {}", e);
+        StatusCode::BAD_REQUEST
+    })?;
+    Ok(Json(IngestBehavioursResponse { ok: true, inserted }))
+}
+
+#[derive(Deserialize)]
+pub struct BehavioursQuery {
+    pub user_id: String,
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct BehavioursResponse {
+    pub stats: UserBehaviourStats,
+    pub records: Vec<BehaviourRecord>,
+}
+
+pub async fn get_behaviours(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<BehavioursQuery>,
+) -> Result<Json<BehavioursResponse>, StatusCode> {
+    let conn = state
+        .db
+        .connection()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let limit = query.limit.unwrap_or(200).min(1000);
+    let records = behaviours::list(&conn, &query.user_id, limit)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(BehavioursResponse {
+        stats: compute_stats(&query.user_id, &records),
+        records,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct DeleteBehavioursQuery {
+    pub user_id: String,
+}
+
+#[derive(Serialize)]
+pub struct DeleteBehavioursResponse {
+    pub ok: bool,
+    pub deleted: usize,
+}
+
+pub async fn delete_behaviours(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DeleteBehavioursQuery>,
+) -> Result<Json<DeleteBehavioursResponse>, StatusCode> {
+    let conn = state
+        .db
+        .connection()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let deleted = behaviours::delete_user(&conn, &query.user_id)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(DeleteBehavioursResponse { ok: true, deleted }))
 }
 
 #[derive(Serialize)]
@@ -628,14 +2067,23 @@ pub struct BuildOntologyV2Response {
     pub insights: BrainInsights,
 }
 
+#[derive(Deserialize)]
+pub struct WorkspaceQuery {
+    pub workspace_id: Option<String>,
+}
+
 pub async fn build_ontology_v1(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<WorkspaceQuery>,
 ) -> Result<Json<BuildOntologyResponse>, StatusCode> {
-    let conn = rusqlite::Connection::open(&state.db_path)
+    let workspace = workspace_param(&query.workspace_id)?;
+    let conn = state
+        .db
+        .connection()
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let (nodes, edges) =
-        build_ontology_from_db(&conn).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let summary = persist_ontology(&brain_data_base_dir(), &nodes, &edges)
+    let (nodes, edges) = build_ontology_scoped(&conn, workspace)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let summary = persist_ontology(&brain_partition_dir(workspace), &nodes, &edges)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(Json(BuildOntologyResponse { ok: true, summary }))
@@ -643,12 +2091,17 @@ pub async fn build_ontology_v1(
 
 pub async fn build_ontology_v2(
     State(state): State<Arc<AppState>>,
+    Query(query): Query<WorkspaceQuery>,
 ) -> Result<Json<BuildOntologyV2Response>, StatusCode> {
-    let conn = rusqlite::Connection::open(&state.db_path)
+    let workspace = workspace_param(&query.workspace_id)?;
+    let conn = state
+        .db
+        .connection()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let (nodes, edges, insights) = build_ontology_v2_scoped(&conn, workspace)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let (nodes, edges, insights) =
-        build_ontology_v2_from_db(&conn).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    let summary = persist_ontology_v2(&brain_data_base_dir(), &nodes, &edges, &insights)
+    let dir = brain_partition_dir(workspace);
+    let summary = persist_ontology_v2(&dir, &nodes, &edges, &insights)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(Json(BuildOntologyV2Response {
@@ -662,6 +2115,7 @@ pub async fn build_ontology_v2(
 pub struct BrainQueryRequest {
     pub query_type: String,
     pub limit: Option<usize>,
+    pub workspace_id: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -669,6 +2123,7 @@ pub struct BrainSearchRequest {
     pub keyword: String,
     pub kinds: Option<Vec<String>>,
     pub limit: Option<usize>,
+    pub workspace_id: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -706,8 +2161,8 @@ fn load_jsonl(path: &StdPath) -> Result<Vec<serde_json::Value>, StatusCode> {
 pub async fn query_brain_v2(
     Json(body): Json<BrainQueryRequest>,
 ) -> Result<Json<BrainQueryResponse>, StatusCode> {
-    let base_dir = brain_data_base_dir();
-    let base = base_dir.join("ontology").join("v2");
+    let workspace = workspace_param(&body.workspace_id)?;
+    let base = brain_partition_dir(workspace).join("ontology").join("v2");
     let nodes_path = base.join("nodes.jsonl");
     let insights_path = base.join("insights.json");
 
@@ -743,6 +2198,12 @@ pub async fn query_brain_v2(
             .filter(|v| v["kind"] == "Decision")
             .take(limit)
             .collect(),
+        "decision_notes" => rows
+            .clone()
+            .into_iter()
+            .filter(|v| v["kind"] == "DecisionNote")
+            .take(limit)
+            .collect(),
         "automation_opportunities" => rows
             .clone()
             .into_iter()
@@ -829,8 +2290,11 @@ pub async fn query_brain_v2(
     }))
 }
 
-pub async fn get_brain_graph_v2() -> Result<Json<BrainGraphResponse>, StatusCode> {
-    let base = brain_data_base_dir().join("ontology").join("v2");
+pub async fn get_brain_graph_v2(
+    Query(query): Query<WorkspaceQuery>,
+) -> Result<Json<BrainGraphResponse>, StatusCode> {
+    let workspace = workspace_param(&query.workspace_id)?;
+    let base = brain_partition_dir(workspace).join("ontology").join("v2");
     let nodes = load_jsonl(&base.join("nodes.jsonl"))?;
     let edges = load_jsonl(&base.join("edges.jsonl"))?;
 
@@ -868,7 +2332,9 @@ pub async fn search_brain_v2(
         .map(|k| k.to_lowercase())
         .collect::<Vec<_>>();
 
-    let rows = load_jsonl(&brain_data_base_dir().join("ontology").join("v2").join("nodes.jsonl"))?;
+    let workspace = workspace_param(&body.workspace_id)?;
+    let base = brain_partition_dir(workspace).join("ontology").join("v2");
+    let rows = load_jsonl(&base.join("nodes.jsonl"))?;
     let limit = body.limit.unwrap_or(20);
     let results = rows
         .into_iter()
@@ -902,6 +2368,28 @@ pub async fn search_brain_v2(
     }))
 }
 
+// ============================================================================
+// Workspaces
+// ============================================================================
+
+#[derive(Serialize)]
+pub struct WorkspacesResponse {
+    pub workspaces: Vec<WorkspaceSummary>,
+}
+
+/// Declared workspaces and those with stored actions
+pub async fn list_workspaces(
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<WorkspacesResponse>, StatusCode> {
+    let conn = state
+        .db
+        .connection()
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let workspaces = workspace::summaries(&conn, workspace::registry())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(WorkspacesResponse { workspaces }))
+}
+
 // ============================================================================
 // Brain Reports (Weekly)
 // ============================================================================
@@ -909,6 +2397,7 @@ pub async fn search_brain_v2(
 #[derive(Deserialize)]
 pub struct WeeklyReportQuery {
     pub week: Option<String>, // YYYY-Www
+    pub workspace_id: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -959,10 +2448,15 @@ pub struct WeeklyReportResponse {
     pub week_start: String,
     pub week_end: String,
     pub headline: String,
+    /// Executive summary written by the LLM (see `report_insights`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
     pub activity: WeeklyActivity,
     pub risk: WeeklyRisk,
     pub patterns: Vec<WeeklyPattern>,
     pub next_actions: Vec<String>,
+    /// Where `summary` and `next_actions` came from: `llm` or `heuristic`
+    pub insights: String,
     pub markdown: String,
     pub created_at: String,
 }
@@ -1012,7 +2506,7 @@ fn week_range_kst(
     Ok((format!("{}-W{:02}", year, iso_week), start_utc, end_utc))
 }
 
-fn build_markdown(report: &WeeklyReportResponse) -> String {
+pub(crate) fn build_markdown(report: &WeeklyReportResponse) -> String {
     let mut out = String::new();
     out.push_str(&format!("# Weekly Report {}\n\n", report.report_id));
     out.push_str(&format!("- Headline: {}\n", report.headline));
@@ -1020,6 +2514,9 @@ fn build_markdown(report: &WeeklyReportResponse) -> String {
         "- Range (UTC): {} ~ {}\n\n",
         report.week_start, report.week_end
     ));
+    if let Some(summary) = &report.summary {
+        out.push_str(&format!("## Summary\n{}\n\n", summary));
+    }
     out.push_str("## Activity\n");
     out.push_str(&format!(
         "- Total events: {}\n",
@@ -1047,7 +2544,7 @@ fn build_markdown(report: &WeeklyReportResponse) -> String {
     out
 }
 
-fn persist_weekly_outputs(base_dir: &StdPath, report: &WeeklyReportResponse) -> anyhow::Result<()> {
+pub(crate) fn persist_weekly_outputs(base_dir: &StdPath, report: &WeeklyReportResponse) -> anyhow::Result<()> {
     let weekly_dir = base_dir.join("reports").join("weekly");
     fs::create_dir_all(&weekly_dir)?;
 
@@ -1134,28 +2631,32 @@ fn materialize_ontology_minimal(
     Ok(())
 }
 
-fn compute_weekly_report(
-    db_path: &str,
+pub(crate) fn compute_weekly_report(
+    pool: &DbPool,
     week: Option<String>,
     workspace_id: Option<String>,
 ) -> anyhow::Result<WeeklyReportResponse> {
-    use rusqlite::Connection;
-
     let (report_id, start_utc, end_utc) = week_range_kst(week)?;
-    let workspace = workspace_id.unwrap_or_else(|| "default".to_string());
-    let conn = Connection::open(db_path)?;
+    if let Some(id) = workspace_id.as_deref().filter(|id| !workspace::valid_id(id)) {
+        anyhow::bail!("invalid workspace id {:?}", id);
+    }
+    // Without a workspace the report covers every action
+    let workspace = workspace_id.clone().unwrap_or_else(|| workspace::ALL.to_string());
+    let (start, end) = (start_utc.to_rfc3339(), end_utc.to_rfc3339());
+    let range = rusqlite::params![start, end, workspace_id];
+    let conn = pool.connection()?;
 
     let total_events: u64 = conn.query_row(
-        "SELECT COUNT(*) FROM actions WHERE timestamp BETWEEN ?1 AND ?2",
-        [start_utc.to_rfc3339(), end_utc.to_rfc3339()],
+        "SELECT COUNT(*) FROM actions WHERE timestamp BETWEEN ?1 AND ?2 AND (?3 IS NULL OR workspace = ?3)",
+        range,
         |r| r.get::<_, i64>(0).map(|v| v as u64),
     )?;
 
     let mut projects_map: HashMap<String, u64> = HashMap::new();
     let mut stmt = conn.prepare(
-        "SELECT COALESCE(target, 'unknown'), COUNT(*) FROM actions WHERE timestamp BETWEEN ?1 AND ?2 GROUP BY COALESCE(target, 'unknown') ORDER BY COUNT(*) DESC LIMIT 5",
+        "SELECT COALESCE(target, 'unknown'), COUNT(*) FROM actions WHERE timestamp BETWEEN ?1 AND ?2 AND (?3 IS NULL OR workspace = ?3) GROUP BY COALESCE(target, 'unknown') ORDER BY COUNT(*) DESC LIMIT 5",
     )?;
-    let rows = stmt.query_map([start_utc.to_rfc3339(), end_utc.to_rfc3339()], |row| {
+    let rows = stmt.query_map(range, |row| {
         Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
     })?;
     for row in rows {
@@ -1173,10 +2674,10 @@ fn compute_weekly_report(
         .collect();
 
     let mut top_tools_stmt = conn.prepare(
-        "SELECT action_type, COUNT(*) FROM actions WHERE timestamp BETWEEN ?1 AND ?2 GROUP BY action_type ORDER BY COUNT(*) DESC LIMIT 5",
+        "SELECT action_type, COUNT(*) FROM actions WHERE timestamp BETWEEN ?1 AND ?2 AND (?3 IS NULL OR workspace = ?3) GROUP BY action_type ORDER BY COUNT(*) DESC LIMIT 5",
     )?;
     let top_tools = top_tools_stmt
-        .query_map([start_utc.to_rfc3339(), end_utc.to_rfc3339()], |row| {
+        .query_map(range, |row| {
             Ok(WeeklyToolCount {
                 tool: row.get::<_, String>(0)?,
                 count: row.get::<_, i64>(1)? as u64,
@@ -1185,27 +2686,23 @@ fn compute_weekly_report(
         .filter_map(Result::ok)
         .collect::<Vec<_>>();
 
-    let critical: u64 = conn.query_row(
-        "SELECT COUNT(*) FROM analysis_results WHERE timestamp BETWEEN ?1 AND ?2 AND risk_level='Critical'",
-        [start_utc.to_rfc3339(), end_utc.to_rfc3339()],
-        |r| r.get::<_, i64>(0).map(|v| v as u64),
-    )?;
-    let warning: u64 = conn.query_row(
-        "SELECT COUNT(*) FROM analysis_results WHERE timestamp BETWEEN ?1 AND ?2 AND risk_level='Warning'",
-        [start_utc.to_rfc3339(), end_utc.to_rfc3339()],
-        |r| r.get::<_, i64>(0).map(|v| v as u64),
-    )?;
-    let info: u64 = conn.query_row(
-        "SELECT COUNT(*) FROM analysis_results WHERE timestamp BETWEEN ?1 AND ?2 AND risk_level='Info'",
-        [start_utc.to_rfc3339(), end_utc.to_rfc3339()],
-        |r| r.get::<_, i64>(0).map(|v| v as u64),
-    )?;
+    let count_risk = |level: RiskLevel| -> rusqlite::Result<u64> {
+        conn.query_row(
+            "SELECT COUNT(*) FROM analysis_results r WHERE risk_level = ?4 AND timestamp BETWEEN ?1 AND ?2
+             AND (?3 IS NULL OR EXISTS (SELECT 1 FROM actions a WHERE a.id = r.action_id AND a.workspace = ?3))",
+            rusqlite::params![start, end, workspace_id, level.as_str()],
+            |r| r.get::<_, i64>(0).map(|v| v as u64),
+        )
+    };
+    let critical = count_risk(RiskLevel::Critical)?;
+    let warning = count_risk(RiskLevel::Warning)?;
+    let info = count_risk(RiskLevel::Info)?;
 
     let mut patterns = Vec::new();
     let mut patt_stmt = conn.prepare(
-        "SELECT content, COUNT(*) as c FROM actions WHERE timestamp BETWEEN ?1 AND ?2 GROUP BY content HAVING c >= 3 ORDER BY c DESC LIMIT 3",
+        "SELECT content, COUNT(*) as c FROM actions WHERE timestamp BETWEEN ?1 AND ?2 AND (?3 IS NULL OR workspace = ?3) GROUP BY content HAVING c >= 3 ORDER BY c DESC LIMIT 3",
     )?;
-    let patt_rows = patt_stmt.query_map([start_utc.to_rfc3339(), end_utc.to_rfc3339()], |row| {
+    let patt_rows = patt_stmt.query_map(range, |row| {
         Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
     })?;
     for row in patt_rows {
@@ -1241,6 +2738,7 @@ fn compute_weekly_report(
         week_start: start_utc.to_rfc3339(),
         week_end: end_utc.to_rfc3339(),
         headline,
+        summary: None,
         activity: WeeklyActivity {
             total_events,
             projects,
@@ -1253,6 +2751,7 @@ fn compute_weekly_report(
         },
         patterns,
         next_actions,
+        insights: report_insights::HEURISTIC.to_string(),
         markdown: String::new(),
         created_at: chrono::Utc::now().to_rfc3339(),
     };
@@ -1264,9 +2763,12 @@ pub async fn get_weekly_report(
     State(state): State<Arc<AppState>>,
     Query(query): Query<WeeklyReportQuery>,
 ) -> Result<Json<WeeklyReportResponse>, StatusCode> {
-    compute_weekly_report(&state.db_path, query.week, None)
-        .map(Json)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+    let workspace = workspace_param(&query.workspace_id)?.map(String::from);
+    let dir = brain_partition_dir(workspace.as_deref());
+    let mut report = compute_weekly_report(&state.db, query.week, workspace)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    report_insights::add_insights(&state.db, &dir, &mut report, false).await;
+    Ok(Json(report))
 }
 
 pub async fn generate_weekly_report(
@@ -1274,11 +2776,13 @@ pub async fn generate_weekly_report(
     Json(body): Json<GenerateWeeklyReportRequest>,
 ) -> Result<Json<WeeklyReportResponse>, StatusCode> {
     let _ = body.timezone;
-    let _ = body.force_regenerate;
-    let report = compute_weekly_report(&state.db_path, body.week, body.workspace_id)
+    let workspace = workspace_param(&body.workspace_id)?.map(String::from);
+    let base_dir = brain_partition_dir(workspace.as_deref());
+    let mut report = compute_weekly_report(&state.db, body.week, workspace)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    let base_dir = brain_data_base_dir();
+    let force = body.force_regenerate.unwrap_or(false);
+    report_insights::add_insights(&state.db, &base_dir, &mut report, force).await;
     persist_weekly_outputs(&base_dir, &report).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     materialize_ontology_minimal(&base_dir, &report)
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -1306,6 +2810,7 @@ mod brain_report_tests {
             week_start: "2026-02-23T00:00:00Z".to_string(),
             week_end: "2026-03-01T23:59:59Z".to_string(),
             headline: "test headline".to_string(),
+            summary: None,
             activity: WeeklyActivity {
                 total_events: 10,
                 projects: vec![WeeklyProjectActivity {
@@ -1324,6 +2829,7 @@ mod brain_report_tests {
             },
             patterns: vec![],
             next_actions: vec!["do x".to_string()],
+            insights: "heuristic".to_string(),
             markdown: "# test".to_string(),
             created_at: "2026-02-27T00:00:00Z".to_string(),
         };
@@ -1336,4 +2842,437 @@ mod brain_report_tests {
         assert!(tmp.path().join("ontology/nodes.jsonl").exists());
         assert!(tmp.path().join("ontology/edges.jsonl").exists());
     }
+
+    #[test]
+    fn test_weekly_report_counts_each_risk_level() {
+        let tmp = tempfile::tempdir().unwrap();
+        let pool = DbPool::open(&tmp.path().join("harness.db")).unwrap();
+        let db = pool.get().unwrap();
+        for (i, risk) in [RiskLevel::Critical, RiskLevel::Warning, RiskLevel::Warning, RiskLevel::Info]
+            .into_iter()
+            .enumerate()
+        {
+            let action = AgentAction {
+                id: format!("a{}", i),
+                timestamp: chrono::Utc::now(),
+                agent: AgentType::ClaudeCode,
+                action_type: ActionType::Exec,
+                content: "ls".to_string(),
+                target: None,
+                session_id: None,
+                metadata: None,
+            };
+            db.store_action(&action).unwrap();
+            db.store_analysis(&AnalysisResult {
+                action,
+                matched_rules: vec![],
+                risk_level: risk,
+                recommendation: crate::Recommendation::LogOnly,
+                explanation: String::new(),
+            })
+            .unwrap();
+        }
+
+        let report = compute_weekly_report(&pool, None, None).unwrap();
+        assert_eq!(report.risk.critical, 1);
+        assert_eq!(report.risk.warning, 2);
+        assert_eq!(report.risk.info, 1);
+    }
+}
+
+#[cfg(test)]
+mod alert_config_tests {
+    use super::*;
+    use crate::enforcer::secrets::MemoryStore;
+
+    fn config(token: Option<&str>, slack: Option<&str>) -> AlertConfigResponse {
+        AlertConfigResponse {
+            telegram_enabled: token.is_some(),
+            telegram_bot_token: token.map(String::from),
+            telegram_chat_id: Some("42".to_string()),
+            slack_enabled: slack.is_some(),
+            slack_webhook: slack.map(String::from),
+            discord_enabled: false,
+            discord_webhook: None,
+            desktop_enabled: true,
+            notify_on_critical: true,
+            notify_on_warning: true,
+            notify_on_info: false,
+        }
+    }
+
+    #[test]
+    fn test_alert_credentials_move_into_keyring() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config/alerts.json");
+        let token = "123456:telegram-secret";
+        let slack = "https://hooks.slack.com/services/T0/B0/secret";
+
+        // A file from before the keyring, with raw credentials
+        write_alert_config(&path, &config(Some(token), Some(slack)), None).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains(token));
+
+        let store = MemoryStore::default();
+        let loaded = load_alert_config(&path, Some(&store)).unwrap();
+        assert_eq!(loaded.telegram_bot_token.as_deref(), Some(token));
+        assert_eq!(loaded.slack_webhook.as_deref(), Some(slack));
+        let on_disk = fs::read_to_string(&path).unwrap();
+        assert!(!on_disk.contains(token) && !on_disk.contains("secret"));
+        assert!(on_disk.contains("\"42\""));
+        assert_eq!(
+            store.get(secrets::TELEGRAM_BOT_TOKEN).unwrap().as_deref(),
+            Some(token)
+        );
+
+        // Saving the masked token back keeps it; clearing slack removes it
+        let masked = config(Some(&mask_token(token)), None);
+        write_alert_config(&path, &masked, Some(&store)).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("****"));
+        assert_eq!(
+            store.get(secrets::TELEGRAM_BOT_TOKEN).unwrap().as_deref(),
+            Some(token)
+        );
+        assert_eq!(store.get(secrets::SLACK_WEBHOOK).unwrap(), None);
+        assert!(!move_file_secrets(&path, &store).unwrap());
+    }
+
+    #[test]
+    fn test_alert_config_leaves_workspace_and_is_sealed() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join("workspace/config/alerts.json");
+        let path = dir.path().join("home/.openclaw-harness/alerts.json");
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        let json = serde_json::to_string(&config(Some("123456:secret"), None)).unwrap();
+        fs::write(&legacy, json).unwrap();
+
+        let store = MemoryStore::default();
+        move_legacy_alert_config(&legacy, &path, Some(&store)).unwrap();
+        assert!(!legacy.exists());
+        assert_eq!(secrets::check_seal(&path), Seal::Intact);
+        let loaded = load_alert_config(&path, Some(&store)).unwrap();
+        assert_eq!(loaded.telegram_bot_token.as_deref(), Some("123456:secret"));
+
+        // An agent redirecting alerts to its own chat
+        let tampered = fs::read_to_string(&path).unwrap().replace("\"42\"", "\"666\"");
+        fs::write(&path, tampered).unwrap();
+        assert_eq!(secrets::check_seal(&path), Seal::Broken);
+        assert!(load_alert_config(&path, Some(&store)).is_none());
+        assert!(move_file_secrets(&path, &store).is_err());
+
+        // Saving through the harness seals it again
+        write_alert_config(&path, &config(Some("123456:secret"), None), Some(&store)).unwrap();
+        assert_eq!(
+            load_alert_config(&path, Some(&store)).unwrap().telegram_chat_id.as_deref(),
+            Some("42")
+        );
+
+        // Deleting the digest doesn't get a tampered file sealed
+        fs::remove_file(dir.path().join("home/.openclaw-harness/alerts.json.sha256")).unwrap();
+        let tampered = fs::read_to_string(&path).unwrap().replace("\"42\"", "\"666\"");
+        fs::write(&path, tampered).unwrap();
+        assert!(load_alert_config(&path, Some(&store)).is_none());
+        assert_eq!(secrets::check_seal(&path), Seal::Broken);
+    }
+}
+
+#[cfg(test)]
+mod simulate_tests {
+    use super::*;
+    use crate::proxy::config::{ProxyConfig, ProxyMode};
+    use crate::proxy::control::ProxyControl;
+    use tokio::sync::{broadcast, RwLock};
+
+    #[tokio::test]
+    async fn test_simulate_reports_behavior_per_mode() {
+        let (event_tx, _) = broadcast::channel(4);
+        let dir = tempfile::tempdir().unwrap();
+        let db = DbPool::open(&dir.path().join("harness.db")).unwrap();
+        let state = Arc::new(AppState {
+            event_tx,
+            web: crate::web::WebConfig::from_env(),
+            db_path: db.path().to_string_lossy().to_string(),
+            db,
+            rules: RwLock::new(crate::rules::default_rules()),
+            proxy_config: RwLock::new(ProxyConfig::default()),
+            proxy: ProxyControl::new(ProxyMode::Enforce, crate::rules::default_rules()),
+            collectors: crate::collectors::CollectorManager::new(),
+            alerter: None,
+            started_at: chrono::Utc::now(),
+            stats_cache: std::sync::Mutex::new(None),
+        });
+        let request = |content: &str| SimulateRequest {
+            action_type: ActionType::Exec,
+            content: content.to_string(),
+            target: None,
+            agent: Some("claude_code".to_string()),
+            session_id: None,
+            tool_name: Some("Bash".to_string()),
+        };
+
+        let Json(risky) = simulate_action(State(state.clone()), Json(request("rm -rf /"))).await;
+        assert_eq!(risky.analysis.risk_level, RiskLevel::Critical);
+        assert_eq!(risky.monitor.behavior, ProxyBehavior::Flag);
+        assert_eq!(risky.enforce.behavior, ProxyBehavior::Replace);
+        assert_eq!(risky.enforce.rule.as_deref(), Some("dangerous_rm"));
+        assert!(risky.enforce.message.unwrap().contains("[Bash]"));
+        assert!(risky.monitor.message.is_none());
+
+        let Json(safe) = simulate_action(State(state.clone()), Json(request("ls -la"))).await;
+        assert!(safe.analysis.matched_rules.is_empty());
+        assert_eq!(safe.enforce.behavior, ProxyBehavior::Passthrough);
+        assert_eq!(safe.current_mode, "enforce");
+
+        // Switching mode reaches the shared proxy control
+        let switch = UpdateProxyConfigRequest {
+            mode: Some("monitor".to_string()),
+            enabled: None,
+        };
+        let Json(status) = update_proxy_config(State(state.clone()), Json(switch))
+            .await
+            .unwrap();
+        assert_eq!(status.mode, "monitor");
+        assert_eq!(state.proxy.mode(), ProxyMode::Monitor);
+        let Json(safe) = simulate_action(State(state), Json(request("ls -la"))).await;
+        assert_eq!(safe.current_mode, "monitor");
+    }
+}
+
+#[cfg(test)]
+mod rules_api_tests {
+    use super::*;
+    use crate::proxy::config::{ProxyConfig, ProxyMode};
+    use crate::proxy::control::ProxyControl;
+    use tokio::sync::{broadcast, RwLock};
+
+    fn request(json: serde_json::Value) -> Json<CreateRuleRequest> {
+        Json(serde_json::from_value(json).unwrap())
+    }
+
+    fn test_state(dir: &StdPath, rules: Vec<Rule>) -> Arc<AppState> {
+        let (event_tx, _) = broadcast::channel(4);
+        let db = DbPool::open(&dir.join("harness.db")).unwrap();
+        Arc::new(AppState {
+            event_tx,
+            web: crate::web::WebConfig::from_env(),
+            db_path: db.path().to_string_lossy().to_string(),
+            db,
+            rules: RwLock::new(rules.clone()),
+            proxy_config: RwLock::new(ProxyConfig::default()),
+            proxy: ProxyControl::new(ProxyMode::Enforce, rules),
+            collectors: crate::collectors::CollectorManager::new(),
+            alerter: None,
+            started_at: chrono::Utc::now(),
+            stats_cache: std::sync::Mutex::new(None),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_keyword_and_template_rules_over_http() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path(), vec![]);
+
+        let keyword = request(serde_json::json!({
+            "name": "no_force_push",
+            "match_type": "keyword",
+            "keyword": { "contains": ["git push", "--force"] },
+            "risk_level": "critical",
+            "action": "block"
+        }));
+        let create = |body| create_rule(State(state.clone()), HeaderMap::new(), body);
+        let Json(created) = create(keyword).await.unwrap();
+        assert_eq!(created.match_type, MatchType::Keyword);
+        assert_eq!(created.action, "Block");
+        assert_eq!(created.keyword.unwrap().contains.len(), 2);
+
+        // Same checks as `rules lint`
+        let missing_param = request(serde_json::json!({
+            "name": "etc",
+            "match_type": "template",
+            "template": "protect_path",
+            "risk_level": "warning",
+            "action": "alert"
+        }));
+        let status = create(missing_param).await.err();
+        assert_eq!(status, Some(StatusCode::BAD_REQUEST));
+        let empty_keyword = request(serde_json::json!({
+            "name": "nothing",
+            "match_type": "keyword",
+            "keyword": {},
+            "risk_level": "info",
+            "action": "alert"
+        }));
+        let status = create(empty_keyword).await.err();
+        assert_eq!(status, Some(StatusCode::BAD_REQUEST));
+        // A typo doesn't turn into an info, log-only rule
+        for (risk, action) in [("critcal", "block"), ("critical", "blokc")] {
+            let typo = request(serde_json::json!({
+                "name": "typo",
+                "pattern": "rm -rf",
+                "risk_level": risk,
+                "action": action
+            }));
+            assert_eq!(create(typo).await.err(), Some(StatusCode::BAD_REQUEST));
+        }
+
+        let template = request(serde_json::json!({
+            "name": "etc",
+            "match_type": "template",
+            "template": "protect_path",
+            "params": { "path": "/etc", "operations": ["write"] },
+            "risk_level": "warning",
+            "action": "alert"
+        }));
+        let Json(created) = create(template).await.unwrap();
+        assert_eq!(created.template.as_deref(), Some("protect_path"));
+        assert!(!created.description.is_empty());
+
+        // A rejected edit leaves the rule alone
+        let bad_glob = UpdateRuleRequest {
+            description: None,
+            match_type: None,
+            pattern: None,
+            keyword: Some(KeywordMatch {
+                glob: vec!["[".to_string()],
+                ..Default::default()
+            }),
+            template: None,
+            params: None,
+            risk_level: None,
+            action: None,
+            enabled: None,
+            category: None,
+            tags: None,
+            priority: None,
+        };
+        let path = Path("no_force_push".to_string());
+        let status = update_rule(State(state.clone()), path, HeaderMap::new(), Json(bad_glob))
+            .await
+            .err();
+        assert_eq!(status, Some(StatusCode::BAD_REQUEST));
+
+        let Json(rules) = get_rules(State(state.clone()), Query(RulesQuery::default())).await;
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].keyword.as_ref().unwrap().glob.len(), 0);
+        assert_eq!(state.proxy.rules().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rule_history_and_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut rule = Rule::new(
+            "no_rm",
+            "No rm",
+            r"rm\s+-rf",
+            RiskLevel::Critical,
+            RuleAction::Block,
+        );
+        rule.compile().unwrap();
+        let state = test_state(dir.path(), vec![rule]);
+        let path = || Path("no_rm".to_string());
+        let mut headers = HeaderMap::new();
+        headers.insert(CHANGED_BY_HEADER, "alice".parse().unwrap());
+
+        // The accidental edit
+        let edit: UpdateRuleRequest =
+            serde_json::from_value(serde_json::json!({ "pattern": "rm -rf /nothing" })).unwrap();
+        let Json(edited) = update_rule(State(state.clone()), path(), headers.clone(), Json(edit))
+            .await
+            .unwrap();
+        assert_eq!(edited.pattern, "rm -rf /nothing");
+        let status = delete_rule(State(state.clone()), path(), headers).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+
+        let Json(history) = get_rule_history(State(state.clone()), path()).await.unwrap();
+        let changes: Vec<&str> = history.iter().map(|v| v.change.as_str()).collect();
+        assert_eq!(changes, ["initial", "update", "delete"]);
+        assert_eq!(history[1].changed_by, "alice");
+        assert!(history[1].diff[0].starts_with("pattern:"));
+
+        // Deleted versions can't be restored; earlier ones come back
+        let to = |version| Json(RuleRollbackRequest { version });
+        let status = rollback_rule(State(state.clone()), path(), HeaderMap::new(), to(3)).await;
+        assert_eq!(status.err(), Some(StatusCode::BAD_REQUEST));
+        let status = rollback_rule(State(state.clone()), path(), HeaderMap::new(), to(9)).await;
+        assert_eq!(status.err(), Some(StatusCode::NOT_FOUND));
+        let Json(restored) = rollback_rule(State(state.clone()), path(), HeaderMap::new(), to(1))
+            .await
+            .unwrap();
+        assert_eq!(restored.pattern, r"rm\s+-rf");
+
+        let rules = state.proxy.rules();
+        let rule = rules.iter().find(|r| r.name == "no_rm").unwrap();
+        let action = AgentAction {
+            id: "t".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "rm -rf /tmp/x".to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        };
+        assert!(rule.matches(&action));
+        let Json(history) = get_rule_history(State(state), path()).await.unwrap();
+        assert_eq!(history.last().unwrap().change, "rollback");
+        assert_eq!(history.last().unwrap().changed_by, "api");
+    }
+
+    #[tokio::test]
+    async fn test_template_catalog_and_wizard() {
+        let Json(templates) = get_templates().await;
+        assert_eq!(templates.len(), all_templates().len());
+        let protect = templates.iter().find(|t| t.name == "protect_path").unwrap();
+        let names: Vec<(&str, bool)> = protect
+            .params
+            .iter()
+            .map(|p| (p.spec.name, p.required))
+            .collect();
+        assert_eq!(names, [("path", true), ("operations", false)]);
+        assert!(protect.example.description.contains("~/Documents"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_state(dir.path(), vec![]);
+        let create = |json: serde_json::Value| {
+            let body = serde_json::from_value(json).unwrap();
+            create_rule_from_template(State(state.clone()), HeaderMap::new(), Json(body))
+        };
+        for bad in [
+            serde_json::json!({ "name": "a", "template": "protect_everything" }),
+            serde_json::json!({ "name": "a", "template": "protect_path" }),
+            // Misspelled, or not one of the template's
+            serde_json::json!({ "name": "a", "template": "protect_path", "params": { "p": "/" } }),
+            serde_json::json!({ "name": "a", "template": "block_sudo", "params": { "path": "/" } }),
+            serde_json::json!({
+                "name": "a",
+                "template": "protect_path",
+                "params": { "path": "/etc", "operations": ["chmod"] }
+            }),
+            // Typos aren't weakened to the defaults
+            serde_json::json!({
+                "name": "a",
+                "template": "block_sudo",
+                "action": "blok"
+            }),
+            serde_json::json!({
+                "name": "a",
+                "template": "block_sudo",
+                "risk_level": "critcal"
+            }),
+        ] {
+            let status = create(bad.clone()).await.err();
+            assert_eq!(status, Some(StatusCode::BAD_REQUEST), "{}", bad);
+        }
+
+        let Json(rule) = create(serde_json::json!({
+            "name": "etc",
+            "template": "protect_path",
+            "params": { "paths": ["/etc", "/usr"], "operations": ["write"] }
+        }))
+        .await
+        .unwrap();
+        assert_eq!(rule.match_type, MatchType::Template);
+        assert_eq!(rule.risk_level, "Warning");
+        assert_eq!(rule.action, "Block");
+        assert_eq!(state.proxy.rules().len(), 1);
+    }
 }
@@ -0,0 +1,334 @@
+//! Server-Sent Events mirror of the WebSocket feed
+//!
+//! `GET /api/events/stream` takes the same filter parameters as `/ws/events`.
+//! Live actions and analyses carry their bus `seq` as the SSE `id`. A
+//! reconnecting client's `Last-Event-ID` resumes right after that event from
+//! the event journal, which keeps delivered events for
+//! `RESUME_WINDOW_MINUTES`; for longer gaps, pass `since` instead.
+
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast::error::RecvError;
+use tracing::warn;
+
+use super::ws::{parse_cursor, replay, FeedFilter, FeedParams, Replayed};
+use super::{AppState, WebEvent};
+use crate::db::DbPool;
+use crate::events::Envelope;
+use crate::rules::Rule;
+
+/// Interval between heartbeat comments on an idle stream
+const HEARTBEAT_SECS: u64 = if cfg!(test) { 1 } else { 15 };
+/// Journal rows read per batch when resuming
+const RESUME_BATCH: usize = 256;
+
+fn to_sse(event: &WebEvent) -> Option<Event> {
+    let name = match event {
+        WebEvent::Action { .. } => "action",
+        WebEvent::Analysis { .. } => "analysis",
+        WebEvent::Status { .. } => "status",
+    };
+    let sse = Event::default().event(name).json_data(event).ok()?;
+    Some(match event.seq() {
+        Some(seq) => sse.id(seq.to_string()),
+        None => sse,
+    })
+}
+
+/// Where a reconnecting client left off
+enum Cursor {
+    /// Last `seq` it received
+    Seq(u64),
+    /// `since`, or an RFC 3339 id from before ids were sequence numbers
+    Time(DateTime<Utc>),
+}
+
+fn cursor(headers: &HeaderMap, params: &FeedParams) -> Option<Cursor> {
+    let last_id = headers.get("last-event-id").and_then(|v| v.to_str().ok());
+    if let Some(seq) = last_id.and_then(|id| id.trim().parse().ok()) {
+        return Some(Cursor::Seq(seq));
+    }
+    last_id
+        .or(params.since.as_deref())
+        .and_then(parse_cursor)
+        .map(Cursor::Time)
+}
+
+/// Journaled events after `seq`, as they went out live
+fn journal_events(pool: &DbPool, after: u64, rules: &[Rule]) -> Vec<WebEvent> {
+    let db = match pool.get() {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("Resume unavailable: {}", e);
+            return vec![];
+        }
+    };
+    let mut events = vec![];
+    let mut seq = after;
+    loop {
+        let rows = match db.events_after(seq, RESUME_BATCH) {
+            Ok(rows) => rows,
+            Err(e) => {
+                warn!("Failed to read the event journal: {}", e);
+                break;
+            }
+        };
+        let done = rows.len() < RESUME_BATCH;
+        for (n, published_at, payload) in rows {
+            seq = n;
+            if let Ok(event) = serde_json::from_str(&payload) {
+                let env = Envelope {
+                    seq: n,
+                    published_at,
+                    event,
+                };
+                events.push(WebEvent::from_envelope(&env, rules));
+            }
+        }
+        if done {
+            return events;
+        }
+    }
+    events
+}
+
+pub async fn sse_handler(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<FeedParams>,
+    headers: HeaderMap,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let cursor = cursor(&headers, &params);
+
+    // Subscribe before replaying so nothing falls between the two
+    let mut rx = state.event_tx.subscribe();
+    let mut filter = FeedFilter::new(&params);
+    let pool = state.db.clone();
+    let rules = state.proxy.rules();
+
+    let stream = async_stream::stream! {
+        let status = WebEvent::Status {
+            connected: true,
+            monitoring: vec!["openclaw".to_string()],
+        };
+        if let Some(event) = to_sse(&status) {
+            yield Ok(event);
+        }
+
+        let mut replayed = Replayed::default();
+        // Live events up to here were already sent
+        let mut last_seq = 0;
+        match cursor {
+            Some(Cursor::Seq(seq)) => {
+                last_seq = seq;
+                let events =
+                    tokio::task::spawn_blocking(move || journal_events(&pool, seq, &rules))
+                        .await
+                        .unwrap_or_default();
+                for event in events {
+                    last_seq = last_seq.max(event.seq().unwrap_or_default());
+                    for event in filter.accept(event).iter().filter_map(to_sse) {
+                        yield Ok(event);
+                    }
+                }
+            }
+            Some(Cursor::Time(since)) => {
+                let (events, seen) = replay(pool, since, rules, &mut filter).await;
+                replayed = seen;
+                for event in events.iter().filter_map(to_sse) {
+                    yield Ok(event);
+                }
+            }
+            None => {}
+        }
+
+        loop {
+            match rx.recv().await {
+                Ok(event) if replayed.contains(&event) => continue,
+                Ok(event) if event.seq().is_some_and(|seq| seq <= last_seq) => continue,
+                Ok(event) => {
+                    for event in filter.accept(event).iter().filter_map(to_sse) {
+                        yield Ok(event);
+                    }
+                }
+                // Slow client; the cursor lets it recover on reconnect
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => break,
+            }
+        }
+    };
+
+    Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(HEARTBEAT_SECS))
+            .text("heartbeat"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::{Event as BusEvent, EventBus};
+    use crate::proxy::config::{ProxyConfig, ProxyMode};
+    use crate::proxy::control::ProxyControl;
+    use crate::{ActionType, AgentAction, AgentType};
+    use tokio::sync::{broadcast, RwLock};
+
+    fn state(dir: &std::path::Path) -> Arc<AppState> {
+        let (event_tx, _) = broadcast::channel(16);
+        let db = DbPool::open(&dir.join("harness.db")).unwrap();
+        Arc::new(AppState {
+            event_tx,
+            web: crate::web::WebConfig::from_env(),
+            db_path: db.path().to_string_lossy().to_string(),
+            db,
+            rules: RwLock::new(vec![]),
+            proxy_config: RwLock::new(ProxyConfig::default()),
+            proxy: ProxyControl::new(ProxyMode::Monitor, vec![]),
+            collectors: crate::collectors::CollectorManager::new(),
+            alerter: None,
+            started_at: Utc::now(),
+            stats_cache: std::sync::Mutex::new(None),
+        })
+    }
+
+    fn action(id: &str, agent: AgentType) -> BusEvent {
+        BusEvent::Action(AgentAction {
+            id: id.to_string(),
+            timestamp: Utc::now(),
+            agent,
+            action_type: ActionType::Exec,
+            content: "ls".to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        })
+    }
+
+    /// Publish on the bus and pass it on as the daemon's web bridge does
+    fn publish(state: &AppState, bus: &EventBus, event: BusEvent) {
+        let seq = bus.publish(event.clone());
+        let env = Envelope {
+            seq,
+            published_at: Utc::now(),
+            event,
+        };
+        let _ = state.event_tx.send(WebEvent::from_envelope(&env, &[]));
+    }
+
+    /// An SSE stream read one message at a time
+    struct Feed {
+        response: reqwest::Response,
+        buf: String,
+    }
+
+    impl Feed {
+        async fn open(state: Arc<AppState>, query: &str, last_id: Option<&str>) -> Self {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            tokio::spawn(async move { axum::serve(listener, crate::web::router(state)).await });
+            let mut request = reqwest::Client::new()
+                .get(format!("http://{}/api/v1/events/stream{}", addr, query));
+            if let Some(id) = last_id {
+                request = request.header("Last-Event-ID", id);
+            }
+            let mut feed = Self {
+                response: request.send().await.unwrap(),
+                buf: String::new(),
+            };
+            assert!(feed.next().await.contains("event: status"));
+            feed
+        }
+
+        async fn next(&mut self) -> String {
+            loop {
+                if let Some(end) = self.buf.find("\n\n") {
+                    let message = self.buf[..end].to_string();
+                    self.buf.drain(..end + 2);
+                    return message;
+                }
+                let chunk = tokio::time::timeout(Duration::from_secs(5), self.response.chunk())
+                    .await
+                    .expect("no event within 5s")
+                    .unwrap()
+                    .unwrap();
+                self.buf.push_str(&String::from_utf8_lossy(&chunk));
+            }
+        }
+
+        /// Next action or analysis as (id, action id)
+        async fn next_event(&mut self) -> (String, String) {
+            loop {
+                let message = self.next().await;
+                let field = |name: &str| {
+                    message
+                        .lines()
+                        .find_map(|l| l.strip_prefix(name))
+                        .map(|v| v.trim().to_string())
+                };
+                if let Some(data) = field("data:") {
+                    let json: serde_json::Value = serde_json::from_str(&data).unwrap();
+                    return (field("id:").unwrap_or_default(), json["id"].to_string());
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_on_idle_stream() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut feed = Feed::open(state(dir.path()), "", None).await;
+        let message = feed.next().await;
+        assert!(message.starts_with(':'), "{}", message);
+        assert!(message.contains("heartbeat"));
+    }
+
+    #[tokio::test]
+    async fn test_filter_and_seq_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(dir.path());
+        let bus = EventBus::in_memory();
+        let mut feed = Feed::open(state.clone(), "?agent=aider", None).await;
+
+        publish(&state, &bus, action("a1", AgentType::ClaudeCode));
+        publish(&state, &bus, action("a2", AgentType::Aider));
+        assert_eq!(
+            feed.next_event().await,
+            ("2".to_string(), "\"a2\"".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_last_event_id() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = state(dir.path());
+        let bus = EventBus::with_journal(state.db.get().unwrap());
+        for id in ["a1", "a2", "a3"] {
+            publish(&state, &bus, action(id, AgentType::Aider));
+        }
+
+        // Reconnecting after a1: the rest come from the journal, once
+        let mut feed = Feed::open(state.clone(), "", Some("1")).await;
+        for (seq, id) in [("2", "\"a2\""), ("3", "\"a3\"")] {
+            assert_eq!(feed.next_event().await, (seq.to_string(), id.to_string()));
+        }
+        let env = Envelope {
+            seq: 3,
+            published_at: Utc::now(),
+            event: action("a3", AgentType::Aider),
+        };
+        let _ = state.event_tx.send(WebEvent::from_envelope(&env, &[]));
+        publish(&state, &bus, action("a4", AgentType::Aider));
+        assert_eq!(
+            feed.next_event().await,
+            ("4".to_string(), "\"a4\"".to_string())
+        );
+    }
+}
@@ -0,0 +1,194 @@
+// OpenClaw Harness Control Center (bundled UI)
+// Talks to the REST API under /api/v1 and the live feed at /ws/events.
+
+const MAX_FEED = 200;
+const actions = new Map();
+// Newest action seen, so a reconnect replays what was missed
+let lastSeen = null;
+
+const $ = (id) => document.getElementById(id);
+
+function text(tag, value, cls) {
+  const el = document.createElement(tag);
+  el.textContent = value ?? '';
+  if (cls) el.className = cls;
+  return el;
+}
+
+// API token (OPENCLAW_HARNESS_API_TOKEN), asked for when a request is refused
+const TOKEN_KEY = 'openclaw-harness-token';
+
+async function api(path, options = {}) {
+  const headers = { 'Content-Type': 'application/json' };
+  const token = localStorage.getItem(TOKEN_KEY);
+  if (token) headers.Authorization = `Bearer ${token}`;
+  const res = await fetch(path, { headers, ...options });
+  if (res.status === 401 && !options.retried) {
+    const given = prompt('API token');
+    if (given) {
+      localStorage.setItem(TOKEN_KEY, given.trim());
+      return api(path, { ...options, retried: true });
+    }
+  }
+  if (!res.ok) throw new Error(`${path}: ${res.status}`);
+  return res.status === 204 ? null : res.json();
+}
+
+// ---- Pages ----------------------------------------------------------------
+
+function showPage() {
+  const page = (location.hash || '#feed').slice(1);
+  document.querySelectorAll('.page').forEach((p) => { p.hidden = p.id !== `page-${page}`; });
+  document.querySelectorAll('nav a').forEach((a) => a.classList.toggle('active', a.hash === `#${page}`));
+  if (page === 'rules') loadRules();
+  if (page === 'approvals') loadApprovals();
+}
+
+// ---- Stats ----------------------------------------------------------------
+
+async function loadStats() {
+  try {
+    const s = await api('/api/v1/stats');
+    $('stat-total').textContent = s.total_events;
+    $('stat-critical').textContent = s.critical_count;
+    $('stat-warning').textContent = s.warning_count;
+    $('stat-blocked').textContent = s.blocked_count;
+    $('stat-rules').textContent = s.rules_count;
+  } catch (e) {
+    console.warn(e);
+  }
+}
+
+// ---- Live feed ------------------------------------------------------------
+
+function addAction(ev) {
+  if (actions.has(ev.id)) return;
+  lastSeen = ev.timestamp;
+  const row = document.createElement('tr');
+  row.id = `action-${ev.id}`;
+  row.append(
+    text('td', new Date(ev.timestamp).toLocaleTimeString()),
+    text('td', ev.agent),
+    text('td', ev.action_type),
+    text('td', ev.content, 'content'),
+    text('td', '', 'risk'),
+  );
+  actions.set(ev.id, ev);
+  const feed = $('feed');
+  feed.prepend(row);
+  while (feed.children.length > MAX_FEED) {
+    actions.delete(feed.lastChild.id.slice('action-'.length));
+    feed.lastChild.remove();
+  }
+}
+
+function addAnalysis(ev) {
+  const cell = document.querySelector(`#action-${CSS.escape(ev.action_id)} .risk`);
+  if (cell) {
+    cell.textContent = ev.risk_level;
+    cell.className = `risk risk-${ev.risk_level.toLowerCase()}`;
+    cell.title = ev.matched_rules.join(', ');
+  }
+  if (ev.recommendation === 'PauseAndAsk') loadApprovals();
+}
+
+function connect() {
+  const proto = location.protocol === 'https:' ? 'wss' : 'ws';
+  const query = lastSeen ? `?since=${encodeURIComponent(lastSeen)}` : '';
+  const ws = new WebSocket(`${proto}://${location.host}/ws/events${query}`);
+  ws.onopen = () => { $('conn').textContent = 'live'; $('conn').className = 'badge on'; };
+  ws.onclose = () => {
+    $('conn').textContent = 'offline';
+    $('conn').className = 'badge off';
+    setTimeout(connect, 2000);
+  };
+  ws.onmessage = (msg) => {
+    const ev = JSON.parse(msg.data);
+    if (ev.type === 'action') addAction(ev);
+    else if (ev.type === 'analysis') { addAnalysis(ev); loadStats(); }
+  };
+}
+
+// ---- Rules ----------------------------------------------------------------
+
+async function loadRules() {
+  const rules = await api('/api/v1/rules');
+  const body = $('rules');
+  body.replaceChildren();
+  for (const r of rules) {
+    const row = document.createElement('tr');
+    const toggle = document.createElement('input');
+    toggle.type = 'checkbox';
+    toggle.checked = r.enabled;
+    toggle.onchange = () => api(`/api/v1/rules/${encodeURIComponent(r.name)}`, {
+      method: 'PUT', body: JSON.stringify({ enabled: toggle.checked }),
+    }).catch(alert);
+    const enabled = document.createElement('td');
+    enabled.append(toggle);
+    const remove = document.createElement('td');
+    if (!r.is_preset) {
+      const btn = text('button', 'Delete');
+      btn.onclick = () => api(`/api/v1/rules/${encodeURIComponent(r.name)}`, { method: 'DELETE' })
+        .then(loadRules).catch(alert);
+      remove.append(btn);
+    }
+    row.append(enabled, text('td', r.name), text('td', r.pattern, 'content'),
+      text('td', r.risk_level, `risk-${r.risk_level.toLowerCase()}`), text('td', r.action), remove);
+    body.append(row);
+  }
+}
+
+$('new-rule').onsubmit = async (e) => {
+  e.preventDefault();
+  const form = new FormData(e.target);
+  try {
+    await api('/api/v1/rules', { method: 'POST', body: JSON.stringify(Object.fromEntries(form)) });
+    e.target.reset();
+    loadRules();
+    loadStats();
+  } catch (err) {
+    alert(err);
+  }
+};
+
+// ---- Approvals ------------------------------------------------------------
+
+// Same queue as Slack and the terminal prompt; a decision here lets the
+// agent's retry through (or not)
+async function loadApprovals() {
+  let pending;
+  try {
+    pending = await api('/api/v1/approvals');
+  } catch (e) {
+    console.warn(e);
+    return;
+  }
+  const list = $('approvals');
+  list.replaceChildren();
+  for (const item of pending) {
+    const li = document.createElement('li');
+    const decide = (verb) => {
+      const btn = text('button', verb === 'approve' ? 'Approve' : 'Deny');
+      btn.onclick = () => api(`/api/v1/approvals/${encodeURIComponent(item.id)}/${verb}`, { method: 'POST' })
+        .then(loadApprovals).catch(alert);
+      return btn;
+    };
+    li.append(
+      text('div', item.id),
+      text('div', item.summary, 'content'),
+      text('div', `requested ${new Date(item.requested_at).toLocaleTimeString()}`, 'hint'),
+      decide('approve'),
+      decide('deny'),
+    );
+    list.append(li);
+  }
+  $('approval-count').textContent = pending.length ? `(${pending.length})` : '';
+}
+
+window.addEventListener('hashchange', showPage);
+showPage();
+loadStats();
+setInterval(loadStats, 10000);
+loadApprovals();
+setInterval(loadApprovals, 10000);
+connect();
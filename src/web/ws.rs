@@ -1,30 +1,244 @@
 //! WebSocket handler for real-time events
+//!
+//! Clients can narrow the feed with query parameters and resume after a
+//! disconnect:
+//!
+//! - `risk=warning` — only actions analyzed at this level or above
+//! - `agent=claude_code,aider` — only these agents
+//! - `action_type=exec,write` — only these action types
+//! - `since=<RFC 3339 timestamp>` — replay stored events from this time
+//!   (inclusive, so clients should dedupe by action ID) before going live
+//!
+//! Analyses carry `severity`, `priority` (`low`, `high`, `urgent`) and the
+//! matched rules' `categories`, for notifications and client-side
+//! filtering; live events carry a sequence number `seq` (see `WebEvent`).
 
 use axum::{
     extract::{
         ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        Query, State,
     },
     response::IntoResponse,
 };
 use futures_util::{SinkExt, StreamExt};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use tracing::{info, warn};
 
 use super::{AppState, WebEvent};
+use crate::db::DbPool;
+use crate::rules::Rule;
+use crate::{AgentType, RiskLevel};
+
+/// Most events replayed for one `since` cursor (newest kept)
+const REPLAY_LIMIT: usize = 1000;
+/// Actions remembered while waiting for their analysis
+const PENDING_LIMIT: usize = 512;
+
+/// Subscription parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct FeedParams {
+    pub risk: Option<String>,
+    pub agent: Option<String>,
+    pub action_type: Option<String>,
+    pub since: Option<String>,
+}
+
+fn list(s: Option<&str>, normalize: fn(&str) -> String) -> Vec<String> {
+    s.map(|s| {
+        s.split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(normalize)
+            .collect()
+    })
+    .unwrap_or_default()
+}
+
+/// Per-connection filter over the event feed.
+///
+/// Risk is only known once an action is analyzed, so with a risk filter
+/// actions are held back and sent together with a matching analysis.
+pub struct FeedFilter {
+    min_risk: Option<RiskLevel>,
+    agents: Vec<String>,
+    action_types: Vec<String>,
+    /// Accepted actions by ID; `Some` while held back for a risk filter
+    pending: HashMap<String, Option<WebEvent>>,
+    order: VecDeque<String>,
+}
+
+impl FeedFilter {
+    pub fn new(params: &FeedParams) -> Self {
+        Self {
+            min_risk: params.risk.as_deref().and_then(|r| r.parse().ok()),
+            agents: list(params.agent.as_deref(), |a| {
+                AgentType::from_name(a).to_string()
+            }),
+            action_types: list(params.action_type.as_deref(), str::to_lowercase),
+            pending: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    fn remember(&mut self, id: String, held: Option<WebEvent>) {
+        if self.order.len() >= PENDING_LIMIT {
+            if let Some(old) = self.order.pop_front() {
+                self.pending.remove(&old);
+            }
+        }
+        self.order.push_back(id.clone());
+        self.pending.insert(id, held);
+    }
+
+    /// Events to deliver for `event` (none, itself, or a held action plus it)
+    pub fn accept(&mut self, event: WebEvent) -> Vec<WebEvent> {
+        match &event {
+            WebEvent::Action {
+                id,
+                agent,
+                action_type,
+                ..
+            } => {
+                let agent_ok = self.agents.is_empty()
+                    || self
+                        .agents
+                        .contains(&AgentType::from_name(agent).to_string());
+                let type_ok = self.action_types.is_empty()
+                    || self.action_types.contains(&action_type.to_lowercase());
+                if !agent_ok || !type_ok {
+                    return vec![];
+                }
+                if self.min_risk.is_some() {
+                    self.remember(id.clone(), Some(event));
+                    vec![]
+                } else {
+                    self.remember(id.clone(), None);
+                    vec![event]
+                }
+            }
+            WebEvent::Analysis {
+                action_id,
+                risk_level,
+                ..
+            } => {
+                let Some(held) = self.pending.remove(action_id) else {
+                    return vec![];
+                };
+                let risk_ok = self
+                    .min_risk
+                    .is_none_or(|min| risk_level.parse::<RiskLevel>().is_ok_and(|r| r >= min));
+                if !risk_ok {
+                    return vec![];
+                }
+                held.into_iter().chain(std::iter::once(event)).collect()
+            }
+            WebEvent::Status { .. } => vec![event],
+        }
+    }
+}
+
+/// Stored events from `since` onwards, oldest first; `rules` give the
+/// matched rules' categories
+pub fn replay_events(
+    pool: &DbPool,
+    since: chrono::DateTime<chrono::Utc>,
+    rules: &[Rule],
+) -> Vec<WebEvent> {
+    let db = match pool.get() {
+        Ok(db) => db,
+        Err(e) => {
+            warn!("Replay unavailable: {}", e);
+            return vec![];
+        }
+    };
+    let mut actions = db
+        .get_actions_between(since, chrono::Utc::now())
+        .unwrap_or_default();
+    if actions.len() > REPLAY_LIMIT {
+        actions.drain(..actions.len() - REPLAY_LIMIT);
+    }
+
+    let mut events = Vec::with_capacity(actions.len() * 2);
+    for action in actions {
+        let analysis = db.get_analysis(&action.id).ok().flatten();
+        events.push(WebEvent::from(&action));
+        if let Some(a) = analysis {
+            events.push(WebEvent::analysis(
+                action.id,
+                a.risk_level,
+                a.matched_rules,
+                a.recommendation,
+                a.explanation,
+                rules,
+            ));
+        }
+    }
+    events
+}
+
+/// IDs delivered by a replay, so live copies of the same events are skipped
+#[derive(Default)]
+pub struct Replayed {
+    actions: HashSet<String>,
+    analyses: HashSet<String>,
+}
+
+impl Replayed {
+    pub fn contains(&self, event: &WebEvent) -> bool {
+        match event {
+            WebEvent::Action { id, .. } => self.actions.contains(id),
+            WebEvent::Analysis { action_id, .. } => self.analyses.contains(action_id),
+            WebEvent::Status { .. } => false,
+        }
+    }
+}
+
+/// Replay stored events since `since` through `filter`, off the async runtime
+pub async fn replay(
+    pool: DbPool,
+    since: chrono::DateTime<chrono::Utc>,
+    rules: Arc<Vec<Rule>>,
+    filter: &mut FeedFilter,
+) -> (Vec<WebEvent>, Replayed) {
+    let events = tokio::task::spawn_blocking(move || replay_events(&pool, since, &rules))
+        .await
+        .unwrap_or_default();
+    let mut replayed = Replayed::default();
+    let mut out = Vec::new();
+    for event in events {
+        match &event {
+            WebEvent::Action { id, .. } => replayed.actions.insert(id.clone()),
+            WebEvent::Analysis { action_id, .. } => replayed.analyses.insert(action_id.clone()),
+            WebEvent::Status { .. } => false,
+        };
+        out.extend(filter.accept(event));
+    }
+    (out, replayed)
+}
+
+/// Parse a `since` cursor (RFC 3339)
+pub fn parse_cursor(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(s.trim())
+        .ok()
+        .map(|t| t.with_timezone(&chrono::Utc))
+}
 
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
+    Query(params): Query<FeedParams>,
 ) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| handle_socket(socket, state))
+    ws.on_upgrade(move |socket| handle_socket(socket, state, params))
 }
 
-async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
+async fn handle_socket(socket: WebSocket, state: Arc<AppState>, params: FeedParams) {
     let (mut sender, mut receiver) = socket.split();
 
-    // Subscribe to events
+    // Subscribe before replaying so nothing falls between the two
     let mut rx = state.event_tx.subscribe();
+    let mut filter = FeedFilter::new(&params);
 
     // Send initial status
     let status = WebEvent::Status {
@@ -37,12 +251,30 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
 
     info!("🔌 WebSocket client connected");
 
+    let since = params.since.as_deref().and_then(parse_cursor);
+
     // Spawn task to forward events to client
+    let pool = state.db.clone();
+    let rules = state.proxy.rules();
     let mut send_task = tokio::spawn(async move {
+        let mut replayed = Replayed::default();
+        if let Some(since) = since {
+            let (events, seen) = replay(pool, since, rules, &mut filter).await;
+            replayed = seen;
+            for event in &events {
+                if send_event(&mut sender, event).await.is_err() {
+                    return;
+                }
+            }
+        }
+
         while let Ok(event) = rx.recv().await {
-            if let Ok(json) = serde_json::to_string(&event) {
-                if sender.send(Message::Text(json)).await.is_err() {
-                    break;
+            if replayed.contains(&event) {
+                continue;
+            }
+            for out in filter.accept(event) {
+                if send_event(&mut sender, &out).await.is_err() {
+                    return;
                 }
             }
         }
@@ -52,11 +284,8 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
     let mut recv_task = tokio::spawn(async move {
         while let Some(msg) = receiver.next().await {
             match msg {
-                Ok(Message::Text(text)) => {
-                    // Handle commands from client
-                    if text == "ping" {
-                        // Client ping - already handled by WebSocket layer
-                    }
+                Ok(Message::Text(text)) if text == "ping" => {
+                    // Client ping - already handled by WebSocket layer
                 }
                 Ok(Message::Close(_)) => {
                     break;
@@ -78,3 +307,111 @@ async fn handle_socket(socket: WebSocket, state: Arc<AppState>) {
 
     info!("🔌 WebSocket client disconnected");
 }
+
+async fn send_event(
+    sender: &mut futures_util::stream::SplitSink<WebSocket, Message>,
+    event: &WebEvent,
+) -> Result<(), axum::Error> {
+    match serde_json::to_string(event) {
+        Ok(json) => sender.send(Message::Text(json)).await,
+        Err(_) => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::web::Priority;
+
+    fn action(id: &str, agent: &str, action_type: &str) -> WebEvent {
+        WebEvent::Action {
+            seq: None,
+            id: id.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            agent: agent.to_string(),
+            action_type: action_type.to_string(),
+            content: "x".to_string(),
+            target: None,
+        }
+    }
+
+    fn analysis(id: &str, risk: &str) -> WebEvent {
+        WebEvent::analysis(
+            id.to_string(),
+            risk.parse().unwrap(),
+            vec![],
+            "Alert".to_string(),
+            String::new(),
+            &[],
+        )
+    }
+
+    #[test]
+    fn test_feed_filter() {
+        let mut filter = FeedFilter::new(&FeedParams {
+            risk: Some("warning".to_string()),
+            agent: Some("claude-code".to_string()),
+            ..Default::default()
+        });
+
+        // Held until analyzed, then sent with its analysis
+        assert!(filter
+            .accept(action("a1", "claude_code", "exec"))
+            .is_empty());
+        assert_eq!(filter.accept(analysis("a1", "CRITICAL")).len(), 2);
+
+        // Too low a risk
+        assert!(filter
+            .accept(action("a2", "claude_code", "exec"))
+            .is_empty());
+        assert!(filter.accept(analysis("a2", "INFO")).is_empty());
+
+        // Other agent
+        assert!(filter.accept(action("a3", "aider", "exec")).is_empty());
+        assert!(filter.accept(analysis("a3", "CRITICAL")).is_empty());
+
+        let mut by_type = FeedFilter::new(&FeedParams {
+            action_type: Some("write".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(by_type.accept(action("b1", "aider", "write")).len(), 1);
+        assert_eq!(by_type.accept(analysis("b1", "INFO")).len(), 1);
+        assert!(by_type.accept(action("b2", "aider", "exec")).is_empty());
+    }
+
+    #[test]
+    fn test_analysis_event_metadata() {
+        let mut custom = crate::rules::default_rules().remove(0);
+        custom.name = "deploy_gate".to_string();
+        custom.category = Some("Deployment".to_string());
+        let event = WebEvent::analysis(
+            "a1".to_string(),
+            RiskLevel::Critical,
+            vec![
+                "dangerous_rm".to_string(),
+                "deploy_gate".to_string(),
+                "mass_delete".to_string(),
+                "gone".to_string(),
+            ],
+            "CriticalAlert".to_string(),
+            String::new(),
+            &[custom],
+        )
+        .with_seq(42);
+
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["seq"], 42);
+        assert_eq!(json["severity"], "critical");
+        assert_eq!(json["priority"], "urgent");
+        assert_eq!(
+            json["categories"],
+            serde_json::json!(["File/Folder Protection", "Deployment", "Other"])
+        );
+
+        assert_eq!(Priority::from(RiskLevel::Info), Priority::Low);
+        let json = serde_json::to_value(analysis("a2", "warning")).unwrap();
+        assert_eq!(json["priority"], "high");
+        // Only live events are numbered
+        assert!(json.get("seq").is_none());
+    }
+}
@@ -0,0 +1,305 @@
+//! Workspaces: one harness serving several projects
+//!
+//! Every stored action belongs to one workspace (the `workspace` column of
+//! `actions`), taken from, strongest first:
+//! 1. `metadata.workspace`, when a collector or client set one
+//! 2. The declared workspace whose root holds the action's working
+//!    directory (`metadata.cwd`) or target path; the longest root wins
+//! 3. `default`
+//!
+//! Roots are declared in `config/workspaces.yaml` or
+//! `~/.openclaw-harness/workspaces.yaml` (first file that loads):
+//!
+//! ```yaml
+//! - id: payments
+//!   roots: [~/work/payments, /srv/payments]
+//! - id: website
+//!   roots: [~/src/site]
+//! ```
+//!
+//! Weekly reports and ontology builds take an optional workspace. Without
+//! one they cover every action and are written to the data directory as
+//! before; a workspace's go to `workspaces/<id>/` under it
+//! (`partition_dir`).
+
+use crate::AgentAction;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::{info, warn};
+
+/// Workspace of actions nothing assigns elsewhere
+pub const DEFAULT: &str = "default";
+/// What reports and ontology builds without a workspace are labelled
+pub const ALL: &str = "all";
+/// Metadata key naming an action's workspace
+pub const METADATA_KEY: &str = "workspace";
+
+const PARTITIONS_DIR: &str = "workspaces";
+const MAX_ID_LEN: usize = 64;
+
+/// Whether `id` can name a workspace: letters, digits, `-`, `_` and `.`,
+/// not starting with `.`, and not `all`
+pub fn valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LEN
+        && !id.starts_with('.')
+        && id != ALL
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    pub id: String,
+    /// Directories whose actions belong to this workspace; `~` is expanded
+    #[serde(default)]
+    pub roots: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct WorkspaceRegistry {
+    workspaces: Vec<WorkspaceConfig>,
+}
+
+impl WorkspaceRegistry {
+    pub fn new(workspaces: Vec<WorkspaceConfig>) -> anyhow::Result<Self> {
+        if let Some(bad) = workspaces.iter().find(|w| !valid_id(&w.id)) {
+            anyhow::bail!("invalid workspace id {:?}", bad.id);
+        }
+        Ok(Self { workspaces })
+    }
+
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::new(serde_yaml::from_str(&content)?)
+    }
+
+    /// Workspaces from the first workspaces file that loads
+    pub fn load() -> Self {
+        for path in candidate_paths().iter().filter(|p| p.exists()) {
+            match Self::from_file(path) {
+                Ok(registry) => {
+                    info!(
+                        "🗂️ Loaded {} workspace(s) from {}",
+                        registry.workspaces.len(),
+                        path.display()
+                    );
+                    return registry;
+                }
+                Err(e) => warn!("Ignoring {}: {}", path.display(), e),
+            }
+        }
+        Self::default()
+    }
+
+    pub fn workspaces(&self) -> &[WorkspaceConfig] {
+        &self.workspaces
+    }
+
+    /// The workspace `action` belongs to
+    pub fn resolve(&self, action: &AgentAction) -> String {
+        let metadata = action.metadata.as_ref();
+        if let Some(id) = metadata
+            .and_then(|m| m[METADATA_KEY].as_str())
+            .map(str::trim)
+            .filter(|id| valid_id(id))
+        {
+            return id.to_string();
+        }
+
+        let paths = [
+            metadata.and_then(|m| m["cwd"].as_str()),
+            action.target.as_deref(),
+        ];
+        paths
+            .into_iter()
+            .flatten()
+            .filter(|p| p.starts_with('/') || p.starts_with('~'))
+            .find_map(|p| self.by_path(&crate::expand_home(p)))
+            .unwrap_or(DEFAULT)
+            .to_string()
+    }
+
+    /// Workspace with the longest root holding `path`
+    fn by_path(&self, path: &Path) -> Option<&str> {
+        self.workspaces
+            .iter()
+            .flat_map(|w| w.roots.iter().map(move |r| (w, crate::expand_home(r))))
+            .filter(|(_, root)| path.starts_with(root))
+            .max_by_key(|(_, root)| root.components().count())
+            .map(|(w, _)| w.id.as_str())
+    }
+}
+
+fn candidate_paths() -> [PathBuf; 2] {
+    [
+        PathBuf::from("config/workspaces.yaml"),
+        crate::harness_path("workspaces.yaml"),
+    ]
+}
+
+/// Declared workspaces, loaded once per process
+pub fn registry() -> &'static WorkspaceRegistry {
+    static REGISTRY: OnceLock<WorkspaceRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(WorkspaceRegistry::load)
+}
+
+/// Where reports and ontology snapshots for `workspace` (or for every
+/// action, without one) are written under the data directory
+pub fn partition_dir(base_dir: &Path, workspace: Option<&str>) -> PathBuf {
+    match workspace {
+        Some(id) => base_dir.join(PARTITIONS_DIR).join(id),
+        None => base_dir.to_path_buf(),
+    }
+}
+
+/// The unscoped partition and every workspace partition written so far
+pub fn partitions(base_dir: &Path) -> Vec<(Option<String>, PathBuf)> {
+    let mut partitions = vec![(None, base_dir.to_path_buf())];
+    if let Ok(entries) = std::fs::read_dir(base_dir.join(PARTITIONS_DIR)) {
+        let mut ids: Vec<String> = entries
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().into_string().ok())
+            .filter(|id| valid_id(id))
+            .collect();
+        ids.sort();
+        for id in ids {
+            let dir = partition_dir(base_dir, Some(&id));
+            partitions.push((Some(id), dir));
+        }
+    }
+    partitions
+}
+
+/// A workspace with what is stored for it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceSummary {
+    pub id: String,
+    pub roots: Vec<String>,
+    pub actions: u64,
+    pub first_seen: Option<String>,
+    pub last_seen: Option<String>,
+}
+
+/// Declared workspaces and those with stored actions, by id
+pub fn summaries(
+    conn: &Connection,
+    registry: &WorkspaceRegistry,
+) -> anyhow::Result<Vec<WorkspaceSummary>> {
+    let mut summaries: Vec<WorkspaceSummary> = registry
+        .workspaces()
+        .iter()
+        .map(|w| WorkspaceSummary {
+            id: w.id.clone(),
+            roots: w.roots.clone(),
+            actions: 0,
+            first_seen: None,
+            last_seen: None,
+        })
+        .collect();
+
+    let mut stmt = conn.prepare(
+        "SELECT workspace, COUNT(*), MIN(timestamp), MAX(timestamp)
+         FROM actions GROUP BY workspace",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, i64>(1)? as u64,
+            row.get::<_, Option<String>>(2)?,
+            row.get::<_, Option<String>>(3)?,
+        ))
+    })?;
+    for row in rows {
+        let (id, actions, first_seen, last_seen) = row?;
+        match summaries.iter_mut().find(|s| s.id == id) {
+            Some(summary) => {
+                summary.actions = actions;
+                summary.first_seen = first_seen;
+                summary.last_seen = last_seen;
+            }
+            None => summaries.push(WorkspaceSummary {
+                id,
+                roots: vec![],
+                actions,
+                first_seen,
+                last_seen,
+            }),
+        }
+    }
+    summaries.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ActionType, AgentType};
+
+    fn action(target: Option<&str>, metadata: Option<serde_json::Value>) -> AgentAction {
+        AgentAction {
+            id: "a1".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::FileWrite,
+            content: "x".to_string(),
+            target: target.map(String::from),
+            session_id: None,
+            metadata,
+        }
+    }
+
+    #[test]
+    fn test_workspace_resolution() {
+        let registry = WorkspaceRegistry::new(
+            serde_yaml::from_str(
+                r#"
+- id: payments
+  roots: [/work/payments]
+- id: payments-api
+  roots: [/work/payments/api]
+- id: website
+  roots: [/srv/site, /work/site]
+"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let resolve = |target, metadata| registry.resolve(&action(target, metadata));
+        assert_eq!(resolve(Some("/work/payments/README.md"), None), "payments");
+        // Longest root wins; roots match whole path components
+        assert_eq!(
+            resolve(Some("/work/payments/api/main.rs"), None),
+            "payments-api"
+        );
+        assert_eq!(resolve(Some("/work/payments-old/a.rs"), None), DEFAULT);
+        assert_eq!(resolve(Some("/srv/site/index.html"), None), "website");
+        // The working directory counts before the target
+        let cwd = serde_json::json!({"cwd": "/work/site"});
+        assert_eq!(resolve(Some("/work/payments/x"), Some(cwd)), "website");
+        // An explicit workspace beats both, unless it is not a valid id
+        let tagged = serde_json::json!({"workspace": "ops", "cwd": "/work/site"});
+        assert_eq!(resolve(None, Some(tagged)), "ops");
+        let bad = serde_json::json!({"workspace": "../etc", "cwd": "/work/site"});
+        assert_eq!(resolve(None, Some(bad)), "website");
+        assert_eq!(resolve(Some("https://example.com"), None), DEFAULT);
+
+        assert!(!valid_id(ALL));
+        assert!(!valid_id(".hidden"));
+        assert!(WorkspaceRegistry::new(vec![WorkspaceConfig {
+            id: "a/b".to_string(),
+            roots: vec![],
+        }])
+        .is_err());
+
+        let tmp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(partition_dir(tmp.path(), Some("website"))).unwrap();
+        let found: Vec<Option<String>> = partitions(tmp.path()).into_iter().map(|p| p.0).collect();
+        assert_eq!(found, [None, Some("website".to_string())]);
+    }
+}
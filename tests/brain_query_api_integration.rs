@@ -1,6 +1,7 @@
-use axum::{http::StatusCode, Json};
+use axum::{extract::Query, http::StatusCode, Json};
 use openclaw_harness::web::routes::{
     get_brain_graph_v2, query_brain_v2, search_brain_v2, BrainQueryRequest, BrainSearchRequest,
+    WorkspaceQuery,
 };
 use serde_json::json;
 
@@ -43,6 +44,7 @@ async fn api_brain_query_recommendations_returns_scored_priorities() {
     let Json(resp) = query_brain_v2(Json(BrainQueryRequest {
         query_type: "recommendations".to_string(),
         limit: Some(5),
+        workspace_id: None,
     }))
     .await
     .unwrap();
@@ -60,7 +62,9 @@ async fn api_brain_graph_and_search_work() {
     write_ontology_fixture(tmp.path());
     std::env::set_var("SAFEBOT_DATA_DIR", tmp.path());
 
-    let Json(graph) = get_brain_graph_v2().await.unwrap();
+    let Json(graph) = get_brain_graph_v2(Query(WorkspaceQuery { workspace_id: None }))
+        .await
+        .unwrap();
     assert!(graph.ok);
     assert_eq!(graph.nodes.len(), 4);
     assert_eq!(graph.edges.len(), 1);
@@ -69,6 +73,7 @@ async fn api_brain_graph_and_search_work() {
         keyword: "build".to_string(),
         kinds: Some(vec!["TaskPattern".to_string()]),
         limit: Some(10),
+        workspace_id: None,
     }))
     .await
     .unwrap();
@@ -84,6 +89,7 @@ async fn api_brain_search_empty_keyword_returns_bad_request() {
         keyword: "   ".to_string(),
         kinds: None,
         limit: None,
+        workspace_id: None,
     }))
     .await;
 
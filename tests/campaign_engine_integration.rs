@@ -1,19 +1,20 @@
+use async_trait::async_trait;
 use openclaw_harness::campaign::{
-    CampaignConstraints, CampaignEngine, MissionAiPlanner, MissionDraft, MissionRule,
-    UserBehaviourStats,
+    load_history, CampaignConstraints, CampaignEngine, CampaignError, MissionAiPlanner,
+    MissionDraft, MissionRule, UserBehaviourStats,
 };
 use rusqlite::Connection;
 
 struct MockPlanner;
 
+#[async_trait]
 impl MissionAiPlanner for MockPlanner {
-    fn propose(
+    async fn propose(
         &self,
-        _conn: &Connection,
         stats: &UserBehaviourStats,
         _history: &[openclaw_harness::campaign::BehaviourRecord],
         _constraints: &CampaignConstraints,
-    ) -> anyhow::Result<MissionDraft> {
+    ) -> Result<MissionDraft, CampaignError> {
         let required_count = if stats.success_rate >= 0.7 { 6 } else { 3 };
         let diff = if stats.success_rate >= 0.7 {
             0.72
@@ -80,9 +81,10 @@ fn setup_db() -> Connection {
     conn
 }
 
-#[test]
-fn integration_generates_dynamic_mission_and_clamps_points() {
+#[tokio::test]
+async fn integration_generates_dynamic_mission_and_clamps_points() {
     let conn = setup_db();
+    let history = load_history(&conn, "user-1").unwrap();
     let engine = CampaignEngine::new(MockPlanner);
     let constraints = CampaignConstraints {
         max_points_per_mission: 100,
@@ -91,7 +93,8 @@ fn integration_generates_dynamic_mission_and_clamps_points() {
     };
 
     let mission = engine
-        .generate_mission(&conn, "user-1", &constraints)
+        .generate_mission("user-1", &history, &constraints)
+        .await
         .unwrap();
 
     assert_eq!(mission.user_id, "user-1");
@@ -103,17 +106,17 @@ fn integration_generates_dynamic_mission_and_clamps_points() {
     assert!(mission.analysis.contains("success_rate"));
 }
 
-#[test]
-fn integration_rejects_unrealistic_mission() {
+#[tokio::test]
+async fn integration_rejects_unrealistic_mission() {
     struct AggressivePlanner;
+    #[async_trait]
     impl MissionAiPlanner for AggressivePlanner {
-        fn propose(
+        async fn propose(
             &self,
-            _conn: &Connection,
             _stats: &UserBehaviourStats,
             _history: &[openclaw_harness::campaign::BehaviourRecord],
             _constraints: &CampaignConstraints,
-        ) -> anyhow::Result<MissionDraft> {
+        ) -> Result<MissionDraft, CampaignError> {
             Ok(MissionDraft {
                 title: "Too hard".to_string(),
                 description: "Should fail feasibility".to_string(),
@@ -133,6 +136,7 @@ fn integration_rejects_unrealistic_mission() {
     }
 
     let conn = setup_db();
+    let history = load_history(&conn, "user-1").unwrap();
     let engine = CampaignEngine::new(AggressivePlanner);
     let constraints = CampaignConstraints {
         max_points_per_mission: 100,
@@ -141,9 +145,11 @@ fn integration_rejects_unrealistic_mission() {
     };
 
     let err = engine
-        .generate_mission(&conn, "user-1", &constraints)
+        .generate_mission("user-1", &history, &constraints)
+        .await
         .unwrap_err();
 
+    assert_eq!(err.kind(), "rejected");
     assert!(err
         .to_string()
         .contains("expected completion probability too low"));
@@ -0,0 +1,34 @@
+//! The simulation corpus in `corpus/` replayed against the shipped rule
+//! pack: a rule or parser change that moves an intercept decision fails here
+
+use openclaw_harness::proxy::corpus;
+use openclaw_harness::rules::load_rules_from_file;
+use std::path::Path;
+
+#[test]
+fn corpus_decisions_hold() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let rules = load_rules_from_file(&root.join("config/rules.yaml")).unwrap();
+    let fixtures = corpus::load(&root.join("corpus")).unwrap();
+
+    let providers: std::collections::HashSet<String> = fixtures
+        .iter()
+        .map(|(_, f)| format!("{:?} streaming={}", f.provider, f.streaming))
+        .collect();
+    assert_eq!(providers.len(), 6, "every provider, streaming and not");
+
+    let failures: Vec<String> = fixtures
+        .iter()
+        .map(|(file, fixture)| corpus::replay(file, fixture, &rules))
+        .filter(|r| !r.failures.is_empty())
+        .map(|r| {
+            format!(
+                "{} ({}): {}",
+                r.name,
+                r.file.display(),
+                r.failures.join("; ")
+            )
+        })
+        .collect();
+    assert!(failures.is_empty(), "{:#?}", failures);
+}
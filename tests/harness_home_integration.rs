@@ -0,0 +1,55 @@
+//! Everything the harness keeps follows `OPENCLAW_HARNESS_HOME`. Its own
+//! test binary, since it changes the process environment.
+
+use openclaw_harness::brain::{self, persist_ontology};
+use openclaw_harness::db::DbPool;
+use openclaw_harness::enforcer::backup::BackupStore;
+use openclaw_harness::enforcer::secrets;
+use openclaw_harness::rules::deployment::Deployment;
+use openclaw_harness::{
+    expand_home, harness_dir, ActionType, AgentAction, AgentType, Config, HOME_ENV,
+};
+use std::path::Path;
+
+#[test]
+fn harness_home_moves_all_state() {
+    let tmp = tempfile::tempdir().unwrap();
+    let home = tmp.path().join("harness");
+    std::env::set_var(HOME_ENV, format!(" {} ", home.display()));
+    std::env::remove_var("SAFEBOT_DATA_DIR");
+    assert_eq!(harness_dir(), home);
+
+    let db_path = expand_home(&Config::default().db_path);
+    assert_eq!(db_path, home.join("openclaw-harness.db"));
+    std::fs::create_dir_all(&home).unwrap();
+    DbPool::open(&db_path).unwrap();
+    assert!(db_path.exists());
+
+    assert_eq!(secrets::alerts_path(), home.join("alerts.json"));
+    assert_eq!(BackupStore::default().root(), home.join("backups"));
+
+    // Self-protection guards the alert secrets where they now are
+    let read = AgentAction {
+        id: "read".to_string(),
+        timestamp: chrono::Utc::now(),
+        agent: AgentType::ClaudeCode,
+        action_type: ActionType::FileRead,
+        content: String::new(),
+        target: Some(home.join("seal.key").to_string_lossy().to_string()),
+        session_id: None,
+        metadata: None,
+    };
+    assert!(Deployment::detect()
+        .rules()
+        .iter()
+        .any(|r| r.name == "self_protect_deployed_alert_secrets" && r.matches(&read)));
+
+    assert_eq!(brain::data_dir(), home);
+    persist_ontology(&brain::data_dir(), &[], &[]).unwrap();
+    assert!(home.join("ontology/v1").is_dir());
+
+    // Unset or blank: the default under the user's home
+    std::env::set_var(HOME_ENV, "  ");
+    assert_eq!(harness_dir(), expand_home("~/.openclaw-harness"));
+    assert!(!Path::new(&Config::default().db_path).starts_with(&home));
+}
@@ -40,7 +40,7 @@ fn setup_db() -> Connection {
 
     conn.execute(
         "INSERT INTO analysis_results (action_id, timestamp, matched_rules, risk_level, recommendation, explanation)
-         VALUES ('a1', datetime('now'), 'dangerous_rm', 'Warning', 'Alert', 'test')",
+         VALUES ('a1', datetime('now'), 'dangerous_rm', 'warning', 'Alert', 'test')",
         [],
     )
     .unwrap();
@@ -100,7 +100,7 @@ fn integration_builds_and_persists_ontology_v2_semantics() {
 
     conn.execute(
         "INSERT INTO analysis_results (action_id, timestamp, matched_rules, risk_level, recommendation, explanation)
-         VALUES ('a3', datetime('now'), 'rule', 'Warning', 'Alert', 'test')",
+         VALUES ('a3', datetime('now'), 'rule', 'warning', 'Alert', 'test')",
         [],
     )
     .unwrap();
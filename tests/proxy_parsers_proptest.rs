@@ -0,0 +1,259 @@
+//! Property tests for the proxy's parsers of upstream data
+//!
+//! Whatever a (compromised or hostile) upstream sends, the proxy must not
+//! panic, and how a stream is split into chunks must not change what is
+//! inspected. The `fuzz/` targets run the same entry points for longer.
+
+use openclaw_harness::proxy::attribution::Attribution;
+use openclaw_harness::proxy::interceptor::{intercept_response, MessageOptions};
+use openclaw_harness::proxy::streaming::{
+    parse_sse_events, SseEvent, SseLineBuffer, StreamInterceptor,
+};
+use openclaw_harness::rules::{default_rules, Rule};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use std::sync::OnceLock;
+
+fn rules() -> &'static [Rule] {
+    static RULES: OnceLock<Vec<Rule>> = OnceLock::new();
+    RULES.get_or_init(|| {
+        let mut rules = default_rules();
+        for rule in &mut rules {
+            rule.compile().unwrap();
+        }
+        rules
+    })
+}
+
+/// Text with SSE framing, JSON punctuation and multi-byte characters
+fn sse_text() -> impl Strategy<Value = String> {
+    proptest::collection::vec(
+        prop_oneof![
+            Just("event: ".to_string()),
+            Just("data: ".to_string()),
+            Just("data:".to_string()),
+            Just("\n".to_string()),
+            Just("\r\n".to_string()),
+            Just("\r".to_string()),
+            Just("\n\n".to_string()),
+            Just(":".to_string()),
+            Just("{\"type\":\"content_block_delta\"".to_string()),
+            Just("é".to_string()),
+            Just("🦀".to_string()),
+            "[a-z{}\\[\\]\":, ]{0,8}",
+        ],
+        0..40,
+    )
+    .prop_map(|parts| parts.concat())
+}
+
+/// Split `text` at `cuts` (clamped to char boundaries)
+fn split(text: &str, cuts: &[usize]) -> Vec<String> {
+    let mut points: Vec<usize> = cuts
+        .iter()
+        .map(|c| {
+            let mut at = c % (text.len() + 1);
+            while !text.is_char_boundary(at) {
+                at -= 1;
+            }
+            at
+        })
+        .collect();
+    points.sort();
+    let mut pieces = vec![];
+    let mut start = 0;
+    for at in points {
+        pieces.push(text[start..at].to_string());
+        start = at;
+    }
+    pieces.push(text[start..].to_string());
+    pieces
+}
+
+fn blocks(chunks: &[String]) -> Vec<String> {
+    let mut buf = SseLineBuffer::new();
+    let mut blocks: Vec<String> = chunks.iter().flat_map(|c| buf.feed(c)).collect();
+    blocks.extend(buf.finish());
+    blocks
+}
+
+fn events(blocks: &[String]) -> Vec<(String, String)> {
+    blocks
+        .iter()
+        .flat_map(|b| parse_sse_events(b))
+        .map(|e| (e.event_type, e.data))
+        .collect()
+}
+
+/// JSON shaped like provider responses, with the wrong types in places
+fn provider_json() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        "[ -~é🦀]{0,12}".prop_map(Value::from),
+        Just(json!("rm -rf /")),
+        Just(json!({"command": "rm -rf ~/"})),
+    ];
+    let value = leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            proptest::collection::vec(inner.clone(), 0..4).prop_map(Value::from),
+            proptest::collection::hash_map("[a-z_]{1,8}", inner, 0..4)
+                .prop_map(|m| Value::Object(m.into_iter().collect())),
+        ]
+    });
+    (value.clone(), value.clone(), value, 0..3u8).prop_map(
+        |(input, name, extra, shape)| match shape {
+            0 => json!({
+                "type": "message",
+                "content": [{"type": "tool_use", "id": extra, "name": name, "input": input}]
+            }),
+            1 => json!({
+                "choices": [{"message": {"tool_calls": [
+                    {"id": extra, "function": {"name": name, "arguments": input}}
+                ]}}]
+            }),
+            _ => json!({
+                "candidates": [{"content": {"parts": [
+                    {"functionCall": {"name": name, "args": input}}, extra
+                ]}}]
+            }),
+        },
+    )
+}
+
+/// SSE events as Anthropic and OpenAI stream them, with hostile data
+fn stream_event() -> impl Strategy<Value = SseEvent> {
+    let data = prop_oneof![
+        Just(r#"{"type":"message_start","message":{"id":"m","content":[]}}"#.to_string()),
+        (0..4u64, "[a-z_]{0,6}").prop_map(|(i, name)| format!(
+            r#"{{"type":"content_block_start","index":{},"content_block":{{"type":"tool_use","id":"t","name":"{}"}}}}"#,
+            i, name
+        )),
+        (0..4u64, "[ -~]{0,12}").prop_map(|(i, partial)| json!({
+            "type": "content_block_delta",
+            "index": i,
+            "delta": {"type": "input_json_delta", "partial_json": partial}
+        }).to_string()),
+        (0..4u64).prop_map(|i| format!(r#"{{"type":"content_block_stop","index":{}}}"#, i)),
+        (0..4u64, "[ -~]{0,12}").prop_map(|(i, args)| json!({
+            "choices": [{"delta": {"tool_calls": [
+                {"index": i, "function": {"name": "exec", "arguments": args}}
+            ]}}]
+        }).to_string()),
+        Just(r#"{"choices":[{"finish_reason":"tool_calls"}]}"#.to_string()),
+        Just("[DONE]".to_string()),
+        any::<i64>().prop_map(|i| format!(r#"{{"type":"content_block_delta","index":{}}}"#, i)),
+        "[ -~é🦀]{0,16}",
+    ];
+    (
+        prop_oneof![
+            Just("message_start"),
+            Just("content_block_start"),
+            Just("content_block_delta"),
+            Just("content_block_stop"),
+            Just("message_stop"),
+            Just("message"),
+        ],
+        data,
+    )
+        .prop_map(|(event_type, data)| SseEvent {
+            event_type: event_type.to_string(),
+            data,
+        })
+}
+
+proptest! {
+    #[test]
+    fn parse_sse_events_never_panics(text in sse_text()) {
+        parse_sse_events(&text);
+    }
+
+    #[test]
+    fn chunking_does_not_change_events(
+        text in sse_text(),
+        cuts in proptest::collection::vec(any::<usize>(), 0..8),
+    ) {
+        let whole = events(&blocks(std::slice::from_ref(&text)));
+        prop_assert_eq!(events(&blocks(&split(&text, &cuts))), whole);
+    }
+
+    #[test]
+    fn splitting_characters_does_not_change_events(
+        text in sse_text(),
+        cuts in proptest::collection::vec(any::<usize>(), 0..8),
+    ) {
+        let bytes = text.as_bytes();
+        let mut points: Vec<usize> = cuts.iter().map(|c| c % (bytes.len() + 1)).collect();
+        points.sort();
+        let mut buf = SseLineBuffer::new();
+        let mut split_blocks = vec![];
+        let mut start = 0;
+        for at in points.into_iter().chain([bytes.len()]) {
+            split_blocks.extend(buf.feed_bytes(&bytes[start..at]));
+            start = at;
+        }
+        split_blocks.extend(buf.finish());
+        prop_assert_eq!(events(&split_blocks), events(&blocks(std::slice::from_ref(&text))));
+    }
+
+    #[test]
+    fn line_buffer_never_panics_on_bytes(
+        chunks in proptest::collection::vec(proptest::collection::vec(any::<u8>(), 0..16), 0..16),
+    ) {
+        let mut buf = SseLineBuffer::new();
+        let mut blocks: Vec<String> = chunks.iter().flat_map(|c| buf.feed_bytes(c)).collect();
+        blocks.extend(buf.finish());
+        events(&blocks);
+    }
+
+    #[test]
+    fn line_buffer_keeps_every_event(
+        stream in proptest::collection::vec(("[a-z_]{1,8}", "[ -~é🦀]{0,20}"), 0..6),
+        crlf in any::<bool>(),
+        cuts in proptest::collection::vec(any::<usize>(), 0..8),
+    ) {
+        let newline = if crlf { "\r\n" } else { "\n" };
+        let text: String = stream
+            .iter()
+            .map(|(event, data)| format!("event: {event}{newline}data: {data}{newline}{newline}"))
+            .collect();
+        let parsed = events(&blocks(&split(&text, &cuts)));
+        prop_assert_eq!(parsed.len(), stream.len());
+        for ((parsed_type, parsed_data), (event_type, data)) in parsed.iter().zip(&stream) {
+            prop_assert_eq!(parsed_type, event_type);
+            prop_assert_eq!(parsed_data, data);
+        }
+    }
+
+    #[test]
+    fn intercept_response_never_panics(body in proptest::collection::vec(any::<u8>(), 0..256)) {
+        intercept_response(&body, rules(), true, MessageOptions::default(), &Attribution::default());
+    }
+
+    #[test]
+    fn intercept_response_survives_odd_shapes(value in provider_json(), enforce in any::<bool>()) {
+        let body = serde_json::to_vec(&value).unwrap();
+        let (out, _) = intercept_response(
+            &body,
+            rules(),
+            enforce,
+            MessageOptions::default(),
+            &Attribution::default(),
+        );
+        prop_assert!(serde_json::from_slice::<Value>(&out).is_ok());
+    }
+
+    #[test]
+    fn stream_interceptor_never_panics(
+        events in proptest::collection::vec(stream_event(), 0..24),
+        enforce in any::<bool>(),
+    ) {
+        let mut interceptor = StreamInterceptor::new(rules().to_vec(), enforce);
+        for event in events {
+            for out in interceptor.process_event(event) {
+                out.to_sse_bytes();
+            }
+        }
+    }
+}
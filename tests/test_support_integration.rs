@@ -0,0 +1,101 @@
+use openclaw_harness::proxy::config::ProxyMode;
+use openclaw_harness::rules::default_rules;
+use openclaw_harness::testing::{ProviderResponse, TestHarness};
+use openclaw_harness::{ActionType, AgentAction, AgentType, RiskLevel};
+use serde_json::json;
+
+fn exec(id: &str, command: &str) -> AgentAction {
+    AgentAction {
+        id: id.to_string(),
+        timestamp: chrono::Utc::now(),
+        agent: AgentType::ClaudeCode,
+        action_type: ActionType::Exec,
+        content: command.to_string(),
+        target: None,
+        session_id: Some("test-session".to_string()),
+        metadata: None,
+    }
+}
+
+fn request() -> serde_json::Value {
+    json!({
+        "model": "claude-sonnet-4-20250514",
+        "max_tokens": 1024,
+        "messages": [{"role": "user", "content": "clean up my home directory"}]
+    })
+}
+
+#[tokio::test]
+async fn proxy_blocks_dangerous_tool_calls_and_logs_them() {
+    let harness = TestHarness::start(default_rules()).await.unwrap();
+    let provider = harness.provider();
+    provider.respond_with(ProviderResponse::anthropic_tool_use(
+        "exec",
+        json!({"command": "rm -rf ~/Documents"}),
+    ));
+    provider.respond_with(ProviderResponse::anthropic_tool_use(
+        "exec",
+        json!({"command": "ls -la"}),
+    ));
+
+    let reply = harness.post("/v1/messages", &request()).await.unwrap();
+    assert_eq!(reply.status, 200);
+    let body = reply.json().unwrap();
+    assert_eq!(body["content"][0]["type"], "text");
+    assert!(body["content"][0]["text"]
+        .as_str()
+        .unwrap()
+        .contains("OpenClaw Harness blocked"));
+
+    let reply = harness.post("/v1/messages", &request()).await.unwrap();
+    assert_eq!(reply.json().unwrap()["content"][0]["type"], "tool_use");
+
+    let requests = provider.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].path, "/v1/messages");
+
+    let logged = harness.db().get_recent_actions(10).unwrap();
+    assert_eq!(logged.len(), 1);
+    assert!(logged[0].id.starts_with("proxy-"));
+    assert!(logged[0].content.contains("rm -rf ~/Documents"));
+}
+
+#[tokio::test]
+async fn proxy_blocks_streamed_tool_calls_unless_monitoring() {
+    let harness = TestHarness::start(default_rules()).await.unwrap();
+    let dangerous =
+        || ProviderResponse::anthropic_tool_use_stream("exec", json!({"command": "rm -rf /"}));
+
+    harness.provider().respond_with(dangerous());
+    let reply = harness.post("/v1/messages", &request()).await.unwrap();
+    assert!(reply.content_type.contains("text/event-stream"));
+    assert!(reply.body.contains("OpenClaw Harness blocked"));
+    assert!(!reply.body.contains("input_json_delta"));
+
+    harness.control().set_mode(ProxyMode::Monitor);
+    harness.provider().respond_with(dangerous());
+    let reply = harness.post("/v1/messages", &request()).await.unwrap();
+    assert!(reply.body.contains("input_json_delta"));
+    assert!(!reply.body.contains("OpenClaw Harness blocked"));
+}
+
+#[tokio::test]
+async fn processed_actions_are_stored_and_alerted() {
+    let harness = TestHarness::start(default_rules()).await.unwrap();
+    let mut events = harness.bus().subscribe("test");
+
+    let result = harness.process(exec("a1", "ls -la")).await.unwrap();
+    assert!(result.matched_rules.is_empty());
+    assert!(harness.channels().sent().is_empty());
+
+    let result = harness.process(exec("a2", "rm -rf /")).await.unwrap();
+    assert_eq!(result.risk_level, RiskLevel::Critical);
+    let sent = harness.channels().sent();
+    let channels: Vec<&str> = sent.iter().map(|a| a.channel.as_str()).collect();
+    assert!(channels.contains(&"slack") && channels.contains(&"discord"));
+    assert!(sent.iter().all(|a| a.text.contains("rm -rf /")));
+
+    assert_eq!(harness.db().get_recent_actions(10).unwrap().len(), 2);
+    let first = events.recv().await.unwrap();
+    assert_eq!(first.event.kind(), "action");
+}
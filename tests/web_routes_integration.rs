@@ -0,0 +1,982 @@
+use axum::http::StatusCode;
+use openclaw_harness::collectors::CollectorManager;
+use openclaw_harness::db::DbPool;
+use openclaw_harness::diagnostics::{Diagnostic, Source};
+use openclaw_harness::enforcer::alerter::Alerter;
+use openclaw_harness::proxy::config::{ProxyConfig, ProxyMode};
+use openclaw_harness::proxy::control::ProxyControl;
+use openclaw_harness::web::{self, AppState, WebConfig};
+use openclaw_harness::{ActionType, AgentAction, AgentType, AlertConfig};
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::{broadcast, RwLock};
+
+fn state(
+    dir: &std::path::Path,
+    api_token: Option<&str>,
+    alerter: Option<Arc<Alerter>>,
+) -> Arc<AppState> {
+    let (event_tx, _) = broadcast::channel(4);
+    let db = DbPool::open(&dir.join("harness.db")).unwrap();
+    let rules = openclaw_harness::rules::default_rules();
+    Arc::new(AppState {
+        event_tx,
+        web: WebConfig {
+            api_token: api_token.map(String::from),
+            ..WebConfig::from_env()
+        },
+        db_path: db.path().to_string_lossy().to_string(),
+        db,
+        rules: RwLock::new(rules.clone()),
+        proxy_config: RwLock::new(ProxyConfig::default()),
+        proxy: ProxyControl::new(ProxyMode::Monitor, rules),
+        collectors: CollectorManager::new(),
+        alerter,
+        started_at: chrono::Utc::now(),
+        stats_cache: std::sync::Mutex::new(None),
+    })
+}
+
+/// Serve the API on a loopback port; returns its base URL
+async fn serve(state: Arc<AppState>) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, web::router(state)).await });
+    format!("http://{}", addr)
+}
+
+async fn call(
+    base: &str,
+    method: Method,
+    path: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let mut request = reqwest::Client::new().request(method, format!("{}{}", base, path));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    if let Some(body) = body {
+        request = request.json(&body);
+    }
+    let response = request.send().await.unwrap();
+    let status = StatusCode::from_u16(response.status().as_u16()).unwrap();
+    (status, response.json().await.unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn brain_campaign_and_report_routes_are_mounted() {
+    let tmp = tempfile::tempdir().unwrap();
+    std::env::set_var("SAFEBOT_DATA_DIR", tmp.path().join("data"));
+    std::env::remove_var("SAFEBOT_LLM_API_KEY");
+    let state = state(tmp.path(), None, None);
+    {
+        let db = state.db.get().unwrap();
+        for i in 0..6 {
+            db.store_action(&AgentAction {
+                id: format!("a{}", i),
+                timestamp: chrono::Utc::now() - chrono::Duration::minutes(60 - i),
+                agent: AgentType::ClaudeCode,
+                action_type: ActionType::Exec,
+                content: "npm run build".to_string(),
+                target: None,
+                session_id: Some("s1".to_string()),
+                metadata: None,
+            })
+            .unwrap();
+        }
+        db.store_action(&AgentAction {
+            id: "p1".to_string(),
+            timestamp: chrono::Utc::now() - chrono::Duration::minutes(5),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::FileWrite,
+            content: "fn main() {}".to_string(),
+            target: Some("/work/payments/src/main.rs".to_string()),
+            session_id: Some("s2".to_string()),
+            metadata: Some(json!({"workspace": "payments"})),
+        })
+        .unwrap();
+    }
+    let app = serve(state).await;
+
+    let (status, body) = call(&app, Method::POST, "/api/brain/ontology/build", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ok"], true);
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/v1/brain/ontology/v2/build",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["summary"]["nodes"].as_u64().unwrap() > 0);
+
+    let query = json!({"query_type": "top_patterns", "limit": 5});
+    let (status, body) = call(&app, Method::POST, "/api/brain/query", None, Some(query)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["ok"], true);
+    let (status, body) = call(&app, Method::GET, "/api/brain/graph", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!body["nodes"].as_array().unwrap().is_empty());
+    let search = json!({"keyword": "npm"});
+    let (status, _) = call(&app, Method::POST, "/api/brain/search", None, Some(search)).await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, body) = call(
+        &app,
+        Method::GET,
+        "/api/reports/weekly?week=2026-W09",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["report_id"], "2026-W09");
+    let generate = json!({"week": "2026-W09"});
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/reports/weekly/generate",
+        None,
+        Some(generate),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["insights"], "heuristic");
+
+    let records: Vec<Value> = (0..8)
+        .map(|i| {
+            json!({
+                "user_id": "u1",
+                "event_type": "checkout",
+                "success": i != 0,
+                "duration_minutes": 10,
+                "created_at": format!("2026-03-0{} 12:00:00", 1 + i % 4),
+            })
+        })
+        .collect();
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/behaviours",
+        None,
+        Some(json!({ "records": records })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["inserted"], 8);
+    let (status, body) = call(&app, Method::GET, "/api/behaviours?user_id=u1", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["stats"]["total_events"], 8);
+
+    let campaign = json!({"user_id": "u1", "max_points_per_mission": 40, "planner": "heuristic"});
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/campaigns/adaptive/generate",
+        None,
+        Some(campaign),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["planner"], "heuristic");
+    assert_eq!(body["mission"]["rule"]["event_type"], "checkout");
+    assert!(body["mission"]["final_points"].as_u64().unwrap() <= 40);
+
+    // Structured errors from the campaign route
+    let unknown =
+        json!({"user_id": "nobody", "max_points_per_mission": 40, "planner": "heuristic"});
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/campaigns/adaptive/generate",
+        None,
+        Some(unknown),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(body["error"], "no_history");
+    let llm = json!({"user_id": "u1", "max_points_per_mission": 40, "planner": "llm"});
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/campaigns/adaptive/generate",
+        None,
+        Some(llm),
+    )
+    .await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(body["error"], "planner_unavailable");
+
+    let (status, body) = call(
+        &app,
+        Method::DELETE,
+        "/api/behaviours?user_id=u1",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["deleted"], 8);
+
+    // Workspaces: listing, scoped reports and ontology partitions
+    let (status, body) = call(&app, Method::GET, "/api/workspaces", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let counts: Vec<(&str, u64)> = body["workspaces"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|w| (w["id"].as_str().unwrap(), w["actions"].as_u64().unwrap()))
+        .collect();
+    assert_eq!(counts, [("default", 6), ("payments", 1)]);
+
+    let (status, all) = call(&app, Method::GET, "/api/reports/weekly", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(all["workspace_id"], "all");
+    let generate = json!({"workspace_id": "payments"});
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/reports/weekly/generate",
+        None,
+        Some(generate),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["workspace_id"], "payments");
+    assert_eq!(body["activity"]["total_events"], 1);
+    assert!(all["activity"]["total_events"].as_u64().unwrap() >= 7);
+    let partition = tmp.path().join("data/workspaces/payments");
+    let report_id = body["report_id"].as_str().unwrap();
+    assert!(partition
+        .join(format!("reports/weekly/{}.json", report_id))
+        .exists());
+
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/api/brain/ontology/v2/build?workspace_id=payments",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, body) = call(
+        &app,
+        Method::GET,
+        "/api/brain/graph?workspace_id=payments",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let ids: Vec<&str> = body["nodes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|n| n["id"].as_str())
+        .collect();
+    assert!(ids.contains(&"session:s2"));
+    assert!(!ids.contains(&"session:s1"));
+
+    let (status, _) = call(
+        &app,
+        Method::GET,
+        "/api/reports/weekly?workspace_id=../etc",
+        None,
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn api_token_guards_insight_routes_only() {
+    let tmp = tempfile::tempdir().unwrap();
+    let app = serve(state(tmp.path(), Some("s3cret"), None)).await;
+
+    for uri in [
+        "/api/behaviours?user_id=u1",
+        "/api/v1/behaviours?user_id=u1",
+        "/api/reports/weekly",
+        "/api/brain/graph",
+    ] {
+        let (status, _) = call(&app, Method::GET, uri, None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+        let (status, _) = call(&app, Method::GET, uri, Some("wrong"), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+    }
+    let campaign = json!({"user_id": "u1", "max_points_per_mission": 40});
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/api/campaigns/adaptive/generate",
+        None,
+        Some(campaign),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, body) = call(
+        &app,
+        Method::GET,
+        "/api/behaviours?user_id=u1",
+        Some("s3cret"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["stats"]["total_events"], 0);
+
+    // Other routes stay open
+    let (status, _) = call(&app, Method::GET, "/api/openapi.json", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn alerts_pause_and_resume() {
+    let tmp = tempfile::tempdir().unwrap();
+    let alerter = Arc::new(Alerter::new(AlertConfig {
+        telegram: None,
+        slack: None,
+        discord: None,
+        desktop: None,
+        quiet_hours: Default::default(),
+    }));
+    let app = serve(state(tmp.path(), None, Some(alerter.clone()))).await;
+
+    let (status, body) = call(&app, Method::GET, "/api/v1/alerts/pause", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["paused_until"].is_null());
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/v1/alerts/pause",
+        None,
+        Some(json!({"minutes": 15})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let until: chrono::DateTime<chrono::Utc> =
+        serde_json::from_value(body["paused_until"].clone()).unwrap();
+    assert_eq!(alerter.paused_until(), Some(until));
+    assert!(until > chrono::Utc::now() + chrono::Duration::minutes(14));
+
+    let (status, body) = call(&app, Method::DELETE, "/api/v1/alerts/pause", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(body["paused_until"].is_null());
+    assert_eq!(alerter.paused_until(), None);
+
+    // Nothing to pause without alert channels
+    let app = serve(state(tmp.path(), None, None)).await;
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/api/v1/alerts/pause",
+        None,
+        Some(json!({})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    let (status, body) = call(&app, Method::GET, "/api/v1/stats", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["today_blocked_count"], 0);
+}
+
+#[tokio::test]
+async fn status_counts_internal_failures() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = state(tmp.path(), None, None);
+    let now = chrono::Utc::now();
+    let failure = |source, component: &str, count| Diagnostic {
+        source,
+        component: component.to_string(),
+        count,
+        first_seen: now - chrono::Duration::hours(1),
+        last_seen: now,
+        last_message: "connection refused".to_string(),
+    };
+    state
+        .db
+        .get()
+        .unwrap()
+        .add_diagnostics(&[
+            failure(Source::Proxy, "https://api.anthropic.com", 2),
+            failure(Source::Collector, "openclaw", 1),
+        ])
+        .unwrap();
+    let app = serve(state).await;
+
+    let (status, body) = call(&app, Method::GET, "/api/v1/status", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+    let diagnostics = &body["diagnostics"];
+    // The status call also flushes whatever this process recorded
+    assert!(diagnostics["total"].as_u64().unwrap() >= 3);
+    assert!(diagnostics["proxy"].as_u64().unwrap() >= 2);
+    assert!(diagnostics["collector"].as_u64().unwrap() >= 1);
+    assert!(diagnostics["last_seen"].is_string());
+}
+
+#[tokio::test]
+async fn api_token_and_origin_guard_changes() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = state(tmp.path(), Some("s3cret"), None);
+    let proxy = state.proxy.clone();
+    let app = serve(state).await;
+
+    for (method, uri, body) in [
+        (
+            Method::PUT,
+            "/api/v1/proxy/config",
+            json!({"mode": "monitor"}),
+        ),
+        (Method::POST, "/api/v1/privacy/purge", json!({"all": true})),
+        (Method::POST, "/api/v1/actions/a1/rollback", json!({})),
+        (Method::POST, "/api/v1/backups", json!({})),
+        (Method::DELETE, "/api/v1/rules/any", json!({})),
+        (Method::POST, "/api/v1/alerts/pause", json!({"minutes": 5})),
+        (Method::PUT, "/api/proxy/config", json!({"mode": "monitor"})),
+    ] {
+        let (status, _) = call(&app, method, uri, None, Some(body)).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", uri);
+    }
+    // Reads stay open
+    let (status, _) = call(&app, Method::GET, "/api/v1/rules", None, None).await;
+    assert_eq!(status, StatusCode::OK);
+
+    // An unknown mode is refused rather than read as monitor
+    proxy.set_mode(ProxyMode::Enforce);
+    let (status, _) = call(
+        &app,
+        Method::PUT,
+        "/api/v1/proxy/config",
+        Some("s3cret"),
+        Some(json!({"mode": "enforced"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(proxy.mode(), ProxyMode::Enforce);
+    let (status, body) = call(
+        &app,
+        Method::PUT,
+        "/api/v1/proxy/config",
+        Some("s3cret"),
+        Some(json!({"mode": "Monitor"})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["mode"], "monitor");
+    assert_eq!(proxy.mode(), ProxyMode::Monitor);
+
+    // Other web pages may not change anything, even with the token
+    let client = reqwest::Client::new();
+    let response = client
+        .put(format!("{}/api/v1/proxy/config", app))
+        .bearer_auth("s3cret")
+        .header("Origin", "https://evil.example")
+        .json(&json!({"mode": "enforce"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(proxy.mode(), ProxyMode::Monitor);
+    let host = app.trim_start_matches("http://");
+    let response = client
+        .put(format!("{}/api/v1/proxy/config", app))
+        .bearer_auth("s3cret")
+        .header("Origin", format!("http://{}", host))
+        .json(&json!({"mode": "enforce"}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(proxy.mode(), ProxyMode::Enforce);
+
+    // ... nor get a CORS grant for it, though they may read
+    let preflight = |method: &'static str| {
+        client
+            .request(Method::OPTIONS, format!("{}/api/v1/proxy/config", app))
+            .header("Origin", "https://evil.example")
+            .header("Access-Control-Request-Method", method)
+            .send()
+    };
+    let response = preflight("PUT").await.unwrap();
+    assert!(response
+        .headers()
+        .get("access-control-allow-origin")
+        .is_none());
+    let response = preflight("GET").await.unwrap();
+    assert_eq!(
+        response.headers()["access-control-allow-origin"],
+        "https://evil.example"
+    );
+}
+
+#[tokio::test]
+async fn purge_needs_the_token_and_an_allowed_origin() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = state(tmp.path(), Some("s3cret"), None);
+    state
+        .db
+        .get()
+        .unwrap()
+        .store_action(&AgentAction {
+            id: "a1".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "ls".to_string(),
+            target: None,
+            session_id: Some("s1".to_string()),
+            metadata: None,
+        })
+        .unwrap();
+    let db = state.db.clone();
+    let app = serve(state).await;
+    let purge = json!({"session_id": "s1", "mode": "delete"});
+
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/api/v1/privacy/purge",
+        None,
+        Some(purge.clone()),
+    )
+    .await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    // A page elsewhere can't erase history, not even with a form post the
+    // browser sends without a preflight
+    let client = reqwest::Client::new();
+    for content_type in ["application/json", "text/plain"] {
+        let response = client
+            .post(format!("{}/api/v1/privacy/purge", app))
+            .bearer_auth("s3cret")
+            .header("Origin", "https://evil.example")
+            .header("Content-Type", content_type)
+            .body(purge.to_string())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 403);
+    }
+    assert!(db.get().unwrap().get_action("a1").unwrap().is_some());
+
+    let (status, body) = call(
+        &app,
+        Method::POST,
+        "/api/v1/privacy/purge",
+        Some("s3cret"),
+        Some(json!({"session_id": "s1", "dry_run": true})),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["actions"], 1);
+}
+
+#[tokio::test]
+async fn export_streams_rows_and_reports_failures() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = state(tmp.path(), None, None);
+    state
+        .db
+        .get()
+        .unwrap()
+        .store_action(&AgentAction {
+            id: "a1".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "ls".to_string(),
+            target: None,
+            session_id: None,
+            metadata: None,
+        })
+        .unwrap();
+    let app = serve(state).await;
+    let get = |path: &str| reqwest::get(format!("{}{}", app, path));
+
+    let csv = get("/api/v1/export?format=csv").await.unwrap();
+    assert_eq!(csv.status().as_u16(), 200);
+    assert_eq!(csv.text().await.unwrap().lines().count(), 2);
+    let parquet = get("/api/v1/export?format=parquet").await.unwrap();
+    assert_eq!(parquet.status().as_u16(), 400);
+
+    // A failed read cuts the body off instead of ending it cleanly
+    rusqlite::Connection::open(tmp.path().join("harness.db"))
+        .unwrap()
+        .execute_batch("DROP TABLE analysis_results")
+        .unwrap();
+    let broken = match get("/api/v1/export?format=csv").await {
+        Ok(response) => response.text().await.err(),
+        Err(e) => Some(e),
+    };
+    assert!(broken.is_some());
+}
+
+#[tokio::test]
+async fn approvals_are_decided_through_the_api() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = state(tmp.path(), Some("s3cret"), None);
+    let db = state.db.clone();
+    let publish = db
+        .get()
+        .unwrap()
+        .request_approval("fp1", "Bash: npm publish")
+        .unwrap();
+    let push = db
+        .get()
+        .unwrap()
+        .request_approval("fp2", "Bash: git push --force")
+        .unwrap();
+    let app = serve(state).await;
+
+    let (status, _) = call(&app, Method::GET, "/api/v1/approvals", None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = call(&app, Method::GET, "/api/v1/approvals", Some("s3cret"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert_eq!(body[0]["summary"], "Bash: npm publish");
+
+    let approve = format!("/api/v1/approvals/{}/approve", publish);
+    let (status, _) = call(&app, Method::POST, &approve, None, None).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    let (status, body) = call(&app, Method::POST, &approve, Some("s3cret"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "approved");
+    assert_eq!(body["decided_by"], "dashboard");
+    // Decided once
+    let (status, _) = call(&app, Method::POST, &approve, Some("s3cret"), None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let deny = format!("/api/v1/approvals/{}/deny", push);
+    let (status, body) = call(&app, Method::POST, &deny, Some("s3cret"), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["status"], "denied");
+
+    let (_, body) = call(&app, Method::GET, "/api/v1/approvals", Some("s3cret"), None).await;
+    assert_eq!(body, json!([]));
+    let conn = db.get().unwrap();
+    assert!(conn
+        .take_approval("fp1", chrono::Duration::minutes(5))
+        .unwrap());
+    assert!(!conn
+        .take_approval("fp2", chrono::Duration::minutes(5))
+        .unwrap());
+}
+
+/// Where `value` strays from `schema`: wrong types, missing required
+/// fields, or fields the schema doesn't declare
+fn schema_errors(spec: &Value, schema: &Value, value: &Value, at: &str) -> Vec<String> {
+    if value.is_null() {
+        return match schema["nullable"].as_bool() {
+            Some(true) => vec![],
+            _ => vec![format!("{}: null", at)],
+        };
+    }
+    if let Some(name) = schema["$ref"].as_str() {
+        let name = name.trim_start_matches("#/components/schemas/");
+        return schema_errors(spec, &spec["components"]["schemas"][name], value, at);
+    }
+    let ty = schema["type"].as_str().unwrap_or_default();
+    let ok = match ty {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        other => panic!("{}: unexpected schema type {:?}", at, other),
+    };
+    if !ok {
+        return vec![format!("{}: expected {}, got {}", at, ty, value)];
+    }
+    let mut errors = vec![];
+    if let Some(items) = value.as_array() {
+        for (i, item) in items.iter().enumerate() {
+            let at = format!("{}[{}]", at, i);
+            errors.extend(schema_errors(spec, &schema["items"], item, &at));
+        }
+    }
+    if let (Some(fields), Some(properties)) = (value.as_object(), schema["properties"].as_object())
+    {
+        for required in schema["required"].as_array().unwrap() {
+            let name = required.as_str().unwrap();
+            if !fields.contains_key(name) {
+                errors.push(format!("{}.{}: missing", at, name));
+            }
+        }
+        for (name, field) in fields {
+            let at = format!("{}.{}", at, name);
+            match properties.get(name) {
+                Some(schema) => errors.extend(schema_errors(spec, schema, field, &at)),
+                None => errors.push(format!("{}: not in the schema", at)),
+            }
+        }
+    }
+    errors
+}
+
+#[tokio::test]
+async fn responses_match_the_openapi_schemas() {
+    let tmp = tempfile::tempdir().unwrap();
+    let state = state(tmp.path(), None, None);
+    let approval = {
+        let db = state.db.get().unwrap();
+        let action = AgentAction {
+            id: "a1".to_string(),
+            timestamp: chrono::Utc::now(),
+            agent: AgentType::ClaudeCode,
+            action_type: ActionType::Exec,
+            content: "rm -rf /tmp/build".to_string(),
+            target: Some("/tmp/build".to_string()),
+            session_id: Some("s1".to_string()),
+            metadata: Some(json!({"tool": "Bash"})),
+        };
+        db.store_action(&action).unwrap();
+        db.store_analysis(&openclaw_harness::AnalysisResult {
+            action,
+            matched_rules: vec!["dangerous_rm".to_string()],
+            risk_level: openclaw_harness::RiskLevel::Critical,
+            recommendation: openclaw_harness::Recommendation::CriticalAlert,
+            explanation: "recursive delete".to_string(),
+        })
+        .unwrap();
+        db.request_approval("fp1", "Bash: npm publish").unwrap()
+    };
+    let app = serve(state).await;
+    let (_, spec) = call(&app, Method::GET, "/api/v1/openapi.json", None, None).await;
+
+    let rule =
+        json!({"name": "no_curl", "pattern": "curl", "risk_level": "warning", "action": "alert"});
+    let calls = [
+        (Method::GET, "/status", "/status".to_string(), None),
+        (Method::GET, "/collectors", "/collectors".to_string(), None),
+        (
+            Method::GET,
+            "/collectors/queue",
+            "/collectors/queue".to_string(),
+            None,
+        ),
+        (Method::GET, "/workspaces", "/workspaces".to_string(), None),
+        (Method::GET, "/stats", "/stats".to_string(), None),
+        (
+            Method::GET,
+            "/stats/by-provider",
+            "/stats/by-provider".to_string(),
+            None,
+        ),
+        (Method::GET, "/events", "/events".to_string(), None),
+        (
+            Method::GET,
+            "/events/recent",
+            "/events/recent".to_string(),
+            None,
+        ),
+        (
+            Method::POST,
+            "/privacy/purge",
+            "/privacy/purge".to_string(),
+            Some(json!({"session_id": "s1", "dry_run": true})),
+        ),
+        (
+            Method::GET,
+            "/privacy/audit",
+            "/privacy/audit".to_string(),
+            None,
+        ),
+        (Method::GET, "/usage", "/usage".to_string(), None),
+        (Method::POST, "/rules", "/rules".to_string(), Some(rule)),
+        (
+            Method::PUT,
+            "/rules/{name}",
+            "/rules/no_curl".to_string(),
+            Some(json!({"pattern": "curl\\s", "action": "block"})),
+        ),
+        (Method::GET, "/rules", "/rules".to_string(), None),
+        (
+            Method::GET,
+            "/rules/{name}/history",
+            "/rules/no_curl/history".to_string(),
+            None,
+        ),
+        (
+            Method::POST,
+            "/rules/test",
+            "/rules/test".to_string(),
+            Some(json!({"pattern": "rm", "input": "rm -rf /"})),
+        ),
+        (
+            Method::POST,
+            "/rules/from-template",
+            "/rules/from-template".to_string(),
+            Some(json!({"name": "no_sudo", "template": "block_sudo"})),
+        ),
+        (Method::GET, "/templates", "/templates".to_string(), None),
+        (
+            Method::POST,
+            "/simulate",
+            "/simulate".to_string(),
+            Some(json!({"action_type": "exec", "content": "rm -rf /"})),
+        ),
+        (
+            Method::GET,
+            "/proxy/status",
+            "/proxy/status".to_string(),
+            None,
+        ),
+        (
+            Method::PUT,
+            "/proxy/config",
+            "/proxy/config".to_string(),
+            Some(json!({"mode": "monitor"})),
+        ),
+        (Method::GET, "/providers", "/providers".to_string(), None),
+        (
+            Method::GET,
+            "/alerts/pause",
+            "/alerts/pause".to_string(),
+            None,
+        ),
+        (Method::GET, "/approvals", "/approvals".to_string(), None),
+        (
+            Method::POST,
+            "/approvals/{id}/approve",
+            format!("/approvals/{}/approve", approval),
+            None,
+        ),
+    ];
+    for (method, documented, path, body) in calls {
+        let operation =
+            &spec["paths"][format!("/api/v1{}", documented)][method.as_str().to_lowercase()];
+        let schema = &operation["responses"]["200"]["content"]["application/json"]["schema"];
+        assert!(
+            schema.is_object(),
+            "{} {} has no response schema",
+            method,
+            documented
+        );
+        let (status, value) = call(
+            &app,
+            method.clone(),
+            &format!("/api/v1{}", path),
+            None,
+            body,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK, "{} {}", method, path);
+        let errors = schema_errors(&spec, schema, &value, "$");
+        assert!(
+            errors.is_empty(),
+            "{} {}:\n{}",
+            method,
+            documented,
+            errors.join("\n")
+        );
+    }
+}
+
+#[tokio::test]
+async fn cli_rule_rollback_sends_the_token() {
+    let tmp = tempfile::tempdir().unwrap();
+    let app = serve(state(tmp.path(), Some("s3cret"), None)).await;
+    // '#' and '?' would end the path if the name went into the URL as is
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/api/v1/rules",
+        Some("s3cret"),
+        Some(json!({
+            "name": "deploy #1?",
+            "pattern": "deploy\\.sh",
+            "risk_level": "warning",
+            "action": "alert",
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let rollback = |token: Option<&str>| {
+        let mut cmd = tokio::process::Command::new(env!("CARGO_BIN_EXE_openclaw-harness"));
+        cmd.args(["rules", "rollback", "deploy #1?", "--to", "1"])
+            .env("OPENCLAW_HARNESS_HOME", tmp.path())
+            .env(
+                "OPENCLAW_HARNESS_WEB_LISTEN",
+                app.trim_start_matches("http://"),
+            )
+            .env_remove("OPENCLAW_HARNESS_API_TOKEN");
+        if let Some(token) = token {
+            cmd.env("OPENCLAW_HARNESS_API_TOKEN", token);
+        }
+        cmd.output()
+    };
+
+    let output = rollback(Some("s3cret")).await.unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(stdout.contains("rolled back to version 1"), "{}", stdout);
+
+    let output = rollback(None).await.unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("OPENCLAW_HARNESS_API_TOKEN"));
+}
+
+#[tokio::test]
+async fn activity_reads_need_the_token_and_an_allowed_origin() {
+    let tmp = tempfile::tempdir().unwrap();
+    let app = serve(state(tmp.path(), Some("s3cret"), None)).await;
+
+    for path in [
+        "/api/v1/export",
+        "/api/v1/events/a1/content",
+        "/api/v1/privacy/audit",
+        "/api/v1/approvals",
+        "/api/export",
+    ] {
+        let (status, _) = call(&app, Method::GET, path, None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED, "{}", path);
+    }
+    let (status, _) = call(
+        &app,
+        Method::GET,
+        "/api/v1/privacy/audit",
+        Some("s3cret"),
+        None,
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    // Other pages may read the rest of the API, but get no CORS grant here
+    let preflight = |path: &str, origin: &str| {
+        reqwest::Client::new()
+            .request(Method::OPTIONS, format!("{}{}", app, path))
+            .header("Origin", origin)
+            .header("Access-Control-Request-Method", "GET")
+            .header("Access-Control-Request-Headers", "authorization")
+            .send()
+    };
+    let granted = |response: reqwest::Response| {
+        response
+            .headers()
+            .get("access-control-allow-origin")
+            .map(|o| o.to_str().unwrap().to_string())
+    };
+    let events = preflight("/api/v1/events", "https://evil.example")
+        .await
+        .unwrap();
+    assert!(granted(events).is_some());
+    let export = preflight("/api/v1/export", "https://evil.example")
+        .await
+        .unwrap();
+    assert_eq!(granted(export), None);
+    let dev_ui = preflight("/api/v1/export", "http://localhost:3000")
+        .await
+        .unwrap();
+    assert_eq!(granted(dev_ui).as_deref(), Some("http://localhost:3000"));
+}
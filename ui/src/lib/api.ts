@@ -1,9 +1,15 @@
 const API_BASE = import.meta.env.DEV ? 'http://localhost:8380' : ''
 export const WS_BASE = import.meta.env.DEV ? 'ws://localhost:8380' : `ws://${window.location.host}`
 
+// API token (OPENCLAW_HARNESS_API_TOKEN), shared with the bundled dashboard
+const TOKEN_KEY = 'openclaw-harness-token'
+
 async function api<T>(path: string, options?: RequestInit): Promise<T> {
+  const headers: Record<string, string> = { 'Content-Type': 'application/json' }
+  const token = localStorage.getItem(TOKEN_KEY)
+  if (token) headers.Authorization = `Bearer ${token}`
   const res = await fetch(`${API_BASE}${path}`, {
-    headers: { 'Content-Type': 'application/json' },
+    headers,
     ...options,
   })
   if (!res.ok) throw new Error(`API error: ${res.status}`)
@@ -28,14 +34,28 @@ export interface ProviderStats {
   request_count: number
 }
 
+export interface KeywordMatch {
+  contains?: string[]
+  starts_with?: string[]
+  ends_with?: string[]
+  glob?: string[]
+  any_of?: string[]
+}
+
 export interface RuleData {
   name: string
   description: string
+  match_type: 'regex' | 'keyword' | 'template'
   pattern: string
+  keyword?: KeywordMatch
+  template?: string
+  params?: Record<string, unknown>
   risk_level: string
   action: string
   enabled: boolean
   is_preset: boolean
+  category: string
+  tags: string[]
 }
 
 export interface ProxyStatus {
@@ -78,6 +98,24 @@ export interface EventData {
   status?: string
 }
 
+export interface TemplateParam {
+  name: string
+  kind: 'string' | 'list'
+  description: string
+  aliases: string[]
+  allowed: string[]
+  example: string[]
+  required: boolean
+}
+
+export interface TemplateData {
+  name: string
+  description: string
+  category: string
+  params: TemplateParam[]
+  example: { params: Record<string, unknown>; description: string; applies_to: string[] }
+}
+
 export interface TestRuleResult {
   matches: boolean
   matched_text: string | null
@@ -94,6 +132,9 @@ export const createRule = (data: { name: string; description: string; pattern: s
   api<RuleData>('/api/rules', { method: 'POST', body: JSON.stringify(data) })
 export const updateRule = (name: string, data: { description?: string; pattern?: string; risk_level?: string; action?: string; enabled?: boolean }) =>
   api<RuleData>(`/api/rules/${encodeURIComponent(name)}`, { method: 'PUT', body: JSON.stringify(data) })
+export const getTemplates = () => api<TemplateData[]>('/api/templates')
+export const createRuleFromTemplate = (data: { name: string; template: string; params: Record<string, unknown>; risk_level?: string; action?: string }) =>
+  api<RuleData>('/api/rules/from-template', { method: 'POST', body: JSON.stringify(data) })
 export const deleteRule = (name: string) =>
   api<void>(`/api/rules/${encodeURIComponent(name)}`, { method: 'DELETE' })
 export const testRule = (pattern: string, input: string) =>
@@ -1,4 +1,4 @@
-import { useEffect, useState } from 'react'
+import { Fragment, useEffect, useState } from 'react'
 import { Plus, Pencil, Trash2, X, FlaskConical, Lock } from 'lucide-react'
 import { getRules, createRule, updateRule, deleteRule, testRule, type RuleData } from '../lib/api'
 
@@ -98,6 +98,11 @@ export default function Rules() {
     }
   }
 
+  const categories = rules.reduce<Record<string, RuleData[]>>((acc, r) => {
+    (acc[r.category] ??= []).push(r)
+    return acc
+  }, {})
+
   const handleTest = async () => {
     if (!form.pattern || !testInput) return
     try {
@@ -131,43 +136,64 @@ export default function Rules() {
             </tr>
           </thead>
           <tbody className="divide-y divide-gray-700/50">
-            {rules.map(r => (
-              <tr key={r.name} className="hover:bg-gray-700/20 transition-colors">
-                <td className="px-4 py-3">
-                  <div className="flex items-center gap-2">
-                    <span className="font-medium text-gray-200">{r.name}</span>
-                    {r.is_preset && <Lock className="w-3 h-3 text-gray-500" />}
-                  </div>
-                  <p className="text-xs text-gray-500 mt-0.5">{r.description}</p>
-                </td>
-                <td className="px-4 py-3 hidden lg:table-cell">
-                  <code className="text-xs text-gray-400 bg-gray-900 px-2 py-1 rounded break-all">
-                    {r.pattern.length > 40 ? r.pattern.slice(0, 40) + '…' : r.pattern}
-                  </code>
-                </td>
-                <td className="px-4 py-3"><RiskBadge level={r.risk_level} /></td>
-                <td className="px-4 py-3"><ActionBadge action={r.action} /></td>
-                <td className="px-4 py-3 text-center">
-                  <button
-                    onClick={() => handleToggle(r)}
-                    className={`w-10 h-5 rounded-full relative transition-colors ${r.enabled ? 'bg-green-500' : 'bg-gray-600'}`}
-                  >
-                    <div className={`w-4 h-4 bg-white rounded-full absolute top-0.5 transition-all ${r.enabled ? 'left-5' : 'left-0.5'}`} />
-                  </button>
-                </td>
-                <td className="px-4 py-3 text-right">
-                  <div className="flex items-center justify-end gap-1">
-                    <button onClick={() => openEdit(r)} className="p-1.5 hover:bg-gray-600 rounded transition-colors" title="Edit">
-                      <Pencil className="w-3.5 h-3.5 text-gray-400" />
-                    </button>
-                    {!r.is_preset && (
-                      <button onClick={() => handleDelete(r.name)} className="p-1.5 hover:bg-red-900/50 rounded transition-colors" title="Delete">
-                        <Trash2 className="w-3.5 h-3.5 text-red-400" />
+            {Object.keys(categories).sort().map(category => (
+              <Fragment key={category}>
+                <tr className="bg-gray-900/40">
+                  <td colSpan={6} className="px-4 py-2 text-xs font-semibold uppercase tracking-wide text-gray-400">{category}</td>
+                </tr>
+                {categories[category].map(r => (
+                  <tr key={r.name} className="hover:bg-gray-700/20 transition-colors">
+                    <td className="px-4 py-3">
+                      <div className="flex items-center gap-2">
+                        <span className="font-medium text-gray-200">{r.name}</span>
+                        {r.is_preset && <Lock className="w-3 h-3 text-gray-500" />}
+                      </div>
+                      <p className="text-xs text-gray-500 mt-0.5">{r.description}</p>
+                      {r.tags.length > 0 && (
+                        <div className="flex flex-wrap gap-1 mt-1">
+                          {r.tags.map(t => (
+                            <span key={t} className="px-1.5 py-0.5 rounded bg-gray-700 text-[10px] text-gray-300">#{t}</span>
+                          ))}
+                        </div>
+                      )}
+                    </td>
+                    <td className="px-4 py-3 hidden lg:table-cell">
+                      <code className="text-xs text-gray-400 bg-gray-900 px-2 py-1 rounded break-all">
+                        {(() => {
+                          const text = r.match_type === 'template'
+                            ? `template: ${r.template ?? ''}`
+                            : r.match_type === 'keyword'
+                            ? `keyword: ${Object.values(r.keyword ?? {}).flat().join(', ')}`
+                            : r.pattern
+                          return text.length > 40 ? text.slice(0, 40) + '…' : text
+                        })()}
+                      </code>
+                    </td>
+                    <td className="px-4 py-3"><RiskBadge level={r.risk_level} /></td>
+                    <td className="px-4 py-3"><ActionBadge action={r.action} /></td>
+                    <td className="px-4 py-3 text-center">
+                      <button
+                        onClick={() => handleToggle(r)}
+                        className={`w-10 h-5 rounded-full relative transition-colors ${r.enabled ? 'bg-green-500' : 'bg-gray-600'}`}
+                      >
+                        <div className={`w-4 h-4 bg-white rounded-full absolute top-0.5 transition-all ${r.enabled ? 'left-5' : 'left-0.5'}`} />
                       </button>
-                    )}
-                  </div>
-                </td>
-              </tr>
+                    </td>
+                    <td className="px-4 py-3 text-right">
+                      <div className="flex items-center justify-end gap-1">
+                        <button onClick={() => openEdit(r)} className="p-1.5 hover:bg-gray-600 rounded transition-colors" title="Edit">
+                          <Pencil className="w-3.5 h-3.5 text-gray-400" />
+                        </button>
+                        {!r.is_preset && (
+                          <button onClick={() => handleDelete(r.name)} className="p-1.5 hover:bg-red-900/50 rounded transition-colors" title="Delete">
+                            <Trash2 className="w-3.5 h-3.5 text-red-400" />
+                          </button>
+                        )}
+                      </div>
+                    </td>
+                  </tr>
+                ))}
+              </Fragment>
             ))}
           </tbody>
         </table>